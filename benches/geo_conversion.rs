@@ -0,0 +1,173 @@
+//! Compares the allocations and durations of converting a large coastline polygon
+//! to geo-types by collecting intermediate points (the former conversion),
+//! with `TryFrom` and with a `GeoConverter` reusing its buffers
+//!
+//! Run with `cargo bench --bench geo_conversion --features geo-types`
+extern crate geo_types;
+extern crate shapefile;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::convert::TryFrom;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+use shapefile::{GeoConverter, MultipartShape, Point, Polygon, Reader, Writer};
+
+const NUM_COAST_POINTS: usize = 500_000;
+const NUM_ISLANDS: usize = 200;
+const NUM_RUNS: usize = 10;
+
+/// Allocator counting the allocations and the bytes allocated
+struct CountingAllocator;
+
+static NUM_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static NUM_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        NUM_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        NUM_BYTES.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        NUM_ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        NUM_BYTES.fetch_add(new_size, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the points of a closed ring around (`x`, `y`) with a jagged radius,
+/// clockwise (an outer ring) or counterclockwise (a hole)
+fn ring(x: f64, y: f64, radius: f64, num_points: usize, clockwise: bool) -> Vec<Point> {
+    let mut points = (0..num_points)
+        .map(|i| {
+            let angle = -2.0 * std::f64::consts::PI * i as f64 / num_points as f64;
+            let r = radius * (1.0 + 0.05 * (i as f64 * 0.7).sin() + 0.02 * (i as f64 * 3.1).cos());
+            Point::new(x + r * angle.cos(), y + r * angle.sin())
+        })
+        .collect::<Vec<_>>();
+    points.push(points[0]);
+    if !clockwise {
+        points.reverse();
+    }
+    points
+}
+
+/// Returns the coastline: a large jagged outer ring with lakes, and small islands off the coast
+fn coastline() -> Polygon {
+    let mut points = ring(0.0, 0.0, 1000.0, NUM_COAST_POINTS, true);
+    let mut parts = vec![0];
+    for i in 0..NUM_ISLANDS {
+        let x = i as f64 * 4.0 - 400.0;
+        parts.push(points.len() as i32);
+        if i % 2 == 0 {
+            points.extend(ring(x, 0.0, 1.5, 1_000, false));
+        } else {
+            points.extend(ring(x, 2000.0, 1.5, 1_000, true));
+        }
+    }
+    Polygon::new(points, parts)
+}
+
+/// The former conversion, collecting the points of each ring before the coordinates
+fn convert_through_points(polygon: Polygon) -> geo_types::MultiPolygon<f64> {
+    let mut polygons = Vec::<geo_types::Polygon<f64>>::new();
+    let mut last_poly = None;
+    for ring in polygon.parts() {
+        let points = ring
+            .iter()
+            .map(|p| geo_types::Point::<f64>::from(*p))
+            .collect::<Vec<geo_types::Point<f64>>>();
+        let area = ring.windows(2).map(|w| (w[1].x - w[0].x) * (w[1].y + w[0].y));
+        if area.sum::<f64>() > 0.0 {
+            if let Some(poly) = last_poly.replace(geo_types::Polygon::new(points.into(), vec![])) {
+                polygons.push(poly);
+            }
+        } else if let Some(ref mut poly) = last_poly {
+            poly.interiors_push(points);
+        }
+    }
+    polygons.extend(last_poly);
+    polygons.into()
+}
+
+/// Runs the conversion, which returns the number of polygons converted,
+/// and returns its mean duration, allocations and bytes allocated
+fn measure<F: FnMut() -> usize>(mut convert: F) -> (Duration, usize, usize) {
+    let mut total = Duration::from_secs(0);
+    let allocations = NUM_ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = NUM_BYTES.load(Ordering::Relaxed);
+    for _ in 0..NUM_RUNS {
+        let start = Instant::now();
+        let num_polygons = convert();
+        total += start.elapsed();
+        assert_eq!(num_polygons, NUM_ISLANDS / 2 + 1);
+    }
+    (
+        total / NUM_RUNS as u32,
+        (NUM_ALLOCATIONS.load(Ordering::Relaxed) - allocations) / NUM_RUNS,
+        (NUM_BYTES.load(Ordering::Relaxed) - bytes) / NUM_RUNS,
+    )
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("shapefile_bench_geo_conversion.shp");
+    Writer::from_path(&path)
+        .and_then(|mut writer| writer.write_shapes(vec![coastline()]))
+        .expect("Failed to write the benchmark file");
+    let polygon = Reader::from_path(&path)
+        .and_then(|reader| reader.read_as::<Polygon>())
+        .expect("Failed to read the benchmark file")
+        .remove(0);
+
+    let expected = geo_types::MultiPolygon::<f64>::try_from(polygon.clone()).unwrap();
+    assert!(convert_through_points(polygon.clone()) == expected);
+
+    // The conversions by value are measured with the clone of the polygon they consume,
+    // the GeoConverter borrows it
+    let through_points = measure(|| convert_through_points(polygon.clone()).0.len());
+    let try_from = measure(|| {
+        let multi_polygon = geo_types::MultiPolygon::<f64>::try_from(polygon.clone()).unwrap();
+        multi_polygon.0.len()
+    });
+    let mut converter = GeoConverter::new();
+    let geo_converter = measure(|| {
+        let multi_polygon = converter.convert_polygon(&polygon).unwrap();
+        let num_polygons = multi_polygon.0.len();
+        converter.recycle(multi_polygon);
+        num_polygons
+    });
+
+    println!(
+        "converting a polygon of {} points in {} rings ({} runs)",
+        polygon.points.len(),
+        polygon.parts.len(),
+        NUM_RUNS
+    );
+    for (name, (duration, allocations, bytes)) in &[
+        ("through points", through_points),
+        ("TryFrom", try_from),
+        ("GeoConverter", geo_converter),
+    ] {
+        println!(
+            "{:<20}{:>10.2?}{:>10} allocations{:>12} KiB",
+            name,
+            duration,
+            allocations,
+            bytes / 1024
+        );
+    }
+
+    for extension in &["shp", "shx", "dbf"] {
+        let _ = std::fs::remove_file(Path::new(&path).with_extension(extension));
+    }
+}
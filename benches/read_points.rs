@@ -0,0 +1,55 @@
+//! Compares reading a file of many small point records from an unbuffered `File`
+//! with reading it through the buffers of `Reader::from_path` and `Reader::with_capacity`
+//!
+//! Run with `cargo bench --bench read_points`
+extern crate shapefile;
+
+use std::fs::File;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use shapefile::{Point, Reader, Writer};
+
+const NUM_POINTS: usize = 200_000;
+const NUM_RUNS: u32 = 5;
+
+/// Returns the mean duration of reading all the points of the file with `read`
+fn time<F: FnMut() -> Vec<Point>>(mut read: F) -> Duration {
+    let mut total = Duration::from_secs(0);
+    for _ in 0..NUM_RUNS {
+        let start = Instant::now();
+        let points = read();
+        total += start.elapsed();
+        assert_eq!(points.len(), NUM_POINTS);
+    }
+    total / NUM_RUNS
+}
+
+fn main() {
+    let path = std::env::temp_dir().join("shapefile_bench_read_points.shp");
+    let points = (0..NUM_POINTS)
+        .map(|i| Point::new(i as f64, -(i as f64)))
+        .collect::<Vec<_>>();
+    Writer::from_path(&path)
+        .and_then(|mut writer| writer.write_shapes(points))
+        .expect("Failed to write the benchmark file");
+
+    let unbuffered = time(|| {
+        let file = File::open(&path).unwrap();
+        Reader::new(file).unwrap().read_as::<Point>().unwrap()
+    });
+    let from_path = time(|| Reader::from_path(&path).unwrap().read_as::<Point>().unwrap());
+    let with_capacity = time(|| {
+        let reader = Reader::with_capacity(&path, 64 * 1024).unwrap();
+        reader.read_as::<Point>().unwrap()
+    });
+
+    println!("reading {} points ({} runs)", NUM_POINTS, NUM_RUNS);
+    println!("Reader::new(File)                   {:>10.2?}", unbuffered);
+    println!("Reader::from_path                   {:>10.2?}", from_path);
+    println!("Reader::with_capacity(64 KiB)       {:>10.2?}", with_capacity);
+
+    for extension in &["shp", "shx", "dbf"] {
+        let _ = std::fs::remove_file(Path::new(&path).with_extension(extension));
+    }
+}
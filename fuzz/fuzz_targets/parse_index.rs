@@ -0,0 +1,9 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+/// The records are read from a valid .shp at the offsets of the fuzzed .shx
+const SHP: &[u8] = include_bytes!("../../tests/data/line.shp");
+
+fuzz_target!(|data: &[u8]| {
+    let _ = shapefile::fuzz::parse_index(SHP, data);
+});
@@ -0,0 +1,10 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+
+// The first 4 bytes are the shape type code, the others the content of the record
+fuzz_target!(|data: &[u8]| {
+    if data.len() >= 4 {
+        let shape_type = i32::from_le_bytes([data[0], data[1], data[2], data[3]]);
+        let _ = shapefile::fuzz::parse_record(shape_type, &data[4..]);
+    }
+});
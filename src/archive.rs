@@ -0,0 +1,229 @@
+//! Module to read and write zipped shapefiles (requires the `zip` feature)
+//!
+//! Shapefiles are often exchanged as a single zip archive holding the *.shp*
+//! and its companion files, all with the same basename.
+//!
+//! - [Reader::from_zip](../reader/struct.Reader.html#method.from_zip) reads the shapefile
+//!   of an archive
+//! - [Writer::finish_into_zip](../writer/struct.Writer.html#method.finish_into_zip)
+//!   packages the files written by a writer into an archive
+use std::borrow::Cow;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+use reader::Reader;
+use writer::Writer;
+use Error;
+
+/// Directory added by macOS to the archives it creates, holding metadata
+/// in files named like the ones archived (`__MACOSX/._roads.shp`)
+const MACOS_METADATA_DIR: &str = "__MACOSX/";
+
+/// Names and contents of the files of a zipped shapefile,
+/// besides the ones written by the [Writer](../writer/struct.Writer.html)
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZipOptions {
+    base_name: String,
+    prj: Option<String>,
+    cpg: Option<String>,
+}
+
+impl ZipOptions {
+    /// The files of the archive are named `base_name.shp`, `base_name.shx`...
+    /// and are at the root of the archive (`base_name` should not contain a directory).
+    pub fn new<S: Into<String>>(base_name: S) -> Self {
+        Self {
+            base_name: base_name.into(),
+            prj: None,
+            cpg: None,
+        }
+    }
+
+    /// Adds a *.prj* with the `wkt` of the coordinate system of the shapes
+    pub fn with_prj<S: Into<String>>(mut self, wkt: S) -> Self {
+        self.prj = Some(wkt.into());
+        self
+    }
+
+    /// Adds a *.cpg* with the `encoding` of the strings of the *.dbf* (e.g. `UTF-8`)
+    pub fn with_cpg<S: Into<String>>(mut self, encoding: S) -> Self {
+        self.cpg = Some(encoding.into());
+        self
+    }
+
+    fn entry_name(&self, extension: &str) -> String {
+        format!("{}.{}", self.base_name, extension)
+    }
+}
+
+impl<T: Read + Write + Seek> Writer<T> {
+    /// Finishes the file (see [finish](#method.finish)) and writes the *.shp*,
+    /// the *.shx* and *.dbf* if the writer has them, and the *.prj* and *.cpg*
+    /// given by the `options`, into a zip archive written to `dest`, which is returned.
+    ///
+    /// The files are copied one after the other from the destinations of the writer
+    /// (which are read from their start), so that when they are files on disk,
+    /// they are never all held in memory.
+    ///
+    /// The destinations must be readable, which the `BufWriter<File>` of
+    /// [from_path](#method.from_path) are not: to archive files on disk, create the writer
+    /// with [new](#method.new) from files opened for reading and writing.
+    ///
+    /// ```compile_fail
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use std::io::Cursor;
+    /// use shapefile::archive::ZipOptions;
+    ///
+    /// let writer = shapefile::Writer::from_path("points.shp")?;
+    /// writer.finish_into_zip(Cursor::new(Vec::new()), &ZipOptions::new("points"))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use std::io::Cursor;
+    /// use shapefile::archive::ZipOptions;
+    ///
+    /// let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    /// writer.add_index_dest(Cursor::new(Vec::new()));
+    /// writer.write_shapes(vec![shapefile::Point::new(1.0, 2.0)])?;
+    ///
+    /// let options = ZipOptions::new("points").with_cpg("UTF-8");
+    /// let archive = writer.finish_into_zip(Cursor::new(Vec::new()), &options)?;
+    ///
+    /// let reader = shapefile::Reader::from_zip(archive)?;
+    /// assert_eq!(reader.read_as::<shapefile::Point>()?.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn finish_into_zip<W: Write + Seek>(
+        self,
+        dest: W,
+        options: &ZipOptions,
+    ) -> Result<W, Error> {
+        let parts = self.into_parts()?;
+        let mut archive = ZipWriter::new(dest);
+        copy_into_zip(&mut archive, options.entry_name("shp"), parts.shp)?;
+        if let Some(shx) = parts.shx {
+            copy_into_zip(&mut archive, options.entry_name("shx"), shx)?;
+        }
+        if let Some(dbf) = parts.dbf {
+            copy_into_zip(&mut archive, options.entry_name("dbf"), dbf)?;
+        }
+        if let Some(ref prj) = options.prj {
+            copy_into_zip(&mut archive, options.entry_name("prj"), Cursor::new(prj))?;
+        }
+        if let Some(ref cpg) = options.cpg {
+            copy_into_zip(&mut archive, options.entry_name("cpg"), Cursor::new(cpg))?;
+        }
+        Ok(archive.finish()?)
+    }
+}
+
+/// Writes the content of the `source`, from its start, as the file `name` of the archive
+fn copy_into_zip<W: Write + Seek, R: Read + Seek>(
+    archive: &mut ZipWriter<W>,
+    name: String,
+    mut source: R,
+) -> Result<(), Error> {
+    source.seek(SeekFrom::Start(0))?;
+    archive.start_file(name, SimpleFileOptions::default())?;
+    std::io::copy(&mut source, archive)?;
+    Ok(())
+}
+
+impl Reader<Cursor<Vec<u8>>> {
+    /// Creates a reader of the shapefile of a zip archive.
+    ///
+    /// The archive must hold exactly one *.shp* (the metadata added by macOS is ignored),
+    /// possibly in a directory, its *.shx* and *.dbf* are the files of the same directory
+    /// with the same basename (ignoring the case of their extension), if there are any.
+    ///
+    /// The files are decompressed in memory.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MissingShpInZip` if the archive has no *.shp*
+    /// - `Error::MultipleShpInZip` if it has more than one, their names are given
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let archive = std::fs::File::open("roads.zip")?;
+    /// let reader = shapefile::Reader::from_zip(archive)?;
+    /// let shapes = reader.read()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_zip<R: Read + Seek>(archive: R) -> Result<Self, Error> {
+        let mut archive = ZipArchive::new(archive)?;
+        let names = archive
+            .file_names()
+            .map(|name| name.map(Cow::into_owned))
+            .collect::<Result<Vec<String>, _>>()?;
+        let mut shp_names: Vec<&String> = names
+            .iter()
+            .filter(|name| !name.starts_with(MACOS_METADATA_DIR))
+            .filter(|name| has_extension(name, "shp"))
+            .collect();
+        let shp_name = match shp_names.len() {
+            0 => return Err(Error::MissingShpInZip),
+            1 => shp_names.remove(0),
+            _ => {
+                return Err(Error::MultipleShpInZip(
+                    shp_names.into_iter().cloned().collect(),
+                ))
+            }
+        };
+        let stem = &shp_name[..shp_name.len() - ".shp".len()];
+        let companion_name = |extension: &str| {
+            names
+                .iter()
+                .find(|name| {
+                    name.len() == shp_name.len()
+                        && name.starts_with(stem)
+                        && has_extension(name, extension)
+                })
+                .cloned()
+        };
+        let shx_name = companion_name("shx");
+        let dbf_name = companion_name("dbf");
+
+        let shp = read_zip_entry(&mut archive, shp_name)?;
+        let shx = match shx_name {
+            Some(name) => Some(read_zip_entry(&mut archive, &name)?),
+            None => None,
+        };
+        let dbf = match dbf_name {
+            Some(name) => Some(read_zip_entry(&mut archive, &name)?),
+            None => None,
+        };
+        Self::from_parts_bytes(shp, shx, dbf)
+    }
+}
+
+/// Returns whether the `name` ends with `.` and the `extension`, in any case
+fn has_extension(name: &str, extension: &str) -> bool {
+    match name.rfind('.') {
+        Some(dot) => name[dot + 1..].eq_ignore_ascii_case(extension),
+        None => false,
+    }
+}
+
+/// Returns the decompressed content of the file with the `name` of the archive
+fn read_zip_entry<R: Read + Seek>(
+    archive: &mut ZipArchive<R>,
+    name: &str,
+) -> Result<Vec<u8>, Error> {
+    let mut entry = archive.by_name(name)?;
+    // The size declared by the archive is not trusted to allocate the buffer
+    let mut bytes = Vec::new();
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
@@ -0,0 +1,146 @@
+//! Module to detect and fix shapefiles whose x and y coordinates were swapped
+//!
+//! Geographic coordinates are stored as (longitude, latitude) in shapefiles,
+//! storing them as (latitude, longitude) is a common mistake that gives extents
+//! like x in [-90, 90] and y in [-180, 180].
+//!
+//! The detection only looks at the extent, so it can only give a
+//! [hint](enum.AxisSwapHint.html), the swap itself is done by
+//! [Shape::swap_xy](../record/enum.Shape.html#method.swap_xy).
+use header::Header;
+use record::traits::HasMutXY;
+use record::{BBox, EsriShape};
+
+/// Result of the [detect_axis_swap](fn.detect_axis_swap.html) heuristic
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AxisSwapHint {
+    /// The extent is valid as (longitude, latitude) but not as (latitude, longitude)
+    Consistent,
+    /// The extent is valid as (latitude, longitude) but not as (longitude, latitude),
+    /// the x and y were likely swapped
+    LikelySwapped,
+    /// The extent is valid both ways (all the coordinates are within [-90, 90]),
+    /// so nothing can be said
+    Ambiguous,
+    /// The coordinates are not geographic (the .prj describes a projected CRS,
+    /// or the extent is not valid as longitudes and latitudes),
+    /// so the heuristic does not apply
+    NotGeographic,
+}
+
+/// Returns whether the x and y of the shapefile are likely to have been swapped,
+/// by looking at the extent given in its header.
+///
+/// `prj` is the content of the .prj of the shapefile, if any.
+/// When it describes a projected CRS (starts with `PROJCS`)
+/// `AxisSwapHint::NotGeographic` is returned, otherwise the coordinates
+/// are assumed to be geographic.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::AxisSwapHint;
+/// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+/// let hint = shapefile::detect_axis_swap(reader.header(), None);
+/// assert_ne!(hint, AxisSwapHint::LikelySwapped);
+/// # Ok(())
+/// # }
+/// ```
+pub fn detect_axis_swap(header: &Header, prj: Option<&str>) -> AxisSwapHint {
+    let bbox = BBox::new(
+        header.point_min[0],
+        header.point_min[1],
+        header.point_max[0],
+        header.point_max[1],
+    );
+    detect_axis_swap_in_bbox(&bbox, prj)
+}
+
+/// Same as [detect_axis_swap](fn.detect_axis_swap.html), but for a single shape
+pub fn detect_shape_axis_swap<S: EsriShape>(shape: &S, prj: Option<&str>) -> AxisSwapHint {
+    detect_axis_swap_in_bbox(&shape.bbox(), prj)
+}
+
+fn detect_axis_swap_in_bbox(bbox: &BBox, prj: Option<&str>) -> AxisSwapHint {
+    let is_projected = prj.map_or(false, |prj| {
+        prj.trim_start()
+            .get(..6)
+            .map_or(false, |keyword| keyword.eq_ignore_ascii_case("PROJCS"))
+    });
+    if is_projected {
+        return AxisSwapHint::NotGeographic;
+    }
+
+    let within = |min: f64, max: f64, limit: f64| -limit <= min && max <= limit;
+    let is_lon_lat = within(bbox.xmin, bbox.xmax, 180.0) && within(bbox.ymin, bbox.ymax, 90.0);
+    let is_lat_lon = within(bbox.xmin, bbox.xmax, 90.0) && within(bbox.ymin, bbox.ymax, 180.0);
+    match (is_lon_lat, is_lat_lon) {
+        (true, false) => AxisSwapHint::Consistent,
+        (false, true) => AxisSwapHint::LikelySwapped,
+        (true, true) => AxisSwapHint::Ambiguous,
+        (false, false) => AxisSwapHint::NotGeographic,
+    }
+}
+
+pub(crate) fn swap_points_xy<PointType: HasMutXY>(points: &mut [PointType]) {
+    for point in points {
+        let x = *point.x_mut();
+        *point.x_mut() = *point.y_mut();
+        *point.y_mut() = x;
+    }
+}
+
+pub(crate) fn swap_bbox_xy(bbox: &BBox) -> BBox {
+    BBox::new(bbox.ymin, bbox.xmin, bbox.ymax, bbox.xmax)
+}
+
+/// Reverses the order of the points of the parts for which `is_ring` returns true,
+/// swapping x and y being a reflection it changes the orientation of the rings.
+///
+/// Parts whose indices are not valid are left untouched.
+pub(crate) fn reverse_rings<PointType, F>(points: &mut [PointType], parts: &[i32], is_ring: F)
+where
+    F: Fn(usize) -> bool,
+{
+    for (i, start) in parts.iter().enumerate() {
+        let end = parts.get(i + 1).map_or(points.len() as i32, |end| *end);
+        if 0 <= *start && *start <= end && end as usize <= points.len() && is_ring(i) {
+            points[*start as usize..end as usize].reverse();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_swapped_extents() {
+        let detect = |xmin, ymin, xmax, ymax| {
+            detect_axis_swap_in_bbox(&BBox::new(xmin, ymin, xmax, ymax), None)
+        };
+        assert_eq!(detect(-120.0, 30.0, -100.0, 45.0), AxisSwapHint::Consistent);
+        assert_eq!(detect(30.0, -120.0, 45.0, -100.0), AxisSwapHint::LikelySwapped);
+        assert_eq!(detect(2.0, 45.0, 3.0, 46.0), AxisSwapHint::Ambiguous);
+        assert_eq!(
+            detect(500000.0, 4000000.0, 600000.0, 4100000.0),
+            AxisSwapHint::NotGeographic
+        );
+    }
+
+    #[test]
+    fn projected_prj_is_not_geographic() {
+        let bbox = BBox::new(30.0, -120.0, 45.0, -100.0);
+        let projcs = r#"PROJCS["NAD83 / UTM zone 10N",GEOGCS["NAD83"]]"#;
+        let geogcs = r#"GEOGCS["GCS_WGS_1984",DATUM["D_WGS_1984"]]"#;
+        assert_eq!(
+            detect_axis_swap_in_bbox(&bbox, Some(projcs)),
+            AxisSwapHint::NotGeographic
+        );
+        assert_eq!(
+            detect_axis_swap_in_bbox(&bbox, Some(geogcs)),
+            AxisSwapHint::LikelySwapped
+        );
+    }
+}
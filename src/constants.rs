@@ -0,0 +1,80 @@
+//! Constants of the shapefile [specification](http://downloads.esri.com/support/whitepapers/mo_/shapefile.pdf)
+//!
+//! The sizes are in bytes, while the lengths stored in the files (the file length
+//! of the header, the content length of the record headers and the
+//! offsets and content lengths of the index) are in 16-bit words.
+
+/// Value written for the measures that are NO_DATA
+pub const NO_DATA: f64 = -10e38;
+
+/// Measures lower than or equal to this value are considered as NO_DATA,
+/// see [is_no_data](fn.is_no_data.html)
+///
+/// The specification gives -10^38, which some tools write as the NO_DATA value.
+pub const NO_DATA_THRESHOLD: f64 = -1e38;
+
+/// File code, the first value (big endian) of the header of the .shp and .shx
+pub const FILE_CODE: i32 = 9994;
+
+/// Version of the specification, written in the header of the .shp and .shx
+pub const VERSION: i32 = 1000;
+
+/// Size of the header of the .shp and .shx
+pub const HEADER_SIZE: i32 = 100;
+
+/// Size of the header of a record of the .shp (record number and content length)
+pub const RECORD_HEADER_SIZE: usize = 2 * std::mem::size_of::<i32>();
+
+/// Size of a record of the .shx (offset and content length)
+pub const INDEX_RECORD_SIZE: usize = 2 * std::mem::size_of::<i32>();
+
+/// Maximum size of the content of a record, as its length is stored as a number
+/// of 16-bit words in an `i32`
+pub const MAX_RECORD_SIZE: u64 = i32::MAX as u64 * 2;
+
+/// Maximum size of a .shp or .shx, the specification limits the files to 2GB
+pub const MAX_FILE_SIZE: u64 = i32::MAX as u64;
+
+/// Returns whether the measure is NO_DATA, that is, lower than or equal to
+/// [NO_DATA_THRESHOLD](constant.NO_DATA_THRESHOLD.html)
+///
+/// # Example
+///
+/// ```
+/// use shapefile::constants::{is_no_data, NO_DATA};
+/// assert!(is_no_data(NO_DATA));
+/// assert!(is_no_data(-1e38));
+/// assert!(!is_no_data(-1e37));
+/// assert!(!is_no_data(0.0));
+/// ```
+pub fn is_no_data(value: f64) -> bool {
+    value <= NO_DATA_THRESHOLD
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spec_values() {
+        assert_eq!(NO_DATA, -1e39);
+        assert_eq!(NO_DATA_THRESHOLD, -1e38);
+        assert_eq!(FILE_CODE, 9994);
+        assert_eq!(VERSION, 1000);
+        assert_eq!(HEADER_SIZE, 100);
+        assert_eq!(RECORD_HEADER_SIZE, 8);
+        assert_eq!(INDEX_RECORD_SIZE, 8);
+        assert_eq!(MAX_RECORD_SIZE, 4_294_967_294);
+        assert_eq!(MAX_FILE_SIZE, 2_147_483_647);
+    }
+
+    #[test]
+    fn no_data_threshold() {
+        assert!(is_no_data(NO_DATA));
+        assert!(is_no_data(f64::MIN));
+        assert!(is_no_data(-1e38));
+        assert!(is_no_data(-5e38));
+        assert!(!is_no_data(-9.9e37));
+        assert!(!is_no_data(f64::NAN));
+    }
+}
@@ -0,0 +1,147 @@
+//! Module with the [copy_with](fn.copy_with.html) function that copies a shapefile
+//! while transforming or filtering its features
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use dbf::DbfWriter;
+use sidecar::copy_sidecars;
+use writer::Writer;
+use {Error, Reader, Shape};
+
+/// Counts of the features processed by [copy_with](fn.copy_with.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct CopyReport {
+    /// Number of features read from the input
+    pub read: usize,
+    /// Number of features written to the output
+    pub written: usize,
+    /// Number of features for which the closure returned `None`
+    pub dropped: usize,
+}
+
+/// Options of [copy_with_options](fn.copy_with_options.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct CopyOptions {
+    preserve_sidecars: bool,
+}
+
+impl CopyOptions {
+    /// Sets whether the sidecar files of the input (*.prj*, *.cpg*, *.shp.xml*...)
+    /// are copied next to the output, renamed to its basename.
+    ///
+    /// Off by default.
+    /// See [sidecar_paths_of](../sidecar/fn.sidecar_paths_of.html) for which files are copied.
+    pub fn preserve_sidecars(mut self, preserve: bool) -> Self {
+        self.preserve_sidecars = preserve;
+        self
+    }
+}
+
+/// Copies the shapefile at `input` (which must have a .dbf) to `output`,
+/// passing each feature to `f` which returns the feature to write, or `None` to drop it.
+///
+/// The .shp, .shx and .dbf are written as the features are read, and their headers
+/// are updated once all the features are written, so only one feature
+/// is in memory at a time.
+///
+/// The bbox of the shapes returned by `f` is computed again from their points,
+/// `f` can modify the points without updating it.
+///
+/// # Errors
+///
+/// Returns `Error::MismatchShapeType` if the non-null shapes returned by `f`
+/// are not all of the same type.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// // Only keep the features with a "Name"
+/// let report = shapefile::copy_with(
+///     "tests/data/multipatch.shp",
+///     std::env::temp_dir().join("named_multipatch.shp"),
+///     |shape, record| {
+///         if record.contains_key("Name") {
+///             Some((shape, record))
+///         } else {
+///             None
+///         }
+///     },
+/// )?;
+/// assert_eq!(report.read, report.written + report.dropped);
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_with<P, Q, F>(input: P, output: Q, f: F) -> Result<CopyReport, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(Shape, dbase::Record) -> Option<(Shape, dbase::Record)>,
+{
+    copy_with_options(input, output, CopyOptions::default(), f)
+}
+
+/// Same as [copy_with](fn.copy_with.html), with `options` on how the copy is done
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::copy::{copy_with_options, CopyOptions};
+/// let report = copy_with_options(
+///     "tests/data/multipatch.shp",
+///     std::env::temp_dir().join("multipatch_with_sidecars.shp"),
+///     CopyOptions::default().preserve_sidecars(true),
+///     |shape, record| Some((shape, record)),
+/// )?;
+/// assert_eq!(report.read, report.written);
+/// # Ok(())
+/// # }
+/// ```
+pub fn copy_with_options<P, Q, F>(
+    input: P,
+    output: Q,
+    options: CopyOptions,
+    mut f: F,
+) -> Result<CopyReport, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+    F: FnMut(Shape, dbase::Record) -> Option<(Shape, dbase::Record)>,
+{
+    let reader = Reader::from_path(&input)?;
+    let input_shapetype = reader.header().shape_type;
+
+    let shp_path = output.as_ref().to_path_buf();
+    let mut writer = Writer::new(BufWriter::new(File::create(&shp_path)?));
+    writer.add_index_dest(BufWriter::new(File::create(shp_path.with_extension("shx"))?));
+    let mut dbf_writer = DbfWriter::create(shp_path.with_extension("dbf"))?;
+
+    let mut report = CopyReport::default();
+    for result in reader.iter_shapes_and_records()? {
+        let (shape, record) = result?;
+        report.read += 1;
+        let (mut shape, record) = match f(shape, record) {
+            Some(feature) => feature,
+            None => {
+                report.dropped += 1;
+                continue;
+            }
+        };
+
+        shape.update_bbox();
+        writer.write_shape(shape)?;
+        dbf_writer.write_record(&record)?;
+        report.written += 1;
+    }
+
+    // The type of the file is the one of its first non-null shape, or the one of the input
+    writer.finish_as(input_shapetype)?;
+    dbf_writer.finish()?;
+
+    if options.preserve_sidecars {
+        copy_sidecars(input, shp_path)?;
+    }
+    Ok(report)
+}
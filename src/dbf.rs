@@ -0,0 +1,246 @@
+//! Module with the writer of .dbf files whose records are written one at a time
+//!
+//! The dbase crate writes all the records at once, as the length of each field
+//! is the length of its longest value. This writer writes each record as it comes
+//! with the largest length a field can have, and once all the records are written,
+//! moves them in place to the lengths of their longest values.
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+
+use Error;
+
+const HEADER_SIZE: usize = 32;
+const FIELD_DESCRIPTOR_SIZE: usize = 32;
+const MAX_FIELD_NAME_LENGTH: usize = 10;
+const MAX_FIELD_LENGTH: u8 = u8::MAX;
+const HEADER_TERMINATOR: u8 = 0x0D;
+const FILE_TERMINATOR: u8 = 0x1A;
+/// Number of bytes of records moved at once when the file is finished
+const MOVE_BUFFER_SIZE: usize = 1 << 16;
+
+/// A field of the records, the type and name are the ones of the first record written
+struct Field {
+    name: String,
+    type_code: u8,
+    /// Length of the field while the records are written
+    max_length: u8,
+    /// Length of the longest value written
+    length: u8,
+}
+
+/// Returns the code of the type of the value and the largest length of its field
+fn type_of(value: &dbase::FieldValue) -> (u8, u8) {
+    match value {
+        dbase::FieldValue::Character(_) => (b'C', MAX_FIELD_LENGTH),
+        dbase::FieldValue::Numeric(_) => (b'N', MAX_FIELD_LENGTH),
+        dbase::FieldValue::Float(_) => (b'F', MAX_FIELD_LENGTH),
+        dbase::FieldValue::Logical(_) => (b'L', 1),
+        dbase::FieldValue::Date(_) => (b'D', 8),
+        dbase::FieldValue::Integer(_) => (b'I', 4),
+        dbase::FieldValue::Double(_) => (b'B', 8),
+    }
+}
+
+impl Field {
+    fn new(name: &str, value: &dbase::FieldValue) -> Result<Self, Error> {
+        if name.len() > MAX_FIELD_NAME_LENGTH {
+            return Err(Error::DbaseError(dbase::Error::FieldNameTooLong));
+        }
+        let (type_code, max_length) = type_of(value);
+        // The fixed length fields have their length from the start
+        let length = if max_length == MAX_FIELD_LENGTH { 1 } else { max_length };
+        Ok(Self {
+            name: name.to_string(),
+            type_code,
+            max_length,
+            length,
+        })
+    }
+
+    fn write_descriptor<T: Write>(&self, dest: &mut T) -> Result<(), std::io::Error> {
+        let mut name = [0u8; 11];
+        name[..self.name.len()].copy_from_slice(self.name.as_bytes());
+        dest.write_all(&name)?;
+        dest.write_u8(self.type_code)?;
+        dest.write_all(&[0u8; 4])?; // displacement
+        dest.write_u8(self.length)?;
+        // decimal places, flags, autoincrement, reserved
+        dest.write_all(&[0u8; 15])
+    }
+}
+
+/// Writes the bytes of the value as the dbase crate writes them,
+/// blanks are written for the values that are `None`
+fn write_value(value: &dbase::FieldValue, dest: &mut Vec<u8>) {
+    match value {
+        dbase::FieldValue::Character(value) => {
+            if let Some(s) = value {
+                dest.extend_from_slice(s.as_bytes());
+            }
+        }
+        dbase::FieldValue::Numeric(value) => {
+            if let Some(n) = value {
+                dest.extend_from_slice(n.to_string().as_bytes());
+            }
+        }
+        dbase::FieldValue::Float(value) => {
+            if let Some(f) = value {
+                dest.extend_from_slice(f.to_string().as_bytes());
+            }
+        }
+        dbase::FieldValue::Logical(value) => dest.push(match value {
+            Some(true) => b't',
+            Some(false) => b'f',
+            None => b'?',
+        }),
+        dbase::FieldValue::Date(value) => match value {
+            Some(d) => dest.extend_from_slice(d.to_string().as_bytes()),
+            None => dest.extend_from_slice(&[b' '; 8]),
+        },
+        dbase::FieldValue::Integer(i) => dest.extend_from_slice(&i.to_le_bytes()),
+        dbase::FieldValue::Double(d) => dest.extend_from_slice(&d.to_le_bytes()),
+    }
+}
+
+/// Writer of a .dbf file whose records are written one at a time,
+/// only the current record is in memory
+pub(crate) struct DbfWriter {
+    dest: BufWriter<File>,
+    fields: Vec<Field>,
+    num_records: u32,
+    value_buffer: Vec<u8>,
+}
+
+impl DbfWriter {
+    /// Creates the .dbf file at `path`, truncating it if it exists
+    pub(crate) fn create<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Ok(Self {
+            dest: BufWriter::new(file),
+            fields: vec![],
+            num_records: 0,
+            value_buffer: vec![],
+        })
+    }
+
+    /// Writes the record after the previous ones.
+    ///
+    /// The fields are the ones of the first record written: the next records must
+    /// have a value of the same type for each of them (the other fields are ignored).
+    ///
+    /// # Errors
+    ///
+    /// - `Error::RecordFieldMismatch` if the record misses a field or has a value
+    ///   of another type for it
+    /// - `Error::DbaseError` if a value is longer than a field can be,
+    ///   or a field name is longer than 10 bytes
+    pub(crate) fn write_record(&mut self, record: &dbase::Record) -> Result<(), Error> {
+        if self.num_records == 0 {
+            self.fields = record
+                .iter()
+                .map(|(name, value)| Field::new(name, value))
+                .collect::<Result<Vec<Field>, Error>>()?;
+            if self.records_offset() > u16::MAX as usize {
+                return Err(Error::DbaseError(dbase::Error::FieldLengthTooLong));
+            }
+            // The header is written once the lengths of the fields are known
+            let placeholder = vec![0u8; self.records_offset()];
+            self.dest.write_all(&placeholder)?;
+        }
+        self.dest.write_u8(b' ')?; // deletion flag
+        for field in &mut self.fields {
+            let value = match record.get(&field.name) {
+                Some(value) if type_of(value).0 == field.type_code => value,
+                _ => {
+                    return Err(Error::RecordFieldMismatch {
+                        field: field.name.clone(),
+                    })
+                }
+            };
+            self.value_buffer.clear();
+            write_value(value, &mut self.value_buffer);
+            if self.value_buffer.len() > field.max_length as usize {
+                return Err(Error::DbaseError(dbase::Error::FieldLengthTooLong));
+            }
+            field.length = field.length.max(self.value_buffer.len() as u8);
+            self.value_buffer.resize(field.max_length as usize, b' ');
+            self.dest.write_all(&self.value_buffer)?;
+        }
+        self.num_records += 1;
+        Ok(())
+    }
+
+    /// Moves the records to the lengths of the longest values of their fields,
+    /// writes the header and flushes the file.
+    ///
+    /// Nothing is written if no record was written.
+    pub(crate) fn finish(mut self) -> Result<(), Error> {
+        self.dest.flush()?;
+        if self.num_records == 0 {
+            return Ok(());
+        }
+        let records_offset = self.records_offset();
+        let written_size = 1 + self.fields.iter().map(|f| f.max_length as usize).sum::<usize>();
+        let record_size = 1 + self.fields.iter().map(|f| f.length as usize).sum::<usize>();
+        if record_size > u16::MAX as usize {
+            return Err(Error::DbaseError(dbase::Error::FieldLengthTooLong));
+        }
+        let file = self.dest.get_mut();
+
+        // The records only shrink, a batch is always moved before where it was read
+        let batch_len = (MOVE_BUFFER_SIZE / written_size).max(1);
+        let mut written = vec![0u8; batch_len * written_size];
+        let mut moved = Vec::<u8>::with_capacity(batch_len * record_size);
+        let mut num_moved = 0usize;
+        while num_moved < self.num_records as usize {
+            let num_in_batch = batch_len.min(self.num_records as usize - num_moved);
+            let batch = &mut written[..num_in_batch * written_size];
+            file.seek(SeekFrom::Start((records_offset + num_moved * written_size) as u64))?;
+            file.read_exact(batch)?;
+            moved.clear();
+            for record in batch.chunks(written_size) {
+                moved.push(record[0]);
+                let mut start = 1;
+                for field in &self.fields {
+                    moved.extend_from_slice(&record[start..start + field.length as usize]);
+                    start += field.max_length as usize;
+                }
+            }
+            file.seek(SeekFrom::Start((records_offset + num_moved * record_size) as u64))?;
+            file.write_all(&moved)?;
+            num_moved += num_in_batch;
+        }
+        file.write_u8(FILE_TERMINATOR)?;
+        let file_length = records_offset + self.num_records as usize * record_size + 1;
+        file.set_len(file_length as u64)?;
+
+        file.seek(SeekFrom::Start(0))?;
+        let mut header = Vec::<u8>::with_capacity(records_offset);
+        header.write_u8(0x03)?; // dBASE III without memo
+        header.write_all(&[90, 12, 25])?; // last update, the one of the dbase crate
+        header.write_u32::<LittleEndian>(self.num_records)?;
+        header.write_u16::<LittleEndian>(records_offset as u16)?;
+        header.write_u16::<LittleEndian>(record_size as u16)?;
+        header.write_all(&[0u8; 20])?;
+        for field in &self.fields {
+            field.write_descriptor(&mut header)?;
+        }
+        header.write_u8(HEADER_TERMINATOR)?;
+        file.write_all(&header)?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Returns the offset of the first record, after the header and the field descriptors
+    fn records_offset(&self) -> usize {
+        HEADER_SIZE + self.fields.len() * FIELD_DESCRIPTOR_SIZE + 1
+    }
+}
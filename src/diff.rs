@@ -0,0 +1,323 @@
+//! Module with the [diff](fn.diff.html) function that compares two shapefiles
+//!
+//! The comparison is semantic: the shapes are compared record by record,
+//! with their coordinates compared within an epsilon, so that two files written
+//! by different writers (or at different times) can be compared without
+//! their non-deterministic bytes getting in the way.
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use constants::is_no_data;
+use extent::parts_of;
+use record::PointZ;
+use {Error, Reader, Shape};
+
+/// Options of [diff](fn.diff.html)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DiffOptions {
+    epsilon: f64,
+    compare_attributes: bool,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            epsilon: 1e-9,
+            compare_attributes: false,
+        }
+    }
+}
+
+impl DiffOptions {
+    /// Sets the largest difference between two coordinates (x, y, z or m)
+    /// for them to be considered equal.
+    ///
+    /// `1e-9` by default.
+    pub fn epsilon(mut self, epsilon: f64) -> Self {
+        self.epsilon = epsilon;
+        self
+    }
+
+    /// Sets whether the records of the .dbf files are compared too,
+    /// both files must then have a .dbf.
+    ///
+    /// Off by default.
+    pub fn compare_attributes(mut self, compare: bool) -> Self {
+        self.compare_attributes = compare;
+        self
+    }
+}
+
+/// A difference found between the two files
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    /// 1-based number of the record the difference was found in,
+    /// `None` if the difference concerns the whole files.
+    pub record_number: Option<usize>,
+    pub description: String,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.record_number {
+            Some(number) => write!(f, "record {}: {}", number, self.description),
+            None => write!(f, "{}", self.description),
+        }
+    }
+}
+
+/// The result of the comparison of two shapefiles
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DiffReport {
+    /// Number of records of the first file
+    pub num_records_a: usize,
+    /// Number of records of the second file
+    pub num_records_b: usize,
+    /// The differences found, the ones concerning the whole files first,
+    /// then the ones of each record in the order of the records
+    pub differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    /// Returns true if no difference was found
+    pub fn is_identical(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Returns the numbers of the records in which differences were found,
+    /// sorted and without duplicates
+    pub fn differing_records(&self) -> Vec<usize> {
+        let mut numbers = self
+            .differences
+            .iter()
+            .filter_map(|difference| difference.record_number)
+            .collect::<Vec<_>>();
+        numbers.dedup();
+        numbers
+    }
+
+    fn push(&mut self, record_number: Option<usize>, description: String) {
+        self.differences.push(Difference {
+            record_number,
+            description,
+        });
+    }
+}
+
+impl fmt::Display for DiffReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} records vs {} records, {} differences",
+            self.num_records_a,
+            self.num_records_b,
+            self.differences.len()
+        )?;
+        for difference in &self.differences {
+            writeln!(f, "{}", difference)?;
+        }
+        Ok(())
+    }
+}
+
+fn approx_eq(a: f64, b: f64, epsilon: f64) -> bool {
+    (a - b).abs() <= epsilon || (is_no_data(a) && is_no_data(b)) || (a.is_nan() && b.is_nan())
+}
+
+/// Returns the points of the shape, all as PointZ
+fn points_of(shape: &Shape) -> Vec<PointZ> {
+    fn to_z<P: Copy>(points: &[P]) -> Vec<PointZ>
+    where
+        PointZ: From<P>,
+    {
+        points.iter().map(|p| PointZ::from(*p)).collect()
+    }
+    match shape {
+        Shape::NullShape(_) | Shape::Unknown { .. } => vec![],
+        Shape::Point(p) => to_z(std::slice::from_ref(p)),
+        Shape::PointM(p) => to_z(std::slice::from_ref(p)),
+        Shape::PointZ(p) => vec![*p],
+        Shape::Polyline(shp) => to_z(&shp.points),
+        Shape::PolylineM(shp) => to_z(&shp.points),
+        Shape::PolylineZ(shp) => shp.points.clone(),
+        Shape::Polygon(shp) => to_z(&shp.points),
+        Shape::PolygonM(shp) => to_z(&shp.points),
+        Shape::PolygonZ(shp) => shp.points.clone(),
+        Shape::Multipoint(shp) => to_z(&shp.points),
+        Shape::MultipointM(shp) => to_z(&shp.points),
+        Shape::MultipointZ(shp) => shp.points.clone(),
+        Shape::Multipatch(shp) => shp.points.clone(),
+    }
+}
+
+/// Returns whether the m values of the shape are written, for the shapes that may omit them
+fn m_present_of(shape: &Shape) -> Option<bool> {
+    match shape {
+        Shape::PolylineM(shp) => Some(shp.m_present),
+        Shape::PolylineZ(shp) => Some(shp.m_present),
+        Shape::PolygonM(shp) => Some(shp.m_present),
+        Shape::PolygonZ(shp) => Some(shp.m_present),
+        Shape::MultipointM(shp) => Some(shp.m_present),
+        Shape::MultipointZ(shp) => Some(shp.m_present),
+        _ => None,
+    }
+}
+
+/// Adds the differences between the shapes of the record to the report
+fn diff_shapes(number: usize, a: &Shape, b: &Shape, epsilon: f64, report: &mut DiffReport) {
+    let record = Some(number);
+    if a.shapetype() != b.shapetype() {
+        let description = format!("shape type {} vs {}", a.shapetype(), b.shapetype());
+        report.push(record, description);
+        return;
+    }
+    if parts_of(a) != parts_of(b) {
+        let description = format!("parts {:?} vs {:?}", parts_of(a), parts_of(b));
+        report.push(record, description);
+    }
+    if m_present_of(a) != m_present_of(b) {
+        let present = |m_present| if m_present == Some(true) { "present" } else { "absent" };
+        let description = format!(
+            "m values {} vs {}",
+            present(m_present_of(a)),
+            present(m_present_of(b))
+        );
+        report.push(record, description);
+    }
+    let (points_a, points_b) = (points_of(a), points_of(b));
+    if points_a.len() != points_b.len() {
+        let description = format!("{} points vs {}", points_a.len(), points_b.len());
+        report.push(record, description);
+        return;
+    }
+    // Only the first differing point is reported
+    let differing_point = points_a.iter().zip(&points_b).position(|(pa, pb)| {
+        !(approx_eq(pa.x, pb.x, epsilon)
+            && approx_eq(pa.y, pb.y, epsilon)
+            && approx_eq(pa.z, pb.z, epsilon)
+            && approx_eq(pa.m, pb.m, epsilon))
+    });
+    if let Some(i) = differing_point {
+        let description = format!("point {} differs: {} vs {}", i, points_a[i], points_b[i]);
+        report.push(record, description);
+    }
+}
+
+/// Adds the differences between the attributes of the record to the report
+fn diff_records(number: usize, a: &dbase::Record, b: &dbase::Record, report: &mut DiffReport) {
+    let names = a.keys().chain(b.keys()).collect::<BTreeSet<_>>();
+    for name in names {
+        let (value_a, value_b) = (a.get(name), b.get(name));
+        if value_a != value_b {
+            let description = format!("attribute '{}' {:?} vs {:?}", name, value_a, value_b);
+            report.push(Some(number), description);
+        }
+    }
+}
+
+fn dbf_reader_of<T: std::io::Read>(
+    reader: &Reader<T>,
+) -> Result<dbase::Reader<std::io::BufReader<std::fs::File>>, Error> {
+    let dbf_path = reader.dbf_path().ok_or(Error::MissingDbf)?;
+    Ok(dbase::Reader::from_path(dbf_path)?)
+}
+
+/// Compares the shapefiles at `a` and `b` record by record and returns the differences found.
+///
+/// The differences looked for are:
+///
+/// - the number of records and the shape type of the files
+/// - the shape type of each record
+/// - the parts of each shape and whether its m values are present
+/// - the number of points of each shape and their coordinates (within the epsilon)
+/// - the attributes of each record (if requested by the options)
+///
+/// The bounding boxes are not compared, as they only depend on the points.
+///
+/// # Errors
+///
+/// Returns the error of a file that can not be read, and `Error::MissingDbf`
+/// if attributes are compared and one of the files has no .dbf.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::diff::DiffOptions;
+/// let options = DiffOptions::default().epsilon(1e-6);
+/// let report = shapefile::diff("tests/data/line.shp", "tests/data/linez.shp", options)?;
+/// assert!(!report.is_identical());
+/// println!("{}", report);
+/// # Ok(())
+/// # }
+/// ```
+pub fn diff<P: AsRef<Path>, Q: AsRef<Path>>(
+    a: P,
+    b: Q,
+    options: DiffOptions,
+) -> Result<DiffReport, Error> {
+    let reader_a = Reader::from_path(a)?;
+    let reader_b = Reader::from_path(b)?;
+    let mut report = DiffReport::default();
+
+    let (type_a, type_b) = (reader_a.header().shape_type, reader_b.header().shape_type);
+    if type_a != type_b {
+        report.push(None, format!("files of shape type {} vs {}", type_a, type_b));
+    }
+    let dbf_readers = if options.compare_attributes {
+        Some((dbf_reader_of(&reader_a)?, dbf_reader_of(&reader_b)?))
+    } else {
+        None
+    };
+
+    let mut shapes_a = reader_a.iter_shapes();
+    let mut shapes_b = reader_b.iter_shapes();
+    let mut record_differences = DiffReport::default();
+    loop {
+        match (shapes_a.next(), shapes_b.next()) {
+            (Some(shape_a), Some(shape_b)) => {
+                let (shape_a, shape_b) = (shape_a?, shape_b?);
+                report.num_records_a += 1;
+                report.num_records_b += 1;
+                let number = report.num_records_a;
+                diff_shapes(number, &shape_a, &shape_b, options.epsilon, &mut record_differences);
+            }
+            (Some(shape_a), None) => {
+                shape_a?;
+                report.num_records_a += 1;
+            }
+            (None, Some(shape_b)) => {
+                shape_b?;
+                report.num_records_b += 1;
+            }
+            (None, None) => break,
+        }
+    }
+    if report.num_records_a != report.num_records_b {
+        let description = format!("{} records vs {}", report.num_records_a, report.num_records_b);
+        report.push(None, description);
+    }
+
+    if let Some((dbf_a, dbf_b)) = dbf_readers {
+        let (num_attributes_a, num_attributes_b) =
+            (dbf_a.header().num_records, dbf_b.header().num_records);
+        if num_attributes_a != num_attributes_b {
+            let description = format!(
+                "{} attribute records vs {}",
+                num_attributes_a, num_attributes_b
+            );
+            report.push(None, description);
+        }
+        for (i, (record_a, record_b)) in dbf_a.zip(dbf_b).enumerate() {
+            diff_records(i + 1, &record_a?, &record_b?, &mut record_differences);
+        }
+    }
+
+    // The differences of the attributes of a record follow the ones of its shape
+    record_differences
+        .differences
+        .sort_by_key(|difference| difference.record_number);
+    report.differences.extend(record_differences.differences);
+    Ok(report)
+}
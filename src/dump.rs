@@ -0,0 +1,216 @@
+//! Module with the [dump](fn.dump.html) function that prints the structure of a shapefile
+//!
+//! The output is meant to be read by humans when debugging a file,
+//! in the spirit of the `shpdump` tool of shapelib, it is not meant to be parsed.
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use constants::is_no_data;
+use header::Header;
+use reader::RecordHeaders;
+use record::traits::MultipartShape;
+use record::{EsriShape, Point, PointM, PointZ, read_record_content};
+use record::RecordSizeCheck;
+use {Error, Shape};
+
+/// Options of the [dump](fn.dump.html)
+#[derive(Debug, Clone, PartialEq)]
+pub struct DumpOptions {
+    /// Number of digits printed after the decimal point
+    pub precision: usize,
+    /// Indices of the records to print, all records are printed if `None`
+    pub records: Option<Range<usize>>,
+    /// Number of coordinates printed at the start and at the end of each record,
+    /// all coordinates are printed if `None`
+    pub num_coordinates: Option<usize>,
+}
+
+impl Default for DumpOptions {
+    /// 6 digits, all the records, no coordinates
+    fn default() -> Self {
+        Self {
+            precision: 6,
+            records: None,
+            num_coordinates: Some(0),
+        }
+    }
+}
+
+/// Prints the header of the shapefile at `path`, then for each record its number
+/// (as written in its header), offset, content length, shape type, bbox,
+/// number of parts and points,
+/// and (depending on the `options`) its coordinates.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::dump::DumpOptions;
+/// let mut output = Vec::<u8>::new();
+/// shapefile::dump("tests/data/line.shp", &mut output, DumpOptions::default())?;
+/// let output = String::from_utf8(output).unwrap();
+/// assert!(output.contains("Record 1: offset 100, content length 164, Polyline"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn dump<P: AsRef<Path>, W: Write>(
+    path: P,
+    dest: &mut W,
+    options: DumpOptions,
+) -> Result<(), Error> {
+    let mut source = BufReader::new(File::open(path)?);
+    let header = Header::read_from(&mut source)?;
+    let precision = options.precision;
+    let file_length = header.file_length.max(0) as u64 * 2;
+
+    writeln!(dest, "File length: {} bytes", file_length)?;
+    writeln!(dest, "Version: {}", header.version)?;
+    writeln!(dest, "Shape type: {}", header.shape_type)?;
+    writeln!(
+        dest,
+        "Bounds: ({:.*}, {:.*}) - ({:.*}, {:.*})",
+        precision,
+        header.point_min[0],
+        precision,
+        header.point_min[1],
+        precision,
+        header.point_max[0],
+        precision,
+        header.point_max[1]
+    )?;
+    writeln!(
+        dest,
+        "Z range: {:.*} - {:.*}",
+        precision, header.point_min[2], precision, header.point_max[2]
+    )?;
+    writeln!(
+        dest,
+        "M range: {} - {}",
+        format_value(header.m_range[0], precision),
+        format_value(header.m_range[1], precision)
+    )?;
+
+    let mut records = RecordHeaders::new(&mut source, file_length)?;
+    let mut index = 0;
+    while let Some(record) = records.next() {
+        let is_dumped = match options.records {
+            Some(ref range) if index >= range.end => break,
+            Some(ref range) => range.contains(&index),
+            None => true,
+        };
+        let (pos, hdr, _) = record?;
+        if is_dumped {
+            let content_size = hdr.record_size as u64 * 2;
+            let shape = read_record_content::<_, Shape>(
+                records.source(),
+                content_size,
+                RecordSizeCheck::Exact,
+            )?;
+            writeln!(dest)?;
+            writeln!(
+                dest,
+                "Record {}: offset {}, content length {}, {}",
+                hdr.record_number,
+                pos,
+                content_size,
+                shape.shapetype()
+            )?;
+            dump_shape(dest, &shape, &options)?;
+        }
+        index += 1;
+    }
+    Ok(())
+}
+
+fn dump_shape<W: Write>(dest: &mut W, shape: &Shape, options: &DumpOptions) -> Result<(), Error> {
+    let precision = options.precision;
+    if let Shape::NullShape(_) = shape {
+        return Ok(());
+    }
+    let bbox = shape.bbox();
+    writeln!(
+        dest,
+        "  Bounds: ({:.*}, {:.*}) - ({:.*}, {:.*})",
+        precision, bbox.xmin, precision, bbox.ymin, precision, bbox.xmax, precision, bbox.ymax
+    )?;
+
+    let coordinates = coordinates_of(shape);
+    writeln!(
+        dest,
+        "  Parts: {}, Points: {}",
+        num_parts_of(shape),
+        coordinates.len()
+    )?;
+
+    let n = options.num_coordinates.unwrap_or(coordinates.len());
+    if n * 2 >= coordinates.len() {
+        write_coordinates(dest, &coordinates, precision)?;
+    } else if n > 0 {
+        write_coordinates(dest, &coordinates[..n], precision)?;
+        writeln!(dest, "  ... {} more", coordinates.len() - n * 2)?;
+        write_coordinates(dest, &coordinates[coordinates.len() - n..], precision)?;
+    }
+    Ok(())
+}
+
+fn write_coordinates<W: Write>(
+    dest: &mut W,
+    coordinates: &[Vec<f64>],
+    precision: usize,
+) -> Result<(), Error> {
+    for coordinate in coordinates {
+        let values = coordinate
+            .iter()
+            .map(|v| format_value(*v, precision))
+            .collect::<Vec<String>>();
+        writeln!(dest, "  ({})", values.join(", "))?;
+    }
+    Ok(())
+}
+
+/// Measures may be NO_DATA, which is more readable as a word than as a number
+fn format_value(value: f64, precision: usize) -> String {
+    if is_no_data(value) {
+        "no data".to_string()
+    } else {
+        format!("{:.*}", precision, value)
+    }
+}
+
+fn num_parts_of(shape: &Shape) -> usize {
+    match shape {
+        Shape::Polyline(shp) => shp.parts_indices().len(),
+        Shape::PolylineM(shp) => shp.parts_indices().len(),
+        Shape::PolylineZ(shp) => shp.parts_indices().len(),
+        Shape::Polygon(shp) => shp.parts_indices().len(),
+        Shape::PolygonM(shp) => shp.parts_indices().len(),
+        Shape::PolygonZ(shp) => shp.parts_indices().len(),
+        Shape::Multipatch(shp) => shp.parts_indices().len(),
+        _ => 0,
+    }
+}
+
+/// Returns the x, y, z (if any) and m (if any) of each point
+fn coordinates_of(shape: &Shape) -> Vec<Vec<f64>> {
+    let xy = |p: &Point| vec![p.x, p.y];
+    let xym = |p: &PointM| vec![p.x, p.y, p.m];
+    let xyzm = |p: &PointZ| vec![p.x, p.y, p.z, p.m];
+    match shape {
+        Shape::NullShape(_) | Shape::Unknown { .. } => vec![],
+        Shape::Point(p) => vec![xy(p)],
+        Shape::PointM(p) => vec![xym(p)],
+        Shape::PointZ(p) => vec![xyzm(p)],
+        Shape::Polyline(shp) => shp.points.iter().map(xy).collect(),
+        Shape::PolylineM(shp) => shp.points.iter().map(xym).collect(),
+        Shape::PolylineZ(shp) => shp.points.iter().map(xyzm).collect(),
+        Shape::Polygon(shp) => shp.points.iter().map(xy).collect(),
+        Shape::PolygonM(shp) => shp.points.iter().map(xym).collect(),
+        Shape::PolygonZ(shp) => shp.points.iter().map(xyzm).collect(),
+        Shape::Multipoint(shp) => shp.points.iter().map(xy).collect(),
+        Shape::MultipointM(shp) => shp.points.iter().map(xym).collect(),
+        Shape::MultipointZ(shp) => shp.points.iter().map(xyzm).collect(),
+        Shape::Multipatch(shp) => shp.points.iter().map(xyzm).collect(),
+    }
+}
@@ -0,0 +1,99 @@
+//! Module with [validate_extent](fn.validate_extent.html) which checks that the points
+//! of shapes are within a domain, such as the one of the CRS of the shapefile
+//! (e.g. `[-180, 180] x [-90, 90]` for EPSG:4326).
+//!
+//! Points outside of the domain can be moved to its border with
+//! [Shape::clamp_to](../record/enum.Shape.html#method.clamp_to).
+use record::BBox;
+use Shape;
+
+/// A point found outside of the domain by [validate_extent](fn.validate_extent.html)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ExtentViolation {
+    /// Index of the shape (of the record) in the shapes validated
+    pub record: usize,
+    /// Index of the part the point belongs to, 0 for shapes without parts
+    pub part: usize,
+    /// Index of the point in its part
+    pub point: usize,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// Returns true if the point is within the domain (borders included),
+/// NaN coordinates are never within it
+pub(crate) fn is_within(domain: &BBox, x: f64, y: f64) -> bool {
+    domain.xmin <= x && x <= domain.xmax && domain.ymin <= y && y <= domain.ymax
+}
+
+/// Returns the indices of the first point of each part of the shape
+pub(crate) fn parts_of(shape: &Shape) -> &[i32] {
+    match shape {
+        Shape::Polyline(shp) => &shp.parts,
+        Shape::PolylineM(shp) => &shp.parts,
+        Shape::PolylineZ(shp) => &shp.parts,
+        Shape::Polygon(shp) => &shp.parts,
+        Shape::PolygonM(shp) => &shp.parts,
+        Shape::PolygonZ(shp) => &shp.parts,
+        Shape::Multipatch(shp) => &shp.parts,
+        _ => &[],
+    }
+}
+
+/// Returns the points of the shape (given by its index) that are not within the `domain`
+pub(crate) fn shape_violations(
+    record: usize,
+    shape: &Shape,
+    domain: &BBox,
+) -> Vec<ExtentViolation> {
+    let parts = parts_of(shape);
+    let mut violations = Vec::<ExtentViolation>::new();
+    for (i, (x, y)) in shape.points_xy().enumerate() {
+        if is_within(domain, x, y) {
+            continue;
+        }
+        // The part is the last one that starts at or before the point
+        let part = parts
+            .iter()
+            .rposition(|start| *start >= 0 && *start as usize <= i)
+            .unwrap_or(0);
+        let part_start = parts.get(part).map_or(0, |start| (*start).max(0) as usize);
+        violations.push(ExtentViolation {
+            record,
+            part,
+            point: i - part_start.min(i),
+            x,
+            y,
+        });
+    }
+    violations
+}
+
+/// Returns the points of the `shapes` that are not within the `domain` (borders included),
+/// in the order of the shapes and of their points.
+///
+/// Points with a NaN x or y are never within the domain.
+///
+/// # Example
+///
+/// ```
+/// use shapefile::record::BBox;
+/// use shapefile::{Point, Polyline, Shape};
+/// let wgs84 = BBox::new(-180.0, -90.0, 180.0, 90.0);
+/// let points = vec![Point::new(170.0, 10.0), Point::new(190.0, 10.0)];
+/// let shapes = vec![Shape::Polyline(Polyline::new(points, vec![0]))];
+///
+/// let violations = shapefile::validate_extent(&shapes, wgs84);
+/// assert_eq!(violations.len(), 1);
+/// assert_eq!((violations[0].record, violations[0].part, violations[0].point), (0, 0, 1));
+/// ```
+pub fn validate_extent<'a, I>(shapes: I, domain: BBox) -> Vec<ExtentViolation>
+where
+    I: IntoIterator<Item = &'a Shape>,
+{
+    shapes
+        .into_iter()
+        .enumerate()
+        .flat_map(|(record, shape)| shape_violations(record, shape, &domain))
+        .collect()
+}
@@ -0,0 +1,54 @@
+//! Entry points for fuzzing the parsing of shapefiles
+//!
+//! Each function parses bytes that may come from an untrusted source.
+//! Whatever the bytes, they return an error instead of panicking, and they never
+//! allocate much more memory than the size of their input.
+//!
+//! They are used by the targets of the `fuzz` directory (run with `cargo fuzz`)
+//! and by the property tests of the test suite, which keeps these guarantees.
+use std::io::{Cursor, Read};
+
+use header::Header;
+use reader::Reader;
+use shared::SharedReader;
+use record::RecordSizeCheck;
+use {Error, Shape, ShapeType};
+
+/// Parses the content of a record (after the shape type) as a shape of the `shape_type` code
+pub fn parse_record(shape_type: i32, bytes: &[u8]) -> Result<Shape, Error> {
+    let shape_type = ShapeType::from(shape_type).ok_or(Error::InvalidShapeType(shape_type))?;
+    let mut content = bytes.take(bytes.len() as u64);
+    Shape::read_content_of(shape_type, &mut content, RecordSizeCheck::Exact)
+}
+
+/// Parses the 100 bytes header of a *.shp* or *.shx*
+pub fn parse_header(bytes: &[u8]) -> Result<Header, Error> {
+    Header::read_from(&mut &bytes[..])
+}
+
+/// Parses a whole *.shp* (without its *.shx*) and returns all its shapes
+pub fn parse_shape_buffer(bytes: &[u8]) -> Result<Vec<Shape>, Error> {
+    Reader::from_seekable(Cursor::new(bytes))?.read()
+}
+
+/// Parses the *.shx* `index_bytes` and reads each shape of the *.shp* `shp_bytes` at the
+/// offset it gives, with a [Reader](../reader/struct.Reader.html) and with a
+/// [SharedReader](../shared/struct.SharedReader.html)
+pub fn parse_index(shp_bytes: &[u8], index_bytes: &[u8]) -> Result<Vec<Shape>, Error> {
+    let mut reader = Reader::from_seekable(Cursor::new(shp_bytes))?;
+    reader.add_index_source(Cursor::new(index_bytes))?;
+    let num_shapes = reader.index_table()?.len();
+    let mut shapes = Vec::<Shape>::new();
+    for n in 0..num_shapes {
+        if let Some(shape) = reader.read_nth_shape(n) {
+            shapes.push(shape?);
+        }
+    }
+    let shared = SharedReader::from_bytes(shp_bytes.to_vec(), Some(index_bytes.to_vec()))?;
+    for n in 0..shared.len() {
+        if let Some(shape) = shared.read_nth(n) {
+            shape?;
+        }
+    }
+    Ok(shapes)
+}
@@ -1,10 +1,13 @@
 use super::{Error, ShapeType};
 
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use std::fs::File;
 use std::io::{Read, Write};
+use std::path::Path;
 
-pub(crate) const HEADER_SIZE: i32 = 100;
-const FILE_CODE: i32 = 9994;
+use constants::{FILE_CODE, HEADER_SIZE, INDEX_RECORD_SIZE, RECORD_HEADER_SIZE, VERSION};
+use record::{BBox, Multipatch, Point, PointM, PointZ, WritableShape};
+use record::{Multipoint, MultipointM, MultipointZ, Polyline, PolylineM, PolylineZ};
 const SIZE_OF_SKIP: usize = std::mem::size_of::<i32>() * 5;
 
 /// struct representing the Header of a shapefile
@@ -35,12 +38,41 @@ impl Default for Header {
             m_range: [0.0; 2],
             shape_type: ShapeType::NullShape,
             file_length: HEADER_SIZE / 2,
-            version: 1000,
+            version: VERSION,
         }
     }
 }
 
 impl Header {
+    /// Returns the 2D bounding box of all the shapes, built from
+    /// `point_min` and `point_max`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let header = shapefile::read_header("tests/data/line.shp")?;
+    /// let bbox = header.bbox();
+    /// assert_eq!((bbox.xmin, bbox.xmax), (header.point_min[0], header.point_max[0]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bbox(&self) -> BBox {
+        BBox::new(
+            self.point_min[0],
+            self.point_min[1],
+            self.point_max[0],
+            self.point_max[1],
+        )
+    }
+
+    /// Returns the min and max z values of all the shapes
+    ///
+    /// Files which shape type has no z values usually have `[0.0, 0.0]`
+    pub fn z_range(&self) -> [f64; 2] {
+        [self.point_min[2], self.point_max[2]]
+    }
+
     pub fn read_from<T: Read>(mut source: &mut T) -> Result<Header, Error> {
         let file_code = source.read_i32::<BigEndian>()?;
 
@@ -55,10 +87,12 @@ impl Header {
         let version = source.read_i32::<LittleEndian>()?;
         let shape_type = ShapeType::read_from(&mut source)?;
 
-        let mut hdr = Header::default();
-        hdr.shape_type = shape_type;
-        hdr.version = version;
-        hdr.file_length = file_length_16_bit;
+        let mut hdr = Header {
+            shape_type,
+            version,
+            file_length: file_length_16_bit,
+            ..Default::default()
+        };
 
         hdr.point_min[0] = source.read_f64::<LittleEndian>()?;
         hdr.point_min[1] = source.read_f64::<LittleEndian>()?;
@@ -100,6 +134,99 @@ impl Header {
     }
 }
 
+/// Reads the header of the shapefile at `path` (a *.shp*, or its *.shx*
+/// which has the same header but for the file length).
+///
+/// Only the 100 bytes of the header are read, and the file is closed right away,
+/// which is faster than creating a [Reader](../reader/struct.Reader.html)
+/// when scanning many files.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// let header = shapefile::read_header("tests/data/line.shp")?;
+/// assert_eq!(header.shape_type, shapefile::ShapeType::Polyline);
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_header<P: AsRef<Path>>(path: P) -> Result<Header, Error> {
+    let mut bytes = [0u8; HEADER_SIZE as usize];
+    File::open(path)?.read_exact(&mut bytes)?;
+    Header::read_from(&mut &bytes[..])
+}
+
+/// Number of records of a shapefile, as given by
+/// [estimate_record_count](fn.estimate_record_count.html)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RecordCount {
+    /// The number of records given by the length of the *.shx*
+    Exact(usize),
+    /// The number of records estimated from the length of the *.shp*
+    Estimated(usize),
+}
+
+impl RecordCount {
+    /// Returns the number of records, whether it is exact or estimated
+    pub fn value(self) -> usize {
+        match self {
+            RecordCount::Exact(count) | RecordCount::Estimated(count) => count,
+        }
+    }
+}
+
+/// Returns the size (in bytes, with the record header) of the smallest
+/// record of a shape of the type, which does not have the optional m values
+fn smallest_record_size(shape_type: ShapeType) -> usize {
+    let content_size = match shape_type {
+        ShapeType::NullShape => 0,
+        ShapeType::Point => Point::default().size_in_bytes(),
+        ShapeType::PointM => PointM::size_of_record(false),
+        ShapeType::PointZ => PointZ::size_of_record(false),
+        ShapeType::Polyline => Polyline::size_of_record(2, 1),
+        ShapeType::PolylineM => PolylineM::size_of_record(2, 1, false),
+        ShapeType::PolylineZ => PolylineZ::size_of_record(2, 1, false),
+        ShapeType::Polygon => Polyline::size_of_record(4, 1),
+        ShapeType::PolygonM => PolylineM::size_of_record(4, 1, false),
+        ShapeType::PolygonZ => PolylineZ::size_of_record(4, 1, false),
+        ShapeType::Multipoint => Multipoint::size_of_record(1),
+        ShapeType::MultipointM => MultipointM::size_of_record(1, false),
+        ShapeType::MultipointZ => MultipointZ::size_of_record(1, false),
+        ShapeType::Multipatch => Multipatch::size_of_record(3, 1, false),
+    };
+    RECORD_HEADER_SIZE + std::mem::size_of::<i32>() + content_size
+}
+
+/// Returns the number of records of a shapefile from its header,
+/// and the length (in bytes) of its *.shx* if known.
+///
+/// With the length of the *.shx* the count is exact, otherwise it is estimated
+/// from the file length of the header as if all the records were the smallest
+/// (non-null) records of the shape type of the file.
+/// The estimation is exact for files of Points without NullShapes,
+/// and an upper bound for the other files without NullShapes.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::header::{estimate_record_count, read_header, RecordCount};
+/// let header = read_header("tests/data/pointz.shp")?;
+/// let shx_len = std::fs::metadata("tests/data/pointz.shx").ok().map(|m| m.len());
+/// let count = estimate_record_count(&header, shx_len);
+/// assert!(count.value() > 0);
+/// # Ok(())
+/// # }
+/// ```
+pub fn estimate_record_count(header: &Header, shx_len: Option<u64>) -> RecordCount {
+    if let Some(shx_len) = shx_len {
+        let records_len = shx_len.saturating_sub(HEADER_SIZE as u64);
+        return RecordCount::Exact((records_len / INDEX_RECORD_SIZE as u64) as usize);
+    }
+    let records_len = (i64::from(header.file_length) * 2 - i64::from(HEADER_SIZE)).max(0);
+    RecordCount::Estimated(records_len as usize / smallest_record_size(header.shape_type))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -117,4 +244,20 @@ mod tests {
         src.seek(SeekFrom::Start(0)).unwrap();
         assert!(Header::read_from(&mut src).is_err());
     }
+
+    #[test]
+    fn unused_bytes_are_written_as_zeros() {
+        let header = Header {
+            file_length: 64,
+            point_min: [-1.0, -2.0, -3.0],
+            point_max: [1.0, 2.0, 3.0],
+            shape_type: ShapeType::PointZ,
+            m_range: [4.0, 5.0],
+            version: 1000,
+        };
+        let mut bytes = Vec::<u8>::new();
+        header.write_to(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), HEADER_SIZE as usize);
+        assert_eq!(&bytes[4..4 + SIZE_OF_SKIP], &[0u8; SIZE_OF_SKIP]);
+    }
 }
@@ -0,0 +1,245 @@
+//! Module with the [IndexTable](struct.IndexTable.html), the content of a *.shx* file
+//!
+//! The *.shx* file gives, for each record of the *.shp*, its offset in the file
+//! and the length of its content, allowing to read any record without having
+//! to read the ones before it.
+use std::io::{Read, Seek, SeekFrom, Write};
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use constants::HEADER_SIZE;
+use header;
+use reader::RecordHeaders;
+use record::RecordHeader;
+use writer::{index_header, write_index_entry};
+use Error;
+
+pub(crate) use constants::INDEX_RECORD_SIZE;
+
+/// One entry of the index, as stored in the file (in 16-bit words)
+pub(crate) struct ShapeIndex {
+    pub offset: i32,
+    pub record_size: i32,
+}
+
+/// Location of a record in the *.shp* file,
+/// see [Reader::record_offsets](../reader/struct.Reader.html#method.record_offsets)
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RecordLocation {
+    /// Number of the record, 0-based as for the [IndexTable](struct.IndexTable.html)
+    pub number: usize,
+    /// Offset of the record (of its header) from the start of the *.shp*, in bytes
+    pub byte_offset: u64,
+    /// Size of the content of the record (without its header), in bytes
+    pub content_length_bytes: usize,
+}
+
+/// Table of the offsets of the records of a *.shp* file
+///
+/// Record numbers are 0-based, as for
+/// [read_nth_shape](../reader/struct.Reader.html#method.read_nth_shape).
+pub struct IndexTable {
+    entries: Vec<ShapeIndex>,
+}
+
+impl IndexTable {
+    /// Reads the content of a *.shx* file
+    ///
+    /// The number of entries is given by the header, but the memory for them is only
+    /// allocated as they are read.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::TruncatedFile` if the `source` ends before the last entry.
+    /// - `Error::InvalidIndexEntry` if an entry has a negative content length
+    ///   or places its record within the header of the *.shp*.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let file = std::fs::File::open("tests/data/line.shx")?;
+    /// let index = shapefile::IndexTable::read_from(file)?;
+    /// assert_eq!(index.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_from<T: Read>(mut source: T) -> Result<Self, Error> {
+        let header = header::Header::read_from(&mut source)?;
+
+        let file_length = header.file_length.max(0) as u64 * 2;
+        let entries_length = file_length.saturating_sub(HEADER_SIZE as u64);
+        let entries_length = entries_length - entries_length % INDEX_RECORD_SIZE as u64;
+        // The header may claim many more entries than the source holds:
+        // the buffer only grows with the bytes actually read
+        let mut bytes = Vec::<u8>::new();
+        source.take(entries_length).read_to_end(&mut bytes)?;
+        if (bytes.len() as u64) < entries_length {
+            return Err(Error::TruncatedFile {
+                expected_bytes: HEADER_SIZE as u64 + entries_length,
+                actual_bytes: HEADER_SIZE as u64 + bytes.len() as u64,
+            });
+        }
+
+        let mut entries = Vec::<ShapeIndex>::with_capacity(bytes.len() / INDEX_RECORD_SIZE);
+        let mut bytes = bytes.as_slice();
+        while !bytes.is_empty() {
+            let entry = ShapeIndex {
+                offset: bytes.read_i32::<BigEndian>()?,
+                record_size: bytes.read_i32::<BigEndian>()?,
+            };
+            if entry.record_size < 0 || (entry.offset as i64 * 2) < HEADER_SIZE as i64 {
+                return Err(Error::InvalidIndexEntry {
+                    index: entries.len(),
+                    offset: entry.offset,
+                    record_size: entry.record_size,
+                });
+            }
+            entries.push(entry);
+        }
+        Ok(Self { entries })
+    }
+
+    /// Checks that the records of all the entries end before the end of the *.shp*,
+    /// given in bytes by its header
+    ///
+    /// # Errors
+    ///
+    /// `Error::InvalidIndexEntry` for the first entry which record ends after the file.
+    pub(crate) fn check_entries_end_before(&self, file_length: u64) -> Result<(), Error> {
+        for (index, entry) in self.entries.iter().enumerate() {
+            let (offset, content_length) = entry_in_bytes(entry);
+            if offset + RecordHeader::SIZE as u64 + content_length as u64 > file_length {
+                return Err(Error::InvalidIndexEntry {
+                    index,
+                    offset: entry.offset,
+                    record_size: entry.record_size,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Builds the table by reading the header of each record of the *.shp* `source`
+    /// (the rest of the records is skipped using `seek`),
+    /// the `source` is then put back after the file header.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidShapeRecordSize` if the content of a record is too small
+    ///   to hold its shape type, or ends after the end of the file given by the header.
+    /// - `Error::TruncatedFile` if the `source` ends before the last record.
+    pub(crate) fn scan_shp<T: Read + Seek>(
+        source: &mut T,
+        header: &header::Header,
+    ) -> Result<Self, Error> {
+        let file_length = header.file_length.max(0) as u64 * 2;
+        let entries = RecordHeaders::new(&mut *source, file_length)?
+            .map(|record| {
+                record.map(|(pos, hdr, _)| ShapeIndex {
+                    offset: (pos / 2) as i32,
+                    record_size: hdr.record_size,
+                })
+            })
+            .collect::<Result<Vec<ShapeIndex>, Error>>();
+        source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        Ok(Self { entries: entries? })
+    }
+
+    /// Writes the table as a *.shx* file, `shp_header` is the header of the *.shp*
+    /// which is copied (with the length of the *.shx*)
+    pub fn write_to<W: Write>(
+        &self,
+        mut dest: W,
+        shp_header: &header::Header,
+    ) -> Result<(), Error> {
+        index_header(shp_header, self.entries.len()).write_to(&mut dest)?;
+        for entry in &self.entries {
+            write_index_entry(&mut dest, entry)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the offset (in bytes, from the start of the *.shp*) of the record
+    /// and the size (in bytes) of its content.
+    ///
+    /// The offset is the one of the record header,
+    /// the size does not include the record header.
+    ///
+    /// Returns `None` if the record number is out of range.
+    pub fn offset_of(&self, record: usize) -> Option<(u64, usize)> {
+        self.entries.get(record).map(entry_in_bytes)
+    }
+
+    /// Returns the number of records in the table
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if the table has no records
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns an iterator over the `(offset, size)` of each record,
+    /// with the same meaning as in [offset_of](#method.offset_of)
+    pub fn iter(&self) -> impl Iterator<Item = (u64, usize)> + '_ {
+        self.entries.iter().map(entry_in_bytes)
+    }
+
+    /// Returns an iterator over the location of each record
+    pub fn locations(&self) -> impl Iterator<Item = RecordLocation> + '_ {
+        self.iter()
+            .enumerate()
+            .map(|(number, (byte_offset, content_length_bytes))| RecordLocation {
+                number,
+                byte_offset,
+                content_length_bytes,
+            })
+    }
+}
+
+/// The entries are checked when read (or built from the *.shp*):
+/// their offset and size are not negative
+fn entry_in_bytes(entry: &ShapeIndex) -> (u64, usize) {
+    (entry.offset as u64 * 2, entry.record_size as usize * 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use std::io::Cursor;
+
+    #[test]
+    fn read_line_shx() {
+        let file = std::fs::File::open("tests/data/line.shx").unwrap();
+        let index = IndexTable::read_from(file).unwrap();
+        assert_eq!(index.len(), 1);
+        assert_eq!(index.offset_of(0), Some((100, 164)));
+        assert_eq!(index.offset_of(1), None);
+    }
+
+    #[test]
+    fn read_in_memory_shx() {
+        let entries = [(50, 10), (64, 2), (70, 100)];
+        let header = header::Header {
+            file_length: (HEADER_SIZE + (entries.len() * INDEX_RECORD_SIZE) as i32) / 2,
+            ..Default::default()
+        };
+        let mut shx = Vec::<u8>::new();
+        header.write_to(&mut shx).unwrap();
+        for (offset, size) in &entries {
+            shx.write_i32::<BigEndian>(*offset).unwrap();
+            shx.write_i32::<BigEndian>(*size).unwrap();
+        }
+
+        let index = IndexTable::read_from(Cursor::new(shx)).unwrap();
+        assert_eq!(index.len(), 3);
+        assert_eq!(index.offset_of(1), Some((128, 4)));
+        assert_eq!(
+            index.iter().collect::<Vec<(u64, usize)>>(),
+            vec![(100, 20), (128, 4), (140, 200)]
+        );
+    }
+}
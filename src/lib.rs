@@ -8,7 +8,7 @@
 //!
 //! 1) Reading as [Shape](record/enum.Shape.html) and then do a `match` to handle the different shapes
 //! 2) Reading directly as concrete shapes (ie Polyline, PolylineZ, Point, etc) this of course only
-//! works if the file actually contains shapes that matches the requested type
+//!    works if the file actually contains shapes that matches the requested type
 //!
 //! # Reading
 //! For more details see the [reader](reader/index.html) module
@@ -25,28 +25,80 @@
 //! The `geo-types` feature can be enabled to have access to `From` and `TryFrom`
 //! implementations allowing to convert (or try to) back and forth between shapefile's type and
 //! the one in `geo_types`
+//!
+//! The `rstar` feature gives access to the [SpatialIndex](spatial_index/struct.SpatialIndex.html),
+//! an in-memory R-tree over the bounding boxes of the shapes of a file
+//!
+//! The `zip` feature allows to read and write zipped shapefiles,
+//! see the [archive](archive/index.html) module
 extern crate byteorder;
 extern crate dbase;
+#[cfg(feature = "rstar")]
+extern crate rstar;
+#[cfg(feature = "zip")]
+extern crate zip;
+
+#[cfg(feature = "zip")]
+pub mod archive;
 
+pub mod axis;
+pub mod constants;
+pub mod copy;
+mod dbf;
+pub mod diff;
+pub mod dump;
+pub mod extent;
+#[doc(hidden)]
+pub mod fuzz;
 pub mod header;
+pub mod index;
+pub mod progress;
 pub mod reader;
 pub mod record;
+pub mod shared;
+pub mod sidecar;
+#[cfg(feature = "rstar")]
+pub mod spatial_index;
+pub mod split;
+pub mod streaming;
+pub mod summary;
+pub mod verify;
 pub mod writer;
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt};
 use std::convert::From;
 use std::fmt;
-use std::io::{Read, Write};
+use std::io::Read;
 
-pub use reader::{read, read_as, Reader};
+pub use axis::{detect_axis_swap, AxisSwapHint};
+pub use constants::{is_no_data, NO_DATA};
+pub use copy::{copy_with, CopyReport};
+pub use diff::diff;
+pub use dump::dump;
+pub use extent::{validate_extent, ExtentViolation};
+#[cfg(feature = "geo-types")]
+pub use record::geo_converter::GeoConverter;
+pub use header::{estimate_record_count, read_header};
+pub use index::{IndexTable, RecordLocation};
+pub use progress::Progress;
+pub use reader::{read, read_as, read_from_bytes, Reader};
 pub use record::traits::{MultipartShape, MultipointShape};
 pub use record::Multipatch;
 pub use record::{convert_shapes_to_vec_of, HasShapeType, ReadableShape};
 pub use record::{Multipoint, MultipointM, MultipointZ};
-pub use record::{PatchType, Shape, NO_DATA};
+pub use record::NullShape;
+pub use record::{IntersectsBBox, PartialShape, RetainPartsInBBox};
+pub use record::{PatchType, Shape};
 pub use record::{Point, PointM, PointZ};
 pub use record::{Polygon, PolygonM, PolygonZ};
 pub use record::{Polyline, PolylineM, PolylineZ};
+pub use shared::SharedReader;
+#[cfg(feature = "rstar")]
+pub use spatial_index::SpatialIndex;
+pub use split::split_by;
+pub use streaming::StreamingParser;
+pub use summary::FileSummary;
+pub use verify::{verify, VerifyReport};
 pub use writer::Writer;
 
 #[cfg(feature = "geo-types")]
@@ -84,8 +136,106 @@ pub enum Error {
     /// geo_types::Multipolygon, this error happen when during such conversion,
     /// an inner ring has no corresponding outer ring.
     OrphanInnerRing,
+    /// The part (given by its index) of a polyline converted into a polygon,
+    /// or of a validated polygon, is not closed (its first and last points differ)
+    UnclosedRing(usize),
+    /// The part (given by its index) of a polyline converted into a polygon,
+    /// of a validated polygon, or of a multipatch created from patches,
+    /// has less than the 4 points required for a ring
+    TooFewPointsInRing(usize),
+    /// The triangle strip or fan (given by its index) of a multipatch created from patches
+    /// has less than the 3 points required for a triangle
+    TooFewPointsInTriangles(usize),
+    /// The shapes given to [write_shapes_streamed](writer/struct.Writer.html#method.write_shapes_streamed)
+    /// were not the same when iterated to be written as when iterated to compute the header
+    ShapesChangedBetweenPasses,
+    /// The polyline converted into a geo_types LineString does not have exactly one part,
+    /// the number of parts it has is given
+    ExpectedSinglePart(usize),
+    /// The polygon converted into a geo_types Polygon does not have exactly one outer ring,
+    /// the number of outer rings it has is given
+    ExpectedSingleOuterRing(usize),
+    /// Some of the requested record indices are not in the file, they are given (sorted)
+    RecordIndicesOutOfRange(Vec<usize>),
+    /// The number of a record written is not greater than the number of the previous record
+    RecordNumberNotIncreasing { previous: i32, number: i32 },
+    /// The number in the header of a record read is not its position in the file
+    /// (record numbers are sequential and start at 1)
+    UnexpectedRecordNumber { expected: i32, number: i32 },
+    /// The part (given by its index) of a [PolylineM](record/poly/type.PolylineM.html) created
+    /// from coordinates and measures does not have as many measures as points
+    /// (a missing part counts as having none)
+    MeasuresLengthMismatch {
+        part: usize,
+        num_points: usize,
+        num_measures: usize,
+    },
+    /// The record holds a NullShape, which can not be read as the requested shape type
+    /// (the record is given by the `Error::RecordError` wrapping it)
+    NullShapeRecord { requested: ShapeType },
+    /// The .shp and the .dbf do not have the same number of records
+    RecordCountMismatch {
+        num_shapes: usize,
+        num_records: usize,
+    },
     NullShapeConversion,
     GeometryCollectionConversion,
+    /// The next shape given to [write_all_from](writer/struct.Writer.html#method.write_all_from)
+    /// would make the .shp larger than the limit (in bytes),
+    /// the shapes written before it form a valid file
+    FileSizeLimitReached { limit: u64, records_written: usize },
+    /// The file ends before the end given by its header or by the header of one of its records
+    TruncatedFile { expected_bytes: u64, actual_bytes: u64 },
+    /// The file is empty (0 bytes), it does not even have a header
+    EmptyFile,
+    /// The header of the record (given by its 0-based index) found at the offset given by
+    /// the index file does not have the content length the index file gives, in bytes
+    IndexEntryMismatch {
+        index: usize,
+        index_length: usize,
+        record_length: usize,
+    },
+    /// The entry (given by its 0-based index) of the index file has a negative content length,
+    /// or places its record within the header of the .shp or after its end
+    /// (the offset and the content length are given as stored, in 16-bit words)
+    InvalidIndexEntry {
+        index: usize,
+        offset: i32,
+        record_size: i32,
+    },
+    /// The bounding box created by [BBox::try_new](record/struct.BBox.html#method.try_new)
+    /// has a min greater than its max, or a coordinate that is not finite
+    InvalidBBox(record::BBox),
+    /// The hole added to a polygon has a point that is not inside any of its outer rings
+    HoleNotContained,
+    /// The hole added to a polygon crosses or touches the ring (given by its index)
+    HoleCrossesRing(usize),
+    /// The content length (in bytes) given by the header of the record (given by its number)
+    /// is larger than the limit of the reader, see
+    /// [max_record_size_bytes](reader/struct.Reader.html#method.max_record_size_bytes)
+    RecordTooLarge {
+        record_number: i32,
+        declared: u64,
+        limit: u64,
+    },
+    /// The error that occurred in a record, with the record it occurred in,
+    /// see [ReadError](struct.ReadError.html)
+    RecordError(Box<ReadError>),
+    /// The record written to a .dbf does not have the field (given by its name)
+    /// of the first record written, or has a value of another type for it
+    RecordFieldMismatch { field: String },
+    /// The records were already written to the .dbf destination of the writer
+    /// by a previous call to [write_records](writer/struct.Writer.html#method.write_records)
+    RecordsAlreadyWritten,
+    /// Wrapper around the errors of the zip archives (a `zip::result::ZipError`).
+    ///
+    /// The variants of the zip archives do not depend on the `zip` feature,
+    /// so that enabling it does not change the variants to match.
+    ZipError(Box<dyn std::error::Error + Send + Sync>),
+    /// The zip archive read does not hold a .shp
+    MissingShpInZip,
+    /// The zip archive read holds more than one .shp, their names are given
+    MultipleShpInZip(Vec<String>),
 }
 
 impl From<std::io::Error> for Error {
@@ -94,6 +244,13 @@ impl From<std::io::Error> for Error {
     }
 }
 
+#[cfg(feature = "zip")]
+impl From<zip::result::ZipError> for Error {
+    fn from(e: zip::result::ZipError) -> Error {
+        Error::ZipError(Box::new(e))
+    }
+}
+
 impl From<dbase::Error> for Error {
     fn from(e: dbase::Error) -> Error {
         Error::DbaseError(e)
@@ -114,21 +271,196 @@ impl fmt::Display for Error {
                 "The code ' {} ' does not correspond to any of the ShapeType code defined by ESRI",
                 code
             ),
+            Error::MismatchShapeType {
+                requested: ShapeType::PolygonZ,
+                actual: ShapeType::Multipatch,
+            } => write!(
+                f,
+                "The requested type: 'PolygonZ' does not correspond to the actual shape type: \
+                 'Multipatch', enable Reader::allow_multipatch_conversion to convert them"
+            ),
             Error::MismatchShapeType { requested, actual } => write!(
                 f,
                 "The requested type: '{}' does not correspond to the actual shape type: '{}'",
                 requested, actual
             ),
+            Error::ExpectedSinglePart(num_parts) => write!(
+                f,
+                "Expected a shape with exactly one part, but it has {} parts",
+                num_parts
+            ),
+            Error::ExpectedSingleOuterRing(num_outer_rings) => write!(
+                f,
+                "Expected a polygon with exactly one outer ring, but it has {} outer rings",
+                num_outer_rings
+            ),
+            Error::RecordIndicesOutOfRange(indices) => write!(
+                f,
+                "The record indices {:?} are out of range",
+                indices
+            ),
+            Error::RecordNumberNotIncreasing { previous, number } => write!(
+                f,
+                "The record number {} is not greater than the previous one ({})",
+                number, previous
+            ),
+            Error::UnexpectedRecordNumber { expected, number } => write!(
+                f,
+                "The record {} has the number {} in its header",
+                expected, number
+            ),
+            Error::MeasuresLengthMismatch {
+                part,
+                num_points,
+                num_measures,
+            } => write!(
+                f,
+                "The part {} has {} points but {} measures",
+                part, num_points, num_measures
+            ),
+            Error::NullShapeRecord { requested } => write!(
+                f,
+                "The record is a NullShape, it can not be read as a {}",
+                requested
+            ),
+            Error::RecordCountMismatch {
+                num_shapes,
+                num_records,
+            } => write!(
+                f,
+                "The .shp has {} shapes but the .dbf has {} records",
+                num_shapes, num_records
+            ),
+            Error::FileSizeLimitReached {
+                limit,
+                records_written,
+            } => write!(
+                f,
+                "The size limit of {} bytes was reached after writing {} records",
+                limit, records_written
+            ),
+            Error::TruncatedFile {
+                expected_bytes,
+                actual_bytes,
+            } => write!(
+                f,
+                "The file is truncated, it has {} bytes instead of {}",
+                actual_bytes, expected_bytes
+            ),
+            Error::InvalidShapeRecordSize => write!(f, "invalid shape record size"),
+            Error::EmptyFile => write!(f, "The file is empty, it does not have a header"),
+            Error::MissingIndexFile => write!(
+                f,
+                "The index file (.shx) is missing, it is needed to access records by their index"
+            ),
+            Error::IndexEntryMismatch {
+                index,
+                index_length,
+                record_length,
+            } => write!(
+                f,
+                "The record {} has {} bytes of content but the index file gives {}",
+                index, record_length, index_length
+            ),
+            Error::InvalidIndexEntry {
+                index,
+                offset,
+                record_size,
+            } => write!(
+                f,
+                "The entry {} of the index file is invalid: offset {} and content length {} \
+                 (in 16-bit words) are not within the .shp",
+                index, offset, record_size
+            ),
+            Error::InvalidBBox(bbox) => write!(
+                f,
+                "The bounding box ({}, {}, {}, {}) is invalid, its coordinates must be finite \
+                 and its min not greater than its max",
+                bbox.xmin, bbox.ymin, bbox.xmax, bbox.ymax
+            ),
+            Error::HoleNotContained => write!(
+                f,
+                "The hole is not contained by any of the outer rings of the polygon"
+            ),
+            Error::HoleCrossesRing(index) => write!(f, "The hole crosses the ring {}", index),
+            Error::UnclosedRing(index) => write!(f, "The ring {} is not closed", index),
+            Error::TooFewPointsInRing(index) => write!(
+                f,
+                "The ring {} has less than the 4 points required for a ring",
+                index
+            ),
+            Error::RecordTooLarge {
+                record_number,
+                declared,
+                limit,
+            } => write!(
+                f,
+                "The record {} has {} bytes of content, more than the limit of {} bytes",
+                record_number, declared, limit
+            ),
+            Error::RecordError(e) => write!(f, "{}", e),
+            Error::ZipError(e) => write!(f, "{}", e),
+            Error::MissingShpInZip => write!(f, "The zip archive does not hold a .shp"),
+            Error::MultipleShpInZip(names) => write!(
+                f,
+                "The zip archive holds more than one .shp: {}",
+                names.join(", ")
+            ),
             e => write!(f, "{:?}", e),
         }
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::RecordError(e) => Some(&e.error),
+            Error::ZipError(e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// An error of a record, with the position of the record in the file.
+///
+/// It is given by a [ContextShapeIterator](reader/struct.ContextShapeIterator.html),
+/// and in an `Error::RecordError` by the methods reading all the records of a file.
+#[derive(Debug)]
+pub struct ReadError {
+    /// The number of the record, as written in its header,
+    /// or its 1-based position in the file if the header could not be read
+    pub record_number: i32,
+    /// The offset in bytes of the record (of its header) in the .shp file
+    pub offset: u64,
+    /// The error that occurred
+    pub error: Error,
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "record {} at byte {}: {}",
+            self.record_number, self.offset, self.error
+        )
+    }
+}
+
+impl std::error::Error for ReadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl From<ReadError> for Error {
+    fn from(e: ReadError) -> Error {
+        Error::RecordError(Box::new(e))
+    }
+}
 
 /// The enum for the ShapeType as defined in the
 /// specification
-#[derive(Debug, PartialEq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
 pub enum ShapeType {
     NullShape = 0,
     Point = 1,
@@ -152,12 +484,7 @@ pub enum ShapeType {
 impl ShapeType {
     pub(crate) fn read_from<T: Read>(source: &mut T) -> Result<ShapeType, Error> {
         let code = source.read_i32::<LittleEndian>()?;
-        Self::from(code).ok_or_else(|| Error::InvalidShapeType(code))
-    }
-
-    pub(crate) fn write_to<T: Write>(self, dest: &mut T) -> Result<(), std::io::Error> {
-        dest.write_i32::<LittleEndian>(self as i32)?;
-        Ok(())
+        Self::from(code).ok_or(Error::InvalidShapeType(code))
     }
 
     /// Returns the ShapeType corresponding to the input code
@@ -190,44 +517,96 @@ impl ShapeType {
 
     /// Returns whether the ShapeType has the third dimension Z
     pub fn has_z(self) -> bool {
-        match self {
-            ShapeType::PointZ
-            | ShapeType::PolylineZ
-            | ShapeType::PolygonZ
-            | ShapeType::MultipointZ => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            ShapeType::PointZ | ShapeType::PolylineZ | ShapeType::PolygonZ | ShapeType::MultipointZ
+        )
+    }
+
+    /// Returns whether the shapes of this type can be read as shapes of the `other` type
+    /// without losing any value: both types have the same class of geometry,
+    /// and the `other` type has more dimensions.
+    ///
+    /// ```
+    /// use shapefile::ShapeType;
+    /// assert!(ShapeType::Polyline.can_be_promoted_to(ShapeType::PolylineZ));
+    /// assert!(ShapeType::PointM.can_be_promoted_to(ShapeType::PointZ));
+    /// assert!(!ShapeType::PolylineZ.can_be_promoted_to(ShapeType::Polyline));
+    /// assert!(!ShapeType::Polyline.can_be_promoted_to(ShapeType::PolygonZ));
+    /// ```
+    pub fn can_be_promoted_to(self, other: ShapeType) -> bool {
+        // A type with z also has m
+        let num_dimensions = |shape_type: ShapeType| {
+            2 + shape_type.has_m() as usize + shape_type.has_z() as usize
+        };
+        self.geometry_class() == other.geometry_class()
+            && num_dimensions(self) < num_dimensions(other)
     }
 
     /// Returns whether the ShapeType has the optional measure dimension
     pub fn has_m(self) -> bool {
-        match self {
+        matches!(
+            self,
             ShapeType::PointZ
-            | ShapeType::PolylineZ
-            | ShapeType::PolygonZ
-            | ShapeType::MultipointZ
-            | ShapeType::PointM
-            | ShapeType::PolylineM
-            | ShapeType::PolygonM
-            | ShapeType::MultipointM => true,
-            _ => false,
-        }
+                | ShapeType::PolylineZ
+                | ShapeType::PolygonZ
+                | ShapeType::MultipointZ
+                | ShapeType::PointM
+                | ShapeType::PolylineM
+                | ShapeType::PolygonM
+                | ShapeType::MultipointM
+        )
     }
 
     /// Returns true if the shape may have multiple parts
     pub fn is_multipart(self) -> bool {
+        !matches!(
+            self.geometry_class(),
+            GeometryClass::Point | GeometryClass::Multipoint
+        )
+    }
+
+    /// Returns the class of geometry of the ShapeType, which ignores its z and m
+    ///
+    /// ```
+    /// use shapefile::{GeometryClass, ShapeType};
+    ///
+    /// assert_eq!(ShapeType::PolygonZ.geometry_class(), GeometryClass::Polygon);
+    /// assert_eq!(ShapeType::NullShape.geometry_class(), GeometryClass::Null);
+    /// ```
+    pub fn geometry_class(self) -> GeometryClass {
         match self {
-            ShapeType::Point
-            | ShapeType::PointM
-            | ShapeType::PointZ
-            | ShapeType::Multipoint
-            | ShapeType::MultipointM
-            | ShapeType::MultipointZ => false,
-            _ => true,
+            ShapeType::NullShape => GeometryClass::Null,
+            ShapeType::Point | ShapeType::PointM | ShapeType::PointZ => GeometryClass::Point,
+            ShapeType::Polyline | ShapeType::PolylineM | ShapeType::PolylineZ => {
+                GeometryClass::Polyline
+            }
+            ShapeType::Polygon | ShapeType::PolygonM | ShapeType::PolygonZ => {
+                GeometryClass::Polygon
+            }
+            ShapeType::Multipoint | ShapeType::MultipointM | ShapeType::MultipointZ => {
+                GeometryClass::Multipoint
+            }
+            ShapeType::Multipatch => GeometryClass::Multipatch,
         }
     }
 }
 
+/// The class of geometry of a [ShapeType](enum.ShapeType.html) or [Shape](record/enum.Shape.html),
+/// regardless of its z and m.
+///
+/// Matching on it instead of the variants of `Shape` keeps user code working
+/// when shapes are added.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+pub enum GeometryClass {
+    Point,
+    Multipoint,
+    Polyline,
+    Polygon,
+    Multipatch,
+    Null,
+}
+
 impl fmt::Display for ShapeType {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
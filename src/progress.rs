@@ -0,0 +1,51 @@
+//! Module with the [Progress](struct.Progress.html) reported while reading or writing
+//!
+//! See [Reader::with_progress](../reader/struct.Reader.html#method.with_progress)
+//! and [Writer::with_progress](../writer/struct.Writer.html#method.with_progress).
+
+/// Progress of a read or a write, given to the progress callback
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Progress {
+    /// Number of records read or written so far
+    pub records_done: usize,
+    /// Total number of records, if known
+    /// (when reading, it is only known if the reader has an index file)
+    pub records_total: Option<usize>,
+    /// Number of bytes of the .shp read or written so far (including its header)
+    pub bytes_done: u64,
+    /// Size of the .shp in bytes, if known
+    pub bytes_total: Option<u64>,
+}
+
+/// Calls the user's callback every `every` records
+pub(crate) struct ProgressReporter {
+    callback: Box<dyn FnMut(Progress) + Send>,
+    every: usize,
+}
+
+impl ProgressReporter {
+    pub(crate) fn new<F: FnMut(Progress) + Send + 'static>(every: usize, callback: F) -> Self {
+        Self {
+            callback: Box::new(callback),
+            every: every.max(1),
+        }
+    }
+
+    /// Reports the progress if the number of records done is a multiple of the interval,
+    /// or if it is the last record.
+    pub(crate) fn on_record(&mut self, progress: Progress, is_last: bool) {
+        if is_last || progress.records_done % self.every == 0 {
+            (self.callback)(progress);
+        }
+    }
+
+    /// Reports the progress of the last record, when the total number
+    /// of records was not known while they were done.
+    ///
+    /// Does nothing if the progress of the last record was already reported.
+    pub(crate) fn on_finished(&mut self, progress: Progress) {
+        if progress.records_done % self.every != 0 {
+            (self.callback)(progress);
+        }
+    }
+}
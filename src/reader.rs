@@ -10,6 +10,10 @@
 //!
 //! If you want to read a shapefile that is not storred in a file (e.g the shp data is in a buffer),
 //! you will have to construct the `Reader` "by hand" with its [new](struct.Reader.html#method.new) method.
+//! The source only has to implement `Read`, so that the .shp can be read from a stream
+//! (stdin, a network socket...). Sources that also implement `Seek` can be given to
+//! [from_seekable](struct.Reader.html#method.from_seekable) to skip records faster
+//! and to use the methods that need the index file.
 //!
 //! If you want the "manually" constructed `Reader` to also read the *shx* and *dbf* file content
 //! you will have to use [add_index_source](struct.Reader.html#method.add_index_source) and/or
@@ -39,9 +43,9 @@
 //! you can use the different `*_as::<S>()` functions.:
 //! - [read_as](struct.Reader.html#method.read_as) To read all the shapes as the specified type
 //! - [iter_shapes_as](struct.Reader.html#method.iter_shapes_as) To iterate over the shapes as shapes
-//! of the specified type
+//!   of the specified type
 //! - [iter_shapes_and_records_as](struct.Reader.html#method.iter_shapes_and_records_as) To iterate
-//! over both the shapes and records
+//!   over both the shapes and records
 //!
 //! Otherwise use the functions that return [Shape](../record/enum.Shape.html)s and do a `match`
 //!
@@ -53,157 +57,1937 @@
 //! Two functions ([read](fn.read.html) and [read_as](fn.read_as.html)) are provided to read
 //! files with one function call (thus not having to build a `Reader`)
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::io::{BufReader, BufWriter, Cursor, Read, Seek, SeekFrom, Write};
 use std::iter::FusedIterator;
-use std::path::Path;
-
-use byteorder::{BigEndian, ReadBytesExt};
+use std::path::{Path, PathBuf};
 
+use constants::HEADER_SIZE;
+use extent::{is_within, shape_violations, ExtentViolation};
 use header;
+use index::{IndexTable, RecordLocation};
+use progress::{Progress, ProgressReporter};
+use sidecar::{companion_path_of, sidecar_paths_of};
+use summary::FileSummary;
 use record;
-use {Error, Shape};
+use {Error, GeometryClass, ReadError, Shape, ShapeType};
+
+use record::{BBox, EsriShape, HasShapeType, Multipatch, PartialShape, ReadableShape};
+use record::{IntersectsBBox, PolygonZ, RecordSizeCheck, RetainPartsInBBox};
+use record::borrowed::{read_shape_ref, ShapeRef};
+
+/// Default of the largest content length, in bytes, of the records read,
+/// see [max_record_size_bytes](struct.Reader.html#method.max_record_size_bytes)
+pub const DEFAULT_MAX_RECORD_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Capacity of the buffers of the files opened by
+/// [Reader::from_path](struct.Reader.html#method.from_path), the one of `std::io::BufReader`
+const DEFAULT_READ_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Reads bytes until the `buf` is full or the end of the `source` is reached,
+/// returns the number of bytes read
+fn read_up_to<T: Read>(source: &mut T, buf: &mut [u8]) -> std::io::Result<usize> {
+    let mut num_read = 0;
+    while num_read < buf.len() {
+        match source.read(&mut buf[num_read..]) {
+            Ok(0) => break,
+            Ok(n) => num_read += n,
+            Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(num_read)
+}
+
+/// Reads the header of the record starting at `pos` (in bytes).
+///
+/// If the source ends within the record header, the error is `Error::TruncatedFile`,
+/// the `file_length` (in bytes) being the one given by the file header.
+pub(crate) fn read_record_header<T: Read>(
+    source: &mut T,
+    pos: u64,
+    file_length: u64,
+) -> Result<record::RecordHeader, Error> {
+    let mut bytes = [0u8; record::RecordHeader::SIZE];
+    let num_read = read_up_to(source, &mut bytes)?;
+    if num_read < bytes.len() {
+        return Err(Error::TruncatedFile {
+            expected_bytes: file_length,
+            actual_bytes: pos + num_read as u64,
+        });
+    }
+    record::RecordHeader::read_from(&mut &bytes[..])
+}
+
+/// Returns the offset (in bytes) of the end of the record starting at `pos`.
+///
+/// A record which content length is negative, or which ends after the end of the file
+/// given by the file header (`file_length`, in bytes), is an `Error::InvalidShapeRecordSize`:
+/// the bytes some tools append after the end of the file are not part of its records.
+pub(crate) fn end_of_record(
+    pos: u64,
+    hdr: &record::RecordHeader,
+    file_length: u64,
+) -> Result<u64, Error> {
+    let record_end = pos as i64 + record::RecordHeader::SIZE as i64 + hdr.record_size as i64 * 2;
+    if hdr.record_size < 0 || record_end > file_length as i64 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    Ok(record_end as u64)
+}
+
+/// Iterator over the headers of the records of a seekable *.shp*, with the offsets
+/// of the start and of the end of each record.
+///
+/// The content of a record is skipped using `seek` when the next header is read,
+/// so it may be read (or partly read) in between through [source](#method.source).
+///
+/// A record which content can not hold its shape type, or which ends after the end
+/// of the file given by the header, is an `Error::InvalidShapeRecordSize`,
+/// a source ending before the end of a record is an `Error::TruncatedFile`.
+/// The iteration ends after an error.
+pub(crate) struct RecordHeaders<'a, T: 'a> {
+    source: &'a mut T,
+    /// Offset of the next record
+    next_pos: u64,
+    /// Length in bytes given by the file header
+    file_length: u64,
+    /// Length in bytes of the source
+    source_length: u64,
+}
 
-use record::ReadableShape;
+impl<'a, T: Read + Seek> RecordHeaders<'a, T> {
+    /// Starts at the first record of the `source`, the `file_length` (in bytes)
+    /// being the one given by its header
+    pub(crate) fn new(source: &'a mut T, file_length: u64) -> Result<Self, Error> {
+        let source_length = source.seek(SeekFrom::End(0))?;
+        Ok(Self {
+            source,
+            next_pos: HEADER_SIZE as u64,
+            file_length,
+            source_length,
+        })
+    }
 
-const INDEX_RECORD_SIZE: usize = 2 * std::mem::size_of::<i32>();
+    /// Returns the source, which is after the header of the last record returned
+    /// until the content of the record is read
+    pub(crate) fn source(&mut self) -> &mut T {
+        self.source
+    }
 
-pub(crate) struct ShapeIndex {
-    pub offset: i32,
-    pub record_size: i32,
+    fn read_next(&mut self) -> Result<(u64, record::RecordHeader, u64), Error> {
+        let pos = self.next_pos;
+        let truncated = Error::TruncatedFile {
+            expected_bytes: self.file_length,
+            actual_bytes: self.source_length,
+        };
+        if pos + record::RecordHeader::SIZE as u64 > self.source_length {
+            return Err(truncated);
+        }
+        self.source.seek(SeekFrom::Start(pos))?;
+        let hdr = record::RecordHeader::read_from(self.source)?;
+        // The content has at least the shape type
+        if hdr.record_size < 2 {
+            return Err(Error::InvalidShapeRecordSize);
+        }
+        let record_end = end_of_record(pos, &hdr, self.file_length)?;
+        if record_end > self.source_length {
+            return Err(truncated);
+        }
+        self.next_pos = record_end;
+        Ok((pos, hdr, record_end))
+    }
 }
 
-/// Read the content of a .shx file
-fn read_index_file<T: Read>(mut source: T) -> Result<Vec<ShapeIndex>, Error> {
-    let header = header::Header::read_from(&mut source)?;
+impl<'a, T: Read + Seek> Iterator for RecordHeaders<'a, T> {
+    type Item = Result<(u64, record::RecordHeader, u64), Error>;
 
-    let num_shapes = ((header.file_length * 2) - header::HEADER_SIZE) / INDEX_RECORD_SIZE as i32;
-    let mut shapes_index = Vec::<ShapeIndex>::with_capacity(num_shapes as usize);
-    for _ in 0..num_shapes {
-        let offset = source.read_i32::<BigEndian>()?;
-        let record_size = source.read_i32::<BigEndian>()?;
-        shapes_index.push(ShapeIndex {
-            offset,
-            record_size,
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next_pos >= self.file_length {
+            return None;
+        }
+        let result = self.read_next();
+        if result.is_err() {
+            self.next_pos = self.file_length;
+        }
+        Some(result)
+    }
+}
+
+/// Size of the beginning of the record content read to get the bbox of a record:
+/// the shape type and the bbox
+const BBOX_BEGINNING_SIZE: usize = 36;
+
+/// Reads the beginning of the content of a record, of `content_size` bytes and starting
+/// at `content_start`, to get its bbox (the point itself for points, `None` for NullShapes).
+///
+/// Returns the bbox with the number of bytes read, the `file_length` (in bytes)
+/// being the one given by the file header.
+pub(crate) fn read_record_bbox<T: Read>(
+    source: &mut T,
+    content_start: u64,
+    content_size: u64,
+    file_length: u64,
+) -> Result<(Option<BBox>, u64), Error> {
+    use byteorder::{LittleEndian, ReadBytesExt};
+
+    let mut beginning = [0u8; BBOX_BEGINNING_SIZE];
+    let beginning_size = content_size.min(BBOX_BEGINNING_SIZE as u64) as usize;
+    let num_read = read_up_to(source, &mut beginning[..beginning_size])?;
+    if num_read < beginning_size {
+        return Err(Error::TruncatedFile {
+            expected_bytes: file_length,
+            actual_bytes: content_start + num_read as u64,
         });
     }
-    Ok(shapes_index)
+    let mut beginning = &beginning[..beginning_size];
+    if beginning.len() < 4 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    let shape_type = ShapeType::read_from(&mut beginning)?;
+    let bbox = match shape_type.geometry_class() {
+        GeometryClass::Null => None,
+        // The content of a point is its coordinates
+        GeometryClass::Point if beginning.len() >= 16 => {
+            let x = beginning.read_f64::<LittleEndian>()?;
+            let y = beginning.read_f64::<LittleEndian>()?;
+            Some(BBox::new(x, y, x, y))
+        }
+        GeometryClass::Point => return Err(Error::InvalidShapeRecordSize),
+        _ if beginning.len() == 32 => Some(BBox::read_from(&mut beginning)?),
+        _ => return Err(Error::InvalidShapeRecordSize),
+    };
+    Ok((bbox, beginning_size as u64))
+}
+
+/// Source counting the bytes read from the source it wraps
+struct CountingReader<'a, T: 'a> {
+    source: &'a mut T,
+    num_read: u64,
+}
+
+impl<'a, T: Read> Read for CountingReader<'a, T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let num_read = self.source.read(buf)?;
+        self.num_read += num_read as u64;
+        Ok(num_read)
+    }
+}
+
+/// Reads the shape from the content of the record starting at `pos`, like `read_content_as`,
+/// a source ending within the record being an `Error::TruncatedFile`
+/// (the `file_length`, in bytes, being the one given by the file header)
+fn read_content_within<T: Read, S: ReadableShape>(
+    source: &mut T,
+    pos: u64,
+    hdr: &record::RecordHeader,
+    file_length: u64,
+    parsing: RecordParsing,
+    buffer: Option<&mut Vec<u8>>,
+) -> Result<S, Error> {
+    let mut content = CountingReader {
+        source,
+        num_read: 0,
+    };
+    let result = read_content_as::<_, S>(&mut content, hdr, parsing, buffer);
+    let is_truncated = content.num_read < hdr.record_size.max(0) as u64 * 2
+        && match result {
+            Ok(_) => true,
+            Err(Error::IoError(ref e)) => e.kind() == std::io::ErrorKind::UnexpectedEof,
+            Err(_) => false,
+        };
+    if is_truncated {
+        return Err(Error::TruncatedFile {
+            expected_bytes: file_length,
+            actual_bytes: pos + record::RecordHeader::SIZE as u64 + content.num_read,
+        });
+    }
+    result
+}
+
+/// The shape type of the records read as another one with the same layout,
+/// see [reinterpret_as](struct.Reader.html#method.reinterpret_as)
+#[derive(Debug, Copy, Clone)]
+struct Reinterpretation {
+    from: ShapeType,
+    to: ShapeType,
+}
+
+/// How the content of the records is parsed, see
+/// [reinterpret_as](struct.Reader.html#method.reinterpret_as),
+/// [allow_multipatch_conversion](struct.Reader.html#method.allow_multipatch_conversion),
+/// [allow_padded_records](struct.Reader.html#method.allow_padded_records),
+/// [max_record_size_bytes](struct.Reader.html#method.max_record_size_bytes) and
+/// [keep_unknown_shapes](struct.Reader.html#method.keep_unknown_shapes)
+#[derive(Debug, Copy, Clone)]
+struct RecordParsing {
+    reinterpretation: Option<Reinterpretation>,
+    multipatch_conversion: bool,
+    size_check: RecordSizeCheck,
+    max_record_size: Option<u64>,
+    keep_unknown_shapes: bool,
+    normalize_polygons: bool,
+}
+
+impl RecordParsing {
+    /// Checks that the content length of the record is not larger than the limit
+    fn check_size(&self, hdr: &record::RecordHeader) -> Result<(), Error> {
+        check_record_size(hdr, self.max_record_size)
+    }
+}
+
+/// Checks that the content length of the record is not larger than the `limit`,
+/// see [max_record_size_bytes](struct.Reader.html#method.max_record_size_bytes)
+pub(crate) fn check_record_size(
+    hdr: &record::RecordHeader,
+    limit: Option<u64>,
+) -> Result<(), Error> {
+    let record_size = hdr.record_size.max(0) as u64 * 2;
+    match limit {
+        Some(limit) if record_size > limit => Err(Error::RecordTooLarge {
+            record_number: hdr.record_number,
+            declared: record_size,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl Default for RecordParsing {
+    fn default() -> Self {
+        RecordParsing {
+            reinterpretation: None,
+            multipatch_conversion: false,
+            size_check: RecordSizeCheck::default(),
+            max_record_size: Some(DEFAULT_MAX_RECORD_SIZE),
+            keep_unknown_shapes: false,
+            normalize_polygons: false,
+        }
+    }
+}
+
+/// Reads the shape from the content of the record, like `record::read_record_content`,
+/// a record of the shape type `from` of the reinterpretation being read as its `to` type,
+/// a multipatch being converted to the shape if the conversion is allowed,
+/// and a record of an unknown shape type being kept if the unknown shapes are kept.
+///
+/// For these, the content is read in the `buffer` before being parsed,
+/// the same buffer can be given for each record to only allocate it once
+/// (without one, the content is read in a new buffer).
+fn read_content_as<T: Read, S: ReadableShape>(
+    source: &mut T,
+    hdr: &record::RecordHeader,
+    parsing: RecordParsing,
+    buffer: Option<&mut Vec<u8>>,
+) -> Result<S, Error> {
+    if hdr.record_size < 0 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    parsing.check_size(hdr)?;
+    let record_size = hdr.record_size as u64 * 2;
+    let reads_as_is = parsing.reinterpretation.is_none()
+        && !parsing.multipatch_conversion
+        && !parsing.keep_unknown_shapes
+        && !parsing.normalize_polygons;
+    if reads_as_is || record_size < 4 {
+        return record::read_record_content(source, record_size, parsing.size_check);
+    }
+    let mut new_buffer = Vec::new();
+    let buffer = buffer.unwrap_or(&mut new_buffer);
+    // The content grows as it is read, the size of a corrupted record may be far too large
+    buffer.clear();
+    source.take(record_size).read_to_end(buffer)?;
+    let content = &mut buffer[..];
+    if let Some(reinterpretation) = parsing.reinterpretation {
+        let from = (reinterpretation.from as i32).to_le_bytes();
+        if content.get(..4) == Some(&from[..]) {
+            content[..4].copy_from_slice(&(reinterpretation.to as i32).to_le_bytes());
+        }
+    }
+    let size_check = parsing.size_check;
+    let read_shape = record::read_record_content::<_, Shape>;
+    let mut shape = match read_shape(&mut &content[..], record_size, size_check) {
+        Err(Error::InvalidShapeType(code)) if parsing.keep_unknown_shapes => {
+            // The code may be the one of a part of a known shape (e.g. of a Multipatch)
+            if content[..4] != code.to_le_bytes() {
+                return Err(Error::InvalidShapeType(code));
+            }
+            let shape = Shape::Unknown {
+                shape_type: code,
+                content: content[4..].to_vec(),
+            };
+            return S::try_from_shape(shape).map_err(|_| Error::InvalidShapeType(code));
+        }
+        result => result?,
+    };
+    if parsing.normalize_polygons {
+        shape.normalize_polygon_rings();
+    }
+    match S::try_from_shape(shape) {
+        Err(Error::MismatchShapeType {
+            requested: ShapeType::PolygonZ,
+            actual: ShapeType::Multipatch,
+        }) if parsing.multipatch_conversion => {
+            let multipatch: Multipatch =
+                record::read_record_content(&mut &content[..], record_size, size_check)?;
+            let mut polygon = PolygonZ::try_from(multipatch)?;
+            if parsing.normalize_polygons {
+                polygon.normalize_rings();
+            }
+            S::try_from_shape(Shape::PolygonZ(polygon))
+        }
+        result => result,
+    }
 }
 
 /// Reads and returns one shape and its header from the source
 fn read_one_shape_as<T: Read, S: ReadableShape>(
     mut source: &mut T,
+    parsing: RecordParsing,
 ) -> Result<(record::RecordHeader, S), Error> {
     let hdr = record::RecordHeader::read_from(&mut source)?;
-    let record_size = hdr.record_size * 2;
-    let shape = S::read_from(&mut source, record_size)?;
+    let shape: S = read_content_as(&mut source, &hdr, parsing, None)?;
     Ok((hdr, shape))
 }
 
+/// What the iterators of a [Reader](struct.Reader.html) do with the records
+/// that can not be read as shapes, see
+/// [set_error_policy](struct.Reader.html#method.set_error_policy)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// The error of the record is returned and the iteration ends (the default)
+    #[default]
+    Fail,
+    /// The error of the record is returned, and the iteration continues with the next record,
+    /// using the content length of the record header to find it.
+    ///
+    /// The iteration still ends after the errors that can not be recovered from,
+    /// see `SkipMalformed`.
+    Continue,
+    /// The record is skipped, using the content length of its record header
+    /// to find the next one.
+    ///
+    /// The numbers of the skipped records are given by
+    /// [skipped_records](struct.ShapeIterator.html#method.skipped_records).
+    ///
+    /// Records which header can not be read, records in which the file ends,
+    /// and I/O errors other than an unexpected end of file, are never skipped:
+    /// they are returned and the iteration ends.
+    ///
+    /// Well-formed records holding a shape of another type than the one requested
+    /// (`Error::MismatchShapeType`, `Error::NullShapeRecord`) are not skipped either:
+    /// their error is returned and the iteration continues, as with `Continue`.
+    SkipMalformed,
+}
+
+/// Returns true if the next record can be found after the error,
+/// returned when reading the content of a record
+fn is_recoverable(error: &Error) -> bool {
+    match error {
+        Error::IoError(e) => e.kind() == std::io::ErrorKind::UnexpectedEof,
+        // The content of the record is not read, the next record can not be found
+        Error::RecordTooLarge { .. } => false,
+        // The file ends within the record, there is no next record
+        Error::TruncatedFile { .. } => false,
+        _ => true,
+    }
+}
+
+/// Returns true if the error, returned when reading the content of a record,
+/// is skipped with the [SkipMalformed](enum.ErrorPolicy.html#variant.SkipMalformed) policy
+fn is_malformed_record(error: &Error) -> bool {
+    match error {
+        // The record is well-formed, its shape is not of the type requested
+        Error::MismatchShapeType { .. } | Error::NullShapeRecord { .. } => false,
+        e => is_recoverable(e),
+    }
+}
+
+/// Returns true if the iteration continues after the error of a record
+/// which content was consumed
+fn continues_after(policy: ErrorPolicy, error: &Error) -> bool {
+    policy != ErrorPolicy::Fail && is_recoverable(error)
+}
+
 /// Struct that handle iteration over the shapes of a .shp file
+///
+/// The records are read one at a time, until the end of the file given by its header.
+///
+/// A record that can not be read as a shape is returned as an error,
+/// and the iteration ends: the following calls to `next` return `None`.
+///
+/// With the [Continue](enum.ErrorPolicy.html#variant.Continue) policy,
+/// the iteration continues with the next record after such an error, and with the
+/// [SkipMalformed](enum.ErrorPolicy.html#variant.SkipMalformed) policy,
+/// the records that can not be read as shapes are skipped instead of returned as errors.
+/// The iteration always ends after an error if the record header itself can not be read.
 pub struct ShapeIterator<T: Read, S: ReadableShape> {
     _shape: std::marker::PhantomData<S>,
     source: T,
     current_pos: usize,
     file_length: usize,
+    num_records_read: usize,
+    records_total: Option<usize>,
+    progress: Option<ProgressReporter>,
+    error_policy: ErrorPolicy,
+    parsing: RecordParsing,
+    skipped_records: Vec<i32>,
+    /// Buffer of the content of the records that are read before being parsed
+    /// (see `read_content_as`), reused between records
+    content_buffer: Vec<u8>,
+    /// Number in the header of the last record read
+    last_record_number: i32,
+    /// Offset of the last record read
+    last_record_offset: usize,
+    /// Whether the iteration ended after an error
+    failed: bool,
+    /// Error of the seek to the first record, returned as the first item
+    initial_error: Option<Error>,
+}
+
+impl<T: Read, S: ReadableShape> ShapeIterator<T, S> {
+    /// Returns the record numbers (as written in their record headers) of the records
+    /// skipped so far with the [SkipMalformed](enum.ErrorPolicy.html#variant.SkipMalformed)
+    /// policy
+    pub fn skipped_records(&self) -> &[i32] {
+        &self.skipped_records
+    }
+
+    /// Makes the iterator return the errors with the number and offset
+    /// of the record they occurred in, see [ReadError](../struct.ReadError.html).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// for result in reader.iter_shapes().with_context() {
+    ///     match result {
+    ///         Ok(shape) => println!("{}", shape),
+    ///         Err(e) => println!("record {} at {}: {}", e.record_number, e.offset, e.error),
+    ///     }
+    /// }
+    /// ```
+    pub fn with_context(self) -> ContextShapeIterator<T, S> {
+        ContextShapeIterator { shape_iter: self }
+    }
+
+    /// Returns whether no record is left to read
+    /// (the error of the seek to the first record may still be returned)
+    fn has_ended(&self) -> bool {
+        self.failed || self.current_pos >= self.file_length
+    }
+
+    /// Ends the iteration after an error of an iterator wrapping this one,
+    /// which record was read, unless the policy is to continue
+    fn end_after(&mut self, error: &Error) {
+        if !continues_after(self.error_policy, error) {
+            self.failed = true;
+        }
+    }
+
+    /// Returns the error, which occurred in the last record read, with the record
+    fn read_error(&self, error: Error) -> ReadError {
+        ReadError {
+            record_number: self.last_record_number,
+            offset: self.last_record_offset as u64,
+            error,
+        }
+    }
+
+    /// Wraps the error, which occurred in the last record read, in an `Error::RecordError`
+    fn record_error(&self, error: Error) -> Error {
+        Error::RecordError(Box::new(self.read_error(error)))
+    }
+
+    /// Returns the error, which occurred in the last record read by a method
+    /// reading all the records, wrapped in an `Error::RecordError`.
+    ///
+    /// An `Error::MismatchShapeType` is returned as is:
+    /// the records are not of the requested type.
+    fn read_all_error(&self, error: Error) -> Error {
+        match error {
+            Error::MismatchShapeType { .. } => error,
+            error => self.record_error(error),
+        }
+    }
 }
 
 impl<T: Read, S: ReadableShape> Iterator for ShapeIterator<T, S> {
     type Item = Result<S, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.current_pos >= self.file_length {
-            None
-        } else {
-            let (hdr, shape) = match read_one_shape_as::<T, S>(&mut self.source) {
-                Err(e) => return Some(Err(e)),
-                Ok(hdr_and_shape) => hdr_and_shape,
+        if let Some(error) = self.initial_error.take() {
+            return Some(Err(error));
+        }
+        loop {
+            if self.has_ended() {
+                return None;
+            }
+            self.last_record_offset = self.current_pos;
+            self.last_record_number = self.num_records_read as i32 + 1;
+            // Without a valid record header, the next record can not be found
+            let (pos, file_length) = (self.current_pos as u64, self.file_length as u64);
+            let hdr = read_record_header(&mut self.source, pos, file_length).and_then(|hdr| {
+                self.parsing.check_size(&hdr)?;
+                end_of_record(pos, &hdr, file_length)?;
+                Ok(hdr)
+            });
+            let hdr = match hdr {
+                Ok(hdr) => hdr,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(e));
+                }
             };
+            // The content of the record is always consumed, even if it can not be read
+            // as a shape, so that the iteration continues with the next record
+            let result = read_content_within::<T, S>(
+                &mut self.source,
+                pos,
+                &hdr,
+                file_length,
+                self.parsing,
+                Some(&mut self.content_buffer),
+            );
             self.current_pos += record::RecordHeader::SIZE;
             self.current_pos += hdr.record_size as usize * 2;
-            Some(Ok(shape))
+            self.num_records_read += 1;
+            self.last_record_number = hdr.record_number;
+            if let Some(ref mut progress) = self.progress {
+                let is_last = self.current_pos >= self.file_length;
+                progress.on_record(
+                    Progress {
+                        records_done: self.num_records_read,
+                        records_total: self.records_total,
+                        bytes_done: self.current_pos as u64,
+                        bytes_total: Some(self.file_length as u64),
+                    },
+                    is_last,
+                );
+            }
+            match result {
+                Err(ref e)
+                    if self.error_policy == ErrorPolicy::SkipMalformed
+                        && is_malformed_record(e) =>
+                {
+                    self.skipped_records.push(hdr.record_number)
+                }
+                Err(e) => {
+                    if !continues_after(self.error_policy, &e) {
+                        self.failed = true;
+                    }
+                    return Some(Err(e));
+                }
+                result => return Some(result),
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.initial_error.is_some() {
+            return (1, Some(1));
+        }
+        if self.failed {
+            return (0, Some(0));
         }
+        if let Some(records_total) = self.records_total {
+            let remaining = records_total.saturating_sub(self.num_records_read);
+            // An error may end the iteration, or a record be skipped
+            let lower = match self.error_policy {
+                ErrorPolicy::Fail => remaining.min(1),
+                ErrorPolicy::Continue => remaining,
+                ErrorPolicy::SkipMalformed => 0,
+            };
+            return (lower, Some(remaining));
+        }
+        // The smallest record is a NullShape: record header + shape type
+        let remaining_bytes = self.file_length.saturating_sub(self.current_pos);
+        let min_record_size = record::RecordHeader::SIZE + std::mem::size_of::<i32>();
+        let lower = if remaining_bytes > 0 { 1 } else { 0 };
+        (lower, Some(remaining_bytes / min_record_size))
     }
 }
 
 impl<T: Read, S: ReadableShape> FusedIterator for ShapeIterator<T, S> {}
 
+/// Iterator over the shapes of a .shp file which errors give the record
+/// they occurred in, created by [with_context](struct.ShapeIterator.html#method.with_context)
+///
+/// Errors end the iteration like they do for the [ShapeIterator](struct.ShapeIterator.html)
+/// it wraps, with the [Continue](enum.ErrorPolicy.html#variant.Continue) policy
+/// the iteration continues with the next record if the record header could be read.
+pub struct ContextShapeIterator<T: Read, S: ReadableShape> {
+    shape_iter: ShapeIterator<T, S>,
+}
+
+impl<T: Read, S: ReadableShape> ContextShapeIterator<T, S> {
+    /// Returns the record numbers of the records skipped so far,
+    /// see [ShapeIterator::skipped_records](struct.ShapeIterator.html#method.skipped_records)
+    pub fn skipped_records(&self) -> &[i32] {
+        self.shape_iter.skipped_records()
+    }
+}
+
+impl<T: Read, S: ReadableShape> Iterator for ContextShapeIterator<T, S> {
+    type Item = Result<S, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let result = self.shape_iter.next()?;
+        Some(result.map_err(|error| self.shape_iter.read_error(error)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shape_iter.size_hint()
+    }
+}
+
+impl<T: Read, S: ReadableShape> FusedIterator for ContextShapeIterator<T, S> {}
+
+/// Iterator over the shapes of a .shp file paired with their record numbers,
+/// created by [iter_numbered_shapes_as](struct.Reader.html#method.iter_numbered_shapes_as)
+///
+/// A shape which record number is not its 1-based position in the file is returned
+/// as an `Error::UnexpectedRecordNumber`.
+pub struct NumberedShapeIterator<T: Read, S: ReadableShape> {
+    shape_iter: ShapeIterator<T, S>,
+}
+
+impl<T: Read, S: ReadableShape> Iterator for NumberedShapeIterator<T, S> {
+    type Item = Result<(i32, S), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let shape = match self.shape_iter.next()? {
+            Err(e) => return Some(Err(e)),
+            Ok(shape) => shape,
+        };
+        // Skipped records keep their place in the numbering
+        let expected = self.shape_iter.num_records_read as i32;
+        let number = self.shape_iter.last_record_number;
+        if number != expected {
+            let error = Error::UnexpectedRecordNumber { expected, number };
+            self.shape_iter.end_after(&error);
+            return Some(Err(error));
+        }
+        Some(Ok((number, shape)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shape_iter.size_hint()
+    }
+}
+
+impl<T: Read, S: ReadableShape> FusedIterator for NumberedShapeIterator<T, S> {}
+
+/// A record as it is in the file: its number, the shape type of its content
+/// (`None` if the code is not the one of a known shape type) and its content
+/// (starting with the shape type code)
+pub type RawRecord = (i32, Option<ShapeType>, Vec<u8>);
+
+/// Iterator over the records of a .shp file, which content is not read as a shape,
+/// created by [iter_raw_records](struct.Reader.html#method.iter_raw_records)
+///
+/// The iteration ends after an error.
+pub struct RawRecordIterator<T: Read> {
+    source: T,
+    current_pos: usize,
+    file_length: usize,
+    parsing: RecordParsing,
+    /// Whether the iteration ended after an error
+    failed: bool,
+    /// Error of the seek to the first record, returned as the first item
+    initial_error: Option<Error>,
+}
+
+impl<T: Read> RawRecordIterator<T> {
+    fn next_record(&mut self) -> Result<RawRecord, Error> {
+        use byteorder::{LittleEndian, ReadBytesExt};
+
+        let hdr = read_record_header(
+            &mut self.source,
+            self.current_pos as u64,
+            self.file_length as u64,
+        )?;
+        // The content has at least the shape type
+        if hdr.record_size < 2 {
+            return Err(Error::InvalidShapeRecordSize);
+        }
+        self.parsing.check_size(&hdr)?;
+        end_of_record(self.current_pos as u64, &hdr, self.file_length as u64)?;
+        let record_size = hdr.record_size as usize * 2;
+        let mut content = Vec::<u8>::new();
+        (&mut self.source)
+            .take(record_size as u64)
+            .read_to_end(&mut content)?;
+        let content_start = self.current_pos + record::RecordHeader::SIZE;
+        if content.len() < record_size {
+            return Err(Error::TruncatedFile {
+                expected_bytes: self.file_length as u64,
+                actual_bytes: (content_start + content.len()) as u64,
+            });
+        }
+        self.current_pos = content_start + record_size;
+        let code = (&content[..4]).read_i32::<LittleEndian>()?;
+        Ok((hdr.record_number, ShapeType::from(code), content))
+    }
+}
+
+impl<T: Read> Iterator for RawRecordIterator<T> {
+    type Item = Result<RawRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.initial_error.take() {
+            return Some(Err(error));
+        }
+        if self.failed || self.current_pos >= self.file_length {
+            return None;
+        }
+        let result = self.next_record();
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+impl<T: Read> FusedIterator for RawRecordIterator<T> {}
+
+/// Iterator over views of the shapes of a .shp file held in memory,
+/// created by [iter_shape_refs](struct.Reader.html#method.iter_shape_refs)
+///
+/// A record that can not be read is handled following the
+/// [ErrorPolicy](enum.ErrorPolicy.html) of the reader, like the
+/// [ShapeIterator](struct.ShapeIterator.html) does (the skipped records are not recorded).
+/// The iteration always ends after an error if the record does not fit in the bytes.
+pub struct ShapeRefIterator<'a> {
+    bytes: &'a [u8],
+    current_pos: usize,
+    file_length: usize,
+    parsing: RecordParsing,
+    error_policy: ErrorPolicy,
+    /// Whether the iteration ended after an error
+    failed: bool,
+}
+
+impl<'a> Iterator for ShapeRefIterator<'a> {
+    type Item = Result<ShapeRef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.next_record()? {
+                Err(ref e)
+                    if self.error_policy == ErrorPolicy::SkipMalformed
+                        && !self.failed
+                        && is_malformed_record(e) => {}
+                Err(e) => {
+                    if !continues_after(self.error_policy, &e) {
+                        self.failed = true;
+                    }
+                    return Some(Err(e));
+                }
+                result => return Some(result),
+            }
+        }
+    }
+}
+
+impl<'a> ShapeRefIterator<'a> {
+    /// Reads the view of the next record, `None` at the end of the file
+    fn next_record(&mut self) -> Option<Result<ShapeRef<'a>, Error>> {
+        if self.failed || self.current_pos >= self.file_length {
+            return None;
+        }
+        let content_start = self.current_pos + record::RecordHeader::SIZE;
+        let hdr = self
+            .bytes
+            .get(self.current_pos..content_start)
+            .ok_or(Error::TruncatedFile {
+                expected_bytes: self.file_length as u64,
+                actual_bytes: self.bytes.len() as u64,
+            })
+            .and_then(|mut hdr| record::RecordHeader::read_from(&mut hdr))
+            .and_then(|hdr| {
+                // The content has at least the shape type
+                if hdr.record_size < 2 {
+                    return Err(Error::InvalidShapeRecordSize);
+                }
+                self.parsing.check_size(&hdr)?;
+                end_of_record(self.current_pos as u64, &hdr, self.file_length as u64)
+            });
+        let content_end = match hdr {
+            Ok(record_end) => record_end as usize,
+            Err(e) => {
+                self.failed = true;
+                return Some(Err(e));
+            }
+        };
+        let content = match self.bytes.get(content_start..content_end) {
+            Some(content) => content,
+            None => {
+                self.failed = true;
+                return Some(Err(Error::TruncatedFile {
+                    expected_bytes: self.file_length as u64,
+                    actual_bytes: self.bytes.len() as u64,
+                }));
+            }
+        };
+        self.current_pos = content_end;
+        Some(read_shape_ref(content))
+    }
+}
+
+impl<'a> FusedIterator for ShapeRefIterator<'a> {}
+
+/// Iterator over the shapes of a .shp file which bounding box
+/// intersects the requested bounding box
+///
+/// NullShapes never intersects.
+pub struct BBoxShapeIterator<T: Read, S: ReadableShape> {
+    shape_iter: ShapeIterator<T, S>,
+    bbox: BBox,
+    /// Test of the shapes which bbox intersects, set by `exact`
+    exact_test: Option<fn(&S, &BBox) -> bool>,
+}
+
+impl<T: Read, S: ReadableShape + EsriShape> BBoxShapeIterator<T, S> {
+    /// Sets whether the shapes themselves must intersect the bbox, not only their
+    /// bounding box (see [IntersectsBBox](../record/part_filter/trait.IntersectsBBox.html)).
+    ///
+    /// Off by default: a diagonal polyline is then returned for a bbox near a corner
+    /// of its bounding box, even if it does not cross it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp").unwrap();
+    /// let bbox = BBox::new(0.0, 0.0, 1.0, 1.0);
+    /// let polygons = reader
+    ///     .iter_shapes_in_bbox_as::<shapefile::Polygon>(bbox)
+    ///     .exact(true)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// ```
+    pub fn exact(mut self, exact: bool) -> Self
+    where
+        S: IntersectsBBox,
+    {
+        self.exact_test = if exact {
+            Some(S::intersects_bbox)
+        } else {
+            None
+        };
+        self
+    }
+
+    /// Makes the iterator only keep, within each shape, the parts that intersect the bbox.
+    ///
+    /// Shapes for which some parts were removed are flagged as being partial.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// let bbox = BBox::new(0.0, 0.0, 3.0, 3.0);
+    /// for result in reader.iter_shapes_in_bbox_as::<shapefile::Polyline>(bbox).retain_intersecting_parts() {
+    ///     let partial = result.unwrap();
+    ///     assert_eq!(partial.is_partial, false);
+    /// }
+    /// ```
+    pub fn retain_intersecting_parts(self) -> PartsInBBoxIterator<T, S>
+    where
+        S: RetainPartsInBBox,
+    {
+        PartsInBBoxIterator { bbox_iter: self }
+    }
+}
+
+impl<T: Read, S: ReadableShape + EsriShape> Iterator for BBoxShapeIterator<T, S> {
+    type Item = Result<S, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.shape_iter.next()? {
+                Err(e) => return Some(Err(e)),
+                Ok(shape) => {
+                    let is_exact_match = |shape: &S| match self.exact_test {
+                        Some(intersects_bbox) => intersects_bbox(shape, &self.bbox),
+                        None => true,
+                    };
+                    if shape.shape_type() != ShapeType::NullShape
+                        && shape.bbox().intersects(&self.bbox)
+                        && is_exact_match(&shape)
+                    {
+                        return Some(Ok(shape));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T: Read, S: ReadableShape + EsriShape> FusedIterator for BBoxShapeIterator<T, S> {}
+
+/// Iterator over the shapes which bounding box intersects the requested bounding box,
+/// only keeping the parts of the shapes that intersect the bounding box.
+pub struct PartsInBBoxIterator<T: Read, S: ReadableShape> {
+    bbox_iter: BBoxShapeIterator<T, S>,
+}
+
+impl<T, S> Iterator for PartsInBBoxIterator<T, S>
+where
+    T: Read,
+    S: ReadableShape + EsriShape + RetainPartsInBBox,
+{
+    type Item = Result<PartialShape<S>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.bbox_iter.next()? {
+                Err(e) => return Some(Err(e)),
+                Ok(shape) => {
+                    if let Some(partial) = shape.retain_parts_in_bbox(&self.bbox_iter.bbox) {
+                        return Some(Ok(partial));
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<T, S> FusedIterator for PartsInBBoxIterator<T, S>
+where
+    T: Read,
+    S: ReadableShape + EsriShape + RetainPartsInBBox,
+{
+}
+
+/// Iterator over the shapes of a .shp file paired with the records of the .dbf
+///
+/// If the .shp and the .dbf do not have the same number of records,
+/// the last item is an `Error::RecordCountMismatch`.
+///
+/// The iteration ends after an error, like the [ShapeIterator](struct.ShapeIterator.html).
+/// When it continues (depending on the [ErrorPolicy](enum.ErrorPolicy.html)),
+/// the .dbf record of a shape that can not be read is skipped with it,
+/// so that the shapes stay paired with their records.
+///
+/// Each call to `next` reads one record of the .shp and the .dbf record paired with it
+/// (and one of each for every record skipped), nothing is read ahead:
+/// besides the shape and record returned, only the buffers of the sources are held,
+/// whose capacity is set by [set_read_buffer_size](struct.Reader.html#method.set_read_buffer_size)
+/// for the files opened by the reader.
+/// The exception is a .dbf with fewer records than the .shp: the remaining shapes
+/// are then read (one at a time, without being kept) to give their count in the error.
 pub struct ShapeRecordIterator<T: Read, S: ReadableShape> {
     shape_iter: ShapeIterator<T, S>,
     dbf_reader: dbase::Reader<T>,
+    /// Number of records of the .dbf, given by its header
+    num_records: usize,
+    num_read: usize,
+    /// Whether the iteration ended, after an error or a count mismatch
+    finished: bool,
+    /// Error reading the .dbf record of a shape that could not be read,
+    /// returned after the error of the shape
+    dbf_error: Option<Error>,
 }
 
 impl<T: Read, S: ReadableShape> Iterator for ShapeRecordIterator<T, S> {
     type Item = Result<(S, dbase::Record), Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.dbf_error.take() {
+            return Some(Err(error));
+        }
+        if self.finished {
+            return None;
+        }
+        let num_skipped = self.shape_iter.skipped_records.len();
+        let shape = self.shape_iter.next();
+        for _ in num_skipped..self.shape_iter.skipped_records.len() {
+            if let Err(e) = self.next_record() {
+                return Some(Err(e));
+            }
+        }
+        let shape = match shape {
+            Some(Err(e)) => {
+                // The record of the shape is not returned, but still consumed
+                if let Err(dbf_error) = self.next_record() {
+                    self.dbf_error = Some(dbf_error);
+                }
+                if self.shape_iter.failed {
+                    self.finished = true;
+                }
+                return Some(Err(e));
+            }
+            Some(Ok(shp)) => shp,
+            None if self.num_read < self.num_records => {
+                self.finished = true;
+                return Some(Err(Error::RecordCountMismatch {
+                    num_shapes: self.num_read,
+                    num_records: self.num_records,
+                }));
+            }
+            None => return None,
+        };
+        Some(self.next_record().map(|record| (shape, record)))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.shape_iter.size_hint()
+    }
+}
+
+impl<T: Read, S: ReadableShape> ShapeRecordIterator<T, S> {
+    /// Returns the record numbers of the records skipped so far,
+    /// see [ShapeIterator::skipped_records](struct.ShapeIterator.html#method.skipped_records)
+    pub fn skipped_records(&self) -> &[i32] {
+        self.shape_iter.skipped_records()
+    }
+
+    /// Reads the .dbf record of the shape that was just read
+    fn next_record(&mut self) -> Result<dbase::Record, Error> {
+        self.num_read += 1;
+        match self.dbf_reader.next() {
+            Some(Ok(record)) => Ok(record),
+            Some(Err(e)) => {
+                self.finished = true;
+                Err(Error::DbaseError(e))
+            }
+            None => {
+                self.finished = true;
+                // The shapes skipped while counting the remaining ones are shapes too
+                let num_skipped = self.shape_iter.skipped_records.len();
+                let num_remaining = self.shape_iter.by_ref().count()
+                    + (self.shape_iter.skipped_records.len() - num_skipped);
+                Err(Error::RecordCountMismatch {
+                    num_shapes: self.num_read + num_remaining,
+                    num_records: self.num_records,
+                })
+            }
+        }
+    }
+}
+
+impl<T: Read, S: ReadableShape> FusedIterator for ShapeRecordIterator<T, S> {}
+
+/// Iterator over the shapes of a .shp file converted to another type as they are read,
+/// created by [iter_as_converted](struct.Reader.html#method.iter_as_converted)
+pub struct ConvertedShapeIterator<T: Read, C> {
+    shape_iter: ShapeIterator<T, Shape>,
+    /// Error of the conversion of the shape type of the header,
+    /// returned for the first record instead of reading it
+    header_mismatch: Option<Error>,
+    _converted: std::marker::PhantomData<C>,
+}
+
+impl<T: Read, C> Iterator for ConvertedShapeIterator<T, C>
+where
+    C: TryFrom<Shape>,
+    Error: From<<C as TryFrom<Shape>>::Error>,
+{
+    type Item = Result<C, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(error) = self.header_mismatch.take() {
+            if !self.shape_iter.has_ended() {
+                self.shape_iter.failed = true;
+                let read_error = ReadError {
+                    record_number: 1,
+                    offset: self.shape_iter.current_pos as u64,
+                    error,
+                };
+                return Some(Err(Error::RecordError(Box::new(read_error))));
+            }
+        }
         let shape = match self.shape_iter.next()? {
             Err(e) => return Some(Err(e)),
-            Ok(shp) => shp,
+            Ok(shape) => shape,
         };
+        let result = C::try_from(shape)
+            .map_err(|e| self.shape_iter.record_error(Error::from(e)));
+        if let Err(ref e) = result {
+            self.shape_iter.end_after(e);
+        }
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.header_mismatch.is_some() {
+            return (0, Some(1));
+        }
+        self.shape_iter.size_hint()
+    }
+}
+
+impl<T: Read, C> FusedIterator for ConvertedShapeIterator<T, C>
+where
+    C: TryFrom<Shape>,
+    Error: From<<C as TryFrom<Shape>>::Error>,
+{
+}
+
+/// Iterator over the shapes of a .shp file of a concrete type, the NullShapes being skipped,
+/// created by [iter_filtered](struct.Reader.html#method.iter_filtered)
+pub struct FilteredShapeIterator<T: Read, S> {
+    shape_iter: ShapeIterator<T, Shape>,
+    _shape: std::marker::PhantomData<S>,
+}
+
+impl<T: Read, S> Iterator for FilteredShapeIterator<T, S>
+where
+    S: HasShapeType + TryFrom<Shape, Error = Error>,
+{
+    type Item = Result<S, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let shape = match self.shape_iter.next()? {
+                Err(e) => return Some(Err(e)),
+                Ok(shape) => shape,
+            };
+            match shape.try_into_concrete::<S>() {
+                Ok(Some(shape)) => return Some(Ok(shape)),
+                Ok(None) => {}
+                Err(e) => {
+                    let error = self.shape_iter.record_error(e);
+                    self.shape_iter.end_after(&error);
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+impl<T: Read, S> FusedIterator for FilteredShapeIterator<T, S>
+where
+    S: HasShapeType + TryFrom<Shape, Error = Error>,
+{
+}
+
+/// The beginning of a record, given to the predicate of
+/// [iter_matching](struct.Reader.html#method.iter_matching)
+/// to decide whether the record is read
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RecordPreview {
+    /// The record number written in the record header
+    pub record_number: i32,
+    pub shape_type: ShapeType,
+    /// The bbox of the record, the point itself for points
+    /// and all zeros for NullShapes
+    pub bbox: BBox,
+    pub num_points: usize,
+    /// The number of parts, 0 for points, multipoints and NullShapes
+    pub num_parts: usize,
+}
+
+impl RecordPreview {
+    /// Maximum number of bytes read by `read_from`
+    const MAX_SIZE: usize = 44;
 
-        let record = match self.dbf_reader.next()? {
-            Err(e) => return Some(Err(Error::DbaseError(e))),
-            Ok(rcd) => rcd,
+    /// Reads the preview from the beginning of the record content,
+    /// at most 44 bytes are read: type, bbox, number of parts and points
+    fn read_from<T: Read>(source: &mut T, record_number: i32) -> Result<Self, Error> {
+        use byteorder::{LittleEndian, ReadBytesExt};
+
+        let shape_type = ShapeType::read_from(source)?;
+        let mut preview = RecordPreview {
+            record_number,
+            shape_type,
+            bbox: BBox::new(0.0, 0.0, 0.0, 0.0),
+            num_points: 0,
+            num_parts: 0,
         };
+        match shape_type.geometry_class() {
+            GeometryClass::Null => {}
+            GeometryClass::Point => {
+                let x = source.read_f64::<LittleEndian>()?;
+                let y = source.read_f64::<LittleEndian>()?;
+                preview.bbox = BBox::new(x, y, x, y);
+                preview.num_points = 1;
+            }
+            _ => {
+                preview.bbox = BBox::read_from(&mut *source)?;
+                if shape_type.is_multipart() {
+                    preview.num_parts = source.read_i32::<LittleEndian>()?.max(0) as usize;
+                }
+                preview.num_points = source.read_i32::<LittleEndian>()?.max(0) as usize;
+            }
+        }
+        Ok(preview)
+    }
+}
 
-        Some(Ok((shape, record)))
+/// Function seeking in the source of a reader, only known for sources that implement `Seek`
+type SeekFn<T> = fn(&mut T, SeekFrom) -> std::io::Result<u64>;
+
+/// Iterator over the shapes of the records accepted by a predicate,
+/// created by [iter_matching_as](struct.Reader.html#method.iter_matching_as)
+///
+/// The iteration stops after the first error.
+pub struct MatchingShapeIterator<T: Read, S: ReadableShape, F> {
+    _shape: std::marker::PhantomData<S>,
+    source: T,
+    seek_fn: Option<SeekFn<T>>,
+    current_pos: u64,
+    file_length: u64,
+    parsing: RecordParsing,
+    predicate: F,
+    /// Error of the seek to the first record, returned as the first item
+    initial_error: Option<Error>,
+}
+
+impl<T, S, F> MatchingShapeIterator<T, S, F>
+where
+    T: Read,
+    S: ReadableShape,
+    F: FnMut(&RecordPreview) -> bool,
+{
+    /// Reads the next record accepted by the predicate, `None` if there are no more records
+    fn read_next_match(&mut self) -> Result<Option<S>, Error> {
+        if let Some(error) = self.initial_error.take() {
+            return Err(error);
+        }
+        while self.current_pos < self.file_length {
+            let record_start = self.current_pos;
+            let hdr = read_record_header(&mut self.source, record_start, self.file_length)?;
+            self.parsing.check_size(&hdr)?;
+            let record_end = end_of_record(record_start, &hdr, self.file_length)?;
+            let content_size = hdr.record_size as u64 * 2;
+            // The beginning of the content is kept to read the shape without going back
+            let mut beginning = Vec::<u8>::with_capacity(RecordPreview::MAX_SIZE);
+            let beginning_size = content_size.min(RecordPreview::MAX_SIZE as u64);
+            (&mut self.source)
+                .take(beginning_size)
+                .read_to_end(&mut beginning)?;
+            if (beginning.len() as u64) < beginning_size {
+                return Err(Error::TruncatedFile {
+                    expected_bytes: self.file_length,
+                    actual_bytes: record_start
+                        + record::RecordHeader::SIZE as u64
+                        + beginning.len() as u64,
+                });
+            }
+            let preview = RecordPreview::read_from(&mut beginning.as_slice(), hdr.record_number)?;
+            self.current_pos = record_end;
+            let mut remaining = (&mut self.source).take(content_size - beginning.len() as u64);
+            if (self.predicate)(&preview) {
+                let mut content = beginning.as_slice().chain(remaining);
+                let (file_length, parsing) = (self.file_length, self.parsing);
+                let shape: S = read_content_within(
+                    &mut content,
+                    record_start,
+                    &hdr,
+                    file_length,
+                    parsing,
+                    None,
+                )?;
+                return Ok(Some(shape));
+            }
+            match self.seek_fn {
+                Some(seek) => {
+                    seek(&mut self.source, SeekFrom::Start(record_end))?;
+                }
+                None => {
+                    std::io::copy(&mut remaining, &mut std::io::sink())?;
+                }
+            }
+        }
+        Ok(None)
     }
 }
 
-impl<T: Read, S: ReadableShape> FusedIterator for ShapeRecordIterator<T, S> {}
+impl<T, S, F> Iterator for MatchingShapeIterator<T, S, F>
+where
+    T: Read,
+    S: ReadableShape,
+    F: FnMut(&RecordPreview) -> bool,
+{
+    type Item = Result<S, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.read_next_match() {
+            Ok(shape) => shape.map(Ok),
+            Err(e) => {
+                self.current_pos = self.file_length;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<T, S, F> FusedIterator for MatchingShapeIterator<T, S, F>
+where
+    T: Read,
+    S: ReadableShape,
+    F: FnMut(&RecordPreview) -> bool,
+{
+}
+
+/// Iterator over the bboxes of the records, which points are not read,
+/// created by [iter_record_bboxes](struct.Reader.html#method.iter_record_bboxes)
+///
+/// The iteration stops after the first error.
+pub struct RecordBBoxIterator<T: Read> {
+    source: T,
+    seek_fn: Option<SeekFn<T>>,
+    current_pos: u64,
+    file_length: u64,
+    /// 0-based index of the next record, NullShapes included
+    index: usize,
+    /// Error of the seek to the first record, returned as the first item
+    initial_error: Option<Error>,
+}
+
+impl<T: Read> RecordBBoxIterator<T> {
+    /// Reads the bbox of the next record that is not a NullShape, with the index of the record
+    /// and its number, `None` if there are no more records
+    fn read_next_bbox(&mut self) -> Result<Option<(usize, i32, BBox)>, Error> {
+        if let Some(error) = self.initial_error.take() {
+            return Err(error);
+        }
+        while self.current_pos < self.file_length {
+            let record_start = self.current_pos;
+            let index = self.index;
+            self.index += 1;
+            let hdr = read_record_header(&mut self.source, record_start, self.file_length)?;
+            let record_end = end_of_record(record_start, &hdr, self.file_length)?;
+            let content_size = hdr.record_size as u64 * 2;
+            let content_start = record_start + record::RecordHeader::SIZE as u64;
+            let (bbox, beginning_size) =
+                read_record_bbox(&mut self.source, content_start, content_size, self.file_length)?;
+            self.current_pos = record_end;
+            let remaining = content_size - beginning_size;
+            match self.seek_fn {
+                Some(seek) => {
+                    seek(&mut self.source, SeekFrom::Start(record_end))?;
+                }
+                None => {
+                    let mut rest = (&mut self.source).take(remaining);
+                    let num_skipped = std::io::copy(&mut rest, &mut std::io::sink())?;
+                    if num_skipped < remaining {
+                        return Err(Error::TruncatedFile {
+                            expected_bytes: self.file_length,
+                            actual_bytes: content_start + beginning_size + num_skipped,
+                        });
+                    }
+                }
+            }
+            if let Some(bbox) = bbox {
+                return Ok(Some((index, hdr.record_number, bbox)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the next bbox with the 0-based index of its record (as used by
+    /// [read_nth_shape](struct.Reader.html#method.read_nth_shape)) and its number
+    fn next_indexed(&mut self) -> Option<Result<(usize, i32, BBox), Error>> {
+        match self.read_next_bbox() {
+            Ok(bbox) => bbox.map(Ok),
+            Err(e) => {
+                self.current_pos = self.file_length;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl<T: Read> Iterator for RecordBBoxIterator<T> {
+    type Item = Result<(i32, BBox), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_indexed()
+            .map(|result| result.map(|(_, record_number, bbox)| (record_number, bbox)))
+    }
+}
+
+impl<T: Read> FusedIterator for RecordBBoxIterator<T> {}
+
+/// Iterator over batches of the items of another iterator,
+/// created by [read_chunks](struct.Reader.html#method.read_chunks)
+/// and its variants.
+///
+/// Each chunk has `chunk_size` items, except the last one which may be shorter.
+/// When an item is an error, the chunk that contains it is returned as this error
+/// (the items of the chunk read before it are dropped), and the iteration stops.
+pub struct ChunkIterator<I> {
+    iter: I,
+    chunk_size: usize,
+    is_done: bool,
+}
+
+impl<I, Item> Iterator for ChunkIterator<I>
+where
+    I: Iterator<Item = Result<Item, Error>>,
+{
+    type Item = Result<Vec<Item>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_done {
+            return None;
+        }
+        let capacity = match self.iter.size_hint() {
+            (_, Some(upper)) => upper.min(self.chunk_size),
+            (_, None) => self.chunk_size,
+        };
+        let mut chunk = Vec::<Item>::with_capacity(capacity);
+        while chunk.len() < self.chunk_size {
+            match self.iter.next() {
+                Some(Ok(item)) => chunk.push(item),
+                Some(Err(e)) => {
+                    self.is_done = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.is_done = true;
+                    break;
+                }
+            }
+        }
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(Ok(chunk))
+        }
+    }
+}
+
+impl<I, Item> FusedIterator for ChunkIterator<I> where I: Iterator<Item = Result<Item, Error>> {}
+
+/// Records selected by [read_selection_as](struct.Reader.html#method.read_selection_as)
+struct Selection {
+    /// The unique indices, sorted, with the offset of their record
+    sorted: Vec<(usize, u64)>,
+    /// For each index given (without duplicates), its position in `sorted`
+    order: Vec<usize>,
+}
 
 //TODO Make it possible for the dbf source to be of a different dtype ?
 /// struct that reads the content of a shapefile
 pub struct Reader<T: Read> {
     source: T,
     header: header::Header,
-    shapes_index: Option<Vec<ShapeIndex>>,
+    index_source: Option<T>,
+    index_table: Option<IndexTable>,
     dbf_reader: Option<dbase::Reader<T>>,
+    progress: Option<ProgressReporter>,
+    error_policy: ErrorPolicy,
+    parsing: RecordParsing,
+    shp_path: Option<PathBuf>,
+    shx_path: Option<PathBuf>,
+    dbf_path: Option<PathBuf>,
+    /// Only known if the reader was created with `from_seekable` or if a method
+    /// seeking in the source was called, the source may then not be at the first record
+    seek_fn: Option<SeekFn<T>>,
 }
 
-impl<T: Read> Reader<T> {
-    /// Creates a new Reader from a source that implements the `Read` trait
+impl<T: Read> Reader<T> {
+    /// Creates a new Reader from a source that implements the `Read` trait
+    ///
+    /// The Shapefile header is read upon creation (but no reading of the Shapes is done)
+    ///
+    /// The source is only read forward, so it can be a stream such as stdin or a socket.
+    /// For sources that implement `Seek`, [from_seekable](#method.from_seekable) allows
+    /// skipping records without reading them.
+    ///
+    /// The source is used as is: the values of the records are read one at a time,
+    /// so an unbuffered source such as a `File` should be wrapped in a `BufReader`
+    /// ([from_path](#method.from_path) does it), a source that already is one is not
+    /// buffered again.
+    ///
+    /// # Errors
+    ///
+    /// Will forward any `std::io::Error`
+    ///
+    /// Will also return an error if the data is not a shapefile (Wrong file code)
+    ///
+    /// Will also return an error if the shapetype read from the input source is invalid
+    ///
+    /// An empty source is an `Error::EmptyFile`, a source that ends within the header
+    /// is an `Error::TruncatedFile`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// let file = File::open("tests/data/line.shp").unwrap();
+    /// let reader = shapefile::Reader::new(file).unwrap();
+    /// ```
+    pub fn new(mut source: T) -> Result<Reader<T>, Error> {
+        let mut header_bytes = [0u8; HEADER_SIZE as usize];
+        match read_up_to(&mut source, &mut header_bytes)? {
+            0 => return Err(Error::EmptyFile),
+            num_read if num_read < header_bytes.len() => {
+                return Err(Error::TruncatedFile {
+                    expected_bytes: HEADER_SIZE as u64,
+                    actual_bytes: num_read as u64,
+                })
+            }
+            _ => {}
+        }
+        let header = header::Header::read_from(&mut &header_bytes[..])?;
+
+        Ok(Reader {
+            source,
+            header,
+            index_source: None,
+            index_table: None,
+            dbf_reader: None,
+            progress: None,
+            error_policy: ErrorPolicy::Fail,
+            parsing: RecordParsing::default(),
+            shp_path: None,
+            shx_path: None,
+            dbf_path: None,
+            seek_fn: None,
+        })
+    }
+
+    /// Returns a non-mutable reference to the header read
+    ///
+    /// The header is read (and its file code checked) when the reader is created,
+    /// so it can be inspected before deciding how to read the shapes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/pointz.shp").unwrap();
+    /// let header = reader.header();
+    /// assert_eq!(header.shape_type, shapefile::ShapeType::PointZ);
+    /// ```
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{Polygon, PolygonZ, ShapeType};
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp")?;
+    /// let bbox = reader.header().bbox();
+    /// println!("[{}, {}] x [{}, {}]", bbox.xmin, bbox.xmax, bbox.ymin, bbox.ymax);
+    /// match reader.header().shape_type {
+    ///     ShapeType::PolygonZ => println!("{}", reader.read_as::<PolygonZ>()?.len()),
+    ///     _ => println!("{}", reader.read_as::<Polygon>()?.len()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn header(&self) -> &header::Header {
+        &self.header
+    }
+
+    /// Makes the iterators created from this reader call the `callback`
+    /// with the progress of the read after each record.
+    ///
+    /// The total number of records is only known if the reader has an index file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")
+    ///     .unwrap()
+    ///     .with_progress(|progress| {
+    ///         println!("{} / {:?} records", progress.records_done, progress.records_total)
+    ///     });
+    /// let shapes = reader.read().unwrap();
+    /// ```
+    pub fn with_progress<F: FnMut(Progress) + Send + 'static>(self, callback: F) -> Self {
+        self.with_progress_every(1, callback)
+    }
+
+    /// Same as [with_progress](#method.with_progress), but the `callback`
+    /// is only called every `num_records` records (and after the last record)
+    pub fn with_progress_every<F: FnMut(Progress) + Send + 'static>(
+        mut self,
+        num_records: usize,
+        callback: F,
+    ) -> Self {
+        self.progress = Some(ProgressReporter::new(num_records, callback));
+        self
+    }
+
+    /// Sets the `callback` the iterators created from this reader call
+    /// with the progress of the read after each record,
+    /// like [with_progress](#method.with_progress) does.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// let records_read = Arc::new(AtomicUsize::new(0));
+    /// let counter = records_read.clone();
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// reader.set_progress_callback(Box::new(move |progress| {
+    ///     counter.store(progress.records_done, Ordering::SeqCst);
+    /// }));
+    /// let shapes = reader.read().unwrap();
+    /// assert_eq!(records_read.load(Ordering::SeqCst), shapes.len());
+    /// ```
+    pub fn set_progress_callback(&mut self, callback: Box<dyn FnMut(Progress) + Send>) {
+        self.progress = Some(ProgressReporter::new(1, callback));
+    }
+
+    /// Sets whether the rings of the polygons (Polygon, PolygonM, PolygonZ) read are reordered
+    /// so that each outer ring is immediately followed by its holes
+    /// (see [normalize_rings](../record/poly/struct.GenericPolygon.html#method.normalize_rings)).
+    ///
+    /// Off by default, the rings are then in the order of the file.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp")?
+    ///     .normalize_polygons(true);
+    /// let polygons = reader.read_as::<shapefile::Polygon>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn normalize_polygons(mut self, normalize: bool) -> Self {
+        self.parsing.normalize_polygons = normalize;
+        self
+    }
+
+    /// Sets what the iterators over the shapes do with the records
+    /// that can not be read as shapes.
+    ///
+    /// With [SkipMalformed](enum.ErrorPolicy.html#variant.SkipMalformed),
+    /// such records are skipped and the reading continues with the next record,
+    /// the numbers of the skipped records are then given by
+    /// [skipped_records](struct.ShapeIterator.html#method.skipped_records).
+    /// When iterating over shapes and records, the .dbf records of the skipped
+    /// shapes are skipped too.
+    ///
+    /// With [Continue](enum.ErrorPolicy.html#variant.Continue), the errors of such records
+    /// are returned and the reading continues with the next record.
+    ///
+    /// [ErrorPolicy::Fail](enum.ErrorPolicy.html#variant.Fail) by default:
+    /// the iterators end after the first error they return.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::reader::ErrorPolicy;
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// reader.set_error_policy(ErrorPolicy::SkipMalformed);
+    /// let mut shapes = reader.iter_shapes_as::<shapefile::Polyline>();
+    /// let polylines = shapes.by_ref().collect::<Result<Vec<_>, _>>()?;
+    /// assert!(shapes.skipped_records().is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    /// Sets whether the Multipatch records are converted when the shapes are read
+    /// as a type a multipatch can be converted to, that is `PolygonZ`
+    /// (see the `TryFrom<Multipatch>` implementation of
+    /// [PolygonZ](../record/poly/type.PolygonZ.html) for how the patches give rings).
+    ///
+    /// Off by default, reading a Multipatch record as a PolygonZ then
+    /// returns an `Error::MismatchShapeType`.
+    /// A record which multipatch can not be converted gives the error of the conversion.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?
+    ///     .allow_multipatch_conversion(true);
+    /// let polygons = reader.read_as::<shapefile::PolygonZ>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn allow_multipatch_conversion(mut self, allow: bool) -> Self {
+        self.parsing.multipatch_conversion = allow;
+        self
+    }
+
+    /// Sets whether the records whose content length is larger than the size
+    /// of the shape they hold are read.
+    ///
+    /// Some writers give records content lengths larger than their shapes.
+    /// When allowed, the shape is read from the numbers of parts and points it gives,
+    /// and the remaining bytes of the record are skipped.
+    /// A content length smaller than the size of the shape is still an error.
+    ///
+    /// For the shapes whose M values are optional, a record of the exact size of the shape
+    /// without M values is read as such. When the record is as large as the shape with M values
+    /// or larger, the bytes after the Z values are the M values only if they are not all zeros
+    /// and the measures are within the M range: otherwise they are padding, and the measures
+    /// are NO_DATA.
+    ///
+    /// Off by default, such records then give an `Error::InvalidShapeRecordSize`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?
+    ///     .allow_padded_records(true);
+    /// let polylines = reader.read_as::<shapefile::Polyline>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn allow_padded_records(mut self, allow: bool) -> Self {
+        self.parsing.size_check = if allow {
+            RecordSizeCheck::AtLeast
+        } else {
+            RecordSizeCheck::Exact
+        };
+        self
+    }
+
+    /// Sets the largest content length, in bytes, of the records read,
+    /// `None` to read records of any length.
+    ///
+    /// A record whose header gives a larger content length is an `Error::RecordTooLarge`,
+    /// returned before reading its content: a corrupted header can not make the reader
+    /// read gigabytes for a single record. The iteration ends after this error,
+    /// whatever the [error policy](#method.set_error_policy).
+    ///
+    /// The limit is [DEFAULT_MAX_RECORD_SIZE](constant.DEFAULT_MAX_RECORD_SIZE.html)
+    /// (256 MiB) by default, records of millions of points (e.g. detailed coastlines)
+    /// may need a larger one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp")?
+    ///     .max_record_size_bytes(None);
+    /// let polygons = reader.read_as::<shapefile::Polygon>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn max_record_size_bytes(mut self, limit: Option<u64>) -> Self {
+        self.parsing.max_record_size = limit;
+        self
+    }
+
+    /// Sets whether the records of a shape type that is not one of the specification
+    /// (e.g. the extension of a vendor) are read as
+    /// [Shape::Unknown](../record/enum.Shape.html#variant.Unknown).
     ///
-    /// The Shapefile header is read upon creation (but no reading of the Shapes is done)
+    /// Their content is kept as it is, and written back unchanged by the
+    /// [Writer](../writer/struct.Writer.html): tools that copy or filter the records
+    /// of a file do not lose them. Only reading as `Shape` (or `Option<Shape>`) keeps them,
+    /// reading as a concrete shape type still fails on them.
+    /// The shape type of the file header must still be a known one.
     ///
-    /// # Errors
+    /// Off by default, such records then give an `Error::InvalidShapeType`.
     ///
-    /// Will forward any `std::io::Error`
+    /// # Example
     ///
-    /// Will also return an error if the data is not a shapefile (Wrong file code)
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?
+    ///     .keep_unknown_shapes(true);
+    /// for shape in reader.read()? {
+    ///     if let shapefile::Shape::Unknown { shape_type, .. } = shape {
+    ///         println!("Record of the unknown shape type {}", shape_type);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn keep_unknown_shapes(mut self, keep: bool) -> Self {
+        self.parsing.keep_unknown_shapes = keep;
+        self
+    }
+
+    /// Makes the records of the shape type given by the header be read as
+    /// records of the `shape_type`, which must have the same layout.
     ///
-    /// Will also return an error if the shapetype read from the input source is invalid
+    /// Polylines and polygons have the same layout (in each of the
+    /// plain, M and Z variants), so files written with the wrong one of the two
+    /// can be read as intended. Only the parsing of the records changes,
+    /// the header still gives the shape type of the file.
+    ///
+    /// # Errors
+    ///
+    /// `Error::MismatchShapeType` if the shape type of the file can not be read
+    /// as the `shape_type`.
     ///
     /// # Example
     ///
     /// ```
-    /// use std::fs::File;
-    /// let file = File::open("tests/data/line.shp").unwrap();
-    /// let reader = shapefile::Reader::new(file).unwrap();
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::ShapeType;
+    /// let polylines = shapefile::Reader::from_path("tests/data/polygon.shp")?
+    ///     .reinterpret_as(ShapeType::Polyline)?
+    ///     .read_as::<shapefile::Polyline>()?;
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn new(mut source: T) -> Result<Reader<T>, Error> {
-        let header = header::Header::read_from(&mut source)?;
-
-        Ok(Reader {
-            source,
-            header,
-            shapes_index: None,
-            dbf_reader: None,
-        })
+    pub fn reinterpret_as(mut self, shape_type: ShapeType) -> Result<Self, Error> {
+        let file_type = self.header.shape_type;
+        let same_layout = |a: ShapeType, b: ShapeType| {
+            a == b
+                || (a.is_multipart()
+                    && b.is_multipart()
+                    && a.has_z() == b.has_z()
+                    && a.has_m() == b.has_m()
+                    && a != ShapeType::Multipatch
+                    && b != ShapeType::Multipatch)
+        };
+        if !same_layout(file_type, shape_type) {
+            return Err(Error::MismatchShapeType {
+                requested: shape_type,
+                actual: file_type,
+            });
+        }
+        self.parsing.reinterpretation = if file_type == shape_type {
+            None
+        } else {
+            Some(Reinterpretation {
+                from: file_type,
+                to: shape_type,
+            })
+        };
+        Ok(self)
     }
 
-    /// Returns a non-mutable reference to the header read
+    /// Returns the paths of the sidecar files (*.prj*, *.cpg*, *.shp.xml*...)
+    /// found next to the shapefile, sorted by name
     ///
-    /// # Examples
+    /// Only readers created with [from_path](#method.from_path) know where their
+    /// shapefile is, other readers return an empty list.
+    /// See [sidecar_paths_of](../sidecar/fn.sidecar_paths_of.html) for which files are listed.
+    ///
+    /// # Example
     ///
     /// ```
-    /// let reader = shapefile::Reader::from_path("tests/data/pointz.shp").unwrap();
-    /// let header = reader.header();
-    /// assert_eq!(header.shape_type, shapefile::ShapeType::PointZ);
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// for path in reader.sidecar_paths()? {
+    ///     println!("{}", path.display());
+    /// }
+    /// # Ok(())
+    /// # }
     /// ```
-    pub fn header(&self) -> &header::Header {
-        &self.header
+    pub fn sidecar_paths(&self) -> Result<Vec<PathBuf>, Error> {
+        match self.shp_path {
+            Some(ref shp_path) => sidecar_paths_of(shp_path),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Returns the path of the .shp, if the reader was created
+    /// with [from_path](#method.from_path)
+    pub fn shp_path(&self) -> Option<&Path> {
+        self.shp_path.as_deref()
+    }
+
+    /// Returns the path of the .shx found by [from_path](#method.from_path), if any
+    pub fn shx_path(&self) -> Option<&Path> {
+        self.shx_path.as_deref()
+    }
+
+    /// Returns the path of the .dbf found by [from_path](#method.from_path), if any
+    pub fn dbf_path(&self) -> Option<&Path> {
+        self.dbf_path.as_deref()
     }
 
     /// Reads all the shape as shape of a certain type.
@@ -213,6 +1997,17 @@ impl<T: Read> Reader<T> {
     /// # Errors
     /// The function has an additional error that is returned if  the shape type you asked to be
     /// read does not match the actual shape type in the file.
+    /// The shapes of a file are promoted to the type asked for when it has more dimensions
+    /// (see [ShapeType::can_be_promoted_to](enum.ShapeType.html#method.can_be_promoted_to)),
+    /// with z = 0 and m = NO_DATA, demoting them has to be asked for with
+    /// [read_as_lossy](#method.read_as_lossy).
+    ///
+    /// A record holding a NullShape is returned as an `Error::NullShapeRecord`,
+    /// see [read_as_optional](#method.read_as_optional) to read files with null records.
+    ///
+    /// The errors of the records are wrapped in an `Error::RecordError`, which gives
+    /// the number of the record and the offset of its header in the file,
+    /// except for `Error::MismatchShapeType`.
     ///
     /// # Examples
     ///
@@ -229,11 +2024,115 @@ impl<T: Read> Reader<T> {
     /// assert_eq!(polylines.is_err(), true);
     /// ```
     pub fn read_as<S: ReadableShape>(self) -> Result<Vec<S>, Error> {
-        self.iter_shapes_as::<S>().collect()
+        let mut shape_iter = self.iter_shapes_as::<S>();
+        let mut shapes = Vec::<S>::new();
+        while let Some(result) = shape_iter.next() {
+            match result {
+                Ok(shape) => shapes.push(shape),
+                Err(Error::MismatchShapeType {
+                    requested,
+                    actual: ShapeType::NullShape,
+                }) => {
+                    let error = Error::NullShapeRecord { requested };
+                    return Err(shape_iter.read_all_error(error));
+                }
+                Err(e) => return Err(shape_iter.read_all_error(e)),
+            }
+        }
+        Ok(shapes)
+    }
+
+    /// Reads all the shapes as shapes of a certain type, the records holding
+    /// a NullShape being read as `None`.
+    ///
+    /// The specification allows NullShape records in a file of any shape type,
+    /// keeping them as `None` keeps the shapes aligned with the records of the .dbf.
+    ///
+    /// The errors of the records are wrapped in an `Error::RecordError`,
+    /// like for [read_as](#method.read_as).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let polylines = reader.read_as_optional::<shapefile::Polyline>()?;
+    /// for (i, polyline) in polylines.iter().enumerate() {
+    ///     match polyline {
+    ///         Some(polyline) => println!("{}: {}", i, polyline),
+    ///         None => println!("{}: null", i),
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_as_optional<S: ReadableShape>(self) -> Result<Vec<Option<S>>, Error> {
+        let mut shape_iter = self.iter_shapes_as::<Option<S>>();
+        let mut shapes = Vec::<Option<S>>::new();
+        while let Some(result) = shape_iter.next() {
+            match result {
+                Ok(shape) => shapes.push(shape),
+                Err(e) => return Err(shape_iter.read_all_error(e)),
+            }
+        }
+        Ok(shapes)
+    }
+
+    /// Reads all the shapes as shapes of a certain type, converting the shapes
+    /// of the file to it even if values are lost.
+    ///
+    /// [read_as](#method.read_as) promotes the shapes of a file to a type with more
+    /// dimensions (e.g. a Polyline file can be read as PolylineZ, with z = 0 and m = NO_DATA),
+    /// but does not drop dimensions: reading a PolylineZ file as Polyline is an error.
+    /// This method drops them (see [Shape::convert_to](enum.Shape.html#method.convert_to)).
+    ///
+    /// # Errors
+    ///
+    /// `Error::MismatchShapeType` if the shapes are not of the same class of geometry
+    /// as `S` (e.g. points read as polylines), a record holding a NullShape
+    /// is returned as an `Error::NullShapeRecord`.
+    /// The errors of the records are wrapped in an `Error::RecordError`,
+    /// like for [read_as](#method.read_as).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/linez.shp")?;
+    /// let polylines = reader.read_as_lossy::<shapefile::Polyline>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_as_lossy<S>(self) -> Result<Vec<S>, Error>
+    where
+        S: HasShapeType + TryFrom<Shape>,
+        Error: From<<S as TryFrom<Shape>>::Error>,
+    {
+        let mut shape_iter = self.iter_shapes();
+        let mut shapes = Vec::<S>::new();
+        while let Some(shape) = shape_iter.next() {
+            let converted = shape.and_then(|shape| {
+                if let Shape::NullShape(_) = shape {
+                    return Err(Error::NullShapeRecord {
+                        requested: S::shapetype(),
+                    });
+                }
+                let converted = shape.convert_to(S::shapetype())?;
+                Ok(S::try_from(converted)?)
+            });
+            match converted {
+                Ok(shape) => shapes.push(shape),
+                Err(e) => return Err(shape_iter.read_all_error(e)),
+            }
+        }
+        Ok(shapes)
     }
 
     /// Reads all the shapes and returns them
     ///
+    /// The errors of the records are wrapped in an `Error::RecordError`,
+    /// like for [read_as](#method.read_as).
+    ///
     /// # Examples
     /// ```
     /// use shapefile::Reader;
@@ -248,18 +2147,94 @@ impl<T: Read> Reader<T> {
     /// ```
     ///
     pub fn read(self) -> Result<Vec<Shape>, Error> {
-        self.into_iter().collect()
+        self.read_as::<Shape>()
+    }
+
+    /// Returns an iterator over the shapes, read as the specified type,
+    /// in batches of `chunk_size` shapes (the last batch may be shorter).
+    ///
+    /// Each batch is allocated with the capacity of `chunk_size` shapes (or of the
+    /// remaining shapes, when the reader knows their number). The records that are read
+    /// in a buffer before being parsed, i.e. with [reinterpret_as](#method.reinterpret_as),
+    /// [allow_multipatch_conversion](#method.allow_multipatch_conversion),
+    /// [keep_unknown_shapes](#method.keep_unknown_shapes) or
+    /// [normalize_polygons](#method.normalize_polygons), reuse the same buffer;
+    /// by default the shapes are parsed from the source. The iterator is `Send`
+    /// when the source is, so that the batches can be processed in parallel
+    /// (e.g. with `par_bridge` of rayon).
+    ///
+    /// If a shape cannot be read, its batch is returned as the error
+    /// and the iteration stops.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/pointz.shp").unwrap();
+    /// for chunk in reader.read_chunks_as::<shapefile::PointZ>(1) {
+    ///     let points = chunk.unwrap();
+    ///     assert_eq!(points.len(), 1);
+    /// }
+    /// ```
+    pub fn read_chunks_as<S: ReadableShape>(
+        self,
+        chunk_size: usize,
+    ) -> ChunkIterator<ShapeIterator<T, S>> {
+        assert!(chunk_size != 0, "chunk_size must be greater than 0");
+        ChunkIterator {
+            iter: self.iter_shapes_as::<S>(),
+            chunk_size,
+            is_done: false,
+        }
+    }
+
+    /// Returns an iterator over the shapes in batches of `chunk_size` shapes,
+    /// see [read_chunks_as](#method.read_chunks_as)
+    pub fn read_chunks(self, chunk_size: usize) -> ChunkIterator<ShapeIterator<T, Shape>> {
+        self.read_chunks_as::<Shape>(chunk_size)
+    }
+
+    /// Returns an iterator over the shapes and their records in batches of `chunk_size`,
+    /// see [read_chunks_as](#method.read_chunks_as)
+    ///
+    /// # Errors
+    ///
+    /// The `Result` will be an error if the .dbf wasn't found
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is 0.
+    pub fn read_chunks_with_records(
+        self,
+        chunk_size: usize,
+    ) -> Result<ChunkIterator<ShapeRecordIterator<T, Shape>>, Error> {
+        assert!(chunk_size != 0, "chunk_size must be greater than 0");
+        Ok(ChunkIterator {
+            iter: self.iter_shapes_and_records()?,
+            chunk_size,
+            is_done: false,
+        })
     }
 
     /// Read and return _only_ the records contained in the *.dbf* file
     pub fn read_records(self) -> Result<Vec<dbase::Record>, Error> {
         let dbf_reader = self.dbf_reader.ok_or(Error::MissingDbf)?;
-        dbf_reader.read().or_else(|e| Err(Error::DbaseError(e)))
+        dbf_reader.read().map_err(Error::DbaseError)
     }
 
     /// Returns an iterator that tries to read the shapes as the specified type
     /// Will return an error of the type `S` does not match the actual type in the file
     ///
+    /// The shapes are read one at a time as the iterator advances,
+    /// see [ShapeIterator](struct.ShapeIterator.html) for how errors are handled.
+    ///
+    /// The iterator owns the source of the reader and does not borrow anything:
+    /// it is `Send` when the source is, so the records can be read on one thread
+    /// and processed on others.
+    ///
     /// # Examples
     ///
     /// ```
@@ -269,12 +2244,51 @@ impl<T: Read> Reader<T> {
     ///     println!("{}", points);
     /// }
     /// ```
-    pub fn iter_shapes_as<S: ReadableShape>(self) -> ShapeIterator<T, S> {
+    ///
+    /// ```
+    /// use std::sync::mpsc;
+    /// use std::thread;
+    ///
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// let shapes = reader.iter_shapes_as::<shapefile::Polyline>();
+    ///
+    /// let (sender, receiver) = mpsc::channel();
+    /// let reading = thread::spawn(move || {
+    ///     for shape in shapes {
+    ///         sender.send(shape).unwrap();
+    ///     }
+    /// });
+    /// let num_points = receiver
+    ///     .iter()
+    ///     .map(|polyline| polyline.unwrap().points.len())
+    ///     .sum::<usize>();
+    /// reading.join().unwrap();
+    /// assert_eq!(num_points, 7);
+    /// ```
+    pub fn iter_shapes_as<S: ReadableShape>(mut self) -> ShapeIterator<T, S> {
+        // The number of records is only needed to report the progress
+        let records_total = if self.progress.is_some() {
+            self.index_table().ok().map(IndexTable::len)
+        } else {
+            None
+        };
+        let (current_pos, initial_error) = self.start_of_records();
         ShapeIterator {
             _shape: std::marker::PhantomData,
             source: self.source,
-            current_pos: header::HEADER_SIZE as usize,
-            file_length: (self.header.file_length * 2) as usize,
+            current_pos: current_pos as usize,
+            file_length: self.header.file_length.max(0) as usize * 2,
+            num_records_read: 0,
+            records_total,
+            progress: self.progress,
+            error_policy: self.error_policy,
+            parsing: self.parsing,
+            skipped_records: Vec::new(),
+            content_buffer: Vec::new(),
+            last_record_number: 0,
+            last_record_offset: 0,
+            failed: false,
+            initial_error,
         }
     }
 
@@ -303,19 +2317,345 @@ impl<T: Read> Reader<T> {
     /// }
     /// ```
     pub fn iter_shapes(self) -> ShapeIterator<T, Shape> {
-        ShapeIterator {
+        self.iter_shapes_as::<Shape>()
+    }
+
+    /// Returns an iterator over the shapes, read as the specified type,
+    /// paired with the numbers of their records.
+    ///
+    /// The record numbers (1-based) are the ones written in the record headers,
+    /// they are the join key to the records of the .dbf.
+    /// They are checked to be sequential and to start at 1: a shape of a record
+    /// with another number is returned as an `Error::UnexpectedRecordNumber`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// for result in reader.iter_numbered_shapes_as::<shapefile::Polyline>() {
+    ///     let (number, polyline) = result?;
+    ///     println!("Record {}: {}", number, polyline);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_numbered_shapes_as<S: ReadableShape>(self) -> NumberedShapeIterator<T, S> {
+        NumberedShapeIterator {
+            shape_iter: self.iter_shapes_as::<S>(),
+        }
+    }
+
+    /// Returns an iterator over the shapes paired with the numbers of their records,
+    /// see [iter_numbered_shapes_as](#method.iter_numbered_shapes_as)
+    pub fn iter_numbered_shapes(self) -> NumberedShapeIterator<T, Shape> {
+        self.iter_numbered_shapes_as::<Shape>()
+    }
+
+    /// Returns an iterator over the records of the .shp as they are in the file:
+    /// their number, the shape type of their content and their content
+    /// (starting with the shape type code), without reading their shapes.
+    ///
+    /// The shape type is `None` if the code is not the one of a known shape type,
+    /// the content is still returned so that such records can be copied
+    /// with [Writer::write_raw_record](../writer/struct.Writer.html#method.write_raw_record).
+    ///
+    /// The options of the reader that apply to the shapes read
+    /// (e.g. [normalize_polygons](#method.normalize_polygons)) do not apply,
+    /// except the [max_record_size_bytes](#method.max_record_size_bytes) limit.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidShapeRecordSize` if the content of a record is too small
+    ///   to hold its shape type
+    /// - `Error::RecordTooLarge` if the content of a record is larger than the limit
+    /// - `Error::TruncatedFile` if the file ends within a record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// for raw_record in reader.iter_raw_records() {
+    ///     let (number, shape_type, content) = raw_record?;
+    ///     assert_eq!(shape_type, Some(shapefile::ShapeType::Polyline));
+    ///     println!("Record {} has {} bytes", number, content.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_raw_records(mut self) -> RawRecordIterator<T> {
+        let (current_pos, initial_error) = self.start_of_records();
+        RawRecordIterator {
+            source: self.source,
+            current_pos: current_pos as usize,
+            file_length: self.header.file_length.max(0) as usize * 2,
+            parsing: self.parsing,
+            initial_error,
+            failed: false,
+        }
+    }
+
+    /// Returns an iterator over the shapes, read as the specified type,
+    /// which bounding box intersects the `bbox`.
+    ///
+    /// Every record is still read, the shapes that do not intersect are discarded.
+    /// Only the bounding boxes of the shapes are tested, unless the iterator is made
+    /// [exact](struct.BBoxShapeIterator.html#method.exact).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// let reader = shapefile::Reader::from_path("tests/data/multipoint.shp").unwrap();
+    /// let bbox = BBox::new(-10.0, -10.0, -5.0, -5.0);
+    /// let count = reader.iter_shapes_in_bbox_as::<shapefile::Multipoint>(bbox).count();
+    /// assert_eq!(count, 0);
+    /// ```
+    pub fn iter_shapes_in_bbox_as<S: ReadableShape + EsriShape>(
+        self,
+        bbox: BBox,
+    ) -> BBoxShapeIterator<T, S> {
+        BBoxShapeIterator {
+            shape_iter: self.iter_shapes_as::<S>(),
+            bbox,
+            exact_test: None,
+        }
+    }
+
+    /// Returns an iterator over the shapes which bounding box intersects the `bbox`.
+    ///
+    /// See [iter_shapes_in_bbox_as](struct.Reader.html#method.iter_shapes_in_bbox_as)
+    pub fn iter_shapes_in_bbox(self, bbox: BBox) -> BBoxShapeIterator<T, Shape> {
+        self.iter_shapes_in_bbox_as::<Shape>(bbox)
+    }
+
+    /// Returns an iterator over the shapes, read as the specified type,
+    /// of the records for which `predicate` returns true.
+    ///
+    /// The predicate is given a [RecordPreview](struct.RecordPreview.html) made from
+    /// the beginning of the record (at most 44 bytes), the points of the records
+    /// that are not accepted are not parsed. They are skipped using `seek` if the reader
+    /// was created with [from_seekable](#method.from_seekable) or [from_path](#method.from_path),
+    /// and read and discarded otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp").unwrap();
+    /// let with_holes = reader
+    ///     .iter_matching_as::<shapefile::Polygon, _>(|preview| preview.num_parts > 1)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(with_holes.len(), 1);
+    /// ```
+    pub fn iter_matching_as<S, F>(mut self, predicate: F) -> MatchingShapeIterator<T, S, F>
+    where
+        S: ReadableShape,
+        F: FnMut(&RecordPreview) -> bool,
+    {
+        let (current_pos, initial_error) = self.start_of_records();
+        MatchingShapeIterator {
             _shape: std::marker::PhantomData,
             source: self.source,
-            current_pos: header::HEADER_SIZE as usize,
-            file_length: (self.header.file_length * 2) as usize,
+            seek_fn: self.seek_fn,
+            current_pos,
+            file_length: self.header.file_length.max(0) as u64 * 2,
+            parsing: self.parsing,
+            predicate,
+            initial_error,
+        }
+    }
+
+    /// Returns an iterator over the shapes of the records for which `predicate` returns true.
+    ///
+    /// See [iter_matching_as](struct.Reader.html#method.iter_matching_as)
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// let count = reader.iter_matching(|preview| preview.num_points > 10_000).count();
+    /// assert_eq!(count, 0);
+    /// ```
+    pub fn iter_matching<F>(self, predicate: F) -> MatchingShapeIterator<T, Shape, F>
+    where
+        F: FnMut(&RecordPreview) -> bool,
+    {
+        self.iter_matching_as::<Shape, F>(predicate)
+    }
+
+    /// Returns an iterator over the record numbers and bboxes of the records,
+    /// e.g. to build a spatial index.
+    ///
+    /// Only the header of each record and the beginning of its content (its shape type and
+    /// its bbox) are read, the points are not: they are skipped using `seek` if the reader
+    /// was created with [from_seekable](#method.from_seekable) or [from_path](#method.from_path),
+    /// and read and discarded otherwise.
+    ///
+    /// The bbox of a point is the point itself, the records holding a NullShape
+    /// have no bbox and are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidShapeRecordSize` if the content of a record is too small
+    ///   to hold its shape type and its bbox, or if the record ends after the end of the file
+    /// - `Error::TruncatedFile` if the file ends within a record
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp")?;
+    /// for result in reader.iter_record_bboxes() {
+    ///     let (record_number, bbox) = result?;
+    ///     println!("Record {}: {:?}", record_number, bbox);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_record_bboxes(mut self) -> RecordBBoxIterator<T> {
+        let (current_pos, initial_error) = self.start_of_records();
+        RecordBBoxIterator {
+            source: self.source,
+            seek_fn: self.seek_fn,
+            current_pos,
+            file_length: self.header.file_length.max(0) as u64 * 2,
+            index: 0,
+            initial_error,
+        }
+    }
+
+    /// Returns the points of the shapes that are not within the `domain`
+    /// (see [validate_extent](../extent/fn.validate_extent.html)).
+    ///
+    /// The bbox of the header is checked first: when it is within the domain,
+    /// no shape is read and no violation is returned. Otherwise the shapes are
+    /// checked as they are read, and are not kept.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::record::BBox;
+    /// let reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let violations = reader.validate_extent(BBox::new(0.0, 0.0, 2.0, 2.0))?;
+    /// assert!(!violations.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn validate_extent(self, domain: BBox) -> Result<Vec<ExtentViolation>, Error> {
+        let header = &self.header;
+        let is_header_within = is_within(&domain, header.point_min[0], header.point_min[1])
+            && is_within(&domain, header.point_max[0], header.point_max[1]);
+        if is_header_within {
+            return Ok(Vec::new());
+        }
+        let mut shape_iter = self.iter_shapes();
+        let mut violations = Vec::<ExtentViolation>::new();
+        let mut record = 0;
+        while let Some(result) = shape_iter.next() {
+            let shape = result.map_err(|e| shape_iter.read_all_error(e))?;
+            violations.extend(shape_violations(record, &shape, &domain));
+            record += 1;
+        }
+        Ok(violations)
+    }
+
+    /// Returns an iterator over the shapes converted to `C` (with its `TryFrom<Shape>`
+    /// implementation) as they are read, so that the shapes read are not kept
+    ///
+    /// Conversion failures are wrapped in an `Error::RecordError` giving the record,
+    /// the iteration can continue after them unless the
+    /// [error policy](#method.set_error_policy) is to fail.
+    ///
+    /// The shape type of the header is checked once: if its shapes can not be converted
+    /// to `C` (`Error::MismatchShapeType`), as all the shapes of the file have its type,
+    /// the first record fails without being read and the iteration ends.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::PolylineZ;
+    /// let reader = shapefile::Reader::from_path("tests/data/linez.shp")?;
+    /// for polyline in reader.iter_as_converted::<PolylineZ>() {
+    ///     let polyline = polyline?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_as_converted<C>(self) -> ConvertedShapeIterator<T, C>
+    where
+        C: TryFrom<Shape>,
+        Error: From<<C as TryFrom<Shape>>::Error>,
+    {
+        let header_mismatch = match C::try_from(Shape::empty_of(self.header.shape_type)) {
+            Err(e) => match Error::from(e) {
+                e @ Error::MismatchShapeType { .. } => Some(e),
+                _ => None,
+            },
+            Ok(_) => None,
+        };
+        ConvertedShapeIterator {
+            shape_iter: self.iter_shapes(),
+            header_mismatch,
+            _converted: std::marker::PhantomData,
+        }
+    }
+
+    /// Returns an iterator over the shapes of the type `S`, the records holding
+    /// a NullShape being skipped.
+    ///
+    /// Every record is read as a [Shape](../record/enum.Shape.html), then converted with
+    /// [try_into_concrete](../record/enum.Shape.html#method.try_into_concrete).
+    /// A shape of another type is an error (the file is corrupted, the shapes of a file
+    /// must all have its type), wrapped in an `Error::RecordError` giving its record.
+    /// The iteration ends after it, unless the
+    /// [error policy](#method.set_error_policy) is to continue.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::Polygon;
+    /// let reader = shapefile::Reader::from_path("tests/data/polygon.shp")?;
+    /// for polygon in reader.iter_filtered::<Polygon>() {
+    ///     let polygon = polygon?;
+    ///     println!("{} rings", polygon.parts.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_filtered<S>(self) -> FilteredShapeIterator<T, S>
+    where
+        S: HasShapeType + TryFrom<Shape, Error = Error>,
+    {
+        FilteredShapeIterator {
+            shape_iter: self.iter_shapes(),
+            _shape: std::marker::PhantomData,
         }
     }
 
+    /// Returns an iterator over the shapes converted to geo_types geometries as they are read,
+    /// see [iter_as_converted](#method.iter_as_converted)
+    ///
+    /// NullShapes can not be converted, they are returned as errors.
+    #[cfg(feature = "geo-types")]
+    pub fn iter_as_geo(self) -> ConvertedShapeIterator<T, geo_types::Geometry<f64>> {
+        self.iter_as_converted::<geo_types::Geometry<f64>>()
+    }
+
     /// Returns an iterator over the Shapes and their Records
     ///
     /// # Errors
     ///
-    /// The `Result` will be an error if the .dbf wasn't found
+    /// The `Result` will be an error if the .dbf wasn't found.
+    ///
+    /// If the .shp and the .dbf do not have the same number of records, the last item
+    /// of the iterator is an `Error::RecordCountMismatch` giving both numbers.
+    /// When the record of a shape that could not be read can not be read either,
+    /// its error is the item following the error of the shape.
     ///
     /// # Example
     /// ```
@@ -334,7 +2674,11 @@ impl<T: Read> Reader<T> {
             let shape_iter = self.iter_shapes_as::<S>();
             Ok(ShapeRecordIterator {
                 shape_iter,
+                num_records: dbf_reader.header().num_records as usize,
                 dbf_reader,
+                num_read: 0,
+                finished: false,
+                dbf_error: None,
             })
         } else {
             Err(Error::MissingDbf)
@@ -365,13 +2709,113 @@ impl<T: Read> Reader<T> {
         self.iter_shapes_and_records_as::<Shape>()
     }
 
-    /// Reads the index file from the source
-    /// This allows to later read shapes by giving their index without reading the whole file
+    /// Reads all the shapes as the specified type, with their records
+    ///
+    /// Reading as `Shape` keeps the NullShapes with their records,
+    /// when reading as a concrete type (e.g. `Polygon`) a NullShape is an error.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MissingDbf` if the .dbf wasn't found
+    /// - `Error::NullShapeRecord` if a record is a NullShape and `S` is a concrete type
+    /// - `Error::RecordCountMismatch` if the .shp and the .dbf do not have
+    ///   the same number of records
+    /// - the errors of [read_as](#method.read_as)
+    ///
+    /// The errors of the records (shapes or dbase records) are wrapped
+    /// in an `Error::RecordError`, like for [read_as](#method.read_as).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?;
+    /// let features = reader.read_as_with_records::<shapefile::Multipatch>()?;
+    /// for (multipatch, record) in features {
+    ///     // ...
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_as_with_records<S: ReadableShape>(self) -> Result<Vec<(S, dbase::Record)>, Error> {
+        let num_records = match self.dbf_reader {
+            Some(ref dbf_reader) => dbf_reader.header().num_records as usize,
+            None => return Err(Error::MissingDbf),
+        };
+        let mut feature_iter = self.iter_shapes_and_records_as::<S>()?;
+        let mut features = Vec::<(S, dbase::Record)>::with_capacity(num_records);
+        while let Some(result) = feature_iter.next() {
+            match result {
+                Ok(feature) => features.push(feature),
+                Err(e @ Error::RecordCountMismatch { .. }) => return Err(e),
+                Err(Error::MismatchShapeType {
+                    requested,
+                    actual: ShapeType::NullShape,
+                }) => {
+                    let error = Error::NullShapeRecord { requested };
+                    return Err(feature_iter.shape_iter.read_all_error(error));
+                }
+                Err(e) => return Err(feature_iter.shape_iter.read_all_error(e)),
+            }
+        }
+        Ok(features)
+    }
+
+    /// Adds the `source` as the source where the index file will be read from
+    /// This allows to later read shapes by giving their index without reading the whole file
+    ///
+    /// The index is only read the first time it is needed
+    /// (see [read_nth_shape()](struct.Reader.html#method.read_nth_shape)
+    /// and [index_table()](struct.Reader.html#method.index_table))
+    pub fn add_index_source(&mut self, source: T) -> Result<(), Error> {
+        self.index_source = Some(source);
+        self.index_table = None;
+        Ok(())
+    }
+
+    /// Returns true if the reader has an index file (*.shx*), to read the shapes
+    /// by their index
+    pub fn has_index(&self) -> bool {
+        self.index_source.is_some() || self.index_table.is_some()
+    }
+
+    /// Returns true if the reader has a *.dbf*, to read the records of the shapes
+    ///
+    /// The *.dbf* is no longer available once its records were read.
+    pub fn has_attributes(&self) -> bool {
+        self.dbf_reader.is_some()
+    }
+
+    /// Returns the table of the offsets of the records, read from the index file.
     ///
-    /// (see [read_nth_shape()](struct.Reader.html#method.read_nth_shape))
-    pub fn add_index_source(&mut self, source: T) -> Result<(), Error> {
-        self.shapes_index = Some(read_index_file(source)?);
-        Ok(())
+    /// The table is read on the first call and then kept by the reader.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MissingIndexFile` if the reader has no index source.
+    /// - The errors of [IndexTable::read_from](../index/struct.IndexTable.html#method.read_from),
+    ///   and `Error::InvalidIndexEntry` if the record of an entry ends after the end
+    ///   of the *.shp*. The index source is dropped only once the table is read.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let index = reader.index_table()?;
+    /// assert_eq!(index.offset_of(0), Some((100, 164)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn index_table(&mut self) -> Result<&IndexTable, Error> {
+        if self.index_table.is_none() {
+            let source = self.index_source.as_mut().ok_or(Error::MissingIndexFile)?;
+            let index_table = IndexTable::read_from(source)?;
+            index_table.check_entries_end_before(self.header.file_length.max(0) as u64 * 2)?;
+            self.index_table = Some(index_table);
+            self.index_source = None;
+        }
+        Ok(self.index_table.as_ref().unwrap())
     }
 
     /// Adds the `source` as the source where the dbf record will be read from
@@ -380,6 +2824,21 @@ impl<T: Read> Reader<T> {
         self.dbf_reader = Some(dbf_reader);
         Ok(())
     }
+
+    /// Returns the position of the first record, seeking back to it if the source
+    /// may have been moved by a previous read.
+    ///
+    /// If the seek fails, the end of the file is returned with the error,
+    /// which the iterators return as their first item before ending.
+    fn start_of_records(&mut self) -> (u64, Option<Error>) {
+        match self.seek_fn {
+            Some(seek) => match seek(&mut self.source, SeekFrom::Start(HEADER_SIZE as u64)) {
+                Ok(pos) => (pos, None),
+                Err(e) => (self.header.file_length.max(0) as u64 * 2, Some(e.into())),
+            },
+            None => (HEADER_SIZE as u64, None),
+        }
+    }
 }
 
 impl<T: Read> IntoIterator for Reader<T> {
@@ -391,12 +2850,104 @@ impl<T: Read> IntoIterator for Reader<T> {
     }
 }
 
+impl Reader<Cursor<Vec<u8>>> {
+    /// Creates a reader from the bytes of a *.shp* held in memory.
+    ///
+    /// The index of the records is built by reading the header of each record
+    /// (see [rebuild_index](#method.rebuild_index)), so that the shapes can be read
+    /// by their index without a *.shx*, and the records outside of a bbox are skipped.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [from_seekable](#method.from_seekable) and of
+    /// [rebuild_index](#method.rebuild_index).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let bytes = std::fs::read("tests/data/line.shp")?;
+    /// let mut reader = shapefile::Reader::from_buffer(bytes)?;
+    /// assert!(reader.read_nth_shape(0).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_buffer(bytes: Vec<u8>) -> Result<Self, Error> {
+        let mut reader = Self::from_seekable(Cursor::new(bytes))?;
+        reader.rebuild_index()?;
+        Ok(reader)
+    }
+}
+
+impl<'a> Reader<Cursor<&'a [u8]>> {
+    /// Creates a reader from the bytes of a *.shp* held in memory, without copying them,
+    /// like [from_buffer](#method.from_buffer).
+    ///
+    /// The shapes can also be read as views that borrow the bytes,
+    /// see [iter_shape_refs](#method.iter_shape_refs).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let bytes = std::fs::read("tests/data/multipoint.shp")?;
+    /// let mut reader = shapefile::Reader::from_slice(&bytes)?;
+    /// let multipoint = reader.read_nth_shape_as::<shapefile::Multipoint>(0).unwrap()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_slice(bytes: &'a [u8]) -> Result<Self, Error> {
+        let mut reader = Self::from_seekable(Cursor::new(bytes))?;
+        reader.rebuild_index()?;
+        Ok(reader)
+    }
+
+    /// Returns an iterator over views of the shapes of a shapefile held in memory,
+    /// which borrow their points and parts from the bytes when possible
+    /// (see the [borrowed](../record/borrowed/index.html) module).
+    ///
+    /// This avoids copying the points of large files, e.g. of memory mapped files.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::record::borrowed::ShapeRef;
+    /// let bytes = std::fs::read("tests/data/line.shp")?;
+    /// let reader = shapefile::Reader::new(std::io::Cursor::new(&bytes[..]))?;
+    /// for shape in reader.iter_shape_refs() {
+    ///     if let ShapeRef::Polyline(polyline) = shape? {
+    ///         println!("{} points", polyline.points.len());
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn iter_shape_refs(&self) -> ShapeRefIterator<'a> {
+        ShapeRefIterator {
+            bytes: self.source.get_ref(),
+            current_pos: HEADER_SIZE as usize,
+            file_length: self.header.file_length.max(0) as usize * 2,
+            parsing: self.parsing,
+            error_policy: self.error_policy,
+            failed: false,
+        }
+    }
+}
+
 impl Reader<BufReader<File>> {
     /// Creates a reader from a path to a file
     ///
     /// Will attempt to read both the .shx and .dbf associated with the file,
     /// if they do not exists the function will not fail, and you will get an error later
     /// if you try to use a function that requires the file to be present.
+    /// See [has_index](#method.has_index) and [has_attributes](#method.has_attributes).
+    ///
+    /// The .shx and .dbf with the same name as the .shp are used if they exist,
+    /// otherwise the case of their names is ignored (`roads.dbf` or `Roads.DBF` are found
+    /// for `ROADS.SHP`), preferring the files whose basename has the case of the .shp.
+    /// The files found are given by [shx_path](#method.shx_path)
+    /// and [dbf_path](#method.dbf_path).
     ///
     ///
     /// # Examples
@@ -408,6 +2959,7 @@ impl Reader<BufReader<File>> {
     ///
     /// // both .shx and .dbf does not exists, but creation does not fail
     /// let mut reader = shapefile::Reader::from_path("tests/data/linem.shp").unwrap();
+    /// assert!(!reader.has_index() && !reader.has_attributes());
     /// let result = reader.iter_shapes_and_records();
     /// assert_eq!(result.is_err(),  true);
     ///
@@ -426,21 +2978,126 @@ impl Reader<BufReader<File>> {
     /// let polylines = reader.read_as::<shapefile::Polyline>().unwrap();
     /// ```
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::with_capacity(path, DEFAULT_READ_BUFFER_SIZE)
+    }
+
+    /// Creates a reader from a path to a file, like [from_path](#method.from_path),
+    /// the .shp, .shx and .dbf being read through buffers of `capacity` bytes
+    /// (instead of 8 KiB).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::Reader::with_capacity("tests/data/multipatch.shp", 64 * 1024)?;
+    /// let shapes = reader.read()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_capacity<P: AsRef<Path>>(path: P, capacity: usize) -> Result<Self, Error> {
         let shape_path = path.as_ref().to_path_buf();
-        let shx_path = shape_path.with_extension("shx");
-        let dbf_path = shape_path.with_extension("dbf");
+        let shx_path = companion_path_of(&shape_path, "shx")?;
+        let dbf_path = companion_path_of(&shape_path, "dbf")?;
 
-        let source = BufReader::new(File::open(shape_path)?);
-        let mut reader = Self::new(source)?;
+        let source = BufReader::with_capacity(capacity, File::open(&shape_path)?);
+        let mut reader = Self::from_seekable(source)?;
+        reader.shp_path = Some(shape_path);
 
-        if shx_path.exists() {
-            let index_source = BufReader::new(File::open(shx_path)?);
+        if let Some(shx_path) = shx_path {
+            let index_source = BufReader::with_capacity(capacity, File::open(&shx_path)?);
             reader.add_index_source(index_source)?;
+            reader.shx_path = Some(shx_path);
         }
 
-        if dbf_path.exists() {
-            let dbf_source = BufReader::new(File::open(dbf_path)?);
+        // An empty layer may come with an empty .dbf, which has no header to read
+        let dbf_path = match dbf_path {
+            Some(dbf_path) if std::fs::metadata(&dbf_path)?.len() == 0 => None,
+            dbf_path => dbf_path,
+        };
+        if let Some(dbf_path) = dbf_path {
+            let dbf_source = BufReader::with_capacity(capacity, File::open(&dbf_path)?);
             reader.add_dbf_source(dbf_source)?;
+            reader.dbf_path = Some(dbf_path);
+        }
+        Ok(reader)
+    }
+
+    /// Sets the capacity, in bytes, of the buffers the .shp, .shx and .dbf are read through
+    /// (8 KiB by default, like `std::io::BufReader`, see also
+    /// [with_capacity](#method.with_capacity)).
+    ///
+    /// The position in the files is kept. The .dbf buffer is only changed for the .dbf
+    /// opened by [from_path](#method.from_path), not for one given to
+    /// [add_dbf_source](#method.add_dbf_source).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?;
+    /// reader.set_read_buffer_size(64 * 1024)?;
+    /// for shape_record in reader.iter_shapes_and_records()? {
+    ///     let (shape, record) = shape_record?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_read_buffer_size(&mut self, bytes: usize) -> Result<(), Error> {
+        self.source = with_buffer_capacity(&mut self.source, bytes)?;
+        if let Some(index_source) = self.index_source.as_mut() {
+            *index_source = with_buffer_capacity(index_source, bytes)?;
+        }
+        // The .dbf reader does not give back its source, but it has not read any record
+        // (the readers of records take it), so the file is opened again
+        if let (Some(_), Some(dbf_path)) = (&self.dbf_reader, &self.dbf_path) {
+            let dbf_source = BufReader::with_capacity(bytes, File::open(dbf_path)?);
+            self.dbf_reader = Some(dbase::Reader::new(dbf_source)?);
+        }
+        Ok(())
+    }
+}
+
+/// Returns a reader of the file of the `source` with a buffer of the `capacity`,
+/// at the position the `source` was
+fn with_buffer_capacity(
+    source: &mut BufReader<File>,
+    capacity: usize,
+) -> std::io::Result<BufReader<File>> {
+    let position = source.stream_position()?;
+    // The clone shares the cursor of the file, which is then set by the seek
+    let mut rebuffered = BufReader::with_capacity(capacity, source.get_ref().try_clone()?);
+    rebuffered.seek(SeekFrom::Start(position))?;
+    Ok(rebuffered)
+}
+
+impl Reader<Cursor<Vec<u8>>> {
+    /// Creates a reader of a shapefile in memory, from the bytes of its .shp,
+    /// and of its .shx and .dbf if it has them
+    /// (see [Writer::into_parts](../writer/struct.Writer.html#method.into_parts)
+    /// to write one).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let shp = std::fs::read("tests/data/multipatch.shp")?;
+    /// let dbf = std::fs::read("tests/data/multipatch.dbf")?;
+    /// let reader = shapefile::Reader::from_parts_bytes(shp, None, Some(dbf))?;
+    /// assert!(reader.has_attributes() && !reader.has_index());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_parts_bytes(
+        shp: Vec<u8>,
+        shx: Option<Vec<u8>>,
+        dbf: Option<Vec<u8>>,
+    ) -> Result<Self, Error> {
+        let mut reader = Self::from_seekable(Cursor::new(shp))?;
+        if let Some(shx) = shx {
+            reader.add_index_source(Cursor::new(shx))?;
+        }
+        if let Some(dbf) = dbf {
+            reader.add_dbf_source(Cursor::new(dbf))?;
         }
         Ok(reader)
     }
@@ -449,53 +3106,490 @@ impl Reader<BufReader<File>> {
 /// Sources that implements `Seek` have access to
 /// a few more methods that uses the *index file(.shx)*
 impl<T: Read + Seek> Reader<T> {
+    /// Creates a new Reader from a source that implements `Seek`,
+    /// which is used to skip records instead of reading them
+    /// (see [iter_matching_as](#method.iter_matching_as)).
+    ///
+    /// # Errors
+    ///
+    /// `Error::TruncatedFile` if the source is shorter than the file length given by the header.
+    /// The source may be longer (some tools append data to the files),
+    /// the records are only read up to the file length.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::fs::File;
+    /// let file = File::open("tests/data/line.shp").unwrap();
+    /// let reader = shapefile::Reader::from_seekable(file).unwrap();
+    /// ```
+    pub fn from_seekable(source: T) -> Result<Self, Error> {
+        let mut reader = Self::new(source)?;
+        reader.seek_fn = Some(<T as Seek>::seek);
+        let actual_bytes = reader.source.seek(SeekFrom::End(0))?;
+        reader.source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        let expected_bytes = reader.header.file_length.max(0) as u64 * 2;
+        if expected_bytes > actual_bytes {
+            return Err(Error::TruncatedFile {
+                expected_bytes,
+                actual_bytes,
+            });
+        }
+        Ok(reader)
+    }
+
     /// Reads the `n`th shape of the shapefile
     ///
+    /// The index file (*.shx*) gives the offset of the record: the source seeks to it
+    /// and only this record is read.
+    /// The index file is found next to the *.shp* by [from_path](#method.from_path),
+    /// or given with [add_index_source](#method.add_index_source).
+    ///
     /// # Returns
     ///
     /// `None` if the index is out of range
     ///
     /// # Errors
     ///
-    /// This method will return an `Error::MissingIndexFile` if you use it
-    /// but no *.shx* was found when opening the shapefile.
+    /// - `Error::MissingIndexFile` if you use it
+    ///   but no *.shx* was found when opening the shapefile.
+    /// - `Error::IndexEntryMismatch` if the header of the record read does not
+    ///   have the content length given by the index file
     ///
+    /// # Example
     ///
+    /// ```
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp").unwrap();
+    /// let polyline = reader.read_nth_shape_as::<shapefile::Polyline>(0).unwrap().unwrap();
+    /// assert!(reader.read_nth_shape_as::<shapefile::Polyline>(1).is_none());
+    /// ```
     pub fn read_nth_shape_as<S: ReadableShape>(
         &mut self,
         index: usize,
     ) -> Option<Result<S, Error>> {
-        if let Some(ref shapes_index) = self.shapes_index {
-            let offset = {
-                let shape_idx = shapes_index.get(index)?;
-                (shape_idx.offset * 2) as u64
-            };
+        self.track_seeks();
+        let (offset, index_length) = match self.index_table() {
+            Ok(index_table) => index_table.offset_of(index)?,
+            Err(e) => return Some(Err(e)),
+        };
 
-            if let Err(e) = self.source.seek(SeekFrom::Start(offset)) {
-                return Some(Err(Error::IoError(e)));
-            }
+        if let Err(e) = self.source.seek(SeekFrom::Start(offset)) {
+            return Some(Err(Error::IoError(e)));
+        }
 
-            let (_, shape) = match read_one_shape_as::<T, S>(&mut self.source) {
-                Err(e) => return Some(Err(e)),
-                Ok(hdr_and_shape) => hdr_and_shape,
-            };
+        let hdr = match record::RecordHeader::read_from(&mut self.source) {
+            Ok(hdr) => hdr,
+            Err(e) => return Some(Err(e)),
+        };
+        let record_length = hdr.record_size as i64 * 2;
+        if record_length != index_length as i64 {
+            return Some(Err(Error::IndexEntryMismatch {
+                index,
+                index_length,
+                record_length: record_length.max(0) as usize,
+            }));
+        }
+        let result = read_content_as::<T, S>(&mut self.source, &hdr, self.parsing, None);
+        let shape = match result {
+            Err(e) => return Some(Err(e)),
+            Ok(shape) => shape,
+        };
 
-            if let Err(e) = self
-                .source
-                .seek(SeekFrom::Start(header::HEADER_SIZE as u64))
-            {
-                return Some(Err(Error::IoError(e)));
-            }
-            Some(Ok(shape))
-        } else {
-            Some(Err(Error::MissingIndexFile))
+        if let Err(e) = self
+            .source
+            .seek(SeekFrom::Start(HEADER_SIZE as u64))
+        {
+            return Some(Err(Error::IoError(e)));
         }
+        Some(Ok(shape))
     }
 
     /// Reads the `n`th shape of the shapefile
     pub fn read_nth_shape(&mut self, index: usize) -> Option<Result<Shape, Error>> {
         self.read_nth_shape_as::<Shape>(index)
     }
+
+    /// Reads the shapes at the given `indices`, as the specified type,
+    /// and returns them paired with their index.
+    ///
+    /// The shapes are returned in the order of the `indices`, without duplicates
+    /// (an index given more than once is only returned at its first position).
+    /// The records are read in the order they are in the file, using the index file
+    /// to seek to each of them.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MissingIndexFile` if the reader has no index file
+    /// - `Error::RecordIndicesOutOfRange` with all the indices that are not in the file
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let selection = reader.read_selection_as::<shapefile::Polyline>(&[0, 0])?;
+    /// assert_eq!(selection.len(), 1);
+    /// assert_eq!(selection[0].0, 0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_selection_as<S: ReadableShape>(
+        &mut self,
+        indices: &[usize],
+    ) -> Result<Vec<(usize, S)>, Error> {
+        self.track_seeks();
+        let selection = self.selection_of(indices)?;
+        let mut shapes = Vec::<Option<S>>::with_capacity(selection.sorted.len());
+        for (_, offset) in &selection.sorted {
+            self.source.seek(SeekFrom::Start(*offset))?;
+            let (_, shape) = read_one_shape_as::<T, S>(&mut self.source, self.parsing)?;
+            shapes.push(Some(shape));
+        }
+        self.source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+
+        let sorted = &selection.sorted;
+        Ok(selection
+            .order
+            .iter()
+            .map(|i| (sorted[*i].0, shapes[*i].take().unwrap()))
+            .collect())
+    }
+
+    /// Reads the shapes at the given `indices`,
+    /// see [read_selection_as](#method.read_selection_as)
+    pub fn read_selection(&mut self, indices: &[usize]) -> Result<Vec<(usize, Shape)>, Error> {
+        self.read_selection_as::<Shape>(indices)
+    }
+
+    /// Reads at most `count` shapes, as the specified type, starting with the shape
+    /// of index `start`.
+    ///
+    /// With an index file, the source seeks to the record of index `start`.
+    /// Without one, the offset of the record is found by reading the record headers,
+    /// seeking over the content of the records (as [rebuild_index](#method.rebuild_index)).
+    ///
+    /// Fewer than `count` shapes are returned if the file ends before,
+    /// and none if `start` is not the index of a record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?;
+    /// let page = reader.read_range_as::<shapefile::Multipatch>(0, 10)?;
+    /// assert_eq!(page.len(), 1);
+    /// assert!(reader.read_range_as::<shapefile::Multipatch>(1, 10)?.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn read_range_as<S: ReadableShape>(
+        &mut self,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<S>, Error> {
+        self.track_seeks();
+        let shapes = self.read_range_from_start::<S>(start, count);
+        self.source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        shapes
+    }
+
+    /// Reads at most `count` shapes starting with the one of index `start`,
+    /// see [read_range_as](#method.read_range_as)
+    pub fn read_range(&mut self, start: usize, count: usize) -> Result<Vec<Shape>, Error> {
+        self.read_range_as::<Shape>(start, count)
+    }
+
+    fn read_range_from_start<S: ReadableShape>(
+        &mut self,
+        start: usize,
+        count: usize,
+    ) -> Result<Vec<S>, Error> {
+        let file_length = self.header.file_length.max(0) as u64 * 2;
+        let offset = if self.has_index() {
+            self.index_table()?.offset_of(start)
+        } else {
+            IndexTable::scan_shp(&mut self.source, &self.header)?.offset_of(start)
+        };
+        let mut pos = match offset {
+            Some((offset, _)) => offset,
+            None => return Ok(Vec::new()),
+        };
+        self.source.seek(SeekFrom::Start(pos))?;
+
+        let mut shapes = Vec::<S>::new();
+        while shapes.len() < count && pos < file_length {
+            let (hdr, shape) = read_one_shape_as::<T, S>(&mut self.source, self.parsing)?;
+            pos += record::RecordHeader::SIZE as u64 + hdr.record_size as u64 * 2;
+            shapes.push(shape);
+        }
+        Ok(shapes)
+    }
+
+    /// Returns the number of records (shapes) of the file, without reading their shapes.
+    ///
+    /// With an index file, this is the number of entries of the index.
+    /// Without one, the record headers are read one after the other,
+    /// seeking over the content of the records.
+    ///
+    /// The reader is left at the first record, so it can still be iterated
+    /// or read afterwards.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/linem.shp")?;
+    /// let num_shapes = reader.shape_count()?;
+    /// let shapes = reader.read()?;
+    /// assert_eq!(shapes.len(), num_shapes);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shape_count(&mut self) -> Result<usize, Error> {
+        if self.has_index() {
+            return Ok(self.index_table()?.len());
+        }
+        self.track_seeks();
+        Ok(IndexTable::scan_shp(&mut self.source, &self.header)?.len())
+    }
+
+    /// Reads the shapes and the records at the given `indices`,
+    /// see [read_selection_as](#method.read_selection_as).
+    ///
+    /// As the .dbf reader cannot seek, the .dbf is read sequentially up to
+    /// the last selected record and the reader no longer has a .dbf afterwards
+    /// (so this can only be called once per reader).
+    ///
+    /// # Errors
+    ///
+    /// Same as [read_selection_as](#method.read_selection_as), and
+    /// `Error::MissingDbf` if the reader has no .dbf.
+    pub fn read_selection_with_records(
+        &mut self,
+        indices: &[usize],
+    ) -> Result<Vec<(usize, Shape, dbase::Record)>, Error> {
+        if self.dbf_reader.is_none() {
+            return Err(Error::MissingDbf);
+        }
+        let shapes = self.read_selection(indices)?;
+        let dbf_reader = self.dbf_reader.take().ok_or(Error::MissingDbf)?;
+
+        let mut sorted_indices = shapes.iter().map(|(i, _)| *i).collect::<Vec<usize>>();
+        sorted_indices.sort_unstable();
+        let mut records = HashMap::<usize, dbase::Record>::with_capacity(shapes.len());
+        let mut wanted = sorted_indices.iter().peekable();
+        for (i, record) in dbf_reader.enumerate() {
+            match wanted.peek() {
+                None => break,
+                Some(&&next) if next == i => {
+                    records.insert(i, record?);
+                    wanted.next();
+                }
+                Some(_) => {
+                    record?;
+                }
+            }
+        }
+        if let Some(&&missing) = wanted.peek() {
+            return Err(Error::RecordIndicesOutOfRange(vec![missing]));
+        }
+
+        Ok(shapes
+            .into_iter()
+            .map(|(i, shape)| {
+                let record = records.remove(&i).unwrap();
+                (i, shape, record)
+            })
+            .collect())
+    }
+
+    fn selection_of(&mut self, indices: &[usize]) -> Result<Selection, Error> {
+        let index_table = self.index_table()?;
+        let mut sorted_indices = indices.to_vec();
+        sorted_indices.sort_unstable();
+        sorted_indices.dedup();
+
+        let out_of_range = sorted_indices
+            .iter()
+            .filter(|i| **i >= index_table.len())
+            .cloned()
+            .collect::<Vec<usize>>();
+        if !out_of_range.is_empty() {
+            return Err(Error::RecordIndicesOutOfRange(out_of_range));
+        }
+        let sorted = sorted_indices
+            .iter()
+            .map(|i| (*i, index_table.offset_of(*i).unwrap().0))
+            .collect::<Vec<(usize, u64)>>();
+
+        let mut is_seen = vec![false; sorted_indices.len()];
+        let mut order = Vec::<usize>::with_capacity(sorted_indices.len());
+        for index in indices {
+            let position = sorted_indices.binary_search(index).unwrap();
+            if !is_seen[position] {
+                is_seen[position] = true;
+                order.push(position);
+            }
+        }
+        Ok(Selection { sorted, order })
+    }
+
+    /// Returns an overview of the content of the file
+    ///
+    /// Only the record headers and the beginning of the records (bbox, number of parts
+    /// and points) are read, the points are skipped using `seek`
+    /// (the measures are read until one that is not NO_DATA is found).
+    ///
+    /// The rings of the polygons are not counted, as their points would have to be read,
+    /// see [summary_with_rings](#method.summary_with_rings).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let summary = reader.summary()?;
+    /// assert_eq!(summary.num_records, 1);
+    /// assert_eq!(summary.num_points, 7);
+    /// assert_eq!(summary.bbox_delta(), Some([0.0, 0.0, 0.0, 0.0]));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summary(&mut self) -> Result<FileSummary, Error> {
+        self.track_seeks();
+        FileSummary::read_from(&mut self.source, &self.header, false)
+    }
+
+    /// Returns an overview of the content of the file, as [summary](#method.summary),
+    /// with the counts of the outer and inner rings of the polygons.
+    ///
+    /// The parts and the x, y of the points of the polygon records are read
+    /// to classify their rings.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/polygon.shp")?;
+    /// let summary = reader.summary_with_rings()?;
+    /// assert_eq!(summary.num_polygons_with_holes, Some(1));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn summary_with_rings(&mut self) -> Result<FileSummary, Error> {
+        self.track_seeks();
+        FileSummary::read_from(&mut self.source, &self.header, true)
+    }
+
+    /// Returns the location (offset and size) of each record of the *.shp*.
+    ///
+    /// The locations come from the index file when the reader has one,
+    /// otherwise the header of each record of the *.shp* is read to find them
+    /// (the rest of the records is skipped using `seek`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let locations = reader.record_offsets()?;
+    /// assert_eq!(locations[0].byte_offset, 100);
+    /// assert_eq!(locations[0].content_length_bytes, 164);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn record_offsets(&mut self) -> Result<Vec<RecordLocation>, Error> {
+        if self.index_table.is_some() || self.index_source.is_some() {
+            return Ok(self.index_table()?.locations().collect());
+        }
+        self.track_seeks();
+        let index_table = IndexTable::scan_shp(&mut self.source, &self.header)?;
+        Ok(index_table.locations().collect())
+    }
+
+    /// Builds the table of the offsets of the records by reading the header of each record
+    /// of the *.shp*, replacing the one of the index file (if any).
+    ///
+    /// Useful when the *.shx* is missing or corrupt, the rest of the records is skipped
+    /// using `seek`.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidShapeRecordSize` if the header of a record gives a size that is too
+    ///   small for a record or that goes past the end of the file given by the header.
+    /// - `Error::TruncatedFile` if the *.shp* ends before its last record.
+    ///
+    /// In both cases the table of the reader is left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let mut reader = shapefile::Reader::from_path("tests/data/linem.shp")?;
+    /// reader.rebuild_index()?;
+    /// assert!(reader.read_nth_shape(0).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn rebuild_index(&mut self) -> Result<(), Error> {
+        self.track_seeks();
+        let index_table = IndexTable::scan_shp(&mut self.source, &self.header)?;
+        self.index_table = Some(index_table);
+        self.index_source = None;
+        Ok(())
+    }
+
+    /// Writes the table of the offsets of the records (see [index_table](#method.index_table))
+    /// as a *.shx* file at `path`, for example after a [rebuild_index](#method.rebuild_index)
+    pub fn write_index<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
+        let header = self.header;
+        let index_table = self.index_table()?;
+        let mut dest = BufWriter::new(File::create(path)?);
+        index_table.write_to(&mut dest, &header)?;
+        dest.flush()?;
+        Ok(())
+    }
+
+    /// Reads the bounding box of each record, without reading the points,
+    /// as [iter_record_bboxes](#method.iter_record_bboxes) but without consuming the reader
+    ///
+    /// Returns the index of the shape (as used by `read_nth_shape`) with its bbox,
+    /// NullShapes have no bbox and are not returned.
+    /// The reader is put back at the first record.
+    #[cfg(feature = "rstar")]
+    pub(crate) fn read_record_bboxes(&mut self) -> Result<Vec<(usize, BBox)>, Error> {
+        self.track_seeks();
+        let bboxes = self.read_record_bboxes_from_start();
+        self.source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        bboxes
+    }
+
+    #[cfg(feature = "rstar")]
+    fn read_record_bboxes_from_start(&mut self) -> Result<Vec<(usize, BBox)>, Error> {
+        let file_length = self.header.file_length.max(0) as u64 * 2;
+        let mut records = RecordHeaders::new(&mut self.source, file_length)?;
+        let mut bboxes = Vec::<(usize, BBox)>::new();
+        let mut index = 0;
+        while let Some(record) = records.next() {
+            let (pos, hdr, _) = record?;
+            let content_start = pos + record::RecordHeader::SIZE as u64;
+            let content_size = hdr.record_size as u64 * 2;
+            let (bbox, _) =
+                read_record_bbox(records.source(), content_start, content_size, file_length)?;
+            if let Some(bbox) = bbox {
+                bboxes.push((index, bbox));
+            }
+            index += 1;
+        }
+        Ok(bboxes)
+    }
+
+    /// Remembers that the source is moved, so that the iterators created afterwards
+    /// seek back to the first record (the methods moving the source do not put it back
+    /// when they fail)
+    fn track_seeks(&mut self) {
+        self.seek_fn = Some(<T as Seek>::seek);
+    }
 }
 
 /// Function to read all the Shapes in a file.
@@ -519,7 +3613,9 @@ pub fn read<T: AsRef<Path>>(path: T) -> Result<Vec<Shape>, Error> {
 
 /// Function to read all the Shapes in a file as a certain type
 ///
-/// Fails and return `Err(Error:MismatchShapeType)`
+/// Fails and return an `Err(Error::RecordError)` wrapping an `Error::MismatchShapeType`
+/// if a shape of the file is not of the requested type, see
+/// [Reader::read_as](struct.Reader.html#method.read_as)
 ///
 ///  # Examples
 ///
@@ -538,5 +3634,82 @@ pub fn read_as<T: AsRef<Path>, S: ReadableShape>(path: T) -> Result<Vec<S>, Erro
     reader.read_as::<S>()
 }
 
+/// Function to read all the Shapes of a *.shp* held in memory as a certain type,
+/// see [Reader::read_as](struct.Reader.html#method.read_as)
+///
+/// # Examples
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// let bytes = std::fs::read("tests/data/polygon.shp")?;
+/// let polygons = shapefile::read_from_bytes::<shapefile::Polygon>(&bytes)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn read_from_bytes<S: ReadableShape>(shp: &[u8]) -> Result<Vec<S>, Error> {
+    Reader::from_seekable(Cursor::new(shp))?.read_as::<S>()
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_table_is_read_lazily() {
+        let mut reader = Reader::from_path("tests/data/line.shp").unwrap();
+        assert!(reader.index_source.is_some());
+        assert!(reader.index_table.is_none());
+
+        reader.read_nth_shape(0).unwrap().unwrap();
+        assert!(reader.index_source.is_none());
+        assert_eq!(reader.index_table().unwrap().len(), 1);
+        assert!(reader.read_nth_shape(0).unwrap().is_ok());
+    }
+
+    #[test]
+    fn index_table_without_index_source() {
+        let mut reader = Reader::from_path("tests/data/linem.shp").unwrap();
+        match reader.index_table() {
+            Err(Error::MissingIndexFile) => {}
+            _ => panic!("Expected MissingIndexFile"),
+        }
+    }
+    #[test]
+    fn index_source_is_kept_when_its_table_is_invalid() {
+        let shp = std::fs::read("tests/data/line.shp").unwrap();
+        let mut shx = std::fs::read("tests/data/line.shx").unwrap();
+        shx[104..108].copy_from_slice(&(-1i32).to_be_bytes());
+        let mut reader = Reader::from_seekable(Cursor::new(shp)).unwrap();
+        reader.add_index_source(Cursor::new(shx)).unwrap();
+        match reader.index_table() {
+            Err(Error::InvalidIndexEntry { index: 0, .. }) => {}
+            _ => panic!("Expected InvalidIndexEntry"),
+        }
+        assert!(reader.index_source.is_some());
+        assert!(reader.has_index());
+    }
+
+    #[test]
+    fn content_buffer_is_reused_between_records() {
+        // A large record followed by smaller ones, read before being parsed
+        let lines = [5_000, 2, 3]
+            .iter()
+            .map(|&size| {
+                let points = (0..size).map(|i| record::Point::new(i as f64, 1.0)).collect();
+                record::Polyline::new(points, vec![0])
+            })
+            .collect::<Vec<_>>();
+        let mut writer = ::Writer::new(Cursor::new(Vec::<u8>::new()));
+        writer.write_shapes(lines).unwrap();
+        let reader = Reader::new(Cursor::new(writer.dest.into_inner()))
+            .unwrap()
+            .keep_unknown_shapes(true);
+        let mut chunks = reader.read_chunks_as::<record::Polyline>(1);
+
+        assert_eq!(chunks.next().unwrap().unwrap()[0].points.len(), 5_000);
+        let buffer = chunks.iter.content_buffer.as_ptr();
+        assert!(chunks.iter.content_buffer.capacity() >= 5_000 * 16);
+        assert_eq!(chunks.by_ref().map(|chunk| chunk.unwrap().len()).sum::<usize>(), 2);
+        assert_eq!(chunks.iter.content_buffer.as_ptr(), buffer);
+    }
+}
@@ -0,0 +1,234 @@
+//! Splitting of the polylines and polygons crossing the antimeridian (±180° of longitude),
+//! used by [Shape::split_at_antimeridian](../enum.Shape.html#method.split_at_antimeridian)
+use record::poly::{GenericPolygon, GenericPolyline};
+use record::ring_signed_area;
+use record::traits::{HasMutXY, HasXY, Interpolate};
+use record::Point;
+use Error;
+
+/// Longitude of the antimeridian
+const ANTIMERIDIAN: f64 = 180.0;
+
+/// Points of a segment crossing the antimeridian, that can be interpolated
+/// and placed on the antimeridian
+pub(crate) trait InterpolatePoint: Interpolate + HasMutXY {}
+
+impl<P: Interpolate + HasMutXY> InterpolatePoint for P {}
+
+/// Returns the points where the segment from `a` to `b` crosses the antimeridian,
+/// as the last point on the side of `a` and the first point on the side of `b`.
+///
+/// A segment crosses the antimeridian when its x delta exceeds 180,
+/// its shortest way around the globe then goes through the antimeridian.
+fn crossing<P: InterpolatePoint>(a: &P, b: &P) -> Option<(P, P)> {
+    let dx = b.x() - a.x();
+    if dx.abs() <= ANTIMERIDIAN {
+        return None;
+    }
+    let edge = if dx < 0.0 { ANTIMERIDIAN } else { -ANTIMERIDIAN };
+    let unwrapped_bx = b.x() + 2.0 * edge;
+    let t = (edge - a.x()) / (unwrapped_bx - a.x());
+    let mut end = a.interpolate(b, t);
+    *end.x_mut() = edge;
+    let mut start = end;
+    *start.x_mut() = -edge;
+    Some((end, start))
+}
+
+/// Returns the start and end of each part, `Error::MalformedShape` if the parts are invalid
+fn part_ranges(num_points: usize, parts: &[i32]) -> Result<Vec<(usize, usize)>, Error> {
+    let mut ranges = Vec::with_capacity(parts.len());
+    for (i, start) in parts.iter().enumerate() {
+        let end = parts.get(i + 1).map_or(num_points as i32, |end| *end);
+        if *start < 0 || end < *start || end as usize > num_points {
+            return Err(Error::MalformedShape);
+        }
+        ranges.push((*start as usize, end as usize));
+    }
+    Ok(ranges)
+}
+
+fn crosses<P: InterpolatePoint>(points: &[P], ranges: &[(usize, usize)]) -> bool {
+    ranges.iter().any(|(start, end)| {
+        points[*start..*end]
+            .windows(2)
+            .any(|pts| crossing(&pts[0], &pts[1]).is_some())
+    })
+}
+
+/// Splits the line into pieces that do not cross the antimeridian
+fn split_line<P: InterpolatePoint>(line: &[P]) -> Vec<Vec<P>> {
+    let mut pieces = Vec::<Vec<P>>::new();
+    let mut current = Vec::<P>::with_capacity(line.len());
+    for (i, point) in line.iter().enumerate() {
+        if let Some((end, start)) = line.get(i.wrapping_sub(1)).and_then(|p| crossing(p, point)) {
+            current.push(end);
+            pieces.push(std::mem::replace(&mut current, vec![start]));
+        }
+        current.push(*point);
+    }
+    pieces.push(current);
+    pieces
+}
+
+/// Returns the signed area of the ring (positive if clockwise)
+/// once its x are unwrapped so that it does not cross the antimeridian
+fn unwrapped_signed_area<P: HasXY>(ring: &[P]) -> f64 {
+    let mut shift = 0.0;
+    let mut unwrapped = Vec::<Point>::with_capacity(ring.len());
+    for (i, point) in ring.iter().enumerate() {
+        if i > 0 {
+            let dx = point.x() - ring[i - 1].x();
+            if dx > ANTIMERIDIAN {
+                shift -= 2.0 * ANTIMERIDIAN;
+            } else if dx < -ANTIMERIDIAN {
+                shift += 2.0 * ANTIMERIDIAN;
+            }
+        }
+        unwrapped.push(Point::new(point.x() + shift, point.y()));
+    }
+    ring_signed_area(&unwrapped)
+}
+
+/// Joins the pieces of a ring that are on the same side of the antimeridian into closed rings.
+///
+/// Each piece starts and ends on the antimeridian, it is followed by the nearest piece
+/// starting after its end when going along the antimeridian (southward if `southward`),
+/// or closed if its own start is nearer.
+fn stitch<P: InterpolatePoint>(mut pieces: Vec<Vec<P>>, southward: bool) -> Vec<Vec<P>> {
+    let mut rings = Vec::<Vec<P>>::with_capacity(pieces.len());
+    while let Some(mut ring) = pieces.pop() {
+        loop {
+            let end = ring[ring.len() - 1].y();
+            let distance = |y: f64| if southward { end - y } else { y - end };
+            let to_start = distance(ring[0].y());
+            let next = pieces
+                .iter()
+                .enumerate()
+                .map(|(i, piece)| (i, distance(piece[0].y())))
+                .filter(|(_, d)| *d >= 0.0 && (to_start < 0.0 || *d < to_start))
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            match next {
+                Some((i, _)) => ring.extend(pieces.swap_remove(i)),
+                None => {
+                    let first = ring[0];
+                    ring.push(first);
+                    break;
+                }
+            }
+        }
+        rings.push(ring);
+    }
+    rings
+}
+
+/// Splits the (closed) ring into rings that do not cross the antimeridian.
+///
+/// Rings crossing it an odd number of times (around a pole) are kept as is.
+fn split_ring<P: InterpolatePoint>(ring: &[P]) -> Vec<Vec<P>> {
+    let mut pieces = split_line(ring);
+    if pieces.len() % 2 == 0 {
+        return vec![ring.to_vec()];
+    }
+    if pieces.len() == 1 {
+        return pieces;
+    }
+    // The ring is closed, so its last piece continues with its first one
+    let last = pieces.pop().unwrap();
+    let first = std::mem::replace(&mut pieces[0], last);
+    pieces[0].extend_from_slice(&first[1..]);
+
+    // Going along the antimeridian with the inside of the ring on the right
+    let is_clockwise = unwrapped_signed_area(ring) >= 0.0;
+    let (east, west): (Vec<Vec<P>>, Vec<Vec<P>>) = pieces
+        .into_iter()
+        .partition(|piece| piece[piece.len() - 1].x() > 0.0);
+    let mut rings = stitch(east, is_clockwise);
+    rings.extend(stitch(west, !is_clockwise));
+    rings
+}
+
+/// Returns the polyline with its parts split at the antimeridian,
+/// `None` if it does not cross it
+pub(crate) fn split_polyline<P: InterpolatePoint>(
+    polyline: &GenericPolyline<P>,
+) -> Result<Option<GenericPolyline<P>>, Error> {
+    let ranges = part_ranges(polyline.points.len(), &polyline.parts)?;
+    if !crosses(&polyline.points, &ranges) {
+        return Ok(None);
+    }
+    let mut points = Vec::<P>::with_capacity(polyline.points.len());
+    let mut parts = Vec::<i32>::with_capacity(polyline.parts.len());
+    for (start, end) in ranges {
+        for piece in split_line(&polyline.points[start..end]) {
+            parts.push(points.len() as i32);
+            points.extend(piece);
+        }
+    }
+    let mut split = GenericPolyline::new(points, parts);
+    split.m_present = polyline.m_present;
+    Ok(Some(split))
+}
+
+/// Returns the polygon with its rings split at the antimeridian and closed along it,
+/// `None` if it does not cross it
+pub(crate) fn split_polygon<P: InterpolatePoint + PartialEq>(
+    polygon: &GenericPolygon<P>,
+) -> Result<Option<GenericPolygon<P>>, Error> {
+    let ranges = part_ranges(polygon.points.len(), &polygon.parts)?;
+    if !crosses(&polygon.points, &ranges) {
+        return Ok(None);
+    }
+    let mut points = Vec::<P>::with_capacity(polygon.points.len());
+    let mut parts = Vec::<i32>::with_capacity(polygon.parts.len());
+    for (i, (start, end)) in ranges.into_iter().enumerate() {
+        let ring = &polygon.points[start..end];
+        if ring.first() != ring.last() {
+            return Err(Error::UnclosedRing(i));
+        }
+        for ring in split_ring(ring) {
+            parts.push(points.len() as i32);
+            points.extend(ring);
+        }
+    }
+    let mut split = GenericPolygon::new(points, parts);
+    split.m_present = polygon.m_present;
+    Ok(Some(split))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use constants::NO_DATA;
+    use record::{PointM, PointZ};
+
+    #[test]
+    fn crossing_interpolates_z_and_m() {
+        let a = PointZ::new(170.0, 0.0, 10.0, NO_DATA);
+        let b = PointZ::new(-170.0, 10.0, 30.0, 4.0);
+        let (end, start) = crossing(&a, &b).unwrap();
+        assert_eq!(end, PointZ::new(180.0, 5.0, 20.0, NO_DATA));
+        assert_eq!(start, PointZ::new(-180.0, 5.0, 20.0, NO_DATA));
+
+        let a = PointM::new(-175.0, 0.0, 0.0);
+        let (end, start) = crossing(&a, &PointM::new(175.0, 4.0, 8.0)).unwrap();
+        assert_eq!(end, PointM::new(-180.0, 2.0, 4.0));
+        assert_eq!(start, PointM::new(180.0, 2.0, 4.0));
+
+        assert!(crossing(&Point::new(-90.0, 0.0), &Point::new(90.0, 0.0)).is_none());
+    }
+
+    #[test]
+    fn split_line_pieces() {
+        let line = [
+            Point::new(170.0, 0.0),
+            Point::new(-170.0, 0.0),
+            Point::new(-160.0, 0.0),
+            Point::new(160.0, 0.0),
+        ];
+        let pieces = split_line(&line);
+        assert_eq!(pieces.len(), 3);
+        assert_eq!(pieces[0], [Point::new(170.0, 0.0), Point::new(180.0, 0.0)]);
+        assert_eq!(pieces[2], [Point::new(180.0, 0.0), Point::new(160.0, 0.0)]);
+    }
+}
@@ -0,0 +1,315 @@
+//! Module with views of shapes that borrow their points and parts
+//! from the bytes of the file instead of copying them.
+//!
+//! They are created by
+//! [Reader::iter_shape_refs](../../reader/struct.Reader.html#method.iter_shape_refs)
+//! for shapefiles held in memory (e.g. read in a buffer, or memory mapped).
+//!
+//! The points of the file are written as little endian f64, and are only 4-bytes aligned
+//! in the file, so they can only be borrowed on little endian hosts and when the bytes
+//! happen to be aligned for a `Point`. Otherwise they are copied, which is why the views
+//! hold a `Cow`.
+//!
+//! Only the x and y of the points are stored contiguously in the file, so only
+//! Polyline, Polygon and Multipoint records have views, the records of other
+//! shape types are read as [Shape](../enum.Shape.html).
+use std::borrow::Cow;
+use std::mem::{align_of, size_of};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use record::multipoint::Multipoint;
+use record::poly::{Polygon, Polyline};
+use record::{read_record_content, BBox, Point, RecordSizeCheck, Shape};
+use {Error, ShapeType};
+
+/// View of a [Polyline](../poly/type.Polyline.html) record
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolylineRef<'a> {
+    pub bbox: BBox,
+    pub points: Cow<'a, [Point]>,
+    pub parts: Cow<'a, [i32]>,
+}
+
+impl<'a> PolylineRef<'a> {
+    /// Copies the view into a Polyline
+    pub fn to_owned(&self) -> Polyline {
+        Polyline {
+            bbox: self.bbox,
+            points: self.points.to_vec(),
+            parts: self.parts.to_vec(),
+            m_present: false,
+        }
+    }
+}
+
+/// View of a [Polygon](../poly/type.Polygon.html) record
+#[derive(Debug, Clone, PartialEq)]
+pub struct PolygonRef<'a> {
+    pub bbox: BBox,
+    pub points: Cow<'a, [Point]>,
+    pub parts: Cow<'a, [i32]>,
+}
+
+impl<'a> PolygonRef<'a> {
+    /// Copies the view into a Polygon
+    pub fn to_owned(&self) -> Polygon {
+        Polygon {
+            bbox: self.bbox,
+            points: self.points.to_vec(),
+            parts: self.parts.to_vec(),
+            m_present: false,
+        }
+    }
+}
+
+/// View of a [Multipoint](../multipoint/type.Multipoint.html) record
+#[derive(Debug, Clone, PartialEq)]
+pub struct MultipointRef<'a> {
+    pub bbox: BBox,
+    pub points: Cow<'a, [Point]>,
+}
+
+impl<'a> MultipointRef<'a> {
+    /// Copies the view into a Multipoint
+    pub fn to_owned(&self) -> Multipoint {
+        Multipoint {
+            bbox: self.bbox,
+            points: self.points.to_vec(),
+            m_present: false,
+        }
+    }
+}
+
+/// A record read by [iter_shape_refs](../../reader/struct.Reader.html#method.iter_shape_refs)
+#[derive(Clone)]
+pub enum ShapeRef<'a> {
+    Polyline(PolylineRef<'a>),
+    Polygon(PolygonRef<'a>),
+    Multipoint(MultipointRef<'a>),
+    /// The records of the other shape types, which points can not be borrowed
+    Owned(Shape),
+}
+
+impl<'a> ShapeRef<'a> {
+    /// Copies the view into a Shape
+    pub fn to_owned(&self) -> Shape {
+        match self {
+            ShapeRef::Polyline(shp) => Shape::Polyline(shp.to_owned()),
+            ShapeRef::Polygon(shp) => Shape::Polygon(shp.to_owned()),
+            ShapeRef::Multipoint(shp) => Shape::Multipoint(shp.to_owned()),
+            ShapeRef::Owned(shp) => shp.clone(),
+        }
+    }
+
+    /// Returns the type of the shape of the record
+    pub fn shapetype(&self) -> ShapeType {
+        match self {
+            ShapeRef::Polyline(_) => ShapeType::Polyline,
+            ShapeRef::Polygon(_) => ShapeType::Polygon,
+            ShapeRef::Multipoint(_) => ShapeType::Multipoint,
+            ShapeRef::Owned(shp) => shp.shapetype(),
+        }
+    }
+}
+
+/// Whether the host stores its values in little endian, the byte order of the values
+/// of the records. The views are read with the byte order of the host given as a parameter
+/// so that the tests also go through the copies made on big endian hosts.
+const IS_LITTLE_ENDIAN_HOST: bool = cfg!(target_endian = "little");
+
+/// Returns true if the bytes can be used as a slice of `T` as they are
+fn can_borrow_as<T>(bytes: &[u8], is_little_endian_host: bool) -> bool {
+    is_little_endian_host && (bytes.as_ptr() as usize) % align_of::<T>() == 0
+}
+
+fn points_of(bytes: &[u8], is_little_endian_host: bool) -> Cow<'_, [Point]> {
+    let num_points = bytes.len() / size_of::<Point>();
+    if can_borrow_as::<Point>(bytes, is_little_endian_host) {
+        // SAFETY: Point is repr(C) with two f64 (so without padding), any bits are a valid f64,
+        // the bytes are aligned for Point and hold `num_points` points in the byte order
+        // of the host
+        let points = unsafe {
+            std::slice::from_raw_parts(bytes.as_ptr() as *const Point, num_points)
+        };
+        Cow::Borrowed(points)
+    } else {
+        let points = bytes
+            .chunks_exact(size_of::<Point>())
+            .map(|xy| {
+                Point::new(
+                    LittleEndian::read_f64(&xy[..8]),
+                    LittleEndian::read_f64(&xy[8..]),
+                )
+            })
+            .collect();
+        Cow::Owned(points)
+    }
+}
+
+fn parts_of(bytes: &[u8], is_little_endian_host: bool) -> Cow<'_, [i32]> {
+    let num_parts = bytes.len() / size_of::<i32>();
+    if can_borrow_as::<i32>(bytes, is_little_endian_host) {
+        // SAFETY: any bits are a valid i32, the bytes are aligned for i32
+        // and hold `num_parts` i32 in the byte order of the host
+        let parts = unsafe { std::slice::from_raw_parts(bytes.as_ptr() as *const i32, num_parts) };
+        Cow::Borrowed(parts)
+    } else {
+        let parts = bytes
+            .chunks_exact(size_of::<i32>())
+            .map(LittleEndian::read_i32)
+            .collect();
+        Cow::Owned(parts)
+    }
+}
+
+/// Returns the number of elements read at `pos` (as a little endian i32),
+/// which must be positive
+fn count_at(content: &[u8], pos: usize) -> Result<u64, Error> {
+    let count = content
+        .get(pos..pos + 4)
+        .map(LittleEndian::read_i32)
+        .ok_or(Error::InvalidShapeRecordSize)?;
+    if count < 0 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    Ok(count as u64)
+}
+
+/// Reads the content of a polyline or polygon record (they have the same layout)
+fn read_poly(content: &[u8], is_little_endian_host: bool) -> Result<PolylineRef<'_>, Error> {
+    // shape type, bbox, number of parts and number of points
+    let parts_start = 4 + 32 + 8;
+    let num_parts = count_at(content, 36)?;
+    let num_points = count_at(content, 40)?;
+    let points_start = parts_start + num_parts * size_of::<i32>() as u64;
+    if content.len() as u64 != points_start + num_points * size_of::<Point>() as u64 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    let (parts_start, points_start) = (parts_start as usize, points_start as usize);
+    Ok(PolylineRef {
+        bbox: BBox::read_from(&content[4..36])?,
+        points: points_of(&content[points_start..], is_little_endian_host),
+        parts: parts_of(&content[parts_start..points_start], is_little_endian_host),
+    })
+}
+
+/// Reads the view of the shape of a record from its content (starting with the shape type)
+pub(crate) fn read_shape_ref(content: &[u8]) -> Result<ShapeRef<'_>, Error> {
+    read_shape_ref_on(content, IS_LITTLE_ENDIAN_HOST)
+}
+
+fn read_shape_ref_on(content: &[u8], is_little_endian_host: bool) -> Result<ShapeRef<'_>, Error> {
+    match ShapeType::read_from(&mut &content[..])? {
+        ShapeType::Polyline => read_poly(content, is_little_endian_host).map(ShapeRef::Polyline),
+        ShapeType::Polygon => {
+            let poly = read_poly(content, is_little_endian_host)?;
+            Ok(ShapeRef::Polygon(PolygonRef {
+                bbox: poly.bbox,
+                points: poly.points,
+                parts: poly.parts,
+            }))
+        }
+        ShapeType::Multipoint => {
+            let num_points = count_at(content, 36)?;
+            if content.len() as u64 != 40 + num_points * size_of::<Point>() as u64 {
+                return Err(Error::InvalidShapeRecordSize);
+            }
+            Ok(ShapeRef::Multipoint(MultipointRef {
+                bbox: BBox::read_from(&content[4..36])?,
+                points: points_of(&content[40..], is_little_endian_host),
+            }))
+        }
+        _ => {
+            let shape = read_record_content::<_, Shape>(
+                &mut &content[..],
+                content.len() as u64,
+                RecordSizeCheck::Exact,
+            )?;
+            Ok(ShapeRef::Owned(shape))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use byteorder::WriteBytesExt;
+    use record::EsriShape;
+
+    /// Returns the content of the record of the shape (starting with the shape type),
+    /// copied at the start of `buffer[offset..]` so that the points it holds at
+    /// `points_start` are aligned for a `Point`
+    fn aligned_content<S: EsriShape>(shape: S, buffer: &mut Vec<u8>, points_start: usize) -> usize {
+        let mut content = Vec::<u8>::new();
+        content.write_i32::<LittleEndian>(S::shapetype() as i32).unwrap();
+        shape.write_to(&mut content).unwrap();
+        buffer.clear();
+        buffer.resize(content.len() + align_of::<Point>(), 0);
+        let misalignment = (buffer.as_ptr() as usize + points_start) % align_of::<Point>();
+        let offset = (align_of::<Point>() - misalignment) % align_of::<Point>();
+        buffer[offset..offset + content.len()].copy_from_slice(&content);
+        buffer.truncate(offset + content.len());
+        offset
+    }
+
+    /// Returns the points of the view
+    fn points_of_view<'a>(shape: &'a ShapeRef) -> &'a Cow<'a, [Point]> {
+        match shape {
+            ShapeRef::Polyline(shp) => &shp.points,
+            ShapeRef::Polygon(shp) => &shp.points,
+            ShapeRef::Multipoint(shp) => &shp.points,
+            ShapeRef::Owned(_) => panic!("Expected a view"),
+        }
+    }
+
+    /// Reads the content with the byte order of both hosts: the views are copies
+    /// on big endian hosts, borrowed on little endian ones, and have the same points
+    fn check_on_both_hosts(content: &[u8], expected: &[Point]) {
+        let on_big_endian = read_shape_ref_on(content, false).unwrap();
+        let points = points_of_view(&on_big_endian);
+        assert!(matches!(points, Cow::Owned(_)));
+        assert_eq!(&points[..], expected);
+
+        // The bytes of the file are only the values of the points on little endian hosts
+        if IS_LITTLE_ENDIAN_HOST {
+            let on_little_endian = read_shape_ref_on(content, true).unwrap();
+            let points = points_of_view(&on_little_endian);
+            assert!(matches!(points, Cow::Borrowed(_)));
+            assert_eq!(&points[..], expected);
+        }
+    }
+
+    #[test]
+    fn polyline_views_on_both_hosts() {
+        let points = vec![Point::new(1.5, -2.0), Point::new(3.25, 4.0)];
+        let polyline = Polyline::new(points, vec![0]);
+        let mut buffer = Vec::<u8>::new();
+        // shape type, bbox, numbers of parts and points, one part
+        let offset = aligned_content(polyline.clone(), &mut buffer, 48);
+        check_on_both_hosts(&buffer[offset..], &polyline.points);
+    }
+
+    #[test]
+    fn polygon_views_on_both_hosts() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(0.0, 0.0),
+        ];
+        let polygon = Polygon::new(points, vec![0]);
+        let mut buffer = Vec::<u8>::new();
+        let offset = aligned_content(polygon.clone(), &mut buffer, 48);
+        check_on_both_hosts(&buffer[offset..], &polygon.points);
+    }
+
+    #[test]
+    fn multipoint_views_on_both_hosts() {
+        let multipoint = Multipoint::new(vec![Point::new(-1.0, 2.5), Point::new(7.0, 0.125)]);
+        let mut buffer = Vec::<u8>::new();
+        // shape type, bbox and number of points
+        let offset = aligned_content(multipoint.clone(), &mut buffer, 40);
+        check_on_both_hosts(&buffer[offset..], &multipoint.points);
+    }
+}
@@ -0,0 +1,90 @@
+//! Canonical encoding of the values of the shapes, used by
+//! [deterministic](../../writer/struct.Writer.html#method.deterministic) writers
+use constants::{is_no_data, NO_DATA};
+use record::traits::HasXY;
+use record::{BBox, Point, PointM, PointZ};
+
+/// Returns the value with its zero written as a positive zero
+fn canonical_value(value: f64) -> f64 {
+    if value == 0.0 {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Returns the measure with all its NO_DATA values (and NaN) written as
+/// [NO_DATA](../../constants/constant.NO_DATA.html)
+fn canonical_measure(m: f64) -> f64 {
+    if m.is_nan() || is_no_data(m) {
+        NO_DATA
+    } else {
+        canonical_value(m)
+    }
+}
+
+/// Points whose values can be written canonically
+pub(crate) trait CanonicalPoint {
+    fn make_canonical(&mut self);
+}
+
+impl CanonicalPoint for Point {
+    fn make_canonical(&mut self) {
+        self.x = canonical_value(self.x);
+        self.y = canonical_value(self.y);
+    }
+}
+
+impl CanonicalPoint for PointM {
+    fn make_canonical(&mut self) {
+        self.x = canonical_value(self.x);
+        self.y = canonical_value(self.y);
+        self.m = canonical_measure(self.m);
+    }
+}
+
+impl CanonicalPoint for PointZ {
+    fn make_canonical(&mut self) {
+        self.x = canonical_value(self.x);
+        self.y = canonical_value(self.y);
+        self.z = canonical_value(self.z);
+        self.m = canonical_measure(self.m);
+    }
+}
+
+/// Makes the points canonical and returns their bbox
+pub(crate) fn canonicalize_points<P: CanonicalPoint + HasXY>(points: &mut [P]) -> BBox {
+    for point in points.iter_mut() {
+        point.make_canonical();
+    }
+    BBox::from_points(points)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_values() {
+        assert_eq!(canonical_value(-0.0).to_bits(), 0.0f64.to_bits());
+        assert_eq!(canonical_value(-1.5), -1.5);
+        assert_eq!(canonical_measure(f64::NAN).to_bits(), NO_DATA.to_bits());
+        assert_eq!(canonical_measure(f64::MIN).to_bits(), NO_DATA.to_bits());
+        assert_eq!(canonical_measure(-0.0).to_bits(), 0.0f64.to_bits());
+        assert_eq!(canonical_measure(3.0), 3.0);
+    }
+
+    #[test]
+    fn canonical_points() {
+        let mut points = vec![
+            PointZ::new(-0.0, 1.0, -0.0, f64::NAN),
+            PointZ::new(2.0, -0.0, 3.0, 4.0),
+        ];
+        let bbox = canonicalize_points(&mut points);
+        assert_eq!(points[0].x.to_bits(), 0.0f64.to_bits());
+        assert_eq!(points[0].z.to_bits(), 0.0f64.to_bits());
+        assert_eq!(points[0].m.to_bits(), NO_DATA.to_bits());
+        assert_eq!(points[1].y.to_bits(), 0.0f64.to_bits());
+        assert_eq!(bbox.ymin.to_bits(), 0.0f64.to_bits());
+    }
+}
@@ -0,0 +1,94 @@
+//! Distances from a point to the parts of polylines and polygons, used by their
+//! `distance_to_point` and `closest_point_on` methods
+use record::ring_contains;
+use record::traits::{HasXY, Interpolate};
+
+/// Returns the position (from 0 to 1) on the segment from `a` to `b` of the point
+/// closest to (x, y), 0 for a segment of zero length (which would give NaN otherwise)
+fn position_on_segment<P: HasXY>(a: &P, b: &P, x: f64, y: f64) -> f64 {
+    let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+    let squared_length = dx * dx + dy * dy;
+    if squared_length == 0.0 {
+        return 0.0;
+    }
+    (((x - a.x()) * dx + (y - a.y()) * dy) / squared_length).clamp(0.0, 1.0)
+}
+
+fn distance_between<P: HasXY>(point: &P, x: f64, y: f64) -> f64 {
+    (x - point.x()).hypot(y - point.y())
+}
+
+/// Returns the distance from (x, y) to the part, a part of a single point being that point
+fn distance_to_part<P: HasXY>(part: &[P], x: f64, y: f64) -> f64 {
+    if let [point] = part {
+        return distance_between(point, x, y);
+    }
+    part.windows(2)
+        .map(|segment| {
+            let (a, b) = (&segment[0], &segment[1]);
+            let t = position_on_segment(a, b, x, y);
+            let closest_x = a.x() + t * (b.x() - a.x());
+            let closest_y = a.y() + t * (b.y() - a.y());
+            (x - closest_x).hypot(y - closest_y)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Returns the minimum distance from (x, y) to the parts, infinity if they have no points
+pub(crate) fn distance_to_parts<'a, P, I>(parts: I, x: f64, y: f64) -> f64
+where
+    P: HasXY + 'a,
+    I: Iterator<Item = &'a [P]>,
+{
+    parts
+        .map(|part| distance_to_part(part, x, y))
+        .fold(f64::INFINITY, f64::min)
+}
+
+/// Returns whether (x, y) is inside the rings, using the even-odd rule
+/// (a point in a hole is outside)
+pub(crate) fn rings_contain<'a, P, I>(rings: I, x: f64, y: f64) -> bool
+where
+    P: HasXY + 'a,
+    I: Iterator<Item = &'a [P]>,
+{
+    rings.filter(|ring| ring_contains(ring, x, y)).count() % 2 == 1
+}
+
+/// Returns the point of the parts closest to (x, y), with its distance and the index
+/// of its part, `None` if the parts have no points.
+///
+/// Ties are resolved in favor of the first part (and of the first segment of a part).
+pub(crate) fn closest_point_on_parts<'a, P, I>(parts: I, x: f64, y: f64) -> Option<(P, f64, usize)>
+where
+    P: Interpolate + 'a,
+    I: Iterator<Item = &'a [P]>,
+{
+    let mut closest: Option<(P, f64, usize)> = None;
+    let mut consider = |point: P, index: usize| {
+        let distance = distance_between(&point, x, y);
+        if closest.map_or(true, |(_, closest_distance, _)| distance < closest_distance) {
+            closest = Some((point, distance, index));
+        }
+    };
+    for (index, part) in parts.enumerate() {
+        if let [point] = part {
+            consider(*point, index);
+        }
+        for segment in part.windows(2) {
+            let (a, b) = (&segment[0], &segment[1]);
+            // The ends are returned as is, so that their m is not lost
+            // if the m of the other end is NO_DATA
+            let t = position_on_segment(a, b, x, y);
+            let point = if t == 0.0 {
+                *a
+            } else if t == 1.0 {
+                *b
+            } else {
+                a.interpolate(b, t)
+            };
+            consider(point, index);
+        }
+    }
+    closest
+}
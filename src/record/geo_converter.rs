@@ -0,0 +1,137 @@
+//! Module with the [GeoConverter](struct.GeoConverter.html), which converts polygons
+//! to geo-types reusing the buffers of the coordinates between the conversions
+//!
+//! Converting a polygon allocates a `Vec` for the coordinates of each of its rings.
+//! When many polygons are converted, processed and dropped one after the other
+//! (e.g. the records of a large coastline file), giving the converted polygons back to the
+//! converter with [recycle](struct.GeoConverter.html#method.recycle) lets the next
+//! conversions reuse these buffers instead of allocating new ones.
+//!
+//! # Example
+//!
+//! ```
+//! # fn main() -> Result<(), shapefile::Error> {
+//! use shapefile::{GeoConverter, Polygon};
+//! let polygons = shapefile::read_as::<_, Polygon>("tests/data/multi_polygon.shp")?;
+//! let mut converter = GeoConverter::new();
+//! for polygon in &polygons {
+//!     let multi_polygon = converter.convert_polygon(polygon)?;
+//!     assert!(!multi_polygon.0.is_empty());
+//!     converter.recycle(multi_polygon);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+use geo_types::{Coordinate, LineString, MultiPolygon, Polygon};
+
+use record::is_outer_ring;
+use record::poly::GenericPolygon;
+use record::traits::{HasXY, MultipartShape};
+use Error;
+
+/// Converter of polygons to geo-types which keeps the buffers of the coordinates
+/// of the polygons given back to it, see the [module](index.html) documentation
+///
+/// The polygons it returns are the same as the ones returned by the `TryFrom` conversions.
+#[derive(Default)]
+pub struct GeoConverter {
+    /// Buffers of coordinates of the rings recycled, reused by the next conversions
+    buffers: Vec<Vec<Coordinate<f64>>>,
+}
+
+impl GeoConverter {
+    /// Creates a converter without any buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts the polygon to a geo_types MultiPolygon,
+    /// the rings are built in the recycled buffers when there are some
+    ///
+    /// # Errors
+    ///
+    /// `Error::OrphanInnerRing` if an inner ring comes before any outer ring
+    pub fn convert_polygon<PointType>(
+        &mut self,
+        polygon: &GenericPolygon<PointType>,
+    ) -> Result<MultiPolygon<f64>, Error>
+    where
+        PointType: HasXY + Copy,
+        Coordinate<f64>: From<PointType>,
+    {
+        let buffers = &mut self.buffers;
+        multi_polygon_from_rings(polygon, || buffers.pop().unwrap_or_default())
+    }
+
+    /// Gives back the buffers of the rings of a multi polygon that is no longer needed,
+    /// for the next conversions
+    ///
+    /// The next conversion takes the buffers in the order of the rings of this multi polygon,
+    /// so that polygons of similar shapes converted in a row reuse buffers of the right size.
+    pub fn recycle(&mut self, multi_polygon: MultiPolygon<f64>) {
+        for polygon in multi_polygon.0.into_iter().rev() {
+            let (exterior, interiors) = polygon.into_inner();
+            self.buffers.extend(interiors.into_iter().rev().map(|interior| interior.0));
+            self.buffers.push(exterior.0);
+        }
+    }
+
+    /// Returns the number of buffers kept for the next conversions
+    pub fn num_buffers(&self) -> usize {
+        self.buffers.len()
+    }
+}
+
+/// Returns the coordinates of the ring in the `buffer`, closed like geo_types closes the
+/// rings of its polygons, with the exact capacity if the buffer has to grow
+pub(crate) fn ring_to_line_string<PointType>(
+    ring: &[PointType],
+    mut buffer: Vec<Coordinate<f64>>,
+) -> LineString<f64>
+where
+    PointType: Copy,
+    Coordinate<f64>: From<PointType>,
+{
+    buffer.clear();
+    let first = ring.first().map(|p| Coordinate::from(*p));
+    let is_open = first != ring.last().map(|p| Coordinate::from(*p));
+    buffer.reserve_exact(ring.len() + is_open as usize);
+    buffer.extend(ring.iter().map(|p| Coordinate::from(*p)));
+    if let (true, Some(first)) = (is_open, first) {
+        buffer.push(first);
+    }
+    LineString(buffer)
+}
+
+/// Converts the polygon to a MultiPolygon: each outer ring starts a polygon
+/// and the inner rings are the interiors of the outer ring before them,
+/// the coordinates of the rings are built in the buffers given by `new_buffer`
+pub(crate) fn multi_polygon_from_rings<PointType, F>(
+    polygon: &GenericPolygon<PointType>,
+    mut new_buffer: F,
+) -> Result<MultiPolygon<f64>, Error>
+where
+    PointType: HasXY + Copy,
+    Coordinate<f64>: From<PointType>,
+    F: FnMut() -> Vec<Coordinate<f64>>,
+{
+    let mut last_poly = None;
+    let mut polygons = Vec::<Polygon<f64>>::new();
+    for ring in polygon.parts() {
+        let line_string = ring_to_line_string(ring, new_buffer());
+        if is_outer_ring(ring) {
+            let new_poly = Polygon::new(line_string, vec![]);
+            if let Some(poly) = last_poly.replace(new_poly) {
+                polygons.push(poly);
+            }
+        } else if let Some(ref mut poly) = last_poly {
+            poly.interiors_push(line_string);
+        } else {
+            return Err(Error::OrphanInnerRing);
+        }
+    }
+    if let Some(poly) = last_poly {
+        polygons.push(poly);
+    }
+    Ok(MultiPolygon(polygons))
+}
@@ -2,8 +2,142 @@ use std::io::{Read, Write};
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
-use record::traits::{HasM, HasMutXY, HasXY};
-use record::{is_no_data, PointZ, NO_DATA};
+use record::traits::{HasM, HasMutXY, HasXY, HasZ};
+use constants::{is_no_data, NO_DATA};
+use record::{PointZ, RecordSizeCheck};
+use Error;
+
+/// Whether a record contains the optional M values (and M range)
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) enum MLayout {
+    /// The record has no M values
+    Absent,
+    /// The record has the M values
+    Present,
+    /// The record is as large as the shape with its M values, padding being allowed:
+    /// the bytes after the Z values are either the M values or padding
+    MaybePadding,
+}
+
+/// Returns whether a record contains the optional M values (and M range),
+/// which is allowed to be omitted by the specification.
+///
+/// `size_of_record` gives the expected size of the record
+/// depending on whether the M values are present.
+///
+/// A record of exactly the size without the M values does not have them.
+/// With `RecordSizeCheck::AtLeast`, a record as large as or larger than the size with
+/// the M values may hold a shape without them followed by padding: `MLayout::MaybePadding`,
+/// the values read decide (see [read_optional_ms_into](fn.read_optional_ms_into.html)).
+///
+/// Returns `Error::InvalidShapeRecordSize` if the `record_size` matches none of the two
+/// (with the `size_check`, a larger record matches).
+pub(crate) fn m_layout_of_record<F>(
+    record_size: u64,
+    size_check: RecordSizeCheck,
+    size_of_record: F,
+) -> Result<MLayout, Error>
+where
+    F: Fn(bool) -> usize,
+{
+    let with_m = size_of_record(true);
+    let without_m = size_of_record(false);
+    if record_size == without_m as u64 {
+        Ok(MLayout::Absent)
+    } else if size_check == RecordSizeCheck::Exact && record_size == with_m as u64 {
+        Ok(MLayout::Present)
+    } else if size_check.accepts(record_size, with_m) {
+        Ok(MLayout::MaybePadding)
+    } else if size_check.accepts(record_size, without_m) {
+        Ok(MLayout::Absent)
+    } else {
+        Err(Error::InvalidShapeRecordSize)
+    }
+}
+
+/// Reads the M range and the M values of the points if the record has them,
+/// returns the M range, `None` when the record has no M values.
+///
+/// With `MLayout::MaybePadding`, the bytes are the M values only if they are not all zeros
+/// and the measures are within the M range, they are padding otherwise:
+/// the measures of the points are then left to NO_DATA.
+pub(crate) fn read_optional_ms_into<T: Read, D: HasM>(
+    source: &mut T,
+    points: &mut [D],
+    layout: MLayout,
+) -> Result<Option<[f64; 2]>, std::io::Error> {
+    if layout == MLayout::Absent {
+        return Ok(None);
+    }
+    let range = read_range(source)?;
+    read_ms_into(source, points)?;
+    if layout == MLayout::Present {
+        return Ok(Some(range));
+    }
+    let is_zero = |value: f64| value.to_bits() == 0;
+    let are_all_zeros = is_zero(range[0])
+        && is_zero(range[1])
+        && points.iter().all(|point| is_zero(point.m()));
+    let is_valid_range = range[0] <= range[1];
+    let are_within_range = points
+        .iter()
+        .map(HasM::m)
+        .filter(|m| !is_no_data(*m))
+        .all(|m| range[0] <= m && m <= range[1]);
+    if are_all_zeros || !is_valid_range || !are_within_range {
+        for point in points.iter_mut() {
+            *point.m_mut() = NO_DATA;
+        }
+        return Ok(None);
+    }
+    Ok(Some(range))
+}
+
+/// Reads the measure of a point if the record has it, NO_DATA otherwise.
+///
+/// With `MLayout::MaybePadding`, zero bytes are padding and give NO_DATA.
+pub(crate) fn read_optional_m<T: Read>(
+    source: &mut T,
+    layout: MLayout,
+) -> Result<f64, std::io::Error> {
+    if layout == MLayout::Absent {
+        return Ok(NO_DATA);
+    }
+    let m = source.read_f64::<LittleEndian>()?;
+    if layout == MLayout::MaybePadding && m.to_bits() == 0 {
+        return Ok(NO_DATA);
+    }
+    Ok(m)
+}
+
+/// Returns true if at least one of the points has a measure.
+///
+/// When no point has a measure, the optional M values (and M range)
+/// are not written, allowing files read without M values to be
+/// written back identically.
+pub(crate) fn has_measures<PointType: HasM>(points: &[PointType]) -> bool {
+    points.iter().any(|point| !is_no_data(point.m()))
+}
+
+/// Reads a number of parts or points of a shape, which can not be negative
+/// (the record can not hold such a shape, it is an `Error::InvalidShapeRecordSize`)
+pub(crate) fn read_count<T: Read>(source: &mut T) -> Result<i32, Error> {
+    let count = source.read_i32::<LittleEndian>()?;
+    if count < 0 {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    Ok(count)
+}
+
+/// Returns a vector to read `count` elements into.
+///
+/// Its capacity is bounded, as the count of a corrupted record may be much larger than
+/// the number of elements the record holds: the vector grows as the elements are read.
+pub(crate) fn vec_with_count_capacity<E>(count: i32) -> Vec<E> {
+    const MAX_PREALLOCATED_BYTES: usize = 1 << 16;
+    let max_count = MAX_PREALLOCATED_BYTES / std::mem::size_of::<E>().max(1);
+    Vec::with_capacity((count.max(0) as usize).min(max_count))
+}
 
 pub(crate) fn read_xy_in_vec_of<PointType, T>(
     source: &mut T,
@@ -13,7 +147,7 @@ where
     PointType: HasMutXY + Default,
     T: Read,
 {
-    let mut points = Vec::<PointType>::with_capacity(num_points as usize);
+    let mut points = vec_with_count_capacity::<PointType>(num_points);
     for _ in 0..num_points {
         let mut p = PointType::default();
         *p.x_mut() = source.read_f64::<LittleEndian>()?;
@@ -25,7 +159,7 @@ where
 
 pub(crate) fn read_ms_into<T: Read, D: HasM>(
     source: &mut T,
-    points: &mut Vec<D>,
+    points: &mut [D],
 ) -> Result<(), std::io::Error> {
     for point in points {
         *point.m_mut() = f64::max(source.read_f64::<LittleEndian>()?, NO_DATA);
@@ -35,7 +169,7 @@ pub(crate) fn read_ms_into<T: Read, D: HasM>(
 
 pub(crate) fn read_zs_into<T: Read>(
     source: &mut T,
-    points: &mut Vec<PointZ>,
+    points: &mut [PointZ],
 ) -> Result<(), std::io::Error> {
     for point in points.iter_mut() {
         point.z = source.read_f64::<LittleEndian>()?;
@@ -54,7 +188,7 @@ pub(crate) fn read_parts<T: Read>(
     source: &mut T,
     num_parts: i32,
 ) -> Result<Vec<i32>, std::io::Error> {
-    let mut parts = Vec::<i32>::with_capacity(num_parts as usize);
+    let mut parts = vec_with_count_capacity::<i32>(num_parts);
     for _ in 0..num_parts {
         parts.push(source.read_i32::<LittleEndian>()?);
     }
@@ -102,29 +236,23 @@ pub(crate) fn write_range<T: Write>(dest: &mut T, range: [f64; 2]) -> Result<(),
     Ok(())
 }
 
-pub(crate) fn calc_m_range<PointType: HasM>(points: &[PointType]) -> [f64; 2] {
-    let mut range = [std::f64::MAX, std::f64::MIN];
-    for point in points {
-        range[0] = f64::min(range[0], point.m());
-        range[1] = f64::max(range[1], point.m());
-    }
-
-    if is_no_data(range[0]) {
-        range[0] = 0.0;
-    }
-
-    if is_no_data(range[1]) {
-        range[1] = 0.0;
-    }
+/// Returns the `[min, max]` of the values, `[0.0, 0.0]` if there are none
+fn range_of<I: Iterator<Item = f64>>(values: I) -> [f64; 2] {
+    values
+        .fold(None, |range: Option<[f64; 2]>, value| match range {
+            Some([min, max]) => Some([f64::min(min, value), f64::max(max, value)]),
+            None => Some([value, value]),
+        })
+        .unwrap_or([0.0, 0.0])
+}
 
-    range
+/// Returns the range of the measures that are not NO_DATA,
+/// `[0.0, 0.0]` if there are none
+pub(crate) fn calc_m_range<PointType: HasM>(points: &[PointType]) -> [f64; 2] {
+    range_of(points.iter().map(HasM::m).filter(|m| !is_no_data(*m)))
 }
 
-pub(crate) fn calc_z_range(points: &[PointZ]) -> [f64; 2] {
-    let mut range = [std::f64::MAX, std::f64::MIN];
-    for point in points {
-        range[0] = f64::min(range[0], point.z);
-        range[1] = f64::max(range[1], point.z);
-    }
-    range
+/// Returns the range of the z values, `[0.0, 0.0]` if there are no points
+pub(crate) fn calc_z_range<PointType: HasZ>(points: &[PointType]) -> [f64; 2] {
+    range_of(points.iter().map(HasZ::z))
 }
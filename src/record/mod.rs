@@ -1,33 +1,47 @@
 use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::fmt;
-use std::io::{Read, Write};
+use std::io::{Read, Take, Write};
 
+mod antimeridian;
+pub mod borrowed;
+mod canonical;
+mod distance;
+#[cfg(feature = "geo-types")]
+pub mod geo_converter;
 pub mod io;
 pub mod multipatch;
 pub mod multipoint;
+pub mod null;
+pub mod part_filter;
 pub mod point;
 pub mod poly;
+mod reproject;
+mod snap;
 pub mod traits;
+pub mod transcode;
+pub mod view;
 
-use super::{Error, ShapeType};
+use super::{Error, GeometryClass, ShapeType};
+use record::antimeridian::{split_polygon, split_polyline};
+use record::io::{calc_m_range, calc_z_range};
+use record::reproject::{reproject_points, reproject_with_bbox};
+use constants::RECORD_HEADER_SIZE;
 pub use record::multipatch::{Multipatch, PatchType};
 pub use record::multipoint::{Multipoint, MultipointM, MultipointZ};
+pub use record::null::NullShape;
+pub use record::part_filter::{IntersectsBBox, PartialShape, RetainPartsInBBox};
 pub use record::point::{Point, PointM, PointZ};
-pub use record::poly::{Polygon, PolygonM, PolygonZ};
+pub use record::poly::{Polygon, PolygonM, PolygonZ, RingCounts};
 pub use record::poly::{Polyline, PolylineM, PolylineZ};
 use record::traits::HasXY;
-pub use record::traits::{MultipartShape, MultipointShape};
-use std::convert::TryFrom;
+use record::view::PartsIter;
+pub use record::traits::{DimStats, MultipartShape, MultipointShape};
+use std::convert::{Infallible, TryFrom};
 
 #[cfg(feature = "geo-types")]
 use geo_types;
 
-/// Value inferior to this are considered as NO_DATA
-pub const NO_DATA: f64 = -10e38;
-
-fn is_no_data(val: f64) -> bool {
-    val <= NO_DATA
-}
+pub use constants::NO_DATA;
 
 /// Traits to be able to retrieve the ShapeType corresponding to the type
 pub trait HasShapeType {
@@ -38,32 +52,193 @@ pub trait HasShapeType {
 /// Simple Trait to store the type of the shape
 pub trait ConcreteShape: Sized + HasShapeType {}
 
+/// How the content length of a record is checked against the size of the shape it holds
+/// (see [Reader::allow_padded_records](../reader/struct.Reader.html#method.allow_padded_records))
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum RecordSizeCheck {
+    /// The content length must be the size of the shape (the default)
+    #[default]
+    Exact,
+    /// The content length may be larger than the size of the shape,
+    /// the bytes after the shape are skipped.
+    AtLeast,
+}
+
+impl RecordSizeCheck {
+    /// Returns whether a record of `record_size` bytes can hold a shape of `shape_size` bytes
+    pub(crate) fn accepts(self, record_size: u64, shape_size: usize) -> bool {
+        match self {
+            RecordSizeCheck::Exact => record_size == shape_size as u64,
+            RecordSizeCheck::AtLeast => record_size >= shape_size as u64,
+        }
+    }
+}
+
 pub trait ConcreteReadableShape: ConcreteShape {
     /// Function that actually reads the `ActualShape` from the source
     /// and returns it
-    fn read_shape_content<T: Read>(source: &mut T, record_size: i32) -> Result<Self, Error>;
+    ///
+    /// The source starts after the shape type and ends with the record,
+    /// its `limit()` is the size of the shape content in bytes,
+    /// which must be the size of the shape.
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error>;
+
+    /// Same as [read_shape_content](#method.read_shape_content), the size of the shape
+    /// being checked against the size of the content with the `size_check`.
+    ///
+    /// The shape is read from the numbers of parts and points it gives, the bytes of
+    /// the content after it are not read.
+    ///
+    /// The default implementation ignores the `size_check` and calls `read_shape_content`:
+    /// shapes that do not override it only accept records of their exact size.
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        _size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        Self::read_shape_content(source)
+    }
+
+    /// Returns the shape if it is of the type of this shape, `None` otherwise.
+    ///
+    /// Used to read the records that are not read directly as this shape:
+    /// the ones of a type that
+    /// [can be promoted](../enum.ShapeType.html#method.can_be_promoted_to) to this one,
+    /// and the ones read with the options of the reader that change the shapes
+    /// (see [try_from_shape](trait.ReadableShape.html#tymethod.try_from_shape)).
+    /// The shapes that do not implement it can only be read as they are.
+    fn from_shape(_shape: Shape) -> Option<Self> {
+        None
+    }
 }
 
 /// Trait implemented by all the Shapes that can be read
 pub trait ReadableShape: Sized {
-    fn read_from<T: Read>(source: &mut T, record_size: i32) -> Result<Self, Error>;
+    /// Reads the shape from the content of a record (starting with the shape type),
+    /// the source ends with the record.
+    fn read_from<T: Read>(source: &mut Take<T>) -> Result<Self, Error>;
+
+    /// Same as [read_from](#method.read_from), the size of the shape being checked
+    /// against the size of the content with the `size_check` (see
+    /// [read_shape_content_with](trait.ConcreteReadableShape.html#method.read_shape_content_with)).
+    ///
+    /// Shapes that do not implement it are read with `read_from`.
+    fn read_from_with<T: Read>(
+        source: &mut Take<T>,
+        _size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        Self::read_from(source)
+    }
+
+    /// Converts a shape read from a record to this shape, promoting it if its type
+    /// [can be promoted](../enum.ShapeType.html#method.can_be_promoted_to) to this one.
+    ///
+    /// The records are read as a [Shape](enum.Shape.html) then converted with it when
+    /// the reader has an option that changes the shapes as they are read:
+    /// [normalize_polygons](../reader/struct.Reader.html#method.normalize_polygons),
+    /// [allow_multipatch_conversion](../reader/struct.Reader.html#method.allow_multipatch_conversion)
+    /// or [keep_unknown_shapes](../reader/struct.Reader.html#method.keep_unknown_shapes).
+    ///
+    /// # Errors
+    ///
+    /// `Error::MismatchShapeType` if the shape can not be converted to this one.
+    fn try_from_shape(shape: Shape) -> Result<Self, Error>;
 }
 
+/// A record of a shape type that can be promoted to the type of the shape
+/// (e.g. a Polyline record read as a PolylineZ) is read as its type, then promoted
+/// (see the [transcode](transcode/index.html) module).
 impl<S: ConcreteReadableShape> ReadableShape for S {
-    fn read_from<T: Read>(mut source: &mut T, mut record_size: i32) -> Result<S, Error> {
+    fn read_from<T: Read>(source: &mut Take<T>) -> Result<S, Error> {
+        Self::read_from_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_from_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<S, Error> {
         let shapetype = ShapeType::read_from(&mut source)?;
-        record_size -= std::mem::size_of::<i32>() as i32;
         if shapetype == Self::shapetype() {
-            S::read_shape_content(&mut source, record_size)
-        } else {
-            Err(Error::MismatchShapeType {
+            return S::read_shape_content_with(source, size_check);
+        }
+        if !shapetype.can_be_promoted_to(Self::shapetype()) {
+            return Err(Error::MismatchShapeType {
                 requested: Self::shapetype(),
                 actual: shapetype,
-            })
+            });
         }
+        let shape = Shape::read_content_of(shapetype, source, size_check)?;
+        Self::try_from_shape(shape)
+    }
+
+    fn try_from_shape(shape: Shape) -> Result<S, Error> {
+        let mismatch = Error::MismatchShapeType {
+            requested: Self::shapetype(),
+            actual: shape.shapetype(),
+        };
+        let shape = if shape.shapetype() == Self::shapetype() {
+            shape
+        } else if shape.shapetype().can_be_promoted_to(Self::shapetype()) {
+            transcode::convert_shape(shape, Self::shapetype())?
+        } else {
+            return Err(mismatch);
+        };
+        S::from_shape(shape).ok_or(mismatch)
     }
 }
 
+/// A record holding a NullShape is read as `None`, so that files in which null records
+/// are interleaved with shapes of a concrete type can be read as that type
+impl<S: ReadableShape> ReadableShape for Option<S> {
+    fn read_from<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_from_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_from_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let code = source.read_i32::<LittleEndian>()?;
+        if code == ShapeType::NullShape as i32 {
+            return Ok(None);
+        }
+        // The shape reads its shape type first, so it is given back to it
+        let code = code.to_le_bytes();
+        let limit = source.limit();
+        let mut content = (&code[..]).chain(source).take(limit + 4);
+        S::read_from_with(&mut content, size_check).map(Some)
+    }
+
+    fn try_from_shape(shape: Shape) -> Result<Self, Error> {
+        match shape {
+            Shape::NullShape(_) => Ok(None),
+            shape => S::try_from_shape(shape).map(Some),
+        }
+    }
+}
+
+/// Reads a shape from the content of a record of `record_size` bytes
+/// (the source being positioned after the record header).
+///
+/// The shape is read from a reader bounded to the record, so that a malformed shape
+/// can not be read past its record (this fails with an `UnexpectedEof` io error instead).
+/// The bytes of the record the shape did not read are skipped, so that the source
+/// is at the start of the next record, and are reported as an
+/// `Error::InvalidShapeRecordSize` if the `size_check` is `Exact`.
+pub(crate) fn read_record_content<T: Read, S: ReadableShape>(
+    source: &mut T,
+    record_size: u64,
+    size_check: RecordSizeCheck,
+) -> Result<S, Error> {
+    let mut content = source.take(record_size);
+    let shape = S::read_from_with(&mut content, size_check);
+    let num_unread_bytes = std::io::copy(&mut content, &mut std::io::sink());
+    let shape = shape?;
+    if num_unread_bytes? != 0 && size_check == RecordSizeCheck::Exact {
+        return Err(Error::InvalidShapeRecordSize);
+    }
+    Ok(shape)
+}
+
 /// Trait implemented by all Shapes that can be written
 pub trait WritableShape {
     /// Returns the size in bytes that the Shapes will take once written.
@@ -74,16 +249,103 @@ pub trait WritableShape {
     fn write_to<T: Write>(self, dest: &mut T) -> Result<(), Error>;
 }
 
-pub trait EsriShape: HasShapeType + WritableShape {
+/// Ranges of the z values and of the measures of a shape,
+/// included by the writer in the extent of the file.
+///
+/// The shapes which points have no z values or no measures
+/// keep the default implementations returning `None`.
+pub trait ShapeRanges {
+    /// Returns the range of the z values, `None` if the shape has no z values
+    fn z_extent(&self) -> Option<[f64; 2]> {
+        None
+    }
+    /// Returns the range of the measures that are not NO_DATA,
+    /// `None` if the shape has no measures or only NO_DATA ones
+    fn m_extent(&self) -> Option<[f64; 2]> {
+        None
+    }
+}
+
+pub trait EsriShape: HasShapeType + WritableShape + ShapeRanges {
+    /// Returns the ShapeType of this particular shape.
+    ///
+    /// For concrete shapes this is always `Self::shapetype()`,
+    /// only the [Shape](enum.Shape.html) enum has a type that depends on its variant
+    fn shape_type(&self) -> ShapeType {
+        Self::shapetype()
+    }
+    /// Returns the code of the shape type written in the record by the writer,
+    /// the one of the `shape_type` except for the [unknown shapes](enum.Shape.html#variant.Unknown)
+    fn shape_type_code(&self) -> i32 {
+        self.shape_type() as i32
+    }
     fn bbox(&self) -> BBox;
-    /// Should return the Z range of this shape (maybe require computing it)
+    /// Returns the Z range of the shape, `[0.0, 0.0]` for shapes without z values.
+    ///
+    /// The shapes with z values have an inherent `z_range` method,
+    /// the writer uses [ShapeRanges](trait.ShapeRanges.html).
+    #[deprecated(
+        note = "only the shapes with z values have a z range, \
+                use their inherent `z_range` method (e.g. `PolylineZ::z_range`)"
+    )]
     fn z_range(&self) -> [f64; 2] {
         [0.0, 0.0]
     }
-    /// Should return the M range of this shape (maybe require computing it)
+    /// Returns the M range of the shape, `[0.0, 0.0]` for shapes without measures.
+    ///
+    /// The shapes with measures have an inherent `m_range` method,
+    /// the writer uses [ShapeRanges](trait.ShapeRanges.html).
+    #[deprecated(
+        note = "only the shapes with measures have a m range, \
+                use their inherent `m_range` method (e.g. `PolylineM::m_range`)"
+    )]
     fn m_range(&self) -> [f64; 2] {
         [0.0, 0.0]
     }
+    /// Returns false if all the measures of the shape are NO_DATA
+    /// (its M range is then not included in the M range of the file)
+    #[deprecated(
+        note = "only the shapes with measures can have measures, \
+                use their inherent `has_measures` method (e.g. `PolylineM::has_measures`)"
+    )]
+    fn has_measures(&self) -> bool {
+        true
+    }
+    /// Checks the shape before it is written by a writer with validation enabled
+    /// (see [Writer::with_validation](../writer/struct.Writer.html#method.with_validation)).
+    ///
+    /// Only polygons are checked: their rings must be closed and have at least 4 points.
+    fn validate(&self) -> Result<(), Error> {
+        Ok(())
+    }
+    /// Closes the rings of the polygons that are not closed, the ones whose first and last
+    /// points are within `tolerance` of each other by moving their last point onto their
+    /// first one
+    /// (see [GenericPolygon::close_rings](poly/struct.GenericPolygon.html#method.close_rings)).
+    ///
+    /// Used by writers created with [Writer::with_ring_closing](../writer/struct.Writer.html#method.with_ring_closing),
+    /// the default implementation does nothing.
+    fn close_rings(&mut self, _tolerance: f64) {}
+    /// Rounds the coordinates (x, y, z and m) of the points to `decimals` decimals,
+    /// leaving the NO_DATA measures untouched, and updates the bbox (and ranges) of the shape.
+    ///
+    /// If `remove_repeated_points` is true, the consecutive points of a part
+    /// that became equal are removed (except for multipatches).
+    ///
+    /// Used by writers created with [Writer::snap_precision](../writer/struct.Writer.html#method.snap_precision),
+    /// the default implementation does nothing.
+    fn snap_to_precision(&mut self, _decimals: u8, _remove_repeated_points: bool) {}
+    /// Rewrites the values of the points that have more than one encoding
+    /// with a single one, so that equal shapes are written with the same bytes:
+    ///
+    /// - negative zeros become positive zeros,
+    /// - NaN measures and measures lower than NO_DATA become exactly NO_DATA.
+    ///
+    /// The bbox (and ranges) of the shape are updated.
+    ///
+    /// Used by writers created with [Writer::deterministic](../writer/struct.Writer.html#method.deterministic),
+    /// the default implementation does nothing.
+    fn canonicalize(&mut self) {}
 }
 
 /// Validate the `parts array` of the any `MultipartShape`.
@@ -121,24 +383,71 @@ pub(crate) fn is_parts_array_valid<PointType, ST: MultipartShape<PointType>>(sha
 /// Outer Rings's points are un clockwise order
 ///
 /// https://stackoverflow.com/questions/1165647/how-to-determine-if-a-list-of-polygon-points-are-in-clockwise-order/1180256#1180256
-#[cfg(feature = "geo-types")]
 pub(crate) fn is_outer_ring<PointType: HasXY>(points: &[PointType]) -> bool {
-    let area = points
+    ring_signed_area(points) >= 0.0
+}
+
+/// Returns the area of the (closed) ring, positive if the ring is clockwise
+pub(crate) fn ring_signed_area<PointType: HasXY>(points: &[PointType]) -> f64 {
+    points
         .windows(2)
         .map(|pts| (pts[1].x() - pts[0].x()) * (pts[1].y() + pts[0].y()) )
-        .sum::<f64>() / 2.0f64;
+        .sum::<f64>() / 2.0f64
+}
 
-    if area < 0.0 {
-        false
-    } else {
-        true
+/// Returns whether the point is inside the (closed) ring, using the even-odd rule
+pub(crate) fn ring_contains<PointType: HasXY>(points: &[PointType], x: f64, y: f64) -> bool {
+    let mut is_inside = false;
+    for pts in points.windows(2) {
+        let (a, b) = (&pts[0], &pts[1]);
+        if (a.y() > y) != (b.y() > y) {
+            let x_crossing = a.x() + (y - a.y()) / (b.y() - a.y()) * (b.x() - a.x());
+            if x < x_crossing {
+                is_inside = !is_inside;
+            }
+        }
+    }
+    is_inside
+}
+
+/// Returns whether a segment of the first ring crosses or touches a segment of the second
+pub(crate) fn rings_intersect<P: HasXY, Q: HasXY>(first: &[P], second: &[Q]) -> bool {
+    first.windows(2).any(|a| {
+        second
+            .windows(2)
+            .any(|b| segments_intersect(&a[0], &a[1], &b[0], &b[1]))
+    })
+}
+
+/// Returns whether the segment from `a` to `b` and the segment from `c` to `d`
+/// have at least one point in common
+fn segments_intersect<P: HasXY, Q: HasXY>(a: &P, b: &P, c: &Q, d: &Q) -> bool {
+    // Positive if r is on the left of the line from p to q, 0 if the three are aligned
+    let side = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        (q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)
+    };
+    // Whether r, aligned with p and q, is between them
+    let is_within = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        p.0.min(q.0) <= r.0 && r.0 <= p.0.max(q.0) && p.1.min(q.1) <= r.1 && r.1 <= p.1.max(q.1)
+    };
+    let (a, b) = ((a.x(), a.y()), (b.x(), b.y()));
+    let (c, d) = ((c.x(), c.y()), (d.x(), d.y()));
+    let (c_side, d_side) = (side(a, b, c), side(a, b, d));
+    let (a_side, b_side) = (side(c, d, a), side(c, d, b));
+    if c_side * d_side < 0.0 && a_side * b_side < 0.0 {
+        return true;
     }
+    (c_side == 0.0 && is_within(a, b, c))
+        || (d_side == 0.0 && is_within(a, b, d))
+        || (a_side == 0.0 && is_within(c, d, a))
+        || (b_side == 0.0 && is_within(c, d, b))
 }
 
 
 /// enum of Shapes that can be read or written to a shapefile
+#[derive(Clone)]
 pub enum Shape {
-    NullShape,
+    NullShape(NullShape),
     Point(Point),
     PointM(PointM),
     PointZ(PointZ),
@@ -152,64 +461,119 @@ pub enum Shape {
     MultipointM(MultipointM),
     MultipointZ(MultipointZ),
     Multipatch(Multipatch),
+    /// A record which shape type is not one of the specification (e.g. the extension
+    /// of a vendor), read only if the reader
+    /// [keeps the unknown shapes](../reader/struct.Reader.html#method.keep_unknown_shapes).
+    ///
+    /// Its content is kept as it is, so that it is written back unchanged. It has no points,
+    /// and its [shapetype](#method.shapetype) is `ShapeType::NullShape`.
+    Unknown {
+        /// The code of the shape type, as written in the record
+        shape_type: i32,
+        /// The content of the record, after the code of the shape type
+        content: Vec<u8>,
+    },
 }
 
 impl HasShapeType for Shape {
     fn shapetype() -> ShapeType {
-        ShapeType::Point
+        ShapeType::NullShape
     }
 }
 
-impl ReadableShape for Shape {
-    fn read_from<T: Read>(mut source: &mut T, mut record_size: i32) -> Result<Self, Error> {
-        let shapetype = ShapeType::read_from(&mut source)?;
-        record_size -= std::mem::size_of::<i32>() as i32;
+impl Shape {
+    /// Reads the shape of the given type from the content of a record,
+    /// the source starting after the shape type
+    pub(crate) fn read_content_of<T: Read>(
+        shapetype: ShapeType,
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
         let shape = match shapetype {
             ShapeType::Polyline => {
-                Shape::Polyline(Polyline::read_shape_content(&mut source, record_size)?)
+                Shape::Polyline(Polyline::read_shape_content_with(source, size_check)?)
             }
             ShapeType::PolylineM => {
-                Shape::PolylineM(PolylineM::read_shape_content(&mut source, record_size)?)
+                Shape::PolylineM(PolylineM::read_shape_content_with(source, size_check)?)
             }
             ShapeType::PolylineZ => {
-                Shape::PolylineZ(PolylineZ::read_shape_content(&mut source, record_size)?)
+                Shape::PolylineZ(PolylineZ::read_shape_content_with(source, size_check)?)
             }
-            ShapeType::Point => Shape::Point(Point::read_shape_content(&mut source, record_size)?),
+            ShapeType::Point => Shape::Point(Point::read_shape_content_with(source, size_check)?),
             ShapeType::PointM => {
-                Shape::PointM(PointM::read_shape_content(&mut source, record_size)?)
+                Shape::PointM(PointM::read_shape_content_with(source, size_check)?)
             }
             ShapeType::PointZ => {
-                Shape::PointZ(PointZ::read_shape_content(&mut source, record_size)?)
+                Shape::PointZ(PointZ::read_shape_content_with(source, size_check)?)
             }
             ShapeType::Polygon => {
-                Shape::Polygon(Polygon::read_shape_content(&mut source, record_size)?)
+                Shape::Polygon(Polygon::read_shape_content_with(source, size_check)?)
             }
             ShapeType::PolygonM => {
-                Shape::PolygonM(PolygonM::read_shape_content(&mut source, record_size)?)
+                Shape::PolygonM(PolygonM::read_shape_content_with(source, size_check)?)
             }
             ShapeType::PolygonZ => {
-                Shape::PolygonZ(PolygonZ::read_shape_content(&mut source, record_size)?)
+                Shape::PolygonZ(PolygonZ::read_shape_content_with(source, size_check)?)
             }
             ShapeType::Multipoint => {
-                Shape::Multipoint(Multipoint::read_shape_content(&mut source, record_size)?)
+                Shape::Multipoint(Multipoint::read_shape_content_with(source, size_check)?)
             }
             ShapeType::MultipointM => {
-                Shape::MultipointM(MultipointM::read_shape_content(&mut source, record_size)?)
+                Shape::MultipointM(MultipointM::read_shape_content_with(source, size_check)?)
             }
             ShapeType::MultipointZ => {
-                Shape::MultipointZ(MultipointZ::read_shape_content(&mut source, record_size)?)
+                Shape::MultipointZ(MultipointZ::read_shape_content_with(source, size_check)?)
             }
             ShapeType::Multipatch => {
-                Shape::Multipatch(Multipatch::read_shape_content(&mut source, record_size)?)
+                Shape::Multipatch(Multipatch::read_shape_content_with(source, size_check)?)
+            }
+            ShapeType::NullShape => {
+                Shape::NullShape(NullShape::read_shape_content_with(source, size_check)?)
             }
-            ShapeType::NullShape => Shape::NullShape,
         };
         Ok(shape)
     }
 }
 
+impl ReadableShape for Shape {
+    fn read_from<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_from_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_from_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let shapetype = ShapeType::read_from(&mut source)?;
+        Shape::read_content_of(shapetype, source, size_check)
+    }
+
+    fn try_from_shape(shape: Shape) -> Result<Self, Error> {
+        Ok(shape)
+    }
+}
+
+/// macro that implements a method returning a reference to the concrete shape
+/// if the Shape is of the corresponding variant
+macro_rules! impl_as_concrete_shape {
+    ($fn_name:ident, Shape::$ShapeEnumVariant:ident => $ConcreteShape:ident) => {
+        #[doc = "Returns a reference to the inner `"]
+        #[doc = stringify!($ConcreteShape)]
+        #[doc = "`, or `None` if the shape is of another type"]
+        pub fn $fn_name(&self) -> Option<&$ConcreteShape> {
+            match self {
+                Shape::$ShapeEnumVariant(shp) => Some(shp),
+                _ => None,
+            }
+        }
+    };
+}
+
 impl Shape {
     /// Returns the shapetype
+    ///
+    /// The type of an [unknown shape](#variant.Unknown) is `ShapeType::NullShape`,
+    /// its code is given by [shape_type_code](trait.EsriShape.html#method.shape_type_code).
     pub fn shapetype(&self) -> ShapeType {
         match self {
             Shape::Polyline(_) => ShapeType::Polyline,
@@ -222,10 +586,580 @@ impl Shape {
             Shape::PolygonM(_) => ShapeType::PolygonM,
             Shape::PolygonZ(_) => ShapeType::PolygonZ,
             Shape::Multipoint(_) => ShapeType::Multipoint,
-            Shape::MultipointM(_) => ShapeType::Multipoint,
-            Shape::MultipointZ(_) => ShapeType::Multipoint,
+            Shape::MultipointM(_) => ShapeType::MultipointM,
+            Shape::MultipointZ(_) => ShapeType::MultipointZ,
             Shape::Multipatch(_) => ShapeType::Multipatch,
-            Shape::NullShape => ShapeType::NullShape,
+            Shape::NullShape(_) | Shape::Unknown { .. } => ShapeType::NullShape,
+        }
+    }
+
+    /// Reorders the rings of polygons so that each outer ring is followed by its holes,
+    /// does nothing for the other shapes
+    /// (see [Reader::normalize_polygons](../reader/struct.Reader.html#method.normalize_polygons))
+    pub(crate) fn normalize_polygon_rings(&mut self) {
+        match self {
+            Shape::Polygon(polygon) => polygon.normalize_rings(),
+            Shape::PolygonM(polygon) => polygon.normalize_rings(),
+            Shape::PolygonZ(polygon) => polygon.normalize_rings(),
+            _ => {}
+        }
+    }
+
+    /// Returns the class of geometry of the shape
+    /// (see [ShapeType::geometry_class](../enum.ShapeType.html#method.geometry_class))
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{GeometryClass, PointZ, Shape};
+    /// let shape = Shape::PointZ(PointZ::new(1.0, 2.0, 3.0, 4.0));
+    /// match shape.group() {
+    ///     GeometryClass::Point | GeometryClass::Multipoint => println!("points"),
+    ///     _ => println!("something else"),
+    /// }
+    /// ```
+    pub fn group(&self) -> GeometryClass {
+        self.shapetype().geometry_class()
+    }
+
+    /// Returns true for Point, PointM and PointZ
+    pub fn is_point_like(&self) -> bool {
+        self.group() == GeometryClass::Point
+    }
+
+    /// Returns true for Multipoint, MultipointM and MultipointZ
+    pub fn is_multipoint_like(&self) -> bool {
+        self.group() == GeometryClass::Multipoint
+    }
+
+    /// Returns true for Polyline, PolylineM and PolylineZ
+    pub fn is_polyline_like(&self) -> bool {
+        self.group() == GeometryClass::Polyline
+    }
+
+    /// Returns true for Polygon, PolygonM and PolygonZ
+    pub fn is_polygon_like(&self) -> bool {
+        self.group() == GeometryClass::Polygon
+    }
+
+    /// Converts the shape into another shape type of the same class
+    /// (e.g. a `Polyline` into a `PolylineM`), see the [transcode](transcode/index.html) module
+    /// for how the z and m values are filled or dropped.
+    ///
+    /// Returns [MismatchShapeType](../enum.Error.html#variant.MismatchShapeType)
+    /// if the class of `shape_type` is not the one of the shape.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Shape, ShapeType, NO_DATA};
+    /// let shape = Shape::Point(Point::new(1.0, 2.0));
+    /// let shape = shape.convert_to(ShapeType::PointM).unwrap();
+    /// assert_eq!(shape.as_point_m().unwrap().m, NO_DATA);
+    ///
+    /// assert!(shape.convert_to(ShapeType::Polyline).is_err());
+    /// ```
+    pub fn convert_to(self, shape_type: ShapeType) -> Result<Shape, Error> {
+        transcode::convert_shape(self, shape_type)
+    }
+
+    /// Returns the inner shape if it is a `S`, `None` if the shape is a NullShape.
+    ///
+    /// The NullShapes can be in a file of any shape type, while the other shapes
+    /// of a file must have its type: an error here means that the file is corrupted.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MismatchShapeType` if the shape is of another type
+    /// - `Error::InvalidShapeType` if the shape is an [unknown](#variant.Unknown) one
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::Polygon;
+    /// let shapes = shapefile::read("tests/data/polygon.shp")?;
+    /// let polygons = shapes
+    ///     .into_iter()
+    ///     .filter_map(|shape| shape.try_into_concrete::<Polygon>().transpose())
+    ///     .collect::<Result<Vec<_>, _>>()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_into_concrete<S>(self) -> Result<Option<S>, Error>
+    where
+        S: HasShapeType + TryFrom<Shape, Error = Error>,
+    {
+        match self {
+            Shape::NullShape(_) => Ok(None),
+            Shape::Unknown { shape_type, .. } => Err(Error::InvalidShapeType(shape_type)),
+            shape => S::try_from(shape).map(Some),
+        }
+    }
+
+    impl_as_concrete_shape!(as_point, Shape::Point => Point);
+    impl_as_concrete_shape!(as_point_m, Shape::PointM => PointM);
+    impl_as_concrete_shape!(as_point_z, Shape::PointZ => PointZ);
+    impl_as_concrete_shape!(as_polyline, Shape::Polyline => Polyline);
+    impl_as_concrete_shape!(as_polyline_m, Shape::PolylineM => PolylineM);
+    impl_as_concrete_shape!(as_polyline_z, Shape::PolylineZ => PolylineZ);
+    impl_as_concrete_shape!(as_polygon, Shape::Polygon => Polygon);
+    impl_as_concrete_shape!(as_polygon_m, Shape::PolygonM => PolygonM);
+    impl_as_concrete_shape!(as_polygon_z, Shape::PolygonZ => PolygonZ);
+    impl_as_concrete_shape!(as_multipoint, Shape::Multipoint => Multipoint);
+    impl_as_concrete_shape!(as_multipoint_m, Shape::MultipointM => MultipointM);
+    impl_as_concrete_shape!(as_multipoint_z, Shape::MultipointZ => MultipointZ);
+    impl_as_concrete_shape!(as_multipatch, Shape::Multipatch => Multipatch);
+
+    /// Swaps the x and y coordinates of all the points of the shape, and updates its bbox.
+    ///
+    /// The rings of polygons (and multipatches) are reversed to keep their orientation,
+    /// see [GenericPolygon::swap_xy](poly/struct.GenericPolygon.html#method.swap_xy).
+    ///
+    /// See the [axis](../axis/index.html) module to detect shapes whose x and y were swapped.
+    pub fn swap_xy(&mut self) {
+        match self {
+            Shape::Polyline(shp) => shp.swap_xy(),
+            Shape::PolylineM(shp) => shp.swap_xy(),
+            Shape::PolylineZ(shp) => shp.swap_xy(),
+            Shape::Point(shp) => shp.swap_xy(),
+            Shape::PointM(shp) => shp.swap_xy(),
+            Shape::PointZ(shp) => shp.swap_xy(),
+            Shape::Polygon(shp) => shp.swap_xy(),
+            Shape::PolygonM(shp) => shp.swap_xy(),
+            Shape::PolygonZ(shp) => shp.swap_xy(),
+            Shape::Multipoint(shp) => shp.swap_xy(),
+            Shape::MultipointM(shp) => shp.swap_xy(),
+            Shape::MultipointZ(shp) => shp.swap_xy(),
+            Shape::Multipatch(shp) => shp.swap_xy(),
+            Shape::NullShape(_) | Shape::Unknown { .. } => {}
+        }
+    }
+
+    /// Computes again the bbox of the shape from its points,
+    /// and the Z and M ranges of a multipatch
+    pub(crate) fn update_bbox(&mut self) {
+        match self {
+            Shape::Polyline(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::PolylineM(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::PolylineZ(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::Polygon(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::PolygonM(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::PolygonZ(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::Multipoint(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::MultipointM(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::MultipointZ(shp) => shp.bbox = BBox::from_points(&shp.points),
+            Shape::Multipatch(shp) => {
+                shp.bbox = BBox::from_points(&shp.points);
+                shp.z_range = calc_z_range(&shp.points);
+                shp.m_range = calc_m_range(&shp.points);
+            }
+            Shape::Point(_)
+            | Shape::PointM(_)
+            | Shape::PointZ(_)
+            | Shape::NullShape(_)
+            | Shape::Unknown { .. } => {}
+        }
+    }
+
+    /// Returns a shape of the `shape_type` without points (the point types are
+    /// at the origin), to check whether the shapes of a type can be converted
+    pub(crate) fn empty_of(shape_type: ShapeType) -> Shape {
+        match shape_type {
+            ShapeType::NullShape => Shape::NullShape(NullShape),
+            ShapeType::Point => Shape::Point(Point::default()),
+            ShapeType::PointM => Shape::PointM(PointM::default()),
+            ShapeType::PointZ => Shape::PointZ(PointZ::default()),
+            ShapeType::Polyline => Shape::Polyline(Polyline::new(vec![], vec![])),
+            ShapeType::PolylineM => Shape::PolylineM(PolylineM::new(vec![], vec![])),
+            ShapeType::PolylineZ => Shape::PolylineZ(PolylineZ::new(vec![], vec![])),
+            ShapeType::Polygon => Shape::Polygon(Polygon::new(vec![], vec![])),
+            ShapeType::PolygonM => Shape::PolygonM(PolygonM::new(vec![], vec![])),
+            ShapeType::PolygonZ => Shape::PolygonZ(PolygonZ::new(vec![], vec![])),
+            ShapeType::Multipoint => Shape::Multipoint(Multipoint::new(vec![])),
+            ShapeType::MultipointM => Shape::MultipointM(MultipointM::new(vec![])),
+            ShapeType::MultipointZ => Shape::MultipointZ(MultipointZ::new(vec![])),
+            ShapeType::Multipatch => Shape::Multipatch(Multipatch::new(vec![], vec![], vec![])),
+        }
+    }
+
+    /// Returns the shape with the parts of its polylines and the rings of its polygons
+    /// split where they cross the antimeridian (±180° of longitude).
+    ///
+    /// A segment crosses the antimeridian if its x delta exceeds 180, the points
+    /// where it crosses are interpolated (with their z and m) at 180 and -180.
+    /// The split rings are closed along the antimeridian.
+    /// Rings crossing it an odd number of times (around a pole) are not split.
+    ///
+    /// Shapes that do not cross the antimeridian, and shapes that are neither
+    /// polylines nor polygons, are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// `Error::MalformedShape` if the parts are invalid,
+    /// `Error::UnclosedRing` if a ring of a polygon crossing the antimeridian is not closed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{Point, Polyline, Shape};
+    /// let polyline = Polyline::new(vec![Point::new(170.0, 0.0), Point::new(-170.0, 10.0)], vec![0]);
+    /// let split = Shape::Polyline(polyline).split_at_antimeridian()?;
+    /// let polyline = split.as_polyline().unwrap();
+    /// assert_eq!(polyline.parts, vec![0, 2]);
+    /// assert_eq!(polyline.points[1], Point::new(180.0, 5.0));
+    /// assert_eq!(polyline.points[2], Point::new(-180.0, 5.0));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split_at_antimeridian(&self) -> Result<Shape, Error> {
+        let split = match self {
+            Shape::Polyline(shp) => split_polyline(shp)?.map(Shape::Polyline),
+            Shape::PolylineM(shp) => split_polyline(shp)?.map(Shape::PolylineM),
+            Shape::PolylineZ(shp) => split_polyline(shp)?.map(Shape::PolylineZ),
+            Shape::Polygon(shp) => split_polygon(shp)?.map(Shape::Polygon),
+            Shape::PolygonM(shp) => split_polygon(shp)?.map(Shape::PolygonM),
+            Shape::PolygonZ(shp) => split_polygon(shp)?.map(Shape::PolygonZ),
+            _ => None,
+        };
+        Ok(split.unwrap_or_else(|| self.clone()))
+    }
+
+    /// Transforms the x and y coordinates of all the points of the shape with `f`
+    /// (typically a reprojection), then recomputes its bbox.
+    ///
+    /// The shape is left untouched if `f` fails for one of its points,
+    /// the error returned by `f` is then returned.
+    ///
+    /// Use [reproject_z](#method.reproject_z) to also transform the z values.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{Point, Polyline, Shape};
+    /// let polyline = Polyline::new(vec![Point::new(1.0, 1.0), Point::new(2.0, 3.0)], vec![0]);
+    /// let mut shape = Shape::Polyline(polyline);
+    /// shape.reproject(|x, y| Ok::<_, String>((x * 2.0, y + 10.0))).unwrap();
+    /// let polyline = shape.as_polyline().unwrap();
+    /// assert_eq!(polyline.points[1], Point::new(4.0, 13.0));
+    /// assert_eq!(polyline.bbox.ymax, 13.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn reproject<F, E>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(f64, f64) -> Result<(f64, f64), E>,
+    {
+        self.reproject_z(|x, y, z| f(x, y).map(|(x, y)| (x, y, z)))
+    }
+
+    /// Same as [reproject](#method.reproject), but `f` also transforms the z values,
+    /// the z and m ranges of multipatches are recomputed too.
+    ///
+    /// The points that do not have a z value are given to `f` with a z of 0,
+    /// and the z returned is ignored.
+    pub fn reproject_z<F, E>(&mut self, mut f: F) -> Result<(), E>
+    where
+        F: FnMut(f64, f64, f64) -> Result<(f64, f64, f64), E>,
+    {
+        let f = &mut f;
+        match self {
+            Shape::Polyline(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::PolylineM(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::PolylineZ(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::Point(shp) => reproject_points(std::slice::from_mut(shp), f),
+            Shape::PointM(shp) => reproject_points(std::slice::from_mut(shp), f),
+            Shape::PointZ(shp) => reproject_points(std::slice::from_mut(shp), f),
+            Shape::Polygon(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::PolygonM(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::PolygonZ(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::Multipoint(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::MultipointM(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::MultipointZ(shp) => reproject_with_bbox(&mut shp.points, &mut shp.bbox, f),
+            Shape::Multipatch(shp) => {
+                reproject_with_bbox(&mut shp.points, &mut shp.bbox, f)?;
+                shp.z_range = calc_z_range(&shp.points);
+                shp.m_range = calc_m_range(&shp.points);
+                Ok(())
+            }
+            Shape::NullShape(_) | Shape::Unknown { .. } => Ok(()),
+        }
+    }
+
+    /// Moves the points that are outside of the `domain` to its border,
+    /// the bbox (and ranges) of the shape are updated.
+    ///
+    /// Coordinates that are NaN are left untouched.
+    /// See [validate_extent](../extent/fn.validate_extent.html) to find these points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// use shapefile::{Point, Polyline, Shape};
+    /// let points = vec![Point::new(170.0, 10.0), Point::new(190.0, -95.0)];
+    /// let mut shape = Shape::Polyline(Polyline::new(points, vec![0]));
+    /// shape.clamp_to(BBox::new(-180.0, -90.0, 180.0, 90.0));
+    ///
+    /// let polyline = shape.as_polyline().unwrap();
+    /// assert_eq!(polyline.points[1], Point::new(180.0, -90.0));
+    /// assert_eq!(polyline.bbox.ymin, -90.0);
+    /// ```
+    pub fn clamp_to(&mut self, domain: BBox) {
+        let clamp = |value: f64, min: f64, max: f64| {
+            if value < min {
+                min
+            } else if value > max {
+                max
+            } else {
+                value
+            }
+        };
+        let result = self.reproject(|x, y| {
+            Ok::<_, Infallible>((
+                clamp(x, domain.xmin, domain.xmax),
+                clamp(y, domain.ymin, domain.ymax),
+            ))
+        });
+        match result {
+            Ok(()) => {}
+            Err(never) => match never {},
+        }
+    }
+
+    /// Returns an iterator over the x and y of the points of the shape,
+    /// whatever its type, without copying the points
+    ///
+    /// A NullShape has no points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let shapes = shapefile::read("tests/data/linez.shp")?;
+    /// let first_point = shapes[0].points_xy().next();
+    /// assert_eq!(first_point, Some((1.0, 5.0)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn points_xy(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        // Only one of the slices is not empty
+        let (points, points_m, points_z): (&[Point], &[PointM], &[PointZ]) = match self {
+            Shape::NullShape(_) | Shape::Unknown { .. } => (&[], &[], &[]),
+            Shape::Point(p) => (std::slice::from_ref(p), &[], &[]),
+            Shape::PointM(p) => (&[], std::slice::from_ref(p), &[]),
+            Shape::PointZ(p) => (&[], &[], std::slice::from_ref(p)),
+            Shape::Polyline(shp) => (&shp.points, &[], &[]),
+            Shape::PolylineM(shp) => (&[], &shp.points, &[]),
+            Shape::PolylineZ(shp) => (&[], &[], &shp.points),
+            Shape::Polygon(shp) => (&shp.points, &[], &[]),
+            Shape::PolygonM(shp) => (&[], &shp.points, &[]),
+            Shape::PolygonZ(shp) => (&[], &[], &shp.points),
+            Shape::Multipoint(shp) => (&shp.points, &[], &[]),
+            Shape::MultipointM(shp) => (&[], &shp.points, &[]),
+            Shape::MultipointZ(shp) => (&[], &[], &shp.points),
+            Shape::Multipatch(shp) => (&[], &[], &shp.points),
+        };
+        points
+            .iter()
+            .map(|p| (p.x, p.y))
+            .chain(points_m.iter().map(|p| (p.x, p.y)))
+            .chain(points_z.iter().map(|p| (p.x, p.y)))
+    }
+
+    /// Returns an iterator over the parts of the shape, whatever its type,
+    /// `None` for the shapes that do not have parts (points, multipoints and NullShape).
+    ///
+    /// The points of the parts are viewed without being copied,
+    /// see the [view](view/index.html) module.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let shapes = shapefile::read("tests/data/linez.shp")?;
+    /// for part in shapes[0].parts().unwrap() {
+    ///     let first = part.get(0).unwrap();
+    ///     println!("{} points, the first at {}, {}", part.len(), first.x(), first.y());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn parts(&self) -> Option<PartsIter<'_>> {
+        PartsIter::new(self)
+    }
+}
+
+impl WritableShape for Shape {
+    fn size_in_bytes(&self) -> usize {
+        match self {
+            Shape::Polyline(shp) => shp.size_in_bytes(),
+            Shape::PolylineM(shp) => shp.size_in_bytes(),
+            Shape::PolylineZ(shp) => shp.size_in_bytes(),
+            Shape::Point(shp) => shp.size_in_bytes(),
+            Shape::PointM(shp) => shp.size_in_bytes(),
+            Shape::PointZ(shp) => shp.size_in_bytes(),
+            Shape::Polygon(shp) => shp.size_in_bytes(),
+            Shape::PolygonM(shp) => shp.size_in_bytes(),
+            Shape::PolygonZ(shp) => shp.size_in_bytes(),
+            Shape::Multipoint(shp) => shp.size_in_bytes(),
+            Shape::MultipointM(shp) => shp.size_in_bytes(),
+            Shape::MultipointZ(shp) => shp.size_in_bytes(),
+            Shape::Multipatch(shp) => shp.size_in_bytes(),
+            Shape::Unknown { content, .. } => content.len(),
+            Shape::NullShape(_) => 0,
+        }
+    }
+
+    fn write_to<T: Write>(self, dest: &mut T) -> Result<(), Error> {
+        match self {
+            Shape::Polyline(shp) => shp.write_to(dest),
+            Shape::PolylineM(shp) => shp.write_to(dest),
+            Shape::PolylineZ(shp) => shp.write_to(dest),
+            Shape::Point(shp) => shp.write_to(dest),
+            Shape::PointM(shp) => shp.write_to(dest),
+            Shape::PointZ(shp) => shp.write_to(dest),
+            Shape::Polygon(shp) => shp.write_to(dest),
+            Shape::PolygonM(shp) => shp.write_to(dest),
+            Shape::PolygonZ(shp) => shp.write_to(dest),
+            Shape::Multipoint(shp) => shp.write_to(dest),
+            Shape::MultipointM(shp) => shp.write_to(dest),
+            Shape::MultipointZ(shp) => shp.write_to(dest),
+            Shape::Multipatch(shp) => shp.write_to(dest),
+            Shape::Unknown { content, .. } => Ok(dest.write_all(&content)?),
+            Shape::NullShape(_) => Ok(()),
+        }
+    }
+}
+
+/// The NullShape and the unknown shapes have no bbox nor ranges, they are all returned as zeros
+impl EsriShape for Shape {
+    fn shape_type(&self) -> ShapeType {
+        self.shapetype()
+    }
+
+    fn shape_type_code(&self) -> i32 {
+        match self {
+            Shape::Unknown { shape_type, .. } => *shape_type,
+            shape => shape.shapetype() as i32,
+        }
+    }
+
+    fn bbox(&self) -> BBox {
+        match self {
+            Shape::Polyline(shp) => shp.bbox(),
+            Shape::PolylineM(shp) => shp.bbox(),
+            Shape::PolylineZ(shp) => shp.bbox(),
+            Shape::Point(shp) => shp.bbox(),
+            Shape::PointM(shp) => shp.bbox(),
+            Shape::PointZ(shp) => shp.bbox(),
+            Shape::Polygon(shp) => shp.bbox(),
+            Shape::PolygonM(shp) => shp.bbox(),
+            Shape::PolygonZ(shp) => shp.bbox(),
+            Shape::Multipoint(shp) => shp.bbox(),
+            Shape::MultipointM(shp) => shp.bbox(),
+            Shape::MultipointZ(shp) => shp.bbox(),
+            Shape::Multipatch(shp) => shp.bbox(),
+            Shape::NullShape(_) | Shape::Unknown { .. } => BBox::new(0.0, 0.0, 0.0, 0.0),
+        }
+    }
+
+    fn z_range(&self) -> [f64; 2] {
+        match self {
+            Shape::PolylineZ(shp) => shp.z_range(),
+            Shape::PointZ(shp) => shp.z_range(),
+            Shape::PolygonZ(shp) => shp.z_range(),
+            Shape::MultipointZ(shp) => shp.z_range(),
+            Shape::Multipatch(shp) => shp.z_range(),
+            _ => [0.0, 0.0],
+        }
+    }
+
+    fn m_range(&self) -> [f64; 2] {
+        match self {
+            Shape::PolylineM(shp) => shp.m_range(),
+            Shape::PolylineZ(shp) => shp.m_range(),
+            Shape::PointM(shp) => shp.m_range(),
+            Shape::PointZ(shp) => shp.m_range(),
+            Shape::PolygonM(shp) => shp.m_range(),
+            Shape::PolygonZ(shp) => shp.m_range(),
+            Shape::MultipointM(shp) => shp.m_range(),
+            Shape::MultipointZ(shp) => shp.m_range(),
+            Shape::Multipatch(shp) => shp.m_range(),
+            _ => [0.0, 0.0],
+        }
+    }
+
+    fn has_measures(&self) -> bool {
+        match self {
+            Shape::PolylineM(shp) => shp.has_measures(),
+            Shape::PolylineZ(shp) => shp.has_measures(),
+            Shape::PointM(shp) => shp.has_measures(),
+            Shape::PointZ(shp) => shp.has_measures(),
+            Shape::PolygonM(shp) => shp.has_measures(),
+            Shape::PolygonZ(shp) => shp.has_measures(),
+            Shape::MultipointM(shp) => shp.has_measures(),
+            Shape::MultipointZ(shp) => shp.has_measures(),
+            Shape::Multipatch(shp) => shp.has_measures(),
+            _ => false,
+        }
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        match self {
+            Shape::Polygon(shp) => shp.validate(),
+            Shape::PolygonM(shp) => shp.validate(),
+            Shape::PolygonZ(shp) => shp.validate(),
+            _ => Ok(()),
+        }
+    }
+
+    fn close_rings(&mut self, tolerance: f64) {
+        match self {
+            Shape::Polygon(shp) => shp.close_rings(tolerance),
+            Shape::PolygonM(shp) => shp.close_rings(tolerance),
+            Shape::PolygonZ(shp) => shp.close_rings(tolerance),
+            _ => {}
+        }
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        match self {
+            Shape::Polyline(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PolylineM(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PolylineZ(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::Point(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PointM(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PointZ(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::Polygon(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PolygonM(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::PolygonZ(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::Multipoint(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::MultipointM(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::MultipointZ(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::Multipatch(shp) => shp.snap_to_precision(decimals, remove_repeated_points),
+            Shape::NullShape(_) | Shape::Unknown { .. } => {}
+        }
+    }
+
+    fn canonicalize(&mut self) {
+        match self {
+            Shape::Polyline(shp) => shp.canonicalize(),
+            Shape::PolylineM(shp) => shp.canonicalize(),
+            Shape::PolylineZ(shp) => shp.canonicalize(),
+            Shape::Point(shp) => shp.canonicalize(),
+            Shape::PointM(shp) => shp.canonicalize(),
+            Shape::PointZ(shp) => shp.canonicalize(),
+            Shape::Polygon(shp) => shp.canonicalize(),
+            Shape::PolygonM(shp) => shp.canonicalize(),
+            Shape::PolygonZ(shp) => shp.canonicalize(),
+            Shape::Multipoint(shp) => shp.canonicalize(),
+            Shape::MultipointM(shp) => shp.canonicalize(),
+            Shape::MultipointZ(shp) => shp.canonicalize(),
+            Shape::Multipatch(shp) => shp.canonicalize(),
+            Shape::NullShape(_) | Shape::Unknown { .. } => {}
         }
     }
 }
@@ -247,7 +1181,11 @@ impl fmt::Display for Shape {
             Shape::MultipointM(shp) => write!(f, "{}", shp),
             Shape::MultipointZ(shp) => write!(f, "{}", shp),
             Shape::Multipatch(shp) => write!(f, "{}", shp),
-            Shape::NullShape => write!(f, "NullShape"),
+            Shape::NullShape(shp) => write!(f, "{}", shp),
+            Shape::Unknown {
+                shape_type,
+                content,
+            } => write!(f, "Unknown({}, {} bytes)", shape_type, content.len()),
         }
     }
 }
@@ -263,7 +1201,8 @@ impl TryFrom<Shape> for geo_types::Geometry<f64> {
     fn try_from(shape: Shape) -> Result<Self, Self::Error> {
         use geo_types::Geometry;
         match shape {
-            Shape::NullShape => Err(Error::NullShapeConversion),
+            Shape::NullShape(_) => Err(Error::NullShapeConversion),
+            Shape::Unknown { shape_type, .. } => Err(Error::InvalidShapeType(shape_type)),
             Shape::Point(point) => Ok(Geometry::Point(geo_types::Point::from(point))),
             Shape::PointM(point) => Ok(Geometry::Point(geo_types::Point::from(point))),
             Shape::PointZ(point) => Ok(Geometry::Point(geo_types::Point::from(point))),
@@ -307,7 +1246,7 @@ impl TryFrom<geo_types::Geometry<f64>> for Shape {
 
 
 /// 2D (x, y) Bounding box
-#[derive(Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub struct BBox {
     pub xmin: f64,
     pub ymin: f64,
@@ -319,10 +1258,10 @@ impl BBox {
     /// Creates a new bounding box by computing the extent from
     /// any slice of points that have a x and y coordinates
     pub fn from_points<PointType: HasXY>(points: &[PointType]) -> Self {
-        let mut xmin = std::f64::MAX;
-        let mut ymin = std::f64::MAX;
-        let mut xmax = std::f64::MIN;
-        let mut ymax = std::f64::MIN;
+        let mut xmin = f64::MAX;
+        let mut ymin = f64::MAX;
+        let mut xmax = f64::MIN;
+        let mut ymax = f64::MIN;
 
         for point in points {
             xmin = f64::min(xmin, point.x());
@@ -338,6 +1277,8 @@ impl BBox {
         }
     }
 
+    /// Creates a new bounding box, the coordinates are not checked
+    /// (see [try_new](#method.try_new))
     pub fn new(xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Self {
         BBox {
             xmin,
@@ -347,6 +1288,49 @@ impl BBox {
         }
     }
 
+    /// Creates a new bounding box, checking that the coordinates are finite
+    /// and that the min are not greater than the max
+    ///
+    /// # Errors
+    ///
+    /// `Error::InvalidBBox` if they are not.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// assert!(BBox::try_new(0.0, 0.0, 10.0, 5.0).is_ok());
+    /// assert!(BBox::try_new(10.0, 0.0, 0.0, 5.0).is_err());
+    /// assert!(BBox::try_new(0.0, 0.0, f64::INFINITY, 5.0).is_err());
+    /// ```
+    pub fn try_new(xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Result<Self, Error> {
+        let bbox = BBox::new(xmin, ymin, xmax, ymax);
+        let is_finite = [xmin, ymin, xmax, ymax].iter().all(|c| c.is_finite());
+        if is_finite && xmin <= xmax && ymin <= ymax {
+            Ok(bbox)
+        } else {
+            Err(Error::InvalidBBox(bbox))
+        }
+    }
+
+    /// Returns true if both bounding boxes have at least one point in common
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// let bbox = BBox::new(0.0, 0.0, 10.0, 10.0);
+    /// assert!(bbox.intersects(&BBox::new(5.0, 5.0, 15.0, 15.0)));
+    /// assert!(bbox.intersects(&BBox::new(10.0, 10.0, 15.0, 15.0)));
+    /// assert!(!bbox.intersects(&BBox::new(11.0, 0.0, 15.0, 15.0)));
+    /// ```
+    pub fn intersects(&self, other: &BBox) -> bool {
+        self.xmin <= other.xmax
+            && other.xmin <= self.xmax
+            && self.ymin <= other.ymax
+            && other.ymin <= self.ymax
+    }
+
     pub fn read_from<T: Read>(mut source: T) -> Result<BBox, std::io::Error> {
         let xmin = source.read_f64::<LittleEndian>()?;
         let ymin = source.read_f64::<LittleEndian>()?;
@@ -369,6 +1353,45 @@ impl BBox {
     }
 }
 
+/// The bounding box of two opposite corners, given in any order
+///
+/// # Example
+///
+/// ```
+/// use shapefile::record::BBox;
+/// use shapefile::Point;
+/// let bbox = BBox::from((Point::new(10.0, 0.0), Point::new(0.0, 5.0)));
+/// assert_eq!(bbox, BBox::new(0.0, 0.0, 10.0, 5.0));
+/// ```
+impl From<(Point, Point)> for BBox {
+    fn from(corners: (Point, Point)) -> Self {
+        BBox::from_points(&[corners.0, corners.1])
+    }
+}
+
+#[cfg(feature = "geo-types")]
+impl From<BBox> for geo_types::Rect<f64> {
+    fn from(bbox: BBox) -> Self {
+        geo_types::Rect {
+            min: geo_types::Coordinate {
+                x: bbox.xmin,
+                y: bbox.ymin,
+            },
+            max: geo_types::Coordinate {
+                x: bbox.xmax,
+                y: bbox.ymax,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "geo-types")]
+impl From<geo_types::Rect<f64>> for BBox {
+    fn from(rect: geo_types::Rect<f64>) -> Self {
+        BBox::new(rect.min.x, rect.min.y, rect.max.x, rect.max.y)
+    }
+}
+
 /// Header of a shape record, present before any shape record
 pub(crate) struct RecordHeader {
     pub record_number: i32,
@@ -376,7 +1399,7 @@ pub(crate) struct RecordHeader {
 }
 
 impl RecordHeader {
-    pub(crate) const SIZE: usize = 2 * std::mem::size_of::<i32>();
+    pub(crate) const SIZE: usize = RECORD_HEADER_SIZE;
 
     pub fn read_from<T: Read>(source: &mut T) -> Result<RecordHeader, Error> {
         let record_number = source.read_i32::<BigEndian>()?;
@@ -473,6 +1496,39 @@ macro_rules! impl_try_from_shape {
     };
 }
 
+/// macro to implement the ShapeRanges trait with the inherent methods of the shapes
+/// which points have measures (`m`) and z values (`z`)
+macro_rules! impl_shape_ranges_for {
+    ($ShapeType:ident) => {
+        impl ShapeRanges for $ShapeType {}
+    };
+    ($ShapeType:ident, m) => {
+        impl ShapeRanges for $ShapeType {
+            fn m_extent(&self) -> Option<[f64; 2]> {
+                if self.has_measures() {
+                    Some(self.m_range())
+                } else {
+                    None
+                }
+            }
+        }
+    };
+    ($ShapeType:ident, z, m) => {
+        impl ShapeRanges for $ShapeType {
+            fn z_extent(&self) -> Option<[f64; 2]> {
+                Some(self.z_range())
+            }
+            fn m_extent(&self) -> Option<[f64; 2]> {
+                if self.has_measures() {
+                    Some(self.m_range())
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
+
 macro_rules! impl_to_way_conversion {
     (Shape::$ShapeEnumVariant:ident<=>$ConcreteShape:ident) => {
         impl_try_from_shape!(Shape::$ShapeEnumVariant => $ConcreteShape);
@@ -480,6 +1536,7 @@ macro_rules! impl_to_way_conversion {
     };
 }
 
+impl_concrete_shape_for!(NullShape);
 impl_concrete_shape_for!(Point);
 impl_concrete_shape_for!(PointM);
 impl_concrete_shape_for!(PointZ);
@@ -494,6 +1551,50 @@ impl_concrete_shape_for!(MultipointM);
 impl_concrete_shape_for!(MultipointZ);
 impl_concrete_shape_for!(Multipatch);
 
+impl_shape_ranges_for!(NullShape);
+impl_shape_ranges_for!(Point);
+impl_shape_ranges_for!(PointM, m);
+impl_shape_ranges_for!(PointZ, z, m);
+impl_shape_ranges_for!(Polyline);
+impl_shape_ranges_for!(PolylineM, m);
+impl_shape_ranges_for!(PolylineZ, z, m);
+impl_shape_ranges_for!(Polygon);
+impl_shape_ranges_for!(PolygonM, m);
+impl_shape_ranges_for!(PolygonZ, z, m);
+impl_shape_ranges_for!(Multipoint);
+impl_shape_ranges_for!(MultipointM, m);
+impl_shape_ranges_for!(MultipointZ, z, m);
+impl_shape_ranges_for!(Multipatch, z, m);
+
+impl ShapeRanges for Shape {
+    fn z_extent(&self) -> Option<[f64; 2]> {
+        match self {
+            Shape::PointZ(shp) => shp.z_extent(),
+            Shape::PolylineZ(shp) => shp.z_extent(),
+            Shape::PolygonZ(shp) => shp.z_extent(),
+            Shape::MultipointZ(shp) => shp.z_extent(),
+            Shape::Multipatch(shp) => shp.z_extent(),
+            _ => None,
+        }
+    }
+
+    fn m_extent(&self) -> Option<[f64; 2]> {
+        match self {
+            Shape::PointM(shp) => shp.m_extent(),
+            Shape::PointZ(shp) => shp.m_extent(),
+            Shape::PolylineM(shp) => shp.m_extent(),
+            Shape::PolylineZ(shp) => shp.m_extent(),
+            Shape::PolygonM(shp) => shp.m_extent(),
+            Shape::PolygonZ(shp) => shp.m_extent(),
+            Shape::MultipointM(shp) => shp.m_extent(),
+            Shape::MultipointZ(shp) => shp.m_extent(),
+            Shape::Multipatch(shp) => shp.m_extent(),
+            _ => None,
+        }
+    }
+}
+
+impl_to_way_conversion!(Shape::NullShape <=> NullShape);
 impl_to_way_conversion!(Shape::Point <=> Point);
 impl_to_way_conversion!(Shape::PointM <=> PointM);
 impl_to_way_conversion!(Shape::PointZ <=> PointZ);
@@ -555,4 +1656,179 @@ mod tests {
         ];
         assert!(convert_shapes_to_vec_of::<Point>(shapes).is_ok());
     }
+
+    /// Writes the shape and reads the f64 at `offset` and `offset + 8` of the content
+    fn written_range_at<S: WritableShape>(shape: S, offset: usize) -> [f64; 2] {
+        use byteorder::{ByteOrder, LittleEndian};
+        let mut content = Vec::<u8>::new();
+        shape.write_to(&mut content).unwrap();
+        [
+            LittleEndian::read_f64(&content[offset..]),
+            LittleEndian::read_f64(&content[offset + 8..]),
+        ]
+    }
+
+    #[test]
+    fn ranges_of_empty_shapes() {
+        let empty = [0.0, 0.0];
+        assert_eq!(PolylineM::new(vec![], vec![]).m_range(), empty);
+        assert_eq!(PolylineZ::new(vec![], vec![]).z_range(), empty);
+        assert_eq!(PolylineZ::new(vec![], vec![]).m_range(), empty);
+        assert_eq!(PolygonM::new(vec![], vec![]).m_range(), empty);
+        assert_eq!(PolygonZ::new(vec![], vec![]).z_range(), empty);
+        assert_eq!(PolygonZ::new(vec![], vec![]).m_range(), empty);
+        assert_eq!(MultipointM::new(vec![]).m_range(), empty);
+        assert_eq!(MultipointZ::new(vec![]).z_range(), empty);
+        assert_eq!(MultipointZ::new(vec![]).m_range(), empty);
+        assert_eq!(Multipatch::new(vec![], vec![], vec![]).z_range(), empty);
+        assert_eq!(Multipatch::new(vec![], vec![], vec![]).m_range(), empty);
+        assert_eq!(PointM::new(0.0, 0.0, NO_DATA).m_range(), empty);
+
+        // Multipart shapes without parts cannot be written
+        // bbox, number of points
+        assert_eq!(written_range_at(MultipointZ::new(vec![]), 36), empty);
+        assert_eq!(written_range_at(MultipointZ::new(vec![]), 52), empty);
+        assert_eq!(written_range_at(MultipointM::new(vec![]), 36), empty);
+    }
+
+    #[test]
+    fn ranges_of_single_point_shapes() {
+        let point_m = PointM::new(1.0, 2.0, 4.0);
+        let point_z = PointZ::new(1.0, 2.0, 3.0, 4.0);
+        let z = [3.0, 3.0];
+        let m = [4.0, 4.0];
+        assert_eq!(point_m.m_range(), m);
+        assert_eq!(point_z.z_range(), z);
+        assert_eq!(point_z.m_range(), m);
+        assert_eq!(PolylineM::new(vec![point_m], vec![0]).m_range(), m);
+        assert_eq!(PolylineZ::new(vec![point_z], vec![0]).z_range(), z);
+        assert_eq!(PolylineZ::new(vec![point_z], vec![0]).m_range(), m);
+        assert_eq!(PolygonM::new(vec![point_m], vec![0]).m_range(), m);
+        assert_eq!(PolygonZ::new(vec![point_z], vec![0]).z_range(), z);
+        assert_eq!(PolygonZ::new(vec![point_z], vec![0]).m_range(), m);
+        assert_eq!(MultipointM::new(vec![point_m]).m_range(), m);
+        assert_eq!(MultipointZ::new(vec![point_z]).z_range(), z);
+        assert_eq!(MultipointZ::new(vec![point_z]).m_range(), m);
+        let multipatch = Multipatch::new(vec![point_z], vec![0], vec![PatchType::TriangleStrip]);
+        assert_eq!(multipatch.z_range(), z);
+        assert_eq!(multipatch.m_range(), m);
+
+        // bbox, number of parts and points, one part, one point
+        assert_eq!(written_range_at(PolylineZ::new(vec![point_z], vec![0]), 60), z);
+        assert_eq!(written_range_at(PolylineZ::new(vec![point_z], vec![0]), 84), m);
+        assert_eq!(written_range_at(PolygonM::new(vec![point_m], vec![0]), 60), m);
+        // bbox, number of points, one point
+        assert_eq!(written_range_at(MultipointZ::new(vec![point_z]), 52), z);
+        assert_eq!(written_range_at(MultipointZ::new(vec![point_z]), 76), m);
+        // bbox, number of parts and points, one part and its type, one point
+        assert_eq!(written_range_at(multipatch, 64), z);
+    }
+
+    /// One shape of each type, with the points (1, 2) and (3, 4)
+    fn one_shape_of_each_type() -> Vec<Shape> {
+        let points = vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)];
+        let points_m = vec![PointM::new(1.0, 2.0, 5.0), PointM::new(3.0, 4.0, 6.0)];
+        let points_z = vec![
+            PointZ::new(1.0, 2.0, 5.0, 6.0),
+            PointZ::new(3.0, 4.0, 7.0, 8.0),
+        ];
+        vec![
+            Shape::Point(points[0]),
+            Shape::PointM(points_m[0]),
+            Shape::PointZ(points_z[0]),
+            Shape::Polyline(Polyline::new(points.clone(), vec![0])),
+            Shape::PolylineM(PolylineM::new(points_m.clone(), vec![0])),
+            Shape::PolylineZ(PolylineZ::new(points_z.clone(), vec![0])),
+            Shape::Polygon(Polygon::new(points.clone(), vec![0])),
+            Shape::PolygonM(PolygonM::new(points_m.clone(), vec![0])),
+            Shape::PolygonZ(PolygonZ::new(points_z.clone(), vec![0])),
+            Shape::Multipoint(Multipoint::new(points)),
+            Shape::MultipointM(MultipointM::new(points_m)),
+            Shape::MultipointZ(MultipointZ::new(points_z.clone())),
+            Shape::Multipatch(Multipatch::new(
+                points_z,
+                vec![0],
+                vec![PatchType::TriangleStrip],
+            )),
+        ]
+    }
+
+    #[test]
+    fn as_concrete_shape() {
+        for shape in one_shape_of_each_type() {
+            let shapetype = shape.shapetype();
+            let is = |t: ShapeType| shapetype == t;
+            assert_eq!(shape.as_point().is_some(), is(ShapeType::Point));
+            assert_eq!(shape.as_point_m().is_some(), is(ShapeType::PointM));
+            assert_eq!(shape.as_point_z().is_some(), is(ShapeType::PointZ));
+            assert_eq!(shape.as_polyline().is_some(), is(ShapeType::Polyline));
+            assert_eq!(shape.as_polyline_m().is_some(), is(ShapeType::PolylineM));
+            assert_eq!(shape.as_polyline_z().is_some(), is(ShapeType::PolylineZ));
+            assert_eq!(shape.as_polygon().is_some(), is(ShapeType::Polygon));
+            assert_eq!(shape.as_polygon_m().is_some(), is(ShapeType::PolygonM));
+            assert_eq!(shape.as_polygon_z().is_some(), is(ShapeType::PolygonZ));
+            assert_eq!(shape.as_multipoint().is_some(), is(ShapeType::Multipoint));
+            assert_eq!(shape.as_multipoint_m().is_some(), is(ShapeType::MultipointM));
+            assert_eq!(shape.as_multipoint_z().is_some(), is(ShapeType::MultipointZ));
+            assert_eq!(shape.as_multipatch().is_some(), is(ShapeType::Multipatch));
+        }
+
+        let shape = Shape::Polyline(Polyline::new(vec![Point::new(1.0, 2.0)], vec![0]));
+        let polyline = shape.as_polyline().unwrap();
+        assert_eq!(polyline.points(), &[Point::new(1.0, 2.0)]);
+
+        let shape = Shape::NullShape(NullShape);
+        assert!(shape.as_point().is_none());
+        assert!(shape.as_polyline().is_none());
+        assert!(shape.as_multipatch().is_none());
+    }
+
+    #[test]
+    fn points_xy_of_each_type() {
+        for shape in one_shape_of_each_type() {
+            let expected = match shape.shapetype() {
+                ShapeType::Point | ShapeType::PointM | ShapeType::PointZ => vec![(1.0, 2.0)],
+                _ => vec![(1.0, 2.0), (3.0, 4.0)],
+            };
+            assert_eq!(
+                shape.points_xy().collect::<Vec<_>>(),
+                expected,
+                "{}",
+                shape.shapetype()
+            );
+        }
+        assert_eq!(Shape::NullShape(NullShape).points_xy().count(), 0);
+    }
+
+    #[test]
+    fn geometry_class_of_every_variant() {
+        let shapes = vec![
+            (Shape::Point(Point::default()), GeometryClass::Point),
+            (Shape::PointM(PointM::default()), GeometryClass::Point),
+            (Shape::PointZ(PointZ::default()), GeometryClass::Point),
+            (Shape::Multipoint(Multipoint::new(vec![])), GeometryClass::Multipoint),
+            (Shape::MultipointM(MultipointM::new(vec![])), GeometryClass::Multipoint),
+            (Shape::MultipointZ(MultipointZ::new(vec![])), GeometryClass::Multipoint),
+            (Shape::Polyline(Polyline::new(vec![], vec![])), GeometryClass::Polyline),
+            (Shape::PolylineM(PolylineM::new(vec![], vec![])), GeometryClass::Polyline),
+            (Shape::PolylineZ(PolylineZ::new(vec![], vec![])), GeometryClass::Polyline),
+            (Shape::Polygon(Polygon::new(vec![], vec![])), GeometryClass::Polygon),
+            (Shape::PolygonM(PolygonM::new(vec![], vec![])), GeometryClass::Polygon),
+            (Shape::PolygonZ(PolygonZ::new(vec![], vec![])), GeometryClass::Polygon),
+            (
+                Shape::Multipatch(Multipatch::new(vec![], vec![], vec![])),
+                GeometryClass::Multipatch,
+            ),
+            (Shape::NullShape(NullShape), GeometryClass::Null),
+        ];
+        for (shape, class) in shapes {
+            assert_eq!(shape.group(), class, "{}", shape.shapetype());
+            assert_eq!(shape.is_point_like(), class == GeometryClass::Point);
+            assert_eq!(shape.is_multipoint_like(), class == GeometryClass::Multipoint);
+            assert_eq!(shape.is_polyline_like(), class == GeometryClass::Polyline);
+            assert_eq!(shape.is_polygon_like(), class == GeometryClass::Polygon);
+            let is_multipart = !matches!(class, GeometryClass::Point | GeometryClass::Multipoint);
+            assert_eq!(shape.shapetype().is_multipart(), is_multipart);
+        }
+    }
 }
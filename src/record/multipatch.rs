@@ -2,22 +2,27 @@
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
 
 use std::fmt;
-use std::io::{Read, Write};
+use std::io::{Read, Take, Write};
 use std::mem::size_of;
 use std::slice::SliceIndex;
 
+use axis::{reverse_rings, swap_bbox_xy, swap_points_xy};
+use record::canonical::canonicalize_points;
+use record::snap::{scale_of, snap_points};
 use record::io::*;
 use record::is_parts_array_valid;
+use record::poly::PolygonZ;
+use record::ring_signed_area;
 use record::traits::{MultipartShape, MultipointShape};
 use record::BBox;
-use record::ConcreteReadableShape;
-use record::{EsriShape, HasShapeType, Point, PointZ, WritableShape};
+use record::{ConcreteReadableShape, RecordSizeCheck};
+use record::{EsriShape, HasShapeType, Point, PointZ, Shape, WritableShape};
 use {Error, ShapeType};
 
+use std::convert::TryFrom;
+
 #[cfg(feature = "geo-types")]
 use geo_types;
-#[cfg(feature = "geo-types")]
-use std::convert::TryFrom;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum PatchType {
@@ -32,7 +37,7 @@ pub enum PatchType {
 impl PatchType {
     pub fn read_from<T: Read>(source: &mut T) -> Result<PatchType, Error> {
         let code = source.read_i32::<LittleEndian>()?;
-        Self::from(code).ok_or_else(|| Error::InvalidPatchType(code))
+        Self::from(code).ok_or(Error::InvalidPatchType(code))
     }
 
     pub fn from(code: i32) -> Option<PatchType> {
@@ -46,8 +51,20 @@ impl PatchType {
             _ => None,
         }
     }
+
+    /// Returns whether the patch is a ring, that is, anything but a triangle strip or fan
+    pub fn is_ring(self) -> bool {
+        match self {
+            PatchType::TriangleStrip | PatchType::TriangleFan => false,
+            PatchType::OuterRing
+            | PatchType::InnerRing
+            | PatchType::FirstRing
+            | PatchType::Ring => true,
+        }
+    }
 }
 
+#[derive(Clone)]
 pub struct Multipatch {
     pub bbox: BBox,
     pub points: Vec<PointZ>,
@@ -55,9 +72,50 @@ pub struct Multipatch {
     pub parts_type: Vec<PatchType>,
     pub z_range: [f64; 2],
     pub m_range: [f64; 2],
+    /// Whether the optional M values (and M range) are written
+    pub(crate) m_present: bool,
 }
 
 impl Multipatch {
+    /// Swaps the x and y coordinates of the points, and updates the bbox.
+    ///
+    /// The order of the points of the rings is reversed to keep their orientation,
+    /// the triangle strips and fans are left as is (so they now face the other way).
+    pub fn swap_xy(&mut self) {
+        swap_points_xy(&mut self.points);
+        let parts_type = &self.parts_type;
+        reverse_rings(&mut self.points, &self.parts, |i| {
+            parts_type.get(i).map_or(false, |patch_type| patch_type.is_ring())
+        });
+        self.bbox = swap_bbox_xy(&self.bbox);
+    }
+
+    /// Returns the range of the z values (the `z_range` field)
+    pub fn z_range(&self) -> [f64; 2] {
+        self.z_range
+    }
+
+    /// Returns the range of the measures that are not NO_DATA (the `m_range` field)
+    pub fn m_range(&self) -> [f64; 2] {
+        self.m_range
+    }
+
+    /// Returns true if at least one of the measures is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        has_measures(&self.points)
+    }
+
+    /// Returns whether the optional M values (and M range) of the record are written,
+    /// see [GenericPolyline::m_present](../poly/struct.GenericPolyline.html#method.m_present)
+    pub fn m_present(&self) -> bool {
+        self.m_present
+    }
+
+    /// Sets whether the optional M values (and M range) of the record are written
+    pub fn set_m_present(&mut self, m_present: bool) {
+        self.m_present = m_present;
+    }
+
     pub fn new(points: Vec<PointZ>, parts: Vec<i32>, parts_type: Vec<PatchType>) -> Self {
         let bbox = BBox::from_points(&points);
         let m_range = calc_m_range(&points);
@@ -69,7 +127,61 @@ impl Multipatch {
             parts_type,
             z_range,
             m_range,
+            m_present: true,
+        }
+    }
+
+    /// Creates a multipatch from its patches, building the points, parts
+    /// and parts type together so that they always agree.
+    ///
+    /// Rings (all the patch types except the triangle strips and fans)
+    /// that are not closed are closed by repeating their first point.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::TooFewPointsInRing` (with the index of the patch) if a ring
+    ///   has less than 4 points once closed
+    /// - `Error::TooFewPointsInTriangles` (with the index of the patch) if a triangle
+    ///   strip or fan has less than 3 points
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{Multipatch, PatchType, PointZ, NO_DATA};
+    /// let point = |x, y| PointZ::new(x, y, 0.0, NO_DATA);
+    /// let multipatch = Multipatch::from_patches(vec![
+    ///     (PatchType::TriangleFan, vec![point(0.0, 0.0), point(0.0, 1.0), point(1.0, 1.0)]),
+    ///     (PatchType::OuterRing, vec![point(5.0, 5.0), point(5.0, 6.0), point(6.0, 6.0)]),
+    /// ])?;
+    /// assert_eq!(multipatch.parts, vec![0, 3]);
+    /// assert_eq!(multipatch.points.len(), 7);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_patches(patches: Vec<(PatchType, Vec<PointZ>)>) -> Result<Self, Error> {
+        let num_points = patches.iter().map(|(_, points)| points.len() + 1).sum();
+        let mut points = Vec::<PointZ>::with_capacity(num_points);
+        let mut parts = Vec::<i32>::with_capacity(patches.len());
+        let mut parts_type = Vec::<PatchType>::with_capacity(patches.len());
+        for (i, (patch_type, mut patch_points)) in patches.into_iter().enumerate() {
+            if patch_type.is_ring() {
+                if let (Some(first), Some(last)) = (patch_points.first(), patch_points.last()) {
+                    if first != last {
+                        patch_points.push(*first);
+                    }
+                }
+                if patch_points.len() < 4 {
+                    return Err(Error::TooFewPointsInRing(i));
+                }
+            } else if patch_points.len() < 3 {
+                return Err(Error::TooFewPointsInTriangles(i));
+            }
+            parts.push(points.len() as i32);
+            parts_type.push(patch_type);
+            points.append(&mut patch_points);
         }
+        Ok(Self::new(points, parts, parts_type))
     }
 
     pub(crate) fn size_of_record(num_points: i32, num_parts: i32, is_m_used: bool) -> usize {
@@ -91,6 +203,101 @@ impl Multipatch {
     }
 }
 
+/// Adds the ring to the points and parts of a polygon,
+/// in clockwise order for an outer ring and counterclockwise order for a hole
+fn push_ring(points: &mut Vec<PointZ>, parts: &mut Vec<i32>, ring: &[PointZ], is_outer: bool) {
+    let start = points.len();
+    parts.push(start as i32);
+    points.extend_from_slice(ring);
+    if ring.first() != ring.last() {
+        points.push(ring[0]);
+    }
+    if (ring_signed_area(&points[start..]) >= 0.0) != is_outer {
+        points[start..].reverse();
+    }
+}
+
+/// Converts the multipatch into a polygon, each patch giving rings of the polygon:
+///
+/// - the triangles of the triangle strips and fans each give an outer ring
+/// - outer rings and first rings give outer rings
+/// - inner rings give holes of the last outer ring
+/// - rings following a first ring give holes of the first ring,
+///   the other rings give outer rings
+///
+/// The rings are reordered to be clockwise for the outer rings and counterclockwise
+/// for the holes, and closed if they are not.
+///
+/// # Errors
+///
+/// - `Error::TooFewPointsInTriangles` (with the index of the patch) if a triangle
+///   strip or fan has less than 3 points
+/// - `Error::TooFewPointsInRing` (with the index of the patch) if a ring has less than 3 points
+/// - `Error::OrphanInnerRing` if an inner ring is not preceded by an outer ring
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use std::convert::TryFrom;
+/// use shapefile::{Multipatch, PatchType, PointZ, PolygonZ, NO_DATA};
+/// let point = |x, y| PointZ::new(x, y, 0.0, NO_DATA);
+/// let multipatch = Multipatch::from_patches(vec![(
+///     PatchType::TriangleStrip,
+///     vec![point(0.0, 0.0), point(0.0, 1.0), point(1.0, 0.0), point(1.0, 1.0)],
+/// )])?;
+/// let polygon = PolygonZ::try_from(multipatch)?;
+/// assert_eq!(polygon.parts, vec![0, 4]);
+/// # Ok(())
+/// # }
+/// ```
+impl TryFrom<Multipatch> for PolygonZ {
+    type Error = Error;
+
+    fn try_from(multipatch: Multipatch) -> Result<Self, Self::Error> {
+        let mut points = Vec::<PointZ>::with_capacity(multipatch.points.len());
+        let mut parts = Vec::<i32>::with_capacity(multipatch.parts.len());
+        let mut has_outer_ring = false;
+        let mut follows_first_ring = false;
+        for (i, (patch, patch_type)) in multipatch.parts().zip(&multipatch.parts_type).enumerate()
+        {
+            match patch_type {
+                PatchType::TriangleStrip | PatchType::TriangleFan => {
+                    if patch.len() < 3 {
+                        return Err(Error::TooFewPointsInTriangles(i));
+                    }
+                    for j in 2..patch.len() {
+                        let triangle = if *patch_type == PatchType::TriangleStrip {
+                            [patch[j - 2], patch[j - 1], patch[j]]
+                        } else {
+                            [patch[0], patch[j - 1], patch[j]]
+                        };
+                        push_ring(&mut points, &mut parts, &triangle, true);
+                    }
+                    has_outer_ring = true;
+                    follows_first_ring = false;
+                    continue;
+                }
+                _ if patch.len() < 3 => return Err(Error::TooFewPointsInRing(i)),
+                PatchType::OuterRing => push_ring(&mut points, &mut parts, patch, true),
+                PatchType::InnerRing if !has_outer_ring => return Err(Error::OrphanInnerRing),
+                PatchType::InnerRing => push_ring(&mut points, &mut parts, patch, false),
+                PatchType::FirstRing => push_ring(&mut points, &mut parts, patch, true),
+                PatchType::Ring => push_ring(&mut points, &mut parts, patch, !follows_first_ring),
+            }
+            has_outer_ring = true;
+            follows_first_ring = match patch_type {
+                PatchType::FirstRing => true,
+                PatchType::Ring => follows_first_ring,
+                _ => false,
+            };
+        }
+        let mut polygon = PolygonZ::new(points, parts);
+        polygon.m_present = multipatch.m_present;
+        Ok(polygon)
+    }
+}
+
 impl fmt::Display for Multipatch {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -127,23 +334,26 @@ impl HasShapeType for Multipatch {
 }
 
 impl ConcreteReadableShape for Multipatch {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
-        let bbox = BBox::read_from(&mut source)?;
-        let num_parts = source.read_i32::<LittleEndian>()?;
-        let num_points = source.read_i32::<LittleEndian>()?;
-
-        let record_size_with_m = Self::size_of_record(num_points, num_parts, true) as i32;
-        let record_size_without_m = Self::size_of_record(num_points, num_parts, false) as i32;
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
 
-        if (record_size != record_size_with_m) & (record_size != record_size_without_m) {
-            return Err(Error::InvalidShapeRecordSize);
-        }
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
+        let bbox = BBox::read_from(&mut source)?;
+        let num_parts = read_count(&mut source)?;
+        let num_points = read_count(&mut source)?;
 
-        let is_m_used = record_size == record_size_with_m;
+        let m_layout = m_layout_of_record(record_size, size_check, |is_m_used| {
+            Self::size_of_record(num_points, num_parts, is_m_used)
+        })?;
 
         let parts = read_parts(&mut source, num_parts)?;
 
-        let mut parts_type = Vec::<PatchType>::with_capacity(num_parts as usize);
+        let mut parts_type = vec_with_count_capacity::<PatchType>(num_parts);
         for _ in 0..num_parts {
             let code = source.read_i32::<LittleEndian>()?;
             match PatchType::from(code) {
@@ -151,16 +361,12 @@ impl ConcreteReadableShape for Multipatch {
                 None => return Err(Error::InvalidPatchType(code)),
             }
         }
-        let mut points = read_xy_in_vec_of::<PointZ, T>(&mut source, num_points)?;
+        let mut points = read_xy_in_vec_of::<PointZ, _>(&mut source, num_points)?;
 
         let z_range = read_range(&mut source)?;
         read_zs_into(&mut source, &mut points)?;
 
-        let mut m_range = [0.0, 0.0];
-        if is_m_used {
-            m_range = read_range(&mut source)?;
-            read_ms_into(&mut source, &mut points)?;
-        }
+        let m_range = read_optional_ms_into(&mut source, &mut points, m_layout)?;
 
         Ok(Self {
             bbox,
@@ -168,9 +374,14 @@ impl ConcreteReadableShape for Multipatch {
             parts_type,
             points,
             z_range,
-            m_range,
+            m_range: m_range.unwrap_or([0.0, 0.0]),
+            m_present: m_range.is_some(),
         })
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for Multipatch {
@@ -181,14 +392,17 @@ impl WritableShape for Multipatch {
         size += size_of::<i32>();
         size += size_of::<i32>() * self.parts.len();
         size += size_of::<i32>() * self.parts_type.len();
-        size += 4 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 3 * size_of::<f64>() * self.points.len();
         size += 2 * size_of::<f64>();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
     fn write_to<T: Write>(self, mut dest: &mut T) -> Result<(), Error> {
-        if !is_parts_array_valid(&self) {
+        if !is_parts_array_valid(&self) || self.parts.len() != self.parts_type.len() {
             return Err(Error::MalformedShape);
         }
         self.bbox.write_to(&mut dest)?;
@@ -203,8 +417,10 @@ impl WritableShape for Multipatch {
         write_range(&mut dest, self.z_range())?;
         write_zs(&mut dest, &self.points)?;
 
-        write_range(&mut dest, self.m_range())?;
-        write_ms(&mut dest, &self.points)?;
+        if self.m_present || has_measures(&self.points) {
+            write_range(&mut dest, self.m_range())?;
+            write_ms(&mut dest, &self.points)?;
+        }
         Ok(())
     }
 }
@@ -215,11 +431,29 @@ impl EsriShape for Multipatch {
     }
 
     fn z_range(&self) -> [f64; 2] {
-        self.z_range
+        self.z_range()
     }
 
     fn m_range(&self) -> [f64; 2] {
-        self.m_range
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    /// The points of a multipatch are never removed, as they may be part of triangle strips
+    fn snap_to_precision(&mut self, decimals: u8, _remove_repeated_points: bool) {
+        snap_points(&mut self.points, scale_of(decimals), false);
+        self.bbox = BBox::from_points(&self.points);
+        self.z_range = calc_z_range(&self.points);
+        self.m_range = calc_m_range(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
+        self.z_range = calc_z_range(&self.points);
+        self.m_range = calc_m_range(&self.points);
     }
 }
 
@@ -302,7 +536,7 @@ impl From<geo_types::Polygon<f64>> for Multipatch {
         let (outer_ls, inners_ls) = polygon.into_inner();
         let mut outer_points = outer_ls
             .into_iter()
-            .map(|c| PointZ::from(c))
+            .map(PointZ::from)
             .collect::<Vec<PointZ>>();
 
         if !is_outer_ring(&outer_points) {
@@ -314,7 +548,7 @@ impl From<geo_types::Polygon<f64>> for Multipatch {
             parts.push((all_points.len() - 1) as i32);
             let mut inner_points = inner_ls
                 .into_iter()
-                .map(|c| PointZ::from(c))
+                .map(PointZ::from)
                 .collect::<Vec<PointZ>>();
 
             if is_outer_ring(&inner_points) {
@@ -334,7 +568,7 @@ impl From<geo_types::MultiPolygon<f64>> for Multipatch {
     fn from(multi_polygon: geo_types::MultiPolygon<f64>) -> Self {
         let multipatches = multi_polygon
             .into_iter()
-            .map(|polyg| Multipatch::from(polyg))
+            .map(Multipatch::from)
             .collect::<Vec<Multipatch>>();
 
         let total_points_count = multipatches
@@ -6,18 +6,22 @@
 //! The `GenericMultipoint` Shape implements the [MultipointShape](../trait.MultipointShape.html) trait
 //! which means that to access the points of a multipoint you will have to use the
 //! [points](../trait.MultipointShape.html#method.points) method
+use std::convert::TryFrom;
 use std::fmt;
-use std::io::{Read, Write};
+use std::io::{Read, Take, Write};
 use std::mem::size_of;
 use std::slice::SliceIndex;
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
 
+use axis::{swap_bbox_xy, swap_points_xy};
+use record::canonical::canonicalize_points;
+use record::snap::{scale_of, snap_points};
 use record::io::*;
-use record::traits::{HasXY, MultipointShape};
-use record::ConcreteReadableShape;
+use record::traits::{HasM, HasMutXY, HasXY, HasZ, MultipointShape};
+use record::{ConcreteReadableShape, RecordSizeCheck};
 use record::{BBox, EsriShape};
-use record::{HasShapeType, WritableShape};
+use record::{HasShapeType, Shape, WritableShape};
 use record::{Point, PointM, PointZ};
 use {Error, ShapeType};
 
@@ -25,10 +29,13 @@ use {Error, ShapeType};
 use geo_types;
 
 /// Generic struct to create the Multipoint, MultipointM, MultipointZ types
+#[derive(Clone)]
 pub struct GenericMultipoint<PointType> {
     /// The 2D bounding box
     pub bbox: BBox,
     pub points: Vec<PointType>,
+    /// Whether the optional M values (and M range) are written
+    pub(crate) m_present: bool,
 }
 
 impl<PointType> MultipointShape<PointType> for GenericMultipoint<PointType> {
@@ -43,6 +50,44 @@ impl<PointType> MultipointShape<PointType> for GenericMultipoint<PointType> {
     }
 }
 
+impl<PointType: HasMutXY> GenericMultipoint<PointType> {
+    /// Swaps the x and y coordinates of the points, and updates the bbox
+    pub fn swap_xy(&mut self) {
+        swap_points_xy(&mut self.points);
+        self.bbox = swap_bbox_xy(&self.bbox);
+    }
+}
+
+impl<PointType: HasZ> GenericMultipoint<PointType> {
+    /// Returns the range of the z values, `[0.0, 0.0]` if there are no points
+    pub fn z_range(&self) -> [f64; 2] {
+        calc_z_range(&self.points)
+    }
+}
+
+impl<PointType: HasM> GenericMultipoint<PointType> {
+    /// Returns the range of the measures that are not NO_DATA, `[0.0, 0.0]` if there are none
+    pub fn m_range(&self) -> [f64; 2] {
+        calc_m_range(&self.points)
+    }
+
+    /// Returns true if at least one of the measures is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        has_measures(&self.points)
+    }
+
+    /// Returns whether the optional M values (and M range) of the record are written,
+    /// see [GenericPolyline::m_present](../poly/struct.GenericPolyline.html#method.m_present)
+    pub fn m_present(&self) -> bool {
+        self.m_present
+    }
+
+    /// Sets whether the optional M values (and M range) of the record are written
+    pub fn set_m_present(&mut self, m_present: bool) {
+        self.m_present = m_present;
+    }
+}
+
 impl<PointType: HasXY> GenericMultipoint<PointType> {
     /// Creates a new Multipoint shape
     ///
@@ -77,10 +122,48 @@ impl<PointType: HasXY> GenericMultipoint<PointType> {
     /// ];
     /// let multipointz = MultipointZ::new(points);
     /// ```
-
     pub fn new(points: Vec<PointType>) -> Self {
         let bbox = BBox::from_points(&points);
-        Self { bbox, points }
+        Self {
+            bbox,
+            points,
+            m_present: true,
+        }
+    }
+
+    /// Returns the index and the point closest to (`x`, `y`),
+    /// or `None` if the multipoint has no points.
+    ///
+    /// The distance is computed in 2D (z and m are ignored),
+    /// when points are at the same distance the one with the lowest index is returned.
+    /// Points with a NaN coordinate are never returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{MultipointZ, PointZ, NO_DATA};
+    /// let spot_heights = MultipointZ::new(vec![
+    ///     PointZ::new(0.0, 0.0, 120.0, NO_DATA),
+    ///     PointZ::new(10.0, 0.0, 135.0, NO_DATA),
+    /// ]);
+    /// let (index, point) = spot_heights.nearest_point(8.0, 1.0).unwrap();
+    /// assert_eq!(index, 1);
+    /// assert_eq!(point.z, 135.0);
+    /// ```
+    pub fn nearest_point(&self, x: f64, y: f64) -> Option<(usize, &PointType)> {
+        let mut nearest: Option<(usize, &PointType, f64)> = None;
+        for (i, point) in self.points.iter().enumerate() {
+            let dx = point.x() - x;
+            let dy = point.y() - y;
+            let distance = dx * dx + dy * dy;
+            if distance.is_nan() {
+                continue;
+            }
+            if nearest.map_or(true, |(_, _, min_distance)| distance < min_distance) {
+                nearest = Some((i, point, distance));
+            }
+        }
+        nearest.map(|(i, point, _)| (i, point))
     }
 }
 
@@ -138,16 +221,32 @@ impl HasShapeType for Multipoint {
 }
 
 impl ConcreteReadableShape for Multipoint {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
-        let num_points = source.read_i32::<LittleEndian>()?;
-        if record_size == Self::size_of_record(num_points) as i32 {
-            let points = read_xy_in_vec_of::<Point, T>(&mut source, num_points)?;
-            Ok(Self { bbox, points })
+        let num_points = read_count(&mut source)?;
+        if size_check.accepts(record_size, Self::size_of_record(num_points)) {
+            let points = read_xy_in_vec_of::<Point, _>(&mut source, num_points)?;
+            Ok(Self {
+                bbox,
+                points,
+                m_present: false,
+            })
         } else {
             Err(Error::InvalidShapeRecordSize)
         }
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for Multipoint {
@@ -174,6 +273,15 @@ impl EsriShape for Multipoint {
     fn bbox(&self) -> BBox {
         self.bbox
     }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        snap_points(&mut self.points, scale_of(decimals), remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
+    }
 }
 
 
@@ -211,26 +319,35 @@ impl HasShapeType for MultipointM {
 }
 
 impl ConcreteReadableShape for MultipointM {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
 
-        let num_points = source.read_i32::<LittleEndian>()?;
+        let num_points = read_count(&mut source)?;
 
-        let size_with_m = Self::size_of_record(num_points, true) as i32;
-        let size_without_m = Self::size_of_record(num_points, false) as i32;
+        let m_layout = m_layout_of_record(record_size, size_check, |is_m_used| {
+            Self::size_of_record(num_points, is_m_used)
+        })?;
 
-        if (record_size != size_with_m) & (record_size != size_without_m) {
-            Err(Error::InvalidShapeRecordSize)
-        } else {
-            let m_is_used = size_with_m == record_size;
-            let mut points = read_xy_in_vec_of::<PointM, T>(&mut source, num_points)?;
+        let mut points = read_xy_in_vec_of::<PointM, _>(&mut source, num_points)?;
 
-            if m_is_used {
-                let _m_range = read_range(&mut source)?;
-                read_ms_into(&mut source, &mut points)?;
-            }
-            Ok(Self { bbox, points })
-        }
+        let is_m_used = read_optional_ms_into(&mut source, &mut points, m_layout)?.is_some();
+        Ok(Self {
+            bbox,
+            points,
+            m_present: is_m_used,
+        })
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
@@ -239,8 +356,11 @@ impl WritableShape for MultipointM {
         let mut size = 0usize;
         size += 4 * size_of::<f64>();
         size += size_of::<i32>();
-        size += 3 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 2 * size_of::<f64>() * self.points.len();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -250,8 +370,10 @@ impl WritableShape for MultipointM {
 
         write_points(&mut dest, &self.points)?;
 
-        write_range(&mut dest, self.m_range())?;
-        write_ms(&mut dest, &self.points)?;
+        if self.m_present || has_measures(&self.points) {
+            write_range(&mut dest, self.m_range())?;
+            write_ms(&mut dest, &self.points)?;
+        }
         Ok(())
     }
 }
@@ -262,7 +384,20 @@ impl EsriShape for MultipointM {
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        snap_points(&mut self.points, scale_of(decimals), remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
@@ -301,29 +436,38 @@ impl HasShapeType for MultipointZ {
 }
 
 impl ConcreteReadableShape for MultipointZ {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
-        let num_points = source.read_i32::<LittleEndian>()?;
+        let num_points = read_count(&mut source)?;
 
-        let size_with_m = Self::size_of_record(num_points, true) as i32;
-        let size_without_m = Self::size_of_record(num_points, false) as i32;
+        let m_layout = m_layout_of_record(record_size, size_check, |is_m_used| {
+            Self::size_of_record(num_points, is_m_used)
+        })?;
 
-        if (record_size != size_with_m) & (record_size != size_without_m) {
-            Err(Error::InvalidShapeRecordSize)
-        } else {
-            let m_is_used = size_with_m == record_size;
-            let mut points = read_xy_in_vec_of::<PointZ, T>(&mut source, num_points)?;
+        let mut points = read_xy_in_vec_of::<PointZ, _>(&mut source, num_points)?;
 
-            let _z_range = read_range(&mut source)?;
-            read_zs_into(&mut source, &mut points)?;
+        let _z_range = read_range(&mut source)?;
+        read_zs_into(&mut source, &mut points)?;
 
-            if m_is_used {
-                let _m_range = read_range(&mut source)?;
-                read_ms_into(&mut source, &mut points)?;
-            }
+        let is_m_used = read_optional_ms_into(&mut source, &mut points, m_layout)?.is_some();
 
-            Ok(Self { bbox, points })
-        }
+        Ok(Self {
+            bbox,
+            points,
+            m_present: is_m_used,
+        })
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
@@ -332,9 +476,12 @@ impl WritableShape for MultipointZ {
         let mut size = 0usize;
         size += 4 * size_of::<f64>();
         size += size_of::<i32>();
-        size += 4 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 3 * size_of::<f64>() * self.points.len();
         size += 2 * size_of::<f64>();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -347,8 +494,10 @@ impl WritableShape for MultipointZ {
         write_range(&mut dest, self.z_range())?;
         write_zs(&mut dest, &self.points)?;
 
-        write_range(&mut dest, self.m_range())?;
-        write_ms(&mut dest, &self.points)?;
+        if self.m_present || has_measures(&self.points) {
+            write_range(&mut dest, self.m_range())?;
+            write_ms(&mut dest, &self.points)?;
+        }
 
         Ok(())
     }
@@ -360,14 +509,78 @@ impl EsriShape for MultipointZ {
     }
 
     fn z_range(&self) -> [f64; 2] {
-        calc_z_range(&self.points)
+        self.z_range()
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        snap_points(&mut self.points, scale_of(decimals), remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
+#[cfg(test)]
+mod nearest_point_tests {
+    use super::*;
+    use NO_DATA;
+
+    #[test]
+    fn nearest_point_of_empty_multipoint() {
+        assert!(Multipoint::new(vec![]).nearest_point(0.0, 0.0).is_none());
+        assert!(MultipointZ::new(vec![]).nearest_point(0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn nearest_point_is_2d() {
+        let multipoint = MultipointZ::new(vec![
+            PointZ::new(0.0, 0.0, 1000.0, NO_DATA),
+            PointZ::new(3.0, 4.0, 0.0, NO_DATA),
+        ]);
+        let (index, point) = multipoint.nearest_point(1.0, 1.0).unwrap();
+        assert_eq!(index, 0);
+        assert_eq!(point.z, 1000.0);
+
+        let multipoint = MultipointM::new(vec![
+            PointM::new(0.0, 0.0, 1.0),
+            PointM::new(3.0, 4.0, 2.0),
+        ]);
+        assert_eq!(multipoint.nearest_point(2.5, 3.5).unwrap().0, 1);
+    }
+
+    #[test]
+    fn nearest_point_ties_give_lowest_index() {
+        let multipoint = Multipoint::new(vec![
+            Point::new(5.0, 5.0),
+            Point::new(1.0, 0.0),
+            Point::new(-1.0, 0.0),
+            Point::new(1.0, 0.0),
+        ]);
+        assert_eq!(
+            multipoint.nearest_point(0.0, 0.0),
+            Some((1, &Point::new(1.0, 0.0)))
+        );
+    }
+
+    #[test]
+    fn nearest_point_skips_nan() {
+        let multipoint = Multipoint::new(vec![Point::new(f64::NAN, 0.0), Point::new(9.0, 9.0)]);
+        assert_eq!(multipoint.nearest_point(0.0, 0.0).unwrap().0, 1);
+
+        let multipoint = Multipoint::new(vec![Point::new(f64::NAN, 0.0)]);
+        assert!(multipoint.nearest_point(0.0, 0.0).is_none());
+    }
+}
 
 #[cfg(test)]
 #[cfg(feature = "geo-types")]
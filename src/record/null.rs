@@ -0,0 +1,84 @@
+//! Module with the definition of the NullShape
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{Read, Take, Write};
+
+use record::{BBox, ConcreteReadableShape, EsriShape, HasShapeType, RecordSizeCheck};
+use record::{Shape, WritableShape};
+use {Error, ShapeType};
+
+/// Shape of the records that have no geometry
+///
+/// Its record content is only its shape type (2 16-bit words).
+/// Reading a file as [Shape](../enum.Shape.html) gives a `Shape::NullShape`
+/// for each of these records, so that the shapes stay aligned with the records of the *.dbf*.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use std::io::Cursor;
+/// use shapefile::{NullShape, Shape};
+///
+/// let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+/// writer.write_shapes(vec![NullShape, NullShape])?;
+///
+/// let shp = writer.into_parts()?.shp.into_inner();
+/// let reader = shapefile::Reader::from_parts_bytes(shp, None, None)?;
+/// assert_eq!(reader.read_as::<NullShape>()?, vec![NullShape, NullShape]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct NullShape;
+
+impl HasShapeType for NullShape {
+    fn shapetype() -> ShapeType {
+        ShapeType::NullShape
+    }
+}
+
+impl ConcreteReadableShape for NullShape {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        if size_check.accepts(source.limit(), 0) {
+            Ok(NullShape)
+        } else {
+            Err(Error::InvalidShapeRecordSize)
+        }
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
+}
+
+impl WritableShape for NullShape {
+    fn size_in_bytes(&self) -> usize {
+        0
+    }
+
+    fn write_to<T: Write>(self, _dest: &mut T) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The NullShape has no bbox, it is returned as zeros
+impl EsriShape for NullShape {
+    fn bbox(&self) -> BBox {
+        BBox::new(0.0, 0.0, 0.0, 0.0)
+    }
+}
+
+impl fmt::Display for NullShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NullShape")
+    }
+}
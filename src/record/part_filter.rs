@@ -0,0 +1,329 @@
+//! Module to only keep the parts of multipart shapes that intersects a bounding box
+//!
+//! Some shapes (like polygons describing an archipelago) are made of a lot of parts
+//! that are far apart from each other, when only a few of these parts are in the
+//! area of interest, keeping the others only waste memory.
+//!
+//! The [IntersectsBBox](trait.IntersectsBBox.html) trait tests the shapes themselves
+//! against a bounding box, not only their bbox.
+use record::multipoint::GenericMultipoint;
+use record::traits::{HasXY, MultipartShape};
+use record::{is_outer_ring, ring_contains, rings_intersect, BBox, EsriShape};
+use record::poly::{GenericPolygon, GenericPolyline};
+use record::{Multipatch, PatchType, Point, PointM, PointZ, Shape};
+
+/// A shape that may only contain some of the parts of the shape stored in the file
+pub struct PartialShape<S> {
+    pub shape: S,
+    /// true if some of the parts of the shape stored in the file were removed
+    pub is_partial: bool,
+}
+
+/// Trait implemented by the shapes that can drop the parts that do not
+/// intersect a bounding box.
+pub trait RetainPartsInBBox: Sized {
+    /// Only keeps the parts of the shape that intersect the `bbox`.
+    ///
+    /// Returns `None` if no part intersects the `bbox`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline, RetainPartsInBBox};
+    /// use shapefile::record::BBox;
+    /// let polyline = Polyline::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(10.0, 10.0),
+    ///         Point::new(11.0, 11.0),
+    ///     ],
+    ///     vec![0, 2],
+    /// );
+    /// let result = polyline.retain_parts_in_bbox(&BBox::new(9.0, 9.0, 12.0, 12.0)).unwrap();
+    /// assert!(result.is_partial);
+    /// assert_eq!(result.shape.points, vec![Point::new(10.0, 10.0), Point::new(11.0, 11.0)]);
+    /// assert_eq!(result.shape.parts, vec![0]);
+    /// ```
+    fn retain_parts_in_bbox(self, bbox: &BBox) -> Option<PartialShape<Self>>;
+}
+
+/// Returns the indices of the parts to keep.
+///
+/// Parts are grouped, a group starts at each part for which `starts_group` returns true
+/// and contains all the following parts for which it returns false.
+/// A group is kept if its first part intersects the bbox.
+fn parts_to_keep<PointType, S, F>(shape: &S, bbox: &BBox, starts_group: F) -> Vec<usize>
+where
+    PointType: HasXY,
+    S: MultipartShape<PointType>,
+    F: Fn(usize, &[PointType]) -> bool,
+{
+    let mut kept = Vec::<usize>::new();
+    let mut is_group_kept = false;
+    let mut is_in_group = false;
+    for (i, points) in shape.parts().enumerate() {
+        let is_group_start = starts_group(i, points);
+        if is_group_start || !is_in_group {
+            is_in_group = is_group_start;
+            is_group_kept = BBox::from_points(points).intersects(bbox);
+        }
+        if is_group_kept {
+            kept.push(i);
+        }
+    }
+    kept
+}
+
+/// Builds the points and parts arrays made of the `kept` parts of the `shape`
+fn rebuild_parts<PointType, S>(shape: &S, kept: &[usize]) -> (Vec<PointType>, Vec<i32>)
+where
+    PointType: Copy,
+    S: MultipartShape<PointType>,
+{
+    let mut points = Vec::<PointType>::new();
+    let mut parts = Vec::<i32>::with_capacity(kept.len());
+    for index in kept {
+        if let Some(part_points) = shape.part(*index) {
+            parts.push(points.len() as i32);
+            points.extend_from_slice(part_points);
+        }
+    }
+    (points, parts)
+}
+
+impl<PointType: HasXY + Copy> RetainPartsInBBox for GenericPolyline<PointType> {
+    fn retain_parts_in_bbox(self, bbox: &BBox) -> Option<PartialShape<Self>> {
+        let kept = parts_to_keep(&self, bbox, |_, _| true);
+        if kept.is_empty() {
+            None
+        } else if kept.len() == self.parts.len() {
+            Some(PartialShape {
+                shape: self,
+                is_partial: false,
+            })
+        } else {
+            let (points, parts) = rebuild_parts(&self, &kept);
+            let mut shape = Self::new(points, parts);
+            shape.m_present = self.m_present;
+            Some(PartialShape {
+                shape,
+                is_partial: true,
+            })
+        }
+    }
+}
+
+/// The holes of a polygon are kept if the outer ring they follow is kept.
+impl<PointType: HasXY + Copy> RetainPartsInBBox for GenericPolygon<PointType> {
+    fn retain_parts_in_bbox(self, bbox: &BBox) -> Option<PartialShape<Self>> {
+        let kept = parts_to_keep(&self, bbox, |_, points| is_outer_ring(points));
+        if kept.is_empty() {
+            None
+        } else if kept.len() == self.parts.len() {
+            Some(PartialShape {
+                shape: self,
+                is_partial: false,
+            })
+        } else {
+            let (points, parts) = rebuild_parts(&self, &kept);
+            let mut shape = Self::new(points, parts);
+            shape.m_present = self.m_present;
+            Some(PartialShape {
+                shape,
+                is_partial: true,
+            })
+        }
+    }
+}
+
+/// Inner rings and rings are kept if the outer ring or first ring they follow is kept.
+impl RetainPartsInBBox for Multipatch {
+    fn retain_parts_in_bbox(self, bbox: &BBox) -> Option<PartialShape<Self>> {
+        let kept = parts_to_keep(&self, bbox, |i, _| {
+            !matches!(
+                self.parts_type.get(i),
+                Some(PatchType::InnerRing) | Some(PatchType::Ring)
+            )
+        });
+        if kept.is_empty() {
+            None
+        } else if kept.len() == self.parts.len() {
+            Some(PartialShape {
+                shape: self,
+                is_partial: false,
+            })
+        } else {
+            let (points, parts) = rebuild_parts(&self, &kept);
+            let parts_type = kept.iter().map(|i| self.parts_type[*i]).collect();
+            let mut shape = Self::new(points, parts, parts_type);
+            shape.m_present = self.m_present;
+            Some(PartialShape {
+                shape,
+                is_partial: true,
+            })
+        }
+    }
+}
+
+macro_rules! retain_parts_of_variant {
+    ($shp:ident, $bbox:ident, Shape::$Variant:ident) => {
+        $shp.retain_parts_in_bbox($bbox).map(|partial| PartialShape {
+            shape: Shape::$Variant(partial.shape),
+            is_partial: partial.is_partial,
+        })
+    };
+}
+
+/// Shapes that do not have parts are either kept whole or dropped
+/// depending on whether their bbox intersects.
+///
+/// The NullShape never intersects.
+impl RetainPartsInBBox for Shape {
+    fn retain_parts_in_bbox(self, bbox: &BBox) -> Option<PartialShape<Self>> {
+        match self {
+            Shape::Polyline(shp) => retain_parts_of_variant!(shp, bbox, Shape::Polyline),
+            Shape::PolylineM(shp) => retain_parts_of_variant!(shp, bbox, Shape::PolylineM),
+            Shape::PolylineZ(shp) => retain_parts_of_variant!(shp, bbox, Shape::PolylineZ),
+            Shape::Polygon(shp) => retain_parts_of_variant!(shp, bbox, Shape::Polygon),
+            Shape::PolygonM(shp) => retain_parts_of_variant!(shp, bbox, Shape::PolygonM),
+            Shape::PolygonZ(shp) => retain_parts_of_variant!(shp, bbox, Shape::PolygonZ),
+            Shape::Multipatch(shp) => retain_parts_of_variant!(shp, bbox, Shape::Multipatch),
+            Shape::NullShape(_) => None,
+            shape => {
+                if shape.bbox().intersects(bbox) {
+                    Some(PartialShape {
+                        shape,
+                        is_partial: false,
+                    })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Trait implemented by the shapes that can test whether they intersect a bounding box.
+///
+/// Two bboxes that intersect are not enough for the shapes to intersect:
+/// a diagonal polyline does not intersect a small bbox near the corner of its bbox.
+/// This test is exact, for quick spatial joins
+/// (see [exact](../../reader/struct.BBoxShapeIterator.html#method.exact)).
+pub trait IntersectsBBox {
+    /// Returns whether the shape and the `bbox` have at least one point in common,
+    /// the border of the `bbox` included.
+    ///
+    /// - points and multipoints intersect if one of their points is in the `bbox`
+    /// - polylines intersect if one of their points is in the `bbox`
+    ///   or one of their segments crosses its border
+    /// - polygons also intersect if the `bbox` is inside them
+    ///   (the center of the `bbox` being inside their rings, with the even-odd rule)
+    /// - multipatches are only tested with their bbox
+    /// - NullShapes (and unknown shapes) never intersect
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::record::BBox;
+    /// use shapefile::{IntersectsBBox, Point, Polyline};
+    /// let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(10.0, 10.0)], vec![0]);
+    /// assert!(polyline.intersects_bbox(&BBox::new(4.0, 4.0, 6.0, 6.0)));
+    /// assert!(!polyline.intersects_bbox(&BBox::new(0.0, 8.0, 2.0, 10.0)));
+    /// ```
+    fn intersects_bbox(&self, bbox: &BBox) -> bool;
+}
+
+/// Returns whether the point is inside the `bbox` or on its border
+fn bbox_contains<PointType: HasXY>(bbox: &BBox, point: &PointType) -> bool {
+    bbox.xmin <= point.x()
+        && point.x() <= bbox.xmax
+        && bbox.ymin <= point.y()
+        && point.y() <= bbox.ymax
+}
+
+/// Returns whether one of the points of the parts is in the `bbox`,
+/// or one of the segments of the parts crosses the border of the `bbox`
+fn parts_intersect_bbox<'a, PointType, I>(parts: I, bbox: &BBox) -> bool
+where
+    PointType: HasXY + 'a,
+    I: IntoIterator<Item = &'a [PointType]>,
+{
+    let border = [
+        Point::new(bbox.xmin, bbox.ymin),
+        Point::new(bbox.xmin, bbox.ymax),
+        Point::new(bbox.xmax, bbox.ymax),
+        Point::new(bbox.xmax, bbox.ymin),
+        Point::new(bbox.xmin, bbox.ymin),
+    ];
+    parts.into_iter().any(|points| {
+        points.iter().any(|p| bbox_contains(bbox, p)) || rings_intersect(points, &border)
+    })
+}
+
+macro_rules! impl_intersects_bbox_for_point {
+    ($PointType:ident) => {
+        impl IntersectsBBox for $PointType {
+            fn intersects_bbox(&self, bbox: &BBox) -> bool {
+                bbox_contains(bbox, self)
+            }
+        }
+    };
+}
+
+impl_intersects_bbox_for_point!(Point);
+impl_intersects_bbox_for_point!(PointM);
+impl_intersects_bbox_for_point!(PointZ);
+
+impl<PointType: HasXY> IntersectsBBox for GenericMultipoint<PointType> {
+    fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        self.bbox.intersects(bbox) && self.points.iter().any(|p| bbox_contains(bbox, p))
+    }
+}
+
+impl<PointType: HasXY> IntersectsBBox for GenericPolyline<PointType> {
+    fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        self.bbox.intersects(bbox) && parts_intersect_bbox(self.parts(), bbox)
+    }
+}
+
+impl<PointType: HasXY> IntersectsBBox for GenericPolygon<PointType> {
+    fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        if !self.bbox.intersects(bbox) {
+            return false;
+        }
+        if parts_intersect_bbox(self.parts(), bbox) {
+            return true;
+        }
+        // No ring crosses the bbox, which is then either inside or outside of the polygon
+        let (x, y) = ((bbox.xmin + bbox.xmax) / 2.0, (bbox.ymin + bbox.ymax) / 2.0);
+        self.parts().filter(|ring| ring_contains(ring, x, y)).count() % 2 == 1
+    }
+}
+
+impl IntersectsBBox for Multipatch {
+    fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        self.bbox.intersects(bbox)
+    }
+}
+
+impl IntersectsBBox for Shape {
+    fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        match self {
+            Shape::Point(shp) => shp.intersects_bbox(bbox),
+            Shape::PointM(shp) => shp.intersects_bbox(bbox),
+            Shape::PointZ(shp) => shp.intersects_bbox(bbox),
+            Shape::Polyline(shp) => shp.intersects_bbox(bbox),
+            Shape::PolylineM(shp) => shp.intersects_bbox(bbox),
+            Shape::PolylineZ(shp) => shp.intersects_bbox(bbox),
+            Shape::Polygon(shp) => shp.intersects_bbox(bbox),
+            Shape::PolygonM(shp) => shp.intersects_bbox(bbox),
+            Shape::PolygonZ(shp) => shp.intersects_bbox(bbox),
+            Shape::Multipoint(shp) => shp.intersects_bbox(bbox),
+            Shape::MultipointM(shp) => shp.intersects_bbox(bbox),
+            Shape::MultipointZ(shp) => shp.intersects_bbox(bbox),
+            Shape::Multipatch(shp) => shp.intersects_bbox(bbox),
+            Shape::NullShape(_) | Shape::Unknown { .. } => false,
+        }
+    }
+}
@@ -1,15 +1,20 @@
 //! Module with the definition of Point, PointM and PointZ
 
-use std::io::{Read, Write};
+use std::io::{Read, Take, Write};
 
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use record::canonical::CanonicalPoint;
+use record::snap::{scale_of, SnapPoint};
 use record::EsriShape;
 use std::mem::size_of;
 use ::{ShapeType, NO_DATA};
 
 use super::Error;
-use record::ConcreteReadableShape;
-use record::{is_no_data, BBox, HasShapeType, WritableShape};
+use record::io::{m_layout_of_record, read_optional_m};
+use record::{ConcreteReadableShape, RecordSizeCheck};
+use constants::is_no_data;
+use record::{BBox, HasShapeType, Shape, WritableShape};
+use std::convert::TryFrom;
 use std::fmt;
 
 #[cfg(feature = "geo-types")]
@@ -17,13 +22,22 @@ use geo_types;
 
 
 /// Point with only `x` and `y` coordinates
+///
+/// Its layout is the one of the points in a file (on little endian hosts),
+/// which allows [borrowed views](../borrowed/index.html) of the points
 #[derive(PartialEq, Debug, Default, Copy, Clone)]
+#[repr(C)]
 pub struct Point {
     pub x: f64,
     pub y: f64,
 }
 
 impl Point {
+    /// Swaps the x and y coordinates of the point
+    pub fn swap_xy(&mut self) {
+        std::mem::swap(&mut self.x, &mut self.y);
+    }
+
     /// Creates a new point
     ///
     /// # Examples
@@ -53,8 +67,15 @@ impl HasShapeType for Point {
 }
 
 impl ConcreteReadableShape for Point {
-    fn read_shape_content<T: Read>(source: &mut T, record_size: i32) -> Result<Self, Error> {
-        if record_size == 2 * size_of::<f64>() as i32 {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        if size_check.accepts(source.limit(), 2 * size_of::<f64>()) {
             let x = source.read_f64::<LittleEndian>()?;
             let y = source.read_f64::<LittleEndian>()?;
             Ok(Self { x, y })
@@ -62,6 +83,10 @@ impl ConcreteReadableShape for Point {
             Err(Error::InvalidShapeRecordSize)
         }
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for Point {
@@ -85,6 +110,14 @@ impl EsriShape for Point {
             ymax: self.y,
         }
     }
+
+    fn snap_to_precision(&mut self, decimals: u8, _remove_repeated_points: bool) {
+        self.snap(scale_of(decimals));
+    }
+
+    fn canonicalize(&mut self) {
+        self.make_canonical();
+    }
 }
 
 impl fmt::Display for Point {
@@ -139,6 +172,11 @@ pub struct PointM {
 }
 
 impl PointM {
+    /// Swaps the x and y coordinates of the point
+    pub fn swap_xy(&mut self) {
+        std::mem::swap(&mut self.x, &mut self.y);
+    }
+
     /// Creates a new pointM
     ///
     /// # Examples
@@ -153,6 +191,28 @@ impl PointM {
     pub fn new(x: f64, y: f64, m: f64) -> Self {
         Self { x, y, m }
     }
+
+    /// Returns `[m, m]`, or `[0.0, 0.0]` if the measure is NO_DATA
+    pub fn m_range(&self) -> [f64; 2] {
+        if is_no_data(self.m) {
+            [0.0, 0.0]
+        } else {
+            [self.m, self.m]
+        }
+    }
+
+    /// Returns true if the measure is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        !is_no_data(self.m)
+    }
+
+    pub(crate) fn size_of_record(is_m_used: bool) -> usize {
+        let mut size = 2 * size_of::<f64>();
+        if is_m_used {
+            size += size_of::<f64>();
+        }
+        size
+    }
 }
 
 impl HasShapeType for PointM {
@@ -162,21 +222,29 @@ impl HasShapeType for PointM {
 }
 
 impl ConcreteReadableShape for PointM {
-    fn read_shape_content<T: Read>(source: &mut T, record_size: i32) -> Result<Self, Error> {
-        if record_size == 3 * size_of::<f64>() as i32 {
-            let x = source.read_f64::<LittleEndian>()?;
-            let y = source.read_f64::<LittleEndian>()?;
-            let m = source.read_f64::<LittleEndian>()?;
-            Ok(Self { x, y, m })
-        } else {
-            Err(Error::InvalidShapeRecordSize)
-        }
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let m_layout = m_layout_of_record(source.limit(), size_check, Self::size_of_record)?;
+        let x = source.read_f64::<LittleEndian>()?;
+        let y = source.read_f64::<LittleEndian>()?;
+        let m = read_optional_m(source, m_layout)?;
+        Ok(Self { x, y, m })
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
 impl WritableShape for PointM {
     fn size_in_bytes(&self) -> usize {
-        3 * size_of::<f64>()
+        Self::size_of_record(true)
     }
 
     fn write_to<T: Write>(self, dest: &mut T) -> Result<(), Error> {
@@ -198,11 +266,19 @@ impl EsriShape for PointM {
     }
 
     fn m_range(&self) -> [f64; 2] {
-        if is_no_data(self.m) {
-            [0.0, 0.0]
-        } else {
-            [self.m, self.m]
-        }
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, _remove_repeated_points: bool) {
+        self.snap(scale_of(decimals));
+    }
+
+    fn canonicalize(&mut self) {
+        self.make_canonical();
     }
 }
 
@@ -270,6 +346,11 @@ pub struct PointZ {
 }
 
 impl PointZ {
+    /// Swaps the x and y coordinates of the point
+    pub fn swap_xy(&mut self) {
+        std::mem::swap(&mut self.x, &mut self.y);
+    }
+
     /// Creates a new pointZ
     ///
     /// # Examples
@@ -285,6 +366,33 @@ impl PointZ {
     pub fn new(x: f64, y: f64, z: f64, m: f64) -> Self {
         Self { x, y, z, m }
     }
+
+    /// Returns `[z, z]`
+    pub fn z_range(&self) -> [f64; 2] {
+        [self.z, self.z]
+    }
+
+    /// Returns `[m, m]`, or `[0.0, 0.0]` if the measure is NO_DATA
+    pub fn m_range(&self) -> [f64; 2] {
+        if is_no_data(self.m) {
+            [0.0, 0.0]
+        } else {
+            [self.m, self.m]
+        }
+    }
+
+    /// Returns true if the measure is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        !is_no_data(self.m)
+    }
+
+    pub(crate) fn size_of_record(is_m_used: bool) -> usize {
+        let mut size = 3 * size_of::<f64>();
+        if is_m_used {
+            size += size_of::<f64>();
+        }
+        size
+    }
 }
 
 impl HasShapeType for PointZ {
@@ -294,22 +402,30 @@ impl HasShapeType for PointZ {
 }
 
 impl ConcreteReadableShape for PointZ {
-    fn read_shape_content<T: Read>(source: &mut T, record_size: i32) -> Result<Self, Error> {
-        if record_size == 4 * size_of::<f64>() as i32 {
-            let x = source.read_f64::<LittleEndian>()?;
-            let y = source.read_f64::<LittleEndian>()?;
-            let z = source.read_f64::<LittleEndian>()?;
-            let m = source.read_f64::<LittleEndian>()?;
-            Ok(Self { x, y, z, m })
-        } else {
-            Err(Error::InvalidShapeRecordSize)
-        }
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let m_layout = m_layout_of_record(source.limit(), size_check, Self::size_of_record)?;
+        let x = source.read_f64::<LittleEndian>()?;
+        let y = source.read_f64::<LittleEndian>()?;
+        let z = source.read_f64::<LittleEndian>()?;
+        let m = read_optional_m(source, m_layout)?;
+        Ok(Self { x, y, z, m })
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
 impl WritableShape for PointZ {
     fn size_in_bytes(&self) -> usize {
-        4 * size_of::<f64>()
+        Self::size_of_record(true)
     }
 
     fn write_to<T: Write>(self, dest: &mut T) -> Result<(), Error> {
@@ -332,15 +448,23 @@ impl EsriShape for PointZ {
     }
 
     fn z_range(&self) -> [f64; 2] {
-        [self.z, self.z]
+        self.z_range()
     }
 
     fn m_range(&self) -> [f64; 2] {
-        if is_no_data(self.m) {
-            [0.0, 0.0]
-        } else {
-            [self.m, self.m]
-        }
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, _remove_repeated_points: bool) {
+        self.snap(scale_of(decimals));
+    }
+
+    fn canonicalize(&mut self) {
+        self.make_canonical();
     }
 }
 
@@ -365,6 +489,50 @@ impl fmt::Display for PointZ {
     }
 }
 
+/*
+ * Conversions between the point types, used to
+ * [transcode](../transcode/index.html) shapes.
+ *
+ * A promoted point gets 0.0 as z and NO_DATA as m,
+ * a demoted point loses the values its type does not have.
+ */
+
+impl From<Point> for PointM {
+    fn from(p: Point) -> Self {
+        Self::new(p.x, p.y, NO_DATA)
+    }
+}
+
+impl From<Point> for PointZ {
+    fn from(p: Point) -> Self {
+        Self::new(p.x, p.y, 0.0, NO_DATA)
+    }
+}
+
+impl From<PointM> for Point {
+    fn from(p: PointM) -> Self {
+        Self::new(p.x, p.y)
+    }
+}
+
+impl From<PointM> for PointZ {
+    fn from(p: PointM) -> Self {
+        Self::new(p.x, p.y, 0.0, p.m)
+    }
+}
+
+impl From<PointZ> for Point {
+    fn from(p: PointZ) -> Self {
+        Self::new(p.x, p.y)
+    }
+}
+
+impl From<PointZ> for PointM {
+    fn from(p: PointZ) -> Self {
+        Self::new(p.x, p.y, p.m)
+    }
+}
+
 
 #[cfg(feature = "geo-types")]
 impl From<PointZ> for geo_types::Point<f64> {
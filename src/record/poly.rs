@@ -1,30 +1,58 @@
 //! Module with the definition of Polyline(M,Z) and Polygon(M,Z)
 
 use std::fmt;
-use std::io::{Read, Write};
+use std::io::{Read, Take, Write};
 use std::mem::size_of;
 use std::slice::SliceIndex;
 
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, WriteBytesExt};
 
+use axis::{reverse_rings, swap_bbox_xy, swap_points_xy};
+use record::canonical::canonicalize_points;
+#[cfg(feature = "geo-types")]
+use record::geo_converter::{multi_polygon_from_rings, ring_to_line_string};
+use record::snap::{scale_of, snap_parts};
 use record::io::*;
-use record::{is_parts_array_valid};
-use record::traits::HasXY;
+use constants::{is_no_data, NO_DATA};
+use record::{is_outer_ring, is_parts_array_valid};
+use record::{ring_contains, ring_signed_area, rings_intersect};
+use record::distance::{closest_point_on_parts, distance_to_parts, rings_contain};
+use record::traits::{HasM, HasMutXY, HasXY, HasZ, Interpolate};
 use record::traits::{MultipartShape, MultipointShape};
-use record::ConcreteReadableShape;
-use record::{BBox, EsriShape, HasShapeType, WritableShape};
+use record::{ConcreteReadableShape, RecordSizeCheck};
+use record::{BBox, EsriShape, HasShapeType, Shape, WritableShape};
 use record::{Point, PointM, PointZ};
+use std::convert::TryFrom;
 use {Error, ShapeType};
 
 #[cfg(feature = "geo-types")]
 use geo_types;
-#[cfg(feature = "geo-types")]
-use std::convert::TryFrom;
 
+/// Number of outer and inner rings of polygons,
+/// see [ring_classification](struct.GenericPolygon.html#method.ring_classification)
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct RingCounts {
+    /// Number of outer rings (the clockwise rings)
+    pub outer: usize,
+    /// Number of inner rings (the counterclockwise rings, the holes)
+    pub inner: usize,
+}
+
+#[derive(Clone)]
 pub struct GenericPolyline<PointType> {
     pub bbox: BBox,
     pub points: Vec<PointType>,
     pub parts: Vec<i32>,
+    /// Whether the optional M values (and M range) are written
+    pub(crate) m_present: bool,
+}
+
+impl<PointType: HasMutXY> GenericPolyline<PointType> {
+    /// Swaps the x and y coordinates of the points, and updates the bbox
+    pub fn swap_xy(&mut self) {
+        swap_points_xy(&mut self.points);
+        self.bbox = swap_bbox_xy(&self.bbox);
+    }
 }
 
 impl<PointType: HasXY> GenericPolyline<PointType> {
@@ -46,26 +74,269 @@ impl<PointType: HasXY> GenericPolyline<PointType> {
             bbox,
             points,
             parts,
+            m_present: true,
         }
     }
 }
 
+impl<PointType: HasZ> GenericPolyline<PointType> {
+    /// Returns the range of the z values, `[0.0, 0.0]` if there are no points
+    pub fn z_range(&self) -> [f64; 2] {
+        calc_z_range(&self.points)
+    }
+}
+
+impl<PointType: HasM> GenericPolyline<PointType> {
+    /// Returns the range of the measures that are not NO_DATA, `[0.0, 0.0]` if there are none
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{PointM, PolylineM, NO_DATA};
+    /// let points = vec![PointM::new(0.0, 0.0, NO_DATA), PointM::new(1.0, 1.0, 5.0)];
+    /// let polyline = PolylineM::new(points, vec![0]);
+    /// assert_eq!(polyline.m_range(), [5.0, 5.0]);
+    /// assert!(polyline.has_measures());
+    /// ```
+    pub fn m_range(&self) -> [f64; 2] {
+        calc_m_range(&self.points)
+    }
+
+    /// Returns true if at least one of the measures is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        has_measures(&self.points)
+    }
+
+    /// Returns whether the optional M values (and M range) of the record are written.
+    ///
+    /// `true` for new shapes, readers set it to `false` when the record did not have them,
+    /// so that such records are written back the same way.
+    /// M values that are not NO_DATA are always written.
+    pub fn m_present(&self) -> bool {
+        self.m_present
+    }
+
+    /// Sets whether the optional M values (and M range) of the record are written
+    pub fn set_m_present(&mut self, m_present: bool) {
+        self.m_present = m_present;
+    }
+}
+
+impl<PointType: HasXY> GenericPolyline<PointType> {
+    /// Returns the minimum distance from the point (x, y) to the segments of the parts,
+    /// infinity if the polyline has no points
+    ///
+    /// A part of a single point is at the distance of that point.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline};
+    /// let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(4.0, 0.0)], vec![0]);
+    /// assert_eq!(polyline.distance_to_point(2.0, 3.0), 3.0);
+    /// assert_eq!(polyline.distance_to_point(7.0, 4.0), 5.0);
+    /// ```
+    pub fn distance_to_point(&self, x: f64, y: f64) -> f64 {
+        distance_to_parts(self.parts(), x, y)
+    }
+}
+
+impl<PointType: Interpolate> GenericPolyline<PointType> {
+    /// Returns the point of the polyline closest to the point (x, y), with its distance
+    /// and the index of its part, `None` if the polyline has no points
+    ///
+    /// The z and m values of the point are interpolated between the ends of its segment.
+    /// When several parts are at the same distance, the first one is returned.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{PointM, PolylineM};
+    /// let points = vec![PointM::new(0.0, 0.0, 100.0), PointM::new(10.0, 0.0, 200.0)];
+    /// let road = PolylineM::new(points, vec![0]);
+    ///
+    /// let (snapped, distance, part) = road.closest_point_on(2.5, 1.0).unwrap();
+    /// assert_eq!(snapped, PointM::new(2.5, 0.0, 125.0));
+    /// assert_eq!((distance, part), (1.0, 0));
+    /// ```
+    pub fn closest_point_on(&self, x: f64, y: f64) -> Option<(PointType, f64, usize)> {
+        closest_point_on_parts(self.parts(), x, y)
+    }
+}
+
 impl<PointType> From<GenericPolygon<PointType>> for GenericPolyline<PointType> {
     fn from(p: GenericPolygon<PointType>) -> Self {
         Self {
             bbox: p.bbox,
             points: p.points,
             parts: p.parts,
+            m_present: p.m_present,
         }
     }
 }
 
+impl<PointType: HasXY + Copy + PartialEq> GenericPolyline<PointType> {
+    /// Converts the polyline into a polygon, checking that each part is a valid ring.
+    ///
+    /// Each part must be closed (its last point is equal to its first)
+    /// and have at least 4 points.
+    ///
+    /// If the first ring is counterclockwise (an inner ring in a shapefile),
+    /// the rings are assumed to follow the opposite convention and all of them are reversed.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MalformedShape` if the parts array is not valid
+    /// - `Error::UnclosedRing` if a part is not closed
+    /// - `Error::TooFewPointsInRing` if a part has less than 4 points
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Error, Point, Polyline};
+    /// let polyline = Polyline::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 1.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(0.0, 0.0),
+    ///     ],
+    ///     vec![0],
+    /// );
+    /// assert!(polyline.into_polygon().is_ok());
+    ///
+    /// let open_polyline = Polyline::new(
+    ///     vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(1.0, 1.0)],
+    ///     vec![0],
+    /// );
+    /// match open_polyline.into_polygon() {
+    ///     Err(Error::UnclosedRing(0)) => {}
+    ///     _ => panic!("expected an unclosed ring"),
+    /// }
+    /// ```
+    pub fn into_polygon(self) -> Result<GenericPolygon<PointType>, Error> {
+        self.into_rings(None)
+    }
+
+    /// Converts the polyline into a polygon, like [into_polygon](#method.into_polygon),
+    /// but the parts that are not closed are closed by adding their first point at their end.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline};
+    /// let polyline = Polyline::new(
+    ///     vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(1.0, 1.0)],
+    ///     vec![0],
+    /// );
+    /// let polygon = polyline.into_polygon_closing_rings().unwrap();
+    /// assert_eq!(polygon.points.len(), 4);
+    /// assert_eq!(polygon.points[3], Point::new(0.0, 0.0));
+    /// ```
+    pub fn into_polygon_closing_rings(self) -> Result<GenericPolygon<PointType>, Error> {
+        self.into_rings(Some(0.0))
+    }
+
+    /// Converts the polyline into a polygon, like
+    /// [into_polygon_closing_rings](#method.into_polygon_closing_rings),
+    /// but the parts whose first and last points are within `tolerance` of each other
+    /// are closed by moving their last point onto their first one instead.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline};
+    /// let polyline = Polyline::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 1.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(1e-9, 0.0),
+    ///     ],
+    ///     vec![0],
+    /// );
+    /// let polygon = polyline.into_polygon_closing_rings_with_tolerance(1e-6).unwrap();
+    /// assert_eq!(polygon.points.len(), 4);
+    /// assert_eq!(polygon.points[3], Point::new(0.0, 0.0));
+    /// ```
+    pub fn into_polygon_closing_rings_with_tolerance(
+        self,
+        tolerance: f64,
+    ) -> Result<GenericPolygon<PointType>, Error> {
+        self.into_rings(Some(tolerance))
+    }
+
+    /// Rings are closed if `close_tolerance` is `Some`
+    fn into_rings(self, close_tolerance: Option<f64>) -> Result<GenericPolygon<PointType>, Error> {
+        if !is_parts_array_valid(&self) {
+            return Err(Error::MalformedShape);
+        }
+        let mut rings = Vec::<Vec<PointType>>::with_capacity(self.parts.len());
+        for (i, part) in self.parts().enumerate() {
+            let mut ring = part.to_vec();
+            if let Some(tolerance) = close_tolerance {
+                close_ring(&mut ring, tolerance);
+            }
+            check_ring(i, &ring)?;
+            rings.push(ring);
+        }
+
+        let is_reversed = !is_outer_ring(&rings[0]);
+        let mut points = Vec::<PointType>::with_capacity(self.points.len());
+        let mut parts = Vec::<i32>::with_capacity(rings.len());
+        for mut ring in rings {
+            if is_reversed {
+                ring.reverse();
+            }
+            parts.push(points.len() as i32);
+            points.append(&mut ring);
+        }
+        let mut polygon = GenericPolygon::new(points, parts);
+        polygon.m_present = self.m_present;
+        Ok(polygon)
+    }
+}
+
+/// Checks that the ring n°`index` is closed and has at least 4 points
+fn check_ring<PointType: PartialEq>(index: usize, ring: &[PointType]) -> Result<(), Error> {
+    if ring.first() != ring.last() {
+        Err(Error::UnclosedRing(index))
+    } else if ring.len() < 4 {
+        Err(Error::TooFewPointsInRing(index))
+    } else {
+        Ok(())
+    }
+}
+
+/// Closes the ring if its last point is not equal to its first.
+///
+/// If they are at most `tolerance` apart (in x and y) the last point is replaced by the first,
+/// otherwise the first point is added at the end of the ring.
+fn close_ring<PointType: HasXY + Copy + PartialEq>(ring: &mut Vec<PointType>, tolerance: f64) {
+    let (first, last) = match (ring.first(), ring.last()) {
+        (Some(first), Some(last)) if first != last => (*first, *last),
+        _ => return,
+    };
+    let distance = (last.x() - first.x()).hypot(last.y() - first.y());
+    if tolerance > 0.0 && distance <= tolerance {
+        let n = ring.len();
+        ring[n - 1] = first;
+    } else {
+        ring.push(first);
+    }
+}
+
+/// Converts the polyline into a polygon as is, without any check.
+///
+/// The parts of the polyline may not be valid rings, use
+/// [into_polygon](struct.GenericPolyline.html#method.into_polygon) for a checked conversion.
 impl<PointType> From<GenericPolyline<PointType>> for GenericPolygon<PointType> {
     fn from(p: GenericPolyline<PointType>) -> Self {
         Self {
             bbox: p.bbox,
             points: p.points,
             parts: p.parts,
+            m_present: p.m_present,
         }
     }
 }
@@ -104,7 +375,34 @@ impl<PointType> From<GenericPolyline<PointType>> for geo_types::MultiLineString<
                     .collect();
             lines.push(line.into());
         }
-        geo_types::MultiLineString::<f64>::from_iter(lines.into_iter())
+        geo_types::MultiLineString::<f64>::from_iter(lines)
+    }
+}
+
+
+/// Converts a shapefile polyline with exactly one part into a geo_types LineString
+///
+/// # Errors
+///
+/// `Error::ExpectedSinglePart` (with the actual number of parts)
+/// if the polyline does not have exactly one part.
+#[cfg(feature = "geo-types")]
+impl<PointType> TryFrom<GenericPolyline<PointType>> for geo_types::LineString<f64>
+    where PointType: Copy,
+         geo_types::Coordinate<f64>: From<PointType>
+    {
+    type Error = Error;
+    fn try_from(polyline: GenericPolyline<PointType>) -> Result<Self, Self::Error> {
+        let num_parts = polyline.parts_indices().len();
+        if num_parts != 1 {
+            return Err(Error::ExpectedSinglePart(num_parts));
+        }
+        let line = polyline.points
+            .iter()
+            .skip(polyline.parts[0].max(0) as usize)
+            .map(|point| geo_types::Coordinate::<f64>::from(*point))
+            .collect::<Vec<geo_types::Coordinate<f64>>>();
+        Ok(line.into())
     }
 }
 
@@ -186,24 +484,37 @@ impl HasShapeType for Polyline {
 }
 
 impl ConcreteReadableShape for Polyline {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
-        let num_parts = source.read_i32::<LittleEndian>()?;
-        let num_points = source.read_i32::<LittleEndian>()?;
+        let num_parts = read_count(&mut source)?;
+        let num_points = read_count(&mut source)?;
 
-        if record_size != Self::size_of_record(num_points, num_parts) as i32 {
+        if !size_check.accepts(record_size, Self::size_of_record(num_points, num_parts)) {
             Err(Error::InvalidShapeRecordSize)
         } else {
             let parts = read_parts(&mut source, num_parts)?;
-            let points = read_xy_in_vec_of::<Point, T>(&mut source, num_points)?;
+            let points = read_xy_in_vec_of::<Point, _>(&mut source, num_points)?;
 
             Ok(Self {
                 bbox,
                 parts,
                 points,
+                m_present: false,
             })
         }
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for Polyline {
@@ -234,6 +545,16 @@ impl EsriShape for Polyline {
     fn bbox(&self) -> BBox {
         self.bbox
     }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
+    }
 }
 
 /*
@@ -242,6 +563,11 @@ impl EsriShape for Polyline {
 
 pub type PolylineM = GenericPolyline<PointM>;
 
+/// (x, y) coordinates of each part of a shape
+pub type PartsCoordinates = Vec<Vec<(f64, f64)>>;
+/// Optional measures of each part of a shape, `None` being NO_DATA
+pub type PartsMeasures = Vec<Vec<Option<f64>>>;
+
 impl PolylineM {
     pub(crate) fn size_of_record(num_points: i32, num_parts: i32, is_m_used: bool) -> usize {
         let mut size = Polyline::size_of_record(num_points, num_parts);
@@ -251,6 +577,76 @@ impl PolylineM {
         }
         size
     }
+
+    /// Creates a polyline from the (x, y) coordinates of its parts and their optional measures,
+    /// `None` measures are stored as [NO_DATA](../constant.NO_DATA.html).
+    ///
+    /// # Errors
+    ///
+    /// `Error::MeasuresLengthMismatch` if a part does not have as many measures as points,
+    /// or if there are not as many parts of measures as parts of points.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{PolylineM, NO_DATA};
+    /// let polyline = PolylineM::from_parts_with_measures(
+    ///     vec![vec![(1.0, 1.0), (2.0, 2.0)]],
+    ///     vec![vec![Some(5.0), None]],
+    /// )?;
+    /// assert_eq!(polyline.points[1].m, NO_DATA);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_parts_with_measures(
+        parts: PartsCoordinates,
+        measures: PartsMeasures,
+    ) -> Result<Self, Error> {
+        if parts.len() != measures.len() {
+            let part = parts.len().min(measures.len());
+            return Err(Error::MeasuresLengthMismatch {
+                part,
+                num_points: parts.get(part).map_or(0, |p| p.len()),
+                num_measures: measures.get(part).map_or(0, |m| m.len()),
+            });
+        }
+        let mut points = Vec::<PointM>::with_capacity(parts.iter().map(|p| p.len()).sum());
+        let mut parts_indices = Vec::<i32>::with_capacity(parts.len());
+        for (i, (part, part_measures)) in parts.into_iter().zip(measures).enumerate() {
+            if part.len() != part_measures.len() {
+                return Err(Error::MeasuresLengthMismatch {
+                    part: i,
+                    num_points: part.len(),
+                    num_measures: part_measures.len(),
+                });
+            }
+            parts_indices.push(points.len() as i32);
+            points.extend(
+                part.into_iter()
+                    .zip(part_measures)
+                    .map(|((x, y), m)| PointM::new(x, y, m.unwrap_or(NO_DATA))),
+            );
+        }
+        Ok(Self::new(points, parts_indices))
+    }
+
+    /// Returns the (x, y) coordinates of the parts and their measures,
+    /// [NO_DATA](../constant.NO_DATA.html) measures are returned as `None`.
+    ///
+    /// This is the inverse of [from_parts_with_measures](#method.from_parts_with_measures).
+    pub fn parts_with_measures(&self) -> (PartsCoordinates, PartsMeasures) {
+        self.parts()
+            .map(|part| {
+                let coords = part.iter().map(|p| (p.x, p.y)).collect();
+                let measures = part
+                    .iter()
+                    .map(|p| if is_no_data(p.m) { None } else { Some(p.m) })
+                    .collect();
+                (coords, measures)
+            })
+            .unzip()
+    }
 }
 
 impl fmt::Display for PolylineM {
@@ -271,45 +667,53 @@ impl HasShapeType for PolylineM {
 }
 
 impl ConcreteReadableShape for PolylineM {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
-        let num_parts = source.read_i32::<LittleEndian>()?;
-        let num_points = source.read_i32::<LittleEndian>()?;
+        let num_parts = read_count(&mut source)?;
+        let num_points = read_count(&mut source)?;
 
-        let parts = read_parts(&mut source, num_parts)?;
+        let m_layout = m_layout_of_record(record_size, size_check, |is_m_used| {
+            Self::size_of_record(num_points, num_parts, is_m_used)
+        })?;
 
-        let record_size_with_m = Self::size_of_record(num_points, num_parts, true) as i32;
-        let record_size_without_m = Self::size_of_record(num_points, num_parts, false) as i32;
+        let parts = read_parts(&mut source, num_parts)?;
+        let mut points = read_xy_in_vec_of::<PointM, _>(&mut source, num_points)?;
 
-        if (record_size != record_size_with_m) & (record_size != record_size_without_m) {
-            return Err(Error::InvalidShapeRecordSize);
-        } else {
-            let is_m_used = record_size == record_size_with_m;
-            let mut points = read_xy_in_vec_of::<PointM, T>(&mut source, num_points)?;
+        let is_m_used = read_optional_ms_into(&mut source, &mut points, m_layout)?.is_some();
 
-            if is_m_used {
-                let _m_range = read_range(&mut source)?;
-                read_ms_into(&mut source, &mut points)?;
-            }
+        Ok(Self {
+            bbox,
+            parts,
+            points,
+            m_present: is_m_used,
+        })
+    }
 
-            Ok(Self {
-                bbox,
-                parts,
-                points,
-            })
-        }
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
 impl WritableShape for PolylineM {
     fn size_in_bytes(&self) -> usize {
-        let mut size = 0 as usize;
+        let mut size = 0usize;
         size += size_of::<f64>() * 4;
         size += size_of::<i32>(); // num parts
         size += size_of::<i32>(); //num points
         size += size_of::<i32>() * self.parts.len();
-        size += 3 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 2 * size_of::<f64>() * self.points.len();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -323,8 +727,10 @@ impl WritableShape for PolylineM {
         write_parts(&mut dest, &self.parts)?;
         write_points(&mut dest, &self.points)?;
 
-        write_range(&mut dest, self.m_range())?;
-        write_ms(&mut dest, &self.points)?;
+        if self.m_present || has_measures(&self.points) {
+            write_range(&mut dest, self.m_range())?;
+            write_ms(&mut dest, &self.points)?;
+        }
         Ok(())
     }
 }
@@ -335,7 +741,21 @@ impl EsriShape for PolylineM {
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
@@ -376,49 +796,58 @@ impl HasShapeType for PolylineZ {
 }
 
 impl ConcreteReadableShape for PolylineZ {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        mut source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let record_size = source.limit();
         let bbox = BBox::read_from(&mut source)?;
-        let num_parts = source.read_i32::<LittleEndian>()?;
-        let num_points = source.read_i32::<LittleEndian>()?;
+        let num_parts = read_count(&mut source)?;
+        let num_points = read_count(&mut source)?;
 
-        let record_size_with_m = Self::size_of_record(num_points, num_parts, true) as i32;
-        let record_size_without_m = Self::size_of_record(num_points, num_parts, false) as i32;
+        let m_layout = m_layout_of_record(record_size, size_check, |is_m_used| {
+            Self::size_of_record(num_points, num_parts, is_m_used)
+        })?;
 
-        if (record_size != record_size_with_m) & (record_size != record_size_without_m) {
-            return Err(Error::InvalidShapeRecordSize);
-        } else {
-            let is_m_used = record_size == record_size_with_m;
-            let parts = read_parts(&mut source, num_parts)?;
+        let parts = read_parts(&mut source, num_parts)?;
 
-            let mut points = read_xy_in_vec_of::<PointZ, T>(&mut source, num_points)?;
+        let mut points = read_xy_in_vec_of::<PointZ, _>(&mut source, num_points)?;
 
-            let _z_range = read_range(&mut source)?;
-            read_zs_into(&mut source, &mut points)?;
+        let _z_range = read_range(&mut source)?;
+        read_zs_into(&mut source, &mut points)?;
 
-            if is_m_used {
-                let _m_range = read_range(&mut source)?;
-                read_ms_into(&mut source, &mut points)?;
-            }
+        let is_m_used = read_optional_ms_into(&mut source, &mut points, m_layout)?.is_some();
 
-            Ok(Self {
-                bbox,
-                parts,
-                points,
-            })
-        }
+        Ok(Self {
+            bbox,
+            parts,
+            points,
+            m_present: is_m_used,
+        })
+    }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
     }
 }
 
 impl WritableShape for PolylineZ {
     fn size_in_bytes(&self) -> usize {
-        let mut size = 0 as usize;
+        let mut size = 0usize;
         size += size_of::<f64>() * 4;
         size += size_of::<i32>(); // num parts
         size += size_of::<i32>(); //num points
         size += size_of::<i32>() * self.parts.len();
-        size += 4 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 3 * size_of::<f64>() * self.points.len();
         size += 2 * size_of::<f64>();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -436,8 +865,10 @@ impl WritableShape for PolylineZ {
         write_range(&mut dest, self.z_range())?;
         write_zs(&mut dest, &self.points)?;
 
-        write_range(&mut dest, self.m_range())?;
-        write_ms(&mut dest, &self.points)?;
+        if self.m_present || has_measures(&self.points) {
+            write_range(&mut dest, self.m_range())?;
+            write_ms(&mut dest, &self.points)?;
+        }
         Ok(())
     }
 }
@@ -448,11 +879,25 @@ impl EsriShape for PolylineZ {
     }
 
     fn z_range(&self) -> [f64; 2] {
-        calc_z_range(&self.points)
+        self.z_range()
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
@@ -461,10 +906,13 @@ impl EsriShape for PolylineZ {
  */
 
 
+#[derive(Clone)]
 pub struct GenericPolygon<PointType> {
     pub bbox: BBox,
     pub points: Vec<PointType>,
     pub parts: Vec<i32>,
+    /// Whether the optional M values (and M range) are written
+    pub(crate) m_present: bool,
 }
 
 impl<PointType: HasXY> GenericPolygon<PointType> {
@@ -489,6 +937,432 @@ impl<PointType: HasXY> GenericPolygon<PointType> {
     }
 }
 
+impl<PointType: HasZ> GenericPolygon<PointType> {
+    /// Returns the range of the z values, `[0.0, 0.0]` if there are no points
+    pub fn z_range(&self) -> [f64; 2] {
+        calc_z_range(&self.points)
+    }
+}
+
+impl<PointType: HasM> GenericPolygon<PointType> {
+    /// Returns the range of the measures that are not NO_DATA, `[0.0, 0.0]` if there are none
+    pub fn m_range(&self) -> [f64; 2] {
+        calc_m_range(&self.points)
+    }
+
+    /// Returns true if at least one of the measures is not NO_DATA
+    pub fn has_measures(&self) -> bool {
+        has_measures(&self.points)
+    }
+
+    /// Returns whether the optional M values (and M range) of the record are written,
+    /// see [GenericPolyline::m_present](struct.GenericPolyline.html#method.m_present)
+    pub fn m_present(&self) -> bool {
+        self.m_present
+    }
+
+    /// Sets whether the optional M values (and M range) of the record are written
+    pub fn set_m_present(&mut self, m_present: bool) {
+        self.m_present = m_present;
+    }
+}
+
+impl<PointType: HasXY> GenericPolygon<PointType> {
+    /// Returns the distance from the point (x, y) to the polygon: 0.0 if the point is inside
+    /// (the point of a hole being outside), the distance to the nearest ring otherwise,
+    /// infinity if the polygon has no points
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let square = vec![
+    ///     Point::new(0.0, 0.0),
+    ///     Point::new(0.0, 4.0),
+    ///     Point::new(4.0, 4.0),
+    ///     Point::new(4.0, 0.0),
+    ///     Point::new(0.0, 0.0),
+    /// ];
+    /// let polygon = Polygon::new(square, vec![0]);
+    /// assert_eq!(polygon.distance_to_point(1.0, 1.0), 0.0);
+    /// assert_eq!(polygon.distance_to_point(6.0, 2.0), 2.0);
+    /// ```
+    pub fn distance_to_point(&self, x: f64, y: f64) -> f64 {
+        if rings_contain(self.parts(), x, y) {
+            0.0
+        } else {
+            distance_to_parts(self.parts(), x, y)
+        }
+    }
+}
+
+impl<PointType: Interpolate> GenericPolygon<PointType> {
+    /// Returns the point of the rings closest to the point (x, y), with its distance
+    /// and the index of its ring, `None` if the polygon has no points
+    ///
+    /// The point is on a ring even if (x, y) is inside the polygon,
+    /// see [GenericPolyline::closest_point_on](struct.GenericPolyline.html#method.closest_point_on).
+    pub fn closest_point_on(&self, x: f64, y: f64) -> Option<(PointType, f64, usize)> {
+        closest_point_on_parts(self.parts(), x, y)
+    }
+}
+
+impl<PointType: HasXY + Copy + PartialEq> GenericPolygon<PointType> {
+    /// Creates a polygon, closing the parts that are not closed.
+    ///
+    /// A part whose first and last points are within `tolerance` of each other
+    /// is closed by moving its last point onto its first one,
+    /// other parts are closed by adding their first point at their end.
+    /// With a `tolerance` of `0.0` the first point is always added.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let polygon = Polygon::with_closed_rings(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 1.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(1e-9, 0.0),
+    ///     ],
+    ///     vec![0],
+    ///     1e-6,
+    /// );
+    /// assert_eq!(polygon.points.len(), 4);
+    /// assert_eq!(polygon.points[3], Point::new(0.0, 0.0));
+    /// ```
+    pub fn with_closed_rings(points: Vec<PointType>, parts: Vec<i32>, tolerance: f64) -> Self {
+        let mut polygon = Self::new(points, parts);
+        polygon.close_rings(tolerance);
+        polygon
+    }
+
+    /// Closes the rings that are not closed, like [with_closed_rings](#method.with_closed_rings),
+    /// and updates the bbox.
+    ///
+    /// The polygon is left unchanged if its parts array is not valid.
+    pub fn close_rings(&mut self, tolerance: f64) {
+        if !is_parts_array_valid(self) {
+            return;
+        }
+        let mut points = Vec::<PointType>::with_capacity(self.points.len());
+        let mut parts = Vec::<i32>::with_capacity(self.parts.len());
+        for part in self.parts() {
+            let mut ring = part.to_vec();
+            close_ring(&mut ring, tolerance);
+            parts.push(points.len() as i32);
+            points.append(&mut ring);
+        }
+        self.bbox = BBox::from_points(&points);
+        self.points = points;
+        self.parts = parts;
+    }
+
+    /// Checks that the parts array is valid and that all the rings
+    /// are closed and have at least 4 points
+    ///
+    /// # Errors
+    ///
+    /// - `Error::MalformedShape` if the parts array is not valid
+    /// - `Error::UnclosedRing` if a ring is not closed
+    /// - `Error::TooFewPointsInRing` if a ring has less than 4 points
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Error, Point, Polygon};
+    /// let polygon = Polygon::new(
+    ///     vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(1.0, 1.0)],
+    ///     vec![0],
+    /// );
+    /// match polygon.validate_rings() {
+    ///     Err(Error::UnclosedRing(0)) => {}
+    ///     _ => panic!("the ring is not closed"),
+    /// }
+    /// ```
+    pub fn validate_rings(&self) -> Result<(), Error> {
+        if !is_parts_array_valid(self) {
+            return Err(Error::MalformedShape);
+        }
+        self.parts()
+            .enumerate()
+            .try_for_each(|(i, ring)| check_ring(i, ring))
+    }
+
+    /// Adds a hole to the polygon, in the outer ring containing it.
+    ///
+    /// The ring is closed (by adding its first point at its end) and made
+    /// counterclockwise if needed. It is inserted after the holes of the smallest outer
+    /// ring containing all its points, so that polygons whose rings are normalized
+    /// (see [normalize_rings](#method.normalize_rings)) stay so, then the bbox is updated.
+    ///
+    /// # Errors
+    ///
+    /// The polygon is left unchanged if the hole can not be added:
+    ///
+    /// - `Error::MalformedShape` if the parts array of the polygon is not valid
+    /// - `Error::HoleNotContained` if no outer ring contains all the points of the hole
+    /// - `Error::HoleCrossesRing` if the hole crosses or touches an outer ring
+    /// - `Error::TooFewPointsInRing` if the closed hole has less than 4 points,
+    ///   the index given being the one the hole would have had
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::{Point, Polygon};
+    /// let mut parcel = Polygon::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 10.0),
+    ///         Point::new(10.0, 10.0),
+    ///         Point::new(10.0, 0.0),
+    ///         Point::new(0.0, 0.0),
+    ///     ],
+    ///     vec![0],
+    /// );
+    /// let footprint = vec![
+    ///     Point::new(2.0, 2.0),
+    ///     Point::new(2.0, 4.0),
+    ///     Point::new(4.0, 4.0),
+    ///     Point::new(4.0, 2.0),
+    /// ];
+    /// parcel.add_hole(footprint)?;
+    /// assert_eq!(parcel.parts, vec![0, 5]);
+    /// assert_eq!(parcel.inner_rings().count(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn add_hole(&mut self, mut ring: Vec<PointType>) -> Result<(), Error> {
+        if !is_parts_array_valid(self) {
+            return Err(Error::MalformedShape);
+        }
+        close_ring(&mut ring, 0.0);
+        if is_outer_ring(&ring) {
+            ring.reverse();
+        }
+
+        let rings = self.parts().collect::<Vec<&[PointType]>>();
+        let is_outer = rings.iter().map(|r| is_outer_ring(r)).collect::<Vec<bool>>();
+        let crossed = (0..rings.len()).find(|i| is_outer[*i] && rings_intersect(&ring, rings[*i]));
+        if let Some(i) = crossed {
+            return Err(Error::HoleCrossesRing(i));
+        }
+        let container = (0..rings.len())
+            .filter(|i| {
+                is_outer[*i]
+                    && ring
+                        .iter()
+                        .all(|point| ring_contains(rings[*i], point.x(), point.y()))
+            })
+            .map(|i| (i, ring_signed_area(rings[i])))
+            .fold(None, |smallest: Option<(usize, f64)>, (i, area)| match smallest {
+                Some((_, smallest_area)) if smallest_area <= area => smallest,
+                _ => Some((i, area)),
+            });
+        let container = match container {
+            Some((i, _)) => i,
+            None => return Err(Error::HoleNotContained),
+        };
+        // The hole goes before the next outer ring, after the holes of its container
+        let index = (container + 1..rings.len())
+            .find(|i| is_outer[*i])
+            .unwrap_or(rings.len());
+        if ring.len() < 4 {
+            return Err(Error::TooFewPointsInRing(index));
+        }
+
+        let start = self.parts.get(index).map_or(self.points.len(), |start| *start as usize);
+        let num_points = ring.len() as i32;
+        self.points.splice(start..start, ring);
+        for part in &mut self.parts[index..] {
+            *part += num_points;
+        }
+        self.parts.insert(index, start as i32);
+        self.bbox = BBox::from_points(&self.points);
+        Ok(())
+    }
+}
+
+impl<PointType: HasMutXY> GenericPolygon<PointType> {
+    /// Swaps the x and y coordinates of the points, and updates the bbox.
+    ///
+    /// As swapping the coordinates changes the orientation of the rings,
+    /// the order of the points of each ring is reversed so that the outer rings
+    /// stay clockwise and the holes counterclockwise.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let mut polygon = Polygon::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 1.0),
+    ///         Point::new(2.0, 1.0),
+    ///         Point::new(2.0, 0.0),
+    ///         Point::new(0.0, 0.0),
+    ///     ],
+    ///     vec![0],
+    /// );
+    /// polygon.swap_xy();
+    /// assert_eq!(polygon.bbox.xmax, 1.0);
+    /// assert_eq!(polygon.bbox.ymax, 2.0);
+    /// assert_eq!(polygon.outer_rings().count(), 1);
+    /// ```
+    pub fn swap_xy(&mut self) {
+        swap_points_xy(&mut self.points);
+        reverse_rings(&mut self.points, &self.parts, |_| true);
+        self.bbox = swap_bbox_xy(&self.bbox);
+    }
+}
+
+impl<PointType: HasXY> GenericPolygon<PointType> {
+    /// Returns an iterator over the outer rings (the clockwise rings) of the polygon
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let polygon = Polygon::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 4.0),
+    ///         Point::new(4.0, 4.0),
+    ///         Point::new(4.0, 0.0),
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(2.0, 1.0),
+    ///         Point::new(2.0, 2.0),
+    ///         Point::new(1.0, 2.0),
+    ///         Point::new(1.0, 1.0),
+    ///     ],
+    ///     vec![0, 5],
+    /// );
+    /// assert_eq!(polygon.outer_rings().count(), 1);
+    /// assert_eq!(polygon.inner_rings().count(), 1);
+    /// ```
+    pub fn outer_rings(&self) -> impl Iterator<Item = &[PointType]> + '_ {
+        self.parts().filter(|ring| is_outer_ring(ring))
+    }
+
+    /// Returns an iterator over the inner rings (the counterclockwise rings, the holes)
+    /// of the polygon
+    pub fn inner_rings(&self) -> impl Iterator<Item = &[PointType]> + '_ {
+        self.parts().filter(|ring| !is_outer_ring(ring))
+    }
+
+    /// Returns the number of outer and inner rings of the polygon,
+    /// classified by the sign of their area in one pass over the rings.
+    ///
+    /// The rings are classified the same way as when converting to geo_types.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::Polygon;
+    /// let polygons = shapefile::read_as::<_, Polygon>("tests/data/polygon.shp").unwrap();
+    /// let counts = polygons[0].ring_classification();
+    /// assert_eq!(counts.outer, polygons[0].outer_rings().count());
+    /// assert_eq!(counts.inner, polygons[0].inner_rings().count());
+    /// ```
+    pub fn ring_classification(&self) -> RingCounts {
+        self.parts()
+            .fold(RingCounts::default(), |mut counts, ring| {
+                if is_outer_ring(ring) {
+                    counts.outer += 1;
+                } else {
+                    counts.inner += 1;
+                }
+                counts
+            })
+    }
+}
+
+impl<PointType: HasXY + Copy> GenericPolygon<PointType> {
+    /// Reorders the rings so that each outer ring is immediately followed by its holes.
+    ///
+    /// Each hole goes after the smallest outer ring containing its first point,
+    /// the holes contained by no outer ring are put at the end.
+    /// The rings keep their relative order otherwise, and their points are unchanged.
+    ///
+    /// Polygons whose parts are not valid are left untouched.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let mut polygon = Polygon::new(
+    ///     vec![
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(2.0, 1.0),
+    ///         Point::new(2.0, 2.0),
+    ///         Point::new(1.0, 2.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 4.0),
+    ///         Point::new(4.0, 4.0),
+    ///         Point::new(4.0, 0.0),
+    ///         Point::new(0.0, 0.0),
+    ///     ],
+    ///     vec![0, 5],
+    /// );
+    /// polygon.normalize_rings();
+    /// assert_eq!(polygon.parts, vec![0, 5]);
+    /// assert_eq!(polygon.points[0], Point::new(0.0, 0.0));
+    /// ```
+    pub fn normalize_rings(&mut self) {
+        if !is_parts_array_valid(self) {
+            return;
+        }
+        let order = {
+            let rings = self.parts().collect::<Vec<&[PointType]>>();
+            let is_outer = rings.iter().map(|ring| is_outer_ring(ring)).collect::<Vec<bool>>();
+            let mut holes_of = vec![Vec::<usize>::new(); rings.len()];
+            let mut orphan_holes = Vec::<usize>::new();
+            for (i, hole) in rings.iter().enumerate().filter(|(i, _)| !is_outer[*i]) {
+                let container = hole.first().and_then(|first| {
+                    rings
+                        .iter()
+                        .enumerate()
+                        .filter(|(j, ring)| {
+                            is_outer[*j] && ring_contains(ring, first.x(), first.y())
+                        })
+                        .map(|(j, ring)| (j, ring_signed_area(ring)))
+                        .fold(None, |smallest: Option<(usize, f64)>, (j, area)| match smallest {
+                            Some((_, smallest_area)) if smallest_area <= area => smallest,
+                            _ => Some((j, area)),
+                        })
+                });
+                match container {
+                    Some((j, _)) => holes_of[j].push(i),
+                    None => orphan_holes.push(i),
+                }
+            }
+            (0..rings.len())
+                .filter(|i| is_outer[*i])
+                .flat_map(|i| std::iter::once(i).chain(holes_of[i].iter().cloned()))
+                .chain(orphan_holes)
+                .collect::<Vec<usize>>()
+        };
+        if order.iter().enumerate().all(|(position, i)| position == *i) {
+            return;
+        }
+
+        let mut points = Vec::<PointType>::with_capacity(self.points.len());
+        let mut parts = Vec::<i32>::with_capacity(self.parts.len());
+        for i in order {
+            let start = self.parts[i] as usize;
+            let end = self.parts.get(i + 1).map_or(self.points.len(), |end| *end as usize);
+            parts.push(points.len() as i32);
+            points.extend_from_slice(&self.points[start..end]);
+        }
+        self.points = points;
+        self.parts = parts;
+    }
+}
+
 impl<PointType> MultipointShape<PointType> for GenericPolygon<PointType> {
     fn point<I: SliceIndex<[PointType]>>(
         &self,
@@ -513,43 +1387,46 @@ impl<PointType> MultipartShape<PointType> for GenericPolygon<PointType> {
 /// which are really just multiple polygons
 ///
 /// Vertices of rings defining holes in polygons are in a counterclockwise direction
+///
+/// The coordinates of each ring are allocated once, with their exact size,
+/// a [GeoConverter](../geo_converter/struct.GeoConverter.html) can also reuse them
+/// between conversions.
 #[cfg(feature = "geo-types")]
 impl<PointType> TryFrom<GenericPolygon<PointType>> for geo_types::MultiPolygon<f64>
     where PointType: HasXY + Copy,
-          geo_types::Point<f64>: From<PointType>{
+          geo_types::Coordinate<f64>: From<PointType>{
     type Error = Error;
     fn try_from(p: GenericPolygon<PointType>) -> Result<Self, Self::Error> {
-        use super::is_outer_ring;
-        let mut last_poly = None;
-        let mut polygons = Vec::<geo_types::Polygon<f64>>::new();
-        for points_slc in p.parts() {
-            let points = points_slc
-                .iter()
-                .map(|p| geo_types::Point::<f64>::from(*p))
-                .collect::<Vec<geo_types::Point<f64>>>();
-            if is_outer_ring(points_slc) {
-                let new_poly = geo_types::Polygon::new(points.into(), vec![]);
-                if last_poly.is_some() {
-                    polygons.push(last_poly.replace(new_poly).unwrap());
-                } else {
-                    last_poly = Some(new_poly);
-                }
-            } else {
-                if let Some(ref mut polygon) = last_poly {
-                    polygon.interiors_push(points);
-                } else {
-                    return Err(Error::OrphanInnerRing)
-                }
-            }
-        }
-        if let Some(poly) = last_poly {
-            polygons.push(poly);
-        }
-        Ok(polygons.into())
+        multi_polygon_from_rings(&p, Vec::new)
     }
 
 }
 
+/// Converts a shapefile polygon with exactly one outer ring into a geo_types Polygon
+///
+/// All the inner rings become the interiors of the polygon.
+///
+/// # Errors
+///
+/// `Error::ExpectedSingleOuterRing` (with the actual number of outer rings)
+/// if the polygon does not have exactly one outer ring.
+#[cfg(feature = "geo-types")]
+impl<PointType> TryFrom<GenericPolygon<PointType>> for geo_types::Polygon<f64>
+    where PointType: HasXY + Copy,
+          geo_types::Coordinate<f64>: From<PointType>{
+    type Error = Error;
+    fn try_from(p: GenericPolygon<PointType>) -> Result<Self, Self::Error> {
+        let to_line_string = |ring: &[PointType]| ring_to_line_string(ring, Vec::new());
+        let num_outer_rings = p.outer_rings().count();
+        if num_outer_rings != 1 {
+            return Err(Error::ExpectedSingleOuterRing(num_outer_rings));
+        }
+        let exterior = p.outer_rings().map(to_line_string).next().unwrap();
+        let interiors = p.inner_rings().map(to_line_string).collect();
+        Ok(geo_types::Polygon::new(exterior, interiors))
+    }
+}
+
 #[cfg(feature = "geo-types")]
 /// geo_types guarantees that Polygons exterior and interiors are closed
 impl<PointType> From<geo_types::Polygon<f64>> for GenericPolygon<PointType>
@@ -578,7 +1455,7 @@ impl<PointType> From<geo_types::Polygon<f64>> for GenericPolygon<PointType>
         all_points.append(&mut outer_points);
 
         for inner_ls in inners_ls {
-            parts.push((all_points.len() - 1) as i32);
+            parts.push(all_points.len() as i32);
             let mut inner_points = inner_ls
                 .into_iter()
                 .map(|c| PointType::from(c))
@@ -600,7 +1477,7 @@ impl<PointType> From<geo_types::MultiPolygon<f64>> for GenericPolygon<PointType>
     fn from(multi_polygon: geo_types::MultiPolygon<f64>) -> Self {
         let polygons = multi_polygon
             .into_iter()
-            .map(|polyg| GenericPolygon::<PointType>::from(polyg))
+            .map(GenericPolygon::<PointType>::from)
             .collect::<Vec<GenericPolygon<PointType>>>();
 
         let total_points_count = polygons
@@ -646,15 +1523,26 @@ impl HasShapeType for Polygon {
 }
 
 impl ConcreteReadableShape for Polygon {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
-        let poly = Polyline::read_shape_content(&mut source, record_size)?;
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let poly = Polyline::read_shape_content_with(source, size_check)?;
         Ok(poly.into())
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for Polygon {
     fn size_in_bytes(&self) -> usize {
-        let mut size = 0 as usize;
+        let mut size = 0usize;
         size += size_of::<f64>() * 4;
         size += size_of::<i32>(); // num parts
         size += size_of::<i32>(); //num points
@@ -673,6 +1561,24 @@ impl EsriShape for Polygon {
     fn bbox(&self) -> BBox {
         self.bbox
     }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.validate_rings()
+    }
+
+    fn close_rings(&mut self, tolerance: f64) {
+        self.close_rings(tolerance)
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
+    }
 }
 
 /*
@@ -699,21 +1605,35 @@ impl HasShapeType for PolygonM {
 }
 
 impl ConcreteReadableShape for PolygonM {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
-        let poly = PolylineM::read_shape_content(&mut source, record_size)?;
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let poly = PolylineM::read_shape_content_with(source, size_check)?;
         Ok(Self::from(poly))
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for PolygonM {
     fn size_in_bytes(&self) -> usize {
-        let mut size = 0 as usize;
+        let mut size = 0usize;
         size += size_of::<f64>() * 4;
         size += size_of::<i32>(); // num parts
         size += size_of::<i32>(); //num points
         size += size_of::<i32>() * self.parts.len();
-        size += 3 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 2 * size_of::<f64>() * self.points.len();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -728,7 +1648,29 @@ impl EsriShape for PolygonM {
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.validate_rings()
+    }
+
+    fn close_rings(&mut self, tolerance: f64) {
+        self.close_rings(tolerance)
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
@@ -756,22 +1698,36 @@ impl HasShapeType for PolygonZ {
 }
 
 impl ConcreteReadableShape for PolygonZ {
-    fn read_shape_content<T: Read>(mut source: &mut T, record_size: i32) -> Result<Self, Error> {
-        let poly = PolylineZ::read_shape_content(&mut source, record_size)?;
+    fn read_shape_content<T: Read>(source: &mut Take<T>) -> Result<Self, Error> {
+        Self::read_shape_content_with(source, RecordSizeCheck::Exact)
+    }
+
+    fn read_shape_content_with<T: Read>(
+        source: &mut Take<T>,
+        size_check: RecordSizeCheck,
+    ) -> Result<Self, Error> {
+        let poly = PolylineZ::read_shape_content_with(source, size_check)?;
         Ok(poly.into())
     }
+
+    fn from_shape(shape: Shape) -> Option<Self> {
+        Self::try_from(shape).ok()
+    }
 }
 
 impl WritableShape for PolygonZ {
     fn size_in_bytes(&self) -> usize {
-        let mut size = 0 as usize;
+        let mut size = 0usize;
         size += size_of::<f64>() * 4;
         size += size_of::<i32>(); // num parts
         size += size_of::<i32>(); //num points
         size += size_of::<i32>() * self.parts.len();
-        size += 4 * size_of::<f64>() * self.points.len();
-        size += 2 * size_of::<f64>();
+        size += 3 * size_of::<f64>() * self.points.len();
         size += 2 * size_of::<f64>();
+        if self.m_present || has_measures(&self.points) {
+            size += size_of::<f64>() * self.points.len();
+            size += 2 * size_of::<f64>();
+        }
         size
     }
 
@@ -786,11 +1742,33 @@ impl EsriShape for PolygonZ {
     }
 
     fn z_range(&self) -> [f64; 2] {
-        calc_z_range(&self.points)
+        self.z_range()
     }
 
     fn m_range(&self) -> [f64; 2] {
-        calc_m_range(&self.points)
+        self.m_range()
+    }
+
+    fn has_measures(&self) -> bool {
+        self.has_measures()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        self.validate_rings()
+    }
+
+    fn close_rings(&mut self, tolerance: f64) {
+        self.close_rings(tolerance)
+    }
+
+    fn snap_to_precision(&mut self, decimals: u8, remove_repeated_points: bool) {
+        let scale = scale_of(decimals);
+        snap_parts(&mut self.points, &mut self.parts, scale, remove_repeated_points);
+        self.bbox = BBox::from_points(&self.points);
+    }
+
+    fn canonicalize(&mut self) {
+        self.bbox = canonicalize_points(&mut self.points);
     }
 }
 
@@ -805,3 +1783,160 @@ mod tests {
     }
 }
 */
+
+#[cfg(test)]
+mod into_polygon_tests {
+    use super::*;
+
+    fn square(xmin: f64, ymin: f64, xmax: f64, ymax: f64) -> Vec<Point> {
+        // clockwise, not closed
+        vec![
+            Point::new(xmin, ymin),
+            Point::new(xmin, ymax),
+            Point::new(xmax, ymax),
+            Point::new(xmax, ymin),
+        ]
+    }
+
+    #[test]
+    fn open_ring_is_rejected() {
+        let mut points = square(0.0, 0.0, 10.0, 10.0);
+        points.push(points[0]);
+        points.extend(square(2.0, 2.0, 4.0, 4.0));
+        let polyline = Polyline::new(points, vec![0, 5]);
+        match polyline.into_polygon() {
+            Err(Error::UnclosedRing(1)) => {}
+            _ => panic!("expected the second ring to be reported as unclosed"),
+        }
+    }
+
+    #[test]
+    fn too_short_ring_is_rejected() {
+        let points = vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(0.0, 0.0),
+        ];
+        let polyline = Polyline::new(points.clone(), vec![0]);
+        match polyline.into_polygon() {
+            Err(Error::TooFewPointsInRing(0)) => {}
+            _ => panic!("expected the ring to be too short"),
+        }
+        let polyline = Polyline::new(points, vec![0]);
+        match polyline.into_polygon_closing_rings() {
+            Err(Error::TooFewPointsInRing(0)) => {}
+            _ => panic!("expected the ring to be too short"),
+        }
+    }
+
+    #[test]
+    fn rings_are_closed() {
+        let mut points = square(0.0, 0.0, 10.0, 10.0);
+        points.extend(square(2.0, 2.0, 4.0, 4.0).into_iter().rev());
+        let polyline = PolylineM::new(
+            points.into_iter().map(|p| PointM::new(p.x, p.y, 1.0)).collect(),
+            vec![0, 4],
+        );
+        let polygon = polyline.into_polygon_closing_rings().unwrap();
+        assert_eq!(polygon.parts, vec![0, 5]);
+        assert_eq!(polygon.points.len(), 10);
+        assert_eq!(polygon.points[4], polygon.points[0]);
+        assert_eq!(polygon.points[9], polygon.points[5]);
+        assert!(is_outer_ring(polygon.part(0).unwrap()));
+        assert!(!is_outer_ring(polygon.part(1).unwrap()));
+        assert_eq!(polygon.bbox, BBox::new(0.0, 0.0, 10.0, 10.0));
+    }
+
+    #[test]
+    fn rings_are_closed_within_tolerance() {
+        let mut points = square(0.0, 0.0, 10.0, 10.0);
+        points.push(Point::new(1e-9, -1e-9));
+        points.extend(square(2.0, 2.0, 4.0, 4.0).into_iter().rev());
+        points.push(Point::new(2.1, 2.0));
+
+        let polygon = Polyline::new(points.clone(), vec![0, 5])
+            .into_polygon_closing_rings_with_tolerance(1e-6)
+            .unwrap();
+        assert_eq!(polygon.parts, vec![0, 5]);
+        assert_eq!(polygon.points.len(), 11);
+        // snapped
+        assert_eq!(polygon.points[4], Point::new(0.0, 0.0));
+        // too far, appended
+        assert_eq!(polygon.points[9], Point::new(2.1, 2.0));
+        assert_eq!(polygon.points[10], polygon.points[5]);
+
+        // without tolerance, the near duplicate is kept
+        let polygon = Polyline::new(points.clone(), vec![0, 5])
+            .into_polygon_closing_rings()
+            .unwrap();
+        assert_eq!(polygon.parts, vec![0, 6]);
+        assert_eq!(polygon.points[4], Point::new(1e-9, -1e-9));
+        assert_eq!(polygon.points[5], Point::new(0.0, 0.0));
+
+        let polygon = Polygon::with_closed_rings(points, vec![0, 5], 1e-6);
+        assert_eq!(polygon.parts, vec![0, 5]);
+        assert_eq!(polygon.points[4], Point::new(0.0, 0.0));
+        assert_eq!(polygon.points[10], polygon.points[5]);
+    }
+
+    #[test]
+    fn counterclockwise_rings_are_reversed() {
+        let mut points = square(0.0, 0.0, 10.0, 10.0);
+        points.push(points[0]);
+        points.reverse();
+        let polyline = Polyline::new(points.clone(), vec![0]);
+        let polygon = polyline.into_polygon().unwrap();
+        points.reverse();
+        assert_eq!(polygon.points, points);
+    }
+}
+
+#[cfg(test)]
+mod measures_tests {
+    use super::*;
+
+    #[test]
+    fn measures_round_trip() {
+        let parts = vec![vec![(0.0, 0.0), (1.0, 1.0)], vec![(5.0, 5.0), (6.0, 6.0), (7.0, 7.0)]];
+        let measures = vec![vec![Some(1.0), None], vec![Some(-2.0), Some(4.0), None]];
+        let polyline = PolylineM::from_parts_with_measures(parts.clone(), measures.clone()).unwrap();
+        assert_eq!(polyline.parts, vec![0, 2]);
+        assert_eq!(polyline.points[1].m, NO_DATA);
+        assert_eq!(polyline.m_range(), [-2.0, 4.0]);
+        assert_eq!(polyline.parts_with_measures(), (parts, measures));
+    }
+
+    #[test]
+    fn all_measures_none() {
+        let polyline =
+            PolylineM::from_parts_with_measures(vec![vec![(0.0, 0.0), (1.0, 1.0)]], vec![vec![None, None]])
+                .unwrap();
+        assert!(polyline.points.iter().all(|p| p.m == NO_DATA));
+        assert_eq!(polyline.m_range(), [0.0, 0.0]);
+        assert_eq!(polyline.parts_with_measures().1, vec![vec![None, None]]);
+    }
+
+    #[test]
+    fn mismatched_measures() {
+        match PolylineM::from_parts_with_measures(
+            vec![vec![(0.0, 0.0)], vec![(1.0, 1.0), (2.0, 2.0)]],
+            vec![vec![None], vec![Some(1.0)]],
+        ) {
+            Err(Error::MeasuresLengthMismatch {
+                part: 1,
+                num_points: 2,
+                num_measures: 1,
+            }) => {}
+            _ => panic!("Expected MeasuresLengthMismatch"),
+        }
+
+        match PolylineM::from_parts_with_measures(vec![vec![(0.0, 0.0)]], vec![]) {
+            Err(Error::MeasuresLengthMismatch {
+                part: 0,
+                num_points: 1,
+                num_measures: 0,
+            }) => {}
+            _ => panic!("Expected MeasuresLengthMismatch"),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+//! Application of a coordinate transformation to the points of the shapes,
+//! used by [Shape::reproject](../enum.Shape.html#method.reproject)
+use record::traits::{HasMutXY, HasXY};
+use record::{BBox, Point, PointM, PointZ};
+
+/// Points whose coordinates can be transformed,
+/// the z of the points that do not have one is 0
+pub(crate) trait ReprojectPoint: HasXY + HasMutXY {
+    fn z(&self) -> f64 {
+        0.0
+    }
+
+    fn set_z(&mut self, _z: f64) {}
+}
+
+impl ReprojectPoint for Point {}
+
+impl ReprojectPoint for PointM {}
+
+impl ReprojectPoint for PointZ {
+    fn z(&self) -> f64 {
+        self.z
+    }
+
+    fn set_z(&mut self, z: f64) {
+        self.z = z;
+    }
+}
+
+/// Transforms the coordinates of the points with `f`,
+/// the points are only modified if `f` succeeds for all of them
+pub(crate) fn reproject_points<P, F, E>(points: &mut [P], f: &mut F) -> Result<(), E>
+where
+    P: ReprojectPoint,
+    F: FnMut(f64, f64, f64) -> Result<(f64, f64, f64), E>,
+{
+    let coordinates = points
+        .iter()
+        .map(|point| f(point.x(), point.y(), point.z()))
+        .collect::<Result<Vec<(f64, f64, f64)>, E>>()?;
+    for (point, (x, y, z)) in points.iter_mut().zip(coordinates) {
+        *point.x_mut() = x;
+        *point.y_mut() = y;
+        point.set_z(z);
+    }
+    Ok(())
+}
+
+/// Same as [reproject_points](fn.reproject_points.html), and recomputes the bbox of the points
+pub(crate) fn reproject_with_bbox<P, F, E>(
+    points: &mut [P],
+    bbox: &mut BBox,
+    f: &mut F,
+) -> Result<(), E>
+where
+    P: ReprojectPoint,
+    F: FnMut(f64, f64, f64) -> Result<(f64, f64, f64), E>,
+{
+    reproject_points(points, f)?;
+    *bbox = BBox::from_points(points);
+    Ok(())
+}
@@ -0,0 +1,90 @@
+//! Rounding of the coordinates of the shapes to a number of decimals,
+//! used by the [Writer](../../writer/struct.Writer.html#method.snap_precision)
+use constants::is_no_data;
+use record::{Point, PointM, PointZ};
+
+/// Returns the factor by which the coordinates are multiplied before being rounded
+pub(crate) fn scale_of(decimals: u8) -> f64 {
+    10f64.powi(i32::from(decimals))
+}
+
+/// Rounds the value to the grid of `1 / scale`, NO_DATA values are left untouched
+pub(crate) fn snap_value(value: f64, scale: f64) -> f64 {
+    if is_no_data(value) {
+        value
+    } else {
+        (value * scale).round() / scale
+    }
+}
+
+/// Points that can be rounded to a grid
+pub(crate) trait SnapPoint: Copy + PartialEq {
+    fn snap(&mut self, scale: f64);
+}
+
+impl SnapPoint for Point {
+    fn snap(&mut self, scale: f64) {
+        self.x = snap_value(self.x, scale);
+        self.y = snap_value(self.y, scale);
+    }
+}
+
+impl SnapPoint for PointM {
+    fn snap(&mut self, scale: f64) {
+        self.x = snap_value(self.x, scale);
+        self.y = snap_value(self.y, scale);
+        self.m = snap_value(self.m, scale);
+    }
+}
+
+impl SnapPoint for PointZ {
+    fn snap(&mut self, scale: f64) {
+        self.x = snap_value(self.x, scale);
+        self.y = snap_value(self.y, scale);
+        self.z = snap_value(self.z, scale);
+        self.m = snap_value(self.m, scale);
+    }
+}
+
+/// Snaps the points, and removes the consecutive points that became equal
+/// if `remove_repeated_points` is true
+pub(crate) fn snap_points<P: SnapPoint>(points: &mut Vec<P>, scale: f64, remove_repeated_points: bool) {
+    for point in points.iter_mut() {
+        point.snap(scale);
+    }
+    if remove_repeated_points {
+        points.dedup();
+    }
+}
+
+/// Snaps the points of each part, and removes the consecutive points of a part
+/// that became equal if `remove_repeated_points` is true.
+///
+/// Points are only compared to the points of the same part, so the first and last
+/// points of a ring (which are equal) are both kept.
+pub(crate) fn snap_parts<P: SnapPoint>(
+    points: &mut Vec<P>,
+    parts: &mut Vec<i32>,
+    scale: f64,
+    remove_repeated_points: bool,
+) {
+    let are_parts_valid = parts
+        .iter()
+        .zip(parts.iter().skip(1).chain(Some(&(points.len() as i32))))
+        .all(|(start, end)| 0 <= *start && start <= end);
+    if !remove_repeated_points || parts.is_empty() || !are_parts_valid {
+        snap_points(points, scale, false);
+        return;
+    }
+    let mut snapped_points = Vec::<P>::with_capacity(points.len());
+    let mut snapped_parts = Vec::<i32>::with_capacity(parts.len());
+    for (i, start) in parts.iter().enumerate() {
+        let end = parts.get(i + 1).map_or(points.len(), |end| *end as usize);
+        let mut part = points[*start as usize..end].to_vec();
+        snap_points(&mut part, scale, true);
+        snapped_parts.push(snapped_points.len() as i32);
+        snapped_points.append(&mut part);
+    }
+    *points = snapped_points;
+    *parts = snapped_parts;
+}
@@ -1,5 +1,6 @@
 use std::slice::SliceIndex;
 
+use constants::{is_no_data, NO_DATA};
 use record::{Point, PointM, PointZ};
 
 /// Trait to acces the x, and y values of a point
@@ -10,16 +11,112 @@ pub trait HasXY {
     fn y(&self) -> f64;
 }
 
-pub(crate) trait HasMutXY {
+/// Trait to modify the x, and y values of a point
+pub trait HasMutXY {
+    /// Returns a mutable reference to the value of the x dimension
     fn x_mut(&mut self) -> &mut f64;
+    /// Returns a mutable reference to the value of the y dimension
     fn y_mut(&mut self) -> &mut f64;
 }
 
-pub(crate) trait HasM {
+/// Trait to access the m value of a point
+pub trait HasM {
+    /// Returns the value of the m dimension (which may be NO_DATA)
     fn m(&self) -> f64;
+    /// Returns a mutable reference to the value of the m dimension
     fn m_mut(&mut self) -> &mut f64;
 }
 
+/// Trait to access the z value of a point
+pub trait HasZ {
+    /// Returns the value of the z dimension
+    fn z(&self) -> f64;
+    /// Returns a mutable reference to the value of the z dimension
+    fn z_mut(&mut self) -> &mut f64;
+}
+
+/// Trait to compute the points along a segment, with their z and m values interpolated
+pub trait Interpolate: HasXY + Copy {
+    /// Returns the point at `t` (from 0 to 1) of the segment from `self` to `other`.
+    ///
+    /// Its m value is NO_DATA if the m value of one of the ends is NO_DATA.
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+/// Interpolates between the values, NO_DATA if one of them is NO_DATA
+fn interpolate_value(a: f64, b: f64, t: f64) -> f64 {
+    if is_no_data(a) || is_no_data(b) {
+        NO_DATA
+    } else {
+        a + t * (b - a)
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Point::new(
+            interpolate_value(self.x, other.x, t),
+            interpolate_value(self.y, other.y, t),
+        )
+    }
+}
+
+impl Interpolate for PointM {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        PointM::new(
+            interpolate_value(self.x, other.x, t),
+            interpolate_value(self.y, other.y, t),
+            interpolate_value(self.m, other.m, t),
+        )
+    }
+}
+
+impl Interpolate for PointZ {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        PointZ::new(
+            interpolate_value(self.x, other.x, t),
+            interpolate_value(self.y, other.y, t),
+            interpolate_value(self.z, other.z, t),
+            interpolate_value(self.m, other.m, t),
+        )
+    }
+}
+
+/// Statistics of the values of a dimension (z or m) of points,
+/// see [MultipartShape::part_z_stats](trait.MultipartShape.html#method.part_z_stats)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct DimStats {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+}
+
+impl DimStats {
+    /// Returns the statistics of the values, the NO_DATA values being skipped,
+    /// `None` if there are no values left
+    fn of_values<I: Iterator<Item = f64>>(values: I) -> Option<Self> {
+        let mut count = 0usize;
+        let mut sum = 0.0;
+        let mut min = f64::INFINITY;
+        let mut max = f64::NEG_INFINITY;
+        for value in values.filter(|value| !is_no_data(*value)) {
+            count += 1;
+            sum += value;
+            min = min.min(value);
+            max = max.max(value);
+        }
+        if count == 0 {
+            None
+        } else {
+            Some(DimStats {
+                min,
+                max,
+                mean: sum / count as f64,
+            })
+        }
+    }
+}
+
 /// Trait that allows access to the slice of points of shapes that
 /// have multiple points (all the shapes except `Point`, `PointM`, `PoinZ`).
 pub trait MultipointShape<PointType> {
@@ -116,13 +213,44 @@ pub trait MultipartShape<PointType>: MultipointShape<PointType> {
     /// let poly_z_parts: Vec<&[shapefile::PointZ]> = poly_z.parts().collect();
     /// assert_eq!(poly_z_parts.len(), 3);
     /// ```
-    fn parts(&self) -> PartIterator<PointType, Self> {
+    fn parts(&self) -> PartIterator<'_, PointType, Self> {
         PartIterator {
             phantom: std::marker::PhantomData,
-            shape: &self,
+            shape: self,
             current_part: 0,
         }
     }
+
+    /// Returns the statistics of the z values of the part n°`index`,
+    /// `None` if the shape has no such part or if all its z values are NO_DATA
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use shapefile::record::MultipartShape;
+    /// let filepath = "tests/data/linez.shp";
+    /// let polylines_z = shapefile::read_as::<&str, shapefile::PolylineZ>(filepath).unwrap();
+    ///
+    /// let stats = polylines_z[0].part_z_stats(0).unwrap();
+    /// assert!(stats.min <= stats.mean && stats.mean <= stats.max);
+    /// assert!(polylines_z[0].part_z_stats(3).is_none());
+    /// ```
+    fn part_z_stats(&self, index: usize) -> Option<DimStats>
+    where
+        PointType: HasZ,
+    {
+        DimStats::of_values(self.part(index)?.iter().map(HasZ::z))
+    }
+
+    /// Returns the statistics of the m values of the part n°`index`, the NO_DATA
+    /// values being skipped, `None` if the shape has no such part or if all its
+    /// m values are NO_DATA
+    fn part_m_stats(&self, index: usize) -> Option<DimStats>
+    where
+        PointType: HasM,
+    {
+        DimStats::of_values(self.part(index)?.iter().map(HasM::m))
+    }
 }
 
 /// Iterator over the parts of a Multipart shape
@@ -201,3 +329,13 @@ impl_has_mut_xy_for!(PointZ);
 
 impl_has_m_for!(PointM);
 impl_has_m_for!(PointZ);
+
+impl HasZ for PointZ {
+    fn z(&self) -> f64 {
+        self.z
+    }
+
+    fn z_mut(&mut self) -> &mut f64 {
+        &mut self.z
+    }
+}
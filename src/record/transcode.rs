@@ -0,0 +1,174 @@
+//! Module to transcode shapes from one point type to another
+//! (e.g. a `Polyline` into a `PolylineZ`), keeping their parts.
+//!
+//! The points are converted with the `From` implementations between
+//! [Point](../point/struct.Point.html), [PointM](../point/struct.PointM.html)
+//! and [PointZ](../point/struct.PointZ.html):
+//!
+//! - a promoted point gets `0.0` as z and [NO_DATA](../../constant.NO_DATA.html) as m
+//! - a demoted point loses the values its type does not have
+//!
+//! Whether the M values of a shape are written
+//! ([m_present](../poly/struct.GenericPolyline.html#method.m_present)) is kept
+//! when both point types have M values, new M values are always written.
+//!
+//! [Shape::convert_to](../enum.Shape.html#method.convert_to) uses these functions
+//! to convert a shape to another shape type of the same class.
+//!
+//! # Example
+//!
+//! ```
+//! use shapefile::record::transcode::transcode_polyline;
+//! use shapefile::{Point, PolylineZ, Polyline, NO_DATA};
+//! let polyline = Polyline::new(vec![Point::new(1.0, 1.0), Point::new(2.0, 2.0)], vec![0]);
+//!
+//! let polyline_z: PolylineZ = transcode_polyline(polyline);
+//! assert_eq!(polyline_z.points[1].z, 0.0);
+//! assert_eq!(polyline_z.points[1].m, NO_DATA);
+//! ```
+use record::multipoint::GenericMultipoint;
+use record::poly::{GenericPolygon, GenericPolyline};
+use record::{HasShapeType, Point, PointM, PointZ, Shape};
+use {Error, ShapeType};
+
+fn transcode_points<Src, Dst: From<Src>>(points: Vec<Src>) -> Vec<Dst> {
+    points.into_iter().map(Dst::from).collect()
+}
+
+/// Returns whether the M values of the transcoded shape are written:
+/// as for the shape if both point types have M values, always otherwise (as for new shapes)
+fn transcode_m_present<Src: HasShapeType, Dst: HasShapeType>(m_present: bool) -> bool {
+    m_present || !(Src::shapetype().has_m() && Dst::shapetype().has_m())
+}
+
+/// Converts the points of the polyline, its bbox and parts are kept
+pub fn transcode_polyline<Src, Dst>(shape: GenericPolyline<Src>) -> GenericPolyline<Dst>
+where
+    Src: HasShapeType,
+    Dst: From<Src> + HasShapeType,
+{
+    GenericPolyline {
+        bbox: shape.bbox,
+        points: transcode_points(shape.points),
+        parts: shape.parts,
+        m_present: transcode_m_present::<Src, Dst>(shape.m_present),
+    }
+}
+
+/// Converts the points of the polygon, its bbox and parts (rings) are kept
+pub fn transcode_polygon<Src, Dst>(shape: GenericPolygon<Src>) -> GenericPolygon<Dst>
+where
+    Src: HasShapeType,
+    Dst: From<Src> + HasShapeType,
+{
+    GenericPolygon {
+        bbox: shape.bbox,
+        points: transcode_points(shape.points),
+        parts: shape.parts,
+        m_present: transcode_m_present::<Src, Dst>(shape.m_present),
+    }
+}
+
+/// Converts the points of the multipoint, its bbox is kept
+pub fn transcode_multipoint<Src, Dst>(shape: GenericMultipoint<Src>) -> GenericMultipoint<Dst>
+where
+    Src: HasShapeType,
+    Dst: From<Src> + HasShapeType,
+{
+    GenericMultipoint {
+        bbox: shape.bbox,
+        points: transcode_points(shape.points),
+        m_present: transcode_m_present::<Src, Dst>(shape.m_present),
+    }
+}
+
+/// Converts the point into the point shape of the requested type,
+/// which must be a point shape type
+fn point_to<Src>(point: Src, shape_type: ShapeType) -> Shape
+where
+    Src: HasShapeType,
+    Point: From<Src>,
+    PointM: From<Src>,
+    PointZ: From<Src>,
+{
+    match shape_type {
+        ShapeType::Point => Shape::Point(point.into()),
+        ShapeType::PointM => Shape::PointM(point.into()),
+        _ => Shape::PointZ(point.into()),
+    }
+}
+
+/// Converts the polyline into the polyline shape of the requested type,
+/// which must be a polyline shape type
+fn polyline_to<Src>(shape: GenericPolyline<Src>, shape_type: ShapeType) -> Shape
+where
+    Src: HasShapeType,
+    Point: From<Src>,
+    PointM: From<Src>,
+    PointZ: From<Src>,
+{
+    match shape_type {
+        ShapeType::Polyline => Shape::Polyline(transcode_polyline(shape)),
+        ShapeType::PolylineM => Shape::PolylineM(transcode_polyline(shape)),
+        _ => Shape::PolylineZ(transcode_polyline(shape)),
+    }
+}
+
+/// Converts the polygon into the polygon shape of the requested type,
+/// which must be a polygon shape type
+fn polygon_to<Src>(shape: GenericPolygon<Src>, shape_type: ShapeType) -> Shape
+where
+    Src: HasShapeType,
+    Point: From<Src>,
+    PointM: From<Src>,
+    PointZ: From<Src>,
+{
+    match shape_type {
+        ShapeType::Polygon => Shape::Polygon(transcode_polygon(shape)),
+        ShapeType::PolygonM => Shape::PolygonM(transcode_polygon(shape)),
+        _ => Shape::PolygonZ(transcode_polygon(shape)),
+    }
+}
+
+/// Converts the multipoint into the multipoint shape of the requested type,
+/// which must be a multipoint shape type
+fn multipoint_to<Src>(shape: GenericMultipoint<Src>, shape_type: ShapeType) -> Shape
+where
+    Src: HasShapeType,
+    Point: From<Src>,
+    PointM: From<Src>,
+    PointZ: From<Src>,
+{
+    match shape_type {
+        ShapeType::Multipoint => Shape::Multipoint(transcode_multipoint(shape)),
+        ShapeType::MultipointM => Shape::MultipointM(transcode_multipoint(shape)),
+        _ => Shape::MultipointZ(transcode_multipoint(shape)),
+    }
+}
+
+/// Converts the shape into the requested shape type, which must be of the same class
+pub(crate) fn convert_shape(shape: Shape, shape_type: ShapeType) -> Result<Shape, Error> {
+    let actual = shape.shapetype();
+    if actual.geometry_class() != shape_type.geometry_class() {
+        return Err(Error::MismatchShapeType {
+            requested: shape_type,
+            actual,
+        });
+    }
+    let converted = match shape {
+        Shape::Point(shp) => point_to(shp, shape_type),
+        Shape::PointM(shp) => point_to(shp, shape_type),
+        Shape::PointZ(shp) => point_to(shp, shape_type),
+        Shape::Polyline(shp) => polyline_to(shp, shape_type),
+        Shape::PolylineM(shp) => polyline_to(shp, shape_type),
+        Shape::PolylineZ(shp) => polyline_to(shp, shape_type),
+        Shape::Polygon(shp) => polygon_to(shp, shape_type),
+        Shape::PolygonM(shp) => polygon_to(shp, shape_type),
+        Shape::PolygonZ(shp) => polygon_to(shp, shape_type),
+        Shape::Multipoint(shp) => multipoint_to(shp, shape_type),
+        Shape::MultipointM(shp) => multipoint_to(shp, shape_type),
+        Shape::MultipointZ(shp) => multipoint_to(shp, shape_type),
+        Shape::Multipatch(_) | Shape::NullShape(_) | Shape::Unknown { .. } => shape,
+    };
+    Ok(converted)
+}
@@ -0,0 +1,180 @@
+//! Module with views of the points and parts of a [Shape](../enum.Shape.html)
+//! that do not depend on its concrete type.
+//!
+//! They are returned by [Shape::parts](../enum.Shape.html#method.parts),
+//! to handle the parts of any multipart shape without matching on the type of the shape.
+//! The views only borrow the points of the shape, nothing is copied.
+use record::traits::MultipartShape;
+use record::{Point, PointM, PointZ, Shape};
+
+/// View of a point of any of the point types
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GenericPointView<'a> {
+    Point(&'a Point),
+    PointM(&'a PointM),
+    PointZ(&'a PointZ),
+}
+
+impl<'a> GenericPointView<'a> {
+    /// Returns the x of the point
+    pub fn x(&self) -> f64 {
+        match self {
+            GenericPointView::Point(p) => p.x,
+            GenericPointView::PointM(p) => p.x,
+            GenericPointView::PointZ(p) => p.x,
+        }
+    }
+
+    /// Returns the y of the point
+    pub fn y(&self) -> f64 {
+        match self {
+            GenericPointView::Point(p) => p.y,
+            GenericPointView::PointM(p) => p.y,
+            GenericPointView::PointZ(p) => p.y,
+        }
+    }
+
+    /// Returns the z of the point, `None` if the point type has no z
+    pub fn z(&self) -> Option<f64> {
+        match self {
+            GenericPointView::PointZ(p) => Some(p.z),
+            _ => None,
+        }
+    }
+
+    /// Returns the m of the point (which may be NO_DATA), `None` if the point type has no m
+    pub fn m(&self) -> Option<f64> {
+        match self {
+            GenericPointView::Point(_) => None,
+            GenericPointView::PointM(p) => Some(p.m),
+            GenericPointView::PointZ(p) => Some(p.m),
+        }
+    }
+}
+
+/// The point is converted like the concrete point it views
+/// (see the conversions between the point types)
+impl<'a> From<GenericPointView<'a>> for Point {
+    fn from(view: GenericPointView<'a>) -> Self {
+        match view {
+            GenericPointView::Point(p) => *p,
+            GenericPointView::PointM(p) => Point::from(*p),
+            GenericPointView::PointZ(p) => Point::from(*p),
+        }
+    }
+}
+
+impl<'a> From<GenericPointView<'a>> for PointM {
+    fn from(view: GenericPointView<'a>) -> Self {
+        match view {
+            GenericPointView::Point(p) => PointM::from(*p),
+            GenericPointView::PointM(p) => *p,
+            GenericPointView::PointZ(p) => PointM::from(*p),
+        }
+    }
+}
+
+impl<'a> From<GenericPointView<'a>> for PointZ {
+    fn from(view: GenericPointView<'a>) -> Self {
+        match view {
+            GenericPointView::Point(p) => PointZ::from(*p),
+            GenericPointView::PointM(p) => PointZ::from(*p),
+            GenericPointView::PointZ(p) => *p,
+        }
+    }
+}
+
+/// View of the points of a part, of any of the point types
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PartView<'a> {
+    Point(&'a [Point]),
+    PointM(&'a [PointM]),
+    PointZ(&'a [PointZ]),
+}
+
+impl<'a> PartView<'a> {
+    /// Returns the number of points of the part
+    pub fn len(&self) -> usize {
+        match self {
+            PartView::Point(points) => points.len(),
+            PartView::PointM(points) => points.len(),
+            PartView::PointZ(points) => points.len(),
+        }
+    }
+
+    /// Returns true if the part has no points
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the view of the point at `index`
+    pub fn get(&self, index: usize) -> Option<GenericPointView<'a>> {
+        match *self {
+            PartView::Point(points) => points.get(index).map(GenericPointView::Point),
+            PartView::PointM(points) => points.get(index).map(GenericPointView::PointM),
+            PartView::PointZ(points) => points.get(index).map(GenericPointView::PointZ),
+        }
+    }
+
+    /// Returns an iterator over the views of the points of the part
+    pub fn iter(&self) -> impl Iterator<Item = GenericPointView<'a>> {
+        let part = *self;
+        (0..part.len()).filter_map(move |i| part.get(i))
+    }
+}
+
+/// Iterator over the parts of a shape, created by
+/// [Shape::parts](../enum.Shape.html#method.parts)
+pub struct PartsIter<'a> {
+    shape: &'a Shape,
+    num_parts: usize,
+    current_part: usize,
+}
+
+impl<'a> PartsIter<'a> {
+    /// Returns the iterator over the parts of the shape, `None` if it is not multipart
+    pub(crate) fn new(shape: &'a Shape) -> Option<Self> {
+        let num_parts = match shape {
+            Shape::Polyline(shp) => shp.parts_indices().len(),
+            Shape::PolylineM(shp) => shp.parts_indices().len(),
+            Shape::PolylineZ(shp) => shp.parts_indices().len(),
+            Shape::Polygon(shp) => shp.parts_indices().len(),
+            Shape::PolygonM(shp) => shp.parts_indices().len(),
+            Shape::PolygonZ(shp) => shp.parts_indices().len(),
+            Shape::Multipatch(shp) => shp.parts_indices().len(),
+            _ => return None,
+        };
+        Some(PartsIter {
+            shape,
+            num_parts,
+            current_part: 0,
+        })
+    }
+}
+
+impl<'a> Iterator for PartsIter<'a> {
+    type Item = PartView<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current_part >= self.num_parts {
+            return None;
+        }
+        let index = self.current_part;
+        self.current_part += 1;
+        match self.shape {
+            Shape::Polyline(shp) => shp.part(index).map(PartView::Point),
+            Shape::PolylineM(shp) => shp.part(index).map(PartView::PointM),
+            Shape::PolylineZ(shp) => shp.part(index).map(PartView::PointZ),
+            Shape::Polygon(shp) => shp.part(index).map(PartView::Point),
+            Shape::PolygonM(shp) => shp.part(index).map(PartView::PointM),
+            Shape::PolygonZ(shp) => shp.part(index).map(PartView::PointZ),
+            Shape::Multipatch(shp) => shp.part(index).map(PartView::PointZ),
+            _ => None,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.num_parts - self.current_part;
+        (remaining, Some(remaining))
+    }
+}
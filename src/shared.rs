@@ -0,0 +1,186 @@
+//! Module with the [SharedReader](struct.SharedReader.html), a reader of a shapefile
+//! held in memory that can be shared between threads
+//!
+//! A [Reader](../reader/struct.Reader.html) seeks in its source to read a record,
+//! so reading needs a `&mut` and each thread needs its own reader.
+//! The `SharedReader` holds the bytes of the *.shp* and the index of its records,
+//! and parses the records directly from the bytes: all its methods take `&self`
+//! and it is `Sync`, so one instance (e.g. behind an `Arc`) can serve all threads.
+use std::io::Cursor;
+use std::path::Path;
+
+use header::Header;
+use index::IndexTable;
+use reader::{read_record_bbox, Reader};
+use record::{self, BBox, EsriShape, ReadableShape, RecordHeader, RecordSizeCheck};
+use sidecar::companion_path_of;
+use {Error, Shape};
+
+/// Reader of a shapefile in memory which methods take `&self`,
+/// see the [module](index.html) documentation
+pub struct SharedReader {
+    shp: Vec<u8>,
+    header: Header,
+    index: IndexTable,
+}
+
+impl SharedReader {
+    /// Creates a reader from the bytes of a *.shp*, and of its *.shx* if there is one.
+    ///
+    /// Without a *.shx*, the index of the records is built by reading
+    /// the header of each record.
+    ///
+    /// # Errors
+    ///
+    /// The errors of [Reader::from_seekable](../reader/struct.Reader.html#method.from_seekable)
+    /// and, without a *.shx*, `Error::InvalidShapeRecordSize` if the header of a record
+    /// is invalid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let shp = std::fs::read("tests/data/line.shp")?;
+    /// let reader = shapefile::SharedReader::from_bytes(shp, None)?;
+    /// assert_eq!(reader.len(), 1);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes(shp: Vec<u8>, shx: Option<Vec<u8>>) -> Result<Self, Error> {
+        let header = *Reader::from_seekable(Cursor::new(shp.as_slice()))?.header();
+        let index = match shx {
+            Some(shx) => {
+                let index = IndexTable::read_from(shx.as_slice())?;
+                index.check_entries_end_before(header.file_length.max(0) as u64 * 2)?;
+                index
+            }
+            None => IndexTable::scan_shp(&mut Cursor::new(shp.as_slice()), &header)?,
+        };
+        Ok(Self { shp, header, index })
+    }
+
+    /// Creates a reader by reading in memory the *.shp* at the `path`,
+    /// and the *.shx* next to it if there is one (see [from_bytes](#method.from_bytes))
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// let reader = shapefile::SharedReader::from_path("tests/data/multipatch.shp")?;
+    /// assert!(reader.read_nth(0).is_some());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let shp_path = path.as_ref();
+        let shx = match companion_path_of(shp_path, "shx")? {
+            Some(shx_path) => Some(std::fs::read(shx_path)?),
+            None => None,
+        };
+        Self::from_bytes(std::fs::read(shp_path)?, shx)
+    }
+
+    /// Returns the header of the *.shp*
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the number of records
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the file has no records
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Reads the `n`th shape (0-based) as the specified type
+    ///
+    /// # Returns
+    ///
+    /// `None` if the index is out of range
+    ///
+    /// # Errors
+    ///
+    /// - `Error::IndexEntryMismatch` if the header of the record does not
+    ///   have the content length given by the index
+    /// - `Error::TruncatedFile` if the record given by the index ends after the *.shp*
+    pub fn read_nth_as<S: ReadableShape>(&self, index: usize) -> Option<Result<S, Error>> {
+        Some(self.record_content(index)?.and_then(|content| {
+            record::read_record_content(
+                &mut &content[..],
+                content.len() as u64,
+                RecordSizeCheck::Exact,
+            )
+        }))
+    }
+
+    /// Reads the `n`th shape (0-based)
+    pub fn read_nth(&self, index: usize) -> Option<Result<Shape, Error>> {
+        self.read_nth_as::<Shape>(index)
+    }
+
+    /// Reads, as the specified type, the shapes which bounding box intersects the `bbox`,
+    /// and returns them paired with their index, in the order of the file.
+    ///
+    /// Only the bounding box of the records is read to select them. NullShapes never intersect.
+    pub fn query_bbox_as<S: ReadableShape + EsriShape>(
+        &self,
+        bbox: &BBox,
+    ) -> Result<Vec<(usize, S)>, Error> {
+        let mut shapes = Vec::<(usize, S)>::new();
+        for index in 0..self.len() {
+            let content = match self.record_content(index) {
+                Some(content) => content?,
+                None => break,
+            };
+            // The content is entirely in memory, it cannot be truncated
+            let size = content.len() as u64;
+            let (record_bbox, _) = read_record_bbox(&mut &content[..], 0, size, size)?;
+            match record_bbox {
+                Some(record_bbox) if record_bbox.intersects(bbox) => {}
+                _ => continue,
+            }
+            let shape = record::read_record_content(
+                &mut &content[..],
+                content.len() as u64,
+                RecordSizeCheck::Exact,
+            )?;
+            shapes.push((index, shape));
+        }
+        Ok(shapes)
+    }
+
+    /// Reads the shapes which bounding box intersects the `bbox`,
+    /// see [query_bbox_as](#method.query_bbox_as)
+    pub fn query_bbox(&self, bbox: &BBox) -> Result<Vec<(usize, Shape)>, Error> {
+        self.query_bbox_as::<Shape>(bbox)
+    }
+
+    /// Returns the content (without its header) of the `n`th record
+    fn record_content(&self, index: usize) -> Option<Result<&[u8], Error>> {
+        let (offset, index_length) = self.index.offset_of(index)?;
+        let content_start = (offset as usize).saturating_add(RecordHeader::SIZE);
+        let content_end = content_start.saturating_add(index_length);
+        if content_end > self.shp.len() {
+            return Some(Err(Error::TruncatedFile {
+                expected_bytes: content_end as u64,
+                actual_bytes: self.shp.len() as u64,
+            }));
+        }
+        let hdr = match RecordHeader::read_from(&mut &self.shp[offset as usize..content_start]) {
+            Ok(hdr) => hdr,
+            Err(e) => return Some(Err(e)),
+        };
+        let record_length = hdr.record_size as i64 * 2;
+        if record_length != index_length as i64 {
+            return Some(Err(Error::IndexEntryMismatch {
+                index,
+                index_length,
+                record_length: record_length.max(0) as usize,
+            }));
+        }
+        Some(Ok(&self.shp[content_start..content_end]))
+    }
+}
@@ -0,0 +1,214 @@
+//! Module to find and copy the sidecar files of a shapefile
+//!
+//! Besides the *.shp*, *.shx* and *.dbf* read and written by this crate,
+//! a shapefile often comes with other files sharing its basename
+//! (*.prj*, *.cpg*, *.shp.xml* metadata, *.sbn*/*.sbx*, *.atx*, *.ain*/*.aih* indices, ...).
+//! Their content is not understood by this crate, but they can be listed
+//! and copied next to another shapefile, for example when transcoding one.
+use std::path::{Path, PathBuf};
+
+use Error;
+
+/// Extensions of the files read and written by this crate, which are not sidecars
+const CORE_EXTENSIONS: [&str; 3] = ["shp", "shx", "dbf"];
+
+/// Returns the basename of the shapefile, that is its file name without its *.shp* extension
+/// (in any case), only the last extension is removed (`archive.2020.shp` gives `archive.2020`).
+fn basename_of(shp_path: &Path) -> Option<&str> {
+    let file_name = shp_path.file_name()?.to_str()?;
+    match file_name.rfind('.') {
+        Some(dot) if file_name[dot + 1..].eq_ignore_ascii_case("shp") => Some(&file_name[..dot]),
+        _ => Some(file_name),
+    }
+}
+
+/// Returns the extension of the sidecar (`prj`, `shp.xml`...) if the `file_name` is the one
+/// of a sidecar of the shapefile with the `basename`.
+///
+/// The extension is either a single extension that is not the one of the *.shp*,
+/// *.shx* or *.dbf*, or an extension of the *.shp* itself (like `shp.xml`),
+/// so that `archive.2020.shp` is not taken for a sidecar of `archive.shp`.
+fn sidecar_extension<'a>(file_name: &'a str, basename: &str) -> Option<&'a str> {
+    if file_name.len() <= basename.len() + 1 || !file_name.starts_with(basename) {
+        return None;
+    }
+    if file_name.as_bytes()[basename.len()] != b'.' {
+        return None;
+    }
+    let extension = &file_name[basename.len() + 1..];
+    let is_single = !extension.contains('.')
+        && !CORE_EXTENSIONS
+            .iter()
+            .any(|core| extension.eq_ignore_ascii_case(core));
+    let extends_shp = extension
+        .split('.')
+        .next()
+        .map_or(false, |first| first.eq_ignore_ascii_case("shp"))
+        && extension.len() > 4;
+    if is_single || extends_shp {
+        Some(extension)
+    } else {
+        None
+    }
+}
+
+/// Returns the path of the file with the `extension` (`shx` or `dbf`) of the shapefile
+/// at `shp_path`, or `None` if there is no such file.
+///
+/// The file with the exact basename and extension is looked for first, then the case
+/// of the extension and of the basename are ignored (`Roads.shx` is found for `ROADS.SHP`),
+/// files with the exact basename being preferred.
+pub(crate) fn companion_path_of(
+    shp_path: &Path,
+    extension: &str,
+) -> Result<Option<PathBuf>, Error> {
+    let path = shp_path.with_extension(extension);
+    if path.exists() {
+        return Ok(Some(path));
+    }
+    let basename = match basename_of(shp_path) {
+        Some(basename) => basename,
+        None => return Ok(None),
+    };
+    let dir = match shp_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    // The first one by name of the files whose basename only differs by its case
+    let mut other_case: Option<String> = None;
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        let expected_len = basename.len() + 1 + extension.len();
+        if name.len() != expected_len || !name.is_char_boundary(basename.len()) {
+            continue;
+        }
+        let (stem, dot_extension) = name.split_at(basename.len());
+        if !dot_extension.starts_with('.')
+            || !dot_extension[1..].eq_ignore_ascii_case(extension)
+            || !stem.eq_ignore_ascii_case(basename)
+            || !entry.file_type()?.is_file()
+        {
+            continue;
+        }
+        if stem == basename {
+            return Ok(Some(dir.join(name)));
+        }
+        if other_case.as_ref().map_or(true, |other| name < *other) {
+            other_case = Some(name);
+        }
+    }
+    Ok(other_case.map(|name| dir.join(name)))
+}
+
+/// Returns the paths of the sidecar files of the shapefile at `shp_path`,
+/// sorted by name.
+///
+/// The sidecars are the files of the same directory whose name is the basename
+/// of the shapefile followed by an extension other than *.shp*, *.shx* and *.dbf*
+/// (the case of the extensions does not matter, the one of the basename does).
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// let sidecars = shapefile::sidecar::sidecar_paths_of("tests/data/line.shp")?;
+/// assert!(sidecars.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+pub fn sidecar_paths_of<P: AsRef<Path>>(shp_path: P) -> Result<Vec<PathBuf>, Error> {
+    let shp_path = shp_path.as_ref();
+    let basename = match basename_of(shp_path) {
+        Some(basename) => basename,
+        None => return Ok(vec![]),
+    };
+    let dir = match shp_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut sidecars = Vec::<PathBuf>::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let file_name = entry.file_name();
+        let is_sidecar = file_name
+            .to_str()
+            .map_or(false, |name| sidecar_extension(name, basename).is_some());
+        if is_sidecar {
+            sidecars.push(dir.join(file_name));
+        }
+    }
+    sidecars.sort();
+    Ok(sidecars)
+}
+
+/// Copies the sidecar files of the shapefile at `input` next to the shapefile at `output`,
+/// giving them the basename of `output`, and returns the paths of the copies.
+///
+/// Existing files are overwritten, a sidecar is not copied onto itself.
+///
+/// See [sidecar_paths_of](fn.sidecar_paths_of.html) for which files are copied.
+pub fn copy_sidecars<P, Q>(input: P, output: Q) -> Result<Vec<PathBuf>, Error>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let input = input.as_ref();
+    let output = output.as_ref();
+    let (input_basename, output_basename) = match (basename_of(input), basename_of(output)) {
+        (Some(input_basename), Some(output_basename)) => (input_basename, output_basename),
+        _ => return Ok(vec![]),
+    };
+    let mut copies = Vec::<PathBuf>::new();
+    for sidecar in sidecar_paths_of(input)? {
+        let extension = sidecar
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| sidecar_extension(name, input_basename))
+            .unwrap();
+        let copy = output.with_file_name(format!("{}.{}", output_basename, extension));
+        if copy == sidecar {
+            continue;
+        }
+        std::fs::copy(&sidecar, &copy)?;
+        copies.push(copy);
+    }
+    Ok(copies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn basenames() {
+        assert_eq!(basename_of(Path::new("dir/roads.shp")), Some("roads"));
+        assert_eq!(basename_of(Path::new("ROADS.SHP")), Some("ROADS"));
+        assert_eq!(basename_of(Path::new("archive.2020.shp")), Some("archive.2020"));
+        assert_eq!(basename_of(Path::new("no_extension")), Some("no_extension"));
+    }
+
+    #[test]
+    fn sidecar_extensions() {
+        assert_eq!(sidecar_extension("roads.prj", "roads"), Some("prj"));
+        assert_eq!(sidecar_extension("roads.PRJ", "roads"), Some("PRJ"));
+        assert_eq!(sidecar_extension("roads.shp.xml", "roads"), Some("shp.xml"));
+        assert_eq!(sidecar_extension("roads.SHP.XML", "roads"), Some("SHP.XML"));
+        assert_eq!(sidecar_extension("roads.shp", "roads"), None);
+        assert_eq!(sidecar_extension("roads.SHX", "roads"), None);
+        assert_eq!(sidecar_extension("roads.dbf", "roads"), None);
+        assert_eq!(sidecar_extension("roads_old.prj", "roads"), None);
+        assert_eq!(sidecar_extension("roads.", "roads"), None);
+        assert_eq!(sidecar_extension("roads.shp.", "roads"), None);
+        // files of another shapefile whose name starts with the basename
+        assert_eq!(sidecar_extension("archive.2020.shp", "archive"), None);
+        assert_eq!(sidecar_extension("archive.2020.prj", "archive"), None);
+        assert_eq!(sidecar_extension("archive.2020.prj", "archive.2020"), Some("prj"));
+    }
+}
@@ -0,0 +1,83 @@
+//! In-memory spatial index over the shapes of a shapefile (requires the `rstar` feature)
+//!
+//! The [SpatialIndex](struct.SpatialIndex.html) is an R-tree mapping the bounding box
+//! of each record to its index in the file. It allows fast and repeated spatial queries
+//! without needing the external *.sbn* or *.qix* index formats.
+//!
+//! The indices returned by a query can then be used with
+//! [read_nth_shape](../reader/struct.Reader.html#method.read_nth_shape) to only read
+//! the matching shapes.
+use std::io::{Read, Seek};
+
+use rstar::primitives::{GeomWithData, Rectangle};
+use rstar::{RTree, AABB};
+
+use reader::Reader;
+use record::BBox;
+use Error;
+
+type IndexedBBox = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// R-tree of the bounding boxes of the shapes of a shapefile
+pub struct SpatialIndex {
+    tree: RTree<IndexedBBox>,
+}
+
+impl SpatialIndex {
+    /// Builds the index from the records of the shapefile.
+    ///
+    /// Only the bounding box of each record is read (the points are skipped),
+    /// which makes building the index cheap even for big files.
+    /// NullShapes are not part of the index.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::record::BBox;
+    /// use shapefile::SpatialIndex;
+    /// let mut reader = shapefile::Reader::from_path("tests/data/line.shp")?;
+    /// let index = SpatialIndex::build(&mut reader)?;
+    /// for i in index.query(&BBox::new(0.0, 0.0, 10.0, 10.0)) {
+    ///     let shape = reader.read_nth_shape(i).unwrap()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn build<T: Read + Seek>(reader: &mut Reader<T>) -> Result<Self, Error> {
+        let entries = reader
+            .read_record_bboxes()?
+            .into_iter()
+            .map(|(index, bbox)| {
+                let rect = Rectangle::from_corners([bbox.xmin, bbox.ymin], [bbox.xmax, bbox.ymax]);
+                GeomWithData::new(rect, index)
+            })
+            .collect();
+        Ok(Self {
+            tree: RTree::bulk_load(entries),
+        })
+    }
+
+    /// Returns the indices (in ascending order) of the shapes which
+    /// bounding box intersects the `bbox`.
+    pub fn query(&self, bbox: &BBox) -> Vec<usize> {
+        let envelope = AABB::from_corners([bbox.xmin, bbox.ymin], [bbox.xmax, bbox.ymax]);
+        let mut indices = self
+            .tree
+            .locate_in_envelope_intersecting(&envelope)
+            .map(|entry| entry.data)
+            .collect::<Vec<usize>>();
+        indices.sort_unstable();
+        indices
+    }
+
+    /// Returns the number of shapes in the index
+    pub fn len(&self) -> usize {
+        self.tree.size()
+    }
+
+    /// Returns true if there are no shapes in the index
+    pub fn is_empty(&self) -> bool {
+        self.tree.size() == 0
+    }
+}
@@ -0,0 +1,101 @@
+//! Module with the [split_by](fn.split_by.html) function that allows to dispatch
+//! the features of a shapefile into multiple shapefiles.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::Hash;
+use std::io::{BufWriter, Read};
+use std::path::PathBuf;
+
+use dbf::DbfWriter;
+use reader::Reader;
+use writer::Writer;
+use {Error, Shape};
+
+/// One of the shapefiles written by `split_by`
+struct Output {
+    path: PathBuf,
+    writer: Writer<BufWriter<File>>,
+    dbf_writer: DbfWriter,
+}
+
+impl Output {
+    fn create(path: PathBuf) -> Result<Self, Error> {
+        let mut writer = Writer::new(BufWriter::new(File::create(&path)?));
+        writer.add_index_dest(BufWriter::new(File::create(path.with_extension("shx"))?));
+        let dbf_writer = DbfWriter::create(path.with_extension("dbf"))?;
+        Ok(Self {
+            path,
+            writer,
+            dbf_writer,
+        })
+    }
+
+    fn finish(mut self) -> Result<PathBuf, Error> {
+        self.writer.finish()?;
+        self.dbf_writer.finish()?;
+        Ok(self.path)
+    }
+}
+
+/// Splits the features (shape + record) read by the `reader` into multiple shapefiles.
+///
+/// Each feature is given to the `key_fn` to compute its key, all the features
+/// having the same key are written to the same output, which path is given by `output_for`.
+/// The .shp, .shx and .dbf of each output are created.
+///
+/// Returns the paths of all the .shp files created, in the order of the first feature
+/// of each key.
+///
+/// # Limits
+///
+/// The features are written as they are read, only one feature is in memory at a time.
+/// An output is created when the first feature of its key is read, and its files stay
+/// open until all the features are written: three files are open for each distinct key,
+/// the number of distinct keys is limited by the number of files the process can open.
+///
+/// # Errors
+///
+/// Returns `Error::MissingDbf` if the reader has no dbf source.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// use shapefile::Shape;
+/// let reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?;
+/// let outputs = shapefile::split_by(
+///     reader,
+///     |shape, _record| shape.shapetype(),
+///     |shapetype| std::env::temp_dir().join(format!("split_doc_{}.shp", shapetype)),
+/// )?;
+/// assert_eq!(outputs.len(), 1);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split_by<T, K, F, P>(reader: Reader<T>, key_fn: F, output_for: P) -> Result<Vec<PathBuf>, Error>
+where
+    T: Read,
+    K: Eq + Hash,
+    F: Fn(&Shape, &dbase::Record) -> K,
+    P: Fn(&K) -> PathBuf,
+{
+    let mut output_of_key = HashMap::<K, usize>::new();
+    let mut outputs = Vec::<Output>::new();
+    for result in reader.iter_shapes_and_records()? {
+        let (shape, record) = result?;
+        let key = key_fn(&shape, &record);
+        let index = match output_of_key.get(&key) {
+            Some(index) => *index,
+            None => {
+                outputs.push(Output::create(output_for(&key))?);
+                output_of_key.insert(key, outputs.len() - 1);
+                outputs.len() - 1
+            }
+        };
+        let output = &mut outputs[index];
+        output.writer.write_shape(shape)?;
+        output.dbf_writer.write_record(&record)?;
+    }
+    outputs.into_iter().map(Output::finish).collect()
+}
@@ -0,0 +1,208 @@
+//! Module with the [StreamingParser](struct.StreamingParser.html), a parser of *.shp* files
+//! to which the bytes are pushed as they arrive
+//!
+//! A [Reader](../reader/struct.Reader.html) pulls the bytes from its source and blocks
+//! until they are available. The `StreamingParser` is fed the bytes, in chunks of any size,
+//! and returns the header and the records that are complete: it never blocks,
+//! which suits event driven code (e.g. a file received over the network).
+//!
+//! The records are parsed by the same code as the reader's, with the same checks.
+//!
+//! # Example
+//!
+//! ```
+//! # fn main() -> Result<(), shapefile::Error> {
+//! use shapefile::streaming::{ParsedEvent, StreamingParser};
+//! let bytes = std::fs::read("tests/data/multipoint.shp")?;
+//! let mut parser = StreamingParser::new();
+//! let mut num_shapes = 0;
+//! for chunk in bytes.chunks(64) {
+//!     for event in parser.feed(chunk) {
+//!         match event {
+//!             ParsedEvent::Header(header) => println!("{}", header.shape_type),
+//!             ParsedEvent::Record(_, _) => num_shapes += 1,
+//!             ParsedEvent::NeedMoreData | ParsedEvent::Finished => {}
+//!             ParsedEvent::Error(error) => return Err(error),
+//!         }
+//!     }
+//! }
+//! parser.finish()?;
+//! assert_eq!(num_shapes, 1);
+//! # Ok(())
+//! # }
+//! ```
+use constants::HEADER_SIZE;
+use header::Header;
+use reader::{check_record_size, DEFAULT_MAX_RECORD_SIZE};
+use record::{self, RecordHeader, RecordSizeCheck};
+use {Error, Shape};
+
+/// What the [StreamingParser](struct.StreamingParser.html) found in the bytes fed to it
+pub enum ParsedEvent {
+    /// The header of the file, the first event
+    Header(Header),
+    /// A record, with its number (as written in its header) and its shape
+    Record(i32, Shape),
+    /// The bytes fed so far end within the header or a record,
+    /// the last event returned by a feed until more bytes are fed
+    NeedMoreData,
+    /// The end of the file given by its header is reached, the last event of the parser
+    /// (the bytes after the end are ignored)
+    Finished,
+    /// The header or a record is invalid, the last event of the parser
+    Error(Error),
+}
+
+/// Parser of a *.shp* which bytes are fed as they arrive,
+/// see the [module](index.html) documentation
+///
+/// The bytes of a record are kept until the record is complete, then parsed and dropped.
+pub struct StreamingParser {
+    /// The bytes fed that are not parsed yet
+    buffer: Vec<u8>,
+    /// Number of bytes of the file parsed
+    position: u64,
+    /// Length of the file in bytes, given by its header
+    file_length: Option<u64>,
+    /// Whether the parser returned its last event
+    is_done: bool,
+    /// Largest content length of the records, in bytes
+    max_record_size: Option<u64>,
+}
+
+impl Default for StreamingParser {
+    fn default() -> Self {
+        Self {
+            buffer: Vec::new(),
+            position: 0,
+            file_length: None,
+            is_done: false,
+            max_record_size: Some(DEFAULT_MAX_RECORD_SIZE),
+        }
+    }
+}
+
+impl StreamingParser {
+    /// Creates a parser expecting the first bytes of a *.shp*
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest content length, in bytes, of the records parsed,
+    /// `None` for no limit.
+    ///
+    /// A record whose header gives a larger content length is an `Error::RecordTooLarge`,
+    /// returned without waiting for its bytes, as they are kept in memory until
+    /// the record is complete. The limit is the one of the
+    /// [Reader](../reader/struct.Reader.html#method.max_record_size_bytes),
+    /// 256 MiB by default.
+    pub fn max_record_size_bytes(mut self, limit: Option<u64>) -> Self {
+        self.max_record_size = limit;
+        self
+    }
+
+    /// Feeds the next bytes of the file to the parser and returns what they completed:
+    /// the header, then the records in order.
+    ///
+    /// The events end with `NeedMoreData` if the file is not complete yet,
+    /// or with `Finished` or `Error` which are the last events of the parser:
+    /// feeding bytes after them returns no events.
+    pub fn feed(&mut self, bytes: &[u8]) -> Vec<ParsedEvent> {
+        if self.is_done {
+            return Vec::new();
+        }
+        self.buffer.extend_from_slice(bytes);
+        let mut events = Vec::<ParsedEvent>::new();
+        let mut start = 0;
+        while let Some((event, num_bytes)) = self.next_event(&self.buffer[start..]) {
+            start += num_bytes;
+            self.position += num_bytes as u64;
+            match event {
+                ParsedEvent::Header(ref header) => {
+                    self.file_length = Some(header.file_length.max(0) as u64 * 2)
+                }
+                ParsedEvent::Finished | ParsedEvent::Error(_) => self.is_done = true,
+                _ => {}
+            }
+            events.push(event);
+            if self.is_done {
+                self.buffer = Vec::new();
+                return events;
+            }
+        }
+        self.buffer.drain(..start);
+        events.push(ParsedEvent::NeedMoreData);
+        events
+    }
+
+    /// Ends the parsing, when there are no more bytes to feed.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::EmptyFile` if no bytes were fed
+    /// - `Error::TruncatedFile` if the bytes end before the end of the file given
+    ///   by its header (or before the end of the header)
+    ///
+    /// The error of an `Error` event is not returned again.
+    pub fn finish(self) -> Result<(), Error> {
+        if self.is_done {
+            return Ok(());
+        }
+        let actual_bytes = self.position + self.buffer.len() as u64;
+        match self.file_length {
+            None if actual_bytes == 0 => Err(Error::EmptyFile),
+            None => Err(Error::TruncatedFile {
+                expected_bytes: HEADER_SIZE as u64,
+                actual_bytes,
+            }),
+            Some(expected_bytes) => Err(Error::TruncatedFile {
+                expected_bytes,
+                actual_bytes,
+            }),
+        }
+    }
+
+    /// Returns the next event found at the start of the `bytes` not parsed yet,
+    /// with the number of bytes it takes, `None` if more bytes are needed
+    fn next_event(&self, bytes: &[u8]) -> Option<(ParsedEvent, usize)> {
+        let file_length = match self.file_length {
+            Some(file_length) => file_length,
+            None => {
+                let header = bytes.get(..HEADER_SIZE as usize)?;
+                let event = match Header::read_from(&mut &header[..]) {
+                    Ok(header) => ParsedEvent::Header(header),
+                    Err(e) => ParsedEvent::Error(e),
+                };
+                return Some((event, header.len()));
+            }
+        };
+        if self.position >= file_length {
+            return Some((ParsedEvent::Finished, 0));
+        }
+        let hdr = match RecordHeader::read_from(&mut bytes.get(..RecordHeader::SIZE)?) {
+            Ok(hdr) => hdr,
+            Err(e) => return Some((ParsedEvent::Error(e), 0)),
+        };
+        let record_size = hdr.record_size as i64 * 2;
+        let record_end = self.position as i64 + RecordHeader::SIZE as i64 + record_size;
+        // Waiting for the bytes of a record that goes past the end of the file
+        // would never end
+        if record_size < 0 || record_end > file_length as i64 {
+            return Some((ParsedEvent::Error(Error::InvalidShapeRecordSize), 0));
+        }
+        if let Err(e) = check_record_size(&hdr, self.max_record_size) {
+            return Some((ParsedEvent::Error(e), 0));
+        }
+        let content = bytes.get(RecordHeader::SIZE..RecordHeader::SIZE + record_size as usize)?;
+        let shape = record::read_record_content::<_, Shape>(
+            &mut &content[..],
+            content.len() as u64,
+            RecordSizeCheck::Exact,
+        );
+        let event = match shape {
+            Ok(shape) => ParsedEvent::Record(hdr.record_number, shape),
+            Err(e) => ParsedEvent::Error(e),
+        };
+        Some((event, RecordHeader::SIZE + content.len()))
+    }
+}
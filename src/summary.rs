@@ -0,0 +1,214 @@
+//! Module with the [FileSummary](struct.FileSummary.html) returned by
+//! [Reader::summary](../reader/struct.Reader.html#method.summary)
+use std::collections::HashMap;
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{LittleEndian, ReadBytesExt};
+
+use constants::{is_no_data, HEADER_SIZE};
+use header::Header;
+use reader::RecordHeaders;
+use record::io::{read_count, read_parts, read_xy_in_vec_of};
+use record::{BBox, Point, Polygon, RecordHeader, RingCounts};
+use {Error, GeometryClass, ShapeType};
+
+/// Overview of the content of a shapefile
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileSummary {
+    /// Number of records in the file
+    pub num_records: usize,
+    /// Number of records of each shape type (including `ShapeType::NullShape`)
+    pub shape_type_counts: HashMap<ShapeType, usize>,
+    /// Total number of points of all the records
+    pub num_points: usize,
+    /// Sizes (in bytes, without the record header) of the smallest
+    /// and the biggest records, `None` if the file has no records
+    pub record_size_range: Option<(usize, usize)>,
+    /// The bbox written in the header
+    pub header_bbox: BBox,
+    /// The bbox computed from the bbox of the records,
+    /// `None` if the file only has NullShapes
+    pub records_bbox: Option<BBox>,
+    /// Whether at least one record has a measure that is not NO_DATA
+    pub measures_used: bool,
+    /// Number of outer and inner rings of all the polygon records
+    /// (Polygon, PolygonM and PolygonZ), only counted by
+    /// [summary_with_rings](../reader/struct.Reader.html#method.summary_with_rings)
+    pub ring_counts: Option<RingCounts>,
+    /// Number of polygon records that have at least one inner ring (a hole), only counted by
+    /// [summary_with_rings](../reader/struct.Reader.html#method.summary_with_rings)
+    pub num_polygons_with_holes: Option<usize>,
+}
+
+impl FileSummary {
+    /// Returns the number of NullShape records
+    pub fn num_null_shapes(&self) -> usize {
+        self.shape_type_counts
+            .get(&ShapeType::NullShape)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Returns the differences between the bbox computed from the records and
+    /// the one of the header, as `[xmin, ymin, xmax, ymax]`
+    pub fn bbox_delta(&self) -> Option<[f64; 4]> {
+        self.records_bbox.map(|bbox| {
+            [
+                bbox.xmin - self.header_bbox.xmin,
+                bbox.ymin - self.header_bbox.ymin,
+                bbox.xmax - self.header_bbox.xmax,
+                bbox.ymax - self.header_bbox.ymax,
+            ]
+        })
+    }
+
+    /// Reads the summary from the `source`, starting after its header
+    ///
+    /// For each record only its header, its bbox, its number of parts and points,
+    /// and (until one is found that is not NO_DATA) its measures are read,
+    /// everything else is skipped. With `classify_rings`, the parts and x, y of the points
+    /// of polygons are read too, to count their rings.
+    ///
+    /// The records are checked as when they are read: a negative count of parts or points,
+    /// or counts that do not fit in the content of the record,
+    /// are an `Error::InvalidShapeRecordSize`.
+    pub(crate) fn read_from<T: Read + Seek>(
+        source: &mut T,
+        header: &Header,
+        classify_rings: bool,
+    ) -> Result<Self, Error> {
+        let mut summary = FileSummary {
+            num_records: 0,
+            shape_type_counts: HashMap::new(),
+            num_points: 0,
+            record_size_range: None,
+            header_bbox: BBox::new(
+                header.point_min[0],
+                header.point_min[1],
+                header.point_max[0],
+                header.point_max[1],
+            ),
+            records_bbox: None,
+            measures_used: false,
+            ring_counts: if classify_rings { Some(RingCounts::default()) } else { None },
+            num_polygons_with_holes: if classify_rings { Some(0) } else { None },
+        };
+
+        let file_length = header.file_length.max(0) as u64 * 2;
+        let mut records = RecordHeaders::new(&mut *source, file_length)?;
+        while let Some(record) = records.next() {
+            let (pos, _, record_end) = record?;
+            let content_start = pos + RecordHeader::SIZE as u64;
+            let content_size = (record_end - content_start) as usize;
+            let source = records.source();
+            let shape_type = ShapeType::read_from(source)?;
+
+            summary.num_records += 1;
+            *summary.shape_type_counts.entry(shape_type).or_insert(0) += 1;
+            summary.record_size_range = Some(match summary.record_size_range {
+                Some((min, max)) => (min.min(content_size), max.max(content_size)),
+                None => (content_size, content_size),
+            });
+
+            let has_z = shape_type.has_z() || shape_type == ShapeType::Multipatch;
+            let has_m = shape_type.has_m() || shape_type == ShapeType::Multipatch;
+            match shape_type.geometry_class() {
+                GeometryClass::Null => {}
+                GeometryClass::Point => {
+                    let x = source.read_f64::<LittleEndian>()?;
+                    let y = source.read_f64::<LittleEndian>()?;
+                    summary.add_bbox(BBox::new(x, y, x, y));
+                    summary.num_points += 1;
+                    // type, x, y and the optional z
+                    let m_offset = if has_z { 28 } else { 20 };
+                    if has_m && content_size >= m_offset + 8 {
+                        source.seek(SeekFrom::Start(content_start + m_offset as u64))?;
+                        let m = source.read_f64::<LittleEndian>()?;
+                        summary.measures_used |= !is_no_data(m);
+                    }
+                }
+                _ => {
+                    summary.add_bbox(BBox::read_from(&mut *source)?);
+                    let is_multipoint = !shape_type.is_multipart();
+                    let num_parts = if is_multipoint {
+                        0
+                    } else {
+                        read_count(source)? as usize
+                    };
+                    let num_points = read_count(source)? as usize;
+
+                    // type, bbox, number of parts and points, parts (and their types),
+                    // the points and the z, computed in u64: the counts are below 2^31
+                    let (parts, points) = (num_parts as u64, num_points as u64);
+                    let mut m_offset = 4 + 32 + 4 + 16 * points;
+                    if !is_multipoint {
+                        m_offset += 4 + 4 * parts;
+                    }
+                    if shape_type == ShapeType::Multipatch {
+                        m_offset += 4 * parts;
+                    }
+                    if has_z {
+                        m_offset += 16 + 8 * points;
+                    }
+                    if m_offset > content_size as u64 {
+                        return Err(Error::InvalidShapeRecordSize);
+                    }
+                    let m_offset = m_offset as usize;
+
+                    summary.num_points += num_points;
+                    if classify_rings && shape_type.geometry_class() == GeometryClass::Polygon {
+                        summary.add_rings_of(source, num_parts, num_points)?;
+                    }
+                    // The M range of the record is not always filled,
+                    // so the measures are read until one is not NO_DATA
+                    let m_size = 16 + 8 * num_points;
+                    if has_m && !summary.measures_used && content_size >= m_offset + m_size {
+                        source.seek(SeekFrom::Start(content_start + m_offset as u64 + 16))?;
+                        for _ in 0..num_points {
+                            if !is_no_data(source.read_f64::<LittleEndian>()?) {
+                                summary.measures_used = true;
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        source.seek(SeekFrom::Start(HEADER_SIZE as u64))?;
+        Ok(summary)
+    }
+
+    /// Reads the parts and the points of a polygon record, and adds the counts of its rings
+    fn add_rings_of<T: Read>(
+        &mut self,
+        source: &mut T,
+        num_parts: usize,
+        num_points: usize,
+    ) -> Result<(), Error> {
+        let parts = read_parts(source, num_parts as i32)?;
+        let points = read_xy_in_vec_of::<Point, T>(source, num_points as i32)?;
+        let counts = Polygon::new(points, parts).ring_classification();
+        if let Some(ref mut ring_counts) = self.ring_counts {
+            ring_counts.outer += counts.outer;
+            ring_counts.inner += counts.inner;
+        }
+        if let Some(ref mut num_polygons_with_holes) = self.num_polygons_with_holes {
+            if counts.inner > 0 {
+                *num_polygons_with_holes += 1;
+            }
+        }
+        Ok(())
+    }
+
+    fn add_bbox(&mut self, bbox: BBox) {
+        self.records_bbox = Some(match self.records_bbox {
+            Some(b) => BBox::new(
+                b.xmin.min(bbox.xmin),
+                b.ymin.min(bbox.ymin),
+                b.xmax.max(bbox.xmax),
+                b.ymax.max(bbox.ymax),
+            ),
+            None => bbox,
+        });
+    }
+}
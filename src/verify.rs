@@ -0,0 +1,477 @@
+//! Module with the [verify](fn.verify.html) function that checks the structure of a shapefile
+//!
+//! Unlike the [Reader](../reader/struct.Reader.html), which stops at the first error,
+//! `verify` goes through the whole file and lists every issue found in a
+//! [VerifyReport](struct.VerifyReport.html), which makes it a good first diagnostic
+//! to run on a file that does not read as expected.
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, Cursor, Read};
+use std::path::Path;
+
+use constants::is_no_data;
+use header::Header;
+use index::IndexTable;
+use reader::RecordHeaders;
+use record::poly::GenericPolygon;
+use record::traits::{HasM, HasXY, MultipartShape};
+use record::{is_outer_ring, is_parts_array_valid, BBox, EsriShape, PointZ};
+use record::{read_record_content, RecordSizeCheck};
+use sidecar::companion_path_of;
+use {Error, Shape, ShapeType};
+
+/// How bad an issue is
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Severity {
+    /// The file can be read, but some values are suspicious
+    /// or do not follow the specification
+    Warning,
+    /// The file (or the record) is malformed
+    Error,
+}
+
+/// An issue found in the file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    /// Index of the record the issue was found in (as used by
+    /// [read_nth_shape](../reader/struct.Reader.html#method.read_nth_shape)),
+    /// `None` if the issue concerns the whole file.
+    pub record: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        match self.record {
+            Some(record) => write!(f, "{} (record {}): {}", severity, record, self.message),
+            None => write!(f, "{}: {}", severity, self.message),
+        }
+    }
+}
+
+/// The result of the verification of a shapefile
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct VerifyReport {
+    /// Number of records found in the .shp
+    pub num_records: usize,
+    /// All the issues found, in the order they were found
+    pub issues: Vec<Issue>,
+}
+
+impl VerifyReport {
+    /// Returns true if no issue with the `Error` severity was found
+    pub fn is_valid(&self) -> bool {
+        self.errors().next().is_none()
+    }
+
+    /// Returns the issues with the `Error` severity
+    pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Error)
+    }
+
+    /// Returns the issues with the `Warning` severity
+    pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == Severity::Warning)
+    }
+
+    fn error<S: Into<String>>(&mut self, record: Option<usize>, message: S) {
+        self.issues.push(Issue {
+            record,
+            severity: Severity::Error,
+            message: message.into(),
+        });
+    }
+
+    fn warning<S: Into<String>>(&mut self, record: Option<usize>, message: S) {
+        self.issues.push(Issue {
+            record,
+            severity: Severity::Warning,
+            message: message.into(),
+        });
+    }
+}
+
+impl fmt::Display for VerifyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "{} records, {} errors, {} warnings",
+            self.num_records,
+            self.errors().count(),
+            self.warnings().count()
+        )?;
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks the structure of the shapefile at `path`, and its .shx and .dbf if they exist.
+///
+/// The checks done are:
+///
+/// - the file length in the header vs the actual file size
+/// - the record numbers and record sizes vs their content
+/// - the shape type of each record vs the one of the header
+/// - the parts of multipart shapes, the shapes as checked by
+///   [EsriShape::validate](../record/trait.EsriShape.html#method.validate)
+///   (the rings of polygons) and the orientation of polygons rings
+/// - the bbox of each record and of the header vs the points
+/// - NaN coordinates and NO_DATA used for x, y or z
+/// - the offsets and sizes in the .shx vs the records of the .shp
+/// - the number of records in the .dbf vs the number of shapes
+///
+/// # Errors
+///
+/// Only the errors that prevent the verification from being done are returned,
+/// (e.g. the file cannot be opened, or is not a shapefile), everything
+/// else is reported in the `VerifyReport`.
+///
+/// # Example
+///
+/// ```
+/// # fn main() -> Result<(), shapefile::Error> {
+/// let report = shapefile::verify("tests/data/line.shp")?;
+/// assert!(report.is_valid());
+/// println!("{}", report);
+/// # Ok(())
+/// # }
+/// ```
+pub fn verify<P: AsRef<Path>>(path: P) -> Result<VerifyReport, Error> {
+    let shp_path = path.as_ref();
+    let file_size = std::fs::metadata(shp_path)?.len();
+    let mut source = BufReader::new(File::open(shp_path)?);
+    let header = Header::read_from(&mut source)?;
+
+    let mut report = VerifyReport::default();
+    let file_length = header.file_length.max(0) as u64 * 2;
+    if header.file_length as i64 * 2 != file_size as i64 {
+        report.error(
+            None,
+            format!(
+                "header file length is {} bytes, but the file is {} bytes",
+                header.file_length as i64 * 2,
+                file_size
+            ),
+        );
+    }
+
+    let mut record_locations = Vec::<(u64, usize)>::new();
+    let mut records_bbox: Option<BBox> = None;
+    // The records are walked with the checks of the reader, up to the end given by the header
+    let mut records = RecordHeaders::new(&mut source, file_length)?;
+    while let Some(record) = records.next() {
+        let index = record_locations.len();
+        let (pos, hdr) = match record {
+            Ok((pos, hdr, _)) => (pos, hdr),
+            Err(e) => {
+                report.error(Some(index), e.to_string());
+                break;
+            }
+        };
+        if hdr.record_number != index as i32 + 1 {
+            report.warning(
+                Some(index),
+                format!("record number is {}, expected {}", hdr.record_number, index + 1),
+            );
+        }
+
+        let content_size = hdr.record_size as usize * 2;
+        let mut content = vec![0u8; content_size];
+        records.source().read_exact(&mut content)?;
+        match read_record_content::<_, Shape>(
+            &mut Cursor::new(content),
+            content_size as u64,
+            RecordSizeCheck::Exact,
+        ) {
+            Ok(shape) => {
+                check_shape(&shape, index, &header, &mut report);
+                if shape.shape_type() != ShapeType::NullShape {
+                    let bbox = shape.bbox();
+                    records_bbox = Some(match records_bbox {
+                        Some(b) => BBox::new(
+                            b.xmin.min(bbox.xmin),
+                            b.ymin.min(bbox.ymin),
+                            b.xmax.max(bbox.xmax),
+                            b.ymax.max(bbox.ymax),
+                        ),
+                        None => bbox,
+                    });
+                }
+            }
+            Err(e) => report.error(Some(index), format!("{}", e)),
+        }
+        record_locations.push((pos, content_size));
+    }
+    report.num_records = record_locations.len();
+
+    if let Some(bbox) = records_bbox {
+        let header_bbox = BBox::new(
+            header.point_min[0],
+            header.point_min[1],
+            header.point_max[0],
+            header.point_max[1],
+        );
+        if header_bbox != bbox {
+            report.warning(
+                None,
+                format!(
+                    "header bbox {:?} differs from the extent of the records {:?}",
+                    header_bbox, bbox
+                ),
+            );
+        }
+    }
+
+    let shx_path = companion_path_of(shp_path, "shx")?;
+    let dbf_path = companion_path_of(shp_path, "dbf")?;
+    check_index(shx_path.as_deref(), &record_locations, &mut report);
+    check_dbf(dbf_path.as_deref(), report.num_records, &mut report);
+    Ok(report)
+}
+
+fn check_index(
+    shx_path: Option<&Path>,
+    record_locations: &[(u64, usize)],
+    report: &mut VerifyReport,
+) {
+    let shx_path = match shx_path {
+        Some(shx_path) => shx_path,
+        None => {
+            report.warning(None, "no .shx file found");
+            return;
+        }
+    };
+    let index = match File::open(shx_path)
+        .map_err(Error::from)
+        .and_then(IndexTable::read_from)
+    {
+        Ok(index) => index,
+        Err(e) => {
+            report.error(None, format!("the .shx could not be read: {}", e));
+            return;
+        }
+    };
+    if index.len() != record_locations.len() {
+        report.error(
+            None,
+            format!(
+                "the .shx has {} entries, but the .shp has {} records",
+                index.len(),
+                record_locations.len()
+            ),
+        );
+    }
+    for (i, (entry, location)) in index.iter().zip(record_locations).enumerate() {
+        if entry != *location {
+            report.error(
+                Some(i),
+                format!(
+                    "the .shx gives offset {} and size {}, but the record is at offset {} with size {}",
+                    entry.0, entry.1, location.0, location.1
+                ),
+            );
+        }
+    }
+}
+
+fn check_dbf(dbf_path: Option<&Path>, num_records: usize, report: &mut VerifyReport) {
+    let dbf_path = match dbf_path {
+        Some(dbf_path) => dbf_path,
+        None => {
+            report.warning(None, "no .dbf file found");
+            return;
+        }
+    };
+    match dbase::Reader::from_path(dbf_path) {
+        Ok(reader) => {
+            let num_dbf_records = reader.header().num_records as usize;
+            if num_dbf_records != num_records {
+                report.error(
+                    None,
+                    format!(
+                        "the .dbf has {} records, but the .shp has {} records",
+                        num_dbf_records, num_records
+                    ),
+                );
+            }
+        }
+        Err(e) => report.error(None, format!("the .dbf could not be read: {}", Error::from(e))),
+    }
+}
+
+fn check_shape(shape: &Shape, index: usize, header: &Header, report: &mut VerifyReport) {
+    let record = Some(index);
+    let shape_type = shape.shape_type();
+    if shape_type != ShapeType::NullShape && shape_type != header.shape_type {
+        report.error(
+            record,
+            format!(
+                "shape type is {}, but the header says {}",
+                shape_type, header.shape_type
+            ),
+        );
+    }
+
+    let are_parts_valid = match shape {
+        Shape::NullShape(_) | Shape::Unknown { .. } => true,
+        Shape::Point(p) => {
+            check_xy(std::slice::from_ref(p), index, report);
+            true
+        }
+        Shape::PointM(p) => {
+            check_xy(std::slice::from_ref(p), index, report);
+            check_ms(std::slice::from_ref(p), index, report);
+            true
+        }
+        Shape::PointZ(p) => {
+            check_xy(std::slice::from_ref(p), index, report);
+            check_zs(std::slice::from_ref(p), index, report);
+            check_ms(std::slice::from_ref(p), index, report);
+            true
+        }
+        Shape::Multipoint(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            true
+        }
+        Shape::MultipointM(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_ms(&shp.points, index, report);
+            true
+        }
+        Shape::MultipointZ(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_zs(&shp.points, index, report);
+            check_ms(&shp.points, index, report);
+            true
+        }
+        Shape::Polyline(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::PolylineM(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_ms(&shp.points, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::PolylineZ(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_zs(&shp.points, index, report);
+            check_ms(&shp.points, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::Polygon(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::PolygonM(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_ms(&shp.points, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::PolygonZ(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_zs(&shp.points, index, report);
+            check_ms(&shp.points, index, report);
+            check_parts(shp, index, report)
+        }
+        Shape::Multipatch(shp) => {
+            check_xy_and_bbox(&shp.points, &shp.bbox, index, report);
+            check_zs(&shp.points, index, report);
+            check_ms(&shp.points, index, report);
+            check_parts(shp, index, report)
+        }
+    };
+    if !are_parts_valid {
+        return;
+    }
+    // The checks of the writers with validation (e.g. the rings of the polygons)
+    if let Err(e) = shape.validate() {
+        report.error(record, e.to_string());
+        return;
+    }
+    match shape {
+        Shape::Polygon(shp) => check_orientation(shp, index, report),
+        Shape::PolygonM(shp) => check_orientation(shp, index, report),
+        Shape::PolygonZ(shp) => check_orientation(shp, index, report),
+        _ => {}
+    }
+}
+
+fn check_parts<PointType, S>(shape: &S, index: usize, report: &mut VerifyReport) -> bool
+where
+    S: MultipartShape<PointType>,
+{
+    let parts = shape.parts_indices();
+    let is_valid = is_parts_array_valid(shape)
+        && parts[0] == 0
+        && parts.windows(2).all(|w| w[0] < w[1]);
+    if !is_valid {
+        report.error(Some(index), format!("invalid parts array: {:?}", parts));
+    }
+    is_valid
+}
+
+/// Checks the orientation of the first ring of the polygon, which rings are valid
+fn check_orientation<PointType>(
+    polygon: &GenericPolygon<PointType>,
+    index: usize,
+    report: &mut VerifyReport,
+) where
+    PointType: HasXY,
+{
+    if let Some(first_ring) = polygon.parts().next() {
+        if !is_outer_ring(first_ring) {
+            report.warning(
+                Some(index),
+                "first ring is counterclockwise (inner ring without an outer ring)",
+            );
+        }
+    }
+}
+
+fn check_xy<PointType: HasXY>(points: &[PointType], index: usize, report: &mut VerifyReport) {
+    if points.iter().any(|p| p.x().is_nan() || p.y().is_nan()) {
+        report.error(Some(index), "x or y coordinates contain NaN");
+    }
+    if points.iter().any(|p| is_no_data(p.x()) || is_no_data(p.y())) {
+        report.error(Some(index), "x or y coordinates contain NO_DATA");
+    }
+}
+
+fn check_xy_and_bbox<PointType: HasXY>(
+    points: &[PointType],
+    bbox: &BBox,
+    index: usize,
+    report: &mut VerifyReport,
+) {
+    check_xy(points, index, report);
+    if !points.is_empty() && BBox::from_points(points) != *bbox {
+        report.warning(Some(index), "bbox does not match the points");
+    }
+}
+
+fn check_zs(points: &[PointZ], index: usize, report: &mut VerifyReport) {
+    if points.iter().any(|p| p.z.is_nan()) {
+        report.error(Some(index), "z coordinates contain NaN");
+    }
+    if points.iter().any(|p| is_no_data(p.z)) {
+        report.error(Some(index), "z coordinates contain NO_DATA");
+    }
+}
+
+fn check_ms<PointType: HasM>(points: &[PointType], index: usize, report: &mut VerifyReport) {
+    if points.iter().any(|p| p.m().is_nan()) {
+        report.warning(Some(index), "measures contain NaN instead of NO_DATA");
+    }
+}
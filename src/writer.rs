@@ -4,16 +4,20 @@
 //! to ensure that both the .shp and .shx files are created.
 //! Then use its [writes_shapes](struct.Writer.html#method.write_shapes) method to write the files.
 
-use std::io::{BufWriter, Write};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
+use constants::{HEADER_SIZE, MAX_FILE_SIZE, VERSION};
 use header;
-use record::{EsriShape, RecordHeader};
+use record::{read_record_content, EsriShape, NullShape, RecordHeader, RecordSizeCheck};
 use std::fs::File;
 use std::path::Path;
-use Error;
+use {Error, Shape, ShapeType};
 
-use byteorder::{BigEndian, WriteBytesExt};
-use reader::ShapeIndex;
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
+use index::{ShapeIndex, INDEX_RECORD_SIZE};
+use progress::{Progress, ProgressReporter};
 
 fn f64_min(a: f64, b: f64) -> f64 {
     if a < b {
@@ -31,29 +35,227 @@ fn f64_max(a: f64, b: f64) -> f64 {
     }
 }
 
+/// Returns the header of the index file of a shapefile with `num_shapes` records
+pub(crate) fn index_header(shapefile_header: &header::Header, num_shapes: usize) -> header::Header {
+    let mut header = *shapefile_header;
+    let content_len = num_shapes * INDEX_RECORD_SIZE;
+    header.file_length = HEADER_SIZE + content_len as i32;
+    header.file_length /= 2;
+    header
+}
+
 fn write_index_file<T: Write>(
     mut dest: &mut T,
     shapefile_header: &header::Header,
     shapes_index: Vec<ShapeIndex>,
 ) -> Result<(), std::io::Error> {
-    let mut header = *shapefile_header;
-    let content_len = shapes_index.len() * 2 * std::mem::size_of::<i32>();
-    header.file_length = header::HEADER_SIZE + content_len as i32;
-    header.file_length /= 2;
-
+    let header = index_header(shapefile_header, shapes_index.len());
     header.write_to(&mut dest)?;
     for shape_index in shapes_index {
-        dest.write_i32::<BigEndian>(shape_index.offset)?;
-        dest.write_i32::<BigEndian>(shape_index.record_size)?;
+        write_index_entry(dest, &shape_index)?;
     }
     Ok(())
 }
 
+/// Writes the record header and the shape, returns the size of the record content (in 16-bit words)
+fn write_record<T: Write, S: EsriShape>(
+    dest: &mut T,
+    record_number: usize,
+    shape: S,
+) -> Result<i32, Error> {
+    //TODO Check record size < i32_max ?
+    let record_size = ((shape.size_in_bytes() + std::mem::size_of::<i32>()) / 2) as i32;
+    let rc_hdr = RecordHeader {
+        record_number: record_number as i32,
+        record_size,
+    };
+    rc_hdr.write_to(dest)?;
+    dest.write_i32::<LittleEndian>(shape.shape_type_code())?;
+    shape.write_to(dest)?;
+    Ok(record_size)
+}
+
+pub(crate) fn write_index_entry<T: Write>(
+    dest: &mut T,
+    shape_index: &ShapeIndex,
+) -> Result<(), std::io::Error> {
+    dest.write_i32::<BigEndian>(shape_index.offset)?;
+    dest.write_i32::<BigEndian>(shape_index.record_size)
+}
+
+/// The extent of the shapes written, used to build the header of the file
+struct ShapesExtent {
+    point_min: [f64; 3],
+    point_max: [f64; 3],
+    m_range: [f64; 2],
+}
+
+impl Default for ShapesExtent {
+    fn default() -> Self {
+        Self {
+            point_min: [f64::MAX, f64::MAX, f64::MAX],
+            point_max: [f64::MIN, f64::MIN, f64::MIN],
+            m_range: [f64::MAX, f64::MIN],
+        }
+    }
+}
+
+impl ShapesExtent {
+    /// Extends the extent with the one of the shape.
+    ///
+    /// NullShapes are ignored, other shapes must be of the `shapetype`.
+    fn add<S: EsriShape>(&mut self, shape: &S, shapetype: ShapeType) -> Result<(), Error> {
+        let actual = shape.shape_type();
+        if actual == ShapeType::NullShape {
+            return Ok(());
+        } else if actual != shapetype {
+            return Err(Error::MismatchShapeType {
+                requested: shapetype,
+                actual,
+            });
+        }
+        let bbox = shape.bbox();
+        let z_range = shape.z_extent().unwrap_or([0.0, 0.0]);
+        self.point_min[0] = f64_min(self.point_min[0], bbox.xmin);
+        self.point_min[1] = f64_min(self.point_min[1], bbox.ymin);
+        self.point_min[2] = f64_min(self.point_min[2], z_range[0]);
+
+        self.point_max[0] = f64_max(self.point_max[0], bbox.xmax);
+        self.point_max[1] = f64_max(self.point_max[1], bbox.ymax);
+        self.point_max[2] = f64_max(self.point_max[2], z_range[1]);
+
+        // Shapes without measures (or with only NO_DATA ones) have no M range
+        // to include in the range of the file
+        if let Some(s_m_range) = shape.m_extent() {
+            self.m_range[0] = f64_min(self.m_range[0], s_m_range[0]);
+            self.m_range[1] = f64_max(self.m_range[1], s_m_range[1]);
+        }
+        Ok(())
+    }
+
+    /// `file_length` is in 16-bit words
+    fn into_header(mut self, file_length: i32, shapetype: ShapeType) -> header::Header {
+        for i in 0..3 {
+            if self.point_min[i] == f64::MAX {
+                self.point_min[i] = 0.0;
+            }
+            if self.point_max[i] == f64::MIN {
+                self.point_max[i] = 0.0;
+            }
+        }
+        if self.m_range[0] > self.m_range[1] {
+            self.m_range = [0.0, 0.0];
+        }
+        header::Header {
+            file_length,
+            point_min: self.point_min,
+            point_max: self.point_max,
+            m_range: self.m_range,
+            shape_type: shapetype,
+            version: VERSION,
+        }
+    }
+}
+
+/// What a [Writer](struct.Writer.html) with validation enabled does with the shapes
+/// that are not valid
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum OnInvalid {
+    /// The write stops and returns the error of the shape validation (the default)
+    #[default]
+    Error,
+    /// The shape is written as a NullShape, so that the records of the .shx and .dbf
+    /// stay aligned with the ones of the .shp.
+    ///
+    /// The indices of the replaced shapes are given by
+    /// [null_substitutions](struct.Writer.html#method.null_substitutions)
+    WriteNull,
+}
+
+/// State of a writer to which shapes are written one by one
+/// (see [write_shape](struct.Writer.html#method.write_shape))
+struct StreamState {
+    /// Type of the first non-null shape
+    shapetype: Option<ShapeType>,
+    extent: ShapesExtent,
+    /// Position in the .shp, in bytes
+    pos: i32,
+    num_records: usize,
+    last_record_number: i32,
+}
+
+impl StreamState {
+    /// Extends the extent with the one of the shape, the type of the file being
+    /// the one of the first non-null shape
+    fn add_shape<S: EsriShape>(&mut self, shape: &S) -> Result<(), Error> {
+        let actual = shape.shape_type();
+        if actual != ShapeType::NullShape && self.shapetype.is_none() {
+            self.shapetype = Some(actual);
+        }
+        self.extent.add(shape, self.shapetype.unwrap_or(actual))
+    }
+
+    /// Updates the state after the record of `record_size` (in 16-bit words)
+    /// with the `number` was written
+    fn add_record(&mut self, number: i32, record_size: i32) {
+        self.pos += RecordHeader::SIZE as i32 + record_size * 2;
+        self.num_records += 1;
+        self.last_record_number = number;
+    }
+}
+
+/// Limit of the size of the .shp written by
+/// [write_all_from](struct.Writer.html#method.write_all_from)
+struct SizeLimit {
+    max_bytes: u64,
+    /// Set once the .shp is larger than 90% of the limit
+    approaching: Arc<AtomicBool>,
+}
+
+/// Returns the progress of a write of which the totals are not known
+fn streamed_progress(records_done: usize, bytes_done: u64) -> Progress {
+    Progress {
+        records_done,
+        records_total: None,
+        bytes_done,
+        bytes_total: None,
+    }
+}
+
+/// The destinations of a [Writer](struct.Writer.html), given back by
+/// [into_parts](struct.Writer.html#method.into_parts)
+pub struct WriterParts<T> {
+    /// Destination of the .shp
+    pub shp: T,
+    /// Destination of the .shx, if one was added
+    pub shx: Option<T>,
+    /// Destination of the .dbf, if one was added
+    pub dbf: Option<T>,
+}
+
+/// Function finishing the shapes written one by one,
+/// only known for destinations that implement `Seek`
+type FinishFn<T> = fn(&mut Writer<T>) -> Result<(), Error>;
+
 /// struct that writes the shapes
 pub struct Writer<T: Write> {
     pub dest: T,
     index_dest: Option<T>,
     dbase_dest: Option<T>,
+    progress: Option<ProgressReporter>,
+    on_invalid: Option<OnInvalid>,
+    null_substitutions: Vec<usize>,
+    snap_decimals: Option<u8>,
+    remove_repeated_points: bool,
+    ring_closing_tolerance: Option<f64>,
+    deterministic: bool,
+    flush_every: Option<usize>,
+    size_limit: Option<SizeLimit>,
+    stream: Option<StreamState>,
+    /// The function that finishes the stream, known once a shape was written
+    /// with [write_shape](#method.write_shape) (which requires the destinations to be `Seek`)
+    finish_fn: Option<FinishFn<T>>,
+    records_written: bool,
 }
 
 impl<T: Write> Writer<T> {
@@ -65,12 +267,258 @@ impl<T: Write> Writer<T> {
             dest,
             index_dest: None,
             dbase_dest: None,
+            progress: None,
+            on_invalid: None,
+            null_substitutions: Vec::new(),
+            snap_decimals: None,
+            remove_repeated_points: false,
+            ring_closing_tolerance: None,
+            deterministic: false,
+            flush_every: None,
+            size_limit: None,
+            stream: None,
+            finish_fn: None,
+            records_written: false,
+        }
+    }
+
+    /// Makes the writer round the x, y, z and m of the points to `decimals` decimals
+    /// before writing them (see [EsriShape::snap_to_precision](../record/trait.EsriShape.html#method.snap_to_precision)),
+    /// the bbox of the records and of the file are the ones of the rounded points.
+    ///
+    /// The rounding happens before the [validation](#method.with_validation).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline};
+    /// let polyline = Polyline::new(vec![Point::new(0.12345, 1.0), Point::new(2.0, 3.98765)], vec![0]);
+    /// let mut shp = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut shp).snap_precision(3);
+    /// writer.write_shapes(vec![polyline]).unwrap();
+    /// ```
+    pub fn snap_precision(mut self, decimals: u8) -> Self {
+        self.snap_decimals = Some(decimals);
+        self
+    }
+
+    /// Same as [snap_precision](#method.snap_precision), but the consecutive points of a part
+    /// that became equal after the rounding are removed.
+    ///
+    /// The first and last points of a ring are always kept, but a ring may end up with
+    /// less than 4 points, which is handled according to the [validation](#method.with_validation).
+    pub fn snap_precision_removing_repeated_points(mut self, decimals: u8) -> Self {
+        self.snap_decimals = Some(decimals);
+        self.remove_repeated_points = true;
+        self
+    }
+
+    /// Makes the writer close the rings of the polygons that are not closed before writing
+    /// them (see [EsriShape::close_rings](../record/trait.EsriShape.html#method.close_rings)):
+    /// a ring whose first and last points are within `tolerance` of each other
+    /// is closed by moving its last point onto its first one,
+    /// the other rings by adding their first point at their end.
+    ///
+    /// The rings are closed before the [rounding](#method.snap_precision)
+    /// and the [validation](#method.with_validation).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polygon};
+    /// let polygon = Polygon::new(
+    ///     vec![
+    ///         Point::new(0.0, 0.0),
+    ///         Point::new(0.0, 1.0),
+    ///         Point::new(1.0, 1.0),
+    ///         Point::new(1e-9, 0.0),
+    ///     ],
+    ///     vec![0],
+    /// );
+    /// let mut shp = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut shp).with_ring_closing(1e-6);
+    /// writer.write_shapes(vec![polygon]).unwrap();
+    /// ```
+    pub fn with_ring_closing(mut self, tolerance: f64) -> Self {
+        self.ring_closing_tolerance = Some(tolerance);
+        self
+    }
+
+    /// Makes the writer write the same bytes for the same shapes, which is useful
+    /// to compare the outputs of different runs or to check them in.
+    ///
+    /// The values that have more than one encoding are written in a single one
+    /// (see [EsriShape::canonicalize](../record/trait.EsriShape.html#method.canonicalize)):
+    /// negative zeros are written as positive zeros and the NaN measures, or measures lower than
+    /// [NO_DATA](../constants/constant.NO_DATA.html), are written as NO_DATA.
+    ///
+    /// The unused bytes of the headers are always written as zeros and the ranges
+    /// of the headers are `[0.0, 0.0]` when no shape defines them.
+    ///
+    /// The order of the fields of the .dbf depends on the `dbase` crate
+    /// and is not made deterministic.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{PointM, NO_DATA};
+    /// let mut first = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut first).deterministic(true);
+    /// writer.write_shapes(vec![PointM::new(-0.0, 1.0, f64::NAN)]).unwrap();
+    ///
+    /// let mut second = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut second).deterministic(true);
+    /// writer.write_shapes(vec![PointM::new(0.0, 1.0, NO_DATA)]).unwrap();
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.deterministic = deterministic;
+        self
+    }
+
+    /// Makes [write_all_from](#method.write_all_from) flush the .shp and .shx
+    /// every `num_records` records.
+    pub fn with_flush_every(mut self, num_records: usize) -> Self {
+        self.flush_every = Some(num_records.max(1));
+        self
+    }
+
+    /// Limits the size of the .shp written by [write_all_from](#method.write_all_from)
+    /// to `max_bytes` (which can not exceed the 2GB of the specification).
+    ///
+    /// `limit_approaching` is set once the .shp is larger than 90% of the limit,
+    /// so that the producers of the shapes can stop early.
+    pub fn with_size_limit(mut self, max_bytes: u64, limit_approaching: Arc<AtomicBool>) -> Self {
+        self.size_limit = Some(SizeLimit {
+            max_bytes: max_bytes.min(MAX_FILE_SIZE),
+            approaching: limit_approaching,
+        });
+        self
+    }
+
+    /// Closes the rings of, snaps and canonicalizes the shape according to the options
+    /// of the writer
+    fn prepare<S: EsriShape>(&self, shape: &mut S) {
+        if let Some(tolerance) = self.ring_closing_tolerance {
+            shape.close_rings(tolerance);
+        }
+        if let Some(decimals) = self.snap_decimals {
+            shape.snap_to_precision(decimals, self.remove_repeated_points);
+        }
+        if self.deterministic {
+            shape.canonicalize();
+        }
+    }
+
+    /// Makes the writer validate the shapes (see [EsriShape::validate](../record/trait.EsriShape.html#method.validate))
+    /// before writing them, the shapes that are not valid are handled according
+    /// to `on_invalid`.
+    ///
+    /// By default the shapes are not validated.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::writer::OnInvalid;
+    /// use shapefile::{Point, Polygon};
+    /// let unclosed = Polygon::new(
+    ///     vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(1.0, 1.0)],
+    ///     vec![0],
+    /// );
+    /// let mut shp = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut shp).with_validation(OnInvalid::WriteNull);
+    /// writer.write_shapes(vec![unclosed]).unwrap();
+    /// assert_eq!(writer.null_substitutions(), &[0]);
+    /// ```
+    pub fn with_validation(mut self, on_invalid: OnInvalid) -> Self {
+        self.on_invalid = Some(on_invalid);
+        self
+    }
+
+    /// Returns the indices of the shapes that were written as NullShapes by the last write,
+    /// because they were not valid and the writer was created
+    /// [with_validation(OnInvalid::WriteNull)](#method.with_validation)
+    pub fn null_substitutions(&self) -> &[usize] {
+        &self.null_substitutions
+    }
+
+    /// Returns whether the shape has to be written as a NullShape,
+    /// or the validation error if the shapes that are not valid are not replaced
+    fn must_write_null<S: EsriShape>(&self, shape: &S) -> Result<bool, Error> {
+        let on_invalid = match self.on_invalid {
+            Some(on_invalid) => on_invalid,
+            None => return Ok(false),
+        };
+        match (shape.validate(), on_invalid) {
+            (Ok(()), _) => Ok(false),
+            (Err(_), OnInvalid::WriteNull) => Ok(true),
+            (Err(error), OnInvalid::Error) => Err(error),
+        }
+    }
+
+    /// Makes the writer call the `callback` with the progress
+    /// of the write after each record.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::Point;
+    /// let mut shp = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut shp).with_progress(|progress| {
+    ///     println!("{} / {:?} records", progress.records_done, progress.records_total)
+    /// });
+    /// writer.write_shapes(vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0)]).unwrap();
+    /// ```
+    pub fn with_progress<F: FnMut(Progress) + Send + 'static>(self, callback: F) -> Self {
+        self.with_progress_every(1, callback)
+    }
+
+    /// Same as [with_progress](#method.with_progress), but the `callback`
+    /// is only called every `num_records` records (and after the last record)
+    pub fn with_progress_every<F: FnMut(Progress) + Send + 'static>(
+        mut self,
+        num_records: usize,
+        callback: F,
+    ) -> Self {
+        self.progress = Some(ProgressReporter::new(num_records, callback));
+        self
+    }
+
+    /// Sets the `callback` called with the progress of the write after each record,
+    /// like [with_progress](#method.with_progress) does.
+    pub fn set_progress_callback(&mut self, callback: Box<dyn FnMut(Progress) + Send>) {
+        self.progress = Some(ProgressReporter::new(1, callback));
+    }
+
+    /// `bytes_done` and `file_length` are in bytes
+    fn report_progress(
+        &mut self,
+        records_done: usize,
+        num_shapes: usize,
+        bytes_done: usize,
+        file_length: usize,
+    ) {
+        if let Some(ref mut progress) = self.progress {
+            progress.on_record(
+                Progress {
+                    records_done,
+                    records_total: Some(num_shapes),
+                    bytes_done: bytes_done as u64,
+                    bytes_total: Some(file_length as u64),
+                },
+                records_done == num_shapes,
+            );
         }
     }
 
     //TODO This method should move as calling it twice would produce a shitty file
     /// Writes the shapes to the file
     ///
+    /// Returns the numbers of the records of the shapes, in the order of the `shapes`.
+    /// The shapes written as NullShapes (see [with_validation](#method.with_validation))
+    /// keep their record number, their indices are given by
+    /// [null_substitutions](#method.null_substitutions).
+    ///
     /// # Examples
     ///
     /// ```
@@ -87,85 +535,173 @@ impl<T: Write> Writer<T> {
     /// let points = vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(2.0, 0.0)];
     /// let polyline = Polyline::new(points, vec![0]);
     ///
-    /// writer.write_shapes(vec![polyline]).unwrap();
+    /// let record_numbers = writer.write_shapes(vec![polyline]).unwrap();
+    /// assert_eq!(record_numbers, vec![1]);
     /// ```
-    pub fn write_shapes<S: EsriShape>(&mut self, shapes: Vec<S>) -> Result<(), Error> {
-        let mut file_length = header::HEADER_SIZE as usize;
-        for shape in &shapes {
-            file_length += 2 * std::mem::size_of::<i32>(); // record_header
-            file_length += std::mem::size_of::<i32>(); // shape_type
-            file_length += shape.size_in_bytes();
+    pub fn write_shapes<S: EsriShape>(&mut self, mut shapes: Vec<S>) -> Result<Vec<i32>, Error> {
+        for shape in &mut shapes {
+            self.prepare(shape);
         }
-        file_length /= 2; // file size is in 16bit words
-
-        if file_length > i32::max_value() as usize {
-            panic!("To big"); //TODO convert in proper error
+        self.null_substitutions.clear();
+        let mut is_null = Vec::<bool>::with_capacity(shapes.len());
+        for (i, shape) in shapes.iter().enumerate() {
+            let must_write_null = self.must_write_null(shape)?;
+            if must_write_null {
+                self.null_substitutions.push(i);
+            }
+            is_null.push(must_write_null);
         }
 
-        let mut point_min = [std::f64::MAX, std::f64::MAX, std::f64::MAX];
-        let mut point_max = [std::f64::MIN, std::f64::MIN, std::f64::MIN];
-        let mut m_range = [0.0, 0.0];
-        for shape in &shapes {
-            let bbox = shape.bbox();
-            let z_range = shape.z_range();
-            point_min[0] = f64_min(point_min[0], bbox.xmin);
-            point_min[1] = f64_min(point_min[1], bbox.ymin);
-            point_min[2] = f64_min(point_min[2], z_range[0]);
+        // The type of the file is the one of its first non-null shape
+        let shapetype = shapes
+            .iter()
+            .zip(&is_null)
+            .filter(|(_, is_null)| !**is_null)
+            .map(|(shape, _)| shape.shape_type())
+            .find(|t| *t != ShapeType::NullShape)
+            .unwrap_or_else(S::shapetype);
 
-            point_max[0] = f64_max(point_max[0], bbox.xmax);
-            point_max[1] = f64_max(point_max[1], bbox.ymax);
-            point_max[2] = f64_max(point_max[2], z_range[1]);
-
-            let s_m_range = shape.m_range();
-            m_range[0] = f64_min(m_range[0], s_m_range[0]);
-            m_range[1] = f64_max(m_range[1], s_m_range[1]);
+        let mut file_length = HEADER_SIZE as usize;
+        for (shape, is_null) in shapes.iter().zip(&is_null) {
+            file_length += 2 * std::mem::size_of::<i32>(); // record_header
+            file_length += std::mem::size_of::<i32>(); // shape_type
+            if !is_null {
+                file_length += shape.size_in_bytes();
+            }
         }
+        file_length /= 2; // file size is in 16bit words
 
-        if point_min[2] == std::f64::MAX {
-            point_min[2] = 0.0;
+        if file_length > i32::MAX as usize {
+            panic!("To big"); //TODO convert in proper error
         }
 
-        if point_max[2] == std::f64::MIN {
-            point_max[2] = 0.0;
+        let mut extent = ShapesExtent::default();
+        for (shape, _) in shapes.iter().zip(&is_null).filter(|(_, is_null)| !**is_null) {
+            extent.add(shape, shapetype)?;
         }
+        let header = extent.into_header(file_length as i32, shapetype);
 
-        let file_length = file_length as i32;
-        let shapetype = S::shapetype();
-        let header = header::Header {
-            file_length,
-            point_min,
-            point_max,
-            m_range,
-            shape_type: shapetype,
-            version: 1000,
-        };
-
-        let mut pos = header::HEADER_SIZE;
+        let mut pos = HEADER_SIZE;
         header.write_to(&mut self.dest)?;
-        let mut shapes_index = Vec::<ShapeIndex>::with_capacity(shapes.len());
-        for (i, shape) in shapes.into_iter().enumerate() {
-            //TODO Check record size < i32_max ?
-            let record_size = (shape.size_in_bytes() + std::mem::size_of::<i32>()) / 2;
-            let rc_hdr = RecordHeader {
-                record_number: i as i32,
-                record_size: record_size as i32,
+        let num_shapes = shapes.len();
+        let mut shapes_index = Vec::<ShapeIndex>::with_capacity(num_shapes);
+        let mut record_numbers = Vec::<i32>::with_capacity(num_shapes);
+        for (i, (shape, is_null)) in shapes.into_iter().zip(is_null).enumerate() {
+            let record_size = if is_null {
+                write_record(&mut self.dest, i + 1, NullShape)?
+            } else {
+                write_record(&mut self.dest, i + 1, shape)?
             };
-
             shapes_index.push(ShapeIndex {
-                offset: pos,
-                record_size: record_size as i32,
+                offset: pos / 2,
+                record_size,
             });
-
-            rc_hdr.write_to(&mut self.dest)?;
-            shapetype.write_to(&mut self.dest)?;
-            shape.write_to(&mut self.dest)?;
-            pos += (record_size * 2) as i32;
+            record_numbers.push(i as i32 + 1);
+            pos += RecordHeader::SIZE as i32 + record_size * 2;
+            self.report_progress(i + 1, num_shapes, pos as usize, file_length * 2);
         }
 
         if let Some(ref mut shx_dest) = &mut self.index_dest {
             write_index_file(shx_dest, &header, shapes_index)?;
         }
 
+        Ok(record_numbers)
+    }
+
+    /// Writes the shapes to the file without holding them all in memory
+    /// and without seeking in the destination.
+    ///
+    /// As the header, which is at the start of the file, depends on all the shapes,
+    /// the `shapes` are iterated twice: the first time to compute the header,
+    /// the second time to write them. So the `shapes` must be an iterator
+    /// that can be cloned and gives the same shapes each time.
+    ///
+    /// # Errors
+    ///
+    /// Returns `Error::ShapesChangedBetweenPasses` if the second iteration
+    /// does not give shapes of the same sizes as the first one,
+    /// in which case the file written is not valid.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use shapefile::{Point, Polyline};
+    /// // The shapes are created when needed
+    /// let polylines = (0..100).map(|i| {
+    ///     let i = i as f64;
+    ///     Polyline::new(vec![Point::new(i, i), Point::new(i + 1.0, i)], vec![0])
+    /// });
+    /// let mut shp = Vec::<u8>::new();
+    /// let mut writer = shapefile::Writer::new(&mut shp);
+    /// writer.write_shapes_streamed(polylines).unwrap();
+    /// ```
+    pub fn write_shapes_streamed<S, I>(&mut self, shapes: I) -> Result<(), Error>
+    where
+        S: EsriShape,
+        I: IntoIterator<Item = S> + Clone,
+    {
+        // The type of the file is the one of its first non-null shape
+        let mut shapetype: Option<ShapeType> = None;
+        let mut extent = ShapesExtent::default();
+        let mut num_shapes = 0usize;
+        let mut file_length = HEADER_SIZE as usize;
+        self.null_substitutions.clear();
+        for mut shape in shapes.clone() {
+            self.prepare(&mut shape);
+            if self.must_write_null(&shape)? {
+                self.null_substitutions.push(num_shapes);
+                file_length += RecordHeader::SIZE + std::mem::size_of::<i32>();
+                num_shapes += 1;
+                continue;
+            }
+            let actual = shape.shape_type();
+            if actual != ShapeType::NullShape && shapetype.is_none() {
+                shapetype = Some(actual);
+            }
+            extent.add(&shape, shapetype.unwrap_or(actual))?;
+            file_length += RecordHeader::SIZE + std::mem::size_of::<i32>() + shape.size_in_bytes();
+            num_shapes += 1;
+        }
+
+        let shapetype = shapetype.unwrap_or_else(S::shapetype);
+        let header = extent.into_header((file_length / 2) as i32, shapetype);
+        header.write_to(&mut self.dest)?;
+        if let Some(ref mut shx_dest) = &mut self.index_dest {
+            index_header(&header, num_shapes).write_to(shx_dest)?;
+        }
+
+        let mut pos = HEADER_SIZE as usize;
+        let mut num_written = 0usize;
+        for mut shape in shapes {
+            self.prepare(&mut shape);
+            let is_null = self.must_write_null(&shape)?;
+            let mut size = RecordHeader::SIZE + std::mem::size_of::<i32>();
+            if !is_null {
+                size += shape.size_in_bytes();
+            }
+            let was_null = self.null_substitutions.binary_search(&num_written).is_ok();
+            if num_written == num_shapes || pos + size > file_length || is_null != was_null {
+                return Err(Error::ShapesChangedBetweenPasses);
+            }
+            num_written += 1;
+            let record_size = if is_null {
+                write_record(&mut self.dest, num_written, NullShape)?
+            } else {
+                write_record(&mut self.dest, num_written, shape)?
+            };
+            if let Some(ref mut shx_dest) = &mut self.index_dest {
+                let shape_index = ShapeIndex {
+                    offset: (pos / 2) as i32,
+                    record_size,
+                };
+                write_index_entry(shx_dest, &shape_index)?;
+            }
+            pos += size;
+            self.report_progress(num_written, num_shapes, pos, file_length);
+        }
+        if num_written != num_shapes || pos != file_length {
+            return Err(Error::ShapesChangedBetweenPasses);
+        }
         Ok(())
     }
 
@@ -178,13 +714,69 @@ impl<T: Write> Writer<T> {
             panic!("The shapes and records vectors must have the same len");
         }
         self.write_shapes(shapes)?;
-        if let Some(dbase_dest) = self.dbase_dest {
+        self.write_records(records)
+    }
+
+    /// Writes the `records` to the .dbf destination, does nothing if the writer has none.
+    ///
+    /// All the records of the file are written at once: calling it a second time
+    /// returns `Error::RecordsAlreadyWritten` (instead of appending a second .dbf).
+    ///
+    /// Unlike [write_shapes_and_records](#method.write_shapes_and_records), the writer
+    /// is kept, so that its destinations can be taken back with
+    /// [into_parts](#method.into_parts).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use std::io::Cursor;
+    /// use shapefile::Point;
+    /// let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    /// writer.add_index_dest(Cursor::new(Vec::<u8>::new()));
+    /// writer.add_dbase_dest(Cursor::new(Vec::<u8>::new()));
+    ///
+    /// let mut record = dbase::Record::new();
+    /// let name = dbase::FieldValue::Character(Some("origin".to_string()));
+    /// record.insert("NAME".to_string(), name);
+    /// writer.write_shapes(vec![Point::new(0.0, 0.0)])?;
+    /// writer.write_records(vec![record])?;
+    ///
+    /// let parts = writer.into_parts()?;
+    /// let dbf = parts.dbf.unwrap().into_inner();
+    /// assert!(!dbf.is_empty());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_records(&mut self, records: Vec<dbase::Record>) -> Result<(), Error> {
+        if self.records_written {
+            return Err(Error::RecordsAlreadyWritten);
+        }
+        if let Some(dbase_dest) = self.dbase_dest.take() {
+            self.records_written = true;
             let dbase_writer = dbase::Writer::new(dbase_dest);
-            dbase_writer.write(&records)?;
+            self.dbase_dest = Some(dbase_writer.write(&records)?);
         }
         Ok(())
     }
 
+    /// Returns the destinations of the .shp, .shx and .dbf,
+    /// e.g. to get the buffers of a shapefile written in memory (which can be read with
+    /// [Reader::from_parts_bytes](../reader/struct.Reader.html#method.from_parts_bytes)).
+    ///
+    /// The shapes written with [write_shape](#method.write_shape) are
+    /// [finished](#method.finish) first, so that the headers are written.
+    pub fn into_parts(mut self) -> Result<WriterParts<T>, Error> {
+        if let Some(finish) = self.finish_fn {
+            finish(&mut self)?;
+        }
+        Ok(WriterParts {
+            shp: self.dest,
+            shx: self.index_dest,
+            dbf: self.dbase_dest,
+        })
+    }
+
     /// Adds dest as the destination where the index file will be written
     pub fn add_index_dest(&mut self, dest: T) {
         self.index_dest = Some(dest);
@@ -196,6 +788,333 @@ impl<T: Write> Writer<T> {
     }
 }
 
+impl<T: Write + Seek> Writer<T> {
+    /// Writes one shape, numbering its record after the previous one
+    /// (the first record is number 1), and returns the number of its record.
+    ///
+    /// The shapes written with this method are written as they come, the headers
+    /// of the .shp and .shx are written by [finish](#method.finish) which must be
+    /// called after the last shape. Only the .shp and .shx are written.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::Point;
+    /// let mut writer = shapefile::Writer::from_path(std::env::temp_dir().join("one_by_one.shp"))?;
+    /// for i in 0..10 {
+    ///     writer.write_shape(Point::new(i as f64, 0.0))?;
+    /// }
+    /// writer.finish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_shape<S: EsriShape>(&mut self, shape: S) -> Result<i32, Error> {
+        let number = self
+            .stream
+            .as_ref()
+            .map_or(1, |stream| stream.last_record_number + 1);
+        self.write_shape_with_number(number, shape)
+    }
+
+    /// Same as [write_shape](#method.write_shape), but the record is given the `number`
+    /// instead of the number following the one of the previous record.
+    ///
+    /// This allows keeping the record numbers of the original file when writing
+    /// some of its shapes. The specification expects the records to be numbered
+    /// from 1 to N, so other readers may reject, or renumber, files with gaps
+    /// in their record numbers. The index of the records (in the .shx) is their position
+    /// in the file, not their number.
+    ///
+    /// Returns the `number`, also when the shape is written as a NullShape
+    /// (see [with_validation](#method.with_validation)).
+    ///
+    /// # Errors
+    ///
+    /// `Error::RecordNumberNotIncreasing` if the `number` is not greater than
+    /// the number of the previous record (or than 0 for the first record),
+    /// in which case nothing is written.
+    pub fn write_shape_with_number<S: EsriShape>(
+        &mut self,
+        number: i32,
+        mut shape: S,
+    ) -> Result<i32, Error> {
+        let last_record_number = self.stream.as_ref().map_or(0, |s| s.last_record_number);
+        if number <= last_record_number {
+            return Err(Error::RecordNumberNotIncreasing {
+                previous: last_record_number,
+                number,
+            });
+        }
+
+        self.prepare(&mut shape);
+        let is_null = self.must_write_null(&shape)?;
+        let mut stream = self.start_stream()?;
+        let result = self.write_streamed_record(&mut stream, number, shape, is_null);
+        self.stream = Some(stream);
+        result.map(|_| number)
+    }
+
+    /// Writes a record which `content` is given as it is in a file (starting with
+    /// the shape type), numbering it after the previous one like
+    /// [write_shape](#method.write_shape) does, and returns the number of the record.
+    ///
+    /// The record header, the .shx entry and the headers of the files are written
+    /// by the writer, allowing to copy records (e.g. read with
+    /// [iter_raw_records](../reader/struct.Reader.html#method.iter_raw_records))
+    /// without reading their shapes. The content is written as it is:
+    /// the writer does not change it (as it would with
+    /// [snap_precision](#method.snap_precision) for example).
+    ///
+    /// The shape of a record with a known shape type is still read, to extend the
+    /// bbox of the file; a record with an unknown shape type is not part of the bbox.
+    ///
+    /// # Errors
+    ///
+    /// - `Error::InvalidShapeRecordSize` if the `content` is shorter than a shape type,
+    ///   has an odd length, or is not the size of its shape
+    /// - the errors of reading the shape of a known shape type
+    /// - `Error::MismatchShapeType` if the shape type is not the one of the previous records
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use std::io::Cursor;
+    /// let reader = shapefile::Reader::from_path("tests/data/multipatch.shp")?;
+    /// let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    /// for raw_record in reader.iter_raw_records() {
+    ///     let (_, _, content) = raw_record?;
+    ///     writer.write_raw_record(&content)?;
+    /// }
+    /// writer.finish()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_raw_record(&mut self, content: &[u8]) -> Result<i32, Error> {
+        if content.len() < std::mem::size_of::<i32>() || content.len() % 2 != 0 {
+            return Err(Error::InvalidShapeRecordSize);
+        }
+        let code = (&content[..4]).read_i32::<LittleEndian>()?;
+        let shape = match ShapeType::from(code) {
+            Some(_) => Some(read_record_content::<_, Shape>(
+                &mut &content[..],
+                content.len() as u64,
+                RecordSizeCheck::Exact,
+            )?),
+            None => None,
+        };
+        let mut stream = self.start_stream()?;
+        let number = stream.last_record_number + 1;
+        let result = self.write_raw_streamed_record(&mut stream, number, content, shape);
+        self.stream = Some(stream);
+        result.map(|_| number)
+    }
+
+    /// Returns the state of the records written one by one, writing the placeholders
+    /// of the headers before the first record.
+    ///
+    /// The state must be given back to `self.stream`.
+    fn start_stream(&mut self) -> Result<StreamState, Error> {
+        if let Some(stream) = self.stream.take() {
+            return Ok(stream);
+        }
+        // The headers are written again by finish
+        let placeholder = [0u8; HEADER_SIZE as usize];
+        self.dest.write_all(&placeholder)?;
+        if let Some(ref mut shx_dest) = self.index_dest {
+            shx_dest.write_all(&placeholder)?;
+        }
+        self.null_substitutions.clear();
+        self.finish_fn = Some(Self::finish);
+        Ok(StreamState {
+            shapetype: None,
+            extent: ShapesExtent::default(),
+            pos: HEADER_SIZE,
+            num_records: 0,
+            last_record_number: 0,
+        })
+    }
+
+    fn write_raw_streamed_record(
+        &mut self,
+        stream: &mut StreamState,
+        number: i32,
+        content: &[u8],
+        shape: Option<Shape>,
+    ) -> Result<(), Error> {
+        if let Some(ref shape) = shape {
+            stream.add_shape(shape)?;
+        }
+        let record_size = (content.len() / 2) as i32;
+        RecordHeader {
+            record_number: number,
+            record_size,
+        }
+        .write_to(&mut self.dest)?;
+        self.dest.write_all(content)?;
+        self.write_streamed_index_entry(stream, record_size)?;
+        stream.add_record(number, record_size);
+        Ok(())
+    }
+
+    /// Writes the .shx entry of the record of `record_size` (in 16-bit words)
+    /// written at the current position
+    fn write_streamed_index_entry(
+        &mut self,
+        stream: &StreamState,
+        record_size: i32,
+    ) -> Result<(), Error> {
+        if let Some(ref mut shx_dest) = self.index_dest {
+            let shape_index = ShapeIndex {
+                offset: stream.pos / 2,
+                record_size,
+            };
+            write_index_entry(shx_dest, &shape_index)?;
+        }
+        Ok(())
+    }
+
+    /// Writes all the shapes given by `shapes` (for example the receiver of a channel),
+    /// as they come, then writes the headers (see [finish](#method.finish)).
+    ///
+    /// The .shp and .shx are flushed as configured by [with_flush_every](#method.with_flush_every),
+    /// and the progress is reported as configured by [with_progress](#method.with_progress)
+    /// (the total number of records is not known).
+    ///
+    /// # Errors
+    ///
+    /// `Error::FileSizeLimitReached` if a shape would make the .shp larger than the limit set by
+    /// [with_size_limit](#method.with_size_limit) (or than 2GB), the shapes written before it
+    /// form a valid file and the remaining shapes are not consumed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # fn main() -> Result<(), shapefile::Error> {
+    /// use shapefile::Point;
+    /// let (sender, receiver) = std::sync::mpsc::sync_channel(16);
+    /// let producer = std::thread::spawn(move || {
+    ///     for i in 0..100 {
+    ///         sender.send(Point::new(i as f64, 0.0)).unwrap();
+    ///     }
+    /// });
+    /// let mut writer = shapefile::Writer::from_path(std::env::temp_dir().join("channel.shp"))?;
+    /// writer.write_all_from(receiver)?;
+    /// producer.join().unwrap();
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_all_from<S, I>(&mut self, shapes: I) -> Result<(), Error>
+    where
+        S: EsriShape,
+        I: IntoIterator<Item = S>,
+    {
+        let max_bytes = self.size_limit.as_ref().map_or(MAX_FILE_SIZE, |l| l.max_bytes);
+        let mut num_written = 0usize;
+        for shape in shapes {
+            let pos = self.stream.as_ref().map_or(HEADER_SIZE, |s| s.pos) as u64;
+            let size = RecordHeader::SIZE + std::mem::size_of::<i32>() + shape.size_in_bytes();
+            if pos + size as u64 > max_bytes {
+                if let Some(ref limit) = self.size_limit {
+                    limit.approaching.store(true, Ordering::SeqCst);
+                }
+                self.finish_stream::<S>()?;
+                return Err(Error::FileSizeLimitReached {
+                    limit: max_bytes,
+                    records_written: num_written,
+                });
+            }
+            self.write_shape(shape)?;
+            num_written += 1;
+
+            let pos = self.stream.as_ref().map_or(HEADER_SIZE, |s| s.pos) as u64;
+            if let Some(ref limit) = self.size_limit {
+                if pos * 10 >= limit.max_bytes * 9 {
+                    limit.approaching.store(true, Ordering::SeqCst);
+                }
+            }
+            if self.flush_every.map_or(false, |n| num_written % n == 0) {
+                self.dest.flush()?;
+                if let Some(ref mut shx_dest) = self.index_dest {
+                    shx_dest.flush()?;
+                }
+            }
+            if let Some(ref mut progress) = self.progress {
+                progress.on_record(streamed_progress(num_written, pos), false);
+            }
+        }
+        let pos = self.stream.as_ref().map_or(HEADER_SIZE, |s| s.pos) as u64;
+        self.finish_stream::<S>()?;
+        if let Some(ref mut progress) = self.progress {
+            progress.on_finished(streamed_progress(num_written, pos));
+        }
+        Ok(())
+    }
+
+    /// Same as [finish](#method.finish), but writes an empty file if no shape was written
+    fn finish_stream<S: EsriShape>(&mut self) -> Result<(), Error> {
+        if self.stream.is_none() {
+            return self.write_shapes(Vec::<S>::new()).map(|_| ());
+        }
+        self.finish()
+    }
+
+    fn write_streamed_record<S: EsriShape>(
+        &mut self,
+        stream: &mut StreamState,
+        number: i32,
+        shape: S,
+        is_null: bool,
+    ) -> Result<(), Error> {
+        let record_size = if is_null {
+            self.null_substitutions.push(stream.num_records);
+            write_record(&mut self.dest, number as usize, NullShape)?
+        } else {
+            stream.add_shape(&shape)?;
+            write_record(&mut self.dest, number as usize, shape)?
+        };
+        self.write_streamed_index_entry(stream, record_size)?;
+        stream.add_record(number, record_size);
+        Ok(())
+    }
+
+    /// Same as [finish](#method.finish), but the type of the file is `shapetype`
+    /// if no non-null shape was written, and the headers of an empty file
+    /// are written if no shape was written
+    pub(crate) fn finish_as(&mut self, shapetype: ShapeType) -> Result<(), Error> {
+        let mut stream = self.start_stream()?;
+        stream.shapetype = stream.shapetype.or(Some(shapetype));
+        self.stream = Some(stream);
+        self.finish()
+    }
+
+    /// Writes the headers of the .shp and .shx once all the shapes were written
+    /// with [write_shape](#method.write_shape), and flushes them.
+    ///
+    /// Does nothing if no shape was written with `write_shape`.
+    /// The writer can then be used to write another file.
+    pub fn finish(&mut self) -> Result<(), Error> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => return Ok(()),
+        };
+        let shapetype = stream.shapetype.unwrap_or(ShapeType::NullShape);
+        let header = stream.extent.into_header(stream.pos / 2, shapetype);
+        self.dest.seek(SeekFrom::Start(0))?;
+        header.write_to(&mut self.dest)?;
+        self.dest.seek(SeekFrom::Start(stream.pos as u64))?;
+        self.dest.flush()?;
+        if let Some(ref mut shx_dest) = self.index_dest {
+            shx_dest.seek(SeekFrom::Start(0))?;
+            index_header(&header, stream.num_records).write_to(shx_dest)?;
+            shx_dest.seek(SeekFrom::End(0))?;
+            shx_dest.flush()?;
+        }
+        Ok(())
+    }
+}
+
 impl Writer<BufWriter<File>> {
     /// Creates a new writer from a path.
     /// Creates both a .shp and .shx files
@@ -209,7 +1128,7 @@ impl Writer<BufWriter<File>> {
     pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
         let shp_path = path.as_ref().to_path_buf();
         let shx_path = shp_path.with_extension("shx");
-        let dbf_path = shp_path.with_extension("dfb");
+        let dbf_path = shp_path.with_extension("dbf");
 
         let shp_file = BufWriter::new(File::create(shp_path)?);
         let shx_file = BufWriter::new(File::create(shx_path)?);
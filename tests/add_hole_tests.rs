@@ -0,0 +1,142 @@
+extern crate shapefile;
+
+use shapefile::record::BBox;
+use shapefile::{Error, Point, PointZ, Polygon, PolygonZ};
+
+/// Returns the closed clockwise square of the given lower left corner and size
+fn square(xmin: f64, ymin: f64, size: f64) -> Vec<Point> {
+    vec![
+        Point::new(xmin, ymin),
+        Point::new(xmin, ymin + size),
+        Point::new(xmin + size, ymin + size),
+        Point::new(xmin + size, ymin),
+        Point::new(xmin, ymin),
+    ]
+}
+
+/// Two parcels, the first one having a hole, with normalized rings
+fn parcels() -> Polygon {
+    let mut points = square(0.0, 0.0, 10.0);
+    points.extend(square(1.0, 1.0, 2.0).into_iter().rev());
+    points.extend(square(20.0, 0.0, 10.0));
+    Polygon::new(points, vec![0, 5, 10])
+}
+
+#[test]
+fn contained_hole_is_added_after_the_holes_of_its_outer_ring() {
+    let mut polygon = parcels();
+    // Clockwise and not closed
+    let footprint = vec![
+        Point::new(5.0, 5.0),
+        Point::new(5.0, 8.0),
+        Point::new(8.0, 8.0),
+        Point::new(8.0, 5.0),
+    ];
+    polygon.add_hole(footprint).unwrap();
+
+    assert_eq!(polygon.parts, vec![0, 5, 10, 15]);
+    assert_eq!(polygon.points.len(), 20);
+    let hole = &polygon.points[10..15];
+    assert_eq!(hole[0], hole[4]);
+    assert_eq!(hole[1], Point::new(8.0, 5.0));
+    assert_eq!(polygon.outer_rings().count(), 2);
+    assert_eq!(polygon.inner_rings().count(), 2);
+    assert_eq!(&polygon.points[15..], &square(20.0, 0.0, 10.0)[..]);
+    assert_eq!(polygon.distance_to_point(6.0, 6.0), 1.0);
+    polygon.validate_rings().unwrap();
+
+    // A counterclockwise hole in the last parcel is added at the end, as is
+    let footprint = square(22.0, 2.0, 3.0).into_iter().rev().collect::<Vec<_>>();
+    polygon.add_hole(footprint.clone()).unwrap();
+    assert_eq!(polygon.parts, vec![0, 5, 10, 15, 20]);
+    assert_eq!(&polygon.points[20..], &footprint[..]);
+    assert_eq!(polygon.bbox, BBox::new(0.0, 0.0, 30.0, 10.0));
+}
+
+#[test]
+fn hole_goes_in_the_smallest_outer_ring() {
+    // An island in the hole of a parcel
+    let mut points = square(0.0, 0.0, 10.0);
+    points.extend(square(2.0, 2.0, 6.0).into_iter().rev());
+    points.extend(square(3.0, 3.0, 4.0));
+    let mut polygon = Polygon::new(points, vec![0, 5, 10]);
+
+    polygon.add_hole(square(4.0, 4.0, 1.0)).unwrap();
+    assert_eq!(polygon.parts, vec![0, 5, 10, 15]);
+    assert_eq!(polygon.points[15], Point::new(4.0, 4.0));
+}
+
+#[test]
+fn z_and_m_of_the_hole_are_kept() {
+    let outer = square(0.0, 0.0, 10.0)
+        .into_iter()
+        .map(|point| PointZ::new(point.x, point.y, 1.0, 2.0))
+        .collect::<Vec<_>>();
+    let mut polygon = PolygonZ::new(outer, vec![0]);
+    let hole = vec![
+        PointZ::new(1.0, 1.0, 5.0, 6.0),
+        PointZ::new(2.0, 1.0, 5.0, 6.0),
+        PointZ::new(2.0, 2.0, 5.0, 6.0),
+        PointZ::new(1.0, 1.0, 5.0, 6.0),
+    ];
+    polygon.add_hole(hole.clone()).unwrap();
+    assert_eq!(&polygon.points[5..], &hole[..]);
+    assert_eq!(polygon.z_range(), [1.0, 5.0]);
+}
+
+#[test]
+fn outside_hole_is_an_error() {
+    let mut polygon = parcels();
+    match polygon.add_hole(square(12.0, 2.0, 3.0)) {
+        Err(Error::HoleNotContained) => {}
+        _ => panic!("expected a HoleNotContained error"),
+    }
+    // A ring around the whole polygon
+    match polygon.add_hole(square(-5.0, -5.0, 50.0)) {
+        Err(Error::HoleNotContained) => {}
+        _ => panic!("expected a HoleNotContained error"),
+    }
+    assert_eq!(polygon.parts, vec![0, 5, 10]);
+    assert_eq!(polygon.points.len(), 15);
+}
+
+#[test]
+fn straddling_hole_is_an_error() {
+    let mut polygon = parcels();
+    match polygon.add_hole(square(8.0, 4.0, 4.0)) {
+        Err(Error::HoleCrossesRing(0)) => {}
+        _ => panic!("expected a HoleCrossesRing error"),
+    }
+    // Spanning both parcels, with all its points inside them
+    let bridge = vec![
+        Point::new(8.0, 4.0),
+        Point::new(8.0, 6.0),
+        Point::new(22.0, 6.0),
+        Point::new(22.0, 4.0),
+    ];
+    match polygon.add_hole(bridge) {
+        Err(Error::HoleCrossesRing(0)) => {}
+        _ => panic!("expected a HoleCrossesRing error"),
+    }
+    // Touching the outer ring
+    match polygon.add_hole(square(5.0, 5.0, 5.0)) {
+        Err(Error::HoleCrossesRing(0)) => {}
+        _ => panic!("expected a HoleCrossesRing error"),
+    }
+    assert_eq!(polygon.parts, vec![0, 5, 10]);
+}
+
+#[test]
+fn degenerate_hole_is_an_error() {
+    let mut polygon = parcels();
+    match polygon.add_hole(vec![Point::new(5.0, 5.0), Point::new(6.0, 6.0)]) {
+        Err(Error::TooFewPointsInRing(2)) => {}
+        _ => panic!("expected a TooFewPointsInRing error"),
+    }
+
+    let mut malformed = Polygon::new(square(0.0, 0.0, 10.0), vec![0, 7]);
+    match malformed.add_hole(square(1.0, 1.0, 1.0)) {
+        Err(Error::MalformedShape) => {}
+        _ => panic!("expected a MalformedShape error"),
+    }
+}
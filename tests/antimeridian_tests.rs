@@ -0,0 +1,132 @@
+extern crate shapefile;
+
+use shapefile::record::traits::MultipartShape;
+use shapefile::{Point, PointZ, Polygon, PolygonZ, Polyline, Shape, NO_DATA};
+
+/// Area of the ring, positive if clockwise
+fn signed_area(ring: &[Point]) -> f64 {
+    ring.windows(2)
+        .map(|pts| (pts[1].x - pts[0].x) * (pts[1].y + pts[0].y))
+        .sum::<f64>()
+        / 2.0
+}
+
+fn assert_rings_within_range(polygon: &Polygon) {
+    for ring in polygon.parts() {
+        assert_eq!(ring.first(), ring.last());
+        assert!(ring.iter().all(|p| -180.0 <= p.x && p.x <= 180.0));
+    }
+}
+
+/// Polygon around Fiji (clockwise), with a hole straddling the antimeridian too
+fn fiji_polygon() -> Polygon {
+    let outer = [(175.0, -20.0), (175.0, -15.0), (-178.0, -15.0), (-178.0, -20.0)];
+    let inner = [(179.0, -18.0), (-179.0, -18.0), (-179.0, -17.0), (179.0, -17.0)];
+    let mut points = Vec::<Point>::new();
+    for ring in &[&outer, &inner] {
+        points.extend(ring.iter().map(|&(x, y)| Point::new(x, y)));
+        points.push(Point::new(ring[0].0, ring[0].1));
+    }
+    Polygon::new(points, vec![0, 5])
+}
+
+#[test]
+fn split_fiji_polygon() {
+    let polygon = fiji_polygon();
+    let split = Shape::Polygon(polygon).split_at_antimeridian().unwrap();
+    let split = split.as_polygon().unwrap();
+
+    assert_eq!(split.parts.len(), 4);
+    assert_rings_within_range(split);
+    let counts = split.ring_classification();
+    assert_eq!((counts.outer, counts.inner), (2, 2));
+    assert_eq!(split.bbox.xmin, -180.0);
+    assert_eq!(split.bbox.xmax, 180.0);
+
+    let area = split.parts().map(signed_area).sum::<f64>();
+    assert!((area - (7.0 * 5.0 - 2.0 * 1.0)).abs() < 1e-9);
+}
+
+#[test]
+fn split_ring_crossing_four_times() {
+    // Bar on the east side, with two prongs on the west side
+    let coordinates = [
+        (170.0, -10.0),
+        (170.0, 10.0),
+        (-170.0, 10.0),
+        (-170.0, 5.0),
+        (175.0, 5.0),
+        (175.0, -5.0),
+        (-170.0, -5.0),
+        (-170.0, -10.0),
+        (170.0, -10.0),
+    ];
+    let points = coordinates.iter().map(|&(x, y)| Point::new(x, y)).collect();
+    let split = Shape::Polygon(Polygon::new(points, vec![0]))
+        .split_at_antimeridian()
+        .unwrap();
+    let split = split.as_polygon().unwrap();
+
+    assert_eq!(split.parts.len(), 3);
+    assert_rings_within_range(split);
+    assert!(split.parts().all(|ring| signed_area(ring) > 0.0));
+    let area = split.parts().map(signed_area).sum::<f64>();
+    assert!((area - (20.0 * 20.0 - 15.0 * 10.0)).abs() < 1e-9);
+}
+
+#[test]
+fn split_polygon_z_interpolates_z() {
+    let points = vec![
+        PointZ::new(170.0, 0.0, 0.0, NO_DATA),
+        PointZ::new(170.0, 10.0, 0.0, NO_DATA),
+        PointZ::new(-170.0, 10.0, 20.0, NO_DATA),
+        PointZ::new(-170.0, 0.0, 20.0, NO_DATA),
+        PointZ::new(170.0, 0.0, 0.0, NO_DATA),
+    ];
+    let split = Shape::PolygonZ(PolygonZ::new(points, vec![0]))
+        .split_at_antimeridian()
+        .unwrap();
+    let split = split.as_polygon_z().unwrap();
+
+    assert_eq!(split.parts.len(), 2);
+    let on_antimeridian = split
+        .points
+        .iter()
+        .filter(|p| p.x.abs() == 180.0)
+        .collect::<Vec<&PointZ>>();
+    assert_eq!(on_antimeridian.len(), 6);
+    assert!(on_antimeridian.iter().all(|p| p.z == 10.0 && p.m == NO_DATA));
+}
+
+#[test]
+fn split_is_noop_when_not_crossing() {
+    let points = vec![Point::new(-179.0, 1.0), Point::new(0.0, 2.0), Point::new(179.0, 3.0)];
+    let polyline = Polyline::new(points.clone(), vec![0]);
+    let split = Shape::Polyline(polyline).split_at_antimeridian().unwrap();
+    let split = split.as_polyline().unwrap();
+    assert_eq!(split.points, points);
+    assert_eq!(split.parts, vec![0]);
+
+    let point = Shape::Point(Point::new(180.0, 0.0));
+    match point.split_at_antimeridian().unwrap() {
+        Shape::Point(p) => assert_eq!(p, Point::new(180.0, 0.0)),
+        _ => panic!("Expected a point"),
+    }
+}
+
+#[test]
+fn split_polyline_parts() {
+    let points = vec![
+        Point::new(170.0, 0.0),
+        Point::new(-170.0, 0.0),
+        Point::new(170.0, 10.0),
+        Point::new(0.0, 0.0),
+        Point::new(10.0, 0.0),
+    ];
+    let split = Shape::Polyline(Polyline::new(points, vec![0, 3]))
+        .split_at_antimeridian()
+        .unwrap();
+    let split = split.as_polyline().unwrap();
+    assert_eq!(split.parts, vec![0, 2, 5, 7]);
+    assert!(split.points.iter().all(|p| -180.0 <= p.x && p.x <= 180.0));
+}
@@ -0,0 +1,126 @@
+extern crate shapefile;
+
+use std::io::Cursor;
+
+use shapefile::record::EsriShape;
+use shapefile::{AxisSwapHint, Multipatch, NullShape, PatchType, Point, PointZ, Polygon, Shape};
+use shapefile::NO_DATA;
+
+/// Square with a square hole, given as (latitude, longitude)
+fn swapped_polygon() -> Polygon {
+    Polygon::new(
+        vec![
+            Point::new(40.0, -120.0),
+            Point::new(40.0, -110.0),
+            Point::new(45.0, -110.0),
+            Point::new(45.0, -120.0),
+            Point::new(40.0, -120.0),
+            Point::new(41.0, -119.0),
+            Point::new(42.0, -119.0),
+            Point::new(42.0, -118.0),
+            Point::new(41.0, -118.0),
+            Point::new(41.0, -119.0),
+        ],
+        vec![0, 5],
+    )
+}
+
+#[test]
+fn detect_swap_in_header() {
+    let mut shp = Vec::<u8>::new();
+    shapefile::Writer::new(Cursor::new(&mut shp))
+        .write_shapes(vec![swapped_polygon()])
+        .unwrap();
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(
+        shapefile::detect_axis_swap(reader.header(), None),
+        AxisSwapHint::LikelySwapped
+    );
+
+    let mut polygon = swapped_polygon();
+    polygon.swap_xy();
+    assert_eq!(
+        shapefile::axis::detect_shape_axis_swap(&polygon, None),
+        AxisSwapHint::Consistent
+    );
+}
+
+#[test]
+fn swap_polygon_keeps_orientation() {
+    let original = swapped_polygon();
+    assert_eq!(original.outer_rings().count(), 1);
+    assert_eq!(original.inner_rings().count(), 1);
+
+    let mut shape = Shape::Polygon(swapped_polygon());
+    shape.swap_xy();
+    let polygon = match shape {
+        Shape::Polygon(polygon) => polygon,
+        _ => unreachable!(),
+    };
+    let bbox = polygon.bbox();
+    assert_eq!(
+        [bbox.xmin, bbox.ymin, bbox.xmax, bbox.ymax],
+        [-120.0, 40.0, -110.0, 45.0]
+    );
+    assert_eq!(polygon.parts, vec![0, 5]);
+    // the rings start at the same point, but are reversed
+    assert_eq!(polygon.points[0], Point::new(-120.0, 40.0));
+    assert_eq!(polygon.points[1], Point::new(-120.0, 45.0));
+    assert_eq!(polygon.points[4], Point::new(-120.0, 40.0));
+    assert_eq!(polygon.points[5], Point::new(-119.0, 41.0));
+    assert_eq!(polygon.outer_rings().count(), 1);
+    assert_eq!(polygon.inner_rings().count(), 1);
+
+    // swapping twice gives back the original
+    let mut polygon = polygon;
+    polygon.swap_xy();
+    assert_eq!(polygon.points, original.points);
+    assert_eq!(polygon.bbox, original.bbox);
+}
+
+#[test]
+fn swap_multipatch_only_reverses_rings() {
+    let point = |x, y| PointZ::new(x, y, 0.0, NO_DATA);
+    let mut multipatch = Multipatch::new(
+        vec![
+            point(0.0, 1.0),
+            point(0.0, 2.0),
+            point(1.0, 3.0),
+            point(0.0, 0.0),
+            point(0.0, 1.0),
+            point(1.0, 1.0),
+            point(0.0, 0.0),
+        ],
+        vec![0, 3],
+        vec![PatchType::TriangleStrip, PatchType::OuterRing],
+    );
+    multipatch.swap_xy();
+    assert_eq!(
+        multipatch.points,
+        vec![
+            point(1.0, 0.0),
+            point(2.0, 0.0),
+            point(3.0, 1.0),
+            point(0.0, 0.0),
+            point(1.0, 1.0),
+            point(1.0, 0.0),
+            point(0.0, 0.0),
+        ]
+    );
+    assert_eq!(multipatch.bbox().xmax, 3.0);
+    assert_eq!(multipatch.bbox().ymax, 1.0);
+}
+
+#[test]
+fn swap_points_and_null_shapes() {
+    let mut shape = Shape::PointZ(PointZ::new(1.0, 2.0, 3.0, 4.0));
+    shape.swap_xy();
+    match shape {
+        Shape::PointZ(point) => assert_eq!(point, PointZ::new(2.0, 1.0, 3.0, 4.0)),
+        _ => unreachable!(),
+    }
+
+    let mut shape = Shape::NullShape(NullShape);
+    shape.swap_xy();
+    assert_eq!(shape.shapetype(), shapefile::ShapeType::NullShape);
+}
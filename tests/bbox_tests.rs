@@ -0,0 +1,203 @@
+extern crate shapefile;
+
+use std::io::{Cursor, Seek, SeekFrom};
+
+use shapefile::record::BBox;
+use shapefile::{IntersectsBBox, Multipoint, Point, PointZ, Polygon, Polyline, Shape};
+
+/// Polygon with two islands, the first one has a hole
+fn two_islands() -> Polygon {
+    let points = vec![
+        // first island
+        Point::new(0.0, 0.0),
+        Point::new(0.0, 10.0),
+        Point::new(10.0, 10.0),
+        Point::new(10.0, 0.0),
+        Point::new(0.0, 0.0),
+        // its hole
+        Point::new(2.0, 2.0),
+        Point::new(3.0, 2.0),
+        Point::new(3.0, 3.0),
+        Point::new(2.0, 3.0),
+        Point::new(2.0, 2.0),
+        // second island
+        Point::new(100.0, 100.0),
+        Point::new(100.0, 110.0),
+        Point::new(110.0, 110.0),
+        Point::new(110.0, 100.0),
+        Point::new(100.0, 100.0),
+    ];
+    Polygon::new(points, vec![0, 5, 10])
+}
+
+fn reader_of_two_islands() -> shapefile::Reader<Cursor<Vec<u8>>> {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![two_islands()]).unwrap();
+    let mut cursor = writer.dest;
+    cursor.seek(SeekFrom::Start(0)).unwrap();
+    shapefile::Reader::new(cursor).unwrap()
+}
+
+#[test]
+fn bbox_query_whole_shapes() {
+    let reader = reader_of_two_islands();
+    let shapes = reader
+        .iter_shapes_in_bbox_as::<Polygon>(BBox::new(105.0, 105.0, 200.0, 200.0))
+        .collect::<Result<Vec<Polygon>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert_eq!(shapes[0].parts, vec![0, 5, 10]);
+
+    let reader = reader_of_two_islands();
+    let count = reader
+        .iter_shapes_in_bbox(BBox::new(200.0, 200.0, 300.0, 300.0))
+        .count();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn bbox_query_retains_only_intersecting_island() {
+    let reader = reader_of_two_islands();
+    let shapes = reader
+        .iter_shapes_in_bbox_as::<Polygon>(BBox::new(105.0, 105.0, 200.0, 200.0))
+        .retain_intersecting_parts()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert!(shapes[0].is_partial);
+    let polygon = &shapes[0].shape;
+    assert_eq!(polygon.parts, vec![0]);
+    assert_eq!(polygon.points, two_islands().points[10..].to_vec());
+    assert_eq!(polygon.bbox.xmin, 100.0);
+    assert_eq!(polygon.bbox.ymax, 110.0);
+}
+
+#[test]
+fn bbox_query_keeps_holes_of_retained_island() {
+    let reader = reader_of_two_islands();
+    let shapes = reader
+        .iter_shapes_in_bbox_as::<Polygon>(BBox::new(-5.0, -5.0, 1.0, 1.0))
+        .retain_intersecting_parts()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert!(shapes[0].is_partial);
+    let polygon = &shapes[0].shape;
+    assert_eq!(polygon.parts, vec![0, 5]);
+    assert_eq!(polygon.points, two_islands().points[..10].to_vec());
+}
+
+#[test]
+fn bbox_query_between_islands() {
+    // The bbox of the record intersects, but none of its parts do
+    let reader = reader_of_two_islands();
+    let count = reader
+        .iter_shapes_in_bbox(BBox::new(50.0, 50.0, 60.0, 60.0))
+        .retain_intersecting_parts()
+        .count();
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn bbox_query_all_parts_intersecting() {
+    let reader = reader_of_two_islands();
+    let shapes = reader
+        .iter_shapes_in_bbox(BBox::new(5.0, 5.0, 105.0, 105.0))
+        .retain_intersecting_parts()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert!(!shapes[0].is_partial);
+}
+
+#[test]
+fn bbox_try_new_checks_coordinates() {
+    assert_eq!(
+        BBox::try_new(0.0, 1.0, 2.0, 3.0).unwrap(),
+        BBox::new(0.0, 1.0, 2.0, 3.0)
+    );
+    assert!(BBox::try_new(1.0, 1.0, 1.0, 1.0).is_ok());
+    for &(xmin, ymin, xmax, ymax) in &[
+        (2.0, 0.0, 1.0, 1.0),
+        (0.0, 2.0, 1.0, 1.0),
+        (f64::NAN, 0.0, 1.0, 1.0),
+        (0.0, f64::NEG_INFINITY, 1.0, 1.0),
+    ] {
+        match BBox::try_new(xmin, ymin, xmax, ymax) {
+            Err(shapefile::Error::InvalidBBox(bbox)) => assert_eq!(bbox.xmax, xmax),
+            _ => panic!("Expected InvalidBBox"),
+        }
+    }
+
+    let bbox = BBox::from((Point::new(4.0, -1.0), Point::new(-2.0, 3.0)));
+    assert_eq!(bbox, BBox::new(-2.0, -1.0, 4.0, 3.0));
+}
+
+#[test]
+fn polygon_intersects_bbox() {
+    let polygon = two_islands();
+    // The bbox inside the first island, which no ring crosses
+    assert!(polygon.intersects_bbox(&BBox::new(5.0, 5.0, 6.0, 6.0)));
+    // The polygon inside the bbox
+    assert!(polygon.intersects_bbox(&BBox::new(-1.0, -1.0, 200.0, 200.0)));
+    // The bbox crossing a ring, and touching one
+    assert!(polygon.intersects_bbox(&BBox::new(8.0, -5.0, 12.0, 5.0)));
+    assert!(polygon.intersects_bbox(&BBox::new(10.0, 4.0, 12.0, 5.0)));
+    // The bbox inside the hole
+    assert!(!polygon.intersects_bbox(&BBox::new(2.2, 2.2, 2.8, 2.8)));
+    // The bbox between the islands, within the bbox of the polygon
+    assert!(!polygon.intersects_bbox(&BBox::new(50.0, 50.0, 60.0, 60.0)));
+}
+
+#[test]
+fn shapes_intersect_bbox() {
+    let bbox = BBox::new(0.0, 0.0, 10.0, 10.0);
+    assert!(Point::new(10.0, 5.0).intersects_bbox(&bbox));
+    assert!(!PointZ::new(11.0, 5.0, 0.0, 0.0).intersects_bbox(&bbox));
+
+    let multipoint = Multipoint::new(vec![Point::new(-1.0, -1.0), Point::new(11.0, 11.0)]);
+    assert!(!multipoint.intersects_bbox(&bbox));
+    let multipoint = Multipoint::new(vec![Point::new(-1.0, -1.0), Point::new(1.0, 1.0)]);
+    assert!(multipoint.intersects_bbox(&bbox));
+
+    // A segment crossing the bbox without any of its points in it
+    let crossing = Polyline::new(vec![Point::new(-5.0, 5.0), Point::new(15.0, 5.0)], vec![0]);
+    assert!(crossing.intersects_bbox(&bbox));
+    // Each part goes along the bbox, but the segment between the parts is not drawn
+    let points = vec![
+        Point::new(-5.0, -5.0),
+        Point::new(-1.0, 20.0),
+        Point::new(11.0, 20.0),
+        Point::new(15.0, -5.0),
+    ];
+    let around = Polyline::new(points, vec![0, 2]);
+    assert!(!around.intersects_bbox(&bbox));
+
+    let shape = Shape::Polyline(crossing);
+    assert!(shape.intersects_bbox(&bbox));
+    assert!(!Shape::NullShape(shapefile::NullShape).intersects_bbox(&bbox));
+}
+
+#[test]
+fn exact_bbox_query() {
+    let between_islands = BBox::new(50.0, 50.0, 60.0, 60.0);
+    let count = reader_of_two_islands()
+        .iter_shapes_in_bbox_as::<Polygon>(between_islands)
+        .count();
+    assert_eq!(count, 1);
+    let count = reader_of_two_islands()
+        .iter_shapes_in_bbox_as::<Polygon>(between_islands)
+        .exact(true)
+        .count();
+    assert_eq!(count, 0);
+
+    let in_first_island = BBox::new(5.0, 5.0, 6.0, 6.0);
+    let shapes = reader_of_two_islands()
+        .iter_shapes_in_bbox(in_first_island)
+        .exact(true)
+        .retain_intersecting_parts()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert!(shapes[0].is_partial);
+}
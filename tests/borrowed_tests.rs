@@ -0,0 +1,156 @@
+extern crate shapefile;
+
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use shapefile::record::borrowed::ShapeRef;
+use shapefile::{Point, Polyline, Shape};
+
+fn fixtures() -> Vec<std::path::PathBuf> {
+    let mut paths = std::fs::read_dir("tests/data")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "shp"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Returns the shapes read from views of the shapefile in `bytes`
+fn shapes_from_refs(bytes: &[u8]) -> Vec<Shape> {
+    let reader = shapefile::Reader::new(Cursor::new(bytes)).unwrap();
+    reader
+        .iter_shape_refs()
+        .map(|shape| shape.unwrap().to_owned())
+        .collect()
+}
+
+fn assert_same_shapes(shapes: &[Shape], expected: &[Shape], name: &str) {
+    assert_eq!(shapes.len(), expected.len(), "{}", name);
+    for (shape, expected) in shapes.iter().zip(expected) {
+        assert_eq!(shape.shapetype(), expected.shapetype(), "{}", name);
+        assert_eq!(
+            shape.points_xy().collect::<Vec<_>>(),
+            expected.points_xy().collect::<Vec<_>>(),
+            "{}",
+            name
+        );
+        assert_eq!(format!("{}", shape), format!("{}", expected), "{}", name);
+    }
+}
+
+#[test]
+fn shape_refs_are_the_same_as_shapes() {
+    for path in fixtures() {
+        let name = path.display().to_string();
+        let expected = shapefile::read(&path).unwrap();
+        let bytes = std::fs::read(&path).unwrap();
+        assert_same_shapes(&shapes_from_refs(&bytes), &expected, &name);
+
+        // At an odd address, nothing can be borrowed
+        let mut shifted = vec![0u8; bytes.len() + 1];
+        shifted[1..].copy_from_slice(&bytes);
+        assert_same_shapes(&shapes_from_refs(&shifted[1..]), &expected, &name);
+    }
+}
+
+#[test]
+fn shape_refs_borrow_aligned_points() {
+    let polylines = vec![
+        Polyline::new(vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)], vec![0]),
+        Polyline::new(
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(1.0, 1.0),
+                Point::new(2.0, 2.0),
+                Point::new(3.0, 3.0),
+            ],
+            vec![0, 2],
+        ),
+    ];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(polylines.clone()).unwrap();
+    let bytes = writer.dest.into_inner();
+
+    let reader = shapefile::Reader::new(Cursor::new(&bytes[..])).unwrap();
+    let shapes = reader.iter_shape_refs().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(shapes.len(), 2);
+    // Offsets of the points: after the file header, the record header,
+    // the shape type, bbox, numbers of parts and points, and the parts
+    let points_offsets = [100 + 8 + 44 + 4, 100 + (8 + 44 + 4 + 32) + 8 + 44 + 8];
+    for ((shape, expected), offset) in shapes.iter().zip(&polylines).zip(&points_offsets) {
+        let polyline = match shape {
+            ShapeRef::Polyline(polyline) => polyline,
+            _ => panic!("Expected a Polyline"),
+        };
+        assert_eq!(polyline.points.as_ref(), expected.points.as_slice());
+        assert_eq!(polyline.parts.as_ref(), expected.parts.as_slice());
+        assert_eq!(polyline.bbox, expected.bbox);
+
+        let address = bytes.as_ptr() as usize + offset;
+        let can_borrow =
+            cfg!(target_endian = "little") && address % std::mem::align_of::<Point>() == 0;
+        match polyline.points {
+            Cow::Borrowed(points) => {
+                assert!(can_borrow);
+                assert_eq!(points.as_ptr() as usize, address);
+            }
+            Cow::Owned(_) => assert!(!can_borrow),
+        }
+    }
+}
+
+#[test]
+fn shape_refs_of_truncated_file() {
+    let bytes = std::fs::read("tests/data/line.shp").unwrap();
+    let truncated = &bytes[..bytes.len() - 8];
+    let reader = shapefile::Reader::new(Cursor::new(truncated)).unwrap();
+    let results = reader.iter_shape_refs().collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    match results[0] {
+        Err(shapefile::Error::TruncatedFile { .. }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+}
+
+#[test]
+fn shape_refs_of_malformed_records() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)]).unwrap();
+    let bytes = writer.dest.into_inner();
+    let first_error = |bytes: &[u8], limit: Option<u64>| {
+        let reader = shapefile::Reader::new(Cursor::new(bytes))
+            .unwrap()
+            .max_record_size_bytes(limit);
+        reader.iter_shape_refs().find_map(Result::err)
+    };
+    assert!(first_error(&bytes, None).is_none());
+
+    // A negative file length in the header
+    let mut negative = bytes.clone();
+    negative[24..28].copy_from_slice(&(-2i32).to_be_bytes());
+    let reader = shapefile::Reader::new(Cursor::new(&negative[..])).unwrap();
+    assert!(reader.iter_shape_refs().next().is_none());
+
+    // A record too small for its shape type
+    let mut too_small = bytes.clone();
+    too_small[104..108].copy_from_slice(&1i32.to_be_bytes());
+    match first_error(&too_small, None) {
+        Some(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+
+    // The last record going past the end given by the header, into the bytes after it
+    let mut past_the_end = bytes.clone();
+    past_the_end.extend_from_slice(&[0; 16]);
+    past_the_end[100 + 28 + 4..100 + 28 + 8].copy_from_slice(&14i32.to_be_bytes());
+    match first_error(&past_the_end, None) {
+        Some(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+
+    match first_error(&bytes, Some(16)) {
+        Some(shapefile::Error::RecordTooLarge { declared: 20, .. }) => {}
+        _ => panic!("Expected RecordTooLarge"),
+    }
+}
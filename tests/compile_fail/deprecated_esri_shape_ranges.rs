@@ -0,0 +1,12 @@
+#![deny(deprecated)]
+extern crate shapefile;
+
+use shapefile::record::EsriShape;
+use shapefile::{Point, Polyline};
+
+fn main() {
+    let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)], vec![0]);
+    let _ = polyline.z_range();
+    let _ = polyline.m_range();
+    let _ = polyline.has_measures();
+}
@@ -0,0 +1,13 @@
+extern crate shapefile;
+
+use shapefile::record::MultipartShape;
+use shapefile::{Point, Polyline, PolylineM};
+
+fn main() {
+    let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)], vec![0]);
+    let _ = polyline.part_z_stats(0);
+    let _ = polyline.part_m_stats(0);
+
+    let polyline_m = PolylineM::new(vec![], vec![]);
+    let _ = polyline_m.part_z_stats(0);
+}
@@ -0,0 +1,10 @@
+extern crate shapefile;
+
+use shapefile::{Multipoint, Point, PointM, Polygon};
+
+fn main() {
+    let _ = Point::new(1.0, 2.0).m_range();
+    let _ = PointM::new(1.0, 2.0, 3.0).z_range();
+    let _ = Multipoint::new(vec![Point::new(1.0, 2.0)]).z_range();
+    let _ = Polygon::new(vec![], vec![]).m_range();
+}
@@ -0,0 +1,13 @@
+extern crate shapefile;
+
+use shapefile::{Point, Polyline, PolylineM};
+
+fn main() {
+    let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)], vec![0]);
+    let _ = polyline.z_range();
+    let _ = polyline.m_range();
+    let _ = polyline.has_measures();
+
+    let polyline_m = PolylineM::new(vec![], vec![]);
+    let _ = polyline_m.z_range();
+}
@@ -0,0 +1,16 @@
+//! Checks that the z and m data can not be requested from the shapes without them.
+//!
+//! The expected errors (the .stderr files of tests/compile_fail) are the ones of rustc 1.95
+//! and change with the versions of the compiler, so the tests are only run with
+//! `cargo test --features compile-fail-tests`. They can be generated again
+//! by setting the `TRYBUILD=overwrite` environment variable.
+#![cfg(feature = "compile-fail-tests")]
+extern crate trybuild;
+
+/// The z and m data of the shapes can only be requested
+/// from the shapes which points have them
+#[test]
+fn dimensions_of_dimensionless_shapes() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile_fail/*.rs");
+}
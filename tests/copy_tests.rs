@@ -0,0 +1,140 @@
+extern crate dbase;
+extern crate shapefile;
+
+use dbase::FieldValue;
+use shapefile::{Point, Polyline, Shape};
+
+fn name_of(record: &dbase::Record) -> String {
+    match record.get("NAME") {
+        Some(FieldValue::Character(Some(name))) => name.clone(),
+        _ => panic!("Record has no NAME"),
+    }
+}
+
+#[test]
+fn copy_scales_and_drops() {
+    let dir = std::env::temp_dir().join("shapefile_copy_scales_and_drops");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.shp");
+    let output = dir.join("output.shp");
+
+    let names = ["a", "drop", "b"];
+    let polylines = (0..names.len())
+        .map(|i| {
+            let i = i as f64;
+            Polyline::new(vec![Point::new(i, i), Point::new(i + 1.0, -i)], vec![0])
+        })
+        .collect::<Vec<Polyline>>();
+    let records = names
+        .iter()
+        .map(|name| {
+            let mut record = dbase::Record::new();
+            record.insert("NAME".to_string(), FieldValue::from(*name));
+            record
+        })
+        .collect::<Vec<dbase::Record>>();
+    let writer = shapefile::Writer::from_path(&input).unwrap();
+    writer.write_shapes_and_records(polylines, records).unwrap();
+
+    let report = shapefile::copy_with(&input, &output, |shape, record| {
+        if name_of(&record) == "drop" {
+            return None;
+        }
+        match shape {
+            Shape::Polyline(polyline) => {
+                let points = polyline
+                    .points
+                    .iter()
+                    .map(|p| Point::new(p.x * 2.0, p.y * 2.0))
+                    .collect();
+                let scaled = Polyline::new(points, polyline.parts);
+                Some((Shape::Polyline(scaled), record))
+            }
+            _ => panic!("Shape is not a Polyline"),
+        }
+    })
+    .unwrap();
+    assert_eq!(
+        report,
+        shapefile::CopyReport {
+            read: 3,
+            written: 2,
+            dropped: 1
+        }
+    );
+
+    let mut reader = shapefile::Reader::from_path(&output).unwrap();
+    {
+        let header = reader.header();
+        assert_eq!(header.shape_type, shapefile::ShapeType::Polyline);
+        assert_eq!(header.point_min, [0.0, -4.0, 0.0]);
+        assert_eq!(header.point_max, [6.0, 4.0, 0.0]);
+        assert_eq!(
+            header.file_length as u64 * 2,
+            std::fs::metadata(&output).unwrap().len()
+        );
+    }
+    assert_eq!(reader.index_table().unwrap().len(), 2);
+    match reader.read_nth_shape(1).unwrap().unwrap() {
+        Shape::Polyline(polyline) => {
+            assert_eq!(polyline.points, vec![Point::new(4.0, 4.0), Point::new(6.0, -4.0)])
+        }
+        _ => panic!("Shape is not a Polyline"),
+    }
+
+    let names = reader
+        .iter_shapes_and_records()
+        .unwrap()
+        .map(|result| name_of(&result.unwrap().1))
+        .collect::<Vec<String>>();
+    assert_eq!(names, vec!["a", "b"]);
+    assert!(shapefile::verify(&output).unwrap().is_valid());
+}
+
+#[test]
+fn copy_updates_the_bbox_of_modified_shapes() {
+    let dir = std::env::temp_dir().join("shapefile_copy_updates_the_bbox");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.shp");
+    let output = dir.join("output.shp");
+
+    let polyline = Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)], vec![0]);
+    let mut record = dbase::Record::new();
+    record.insert("NAME".to_string(), FieldValue::from("a"));
+    let writer = shapefile::Writer::from_path(&input).unwrap();
+    writer.write_shapes_and_records(vec![polyline], vec![record]).unwrap();
+
+    // The points are moved without updating the bbox
+    shapefile::copy_with(&input, &output, |mut shape, record| {
+        if let Shape::Polyline(ref mut polyline) = shape {
+            for point in &mut polyline.points {
+                point.x += 10.0;
+            }
+        }
+        Some((shape, record))
+    })
+    .unwrap();
+
+    let reader = shapefile::Reader::from_path(&output).unwrap();
+    assert_eq!(reader.header().point_min, [10.0, 0.0, 0.0]);
+    assert_eq!(reader.header().point_max, [11.0, 1.0, 0.0]);
+    let polylines = reader.read_as::<Polyline>().unwrap();
+    assert_eq!(polylines[0].bbox, shapefile::record::BBox::new(10.0, 0.0, 11.0, 1.0));
+    assert!(shapefile::verify(&output).unwrap().is_valid());
+}
+
+#[test]
+fn copy_dropping_all_features_keeps_the_shape_type() {
+    let dir = std::env::temp_dir().join("shapefile_copy_dropping_all_features");
+    std::fs::create_dir_all(&dir).unwrap();
+    let output = dir.join("output.shp");
+
+    let report = shapefile::copy_with("tests/data/multipatch.shp", &output, |_, _| None).unwrap();
+    assert_eq!(report.written, 0);
+
+    let reader = shapefile::Reader::from_path(&output).unwrap();
+    assert_eq!(reader.header().shape_type, shapefile::ShapeType::Multipatch);
+    assert_eq!(reader.header().file_length, 50);
+    assert_eq!(std::fs::metadata(&output).unwrap().len(), 100);
+    assert_eq!(std::fs::metadata(output.with_extension("shx")).unwrap().len(), 100);
+}
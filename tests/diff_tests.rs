@@ -0,0 +1,115 @@
+extern crate dbase;
+extern crate shapefile;
+
+mod testfiles;
+
+use dbase::FieldValue;
+use shapefile::diff::DiffOptions;
+use shapefile::{Point, Polyline};
+
+fn temp_path(dir_name: &str, file_name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir.join(file_name)
+}
+
+/// Writes a copy of the line fixture with the first point of the given record moved by `dx`
+fn write_moved_line(path: &std::path::Path, record: usize, dx: f64) {
+    let mut polylines = shapefile::read_as::<_, Polyline>(testfiles::LINE_PATH).unwrap();
+    let polyline = &polylines[record];
+    let mut points = polyline.points.clone();
+    points[0] = Point::new(points[0].x + dx, points[0].y);
+    polylines[record] = Polyline::new(points, polyline.parts.clone());
+    let mut writer = shapefile::Writer::from_path(path).unwrap();
+    writer.write_shapes(polylines).unwrap();
+}
+
+fn write_named_points(path: &std::path::Path, names: &[&str]) {
+    let points = (0..names.len())
+        .map(|i| Point::new(i as f64, i as f64))
+        .collect::<Vec<_>>();
+    let records = names
+        .iter()
+        .map(|name| {
+            let mut record = dbase::Record::new();
+            record.insert("NAME".to_string(), FieldValue::from(*name));
+            record
+        })
+        .collect::<Vec<_>>();
+    let writer = shapefile::Writer::from_path(path).unwrap();
+    writer.write_shapes_and_records(points, records).unwrap();
+}
+
+#[test]
+fn diff_of_same_file() {
+    let report =
+        shapefile::diff(testfiles::LINE_PATH, testfiles::LINE_PATH, DiffOptions::default())
+            .unwrap();
+    assert!(report.is_identical());
+    assert_eq!(report.num_records_a, report.num_records_b);
+    assert!(report.num_records_a > 0);
+}
+
+#[test]
+fn diff_of_rewritten_file() {
+    let copy = temp_path("shapefile_diff_of_rewritten_file", "line.shp");
+    write_moved_line(&copy, 0, 0.0);
+    let report = shapefile::diff(testfiles::LINE_PATH, &copy, DiffOptions::default()).unwrap();
+    assert!(report.is_identical(), "{}", report);
+}
+
+#[test]
+fn diff_of_perturbed_copy() {
+    let copy = temp_path("shapefile_diff_of_perturbed_copy", "line.shp");
+    write_moved_line(&copy, 0, 1e-3);
+
+    let report = shapefile::diff(testfiles::LINE_PATH, &copy, DiffOptions::default()).unwrap();
+    assert_eq!(report.differing_records(), vec![1]);
+    assert_eq!(report.differences.len(), 1);
+    assert!(report.differences[0].description.starts_with("point 0 differs"));
+
+    let options = DiffOptions::default().epsilon(1e-2);
+    let report = shapefile::diff(testfiles::LINE_PATH, &copy, options).unwrap();
+    assert!(report.is_identical(), "{}", report);
+}
+
+#[test]
+fn diff_of_different_shape_types() {
+    let report =
+        shapefile::diff(testfiles::LINE_PATH, testfiles::LINEZ_PATH, DiffOptions::default())
+            .unwrap();
+    assert_eq!(report.differences[0].record_number, None);
+    assert!(report
+        .differences
+        .iter()
+        .filter(|difference| difference.record_number.is_some())
+        .all(|difference| difference.description.starts_with("shape type")));
+}
+
+#[test]
+fn diff_of_attributes() {
+    let a = temp_path("shapefile_diff_of_attributes", "a.shp");
+    let b = temp_path("shapefile_diff_of_attributes", "b.shp");
+    write_named_points(&a, &["x", "y", "z"]);
+    write_named_points(&b, &["x", "w", "z", "extra"]);
+
+    let report = shapefile::diff(&a, &b, DiffOptions::default()).unwrap();
+    assert_eq!((report.num_records_a, report.num_records_b), (3, 4));
+    assert_eq!(report.differences.len(), 1);
+    assert_eq!(report.differences[0].description, "3 records vs 4");
+
+    let options = DiffOptions::default().compare_attributes(true);
+    let report = shapefile::diff(&a, &b, options).unwrap();
+    assert_eq!(report.differing_records(), vec![2]);
+    assert_eq!(report.differences.len(), 3);
+    assert!(report.differences[2].description.starts_with("attribute 'NAME'"));
+}
+
+#[test]
+fn diff_of_attributes_without_dbf() {
+    let options = DiffOptions::default().compare_attributes(true);
+    match shapefile::diff(testfiles::LINE_PATH, testfiles::LINE_PATH, options) {
+        Err(shapefile::Error::MissingDbf) => {}
+        _ => panic!("Expected MissingDbf"),
+    }
+}
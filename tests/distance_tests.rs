@@ -0,0 +1,123 @@
+extern crate shapefile;
+
+use shapefile::{Point, PointM, PointZ, Polygon, Polyline, PolylineM, PolylineZ, NO_DATA};
+
+fn square(xmin: f64, ymin: f64, size: f64) -> Vec<Point> {
+    vec![
+        Point::new(xmin, ymin),
+        Point::new(xmin, ymin + size),
+        Point::new(xmin + size, ymin + size),
+        Point::new(xmin + size, ymin),
+        Point::new(xmin, ymin),
+    ]
+}
+
+#[test]
+fn point_equidistant_from_two_parts() {
+    let polyline = Polyline::new(
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(10.0, 0.0),
+            Point::new(0.0, 4.0),
+            Point::new(10.0, 4.0),
+        ],
+        vec![0, 2],
+    );
+    assert_eq!(polyline.distance_to_point(5.0, 2.0), 2.0);
+    let (point, distance, part) = polyline.closest_point_on(5.0, 2.0).unwrap();
+    assert_eq!(point, Point::new(5.0, 0.0));
+    assert_eq!(distance, 2.0);
+    assert_eq!(part, 0);
+
+    let (point, distance, part) = polyline.closest_point_on(5.0, 3.0).unwrap();
+    assert_eq!(point, Point::new(5.0, 4.0));
+    assert_eq!(distance, 1.0);
+    assert_eq!(part, 1);
+}
+
+#[test]
+fn zero_length_segments_are_not_nan() {
+    let polyline = Polyline::new(
+        vec![
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(4.0, 5.0),
+            Point::new(4.0, 5.0),
+            Point::new(6.0, 1.0),
+        ],
+        vec![0, 4],
+    );
+    assert_eq!(polyline.distance_to_point(-2.0, 1.0), 3.0);
+    let (point, distance, part) = polyline.closest_point_on(1.0, -3.0).unwrap();
+    assert_eq!((point, distance, part), (Point::new(1.0, 1.0), 4.0, 0));
+
+    // The part of a single point is at the distance of that point
+    let (point, distance, part) = polyline.closest_point_on(6.0, 0.0).unwrap();
+    assert_eq!((point, distance, part), (Point::new(6.0, 1.0), 1.0, 1));
+
+    let degenerate = PolylineZ::new(vec![PointZ::new(3.0, 3.0, 1.0, 2.0); 2], vec![0]);
+    assert_eq!(degenerate.distance_to_point(0.0, -1.0), 5.0);
+    let (point, distance, _) = degenerate.closest_point_on(0.0, -1.0).unwrap();
+    assert_eq!(point, PointZ::new(3.0, 3.0, 1.0, 2.0));
+    assert_eq!(distance, 5.0);
+}
+
+#[test]
+fn closest_point_interpolates_z_and_m() {
+    let polyline = PolylineZ::new(
+        vec![
+            PointZ::new(0.0, 0.0, 10.0, 0.0),
+            PointZ::new(0.0, 8.0, 30.0, 8.0),
+            PointZ::new(8.0, 8.0, 30.0, NO_DATA),
+        ],
+        vec![0],
+    );
+    let (point, distance, _) = polyline.closest_point_on(-1.0, 2.0).unwrap();
+    assert_eq!(point, PointZ::new(0.0, 2.0, 15.0, 2.0));
+    assert_eq!(distance, 1.0);
+
+    // On a segment with a NO_DATA end, the interpolated measure is NO_DATA
+    let (point, _, _) = polyline.closest_point_on(4.0, 9.0).unwrap();
+    assert_eq!(point, PointZ::new(4.0, 8.0, 30.0, NO_DATA));
+    // but the measure of a vertex is kept
+    let (point, _, _) = polyline.closest_point_on(-1.0, 9.0).unwrap();
+    assert_eq!(point, PointZ::new(0.0, 8.0, 30.0, 8.0));
+
+    let polyline = PolylineM::new(
+        vec![PointM::new(0.0, 0.0, 100.0), PointM::new(10.0, 10.0, 200.0)],
+        vec![0],
+    );
+    let (point, _, _) = polyline.closest_point_on(0.0, 10.0).unwrap();
+    assert_eq!(point, PointM::new(5.0, 5.0, 150.0));
+}
+
+#[test]
+fn distance_to_polygon() {
+    let mut points = square(0.0, 0.0, 10.0);
+    points.extend(square(2.0, 2.0, 4.0).into_iter().rev());
+    points.extend(square(20.0, 0.0, 2.0));
+    let polygon = Polygon::new(points, vec![0, 5, 10]);
+
+    assert_eq!(polygon.distance_to_point(1.0, 1.0), 0.0);
+    assert_eq!(polygon.distance_to_point(8.0, 4.0), 0.0);
+    assert_eq!(polygon.distance_to_point(21.0, 1.0), 0.0);
+    // In the hole
+    assert_eq!(polygon.distance_to_point(3.0, 4.0), 1.0);
+    // Outside, between the two outer rings
+    assert_eq!(polygon.distance_to_point(14.0, 1.0), 4.0);
+
+    // The closest point is on a ring, even for a point inside
+    let (point, distance, ring) = polygon.closest_point_on(7.0, 4.0).unwrap();
+    assert_eq!((point, distance, ring), (Point::new(6.0, 4.0), 1.0, 1));
+}
+
+#[test]
+fn distance_to_empty_shapes() {
+    let polyline = Polyline::new(vec![], vec![]);
+    assert_eq!(polyline.distance_to_point(0.0, 0.0), f64::INFINITY);
+    assert!(polyline.closest_point_on(0.0, 0.0).is_none());
+
+    let polygon = Polygon::new(vec![], vec![]);
+    assert_eq!(polygon.distance_to_point(0.0, 0.0), f64::INFINITY);
+    assert!(polygon.closest_point_on(0.0, 0.0).is_none());
+}
@@ -0,0 +1,57 @@
+extern crate shapefile;
+
+use shapefile::dump::DumpOptions;
+
+mod testfiles;
+
+fn dump_to_string(path: &str, options: DumpOptions) -> String {
+    let mut output = Vec::<u8>::new();
+    shapefile::dump(path, &mut output, options).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn dump_line_all_coordinates() {
+    let options = DumpOptions {
+        precision: 2,
+        records: None,
+        num_coordinates: None,
+    };
+    let output = dump_to_string(testfiles::LINE_PATH, options);
+    assert_eq!(output, include_str!("data/dump/line.txt"));
+}
+
+#[test]
+fn dump_polygonz_first_and_last_coordinates() {
+    let options = DumpOptions {
+        precision: 3,
+        records: None,
+        num_coordinates: Some(2),
+    };
+    let output = dump_to_string(testfiles::POLYGONZ_PATH, options);
+    assert_eq!(output, include_str!("data/dump/polygonz.txt"));
+}
+
+#[test]
+fn dump_record_range() {
+    let options = DumpOptions {
+        precision: 1,
+        records: Some(1..2),
+        num_coordinates: None,
+    };
+    let output = dump_to_string(testfiles::POINTZ_PATH, options);
+    assert_eq!(output, include_str!("data/dump/pointz_record_1.txt"));
+}
+
+#[test]
+fn dump_negative_file_length() {
+    let mut shp = std::fs::read(testfiles::LINE_PATH).unwrap();
+    shp[24..28].copy_from_slice(&(-2i32).to_be_bytes());
+    let path = std::env::temp_dir().join("shapefile_dump_negative_file_length.shp");
+    std::fs::write(&path, &shp).unwrap();
+
+    let mut output = Vec::<u8>::new();
+    shapefile::dump(&path, &mut output, DumpOptions::default()).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("File length: 0 bytes\n"));
+}
@@ -0,0 +1,155 @@
+//! Byte-level fixtures of the fields of the format, written by hand so that the tests
+//! do not depend on the endianness of the host: the file code, the file length
+//! and the record headers are big-endian, all the other fields are little-endian.
+//!
+//! The views of `record::borrowed`, which borrow the points of the file only on little
+//! endian hosts, are read with the byte order of both hosts by the tests of that module.
+extern crate shapefile;
+
+use std::io::Cursor;
+
+use shapefile::record::borrowed::ShapeRef;
+use shapefile::{Point, Polyline};
+
+/// Returns the bytes given as space separated hexadecimal pairs
+fn hex(bytes: &str) -> Vec<u8> {
+    bytes
+        .split_whitespace()
+        .map(|pair| u8::from_str_radix(pair, 16).unwrap())
+        .collect()
+}
+
+const ZERO: &str = "00 00 00 00 00 00 00 00";
+const ONE: &str = "00 00 00 00 00 00 F0 3F";
+const ONE_AND_HALF: &str = "00 00 00 00 00 00 F8 3F";
+const MINUS_TWO: &str = "00 00 00 00 00 00 00 C0";
+
+/// Header of a file of the `shape_type` (LE) and `file_length` (BE) given in hex,
+/// whose bbox is given by the `xmin, ymin, xmax, ymax` hex values
+fn header(file_length: &str, shape_type: &str, bbox: [&str; 4]) -> Vec<u8> {
+    let mut bytes = hex("00 00 27 0A"); // file code 9994
+    bytes.extend(hex(&"00 ".repeat(20)));
+    bytes.extend(hex(file_length));
+    bytes.extend(hex("E8 03 00 00")); // version 1000
+    bytes.extend(hex(shape_type));
+    for value in &bbox {
+        bytes.extend(hex(value));
+    }
+    // z and m ranges
+    for _ in 0..4 {
+        bytes.extend(hex(ZERO));
+    }
+    assert_eq!(bytes.len(), 100);
+    bytes
+}
+
+fn point_shp() -> Vec<u8> {
+    let mut bytes = header(
+        "00 00 00 40",
+        "01 00 00 00",
+        [ONE_AND_HALF, MINUS_TWO, ONE_AND_HALF, MINUS_TWO],
+    );
+    bytes.extend(hex("00 00 00 01 00 00 00 0A")); // record 1, 10 words
+    bytes.extend(hex("01 00 00 00"));
+    bytes.extend(hex(ONE_AND_HALF));
+    bytes.extend(hex(MINUS_TWO));
+    bytes
+}
+
+fn point_shx() -> Vec<u8> {
+    let mut bytes = header(
+        "00 00 00 36",
+        "01 00 00 00",
+        [ONE_AND_HALF, MINUS_TWO, ONE_AND_HALF, MINUS_TWO],
+    );
+    bytes.extend(hex("00 00 00 32 00 00 00 0A")); // offset 50 words, 10 words
+    bytes
+}
+
+fn polyline_shp() -> Vec<u8> {
+    let mut bytes = header("00 00 00 5E", "03 00 00 00", [ZERO, ZERO, ONE, ONE]);
+    bytes.extend(hex("00 00 00 01 00 00 00 28")); // record 1, 40 words
+    bytes.extend(hex("03 00 00 00"));
+    for value in &[ZERO, ZERO, ONE, ONE] {
+        bytes.extend(hex(value));
+    }
+    bytes.extend(hex("01 00 00 00 02 00 00 00")); // 1 part, 2 points
+    bytes.extend(hex("00 00 00 00")); // the part starts at 0
+    for value in &[ZERO, ZERO, ONE, ONE] {
+        bytes.extend(hex(value));
+    }
+    bytes
+}
+
+fn polyline() -> Polyline {
+    Polyline::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)], vec![0])
+}
+
+#[test]
+fn read_point_fixture() {
+    let mut reader = shapefile::Reader::new(Cursor::new(point_shp())).unwrap();
+    reader.add_index_source(Cursor::new(point_shx())).unwrap();
+    assert_eq!(reader.header().file_length, 64);
+    assert_eq!(reader.header().version, 1000);
+    assert_eq!(reader.header().shape_type, shapefile::ShapeType::Point);
+    assert_eq!(reader.index_table().unwrap().offset_of(0), Some((100, 20)));
+    let point = reader.read_nth_shape_as::<Point>(0).unwrap().unwrap();
+    assert_eq!(point, Point::new(1.5, -2.0));
+}
+
+#[test]
+fn write_point_fixture() {
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp);
+        writer.add_index_dest(&mut shx);
+        writer.write_shapes(vec![Point::new(1.5, -2.0)]).unwrap();
+    }
+    assert_eq!(shp, point_shp());
+    assert_eq!(shx, point_shx());
+}
+
+#[test]
+fn read_polyline_fixture() {
+    let reader = shapefile::Reader::new(Cursor::new(polyline_shp())).unwrap();
+    let polylines = reader.read_as::<Polyline>().unwrap();
+    assert_eq!(polylines.len(), 1);
+    assert_eq!(polylines[0].parts, vec![0]);
+    assert_eq!(polylines[0].points, polyline().points);
+}
+
+#[test]
+fn read_polyline_fixture_as_view() {
+    let shp = polyline_shp();
+    let reader = shapefile::Reader::new(Cursor::new(&shp[..])).unwrap();
+    let shapes = reader
+        .iter_shape_refs()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    match shapes.as_slice() {
+        [ShapeRef::Polyline(view)] => {
+            assert_eq!(view.parts.to_vec(), vec![0]);
+            assert_eq!(view.points.to_vec(), polyline().points);
+        }
+        _ => panic!("Expected a view of a polyline"),
+    }
+}
+
+#[test]
+fn write_polyline_fixture() {
+    let mut shp = Vec::<u8>::new();
+    shapefile::Writer::new(&mut shp)
+        .write_shapes(vec![polyline()])
+        .unwrap();
+    assert_eq!(shp, polyline_shp());
+
+    // The same bytes with the streaming writer, which writes the header last
+    let mut streamed = Cursor::new(Vec::<u8>::new());
+    {
+        let mut writer = shapefile::Writer::new(&mut streamed);
+        writer.write_shape(polyline()).unwrap();
+        writer.finish().unwrap();
+    }
+    assert_eq!(streamed.into_inner(), polyline_shp());
+}
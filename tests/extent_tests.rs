@@ -0,0 +1,73 @@
+extern crate shapefile;
+
+use std::io::Cursor;
+
+use shapefile::record::BBox;
+use shapefile::{ExtentViolation, NullShape, Point, Polyline, Shape};
+
+fn wgs84() -> BBox {
+    BBox::new(-180.0, -90.0, 180.0, 90.0)
+}
+
+fn polyline_with_one_bad_vertex() -> Shape {
+    let points = vec![
+        Point::new(0.0, 0.0),
+        Point::new(10.0, 10.0),
+        Point::new(20.0, 20.0),
+        Point::new(181.5, 30.0),
+        Point::new(40.0, 40.0),
+    ];
+    Shape::Polyline(Polyline::new(points, vec![0, 2]))
+}
+
+#[test]
+fn validate_extent_of_polyline() {
+    let shapes = vec![Shape::Point(Point::new(1.0, 1.0)), polyline_with_one_bad_vertex()];
+    let violations = shapefile::validate_extent(&shapes, wgs84());
+    assert_eq!(
+        violations,
+        vec![ExtentViolation {
+            record: 1,
+            part: 1,
+            point: 1,
+            x: 181.5,
+            y: 30.0,
+        }]
+    );
+
+    let nan = vec![Shape::Point(Point::new(f64::NAN, 1.0)), Shape::NullShape(NullShape)];
+    assert_eq!(shapefile::validate_extent(&nan, wgs84()).len(), 1);
+}
+
+#[test]
+fn clamp_polyline_to_domain() {
+    let mut shape = polyline_with_one_bad_vertex();
+    shape.clamp_to(wgs84());
+
+    let polyline = shape.as_polyline().unwrap();
+    assert_eq!(polyline.points[3], Point::new(180.0, 30.0));
+    assert_eq!(polyline.points[4], Point::new(40.0, 40.0));
+    assert_eq!(polyline.bbox.xmax, 180.0);
+    assert!(shapefile::validate_extent(&[shape], wgs84()).is_empty());
+}
+
+#[test]
+fn reader_validate_extent() {
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(Cursor::new(&mut shp));
+    writer.write_shapes(vec![polyline_with_one_bad_vertex()]).unwrap();
+
+    let reader = shapefile::Reader::new(Cursor::new(shp.clone())).unwrap();
+    let violations = reader.validate_extent(wgs84()).unwrap();
+    assert_eq!(violations.len(), 1);
+    assert_eq!((violations[0].record, violations[0].part, violations[0].point), (0, 1, 1));
+
+    // The header bbox is within the domain, so the records
+    // (which are missing here) are not read
+    let header_only = shp[..100].to_vec();
+    let reader = shapefile::Reader::new(Cursor::new(header_only.clone())).unwrap();
+    let domain = BBox::new(-200.0, -90.0, 200.0, 90.0);
+    assert!(reader.validate_extent(domain).unwrap().is_empty());
+    let reader = shapefile::Reader::new(Cursor::new(header_only)).unwrap();
+    assert!(reader.validate_extent(wgs84()).is_err());
+}
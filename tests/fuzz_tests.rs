@@ -0,0 +1,172 @@
+extern crate byteorder;
+extern crate proptest;
+extern crate shapefile;
+
+use byteorder::{LittleEndian, WriteBytesExt};
+use proptest::prelude::*;
+
+use shapefile::fuzz::{parse_header, parse_index, parse_record, parse_shape_buffer};
+use shapefile::{Error, ShapeType};
+
+const SHAPE_TYPE_CODES: [i32; 14] = [0, 1, 3, 5, 8, 11, 13, 15, 18, 21, 23, 25, 28, 31];
+
+const FILES: [&str; 8] = [
+    "tests/data/line.shp",
+    "tests/data/linez.shp",
+    "tests/data/multipatch.shp",
+    "tests/data/multipointz.shp",
+    "tests/data/pointm.shp",
+    "tests/data/polygon.shp",
+    "tests/data/polygonm_no_m.shp",
+    "tests/data/polygonz.shp",
+];
+
+fn shape_type_code() -> impl Strategy<Value = i32> {
+    prop_oneof![
+        4 => proptest::sample::select(&SHAPE_TYPE_CODES[..]),
+        1 => any::<i32>(),
+    ]
+}
+
+/// A count of parts or points that is likely to be invalid
+fn count() -> impl Strategy<Value = i32> {
+    prop_oneof![
+        Just(i32::MIN),
+        Just(-1),
+        Just(i32::MAX),
+        0..16i32,
+        any::<i32>(),
+    ]
+}
+
+/// The content of a multipart record: a bbox, the counts, then random bytes
+fn multipart_content() -> impl Strategy<Value = Vec<u8>> {
+    (count(), count(), proptest::collection::vec(any::<u8>(), 0..256)).prop_map(
+        |(num_parts, num_points, rest)| {
+            let mut content = vec![0u8; 32];
+            content.write_i32::<LittleEndian>(num_parts).unwrap();
+            content.write_i32::<LittleEndian>(num_points).unwrap();
+            content.extend(rest);
+            content
+        },
+    )
+}
+
+/// One of the test files, with some of its bytes overwritten
+fn corrupted_file() -> impl Strategy<Value = Vec<u8>> {
+    let changes = proptest::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..16);
+    (proptest::sample::select(&FILES[..]), changes).prop_map(|(path, changes)| {
+        let mut bytes = std::fs::read(path).unwrap();
+        for (index, byte) in changes {
+            let i = index.index(bytes.len());
+            bytes[i] = byte;
+        }
+        bytes
+    })
+}
+
+proptest! {
+    #[test]
+    fn parse_record_never_panics(
+        shape_type in shape_type_code(),
+        bytes in proptest::collection::vec(any::<u8>(), 0..512),
+    ) {
+        let _ = parse_record(shape_type, &bytes);
+    }
+
+    #[test]
+    fn parse_record_with_invalid_counts_never_panics(
+        shape_type in proptest::sample::select(&SHAPE_TYPE_CODES[..]),
+        content in multipart_content(),
+    ) {
+        let _ = parse_record(shape_type, &content);
+    }
+
+    #[test]
+    fn parse_header_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..128)) {
+        let _ = parse_header(&bytes);
+    }
+
+    #[test]
+    fn parse_shape_buffer_never_panics(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+        let _ = parse_shape_buffer(&bytes);
+    }
+
+    #[test]
+    fn parse_corrupted_file_never_panics(bytes in corrupted_file()) {
+        let _ = parse_shape_buffer(&bytes);
+    }
+
+    #[test]
+    fn parse_corrupted_index_never_panics(
+        changes in proptest::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..16),
+    ) {
+        let shp = std::fs::read("tests/data/line.shp").unwrap();
+        let mut shx = std::fs::read("tests/data/line.shx").unwrap();
+        for (index, byte) in changes {
+            let i = index.index(shx.len());
+            shx[i] = byte;
+        }
+        let _ = parse_index(&shp, &shx);
+    }
+}
+
+#[test]
+fn negative_counts_are_errors() {
+    for &(num_parts, num_points) in &[(-1, 0), (0, -1), (i32::MIN, i32::MIN)] {
+        let mut content = vec![0u8; 32];
+        content.write_i32::<LittleEndian>(num_parts).unwrap();
+        content.write_i32::<LittleEndian>(num_points).unwrap();
+        for &shape_type in &[ShapeType::Polyline, ShapeType::PolygonZ, ShapeType::Multipatch] {
+            match parse_record(shape_type as i32, &content) {
+                Err(Error::InvalidShapeRecordSize) => {}
+                _ => panic!("expected an InvalidShapeRecordSize error"),
+            }
+        }
+    }
+}
+
+#[test]
+fn huge_counts_are_not_allocated() {
+    // A record claiming i32::MAX points holds only one
+    let mut content = vec![0u8; 32];
+    content.write_i32::<LittleEndian>(i32::MAX).unwrap();
+    content.extend(vec![0u8; 16]);
+    assert!(parse_record(ShapeType::Multipoint as i32, &content).is_err());
+
+    // The content length of the record matches the number of points (2 GiB of points),
+    // reading it must fail at the end of the file without allocating for all the points
+    let num_points = 1i32 << 27;
+    let mut shp = std::fs::read("tests/data/multipoint.shp").unwrap();
+    let content_length = 20 + 8 * num_points;
+    shp[104..108].copy_from_slice(&content_length.to_be_bytes());
+    (&mut shp[108 + 36..]).write_i32::<LittleEndian>(num_points).unwrap();
+    assert!(parse_shape_buffer(&shp).is_err());
+}
+
+#[test]
+fn invalid_index_entries_are_errors() {
+    let shp = std::fs::read("tests/data/line.shp").unwrap();
+    let shx = std::fs::read("tests/data/line.shx").unwrap();
+    assert_eq!(parse_index(&shp, &shx).unwrap().len(), 1);
+
+    // A negative content length, an offset within the header of the .shp,
+    // and a record ending after the end of the .shp
+    for &(offset, size) in &[(50, -1i32), (-4, 82), (40, 82), (i32::MAX, 82), (50, 83)] {
+        let mut shx = shx.clone();
+        shx[100..104].copy_from_slice(&offset.to_be_bytes());
+        shx[104..108].copy_from_slice(&size.to_be_bytes());
+        match parse_index(&shp, &shx) {
+            Err(Error::InvalidIndexEntry { index: 0, .. }) => {}
+            _ => panic!("expected an InvalidIndexEntry error for {:?}", (offset, size)),
+        }
+    }
+
+    // A header claiming about 2 GB of entries, the file only has one
+    let mut shx = shx.clone();
+    shx[24..28].copy_from_slice(&0x7000_0000i32.to_be_bytes());
+    match parse_index(&shp, &shx) {
+        Err(Error::TruncatedFile { actual_bytes: 108, .. }) => {}
+        _ => panic!("expected a TruncatedFile error"),
+    }
+}
@@ -0,0 +1,197 @@
+//! Compares the bytes written by a deterministic writer to the golden files of tests/data/golden.
+//!
+//! Run with `cargo test --features golden-tests`, the golden files can be generated again
+//! by setting the `SHAPEFILE_BLESS_GOLDEN` environment variable.
+//!
+//! The golden files are written by this writer, so their layout is also checked here
+//! against the ESRI specification, without the reader of the crate (see `check_layout`).
+//! They have not been checked with GDAL or shapelib: after generating them again,
+//! check them with `ogrinfo -al tests/data/golden/<name>.shp` or `shpdump` before
+//! committing them.
+#![cfg(feature = "golden-tests")]
+extern crate shapefile;
+
+use std::convert::TryInto;
+use std::path::Path;
+
+use shapefile::record::EsriShape;
+use shapefile::{Multipatch, Multipoint, MultipointM, MultipointZ, PatchType};
+use shapefile::{Point, PointM, PointZ, NO_DATA};
+use shapefile::{Polygon, PolygonM, PolygonZ, Polyline, PolylineM, PolylineZ};
+use shapefile::ShapeType;
+
+const GOLDEN_DIR: &str = "tests/data/golden";
+
+fn write_in_memory<S: EsriShape>(shapes: Vec<S>) -> (Vec<u8>, Vec<u8>) {
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp).deterministic(true);
+        writer.add_index_dest(&mut shx);
+        writer.write_shapes(shapes).unwrap();
+    }
+    (shp, shx)
+}
+
+fn check_golden(name: &str, bytes: &[u8]) {
+    let path = Path::new(GOLDEN_DIR).join(name);
+    if std::env::var_os("SHAPEFILE_BLESS_GOLDEN").is_some() {
+        std::fs::create_dir_all(GOLDEN_DIR).unwrap();
+        std::fs::write(&path, bytes).unwrap();
+        return;
+    }
+    let expected = std::fs::read(&path).unwrap();
+    assert_eq!(bytes.len(), expected.len(), "{}", path.display());
+    if let Some(pos) = bytes.iter().zip(&expected).position(|(a, b)| a != b) {
+        panic!("{} differs at byte {}", path.display(), pos);
+    }
+}
+
+fn be_i32(bytes: &[u8], pos: usize) -> i32 {
+    i32::from_be_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+fn le_i32(bytes: &[u8], pos: usize) -> i32 {
+    i32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+/// Checks the header of a .shp or .shx of the `shape_type`, in which the unused bytes
+/// and the ranges of the dimensions the shapes do not have must be zeros
+fn check_header(bytes: &[u8], shape_type: ShapeType) {
+    assert_eq!(be_i32(bytes, 0), 9994);
+    assert_eq!(&bytes[4..24], &[0u8; 20]);
+    assert_eq!(be_i32(bytes, 24) as usize * 2, bytes.len());
+    assert_eq!(le_i32(bytes, 28), 1000);
+    assert_eq!(le_i32(bytes, 32), shape_type as i32);
+    // Multipatches have z values and optional measures
+    let is_multipatch = shape_type == ShapeType::Multipatch;
+    if !shape_type.has_z() && !is_multipatch {
+        assert_eq!(&bytes[68..84], &[0u8; 16]);
+    }
+    if !shape_type.has_m() && !is_multipatch {
+        assert_eq!(&bytes[84..100], &[0u8; 16]);
+    }
+}
+
+/// Checks the layout of the golden files as given by the ESRI specification:
+/// the headers, the numbers of the records (from 1), their shape type,
+/// and the entries of the .shx, which give the offset and size (in 16-bit words)
+/// of each record of the .shp
+fn check_layout(name: &str, shape_type: ShapeType) {
+    let shp = std::fs::read(Path::new(GOLDEN_DIR).join(format!("{}.shp", name))).unwrap();
+    let shx = std::fs::read(Path::new(GOLDEN_DIR).join(format!("{}.shx", name))).unwrap();
+    check_header(&shp, shape_type);
+    check_header(&shx, shape_type);
+    assert_eq!(&shp[32..100], &shx[32..100], "{}", name);
+
+    let mut pos = 100;
+    let mut entries = shx[100..].chunks(8);
+    let mut record_number = 1;
+    while pos < shp.len() {
+        let content_length = be_i32(&shp, pos + 4) as usize * 2;
+        assert_eq!(be_i32(&shp, pos), record_number, "{}", name);
+        assert_eq!(le_i32(&shp, pos + 8), shape_type as i32, "{}", name);
+        let entry = entries.next().unwrap();
+        assert_eq!(be_i32(entry, 0) as usize * 2, pos, "{}", name);
+        assert_eq!(be_i32(entry, 4) as usize * 2, content_length, "{}", name);
+        pos += 8 + content_length;
+        record_number += 1;
+    }
+    assert_eq!(pos, shp.len(), "{}", name);
+    assert!(entries.next().is_none(), "{}", name);
+}
+
+fn check_shapes<S: EsriShape + Clone>(name: &str, shapes: Vec<S>) {
+    let (shp, shx) = write_in_memory(shapes.clone());
+    check_golden(&format!("{}.shp", name), &shp);
+    check_golden(&format!("{}.shx", name), &shx);
+    check_layout(name, S::shapetype());
+
+    // Two runs give the same bytes
+    assert_eq!(write_in_memory(shapes), (shp, shx));
+}
+
+fn square() -> Vec<(f64, f64)> {
+    vec![(0.0, 0.0), (0.0, 2.0), (2.0, 2.0), (2.0, 0.0), (0.0, 0.0)]
+}
+
+fn line() -> Vec<(f64, f64)> {
+    vec![(-1.5, 3.25), (0.5, -2.0), (4.0, 1.0)]
+}
+
+fn points(coords: Vec<(f64, f64)>) -> Vec<Point> {
+    coords.into_iter().map(|(x, y)| Point::new(x, y)).collect()
+}
+
+fn points_m(coords: Vec<(f64, f64)>) -> Vec<PointM> {
+    coords
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y))| PointM::new(x, y, if i == 1 { NO_DATA } else { i as f64 * 10.0 }))
+        .collect()
+}
+
+fn points_z(coords: Vec<(f64, f64)>) -> Vec<PointZ> {
+    coords
+        .into_iter()
+        .enumerate()
+        .map(|(i, (x, y))| PointZ::new(x, y, 100.0 + i as f64, i as f64 * 10.0))
+        .collect()
+}
+
+#[test]
+fn golden_points() {
+    check_shapes("point", points(line()));
+    check_shapes("pointm", points_m(line()));
+    check_shapes("pointz", points_z(line()));
+}
+
+#[test]
+fn golden_multipoints() {
+    check_shapes("multipoint", vec![Multipoint::new(points(line()))]);
+    check_shapes("multipointm", vec![MultipointM::new(points_m(line()))]);
+    check_shapes("multipointz", vec![MultipointZ::new(points_z(line()))]);
+}
+
+#[test]
+fn golden_polylines() {
+    let mut coords = line();
+    coords.extend(square());
+    check_shapes("polyline", vec![Polyline::new(points(coords.clone()), vec![0, 3])]);
+    check_shapes("polylinem", vec![PolylineM::new(points_m(coords.clone()), vec![0, 3])]);
+    check_shapes("polylinez", vec![PolylineZ::new(points_z(coords), vec![0, 3])]);
+}
+
+#[test]
+fn golden_polygons() {
+    check_shapes("polygon", vec![Polygon::new(points(square()), vec![0])]);
+    check_shapes("polygonm", vec![PolygonM::new(points_m(square()), vec![0])]);
+    check_shapes("polygonz", vec![PolygonZ::new(points_z(square()), vec![0])]);
+}
+
+#[test]
+fn golden_multipatch() {
+    let fan = points_z(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)]);
+    let multipatch = Multipatch::from_patches(vec![
+        (PatchType::TriangleFan, fan),
+        (PatchType::OuterRing, points_z(square())),
+    ])
+    .unwrap();
+    check_shapes("multipatch", vec![multipatch]);
+}
+
+#[test]
+fn deterministic_writer_canonicalizes_values() {
+    let canonical = vec![
+        PointZ::new(0.0, 1.0, 0.0, NO_DATA),
+        PointZ::new(2.0, 0.0, 3.0, NO_DATA),
+    ];
+    let other_encodings = vec![
+        PointZ::new(-0.0, 1.0, -0.0, f64::NAN),
+        PointZ::new(2.0, -0.0, 3.0, -1e300),
+    ];
+    assert_eq!(
+        write_in_memory(vec![MultipointZ::new(canonical)]),
+        write_in_memory(vec![MultipointZ::new(other_encodings)])
+    );
+}
@@ -0,0 +1,81 @@
+extern crate shapefile;
+
+use shapefile::header::RecordCount;
+use shapefile::ShapeType;
+
+fn fixtures() -> Vec<std::path::PathBuf> {
+    let mut paths = std::fs::read_dir("tests/data")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "shp"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn read_header_matches_reader() {
+    let paths = fixtures();
+    assert!(paths.len() > 10);
+    for path in paths {
+        let header = shapefile::read_header(&path).unwrap();
+        let reader = shapefile::Reader::from_path(&path).unwrap();
+        assert!(header == *reader.header(), "{}", path.display());
+
+        let num_shapes = reader.read().unwrap().len();
+        let shx_len = std::fs::metadata(path.with_extension("shx"))
+            .ok()
+            .map(|metadata| metadata.len());
+        match shapefile::estimate_record_count(&header, shx_len) {
+            RecordCount::Exact(count) => assert_eq!(count, num_shapes, "{}", path.display()),
+            RecordCount::Estimated(count) => {
+                assert!(count >= num_shapes, "{}", path.display());
+                if header.shape_type == ShapeType::Point {
+                    assert_eq!(count, num_shapes, "{}", path.display());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn header_bbox_contains_the_shapes() {
+    let reader = shapefile::Reader::from_path("tests/data/pointz.shp").unwrap();
+    let header = *reader.header();
+    let bbox = header.bbox();
+    let [zmin, zmax] = header.z_range();
+    let points = reader.read_as::<shapefile::PointZ>().unwrap();
+    assert!(!points.is_empty());
+    for point in points {
+        assert!(bbox.xmin <= point.x && point.x <= bbox.xmax);
+        assert!(bbox.ymin <= point.y && point.y <= bbox.ymax);
+        assert!(zmin <= point.z && point.z <= zmax);
+    }
+}
+
+#[test]
+fn read_header_of_invalid_files() {
+    match shapefile::read_header("tests/data/multipatch.dbf") {
+        Err(shapefile::Error::InvalidFileCode(_)) => {}
+        _ => panic!("Expected InvalidFileCode"),
+    }
+    assert!(shapefile::read_header("tests/data/does_not_exist.shp").is_err());
+}
+
+#[test]
+fn estimate_record_count_of_header() {
+    let mut header = shapefile::header::Header {
+        shape_type: ShapeType::Point,
+        file_length: (100 + 5 * 28) / 2,
+        ..Default::default()
+    };
+    assert_eq!(shapefile::estimate_record_count(&header, None), RecordCount::Estimated(5));
+    assert_eq!(
+        shapefile::estimate_record_count(&header, Some(100 + 3 * 8)),
+        RecordCount::Exact(3)
+    );
+    assert_eq!(RecordCount::Exact(3).value(), 3);
+
+    header.file_length = 0;
+    assert_eq!(shapefile::estimate_record_count(&header, None), RecordCount::Estimated(0));
+}
@@ -0,0 +1,152 @@
+extern crate shapefile;
+
+use std::fs::File;
+use std::sync::{Arc, Mutex};
+
+use shapefile::{Point, Progress};
+
+mod testfiles;
+
+fn collector() -> (Arc<Mutex<Vec<Progress>>>, impl FnMut(Progress) + Send + 'static) {
+    let reports = Arc::new(Mutex::new(Vec::<Progress>::new()));
+    let reports_clone = Arc::clone(&reports);
+    (reports, move |progress| {
+        reports_clone.lock().unwrap().push(progress)
+    })
+}
+
+fn assert_monotonic(reports: &[Progress]) {
+    for pair in reports.windows(2) {
+        assert!(pair[0].records_done < pair[1].records_done);
+        assert!(pair[0].bytes_done < pair[1].bytes_done);
+    }
+}
+
+fn write_points(dir_name: &str, num_points: usize) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("points.shp");
+    let points = (0..num_points)
+        .map(|i| Point::new(i as f64, 0.0))
+        .collect::<Vec<Point>>();
+    let mut writer = shapefile::Writer::new(File::create(&shp_path).unwrap());
+    writer.add_index_dest(File::create(dir.join("points.shx")).unwrap());
+    writer.write_shapes(points).unwrap();
+    shp_path
+}
+
+#[test]
+fn read_progress_with_index() {
+    let shp_path = write_points("shapefile_read_progress_with_index", 10);
+    let (reports, callback) = collector();
+    let reader = shapefile::Reader::from_path(&shp_path)
+        .unwrap()
+        .with_progress_every(3, callback);
+    let points = reader.read_as::<Point>().unwrap();
+    assert_eq!(points.len(), 10);
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports.iter().map(|p| p.records_done).collect::<Vec<_>>(),
+        vec![3, 6, 9, 10]
+    );
+    assert_monotonic(&reports);
+    assert!(reports.iter().all(|p| p.records_total == Some(10)));
+    let last = reports.last().unwrap();
+    assert_eq!(last.bytes_total, Some(100 + 10 * 28));
+    assert_eq!(Some(last.bytes_done), last.bytes_total);
+}
+
+#[test]
+fn read_progress_without_index() {
+    let (reports, callback) = collector();
+    let file = File::open(testfiles::POINTZ_PATH).unwrap();
+    let reader = shapefile::Reader::new(file).unwrap().with_progress(callback);
+    let shapes = reader.read().unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), shapes.len());
+    assert_monotonic(&reports);
+    assert!(reports.iter().all(|p| p.records_total.is_none()));
+    assert_eq!(reports.last().unwrap().bytes_total, Some(188));
+}
+
+#[test]
+fn write_progress() {
+    let (reports, callback) = collector();
+    let points = (0..5)
+        .map(|i| Point::new(i as f64, 0.0))
+        .collect::<Vec<Point>>();
+    let mut shp = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp).with_progress_every(2, callback);
+        writer.write_shapes(points.clone()).unwrap();
+    }
+
+    let expected = [2, 4, 5]
+        .iter()
+        .map(|&n| Progress {
+            records_done: n,
+            records_total: Some(5),
+            bytes_done: 100 + n as u64 * 28,
+            bytes_total: Some(240),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(*reports.lock().unwrap(), expected);
+
+    // The same is reported when streaming the shapes
+    let (reports, callback) = collector();
+    let mut shp = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp).with_progress_every(2, callback);
+        writer.write_shapes_streamed(points).unwrap();
+    }
+    assert_eq!(*reports.lock().unwrap(), expected);
+}
+
+#[test]
+fn write_all_from_progress() {
+    let (reports, callback) = collector();
+    let points = (0..10).map(|i| Point::new(i as f64, 0.0));
+    let mut writer = shapefile::Writer::new(std::io::Cursor::new(Vec::<u8>::new()))
+        .with_progress_every(4, callback);
+    writer.write_all_from(points).unwrap();
+
+    let reports = reports.lock().unwrap();
+    assert_eq!(
+        reports.iter().map(|p| p.records_done).collect::<Vec<_>>(),
+        vec![4, 8, 10]
+    );
+    assert_monotonic(&reports);
+    assert!(reports.iter().all(|p| p.records_total.is_none() && p.bytes_total.is_none()));
+    assert_eq!(reports.last().unwrap().bytes_done, 100 + 10 * 28);
+}
+
+#[test]
+fn progress_callbacks_fire_once_per_record() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    let shp_path = write_points("shapefile_progress_callbacks", 25);
+
+    let num_calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&num_calls);
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    reader.set_progress_callback(Box::new(move |progress| {
+        let previous = counter.fetch_add(1, Ordering::SeqCst);
+        assert_eq!(progress.records_done, previous + 1);
+        assert_eq!(progress.bytes_total, Some(100 + 25 * 28));
+    }));
+    let points = reader.read_as::<Point>().unwrap();
+    assert_eq!(num_calls.load(Ordering::SeqCst), 25);
+
+    let num_calls = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&num_calls);
+    let mut shp = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp);
+        writer.set_progress_callback(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+        writer.write_shapes(points).unwrap();
+    }
+    assert_eq!(num_calls.load(Ordering::SeqCst), 25);
+}
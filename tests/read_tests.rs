@@ -1,3 +1,4 @@
+extern crate dbase;
 extern crate shapefile;
 
 use std::io::Cursor;
@@ -7,12 +8,21 @@ use std::io::SeekFrom;
 
 mod testfiles;
 
-use shapefile::record::{MultipartShape, MultipointShape};
-use shapefile::{Multipatch, Point, PointM, PointZ};
-use shapefile::{Multipoint, MultipointZ};
-use shapefile::{Polygon, PolygonM, PolygonZ};
+use shapefile::record::{BBox, MultipartShape, MultipointShape};
+use shapefile::{Multipatch, Point, PointM, PointZ, NO_DATA};
+use shapefile::{Multipoint, MultipointM, MultipointZ};
+use shapefile::PolygonM;
+use shapefile::reader::ErrorPolicy;
 use shapefile::{Polyline, PolylineM, PolylineZ};
 
+/// Returns the error wrapped in the `RecordError` of the record with the `record_number`
+fn record_error_source(error: shapefile::Error, record_number: i32) -> shapefile::Error {
+    match error {
+        shapefile::Error::RecordError(e) if e.record_number == record_number => e.error,
+        e => panic!("Expected a RecordError for the record {}, got {}", record_number, e),
+    }
+}
+
 fn check_line<T: Read>(reader: shapefile::Reader<T>) {
     {
         let header = reader.header();
@@ -29,7 +39,7 @@ fn check_line<T: Read>(reader: shapefile::Reader<T>) {
     match &shapes[0] {
         shapefile::record::Shape::Polyline(_poly) => {}
         _ => {
-            assert!(false);
+            panic!();
         }
     }
 
@@ -37,7 +47,6 @@ fn check_line<T: Read>(reader: shapefile::Reader<T>) {
 }
 
 fn check_linem<T: Read>(reader: shapefile::Reader<T>) {
-    use shapefile::NO_DATA;
     {
         let header = reader.header();
         assert_eq!(header.file_length, 172);
@@ -95,7 +104,7 @@ fn check_linem<T: Read>(reader: shapefile::Reader<T>) {
         ];
         assert_eq!(shape.points(), expected_points.as_slice());
     } else {
-        assert!(false, "The shape is not a PolylineM");
+        panic!("The shape is not a PolylineM");
     }
 }
 
@@ -182,7 +191,7 @@ fn check_linez<T: Read>(reader: shapefile::Reader<T>) {
         //assert_eq!(shp.z_range, [0.0, 22.0]);
         //assert_eq!(shp.m_range, [0.0, 3.0]);
         } else {
-            assert!(false, "The shape is not a PolylineZ");
+            panic!("The shape is not a PolylineZ");
         }
     }
 }
@@ -220,7 +229,7 @@ pub fn check_first_point_m(shape: &shapefile::Shape) {
     if let shapefile::Shape::PointM(shp) = shape {
         _check_first_point_m(shp);
     } else {
-        assert!(false, "The first shape is not a PointZ");
+        panic!("The first shape is not a PointZ");
     }
 }
 
@@ -234,7 +243,7 @@ pub fn check_second_point_m(shape: &shapefile::Shape) {
     if let shapefile::Shape::PointM(shp) = shape {
         _check_second_point_m(shp);
     } else {
-        assert!(false, "The second shape is not a PointZ");
+        panic!("The second shape is not a PointZ");
     }
 }
 
@@ -295,16 +304,42 @@ fn check_pointz<T: Read>(reader: shapefile::Reader<T>) {
     if let shapefile::Shape::PointZ(shp) = &shapes[0] {
         _check_first_point_z(shp);
     } else {
-        assert!(false, "The first shape is not a PointZ");
+        panic!("The first shape is not a PointZ");
     }
 
     if let shapefile::Shape::PointZ(shp) = &shapes[1] {
         _check_second_point_z(shp);
     } else {
-        assert!(false, "The second shape is not a PointZ");
+        panic!("The second shape is not a PointZ");
+    }
+}
+
+fn check_pointm_no_m<T: Read>(reader: shapefile::Reader<T>) {
+    assert_eq!(reader.header().file_length, 78);
+    let points = reader.read_as::<PointM>().unwrap();
+    assert_eq!(points.len(), 2, "Wrong number of shapes");
+    assert_eq!(
+        points,
+        vec![
+            PointM::new(160477.9000324604, 5403959.561417906, NO_DATA),
+            PointM::new(160467.63787299366, 5403971.985031904, NO_DATA),
+        ]
+    );
+}
+
+fn check_pointz_no_m<T: Read>(reader: shapefile::Reader<T>) {
+    {
+        let header = reader.header();
+        assert_eq!(header.file_length, 86);
+        assert_eq!(header.shape_type, shapefile::ShapeType::PointZ);
     }
+    let points = reader.read_as::<PointZ>().unwrap();
+    assert_eq!(points.len(), 2, "Wrong number of shapes");
+    _check_first_point_z(&points[0]);
+    _check_second_point_z(&points[1]);
 }
 
+#[allow(dead_code)]
 fn check_polygon<T: Read>(reader: shapefile::Reader<T>) {
     {
         let header = reader.header();
@@ -334,7 +369,7 @@ fn check_polygon<T: Read>(reader: shapefile::Reader<T>) {
         assert_eq!(shp.points(), expected_points.as_slice());
         assert_eq!(shp.parts_indices(), vec![0, 5, 8].as_slice());
     } else {
-        assert!(false, "The second shape is not a Polygon");
+        panic!("The second shape is not a Polygon");
     }
 }
 
@@ -384,10 +419,11 @@ fn check_polygonm<T: Read>(reader: shapefile::Reader<T>) {
         assert_eq!(shp.points(), expected_points.as_slice());
         assert_eq!(shp.parts_indices(), vec![0].as_slice());
     } else {
-        assert!(false, "The second shape is not a PolygonZ");
+        panic!("The second shape is not a PolygonZ");
     }
 }
 
+#[allow(dead_code)]
 fn check_polygonz<T: Read>(reader: shapefile::Reader<T>) {
     {
         let header = reader.header();
@@ -406,7 +442,7 @@ fn check_polygonz<T: Read>(reader: shapefile::Reader<T>) {
         //assert_eq!(shp.m_range, [shapefile::NO_DATA, shapefile::NO_DATA]);
         assert_eq!(shp.parts_indices(), vec![0].as_slice());
     } else {
-        assert!(false, "The second shape is not a PolygonZ");
+        panic!("The second shape is not a PolygonZ");
     }
 }
 
@@ -426,7 +462,7 @@ fn check_multipoint<T: Read>(reader: shapefile::Reader<T>) {
         let expected_points = vec![Point { x: 122.0, y: 37.0 }, Point { x: 124.0, y: 32.0 }];
         assert_eq!(shp.points(), expected_points.as_slice());
     } else {
-        assert!(false, "Shape is not a Multipoint");
+        panic!("Shape is not a Multipoint");
     }
 }
 
@@ -478,10 +514,36 @@ fn check_multipointz<T: Read>(reader: shapefile::Reader<T>) {
         ];
         assert_eq!(shp.points(), expected_points.as_slice());
     } else {
-        assert!(false, "Shape is not a Multipoint");
+        panic!("Shape is not a Multipoint");
     }
 }
 
+fn check_multipointm_no_m<T: Read>(reader: shapefile::Reader<T>) {
+    assert_eq!(reader.header().file_length, 90);
+    let multipoints = reader.read_as::<MultipointM>().unwrap();
+    assert_eq!(multipoints.len(), 1, "Wrong number of shapes");
+
+    let expected_points = vec![
+        PointM::new(122.0, 37.0, NO_DATA),
+        PointM::new(124.0, 32.0, NO_DATA),
+    ];
+    assert_eq!(multipoints[0].points(), expected_points.as_slice());
+}
+
+fn check_multipointz_no_m<T: Read>(reader: shapefile::Reader<T>) {
+    assert_eq!(reader.header().file_length, 130);
+    let multipoints = reader.read_as::<MultipointZ>().unwrap();
+    assert_eq!(multipoints.len(), 1, "Wrong number of shapes");
+
+    let expected_points = vec![
+        PointZ::new(1422671.7232666016, 4188903.4295959473, 72.00995635986328, NO_DATA),
+        PointZ::new(1422672.1022949219, 4188903.4295959473, 72.0060806274414, NO_DATA),
+        PointZ::new(1422671.9127807617, 4188903.7578430176, 72.00220489501953, NO_DATA),
+        PointZ::new(1422671.9127807617, 4188903.539001465, 71.99445343017578, NO_DATA),
+    ];
+    assert_eq!(multipoints[0].points(), expected_points.as_slice());
+}
+
 fn check_multipatch<T: Read>(reader: shapefile::Reader<T>) {
     {
         let header = reader.header();
@@ -605,7 +667,7 @@ fn check_multipatch<T: Read>(reader: shapefile::Reader<T>) {
             ]
         );
     } else {
-        assert!(false, "Shape is not a Multipatch");
+        panic!("Shape is not a Multipatch");
     }
 }
 
@@ -660,6 +722,8 @@ read_test!(read_linez, check_linez, testfiles::LINEZ_PATH);
 read_test!(read_point, check_point, testfiles::POINT_PATH);
 read_test!(read_pointm, check_pointm, testfiles::POINTM_PATH);
 read_test!(read_pointz, check_pointz, testfiles::POINTZ_PATH);
+read_test!(read_pointm_no_m, check_pointm_no_m, testfiles::POINTM_NO_M_PATH);
+read_test!(read_pointz_no_m, check_pointz_no_m, testfiles::POINTZ_NO_M_PATH);
 
 /* Read tests on Polygon */
 //read_test!(read_polygon, check_polygon, testfiles::POLYGON_PATH);
@@ -677,6 +741,16 @@ read_test!(
     check_multipointz,
     testfiles::MULTIPOINTZ_PATH
 );
+read_test!(
+    read_multipointm_no_m,
+    check_multipointm_no_m,
+    testfiles::MULTIPOINTM_NO_M_PATH
+);
+read_test!(
+    read_multipointz_no_m,
+    check_multipointz_no_m,
+    testfiles::MULTIPOINTZ_NO_M_PATH
+);
 
 /* Read tests on Multipatch */
 read_test!(
@@ -760,7 +834,7 @@ read_write_read_test!(
 #[test]
 fn read_as_point() {
     let points = shapefile::read_as::<&str, shapefile::Point>(testfiles::POINT_PATH);
-    assert_eq!(points.is_ok(), true);
+    assert!(points.is_ok());
 
     let points = points.unwrap();
     assert_eq!(points.len(), 1);
@@ -770,7 +844,7 @@ fn read_as_point() {
 #[test]
 fn read_as_point_m() {
     let points_m = shapefile::read_as::<&str, shapefile::PointM>(testfiles::POINTM_PATH);
-    assert_eq!(points_m.is_ok(), true);
+    assert!(points_m.is_ok());
 
     let points_m = points_m.unwrap();
     assert_eq!(points_m.len(), 2);
@@ -792,17 +866,1667 @@ fn read_as_point_z() {
 #[test]
 fn read_point_as_wrong_type() {
     use shapefile::{Error, ShapeType};
-    let points = shapefile::read_as::<&str, shapefile::PointM>(testfiles::POINT_PATH);
+    let points = shapefile::read_as::<&str, shapefile::Point>(testfiles::POINTM_PATH);
 
     if let Err(error) = points {
         match error {
             Error::MismatchShapeType {
-                requested: ShapeType::PointM,
-                actual: ShapeType::Point,
+                requested: ShapeType::Point,
+                actual: ShapeType::PointM,
             } => {}
-            _ => assert!(false),
+            _ => panic!(),
         }
     } else {
-        assert!(false);
+        panic!();
+    }
+}
+
+#[test]
+fn read_write_read_pointz_no_m() {
+    // Files without the optional M are written back with it
+    let points = shapefile::read_as::<_, PointZ>(testfiles::POINTZ_NO_M_PATH).unwrap();
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    let mut cursor = writer.dest;
+    cursor.seek(SeekFrom::Start(0)).unwrap();
+
+    let reader = shapefile::Reader::new(cursor).unwrap();
+    check_pointz(reader);
+}
+
+/// Reads the file as S and writes it back, the shapes and the bbox read back must be
+/// the ones of the input, and the output must have the size of the input
+/// (the M values missing from the records of the input being left out)
+fn check_round_trip<S, P>(path: &str)
+where
+    S: shapefile::ReadableShape + shapefile::record::EsriShape + MultipointShape<P> + Clone,
+    P: PartialEq + std::fmt::Debug,
+{
+    let reader = shapefile::Reader::from_path(path).unwrap();
+    let header = *reader.header();
+    let shapes = reader.read_as::<S>().unwrap();
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(shapes.clone()).unwrap();
+    let written = writer.dest.into_inner();
+    assert_eq!(written.len() as u64, std::fs::metadata(path).unwrap().len());
+
+    let reader = shapefile::Reader::new(Cursor::new(written)).unwrap();
+    assert_eq!(reader.header().point_min, header.point_min);
+    assert_eq!(reader.header().point_max, header.point_max);
+    let written_shapes = reader.read_as::<S>().unwrap();
+    assert_eq!(written_shapes.len(), shapes.len());
+    for (written_shape, shape) in written_shapes.iter().zip(&shapes) {
+        assert_eq!(written_shape.points(), shape.points());
+        assert_eq!(written_shape.bbox(), shape.bbox());
+    }
+}
+
+#[test]
+fn round_trip_keeps_missing_measures() {
+    check_round_trip::<PolygonM, _>(testfiles::POLYGONM_NO_M_PATH);
+    check_round_trip::<MultipointM, _>(testfiles::MULTIPOINTM_NO_M_PATH);
+    check_round_trip::<MultipointZ, _>(testfiles::MULTIPOINTZ_NO_M_PATH);
+}
+
+/// Writes `n` points in memory, the point i being (i, i)
+fn points_in_memory(n: usize) -> Vec<u8> {
+    let points = (0..n)
+        .map(|i| Point::new(i as f64, i as f64))
+        .collect::<Vec<Point>>();
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    writer.dest.into_inner()
+}
+
+#[test]
+fn read_chunks_last_is_shorter() {
+    let reader = shapefile::Reader::new(Cursor::new(points_in_memory(10))).unwrap();
+    let chunks = reader
+        .read_chunks(4)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        chunks.iter().map(Vec::len).collect::<Vec<_>>(),
+        vec![4, 4, 2]
+    );
+
+    let reader = shapefile::Reader::new(Cursor::new(points_in_memory(4))).unwrap();
+    let chunks = reader
+        .read_chunks_as::<Point>(2)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[1], vec![Point::new(2.0, 2.0), Point::new(3.0, 3.0)]);
+}
+
+#[test]
+fn read_chunks_of_records_read_before_being_parsed() {
+    // A large record followed by smaller ones, the buffer of the content being reused
+    let sizes = [5_000, 2, 3, 4];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(lines_of_sizes(&sizes)).unwrap();
+    let reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner()))
+        .unwrap()
+        .keep_unknown_shapes(true);
+    fn assert_send<I: Send>(iter: I) -> I {
+        iter
+    }
+    let chunks = assert_send(reader.read_chunks_as::<Polyline>(3))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].capacity(), 3);
+    let polylines = chunks.into_iter().flatten().collect::<Vec<_>>();
+    for (polyline, expected) in polylines.iter().zip(lines_of_sizes(&sizes)) {
+        assert_eq!(polyline.points, expected.points);
+        assert_eq!(polyline.parts, expected.parts);
+    }
+}
+
+#[test]
+fn read_chunks_stops_at_error() {
+    let mut shp = points_in_memory(6);
+    // The shape type of the 4th record is changed to Polyline
+    let offset = 100 + 3 * 28 + 8;
+    shp[offset] = 3;
+
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    let mut chunks = reader.read_chunks_as::<Point>(2);
+    assert_eq!(chunks.next().unwrap().unwrap().len(), 2);
+    match chunks.next() {
+        Some(Err(shapefile::Error::MismatchShapeType { .. })) => {}
+        _ => panic!("Expected the second chunk to be an error"),
+    }
+    assert!(chunks.next().is_none());
+}
+
+#[test]
+fn read_chunks_with_records() {
+    let reader = shapefile::Reader::from_path(testfiles::MULTIPATCH_PATH).unwrap();
+    let chunks = reader
+        .read_chunks_with_records(10)
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 1);
+    assert_eq!(chunks[0].len(), 1);
+
+    let reader = shapefile::Reader::new(Cursor::new(points_in_memory(1))).unwrap();
+    match reader.read_chunks_with_records(10) {
+        Err(shapefile::Error::MissingDbf) => {}
+        _ => panic!("Expected MissingDbf"),
+    }
+}
+
+#[test]
+#[should_panic]
+fn read_chunks_of_zero_shapes() {
+    let reader = shapefile::Reader::new(Cursor::new(points_in_memory(1))).unwrap();
+    let _ = reader.read_chunks(0);
+}
+
+/// Returns the points written by `points_in_memory(n)`, with `padding` bytes
+/// added at the end of the record `index`
+fn points_with_padded_record(n: usize, index: usize, padding: usize) -> Vec<u8> {
+    let shp = points_in_memory(n);
+    let record_start = 100 + index * 28;
+    let mut padded = shp[..record_start + 28].to_vec();
+    padded.extend(vec![0u8; padding]);
+    padded.extend_from_slice(&shp[record_start + 28..]);
+    // content length and file length are in 16 bits words, big endian
+    padded[record_start + 7] += (padding / 2) as u8;
+    padded[27] += (padding / 2) as u8;
+    padded
+}
+
+#[test]
+fn read_record_larger_than_shape() {
+    let shp = points_with_padded_record(3, 1, 4);
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let mut shapes = reader.iter_shapes_as::<Point>();
+    assert_eq!(shapes.next().unwrap().unwrap(), Point::new(0.0, 0.0));
+    match shapes.next() {
+        Some(Err(shapefile::Error::InvalidShapeRecordSize)) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+    // The unread bytes of the record were skipped
+    assert_eq!(shapes.next().unwrap().unwrap(), Point::new(2.0, 2.0));
+}
+
+#[test]
+fn read_shape_larger_than_record() {
+    let mut shp = points_in_memory(2);
+    // The shape type of the 1st record is changed to Polyline,
+    // whose bbox alone is larger than the content of the record
+    shp[100 + 8] = 3;
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let mut shapes = reader.iter_shapes();
+    match shapes.next() {
+        Some(Err(shapefile::Error::IoError(ref e)))
+            if e.kind() == std::io::ErrorKind::UnexpectedEof => {}
+        _ => panic!("Expected the polyline to not be read past its record"),
+    }
+    match shapes.next() {
+        Some(Ok(shapefile::Shape::Point(point))) => assert_eq!(point, Point::new(1.0, 1.0)),
+        _ => panic!("Expected the second point"),
+    }
+}
+
+/// Closed square ring, clockwise (an outer ring) or counterclockwise (a hole)
+fn square_ring(xmin: f64, ymin: f64, size: f64, clockwise: bool) -> Vec<Point> {
+    let mut ring = vec![
+        Point::new(xmin, ymin),
+        Point::new(xmin, ymin + size),
+        Point::new(xmin + size, ymin + size),
+        Point::new(xmin + size, ymin),
+        Point::new(xmin, ymin),
+    ];
+    if !clockwise {
+        ring.reverse();
+    }
+    ring
+}
+
+/// Polygon whose holes come before their outer rings, or are separated from them
+fn polygon_with_holes_first_in_memory() -> Vec<u8> {
+    let rings = [
+        square_ring(21.0, 1.0, 2.0, false), // hole of the 2nd outer ring
+        square_ring(0.0, 0.0, 10.0, true),
+        square_ring(20.0, 0.0, 10.0, true),
+        square_ring(1.0, 1.0, 2.0, false), // hole of the 1st outer ring
+        square_ring(5.0, 5.0, 2.0, false), // hole of the 1st outer ring
+    ];
+    let parts = (0..rings.len() as i32).map(|i| i * 5).collect();
+    let polygon = shapefile::Polygon::new(rings.concat(), parts);
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![polygon]).unwrap();
+    writer.dest.into_inner()
+}
+
+#[test]
+fn read_normalized_polygons() {
+    let shp = polygon_with_holes_first_in_memory();
+    let original = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<shapefile::Polygon>()
+        .unwrap()
+        .remove(0);
+    let first_points = |polygon: &shapefile::Polygon| {
+        polygon
+            .parts
+            .iter()
+            .map(|i| polygon.points[*i as usize])
+            .collect::<Vec<Point>>()
+    };
+    assert_eq!(
+        first_points(&original),
+        vec![
+            Point::new(21.0, 1.0),
+            Point::new(0.0, 0.0),
+            Point::new(20.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(5.0, 5.0)
+        ]
+    );
+
+    let shapes = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .normalize_polygons(true)
+        .read()
+        .unwrap();
+    let normalized = match shapes.into_iter().next() {
+        Some(shapefile::Shape::Polygon(polygon)) => polygon,
+        _ => panic!("Expected a polygon"),
+    };
+    assert_eq!(normalized.parts, vec![0, 5, 10, 15, 20]);
+    assert_eq!(
+        first_points(&normalized),
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(1.0, 1.0),
+            Point::new(5.0, 5.0),
+            Point::new(20.0, 0.0),
+            Point::new(21.0, 1.0)
+        ]
+    );
+    let mut original_rings = original.parts().map(<[Point]>::to_vec).collect::<Vec<_>>();
+    let mut normalized_rings = normalized.parts().map(<[Point]>::to_vec).collect::<Vec<_>>();
+    let by_first_point = |a: &Vec<Point>, b: &Vec<Point>| a[0].x.partial_cmp(&b[0].x).unwrap();
+    original_rings.sort_by(by_first_point);
+    normalized_rings.sort_by(by_first_point);
+    assert_eq!(original_rings, normalized_rings);
+}
+
+/// Returns a reader of the shapes written in memory, with a .dbf of `num_records` records
+fn features_in_memory(
+    shapes: Vec<shapefile::Shape>,
+    num_records: usize,
+) -> shapefile::Reader<Cursor<Vec<u8>>> {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(shapes).unwrap();
+    let records = (0..num_records)
+        .map(|i| {
+            let mut record = dbase::Record::new();
+            record.insert("ID".to_string(), dbase::FieldValue::from(i.to_string().as_str()));
+            record
+        })
+        .collect::<Vec<dbase::Record>>();
+    let dbf = dbase::Writer::new(Cursor::new(Vec::<u8>::new()))
+        .write(&records)
+        .unwrap();
+
+    let mut reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner())).unwrap();
+    reader.add_dbf_source(Cursor::new(dbf.into_inner())).unwrap();
+    reader
+}
+
+fn points_and_null() -> Vec<shapefile::Shape> {
+    vec![
+        shapefile::Shape::Point(Point::new(1.0, 1.0)),
+        shapefile::Shape::NullShape(shapefile::NullShape),
+        shapefile::Shape::Point(Point::new(3.0, 3.0)),
+    ]
+}
+
+#[test]
+fn read_as_with_records() {
+    let features = shapefile::Reader::from_path(testfiles::MULTIPATCH_PATH)
+        .unwrap()
+        .read_as_with_records::<Multipatch>()
+        .unwrap();
+    assert_eq!(features.len(), 1);
+
+    let points = (0..3).map(|i| shapefile::Shape::Point(Point::new(i as f64, 0.0))).collect();
+    let features = features_in_memory(points, 3)
+        .read_as_with_records::<Point>()
+        .unwrap();
+    let ids = features
+        .iter()
+        .map(|(_, record)| match record.get("ID") {
+            Some(dbase::FieldValue::Character(Some(id))) => id.clone(),
+            _ => panic!("Record has no ID"),
+        })
+        .collect::<Vec<String>>();
+    assert_eq!(ids, vec!["0", "1", "2"]);
+    assert_eq!(features[2].0, Point::new(2.0, 0.0));
+
+    let reader = shapefile::Reader::new(Cursor::new(points_in_memory(1))).unwrap();
+    match reader.read_as_with_records::<Point>() {
+        Err(shapefile::Error::MissingDbf) => {}
+        _ => panic!("Expected MissingDbf"),
+    }
+}
+
+#[test]
+fn read_as_with_records_null_shapes() {
+    // Null shapes are kept with their records when reading the Shape enum
+    let features = features_in_memory(points_and_null(), 3)
+        .read_as_with_records::<shapefile::Shape>()
+        .unwrap();
+    assert_eq!(features.len(), 3);
+    match features[1].0 {
+        shapefile::Shape::NullShape(_) => {}
+        _ => panic!("Expected a NullShape"),
+    }
+
+    // and are an error when reading a concrete type
+    let features = features_in_memory(points_and_null(), 3).read_as_with_records::<Point>();
+    match features.map_err(|e| record_error_source(e, 2)) {
+        Err(shapefile::Error::NullShapeRecord {
+            requested: shapefile::ShapeType::Point,
+        }) => {}
+        Err(e) => panic!("Expected NullShapeRecord, got {}", e),
+        Ok(_) => panic!("Expected NullShapeRecord"),
+    }
+}
+
+#[test]
+fn read_as_optional_null_shapes() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let source = Cursor::new(writer.dest.into_inner());
+
+    let points = shapefile::Reader::new(source.clone())
+        .unwrap()
+        .read_as_optional::<Point>()
+        .unwrap();
+    assert_eq!(
+        points,
+        vec![Some(Point::new(1.0, 1.0)), None, Some(Point::new(3.0, 3.0))]
+    );
+
+    let points = shapefile::Reader::new(source.clone()).unwrap().read_as::<Point>();
+    match points.map_err(|e| record_error_source(e, 2)) {
+        Err(shapefile::Error::NullShapeRecord {
+            requested: shapefile::ShapeType::Point,
+        }) => {}
+        Err(e) => panic!("Expected NullShapeRecord, got {}", e),
+        Ok(_) => panic!("Expected NullShapeRecord"),
+    }
+
+    // Other shape types are still an error
+    let polylines = shapefile::Reader::new(source).unwrap().read_as_optional::<Polyline>();
+    match polylines {
+        Err(shapefile::Error::MismatchShapeType {
+            requested: shapefile::ShapeType::Polyline,
+            actual: shapefile::ShapeType::Point,
+        }) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+}
+
+#[test]
+fn read_as_with_records_count_mismatch() {
+    let cases = [(3, 2), (3, 4), (1, 3)];
+    for &(num_shapes, num_records) in &cases {
+        let points = (0..num_shapes)
+            .map(|i| shapefile::Shape::Point(Point::new(i as f64, 0.0)))
+            .collect();
+        let reader = features_in_memory(points, num_records);
+        match reader.read_as_with_records::<Point>() {
+            Err(shapefile::Error::RecordCountMismatch {
+                num_shapes: s,
+                num_records: r,
+            }) => assert_eq!((s, r), (num_shapes, num_records)),
+            Err(e) => panic!("Expected RecordCountMismatch, got {}", e),
+            Ok(_) => panic!("Expected RecordCountMismatch"),
+        }
+    }
+
+    // The null shape is reported before the mismatch
+    let reader = features_in_memory(points_and_null(), 1);
+    match reader.read_as_with_records::<shapefile::Shape>() {
+        Err(shapefile::Error::RecordCountMismatch {
+            num_shapes: 3,
+            num_records: 1,
+        }) => {}
+        _ => panic!("Expected RecordCountMismatch"),
+    }
+}
+
+#[test]
+fn iter_as_converted() {
+    let reader = shapefile::Reader::from_path(testfiles::LINEZ_PATH).unwrap();
+    let polylines = reader
+        .iter_as_converted::<PolylineZ>()
+        .collect::<Result<Vec<PolylineZ>, _>>()
+        .unwrap();
+    assert_eq!(polylines.len(), 1);
+
+    // The file has PolylineM, the first record fails
+    let reader = shapefile::Reader::from_path(testfiles::LINEM_PATH).unwrap();
+    match reader.iter_as_converted::<Polyline>().next() {
+        Some(Err(error)) => match record_error_source(error, 1) {
+            shapefile::Error::MismatchShapeType {
+                requested: shapefile::ShapeType::Polyline,
+                actual: shapefile::ShapeType::PolylineM,
+            } => {}
+            e => panic!("Expected MismatchShapeType, got {}", e),
+        },
+        _ => panic!("Expected a RecordError"),
+    }
+    // The type of the header is checked once, the iteration ends even if it could continue
+    let mut reader = shapefile::Reader::from_path(testfiles::LINEM_PATH).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    assert_eq!(reader.iter_as_converted::<Polyline>().count(), 1);
+}
+
+#[test]
+fn iter_as_converted_continues_after_error() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let mut reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner())).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+
+    let results = reader.iter_as_converted::<Point>().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    match results[1] {
+        Err(shapefile::Error::RecordError(ref e)) if e.record_number == 2 => {}
+        _ => panic!("Expected the second record to fail"),
+    }
+    assert_eq!(results[2].as_ref().unwrap(), &Point::new(3.0, 3.0));
+}
+
+#[test]
+fn iter_as_converted_gives_the_number_of_the_record_header() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let mut shp = writer.dest.into_inner();
+    // The second record (the NullShape), after the header and the first point record
+    shp[100 + 28..100 + 32].copy_from_slice(&42i32.to_be_bytes());
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+
+    let results = reader.iter_as_converted::<Point>().collect::<Vec<_>>();
+    match results[1] {
+        Err(shapefile::Error::RecordError(ref e)) if e.record_number == 42 => {}
+        _ => panic!("Expected the record 42 to fail"),
+    }
+}
+
+/// Counts the bytes read from the inner source
+struct CountingReader<T> {
+    inner: T,
+    bytes_read: std::rc::Rc<std::cell::Cell<usize>>,
+}
+
+impl<T: Read> Read for CountingReader<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.set(self.bytes_read.get() + n);
+        Ok(n)
+    }
+}
+
+impl<T: Seek> Seek for CountingReader<T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+fn lines_of_sizes(sizes: &[usize]) -> Vec<Polyline> {
+    sizes
+        .iter()
+        .map(|size| {
+            let points = (0..*size).map(|i| Point::new(i as f64, 1.0)).collect();
+            let parts = if *size > 2 { vec![0, 2] } else { vec![0] };
+            Polyline::new(points, parts)
+        })
+        .collect()
+}
+
+#[test]
+fn iter_matching_only_reads_accepted_records() {
+    let sizes = [2, 5_000, 3, 4_000, 2];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(lines_of_sizes(&sizes)).unwrap();
+    let bytes_read = std::rc::Rc::new(std::cell::Cell::new(0usize));
+    let source = CountingReader {
+        inner: Cursor::new(writer.dest.into_inner()),
+        bytes_read: bytes_read.clone(),
+    };
+    let reader = shapefile::Reader::from_seekable(source).unwrap();
+
+    let mut previews = Vec::<shapefile::reader::RecordPreview>::new();
+    let shapes = reader
+        .iter_matching_as::<Polyline, _>(|preview| {
+            previews.push(*preview);
+            preview.num_parts > 1 && preview.num_points < 1_000
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+
+    assert_eq!(shapes.len(), 1);
+    assert_eq!(shapes[0].points(), lines_of_sizes(&[3])[0].points());
+    assert_eq!(previews.len(), sizes.len());
+    for (i, preview) in previews.iter().enumerate() {
+        assert_eq!(preview.record_number, i as i32 + 1);
+        assert_eq!(preview.shape_type, shapefile::ShapeType::Polyline);
+        assert_eq!(preview.num_points, sizes[i]);
+        assert_eq!(preview.bbox.xmax, (sizes[i] - 1) as f64);
+    }
+    // The header, the record headers with the previews, and the rest of the accepted record
+    let accepted_size = 2 * 4 + 3 * 16;
+    assert_eq!(bytes_read.get(), 100 + sizes.len() * (8 + 44) + accepted_size);
+}
+
+#[test]
+fn iter_matching_null_shapes_and_points() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner())).unwrap();
+
+    let mut previews = Vec::<shapefile::reader::RecordPreview>::new();
+    let shapes = reader
+        .iter_matching(|preview| {
+            previews.push(*preview);
+            preview.bbox.xmin > 2.0
+        })
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    assert_eq!(shapes[0].as_point(), Some(&Point::new(3.0, 3.0)));
+    assert_eq!(previews[0].num_points, 1);
+    assert_eq!(previews[1].shape_type, shapefile::ShapeType::NullShape);
+    assert_eq!(previews[1].num_points, 0);
+}
+
+#[test]
+fn iter_record_bboxes_does_not_read_the_points() {
+    let sizes = [2, 5_000, 3, 4_000, 2];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(lines_of_sizes(&sizes)).unwrap();
+    let shp = writer.dest.into_inner();
+    let bytes_read = std::rc::Rc::new(std::cell::Cell::new(0usize));
+    let source = CountingReader {
+        inner: Cursor::new(shp.clone()),
+        bytes_read: bytes_read.clone(),
+    };
+    let reader = shapefile::Reader::from_seekable(source).unwrap();
+
+    let bboxes = reader
+        .iter_record_bboxes()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(bboxes.len(), sizes.len());
+    for (i, &(record_number, bbox)) in bboxes.iter().enumerate() {
+        assert_eq!(record_number, i as i32 + 1);
+        assert_eq!(bbox, BBox::new(0.0, 1.0, (sizes[i] - 1) as f64, 1.0));
+    }
+    // The header, then the record headers with the shape types and the bboxes
+    assert_eq!(bytes_read.get(), 100 + sizes.len() * (8 + 36));
+
+    // Without seek, the points are read and discarded
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    let unseekable_bboxes = reader
+        .iter_record_bboxes()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(unseekable_bboxes, bboxes);
+}
+
+#[test]
+fn iter_record_bboxes_of_points_and_null_shapes() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner())).unwrap();
+
+    let bboxes = reader
+        .iter_record_bboxes()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(
+        bboxes,
+        vec![
+            (1, BBox::new(1.0, 1.0, 1.0, 1.0)),
+            (3, BBox::new(3.0, 3.0, 3.0, 3.0)),
+        ]
+    );
+}
+
+/// A source which seeks fail once `fails` is set
+struct FailingSeek<T> {
+    inner: T,
+    fails: std::rc::Rc<std::cell::Cell<bool>>,
+}
+
+impl<T: Read> Read for FailingSeek<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<T: Seek> Seek for FailingSeek<T> {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        if self.fails.get() {
+            return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "seek failed"));
+        }
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn iterators_return_the_error_of_the_seek_to_the_first_record() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let shp = writer.dest.into_inner();
+    let fails = std::rc::Rc::new(std::cell::Cell::new(false));
+    let new_reader = || {
+        fails.set(false);
+        let source = FailingSeek {
+            inner: Cursor::new(shp.clone()),
+            fails: fails.clone(),
+        };
+        let reader = shapefile::Reader::from_seekable(source).unwrap();
+        fails.set(true);
+        reader
+    };
+
+    let mut bboxes = new_reader().iter_record_bboxes();
+    assert!(matches!(bboxes.next(), Some(Err(shapefile::Error::IoError(_)))));
+    assert!(bboxes.next().is_none());
+    let mut shapes = new_reader().iter_shapes();
+    assert!(matches!(shapes.next(), Some(Err(shapefile::Error::IoError(_)))));
+    assert!(shapes.next().is_none());
+    let mut raw_records = new_reader().iter_raw_records();
+    assert!(matches!(raw_records.next(), Some(Err(shapefile::Error::IoError(_)))));
+    assert!(raw_records.next().is_none());
+    let mut matching = new_reader().iter_matching(|_| true);
+    assert!(matches!(matching.next(), Some(Err(shapefile::Error::IoError(_)))));
+    assert!(matching.next().is_none());
+}
+
+/// Points file in which the shape type of the second record is the one of a polyline
+fn points_with_bad_second_record() -> Vec<u8> {
+    let points = vec![Point::new(1.0, 1.0), Point::new(2.0, 2.0), Point::new(3.0, 3.0)];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    let mut shp = writer.dest.into_inner();
+    let second_record = 100 + 28;
+    shp[second_record + 8..second_record + 12].copy_from_slice(&3i32.to_le_bytes());
+    shp
+}
+
+#[test]
+fn iter_shapes_continues_after_bad_record() {
+    let mut reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_shapes_as::<Point>().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    match results[1] {
+        Err(shapefile::Error::MismatchShapeType { .. }) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+    assert_eq!(results[2].as_ref().unwrap(), &Point::new(3.0, 3.0));
+
+    // The content of the bad record is not a polyline
+    let mut reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_shapes().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    assert_eq!(results[2].as_ref().unwrap().as_point(), Some(&Point::new(3.0, 3.0)));
+}
+
+#[test]
+fn iterators_end_after_error() {
+    let reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    let mut shapes = reader.iter_shapes_as::<Point>();
+    assert_eq!(shapes.next().unwrap().unwrap(), Point::new(1.0, 1.0));
+    assert!(shapes.next().unwrap().is_err());
+    assert!(shapes.next().is_none());
+    assert!(shapes.next().is_none());
+    assert_eq!(shapes.size_hint(), (0, Some(0)));
+
+    let reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    let results = reader.iter_numbered_shapes().collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[1].is_err());
+
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner())).unwrap();
+    let results = reader.iter_as_converted::<Point>().collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[1].is_err());
+}
+
+#[test]
+fn shape_refs_error_policies() {
+    let shp = points_with_bad_second_record();
+    let policies = [
+        (ErrorPolicy::Fail, vec![true, false]),
+        (ErrorPolicy::Continue, vec![true, false, true]),
+        (ErrorPolicy::SkipMalformed, vec![true, true]),
+    ];
+    for (policy, expected) in policies.iter() {
+        let mut reader = shapefile::Reader::new(Cursor::new(&shp[..])).unwrap();
+        reader.set_error_policy(*policy);
+        let results = reader.iter_shape_refs().map(|r| r.is_ok()).collect::<Vec<_>>();
+        assert_eq!(&results, expected, "{:?}", policy);
+    }
+}
+
+#[test]
+fn iter_shapes_with_context_locates_bad_records() {
+    let mut reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_shapes_as::<Point>().with_context().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    match results[1] {
+        Err(shapefile::ReadError {
+            record_number: 2,
+            offset: 128,
+            error: shapefile::Error::MismatchShapeType { .. },
+        }) => {}
+        _ => panic!("Expected MismatchShapeType in the record 2"),
+    }
+    assert_eq!(results[2].as_ref().unwrap(), &Point::new(3.0, 3.0));
+
+    // The header of the third record is missing
+    let mut shp = points_with_bad_second_record();
+    shp.truncate(100 + 28 + 28 + 4);
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_shapes().with_context().take(10).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    match results[2] {
+        Err(shapefile::ReadError {
+            record_number: 3,
+            offset: 156,
+            ..
+        }) => {}
+        _ => panic!("Expected an error in the record 3"),
+    }
+}
+
+#[test]
+fn iter_shapes_of_truncated_file_ends() {
+    let mut shp = points_with_bad_second_record();
+    shp.truncate(100 + 28 + 8);
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_shapes().take(10).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    // The file ends within the second record, the iteration ends there
+    match results[1] {
+        Err(shapefile::Error::TruncatedFile {
+            expected_bytes: 184,
+            actual_bytes: 136,
+        }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+}
+
+#[test]
+fn iter_shapes_skips_malformed_records() {
+    let mut reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    reader.set_error_policy(ErrorPolicy::SkipMalformed);
+    // The content of the second record is too small for a polyline
+    let mut shapes = reader.iter_shapes();
+    let shapes_read = shapes.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+    let points = shapes_read
+        .into_iter()
+        .map(|shape| *shape.as_point().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(points, vec![Point::new(1.0, 1.0), Point::new(3.0, 3.0)]);
+    assert_eq!(shapes.skipped_records(), &[2]);
+}
+
+#[test]
+fn skip_malformed_does_not_skip_other_shape_types() {
+    let mut reader = shapefile::Reader::new(Cursor::new(points_with_bad_second_record())).unwrap();
+    reader.set_error_policy(ErrorPolicy::SkipMalformed);
+    let mut shapes = reader.iter_shapes_as::<Point>();
+    let results = shapes.by_ref().collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0].as_ref().unwrap(), &Point::new(1.0, 1.0));
+    match results[1] {
+        Err(shapefile::Error::MismatchShapeType { .. }) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+    // The iteration continues after the record of another type
+    assert_eq!(results[2].as_ref().unwrap(), &Point::new(3.0, 3.0));
+    assert!(shapes.skipped_records().is_empty());
+}
+
+#[test]
+fn skip_malformed_does_not_skip_unreadable_headers() {
+    let mut shp = points_with_bad_second_record();
+    shp.truncate(100 + 28 + 8);
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::SkipMalformed);
+    let mut shapes = reader.iter_shapes();
+    let results = shapes.by_ref().take(10).collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    // The file ends within the second record, which is not skipped
+    match results[1] {
+        Err(shapefile::Error::TruncatedFile { .. }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+    assert!(shapes.skipped_records().is_empty());
+}
+
+#[test]
+fn iter_numbered_shapes() {
+    let reader = shapefile::Reader::from_path(testfiles::POINT_PATH).unwrap();
+    let numbered_points = reader
+        .iter_numbered_shapes_as::<Point>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert!(!numbered_points.is_empty());
+    for (i, (number, _)) in numbered_points.iter().enumerate() {
+        assert_eq!(*number, i as i32 + 1);
+    }
+}
+
+#[test]
+fn iter_numbered_shapes_with_zero_based_numbers() {
+    let points = vec![Point::new(1.0, 1.0), Point::new(2.0, 2.0)];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    let mut shp = writer.dest.into_inner();
+    // Record numbers are big endian
+    shp[100..104].copy_from_slice(&0i32.to_be_bytes());
+    shp[100 + 28..100 + 32].copy_from_slice(&1i32.to_be_bytes());
+
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_numbered_shapes().collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    match results[0] {
+        Err(shapefile::Error::UnexpectedRecordNumber {
+            expected: 1,
+            number: 0,
+        }) => {}
+        _ => panic!("Expected UnexpectedRecordNumber"),
+    }
+    match results[1] {
+        Err(shapefile::Error::UnexpectedRecordNumber {
+            expected: 2,
+            number: 1,
+        }) => {}
+        _ => panic!("Expected UnexpectedRecordNumber"),
+    }
+}
+
+/// A source that can only be read forward, as stdin or a socket
+struct Stream<T>(T);
+
+impl<T: Read> Read for Stream<T> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<T> Seek for Stream<T> {
+    fn seek(&mut self, _pos: SeekFrom) -> std::io::Result<u64> {
+        panic!("A stream can not seek");
+    }
+}
+
+#[test]
+fn read_from_stream() {
+    let sizes = [2, 5_000, 3, 4_000, 2];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(lines_of_sizes(&sizes)).unwrap();
+    let shp = writer.dest.into_inner();
+
+    let reader = shapefile::Reader::new(Stream(shp.as_slice())).unwrap();
+    let polylines = reader.read_as::<Polyline>().unwrap();
+    assert_eq!(polylines.len(), sizes.len());
+
+    let reader = shapefile::Reader::new(Stream(shp.as_slice())).unwrap();
+    let bbox = shapefile::record::BBox::new(3_000.0, 0.0, 4_500.0, 2.0);
+    assert_eq!(reader.iter_shapes_in_bbox(bbox).count(), 2);
+
+    // The rejected records are read and discarded
+    let reader = shapefile::Reader::new(Stream(shp.as_slice())).unwrap();
+    let shapes = reader
+        .iter_matching_as::<Polyline, _>(|preview| preview.num_points > 2)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(shapes.len(), 3);
+    assert_eq!(shapes[1].points(), lines_of_sizes(&[3])[0].points());
+}
+
+#[test]
+fn read_empty_file() {
+    match shapefile::Reader::from_path(testfiles::EMPTY_PATH) {
+        Err(shapefile::Error::EmptyFile) => {}
+        _ => panic!("Expected EmptyFile"),
+    }
+    match shapefile::Reader::new(Cursor::new(Vec::<u8>::new())) {
+        Err(shapefile::Error::EmptyFile) => {}
+        _ => panic!("Expected EmptyFile"),
+    }
+    let header = std::fs::read(testfiles::HEADER_ONLY_PATH).unwrap();
+    match shapefile::Reader::new(&header[..60]) {
+        Err(shapefile::Error::TruncatedFile {
+            expected_bytes: 100,
+            actual_bytes: 60,
+        }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+}
+
+#[test]
+fn read_header_only_file() {
+    let reader = shapefile::Reader::from_path(testfiles::HEADER_ONLY_PATH).unwrap();
+    assert!(reader.has_attributes());
+    assert_eq!(reader.iter_shapes_and_records().unwrap().count(), 0);
+    let shapes = shapefile::read(testfiles::HEADER_ONLY_PATH).unwrap();
+    assert!(shapes.is_empty());
+
+    // The .dbf of an empty layer may have records, or be empty
+    let header = std::fs::read(testfiles::HEADER_ONLY_PATH).unwrap();
+    let dbf = std::fs::read("tests/data/multipatch.dbf").unwrap();
+    let mut reader = shapefile::Reader::new(Cursor::new(header)).unwrap();
+    reader.add_dbf_source(Cursor::new(dbf)).unwrap();
+    let results = reader.iter_shapes_and_records().unwrap().collect::<Vec<_>>();
+    match results.as_slice() {
+        [Err(shapefile::Error::RecordCountMismatch { num_shapes: 0, .. })] => {}
+        _ => panic!("Expected RecordCountMismatch"),
+    }
+
+    let dir = std::env::temp_dir().join("shapefile_read_header_only_file");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(testfiles::HEADER_ONLY_PATH, dir.join("empty_layer.shp")).unwrap();
+    std::fs::write(dir.join("empty_layer.dbf"), b"").unwrap();
+    let reader = shapefile::Reader::from_path(dir.join("empty_layer.shp")).unwrap();
+    assert!(!reader.has_attributes());
+    assert_eq!(reader.read().unwrap().len(), 0);
+}
+
+#[test]
+fn read_header_only_file_with_stale_length() {
+    match shapefile::Reader::from_path(testfiles::STALE_LENGTH_PATH) {
+        Err(shapefile::Error::TruncatedFile {
+            expected_bytes: 128,
+            actual_bytes: 100,
+        }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+
+    // A stream can not be checked when opened, the error comes with the first record
+    let header = std::fs::read(testfiles::STALE_LENGTH_PATH).unwrap();
+    let reader = shapefile::Reader::new(&header[..]).unwrap();
+    let results = reader.iter_shapes().collect::<Vec<_>>();
+    match results.as_slice() {
+        [Err(shapefile::Error::TruncatedFile {
+            expected_bytes: 128,
+            actual_bytes: 100,
+        })] => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+}
+
+#[test]
+fn reinterpret_polygons_as_polylines() {
+    let polygons = shapefile::Reader::from_path(testfiles::POLYGON_PATH)
+        .unwrap()
+        .read_as::<shapefile::Polygon>()
+        .unwrap();
+    let reader = shapefile::Reader::from_path(testfiles::POLYGON_PATH)
+        .unwrap()
+        .reinterpret_as(shapefile::ShapeType::Polyline)
+        .unwrap();
+    assert_eq!(reader.header().shape_type, shapefile::ShapeType::Polygon);
+    let polylines = reader.read_as::<Polyline>().unwrap();
+
+    assert_eq!(polylines.len(), polygons.len());
+    for (polyline, polygon) in polylines.iter().zip(&polygons) {
+        assert_eq!(polyline.points(), polygon.points());
+        assert_eq!(polyline.parts_indices(), polygon.parts_indices());
+        assert_eq!(polyline.bbox, polygon.bbox);
+    }
+
+    let shapes = shapefile::Reader::from_path(testfiles::POLYGON_PATH)
+        .unwrap()
+        .reinterpret_as(shapefile::ShapeType::Polyline)
+        .unwrap()
+        .read()
+        .unwrap();
+    assert!(shapes.iter().all(|shape| shape.shapetype() == shapefile::ShapeType::Polyline));
+}
+
+#[test]
+fn reinterpret_as_incompatible_type() {
+    let reader = shapefile::Reader::from_path(testfiles::POLYGON_PATH).unwrap();
+    match reader.reinterpret_as(shapefile::ShapeType::PolylineZ) {
+        Err(shapefile::Error::MismatchShapeType {
+            requested: shapefile::ShapeType::PolylineZ,
+            actual: shapefile::ShapeType::Polygon,
+        }) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+
+    let reader = shapefile::Reader::from_path(testfiles::POLYGONZ_PATH).unwrap();
+    assert!(reader.reinterpret_as(shapefile::ShapeType::PolylineZ).is_ok());
+    let reader = shapefile::Reader::from_path(testfiles::POLYGONZ_PATH).unwrap();
+    assert!(reader.reinterpret_as(shapefile::ShapeType::Multipatch).is_err());
+}
+
+#[test]
+fn read_multipatch_as_polygonz() {
+    let polygons = shapefile::Reader::from_path(testfiles::MULTIPATCH_PATH)
+        .unwrap()
+        .allow_multipatch_conversion(true)
+        .read_as::<shapefile::PolygonZ>()
+        .unwrap();
+    assert_eq!(polygons.len(), 1);
+    // A strip of 10 points and a fan of 6 points, each triangle giving an outer ring
+    let counts = polygons[0].ring_classification();
+    assert_eq!((counts.outer, counts.inner), (8 + 4, 0));
+    for ring in polygons[0].parts() {
+        assert_eq!(ring.len(), 4);
+        assert_eq!(ring[0], ring[3]);
+    }
+
+    match shapefile::Reader::from_path(testfiles::MULTIPATCH_PATH)
+        .unwrap()
+        .read_as::<shapefile::PolygonZ>()
+    {
+        Err(error @ shapefile::Error::MismatchShapeType { .. }) => {
+            assert!(error.to_string().contains("allow_multipatch_conversion"));
+        }
+        _ => panic!("Expected MismatchShapeType"),
+    }
+}
+
+#[test]
+fn read_multipatch_as_polygonz_conversion_error() {
+    let point = |x, y| PointZ::new(x, y, 0.0, NO_DATA);
+    let ring = vec![point(0.0, 0.0), point(1.0, 0.0), point(1.0, 1.0), point(0.0, 0.0)];
+    let valid = Multipatch::from_patches(vec![(shapefile::PatchType::OuterRing, ring.clone())]);
+    let orphan = Multipatch::from_patches(vec![(shapefile::PatchType::InnerRing, ring)]);
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![valid.unwrap(), orphan.unwrap()]).unwrap();
+
+    let reader = shapefile::Reader::new(Cursor::new(writer.dest.into_inner()))
+        .unwrap()
+        .allow_multipatch_conversion(true);
+    let results = reader.iter_shapes_as::<shapefile::PolygonZ>().collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    match results[1] {
+        Err(shapefile::Error::OrphanInnerRing) => {}
+        _ => panic!("Expected OrphanInnerRing"),
+    }
+}
+
+#[test]
+fn read_as_promotes_to_more_dimensions() {
+    let polylines = shapefile::read_as::<_, Polyline>(testfiles::LINE_PATH).unwrap();
+    let polylines_z = shapefile::read_as::<_, PolylineZ>(testfiles::LINE_PATH).unwrap();
+    assert_eq!(polylines_z.len(), polylines.len());
+    for (polyline_z, polyline) in polylines_z.iter().zip(&polylines) {
+        assert_eq!(polyline_z.parts_indices(), polyline.parts_indices());
+        for (point_z, point) in polyline_z.points().iter().zip(polyline.points()) {
+            assert_eq!((point_z.x, point_z.y), (point.x, point.y));
+            assert_eq!((point_z.z, point_z.m), (0.0, NO_DATA));
+        }
+    }
+    let polylines_m = shapefile::read_as::<_, PolylineM>(testfiles::LINE_PATH).unwrap();
+    assert!(polylines_m[0].points().iter().all(|p| p.m == NO_DATA));
+
+    let points = shapefile::read_as::<_, PointZ>(testfiles::POINT_PATH).unwrap();
+    assert!(!points.is_empty());
+    let polygons = shapefile::read_as::<_, shapefile::PolygonZ>(testfiles::POLYGON_PATH);
+    assert!(polygons.is_ok());
+    let multipoints = shapefile::read_as::<_, MultipointM>(testfiles::MULTIPOINT_PATH);
+    assert!(multipoints.is_ok());
+}
+
+#[test]
+fn read_as_does_not_demote() {
+    let polylines = shapefile::read_as::<_, Polyline>(testfiles::LINEZ_PATH);
+    match polylines {
+        Err(shapefile::Error::MismatchShapeType {
+            requested: shapefile::ShapeType::Polyline,
+            actual: shapefile::ShapeType::PolylineZ,
+        }) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+
+    let polylines_z = shapefile::read_as::<_, PolylineZ>(testfiles::LINEZ_PATH).unwrap();
+    let polylines = shapefile::Reader::from_path(testfiles::LINEZ_PATH)
+        .unwrap()
+        .read_as_lossy::<Polyline>()
+        .unwrap();
+    assert_eq!(polylines.len(), polylines_z.len());
+    for (polyline, polyline_z) in polylines.iter().zip(&polylines_z) {
+        let xy = polyline_z.points().iter().map(|p| Point::new(p.x, p.y)).collect::<Vec<_>>();
+        assert_eq!(polyline.points(), &xy[..]);
+    }
+
+    let reader = shapefile::Reader::from_path(testfiles::LINEZ_PATH).unwrap();
+    assert!(reader.read_as_lossy::<Multipoint>().is_err());
+}
+
+#[test]
+fn read_seekable_checks_file_length() {
+    let points = (0..5).map(|i| Point::new(i as f64, i as f64)).collect::<Vec<_>>();
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points.clone()).unwrap();
+    let shp = writer.dest.into_inner();
+
+    // The last record is missing
+    match shapefile::Reader::from_seekable(Cursor::new(shp[..shp.len() - 28].to_vec())) {
+        Err(shapefile::Error::TruncatedFile {
+            expected_bytes: 240,
+            actual_bytes: 212,
+        }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+
+    // Data after the end given by the header is not read
+    let mut with_garbage = shp.clone();
+    with_garbage.extend_from_slice(&[0xFF; 50]);
+    let reader = shapefile::Reader::from_seekable(Cursor::new(with_garbage)).unwrap();
+    assert_eq!(reader.read_as::<Point>().unwrap(), points);
+}
+
+/// Changes the content length of the only record of the `shp` by `delta` bytes,
+/// adding zeros at its end or removing its last bytes
+fn resize_only_record(mut shp: Vec<u8>, delta: i32) -> Vec<u8> {
+    let add_words = |bytes: &mut [u8], words: i32| {
+        let value = i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) + words;
+        bytes.copy_from_slice(&value.to_be_bytes());
+    };
+    add_words(&mut shp[24..28], delta / 2);
+    add_words(&mut shp[104..108], delta / 2);
+    let new_len = (shp.len() as i32 + delta) as usize;
+    shp.resize(new_len, 0);
+    shp
+}
+
+fn write_in_memory<S: shapefile::record::EsriShape>(shape: S) -> Vec<u8> {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![shape]).unwrap();
+    writer.dest.into_inner()
+}
+
+#[test]
+fn read_padded_records() {
+    let shp = points_with_padded_record(3, 1, 4);
+    let points = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .allow_padded_records(true)
+        .read_as::<Point>()
+        .unwrap();
+    let expected = (0..3).map(|i| Point::new(i as f64, i as f64)).collect::<Vec<_>>();
+    assert_eq!(points, expected);
+
+    let points = vec![
+        PointM::new(0.0, 0.0, 1.0),
+        PointM::new(1.0, 0.0, 2.0),
+        PointM::new(5.0, 5.0, 3.0),
+        PointM::new(6.0, 5.0, 4.0),
+    ];
+    let polyline = PolylineM::new(points.clone(), vec![0, 2]);
+    let padded = resize_only_record(write_in_memory(polyline), 12);
+    let reader = shapefile::Reader::new(Cursor::new(padded.clone()))
+        .unwrap()
+        .allow_padded_records(true);
+    let polylines = reader.read_as::<PolylineM>().unwrap();
+    assert_eq!(polylines.len(), 1);
+    assert_eq!(polylines[0].points(), &points[..]);
+    assert_eq!(polylines[0].parts_indices(), &[0, 2]);
+    let shapes = shapefile::Reader::new(Cursor::new(padded.clone()))
+        .unwrap()
+        .allow_padded_records(true)
+        .read()
+        .unwrap();
+    assert_eq!(shapes.len(), 1);
+    // Still an error by default
+    let reader = shapefile::Reader::new(Cursor::new(padded)).unwrap();
+    match reader.read_as::<PolylineM>().map_err(|e| record_error_source(e, 1)) {
+        Err(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+
+    // Without measures, the padding is not taken for them
+    let points = vec![PointM::new(0.0, 0.0, NO_DATA), PointM::new(1.0, 0.0, NO_DATA)];
+    let mut polyline = PolylineM::new(points.clone(), vec![0]);
+    polyline.set_m_present(false);
+    let padded = resize_only_record(write_in_memory(polyline), 8);
+    let reader = shapefile::Reader::new(Cursor::new(padded))
+        .unwrap()
+        .allow_padded_records(true);
+    let polylines = reader.read_as::<PolylineM>().unwrap();
+    assert_eq!(polylines[0].points(), &points[..]);
+    assert!(!polylines[0].m_present());
+
+    // Neither when the zero padding is as large as or larger than the M values
+    for &padding in &[32, 40] {
+        let mut polyline = PolylineM::new(points.clone(), vec![0]);
+        polyline.set_m_present(false);
+        let padded = resize_only_record(write_in_memory(polyline), padding);
+        let reader = shapefile::Reader::new(Cursor::new(padded))
+            .unwrap()
+            .allow_padded_records(true);
+        let polylines = reader.read_as::<PolylineM>().unwrap();
+        assert_eq!(polylines[0].points(), &points[..]);
+        assert!(!polylines[0].m_present());
+    }
+}
+
+#[test]
+fn read_record_smaller_than_shape_with_padding_allowed() {
+    let multipoint = Multipoint::new(vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0)]);
+    let shp = resize_only_record(write_in_memory(multipoint), -16);
+    let reader = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .allow_padded_records(true);
+    match reader.read_as::<Multipoint>().map_err(|e| record_error_source(e, 1)) {
+        Err(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+}
+
+#[test]
+fn shape_iterator_feeds_worker_threads() {
+    use std::sync::mpsc;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    fn is_send<T: Send>(_: &T) {}
+
+    let expected = shapefile::read_as::<_, PointZ>("tests/data/pointz.shp").unwrap();
+    let reader = shapefile::Reader::from_path("tests/data/pointz.shp").unwrap();
+    let shapes = reader.iter_shapes_as::<PointZ>();
+    is_send(&shapes);
+
+    let (shape_sender, shape_receiver) = mpsc::sync_channel::<(usize, PointZ)>(1);
+    let shape_receiver = Arc::new(Mutex::new(shape_receiver));
+    let (result_sender, result_receiver) = mpsc::channel();
+    let workers = (0..3)
+        .map(|_| {
+            let shape_receiver = Arc::clone(&shape_receiver);
+            let result_sender = result_sender.clone();
+            thread::spawn(move || loop {
+                let received = shape_receiver.lock().unwrap().recv();
+                match received {
+                    Ok((index, point)) => result_sender.send((index, point.z)).unwrap(),
+                    Err(_) => break,
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(result_sender);
+
+    let reading = thread::spawn(move || {
+        for (index, shape) in shapes.enumerate() {
+            shape_sender.send((index, shape.unwrap())).unwrap();
+        }
+    });
+    reading.join().unwrap();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+
+    let mut zs = result_receiver.iter().collect::<Vec<_>>();
+    zs.sort_by_key(|(index, _)| *index);
+    assert_eq!(
+        zs.into_iter().map(|(_, z)| z).collect::<Vec<_>>(),
+        expected.iter().map(|point| point.z).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_errors_give_the_record_number_and_offset() {
+    use std::error::Error as StdError;
+
+    let points = (0..5).map(|i| Point::new(i as f64, 0.0)).collect::<Vec<_>>();
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    let mut shp = writer.dest.into_inner();
+    // A point record is 28 bytes, the content of the fourth one is made too short
+    let offset = 100 + 3 * 28;
+    shp[offset + 4..offset + 8].copy_from_slice(&8i32.to_be_bytes());
+
+    let error = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<Point>()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "record 4 at byte 184: invalid shape record size");
+    assert!(error.source().is_some());
+    match record_error_source(error, 4) {
+        shapefile::Error::InvalidShapeRecordSize => {}
+        e => panic!("Expected InvalidShapeRecordSize, got {}", e),
+    }
+
+    let errors = vec![
+        shapefile::Reader::new(Cursor::new(shp.clone())).unwrap().read().err(),
+        shapefile::Reader::new(Cursor::new(shp.clone()))
+            .unwrap()
+            .read_as_optional::<Point>()
+            .err(),
+        shapefile::Reader::new(Cursor::new(shp))
+            .unwrap()
+            .read_as_lossy::<Point>()
+            .err(),
+    ];
+    for error in errors {
+        match error {
+            Some(shapefile::Error::RecordError(ref e))
+                if e.record_number == 4 && e.offset == 184 => {}
+            _ => panic!("Expected a RecordError for the record 4"),
+        }
+    }
+}
+
+#[test]
+fn record_larger_than_the_limit() {
+    let points = (0..10).map(|i| Point::new(i as f64, 0.0)).collect::<Vec<_>>();
+    let polylines = vec![
+        Polyline::new(points.clone(), vec![0]),
+        Polyline::new(points, vec![0]),
+    ];
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(polylines).unwrap();
+    let shp = writer.dest.into_inner();
+
+    // The first record declares 2 GiB of content
+    let mut corrupted = shp.clone();
+    corrupted[104..108].copy_from_slice(&(1i32 << 30).to_be_bytes());
+    let error = shapefile::Reader::new(Cursor::new(corrupted.clone()))
+        .unwrap()
+        .read_as::<Polyline>()
+        .err()
+        .unwrap();
+    match record_error_source(error, 1) {
+        shapefile::Error::RecordTooLarge {
+            record_number: 1,
+            declared,
+            limit,
+        } => {
+            assert_eq!(declared, 1 << 31);
+            assert_eq!(limit, shapefile::reader::DEFAULT_MAX_RECORD_SIZE);
+        }
+        e => panic!("Expected RecordTooLarge, got {}", e),
+    }
+    // The next record can not be found, the iteration ends whatever the policy
+    for &policy in &[ErrorPolicy::Continue, ErrorPolicy::SkipMalformed] {
+        let mut reader = shapefile::Reader::new(Cursor::new(corrupted.clone())).unwrap();
+        reader.set_error_policy(policy);
+        let results = reader.iter_shapes_as::<Polyline>().collect::<Vec<_>>();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+    }
+
+    // Each record has 4 + 32 + 8 + 4 + 160 bytes of content
+    let error = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .max_record_size_bytes(Some(200))
+        .read_as::<Polyline>()
+        .err()
+        .unwrap();
+    match record_error_source(error, 1) {
+        shapefile::Error::RecordTooLarge {
+            declared: 208,
+            limit: 200,
+            ..
+        } => {}
+        e => panic!("Expected RecordTooLarge, got {}", e),
+    }
+    let mut raw_records = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .max_record_size_bytes(Some(200))
+        .iter_raw_records();
+    match raw_records.next() {
+        Some(Err(shapefile::Error::RecordTooLarge { declared: 208, .. })) => {}
+        _ => panic!("Expected RecordTooLarge"),
+    }
+    assert!(raw_records.next().is_none());
+    for &limit in &[Some(208), None] {
+        let polylines = shapefile::Reader::new(Cursor::new(shp.clone()))
+            .unwrap()
+            .max_record_size_bytes(limit)
+            .read_as::<Polyline>()
+            .unwrap();
+        assert_eq!(polylines.len(), 2);
+    }
+}
+
+#[test]
+fn bytes_after_the_end_of_the_file_are_ignored() {
+    let mut shp = points_in_memory(3);
+    // Padding or garbage left by the tool that wrote the file
+    shp.extend_from_slice(&[0xAB; 37]);
+
+    let points = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<Point>()
+        .unwrap();
+    assert_eq!(points.len(), 3);
+    let raw_records = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .iter_raw_records()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(raw_records.len(), 3);
+    let mut reader = shapefile::Reader::from_seekable(Cursor::new(shp.clone())).unwrap();
+    assert_eq!(reader.shape_count().unwrap(), 3);
+    let points = reader
+        .iter_matching_as::<Point, _>(|_| true)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(points.len(), 3);
+
+    // A record going past the end of the file does not take the bytes after it
+    let last_record = 100 + 2 * 28;
+    shp[last_record + 4..last_record + 8].copy_from_slice(&14i32.to_be_bytes());
+    let error = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<Point>()
+        .unwrap_err();
+    match record_error_source(error, 3) {
+        shapefile::Error::InvalidShapeRecordSize => {}
+        e => panic!("Expected InvalidShapeRecordSize, got {}", e),
+    }
+    match shapefile::Reader::new(Cursor::new(shp)).unwrap().iter_raw_records().last() {
+        Some(Err(shapefile::Error::InvalidShapeRecordSize)) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+}
+
+#[test]
+fn file_ending_within_a_record_is_truncated() {
+    let mut shp = points_in_memory(3);
+    shp.truncate(shp.len() - 5);
+    let error = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<Point>()
+        .unwrap_err();
+    match record_error_source(error, 3) {
+        shapefile::Error::TruncatedFile {
+            expected_bytes: 184,
+            actual_bytes: 179,
+        } => {}
+        e => panic!("Expected TruncatedFile, got {}", e),
+    }
+
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::SkipMalformed);
+    match reader.iter_matching_as::<Point, _>(|_| true).last() {
+        Some(Err(shapefile::Error::TruncatedFile { .. })) => {}
+        Some(Err(e)) => panic!("Expected TruncatedFile, got {}", e),
+        _ => panic!("Expected TruncatedFile"),
+    }
+}
+
+#[test]
+fn negative_file_length_has_no_records() {
+    let mut shp = points_in_memory(3);
+    shp[24..28].copy_from_slice(&(-2i32).to_be_bytes());
+
+    let reader = shapefile::Reader::new(Cursor::new(shp.clone())).unwrap();
+    assert!(reader.iter_shapes_as::<Point>().next().is_none());
+    let reader = shapefile::Reader::from_seekable(Cursor::new(shp)).unwrap();
+    assert!(reader
+        .iter_matching_as::<Point, _>(|_| true)
+        .next()
+        .is_none());
+}
+
+/// Points file in which the second record has the shape type 77,
+/// its content being still the one of a point within the bbox of the file
+fn points_with_unknown_second_record() -> Vec<u8> {
+    let mut shp = points_in_memory(3);
+    let second_record = 100 + 28;
+    shp[second_record + 8..second_record + 12].copy_from_slice(&77i32.to_le_bytes());
+    shp
+}
+
+#[test]
+fn unknown_shapes_are_written_back_unchanged() {
+    let shp = points_with_unknown_second_record();
+    let error = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read()
+        .err()
+        .unwrap();
+    match record_error_source(error, 2) {
+        shapefile::Error::InvalidShapeType(77) => {}
+        e => panic!("Expected InvalidShapeType(77), got {}", e),
+    }
+
+    let shapes = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .keep_unknown_shapes(true)
+        .read()
+        .unwrap();
+    assert_eq!(shapes.len(), 3);
+    assert_eq!(shapes[0].as_point(), Some(&Point::new(0.0, 0.0)));
+    match shapes[1] {
+        shapefile::Shape::Unknown {
+            shape_type,
+            ref content,
+        } => {
+            assert_eq!(shape_type, 77);
+            assert_eq!(content[..], shp[100 + 28 + 12..100 + 2 * 28]);
+        }
+        ref shape => panic!("Expected an unknown shape, got {}", shape),
+    }
+    assert_eq!(shapes[1].shapetype(), shapefile::ShapeType::NullShape);
+    assert_eq!(shapes[1].points_xy().count(), 0);
+
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(shapes).unwrap();
+    assert_eq!(writer.dest.into_inner(), shp);
+}
+
+#[test]
+fn unknown_shapes_are_only_kept_as_shapes() {
+    let shp = points_with_unknown_second_record();
+    let optional_shapes = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .keep_unknown_shapes(true)
+        .read_as_optional::<shapefile::Shape>()
+        .unwrap();
+    match optional_shapes[1] {
+        Some(shapefile::Shape::Unknown { shape_type: 77, .. }) => {}
+        _ => panic!("Expected an unknown shape"),
+    }
+
+    let error = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .keep_unknown_shapes(true)
+        .read_as::<Point>()
+        .unwrap_err();
+    match record_error_source(error, 2) {
+        shapefile::Error::InvalidShapeType(77) => {}
+        e => panic!("Expected InvalidShapeType(77), got {}", e),
+    }
+}
+
+#[test]
+fn iter_filtered_skips_null_shapes() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points_and_null()).unwrap();
+    let shp = writer.dest.into_inner();
+
+    let points = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .iter_filtered::<Point>()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(points, vec![Point::new(1.0, 1.0), Point::new(3.0, 3.0)]);
+
+    let results = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .iter_filtered::<shapefile::Polygon>()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 1);
+    match record_error_source(results.into_iter().next().unwrap().err().unwrap(), 1) {
+        shapefile::Error::MismatchShapeType {
+            requested: shapefile::ShapeType::Polygon,
+            actual: shapefile::ShapeType::Point,
+        } => {}
+        e => panic!("Expected MismatchShapeType, got {}", e),
+    }
+
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.set_error_policy(ErrorPolicy::Continue);
+    let results = reader.iter_filtered::<shapefile::Polygon>().collect::<Vec<_>>();
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|result| result.is_err()));
+}
+
+#[test]
+fn try_into_concrete_of_read_shapes() {
+    let shapes = points_and_null();
+    let points = shapes
+        .clone()
+        .into_iter()
+        .filter_map(|shape| shape.try_into_concrete::<Point>().transpose())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(points, vec![Point::new(1.0, 1.0), Point::new(3.0, 3.0)]);
+
+    let mut polylines = shapes
+        .into_iter()
+        .map(|shape| shape.try_into_concrete::<Polyline>());
+    match polylines.next() {
+        Some(Err(shapefile::Error::MismatchShapeType { .. })) => {}
+        _ => panic!("Expected MismatchShapeType"),
+    }
+    match polylines.next() {
+        Some(Ok(None)) => {}
+        _ => panic!("Expected no polyline for the NullShape"),
+    }
+
+    let unknown = shapefile::Shape::Unknown {
+        shape_type: 77,
+        content: vec![],
+    };
+    match unknown.try_into_concrete::<Point>() {
+        Err(shapefile::Error::InvalidShapeType(77)) => {}
+        _ => panic!("Expected InvalidShapeType"),
     }
 }
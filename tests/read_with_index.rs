@@ -1,3 +1,4 @@
+extern crate dbase;
 extern crate shapefile;
 
 mod testfiles;
@@ -10,8 +11,648 @@ fn test_line() {
         let shp = shape.unwrap();
         testfiles::check_line_first_shape(&shp);
     } else {
-        assert!(false, "Should be Some(shape)")
+        panic!("Should be Some(shape)")
     }
 
-    assert_eq!(reader.read_nth_shape(1).is_none(), true);
+    assert!(reader.read_nth_shape(1).is_none());
+}
+
+/// Writes points (i, i) with a .shx and a .dbf whose "ID" field is i
+fn write_points_with_ids(dir_name: &str, n: usize) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("points.shp");
+    let points = (0..n)
+        .map(|i| shapefile::Point::new(i as f64, i as f64))
+        .collect::<Vec<_>>();
+    let records = (0..n)
+        .map(|i| {
+            let mut record = dbase::Record::new();
+            record.insert("ID".to_string(), dbase::FieldValue::Numeric(Some(i as f64)));
+            record
+        })
+        .collect::<Vec<_>>();
+    shapefile::Writer::from_path(&shp_path)
+        .unwrap()
+        .write_shapes_and_records(points, records)
+        .unwrap();
+    shp_path
+}
+
+#[test]
+fn read_selection_in_requested_order() {
+    let shp_path = write_points_with_ids("shapefile_read_selection_in_requested_order", 10);
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let selection = reader
+        .read_selection_as::<shapefile::Point>(&[7, 2, 7, 9, 2, 0])
+        .unwrap();
+    assert_eq!(
+        selection,
+        vec![
+            (7, shapefile::Point::new(7.0, 7.0)),
+            (2, shapefile::Point::new(2.0, 2.0)),
+            (9, shapefile::Point::new(9.0, 9.0)),
+            (0, shapefile::Point::new(0.0, 0.0)),
+        ]
+    );
+    assert!(reader.read_selection(&[]).unwrap().is_empty());
+
+    // The reader can still be used
+    match reader.read_nth_shape(3) {
+        Some(Ok(shapefile::Shape::Point(point))) => assert_eq!(point.x, 3.0),
+        _ => panic!("Expected a point"),
+    }
+}
+
+#[test]
+fn read_selection_out_of_range() {
+    let shp_path = write_points_with_ids("shapefile_read_selection_out_of_range", 3);
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    match reader.read_selection(&[12, 1, 3, 12]) {
+        Err(shapefile::Error::RecordIndicesOutOfRange(indices)) => {
+            assert_eq!(indices, vec![3, 12])
+        }
+        _ => panic!("Expected RecordIndicesOutOfRange"),
+    }
+}
+
+#[test]
+fn read_selection_with_records() {
+    let shp_path = write_points_with_ids("shapefile_read_selection_with_records", 5);
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let selection = reader.read_selection_with_records(&[3, 1]).unwrap();
+    assert_eq!(selection.len(), 2);
+    for (i, shape, record) in selection {
+        match shape {
+            shapefile::Shape::Point(point) => assert_eq!(point.x, i as f64),
+            _ => panic!("Expected a point"),
+        }
+        assert_eq!(
+            record.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(i as f64)))
+        );
+    }
+
+    // The .dbf can only be read once
+    match reader.read_selection_with_records(&[0]) {
+        Err(shapefile::Error::MissingDbf) => {}
+        _ => panic!("Expected MissingDbf"),
+    }
+}
+
+#[test]
+fn record_offsets_with_and_without_index() {
+    use shapefile::{Point, Polyline, Shape};
+
+    let dir = std::env::temp_dir().join("shapefile_record_offsets_with_and_without_index");
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("lines.shp");
+    let shapes = (1..5)
+        .map(|n| {
+            let points = (0..n * 2).map(|i| Point::new(i as f64, n as f64)).collect();
+            Shape::Polyline(Polyline::new(points, vec![0]))
+        })
+        .collect::<Vec<Shape>>();
+    let mut writer = shapefile::Writer::new(std::fs::File::create(&shp_path).unwrap());
+    writer.add_index_dest(std::fs::File::create(dir.join("lines.shx")).unwrap());
+    writer.write_shapes(shapes).unwrap();
+
+    let from_shx = shapefile::Reader::from_path(&shp_path)
+        .unwrap()
+        .record_offsets()
+        .unwrap();
+    let mut reader = shapefile::Reader::new(std::fs::File::open(&shp_path).unwrap()).unwrap();
+    let from_scan = reader.record_offsets().unwrap();
+    assert_eq!(from_shx, from_scan);
+
+    assert_eq!(from_scan.len(), 4);
+    assert_eq!(from_scan[0].number, 0);
+    assert_eq!(from_scan[0].byte_offset, 100);
+    for pair in from_scan.windows(2) {
+        assert_eq!(pair[1].number, pair[0].number + 1);
+        assert_eq!(
+            pair[1].byte_offset,
+            pair[0].byte_offset + 8 + pair[0].content_length_bytes as u64
+        );
+    }
+    // type, bbox, number of parts and points, one part and the points
+    assert_eq!(from_scan[3].content_length_bytes, 4 + 32 + 8 + 4 + 8 * 16);
+
+    // The shapes can still be read after the scan
+    assert_eq!(reader.read().unwrap().len(), 4);
+}
+
+fn write_lines(dir_name: &str, n: usize) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(dir_name);
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("lines.shp");
+    let lines = (0..n)
+        .map(|i| {
+            let points = (0..=i).map(|j| shapefile::Point::new(i as f64, j as f64)).collect();
+            shapefile::Polyline::new(points, vec![0])
+        })
+        .collect::<Vec<_>>();
+    let mut writer = shapefile::Writer::new(std::fs::File::create(&shp_path).unwrap());
+    writer.add_index_dest(std::fs::File::create(shp_path.with_extension("shx")).unwrap());
+    writer.write_shapes(lines).unwrap();
+    shp_path
+}
+
+#[test]
+fn rebuild_index_of_zeroed_shx() {
+    let shp_path = write_lines("shapefile_rebuild_index_of_zeroed_shx", 6);
+    let shx_path = shp_path.with_extension("shx");
+    let good_shx = std::fs::read(&shx_path).unwrap();
+    std::fs::write(&shx_path, vec![0u8; good_shx.len()]).unwrap();
+
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    match reader.read_nth_shape(3) {
+        Some(Err(shapefile::Error::InvalidFileCode(0))) => {}
+        _ => panic!("Expected the zeroed .shx to be invalid"),
+    }
+
+    reader.rebuild_index().unwrap();
+    assert_eq!(reader.index_table().unwrap().len(), 6);
+    for i in [5, 0, 3] {
+        let line = reader.read_nth_shape_as::<shapefile::Polyline>(i).unwrap().unwrap();
+        assert_eq!(line.points.len(), i + 1);
+        assert_eq!(line.points[0].x, i as f64);
+    }
+    assert!(reader.read_nth_shape(6).is_none());
+
+    reader.write_index(&shx_path).unwrap();
+    assert_eq!(std::fs::read(&shx_path).unwrap(), good_shx);
+}
+
+#[test]
+fn rebuild_index_of_broken_shp() {
+    let shp_path = write_lines("shapefile_rebuild_index_of_broken_shp", 4);
+    let shp = std::fs::read(&shp_path).unwrap();
+
+    let truncated = shp[..shp.len() - 10].to_vec();
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(truncated)).unwrap();
+    match reader.rebuild_index() {
+        Err(shapefile::Error::TruncatedFile {
+            expected_bytes,
+            actual_bytes,
+        }) => {
+            assert_eq!(expected_bytes, shp.len() as u64);
+            assert_eq!(actual_bytes, shp.len() as u64 - 10);
+        }
+        _ => panic!("Expected TruncatedFile"),
+    }
+
+    // The content length of the second record goes past the end of the file
+    let mut corrupt = shp.clone();
+    let second_record = 100 + 8 + 64;
+    corrupt[second_record + 4..second_record + 8].copy_from_slice(&10_000i32.to_be_bytes());
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(corrupt)).unwrap();
+    match reader.rebuild_index() {
+        Err(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+    match reader.index_table() {
+        Err(shapefile::Error::MissingIndexFile) => {}
+        _ => panic!("The table must not be built"),
+    }
+}
+
+/// Writes points (i, i) in memory, returns the .shp and the .shx
+fn points_in_memory(n: usize) -> (Vec<u8>, Vec<u8>) {
+    let points = (0..n)
+        .map(|i| shapefile::Point::new(i as f64, i as f64))
+        .collect::<Vec<_>>();
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut shp);
+        writer.add_index_dest(&mut shx);
+        writer.write_shapes(points).unwrap();
+    }
+    (shp, shx)
+}
+
+#[test]
+fn read_range_with_and_without_index() {
+    let (shp, shx) = points_in_memory(10);
+    let mut with_index = shapefile::Reader::new(std::io::Cursor::new(shp.clone())).unwrap();
+    with_index.add_index_source(std::io::Cursor::new(shx)).unwrap();
+    let without_index = shapefile::Reader::new(std::io::Cursor::new(shp)).unwrap();
+    assert!(!without_index.has_index());
+
+    for reader in &mut [with_index, without_index] {
+        let cases = [(0, 3, 0..3), (4, 3, 4..7), (8, 5, 8..10), (10, 2, 0..0), (42, 1, 0..0)];
+        for (start, count, expected) in cases.iter().cloned() {
+            let points = reader.read_range_as::<shapefile::Point>(start, count).unwrap();
+            let expected = expected
+                .map(|i| shapefile::Point::new(i as f64, i as f64))
+                .collect::<Vec<_>>();
+            assert_eq!(points, expected, "start: {}, count: {}", start, count);
+        }
+        assert!(reader.read_range(3, 0).unwrap().is_empty());
+    }
+}
+
+#[test]
+fn shape_count_with_and_without_index() {
+    let (shp, shx) = points_in_memory(10);
+    let mut with_index = shapefile::Reader::new(std::io::Cursor::new(shp.clone())).unwrap();
+    with_index.add_index_source(std::io::Cursor::new(shx)).unwrap();
+    let without_index = shapefile::Reader::new(std::io::Cursor::new(shp)).unwrap();
+
+    for mut reader in [with_index, without_index] {
+        assert_eq!(reader.shape_count().unwrap(), 10);
+        // The reader can still be read from its first record
+        let points = reader.read_as::<shapefile::Point>().unwrap();
+        assert_eq!(points.len(), 10);
+        assert_eq!(points[0], shapefile::Point::new(0.0, 0.0));
+    }
+}
+
+#[test]
+fn read_nth_shape_with_index_source() {
+    let (shp, shx) = points_in_memory(10);
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(shp)).unwrap();
+    reader.add_index_source(std::io::Cursor::new(shx)).unwrap();
+    for &i in &[7, 2, 9] {
+        let point = reader.read_nth_shape_as::<shapefile::Point>(i).unwrap().unwrap();
+        assert_eq!(point, shapefile::Point::new(i as f64, i as f64));
+    }
+    assert!(reader.read_nth_shape(10).is_none());
+}
+
+#[test]
+fn iterators_start_at_first_record_after_failed_seeking_read() {
+    let (shp, shx) = points_in_memory(10);
+    let new_reader = || {
+        let mut reader = shapefile::Reader::new(std::io::Cursor::new(shp.clone())).unwrap();
+        reader.add_index_source(std::io::Cursor::new(shx.clone())).unwrap();
+        // Fails after the header of the record is read, the source is left within the file
+        assert!(reader.read_nth_shape_as::<shapefile::Polyline>(5).unwrap().is_err());
+        reader
+    };
+    let expected = (0..10)
+        .map(|i| shapefile::Point::new(i as f64, i as f64))
+        .collect::<Vec<_>>();
+
+    let points = new_reader().read_as::<shapefile::Point>().unwrap();
+    assert_eq!(points, expected);
+    let points = new_reader()
+        .iter_matching_as::<shapefile::Point, _>(|_| true)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(points, expected);
+    let bboxes = new_reader()
+        .iter_record_bboxes()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(bboxes.len(), 10);
+    assert_eq!(bboxes[0].0, 1);
+    assert_eq!(new_reader().iter_raw_records().count(), 10);
+
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(shp.clone())).unwrap();
+    assert_eq!(reader.summary().unwrap().num_records, 10);
+    let points = reader.read_as::<shapefile::Point>().unwrap();
+    assert_eq!(points, expected);
+}
+
+#[test]
+fn readers_of_bytes_in_memory() {
+    let (shp, _) = points_in_memory(10);
+    let mut from_buffer = shapefile::Reader::from_buffer(shp.clone()).unwrap();
+    assert!(from_buffer.has_index());
+    let from_slice = shapefile::Reader::from_slice(&shp).unwrap();
+    assert!(from_slice.has_index());
+
+    let point = from_buffer.read_nth_shape_as::<shapefile::Point>(7).unwrap().unwrap();
+    assert_eq!(point, shapefile::Point::new(7.0, 7.0));
+    assert!(from_buffer.read_nth_shape(10).is_none());
+
+    let bbox = shapefile::record::BBox::new(2.5, 2.5, 4.5, 4.5);
+    let points = from_slice
+        .iter_shapes_in_bbox_as::<shapefile::Point>(bbox)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(points, vec![shapefile::Point::new(3.0, 3.0), shapefile::Point::new(4.0, 4.0)]);
+
+    let points = shapefile::read_from_bytes::<shapefile::Point>(&shp).unwrap();
+    assert_eq!(points.len(), 10);
+    match shapefile::read_from_bytes::<shapefile::Point>(&shp[..150]) {
+        Err(shapefile::Error::TruncatedFile { .. }) => {}
+        _ => panic!("Expected TruncatedFile"),
+    }
+    match shapefile::Reader::from_slice(&[]) {
+        Err(shapefile::Error::EmptyFile) => {}
+        _ => panic!("Expected EmptyFile"),
+    }
+}
+
+#[test]
+fn read_nth_shape_without_index() {
+    let (shp, _) = points_in_memory(3);
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(shp)).unwrap();
+    match reader.read_nth_shape(1) {
+        Some(Err(e @ shapefile::Error::MissingIndexFile)) => {
+            assert!(e.to_string().contains(".shx"))
+        }
+        _ => panic!("Expected MissingIndexFile"),
+    }
+}
+
+#[test]
+fn read_nth_shape_with_mismatched_index_entry() {
+    let (shp, mut shx) = points_in_memory(3);
+    // Content length of the second entry, in 16-bit words
+    let entry = 100 + 8;
+    shx[entry + 4..entry + 8].copy_from_slice(&12i32.to_be_bytes());
+    let mut reader = shapefile::Reader::new(std::io::Cursor::new(shp)).unwrap();
+    reader.add_index_source(std::io::Cursor::new(shx)).unwrap();
+    match reader.read_nth_shape(1) {
+        Some(Err(shapefile::Error::IndexEntryMismatch {
+            index: 1,
+            index_length: 24,
+            record_length: 20,
+        })) => {}
+        _ => panic!("Expected IndexEntryMismatch"),
+    }
+    assert!(reader.read_nth_shape(2).unwrap().is_ok());
+}
+
+/// Writes a .dbf with `n` records whose "ID" field is i
+fn write_ids(dbf_path: &std::path::Path, n: usize) {
+    let records = (0..n)
+        .map(|i| {
+            let mut record = dbase::Record::new();
+            record.insert("ID".to_string(), dbase::FieldValue::Numeric(Some(i as f64)));
+            record
+        })
+        .collect::<Vec<_>>();
+    dbase::Writer::new(std::fs::File::create(dbf_path).unwrap())
+        .write(&records)
+        .unwrap();
+}
+
+#[test]
+fn from_path_finds_uppercase_extensions() {
+    let shp_path = write_points_with_ids("shapefile_from_path_finds_uppercase_extensions", 3);
+    std::fs::rename(shp_path.with_extension("shx"), shp_path.with_extension("SHX")).unwrap();
+    std::fs::rename(shp_path.with_extension("dbf"), shp_path.with_extension("DBF")).unwrap();
+
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    assert!(reader.has_index());
+    assert!(reader.has_attributes());
+    let features = reader
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(features.len(), 3);
+    for (i, (point, record)) in features.iter().enumerate() {
+        assert_eq!(point, &shapefile::Point::new(i as f64, i as f64));
+        assert_eq!(
+            record.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(i as f64)))
+        );
+    }
+}
+
+#[test]
+fn iter_shapes_and_records_with_different_counts() {
+    let shp_path = write_points_with_ids("shapefile_iter_shapes_and_records_counts", 3);
+    for &num_records in &[2, 4] {
+        write_ids(&shp_path.with_extension("dbf"), num_records);
+        let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+        let results = reader.iter_shapes_and_records().unwrap().collect::<Vec<_>>();
+        assert_eq!(results.len(), num_records.min(3) + 1);
+        assert!(results[..num_records.min(3)].iter().all(|result| result.is_ok()));
+        match results.last().unwrap() {
+            Err(shapefile::Error::RecordCountMismatch {
+                num_shapes: 3,
+                num_records: n,
+            }) if *n == num_records => {}
+            _ => panic!("Expected RecordCountMismatch"),
+        }
+    }
+}
+
+#[test]
+fn iter_shapes_and_records_as_pairs_shapes_and_records() {
+    let shp_path = write_points_with_ids("shapefile_iter_features", 3);
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let features = reader
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(features.len(), 3);
+    for (i, (point, record)) in features.iter().enumerate() {
+        assert_eq!(point, &shapefile::Point::new(i as f64, i as f64));
+        assert_eq!(
+            record.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(i as f64)))
+        );
+    }
+
+    write_ids(&shp_path.with_extension("dbf"), 2);
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let results = reader
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .collect::<Vec<_>>();
+    match results.as_slice() {
+        [Ok(_), Ok(_), Err(shapefile::Error::RecordCountMismatch {
+            num_shapes: 3,
+            num_records: 2,
+        })] => {}
+        _ => panic!("Expected RecordCountMismatch after 2 features"),
+    }
+
+    std::fs::remove_file(shp_path.with_extension("dbf")).unwrap();
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    match reader.iter_shapes_and_records_as::<shapefile::Point>() {
+        Err(shapefile::Error::MissingDbf) => {}
+        _ => panic!("Expected MissingDbf"),
+    }
+}
+
+#[test]
+fn iter_shapes_and_records_skips_records_of_malformed_shapes() {
+    let shp_path = write_points_with_ids("shapefile_skip_malformed_with_records", 3);
+    // The shape type of the second record is the one of a polyline,
+    // its content is too small for a polyline
+    let mut shp = std::fs::read(&shp_path).unwrap();
+    shp[100 + 28 + 8..100 + 28 + 12].copy_from_slice(&3i32.to_le_bytes());
+    std::fs::write(&shp_path, shp).unwrap();
+
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    reader.set_error_policy(shapefile::reader::ErrorPolicy::SkipMalformed);
+    let mut features = reader.iter_shapes_and_records().unwrap();
+    let ids = features
+        .by_ref()
+        .map(|feature| match feature.unwrap().1.get("ID") {
+            Some(dbase::FieldValue::Numeric(id)) => id.unwrap(),
+            _ => panic!("Expected a numeric ID"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![0.0, 2.0]);
+    assert_eq!(features.skipped_records(), &[2]);
+}
+
+#[test]
+fn iter_shapes_and_records_error_policies() {
+    let shp_path = write_points_with_ids("shapefile_error_policies_with_records", 3);
+    // The shape type of the second record is the one of a polyline
+    let mut shp = std::fs::read(&shp_path).unwrap();
+    shp[100 + 28 + 8..100 + 28 + 12].copy_from_slice(&3i32.to_le_bytes());
+    std::fs::write(&shp_path, shp).unwrap();
+
+    // The iteration ends after the error
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let mut features = reader.iter_shapes_and_records_as::<shapefile::Point>().unwrap();
+    assert!(features.next().unwrap().is_ok());
+    assert!(features.next().unwrap().is_err());
+    assert!(features.next().is_none());
+    assert!(features.next().is_none());
+
+    // or continues with the next shape, still paired with its record
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    reader.set_error_policy(shapefile::reader::ErrorPolicy::Continue);
+    let results = reader
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    let (point, record) = results[2].as_ref().unwrap();
+    assert_eq!(point, &shapefile::Point::new(2.0, 2.0));
+    assert_eq!(
+        record.get("ID"),
+        Some(&dbase::FieldValue::Numeric(Some(2.0)))
+    );
+}
+
+#[test]
+fn iter_shapes_and_records_reports_the_record_error_of_a_malformed_shape() {
+    let shp_path = write_points_with_ids("shapefile_record_error_of_malformed_shape", 3);
+    // The shape type of the second record is the one of a polyline,
+    // and the .dbf has no record for it
+    let mut shp = std::fs::read(&shp_path).unwrap();
+    shp[100 + 28 + 8..100 + 28 + 12].copy_from_slice(&3i32.to_le_bytes());
+    std::fs::write(&shp_path, shp).unwrap();
+    write_ids(&shp_path.with_extension("dbf"), 1);
+
+    let reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    let mut features = reader.iter_shapes_and_records_as::<shapefile::Point>().unwrap();
+    assert!(features.next().unwrap().is_ok());
+    assert!(features.next().unwrap().is_err());
+    match features.next() {
+        Some(Err(shapefile::Error::RecordCountMismatch { num_records: 1, .. })) => {}
+        _ => panic!("Expected RecordCountMismatch"),
+    }
+    assert!(features.next().is_none());
+}
+
+/// Source that logs the number of bytes of each of its reads,
+/// in a log shared with the other sources
+struct InstrumentedSource {
+    file: &'static str,
+    inner: std::io::Cursor<Vec<u8>>,
+    log: std::rc::Rc<std::cell::RefCell<Vec<(&'static str, usize)>>>,
+}
+
+impl std::io::Read for InstrumentedSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let num_bytes = self.inner.read(buf)?;
+        if num_bytes > 0 {
+            self.log.borrow_mut().push((self.file, num_bytes));
+        }
+        Ok(num_bytes)
+    }
+}
+
+#[test]
+fn iter_shapes_and_records_reads_one_record_of_each_file_per_item() {
+    let n = 10_000;
+    let shp_path = write_points_with_ids("shapefile_interleaved_shapes_and_records", n);
+    let shp = std::fs::read(&shp_path).unwrap();
+    let dbf = std::fs::read(shp_path.with_extension("dbf")).unwrap();
+    let dbf_header_size = u16::from_le_bytes([dbf[8], dbf[9]]) as usize;
+    // Not the record size of the header, which does not count the deletion flag
+    // when written by dbase, but the size of the records between the header and the end marker
+    let dbf_record_size = (dbf.len() - dbf_header_size - 1) / n;
+    // Record header and shape type, then x and y
+    let shp_record_size = 8 + 4 + 16;
+
+    let log = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+    let source = |file, data| InstrumentedSource {
+        file,
+        inner: std::io::Cursor::new(data),
+        log: log.clone(),
+    };
+    let mut reader = shapefile::Reader::new(source("shp", shp)).unwrap();
+    reader.add_dbf_source(source("dbf", dbf)).unwrap();
+    let bytes_read = |file| {
+        log.borrow()
+            .iter()
+            .filter(|(f, _)| *f == file)
+            .map(|(_, num_bytes)| num_bytes)
+            .sum::<usize>()
+    };
+
+    let mut features = reader.iter_shapes_and_records_as::<shapefile::Point>().unwrap();
+    for i in 0..n {
+        let (point, record) = features.next().unwrap().unwrap();
+        assert_eq!(point, shapefile::Point::new(i as f64, i as f64));
+        assert_eq!(
+            record.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(i as f64)))
+        );
+        assert_eq!(bytes_read("shp"), 100 + (i + 1) * shp_record_size);
+        assert_eq!(bytes_read("dbf"), dbf_header_size + (i + 1) * dbf_record_size);
+    }
+    assert!(features.next().is_none());
+    // No read is larger than the file header
+    assert!(log.borrow().iter().all(|&(_, num_bytes)| num_bytes <= 100));
+}
+
+#[test]
+fn read_buffer_size_keeps_positions() {
+    let shp_path = write_points_with_ids("shapefile_read_buffer_size", 100);
+    let expected = shapefile::Reader::from_path(&shp_path)
+        .unwrap()
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .map(|result| result.unwrap().0)
+        .collect::<Vec<_>>();
+
+    for &capacity in &[0, 16, 1 << 20] {
+        let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+        reader.set_read_buffer_size(capacity).unwrap();
+        assert_eq!(reader.index_table().unwrap().len(), 100);
+        let features = reader
+            .iter_shapes_and_records_as::<shapefile::Point>()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let points = features.iter().map(|(point, _)| *point).collect::<Vec<_>>();
+        assert_eq!(points, expected);
+        assert_eq!(
+            features[99].1.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(99.0)))
+        );
+
+        let mut reader = shapefile::Reader::with_capacity(&shp_path, capacity).unwrap();
+        assert_eq!(reader.index_table().unwrap().len(), 100);
+        let features = reader
+            .iter_shapes_and_records_as::<shapefile::Point>()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let points = features.iter().map(|(point, _)| *point).collect::<Vec<_>>();
+        assert_eq!(points, expected);
+        assert_eq!(
+            features[99].1.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(99.0)))
+        );
+    }
 }
@@ -0,0 +1,90 @@
+extern crate shapefile;
+
+use shapefile::record::EsriShape;
+use shapefile::{Multipatch, PatchType, Point, PointZ, Polygon, Shape, NO_DATA};
+
+fn affine(x: f64, y: f64) -> Result<(f64, f64), String> {
+    Ok((2.0 * x + 1.0, -y))
+}
+
+#[test]
+fn reproject_with_affine_callback() {
+    let points = vec![
+        Point::new(0.0, 0.0),
+        Point::new(0.0, 2.0),
+        Point::new(3.0, 2.0),
+        Point::new(0.0, 0.0),
+    ];
+    let mut shape = Shape::Polygon(Polygon::new(points, vec![0]));
+    shape.reproject(affine).unwrap();
+
+    let polygon = shape.as_polygon().unwrap();
+    assert_eq!(polygon.points[2], Point::new(7.0, -2.0));
+    assert_eq!(
+        (polygon.bbox.xmin, polygon.bbox.ymin, polygon.bbox.xmax, polygon.bbox.ymax),
+        (1.0, -2.0, 7.0, 0.0)
+    );
+
+    let mut point = Shape::Point(Point::new(1.0, 1.0));
+    point.reproject(affine).unwrap();
+    assert_eq!(point.as_point(), Some(&Point::new(3.0, -1.0)));
+}
+
+#[test]
+fn reproject_z_refreshes_ranges() {
+    let patch = vec![
+        PointZ::new(0.0, 0.0, 1.0, 5.0),
+        PointZ::new(1.0, 0.0, 2.0, NO_DATA),
+        PointZ::new(1.0, 1.0, 3.0, 7.0),
+    ];
+    let multipatch = Multipatch::from_patches(vec![(PatchType::TriangleFan, patch)]).unwrap();
+    let mut shape = Shape::Multipatch(multipatch);
+    shape
+        .reproject_z(|x, y, z| Ok::<_, String>((x + 10.0, y, z * 100.0)))
+        .unwrap();
+
+    assert_eq!(shape.bbox().xmin, 10.0);
+    assert_eq!(shape.bbox().xmax, 11.0);
+    match shape {
+        Shape::Multipatch(ref multipatch) => {
+            assert_eq!(multipatch.z_range(), [100.0, 300.0]);
+            assert_eq!(multipatch.m_range(), [5.0, 7.0]);
+        }
+        _ => panic!("expected a multipatch"),
+    }
+
+    // The z of 2D shapes is 0 and is not kept
+    let mut point = Shape::Point(Point::new(1.0, 1.0));
+    point
+        .reproject_z(|x, y, z| {
+            assert_eq!(z, 0.0);
+            Ok::<_, String>((x, y, 42.0))
+        })
+        .unwrap();
+    assert_eq!(point.as_point(), Some(&Point::new(1.0, 1.0)));
+}
+
+#[test]
+fn reproject_error_leaves_shape_untouched() {
+    let points = vec![
+        Point::new(0.0, 0.0),
+        Point::new(0.0, 2.0),
+        Point::new(3.0, 2.0),
+        Point::new(0.0, 0.0),
+    ];
+    let mut shape = Shape::Polygon(Polygon::new(points.clone(), vec![0]));
+    let mut num_calls = 0;
+    let result = shape.reproject(|x, y| {
+        num_calls += 1;
+        if num_calls == 3 {
+            Err(format!("cannot reproject ({}, {})", x, y))
+        } else {
+            Ok((x + 100.0, y + 100.0))
+        }
+    });
+
+    assert_eq!(result, Err("cannot reproject (3, 2)".to_string()));
+    let polygon = shape.as_polygon().unwrap();
+    assert_eq!(polygon.points, points);
+    assert_eq!(polygon.bbox.xmax, 3.0);
+}
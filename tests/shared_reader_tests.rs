@@ -0,0 +1,121 @@
+extern crate shapefile;
+
+use std::io::Cursor;
+use std::sync::Arc;
+use std::thread;
+
+use shapefile::record::BBox;
+use shapefile::{Point, Polyline, Reader, Shape, SharedReader, Writer};
+
+const NUM_SHAPES: usize = 500;
+const NUM_THREADS: usize = 8;
+const READS_PER_THREAD: usize = 2000;
+
+fn polylines() -> Vec<Polyline> {
+    (0..NUM_SHAPES)
+        .map(|i| {
+            let x = (i % 25) as f64;
+            let y = (i / 25) as f64;
+            let points = (0..(i % 7) + 2)
+                .map(|j| Point::new(x + j as f64 * 0.1, y + j as f64 * 0.05))
+                .collect::<Vec<_>>();
+            Polyline::new(points, vec![0])
+        })
+        .collect()
+}
+
+fn write_in_memory(with_index: bool) -> (Vec<u8>, Option<Vec<u8>>) {
+    let mut writer = Writer::new(Cursor::new(Vec::<u8>::new()));
+    if with_index {
+        writer.add_index_dest(Cursor::new(Vec::new()));
+    }
+    writer.write_shapes(polylines()).unwrap();
+    let parts = writer.into_parts().unwrap();
+    (parts.shp.into_inner(), parts.shx.map(Cursor::into_inner))
+}
+
+fn is_sync<T: Sync + Send>() {}
+
+#[test]
+fn shared_reader_is_sync() {
+    is_sync::<SharedReader>();
+}
+
+#[test]
+fn read_nth_from_many_threads() {
+    let (shp, shx) = write_in_memory(true);
+    let sequential = Reader::from_parts_bytes(shp.clone(), None, None).unwrap();
+    let expected = Arc::new(sequential.read().unwrap());
+    let reader = Arc::new(SharedReader::from_bytes(shp, shx).unwrap());
+    assert_eq!(reader.len(), NUM_SHAPES);
+    assert!(reader.read_nth(NUM_SHAPES).is_none());
+
+    let handles = (0..NUM_THREADS)
+        .map(|t| {
+            let reader = Arc::clone(&reader);
+            let expected = Arc::clone(&expected);
+            thread::spawn(move || {
+                // Linear congruential generator, so that each thread reads its own sequence
+                let mut state = 12345 + t as u64;
+                for _ in 0..READS_PER_THREAD {
+                    state = state
+                        .wrapping_mul(6364136223846793005)
+                        .wrapping_add(1442695040888963407);
+                    let index = (state >> 33) as usize % NUM_SHAPES;
+                    let shape = reader.read_nth(index).unwrap().unwrap();
+                    match (&shape, &expected[index]) {
+                        (Shape::Polyline(read), Shape::Polyline(expected)) => {
+                            assert_eq!(read.points, expected.points)
+                        }
+                        _ => panic!("expected polylines"),
+                    }
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn query_bbox_matches_sequential_filter() {
+    let (shp, _) = write_in_memory(false);
+    let reader = SharedReader::from_bytes(shp, None).unwrap();
+    assert_eq!(reader.len(), NUM_SHAPES);
+
+    let bbox = BBox::new(3.5, 2.5, 6.0, 4.0);
+    let found = reader.query_bbox_as::<Polyline>(&bbox).unwrap();
+    let expected = polylines()
+        .into_iter()
+        .enumerate()
+        .filter(|(_, polyline)| polyline.bbox.intersects(&bbox))
+        .collect::<Vec<_>>();
+    assert!(!expected.is_empty());
+    assert_eq!(found.len(), expected.len());
+    for ((index, polyline), (expected_index, expected_polyline)) in found.iter().zip(&expected) {
+        assert_eq!(index, expected_index);
+        assert_eq!(polyline.points, expected_polyline.points);
+    }
+
+    let shapes = reader.query_bbox(&bbox).unwrap();
+    assert_eq!(
+        shapes.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+        found.iter().map(|(index, _)| *index).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn read_nth_with_mismatching_index() {
+    let (shp, shx) = write_in_memory(true);
+    let mut shx = shx.unwrap();
+    // Makes the content length of the first entry one word larger
+    let length = u32::from_be_bytes([shx[104], shx[105], shx[106], shx[107]]) + 1;
+    shx[104..108].copy_from_slice(&length.to_be_bytes());
+    let reader = SharedReader::from_bytes(shp, Some(shx)).unwrap();
+    match reader.read_nth(0) {
+        Some(Err(shapefile::Error::IndexEntryMismatch { index: 0, .. })) => {}
+        _ => panic!("expected an IndexEntryMismatch error"),
+    }
+    assert!(reader.read_nth(1).unwrap().is_ok());
+}
@@ -0,0 +1,158 @@
+extern crate shapefile;
+
+use std::path::{Path, PathBuf};
+
+use shapefile::copy::{copy_with_options, CopyOptions};
+
+/// Creates a directory with the `archive` shapefile, its sidecars,
+/// and the files of other shapefiles whose name start with `archive`
+fn fixture_tree(dir_name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(dir_name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("archive.d")).unwrap();
+    for basename in &["archive", "archive.2020"] {
+        std::fs::copy("tests/data/multipatch.shp", dir.join(format!("{}.shp", basename))).unwrap();
+        std::fs::copy("tests/data/multipatch.dbf", dir.join(format!("{}.dbf", basename))).unwrap();
+    }
+    let files = [
+        "archive.PRJ",
+        "archive.SHP.XML",
+        "archive.cpg",
+        "archive.2020.prj",
+        "archive_old.prj",
+    ];
+    for file in &files {
+        std::fs::write(dir.join(file), file.as_bytes()).unwrap();
+    }
+    dir
+}
+
+fn file_names(paths: &[PathBuf]) -> Vec<&str> {
+    paths
+        .iter()
+        .map(|path| path.file_name().unwrap().to_str().unwrap())
+        .collect()
+}
+
+#[test]
+fn sidecar_paths_of_fixture_tree() {
+    let dir = fixture_tree("shapefile_sidecar_paths_of_fixture_tree");
+
+    let reader = shapefile::Reader::from_path(dir.join("archive.shp")).unwrap();
+    let sidecars = reader.sidecar_paths().unwrap();
+    assert_eq!(file_names(&sidecars), ["archive.PRJ", "archive.SHP.XML", "archive.cpg"]);
+    assert!(sidecars.iter().all(|path| path.parent() == Some(dir.as_path())));
+
+    let sidecars = shapefile::sidecar::sidecar_paths_of(dir.join("archive.2020.shp")).unwrap();
+    assert_eq!(file_names(&sidecars), ["archive.2020.prj"]);
+
+    let sidecars = shapefile::sidecar::sidecar_paths_of(dir.join("ARCHIVE.shp")).unwrap();
+    assert!(sidecars.is_empty());
+}
+
+#[test]
+fn sidecar_paths_of_reader_without_path() {
+    let file = std::fs::File::open("tests/data/line.shp").unwrap();
+    let reader = shapefile::Reader::new(file).unwrap();
+    assert!(reader.sidecar_paths().unwrap().is_empty());
+}
+
+#[test]
+fn copy_preserves_sidecars() {
+    let dir = fixture_tree("shapefile_copy_preserves_sidecars");
+    let output_dir = dir.join("out");
+    std::fs::create_dir_all(&output_dir).unwrap();
+
+    let copy = |output: &Path, preserve: bool| {
+        copy_with_options(
+            dir.join("archive.shp"),
+            output,
+            CopyOptions::default().preserve_sidecars(preserve),
+            |shape, record| Some((shape, record)),
+        )
+        .unwrap()
+    };
+
+    copy(&output_dir.join("copy.shp"), false);
+    let sidecars = shapefile::sidecar::sidecar_paths_of(output_dir.join("copy.shp")).unwrap();
+    assert!(sidecars.is_empty());
+
+    let report = copy(&output_dir.join("copy.v2.shp"), true);
+    assert_eq!(report.read, report.written);
+    let sidecars = shapefile::sidecar::sidecar_paths_of(output_dir.join("copy.v2.shp")).unwrap();
+    assert_eq!(file_names(&sidecars), ["copy.v2.PRJ", "copy.v2.SHP.XML", "copy.v2.cpg"]);
+    assert_eq!(std::fs::read(&sidecars[0]).unwrap(), b"archive.PRJ");
+    assert!(!output_dir.join("copy.v2.2020.prj").exists());
+}
+
+/// Creates the `dir` with the `files`, which are copies of the `sources`,
+/// returns false if the filesystem ignores the case of names
+fn mixed_case_dir(dir: &Path, files: &[(&str, &str)]) -> bool {
+    let _ = std::fs::remove_dir_all(dir);
+    std::fs::create_dir_all(dir).unwrap();
+    std::fs::write(dir.join("case_probe"), b"").unwrap();
+    if dir.join("CASE_PROBE").exists() {
+        return false;
+    }
+    for (source, name) in files {
+        std::fs::copy(source, dir.join(name)).unwrap();
+    }
+    true
+}
+
+#[test]
+fn from_path_finds_differently_cased_files() {
+    let dir = std::env::temp_dir().join("shapefile_differently_cased_files");
+    let files = [
+        ("tests/data/line.shp", "ROADS.SHP"),
+        ("tests/data/line.shx", "Roads.shx"),
+        ("tests/data/multipatch.dbf", "roads.dbf"),
+    ];
+    if !mixed_case_dir(&dir, &files) {
+        return;
+    }
+    let mut reader = shapefile::Reader::from_path(dir.join("ROADS.SHP")).unwrap();
+    assert!(reader.has_index() && reader.has_attributes());
+    assert_eq!(reader.shp_path(), Some(dir.join("ROADS.SHP").as_path()));
+    assert_eq!(reader.shx_path(), Some(dir.join("Roads.shx").as_path()));
+    assert_eq!(reader.dbf_path(), Some(dir.join("roads.dbf").as_path()));
+    assert!(reader.read_nth_shape(0).unwrap().is_ok());
+}
+
+#[test]
+fn from_path_prefers_the_case_of_the_shp() {
+    let dir = std::env::temp_dir().join("shapefile_prefers_the_case_of_the_shp");
+    let files = [
+        ("tests/data/multipatch.shp", "Roads.shp"),
+        ("tests/data/multipatch.dbf", "ROADS.DBF"),
+        ("tests/data/multipatch.dbf", "Roads.DBF"),
+        ("tests/data/multipatch.dbf", "roads.dbf"),
+    ];
+    if !mixed_case_dir(&dir, &files) {
+        return;
+    }
+    let reader = shapefile::Reader::from_path(dir.join("Roads.shp")).unwrap();
+    assert_eq!(reader.dbf_path(), Some(dir.join("Roads.DBF").as_path()));
+    assert_eq!(reader.shx_path(), None);
+
+    // The exact name is used when it exists
+    std::fs::copy("tests/data/multipatch.dbf", dir.join("Roads.dbf")).unwrap();
+    let reader = shapefile::Reader::from_path(dir.join("Roads.shp")).unwrap();
+    assert_eq!(reader.dbf_path(), Some(dir.join("Roads.dbf").as_path()));
+}
+
+#[test]
+fn from_path_without_exact_stem() {
+    let dir = std::env::temp_dir().join("shapefile_from_path_without_exact_stem");
+    let files = [
+        ("tests/data/multipatch.shp", "roads.shp"),
+        ("tests/data/multipatch.dbf", "ROADS.dbf"),
+        ("tests/data/multipatch.dbf", "Roads.dbf"),
+    ];
+    if !mixed_case_dir(&dir, &files) {
+        return;
+    }
+    // The first one by name
+    let reader = shapefile::Reader::from_path(dir.join("roads.shp")).unwrap();
+    assert_eq!(reader.dbf_path(), Some(dir.join("ROADS.dbf").as_path()));
+}
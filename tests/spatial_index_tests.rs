@@ -0,0 +1,114 @@
+#[cfg(feature = "rstar")]
+extern crate shapefile;
+
+#[cfg(feature = "rstar")]
+mod spatial_index {
+    use std::cell::Cell;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+    use std::rc::Rc;
+
+    use shapefile::record::BBox;
+    use shapefile::{NullShape, Point, Polyline, Shape, SpatialIndex};
+
+    const NUM_SHAPES: usize = 100;
+    const NUM_POINTS: usize = 1000;
+
+    /// Source that counts the number of bytes read from it
+    struct CountingSource {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: Rc<Cell<usize>>,
+    }
+
+    impl Read for CountingSource {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = self.inner.read(buf)?;
+            self.bytes_read.set(self.bytes_read.get() + n);
+            Ok(n)
+        }
+    }
+
+    impl Seek for CountingSource {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    /// Polylines on a diagonal, the i-th spans from (i, i) to (i + 0.5, i + 0.5)
+    fn diagonal_polylines() -> Vec<Shape> {
+        let mut shapes = (0..NUM_SHAPES)
+            .map(|i| {
+                let points = (0..NUM_POINTS)
+                    .map(|j| {
+                        let offset = 0.5 * j as f64 / (NUM_POINTS - 1) as f64;
+                        Point::new(i as f64 + offset, i as f64 + offset)
+                    })
+                    .collect::<Vec<Point>>();
+                Shape::Polyline(Polyline::new(points, vec![0]))
+            })
+            .collect::<Vec<Shape>>();
+        shapes.push(Shape::NullShape(NullShape));
+        shapes
+    }
+
+    fn write_to_memory(shapes: Vec<Shape>) -> (Vec<u8>, Vec<u8>) {
+        let mut shp = Vec::<u8>::new();
+        let mut shx = Vec::<u8>::new();
+        {
+            let mut writer = shapefile::Writer::new(Cursor::new(&mut shp));
+            writer.add_index_dest(Cursor::new(&mut shx));
+            writer.write_shapes(shapes).unwrap();
+        }
+        (shp, shx)
+    }
+
+    #[test]
+    fn build_does_not_read_points() {
+        let (shp, _) = write_to_memory(diagonal_polylines());
+        let bytes_read = Rc::new(Cell::new(0usize));
+        let source = CountingSource {
+            inner: Cursor::new(shp),
+            bytes_read: bytes_read.clone(),
+        };
+        let mut reader = shapefile::Reader::new(source).unwrap();
+        bytes_read.set(0);
+
+        let index = SpatialIndex::build(&mut reader).unwrap();
+        assert_eq!(index.len(), NUM_SHAPES);
+
+        // record header + shape type + bbox for the polylines
+        // record header + shape type for the null shape
+        let expected = NUM_SHAPES * (8 + 4 + 32) + (8 + 4);
+        assert_eq!(bytes_read.get(), expected);
+    }
+
+    #[test]
+    fn query_then_read_nth_shape() {
+        let (shp, shx) = write_to_memory(diagonal_polylines());
+        let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+        reader.add_index_source(Cursor::new(shx)).unwrap();
+
+        let index = SpatialIndex::build(&mut reader).unwrap();
+        let indices = index.query(&BBox::new(10.25, 10.25, 12.75, 12.75));
+        assert_eq!(indices, vec![10, 11, 12]);
+
+        for i in indices {
+            match reader.read_nth_shape(i).unwrap().unwrap() {
+                Shape::Polyline(polyline) => assert_eq!(polyline.points[0].x, i as f64),
+                _ => panic!("Expected a Polyline"),
+            }
+        }
+
+        assert!(index.query(&BBox::new(-10.0, -10.0, -1.0, -1.0)).is_empty());
+    }
+
+    #[test]
+    fn build_with_invalid_record_size() {
+        let mut shp = std::fs::read("tests/data/line.shp").unwrap();
+        shp[104..108].copy_from_slice(&(-1i32).to_be_bytes());
+        let mut reader = shapefile::Reader::from_seekable(Cursor::new(shp)).unwrap();
+        match SpatialIndex::build(&mut reader) {
+            Err(shapefile::Error::InvalidShapeRecordSize) => {}
+            _ => panic!("expected an InvalidShapeRecordSize error"),
+        }
+    }
+}
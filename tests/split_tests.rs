@@ -0,0 +1,129 @@
+extern crate dbase;
+extern crate shapefile;
+
+use std::path::PathBuf;
+
+use dbase::FieldValue;
+use shapefile::{Point, Shape};
+
+fn state_of(record: &dbase::Record) -> String {
+    match record.get("STATE") {
+        Some(FieldValue::Character(Some(state))) => state.clone(),
+        _ => panic!("Record has no STATE"),
+    }
+}
+
+#[test]
+fn split_three_keys() {
+    let dir = std::env::temp_dir().join("shapefile_split_three_keys");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.shp");
+
+    let states = ["a", "b", "c", "a", "c", "a"];
+    let points = states
+        .iter()
+        .enumerate()
+        .map(|(i, _)| Point::new(i as f64, -(i as f64)))
+        .collect::<Vec<Point>>();
+    let records = states
+        .iter()
+        .map(|state| {
+            let mut record = dbase::Record::new();
+            record.insert("STATE".to_string(), FieldValue::from(*state));
+            record
+        })
+        .collect::<Vec<dbase::Record>>();
+
+    let writer = shapefile::Writer::from_path(&input).unwrap();
+    writer.write_shapes_and_records(points, records).unwrap();
+
+    let reader = shapefile::Reader::from_path(&input).unwrap();
+    let mut outputs = shapefile::split_by(
+        reader,
+        |_shape, record| state_of(record),
+        |state| dir.join(format!("{}.shp", state)),
+    )
+    .unwrap();
+    outputs.sort();
+
+    let expected: Vec<PathBuf> = vec![dir.join("a.shp"), dir.join("b.shp"), dir.join("c.shp")];
+    assert_eq!(outputs, expected);
+
+    for (path, expected_count) in outputs.iter().zip(&[3, 1, 2]) {
+        let state = path.file_stem().unwrap().to_str().unwrap().to_string();
+        let reader = shapefile::Reader::from_path(path).unwrap();
+        assert_eq!(reader.header().shape_type, shapefile::ShapeType::Point);
+
+        let features = reader
+            .iter_shapes_and_records()
+            .unwrap()
+            .collect::<Result<Vec<(Shape, dbase::Record)>, shapefile::Error>>()
+            .unwrap();
+        assert_eq!(features.len(), *expected_count);
+        for (shape, record) in &features {
+            assert_eq!(state_of(record), state);
+            match shape {
+                Shape::Point(point) => assert_eq!(states[point.x as usize], state),
+                _ => panic!("Shape is not a Point"),
+            }
+        }
+    }
+}
+
+#[test]
+fn split_streams_many_features() {
+    let dir = std::env::temp_dir().join("shapefile_split_many_features");
+    std::fs::create_dir_all(&dir).unwrap();
+    let input = dir.join("input.shp");
+
+    let num_features = 2000;
+    let points = (0..num_features)
+        .map(|i| Point::new(i as f64, 0.0))
+        .collect::<Vec<Point>>();
+    let records = (0..num_features)
+        .map(|i| {
+            let mut record = dbase::Record::new();
+            let state = ["a", "b", "c"][i % 3];
+            record.insert("STATE".to_string(), FieldValue::from(state));
+            record.insert("NAME".to_string(), FieldValue::from("x".repeat(i % 40).as_str()));
+            record.insert("VALUE".to_string(), FieldValue::Numeric(Some(i as f64)));
+            record
+        })
+        .collect::<Vec<dbase::Record>>();
+    let writer = shapefile::Writer::from_path(&input).unwrap();
+    writer.write_shapes_and_records(points, records).unwrap();
+
+    let reader = shapefile::Reader::from_path(&input).unwrap();
+    let outputs = shapefile::split_by(
+        reader,
+        |_shape, record| state_of(record),
+        |state| dir.join(format!("{}.shp", state)),
+    )
+    .unwrap();
+    // In the order of the first feature of each key
+    let expected: Vec<PathBuf> = vec![dir.join("a.shp"), dir.join("b.shp"), dir.join("c.shp")];
+    assert_eq!(outputs, expected);
+
+    for (key, path) in outputs.iter().enumerate() {
+        let features = shapefile::Reader::from_path(path)
+            .unwrap()
+            .iter_shapes_and_records_as::<Point>()
+            .unwrap()
+            .collect::<Result<Vec<(Point, dbase::Record)>, shapefile::Error>>()
+            .unwrap();
+        assert_eq!(features.len(), (0..num_features).filter(|i| i % 3 == key).count());
+        // The fields have the length of their longest value: 1 + 39 + 4 bytes
+        let dbf_length = std::fs::metadata(path.with_extension("dbf")).unwrap().len();
+        assert_eq!(dbf_length as usize, 32 + 3 * 32 + 1 + features.len() * (1 + 44) + 1);
+        for (point, record) in &features {
+            let i = point.x as usize;
+            assert_eq!(i % 3, key);
+            let name = match record.get("NAME") {
+                Some(FieldValue::Character(name)) => name.clone().unwrap_or_default(),
+                _ => panic!("Record has no NAME"),
+            };
+            assert_eq!(name, "x".repeat(i % 40));
+            assert_eq!(record.get("VALUE"), Some(&FieldValue::Numeric(Some(i as f64))));
+        }
+    }
+}
@@ -0,0 +1,68 @@
+extern crate shapefile;
+
+use shapefile::record::{DimStats, MultipartShape};
+use shapefile::{PointM, PointZ, PolygonZ, PolylineM, PolylineZ, NO_DATA};
+
+#[test]
+fn part_z_stats() {
+    let points = vec![
+        PointZ::new(0.0, 0.0, 10.0, NO_DATA),
+        PointZ::new(1.0, 0.0, 20.0, NO_DATA),
+        PointZ::new(2.0, 0.0, 45.0, NO_DATA),
+        PointZ::new(0.0, 1.0, -5.0, NO_DATA),
+        PointZ::new(1.0, 1.0, 5.0, NO_DATA),
+    ];
+    let polyline = PolylineZ::new(points, vec![0, 3]);
+    assert_eq!(
+        polyline.part_z_stats(0),
+        Some(DimStats {
+            min: 10.0,
+            max: 45.0,
+            mean: 25.0
+        })
+    );
+    assert_eq!(
+        polyline.part_z_stats(1),
+        Some(DimStats {
+            min: -5.0,
+            max: 5.0,
+            mean: 0.0
+        })
+    );
+    assert_eq!(polyline.part_z_stats(2), None);
+    // The measures are all missing
+    assert_eq!(polyline.part_m_stats(0), None);
+
+    let ring = vec![
+        PointZ::new(0.0, 0.0, 1.0, 4.0),
+        PointZ::new(0.0, 1.0, 2.0, NO_DATA),
+        PointZ::new(1.0, 1.0, 3.0, 8.0),
+        PointZ::new(0.0, 0.0, 1.0, NO_DATA),
+    ];
+    let polygon = PolygonZ::new(ring, vec![0]);
+    assert_eq!(polygon.part_z_stats(0).unwrap().max, 3.0);
+    assert_eq!(polygon.part_m_stats(0).unwrap().mean, 6.0);
+}
+
+#[test]
+fn part_m_stats_skip_no_data() {
+    let points = vec![
+        PointM::new(0.0, 0.0, NO_DATA),
+        PointM::new(1.0, 0.0, 3.0),
+        PointM::new(2.0, 0.0, NO_DATA),
+        PointM::new(3.0, 0.0, 1.0),
+        PointM::new(0.0, 1.0, NO_DATA),
+        PointM::new(1.0, 1.0, -1e39),
+    ];
+    let polyline = PolylineM::new(points, vec![0, 4]);
+    assert_eq!(
+        polyline.part_m_stats(0),
+        Some(DimStats {
+            min: 1.0,
+            max: 3.0,
+            mean: 2.0
+        })
+    );
+    // Values below -1e38 are NO_DATA too
+    assert_eq!(polyline.part_m_stats(1), None);
+}
@@ -0,0 +1,156 @@
+extern crate shapefile;
+
+use std::io::Cursor;
+
+use shapefile::record::WritableShape;
+use shapefile::streaming::ParsedEvent;
+use shapefile::{Error, Point, Shape, StreamingParser, Writer};
+
+fn fixtures() -> Vec<std::path::PathBuf> {
+    let mut paths = std::fs::read_dir("tests/data")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == "shp"))
+        .collect::<Vec<_>>();
+    paths.sort();
+    paths
+}
+
+/// Returns the bytes of the content of the shape, as it is written in a record
+fn content_of(shape: Shape) -> Vec<u8> {
+    let mut bytes = Vec::<u8>::new();
+    bytes.extend_from_slice(&(shape.shapetype() as i32).to_le_bytes());
+    shape.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+/// Feeds the bytes to a parser in chunks of `chunk_size` bytes and returns all the events
+fn feed_in_chunks(bytes: &[u8], chunk_size: usize) -> Vec<ParsedEvent> {
+    let mut parser = StreamingParser::new();
+    let events = bytes
+        .chunks(chunk_size)
+        .flat_map(|chunk| parser.feed(chunk))
+        .collect::<Vec<_>>();
+    parser.finish().unwrap();
+    events
+}
+
+#[test]
+fn events_are_the_shapes_of_the_reader() {
+    for path in fixtures() {
+        let name = path.display().to_string();
+        let bytes = std::fs::read(&path).unwrap();
+        let reader = shapefile::Reader::from_slice(&bytes).unwrap();
+        let header = *reader.header();
+        let shapes = reader.read().unwrap();
+        let numbers = shapefile::Reader::from_slice(&bytes)
+            .unwrap()
+            .iter_raw_records()
+            .map(|raw_record| raw_record.unwrap().0);
+        let expected = numbers.zip(shapes).collect::<Vec<_>>();
+
+        let mut events = feed_in_chunks(&bytes, 7)
+            .into_iter()
+            .filter(|event| !matches!(event, ParsedEvent::NeedMoreData));
+        match events.next() {
+            Some(ParsedEvent::Header(read)) => assert!(read == header, "{}", name),
+            _ => panic!("{}: expected the header first", name),
+        }
+        let mut records = Vec::<(i32, Shape)>::new();
+        for event in events.by_ref() {
+            match event {
+                ParsedEvent::Record(number, shape) => records.push((number, shape)),
+                ParsedEvent::Finished => break,
+                _ => panic!("{}: unexpected event", name),
+            }
+        }
+        assert!(events.next().is_none(), "{}: events after Finished", name);
+
+        assert_eq!(records.len(), expected.len(), "{}", name);
+        for ((number, shape), (expected_number, expected)) in records.into_iter().zip(expected) {
+            assert_eq!(number, expected_number, "{}", name);
+            assert_eq!(content_of(shape), content_of(expected), "{}", name);
+        }
+    }
+}
+
+#[test]
+fn need_more_data_ends_incomplete_feeds() {
+    let bytes = std::fs::read("tests/data/point.shp").unwrap();
+    let mut parser = StreamingParser::new();
+    let events = parser.feed(&bytes[..99]);
+    assert!(matches!(events[..], [ParsedEvent::NeedMoreData]));
+    let events = parser.feed(&bytes[99..110]);
+    assert!(matches!(events[..], [ParsedEvent::Header(_), ParsedEvent::NeedMoreData]));
+    let events = parser.feed(&bytes[110..]);
+    assert!(matches!(events[..], [ParsedEvent::Record(1, _), ParsedEvent::Finished]));
+    assert!(parser.feed(&[0u8; 8]).is_empty());
+    parser.finish().unwrap();
+}
+
+#[test]
+fn invalid_bytes_end_with_an_error() {
+    let mut parser = StreamingParser::new();
+    match parser.feed(&[0u8; 100])[..] {
+        [ParsedEvent::Error(Error::InvalidFileCode(0))] => {}
+        _ => panic!("Expected InvalidFileCode"),
+    }
+    assert!(parser.feed(&[0u8; 100]).is_empty());
+
+    // A point record which content is too short for the point
+    let mut writer = Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![Point::new(1.0, 2.0)]).unwrap();
+    let mut shp = writer.dest.into_inner();
+    shp[104..108].copy_from_slice(&8i32.to_be_bytes());
+    shp.truncate(100 + 8 + 16);
+    let file_length = (shp.len() / 2) as i32;
+    shp[24..28].copy_from_slice(&file_length.to_be_bytes());
+    let events = feed_in_chunks(&shp, 7);
+    match events.last() {
+        Some(ParsedEvent::Error(Error::InvalidShapeRecordSize)) => {}
+        _ => panic!("Expected InvalidShapeRecordSize"),
+    }
+}
+
+#[test]
+fn record_larger_than_the_limit_is_an_error() {
+    let mut writer = Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![Point::new(1.0, 2.0)]).unwrap();
+    let shp = writer.dest.into_inner();
+
+    // The content of a point record has 20 bytes, the error comes before them
+    let mut parser = StreamingParser::new().max_record_size_bytes(Some(16));
+    match parser.feed(&shp[..100 + 8]).last() {
+        Some(ParsedEvent::Error(Error::RecordTooLarge {
+            record_number: 1,
+            declared: 20,
+            limit: 16,
+        })) => {}
+        _ => panic!("Expected RecordTooLarge"),
+    }
+
+    let mut parser = StreamingParser::new().max_record_size_bytes(None);
+    assert!(parser
+        .feed(&shp)
+        .iter()
+        .any(|event| matches!(event, ParsedEvent::Record(1, _))));
+}
+
+#[test]
+fn finish_reports_truncated_files() {
+    let bytes = std::fs::read("tests/data/line.shp").unwrap();
+    match StreamingParser::new().finish() {
+        Err(Error::EmptyFile) => {}
+        _ => panic!("Expected EmptyFile"),
+    }
+    for &num_bytes in &[50, bytes.len() - 1] {
+        let mut parser = StreamingParser::new();
+        parser.feed(&bytes[..num_bytes]);
+        match parser.finish() {
+            Err(Error::TruncatedFile { actual_bytes, .. }) => {
+                assert_eq!(actual_bytes, num_bytes as u64)
+            }
+            _ => panic!("Expected TruncatedFile"),
+        }
+    }
+}
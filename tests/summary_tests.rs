@@ -0,0 +1,156 @@
+extern crate shapefile;
+
+use shapefile::record::RingCounts;
+use shapefile::{Shape, ShapeType};
+
+mod testfiles;
+
+fn num_points_of(shape: &Shape) -> usize {
+    match shape {
+        Shape::NullShape(_) | Shape::Unknown { .. } => 0,
+        Shape::Point(_) | Shape::PointM(_) | Shape::PointZ(_) => 1,
+        Shape::Polyline(shp) => shp.points.len(),
+        Shape::PolylineM(shp) => shp.points.len(),
+        Shape::PolylineZ(shp) => shp.points.len(),
+        Shape::Polygon(shp) => shp.points.len(),
+        Shape::PolygonM(shp) => shp.points.len(),
+        Shape::PolygonZ(shp) => shp.points.len(),
+        Shape::Multipoint(shp) => shp.points.len(),
+        Shape::MultipointM(shp) => shp.points.len(),
+        Shape::MultipointZ(shp) => shp.points.len(),
+        Shape::Multipatch(shp) => shp.points.len(),
+    }
+}
+
+fn ring_counts_of(shape: &Shape) -> Option<RingCounts> {
+    match shape {
+        Shape::Polygon(shp) => Some(shp.ring_classification()),
+        Shape::PolygonM(shp) => Some(shp.ring_classification()),
+        Shape::PolygonZ(shp) => Some(shp.ring_classification()),
+        _ => None,
+    }
+}
+
+#[test]
+fn summary_matches_full_parse() {
+    let paths = [
+        testfiles::LINE_PATH,
+        testfiles::LINEM_PATH,
+        testfiles::LINEZ_PATH,
+        testfiles::POINT_PATH,
+        testfiles::POINTM_PATH,
+        testfiles::POINTZ_PATH,
+        testfiles::POINTM_NO_M_PATH,
+        testfiles::POINTZ_NO_M_PATH,
+        testfiles::POLYGON_PATH,
+        testfiles::POLYGONM_PATH,
+        testfiles::POLYGONZ_PATH,
+        testfiles::POLYGONM_NO_M_PATH,
+        testfiles::MULTIPOINT_PATH,
+        testfiles::MULTIPOINTZ_PATH,
+        testfiles::MULTIPOINTM_NO_M_PATH,
+        testfiles::MULTIPOINTZ_NO_M_PATH,
+        testfiles::MULTIPATCH_PATH,
+    ];
+    for path in &paths {
+        let mut reader = shapefile::Reader::from_path(path).unwrap();
+        let summary = reader.summary_with_rings().unwrap();
+        let shapes = reader.read().unwrap();
+
+        assert_eq!(summary.num_records, shapes.len(), "{}", path);
+        assert_eq!(
+            summary.num_points,
+            shapes.iter().map(num_points_of).sum::<usize>(),
+            "{}",
+            path
+        );
+        assert_eq!(summary.num_null_shapes(), 0, "{}", path);
+        let ring_counts = shapes.iter().filter_map(ring_counts_of).collect::<Vec<_>>();
+        let summary_ring_counts = summary.ring_counts.unwrap();
+        assert_eq!(
+            summary_ring_counts.outer,
+            ring_counts.iter().map(|c| c.outer).sum::<usize>(),
+            "{}",
+            path
+        );
+        assert_eq!(
+            summary_ring_counts.inner,
+            ring_counts.iter().map(|c| c.inner).sum::<usize>(),
+            "{}",
+            path
+        );
+        assert_eq!(
+            summary.num_polygons_with_holes,
+            Some(ring_counts.iter().filter(|c| c.inner > 0).count()),
+            "{}",
+            path
+        );
+        assert!(summary.record_size_range.is_some(), "{}", path);
+    }
+}
+
+#[test]
+fn summary_of_pointz() {
+    let mut reader = shapefile::Reader::from_path(testfiles::POINTZ_PATH).unwrap();
+    let summary = reader.summary().unwrap();
+    assert_eq!(summary.num_records, 2);
+    assert_eq!(summary.shape_type_counts.get(&ShapeType::PointZ), Some(&2));
+    assert_eq!(summary.record_size_range, Some((36, 36)));
+    assert!(!summary.measures_used);
+    assert_eq!(summary.bbox_delta(), Some([0.0, 0.0, 0.0, 0.0]));
+
+    // the reader can still be used after the summary
+    assert_eq!(reader.read().unwrap().len(), 2);
+}
+
+#[test]
+fn summary_detects_measures() {
+    let used = [
+        (testfiles::POINTM_PATH, true),
+        (testfiles::POINTM_NO_M_PATH, false),
+        (testfiles::POLYGONZ_PATH, true),
+        (testfiles::POLYGONM_NO_M_PATH, false),
+        (testfiles::LINE_PATH, false),
+    ];
+    for (path, expected) in &used {
+        let mut reader = shapefile::Reader::from_path(path).unwrap();
+        assert_eq!(reader.summary().unwrap().measures_used, *expected, "{}", path);
+    }
+}
+
+#[test]
+fn summary_counts_rings() {
+    let mut reader = shapefile::Reader::from_path(testfiles::POLYGON_PATH).unwrap();
+    let summary = reader.summary_with_rings().unwrap();
+    assert_eq!(summary.ring_counts, Some(RingCounts { outer: 1, inner: 2 }));
+    assert_eq!(summary.num_polygons_with_holes, Some(1));
+    // The points are not read by default
+    let summary = reader.summary().unwrap();
+    assert_eq!(summary.ring_counts, None);
+    assert_eq!(summary.num_polygons_with_holes, None);
+
+    let mut reader = shapefile::Reader::from_path(testfiles::LINE_PATH).unwrap();
+    let summary = reader.summary_with_rings().unwrap();
+    assert_eq!(summary.ring_counts, Some(RingCounts::default()));
+    assert_eq!(summary.num_polygons_with_holes, Some(0));
+}
+
+#[test]
+fn summary_of_invalid_counts_is_an_error() {
+    let line = std::fs::read(testfiles::LINE_PATH).unwrap();
+    // The number of points, after the shape type, the bbox and the number of parts,
+    // then the content length of the record
+    for &(offset, value) in &[(148usize, -5i32), (148, 1 << 30), (104, -1)] {
+        let mut bytes = line.clone();
+        if offset == 104 {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        } else {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        }
+        let mut reader = shapefile::Reader::from_seekable(std::io::Cursor::new(bytes)).unwrap();
+        match reader.summary() {
+            Err(shapefile::Error::InvalidShapeRecordSize) => {}
+            _ => panic!("expected an InvalidShapeRecordSize error for {}", value),
+        }
+    }
+}
@@ -30,8 +30,241 @@ mod geo_types_conversions {
         assert_eq!(&polygon.parts, &shp_polygons[0].parts);
     }
 
+    fn square(xmin: f64, ymin: f64, size: f64, clockwise: bool) -> Vec<shapefile::Point> {
+        let mut points = vec![
+            shapefile::Point::new(xmin, ymin),
+            shapefile::Point::new(xmin, ymin + size),
+            shapefile::Point::new(xmin + size, ymin + size),
+            shapefile::Point::new(xmin + size, ymin),
+            shapefile::Point::new(xmin, ymin),
+        ];
+        if !clockwise {
+            points.reverse();
+        }
+        points
+    }
+
+    #[test]
+    fn test_polygon_with_holes_to_geo_polygon() {
+        let mut points = square(0.0, 0.0, 10.0, true);
+        points.extend(square(1.0, 1.0, 2.0, false));
+        points.extend(square(5.0, 5.0, 2.0, false));
+        let polygon = shapefile::Polygon::new(points, vec![0, 5, 10]);
+        assert_eq!(polygon.outer_rings().count(), 1);
+        assert_eq!(polygon.inner_rings().count(), 2);
+
+        let geo_polygon = geo_types::Polygon::<f64>::try_from(polygon).unwrap();
+        assert_eq!(geo_polygon.exterior().num_coords(), 5);
+        assert_eq!(geo_polygon.interiors().len(), 2);
+        assert_eq!(geo_polygon.interiors()[1].0[0], geo_types::Coordinate { x: 5.0, y: 5.0 });
+    }
+
+    #[test]
+    fn test_geo_converter_is_the_same_as_try_from() {
+        let path = "tests/data/multi_polygon.shp";
+        let mut polygons = shapefile::read_as::<_, shapefile::Polygon>(path).unwrap();
+        let mut points = square(0.0, 0.0, 10.0, true);
+        points.extend(square(1.0, 1.0, 2.0, false));
+        points.extend(square(20.0, 0.0, 10.0, true));
+        polygons.push(shapefile::Polygon::new(points, vec![0, 5, 10]));
+
+        let mut converter = shapefile::GeoConverter::new();
+        for _ in 0..2 {
+            for polygon in &polygons {
+                let expected = geo_types::MultiPolygon::<f64>::try_from(polygon.clone()).unwrap();
+                let rings = expected.0.iter()
+                    .flat_map(|p| Some(p.exterior()).into_iter().chain(p.interiors()));
+                for ring in rings {
+                    assert_eq!(ring.0.capacity(), ring.0.len());
+                }
+                let converted = converter.convert_polygon(polygon).unwrap();
+                assert_eq!(converted, expected);
+                converter.recycle(converted);
+            }
+        }
+        // Every buffer is reused, only the polygon with the most rings allocated them
+        let max_num_rings = polygons.iter().map(|p| p.parts.len()).max().unwrap();
+        assert_eq!(converter.num_buffers(), max_num_rings);
+    }
+
+    #[test]
+    fn test_geo_converter_orphan_inner_ring() {
+        let polygon = shapefile::Polygon::new(square(0.0, 0.0, 1.0, false), vec![0]);
+        let mut converter = shapefile::GeoConverter::new();
+        match converter.convert_polygon(&polygon) {
+            Err(shapefile::Error::OrphanInnerRing) => {}
+            _ => panic!("Expected OrphanInnerRing"),
+        }
+    }
+
+    #[test]
+    fn test_polygon_with_two_outer_rings_to_geo_polygon() {
+        let mut points = square(0.0, 0.0, 1.0, true);
+        points.extend(square(5.0, 5.0, 1.0, true));
+        let polygon = shapefile::Polygon::new(points, vec![0, 5]);
+        match geo_types::Polygon::<f64>::try_from(polygon) {
+            Err(e @ shapefile::Error::ExpectedSingleOuterRing(2)) => {
+                assert!(format!("{}", e).contains("2 outer rings"));
+            }
+            _ => panic!("Expected ExpectedSingleOuterRing(2)"),
+        }
+    }
+
+    #[test]
+    fn test_ring_classification_is_the_same_as_multi_polygon() {
+        let shp_polygons = shapefile::read_as::<_, shapefile::Polygon>("tests/data/multi_polygon.shp")
+            .unwrap();
+        for polygon in shp_polygons {
+            let num_outer_rings = polygon.outer_rings().count();
+            let num_inner_rings = polygon.inner_rings().count();
+            let counts = polygon.ring_classification();
+            assert_eq!((counts.outer, counts.inner), (num_outer_rings, num_inner_rings));
+            let multi_polygon = geo_types::MultiPolygon::<f64>::try_from(polygon).unwrap();
+            assert_eq!(multi_polygon.0.len(), num_outer_rings);
+            assert_eq!(
+                multi_polygon.0.iter().map(|p| p.interiors().len()).sum::<usize>(),
+                num_inner_rings
+            );
+        }
+    }
+
+    #[test]
+    fn test_polyline_to_line_string() {
+        let polyline = shapefile::Polyline::new(square(0.0, 0.0, 1.0, true), vec![0]);
+        let line_string = geo_types::LineString::<f64>::try_from(polyline).unwrap();
+        assert_eq!(line_string.num_coords(), 5);
+
+        let polyline = shapefile::Polyline::new(square(0.0, 0.0, 1.0, true), vec![0, 2]);
+        match geo_types::LineString::<f64>::try_from(polyline) {
+            Err(shapefile::Error::ExpectedSinglePart(2)) => {}
+            _ => panic!("Expected ExpectedSinglePart(2)"),
+        }
+    }
+
+    fn geo_ring(xmin: f64, ymin: f64, size: f64, clockwise: bool) -> geo_types::LineString<f64> {
+        square(xmin, ymin, size, clockwise)
+            .into_iter()
+            .map(|p| geo_types::Coordinate { x: p.x, y: p.y })
+            .collect::<Vec<geo_types::Coordinate<f64>>>()
+            .into()
+    }
+
+    /// Square polygon of 10x10 with `num_holes` holes of 1x1 along its diagonal
+    fn geo_polygon_with_holes(xmin: f64, num_holes: usize) -> geo_types::Polygon<f64> {
+        let holes = (0..num_holes)
+            .map(|i| geo_ring(xmin + 1.0 + 2.0 * i as f64, 1.0 + 2.0 * i as f64, 1.0, false))
+            .collect();
+        geo_types::Polygon::new(geo_ring(xmin, 0.0, 10.0, true), holes)
+    }
+
+    #[test]
+    fn test_geo_polygon_with_holes_to_polygon() {
+        let polygon = shapefile::Polygon::from(geo_polygon_with_holes(0.0, 2));
+        assert_eq!(polygon.parts, vec![0, 5, 10]);
+        assert_eq!(polygon.points.len(), 15);
+        assert_eq!(polygon.points[5], shapefile::Point::new(1.0, 1.0));
+        assert_eq!(polygon.points[10], shapefile::Point::new(3.0, 3.0));
+        assert_eq!(polygon.outer_rings().count(), 1);
+        assert_eq!(polygon.inner_rings().count(), 2);
+    }
+
+    #[test]
+    fn test_geo_multi_polygon_with_holes_to_polygon() {
+        let multi_polygon = geo_types::MultiPolygon::from(vec![
+            geo_polygon_with_holes(0.0, 1),
+            geo_polygon_with_holes(20.0, 1),
+        ]);
+        let polygon = shapefile::Polygon::from(multi_polygon);
+        assert_eq!(polygon.parts, vec![0, 5, 10, 15]);
+        assert_eq!(polygon.points.len(), 20);
+        let first_points = polygon
+            .parts
+            .iter()
+            .map(|i| polygon.points[*i as usize])
+            .collect::<Vec<shapefile::Point>>();
+        assert_eq!(
+            first_points,
+            vec![
+                shapefile::Point::new(0.0, 0.0),
+                shapefile::Point::new(1.0, 1.0),
+                shapefile::Point::new(20.0, 0.0),
+                shapefile::Point::new(21.0, 1.0),
+            ]
+        );
+        assert_eq!(polygon.outer_rings().count(), 2);
+        assert_eq!(polygon.inner_rings().count(), 2);
+    }
+
+    #[test]
+    fn test_geo_multi_polygon_round_trip() {
+        // Every combination of up to 3 polygons having up to 3 holes each
+        for num_polygons in 0..4 {
+            for num_holes in 0..4usize.pow(num_polygons as u32) {
+                let geo_polygons = (0..num_polygons)
+                    .map(|i| {
+                        let holes = num_holes / 4usize.pow(i as u32) % 4;
+                        geo_polygon_with_holes(20.0 * i as f64, holes)
+                    })
+                    .collect::<Vec<geo_types::Polygon<f64>>>();
+                let multi_polygon = geo_types::MultiPolygon::from(geo_polygons.clone());
+
+                let polygon = shapefile::Polygon::from(multi_polygon);
+                assert_eq!(
+                    polygon.parts.len(),
+                    geo_polygons.iter().map(|p| 1 + p.interiors().len()).sum::<usize>()
+                );
+                let round_tripped = geo_types::MultiPolygon::<f64>::try_from(polygon).unwrap();
+                assert_eq!(round_tripped.0, geo_polygons);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ring_classification_of_polygons_with_holes() {
+        for num_holes in 0..4 {
+            let geo_polygons = vec![
+                geo_polygon_with_holes(0.0, num_holes),
+                geo_polygon_with_holes(20.0, 3 - num_holes),
+            ];
+            let polygon = shapefile::Polygon::from(geo_types::MultiPolygon::from(geo_polygons));
+            let counts = polygon.ring_classification();
+            let multi_polygon = geo_types::MultiPolygon::<f64>::try_from(polygon).unwrap();
+            assert_eq!(counts.outer, multi_polygon.0.len());
+            assert_eq!(
+                counts.inner,
+                multi_polygon.0.iter().map(|p| p.interiors().len()).sum::<usize>()
+            );
+            assert_eq!(counts.inner, 3);
+        }
+    }
+
     #[test]
     fn test_multipatch_conversion() {
 
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_iter_as_geo() {
+        let reader = shapefile::Reader::from_path("tests/data/multi_polygon.shp").unwrap();
+        let geometries = reader
+            .iter_as_geo()
+            .collect::<Result<Vec<geo_types::Geometry<f64>>, _>>()
+            .unwrap();
+        assert_eq!(geometries.len(), 1);
+        match geometries[0] {
+            geo_types::Geometry::MultiPolygon(ref multi_polygon) => {
+                assert_eq!(multi_polygon.0.len(), 4)
+            }
+            _ => panic!("Expected a MultiPolygon"),
+        }
+    }
+
+    #[test]
+    fn test_bbox_rect_round_trip() {
+        let bbox = shapefile::record::BBox::new(-1.5, 2.0, 3.0, 4.25);
+        let rect = geo_types::Rect::<f64>::from(bbox);
+        assert_eq!(rect.min, geo_types::Coordinate { x: -1.5, y: 2.0 });
+        assert_eq!(rect.max, geo_types::Coordinate { x: 3.0, y: 4.25 });
+        assert_eq!(shapefile::record::BBox::from(rect), bbox);
+    }
+}
@@ -11,16 +11,28 @@ pub const LINEZ_PATH: &str = "./tests/data/linez.shp";
 pub const POINT_PATH: &str = "./tests/data/point.shp";
 pub const POINTM_PATH: &str = "./tests/data/pointm.shp";
 pub const POINTZ_PATH: &str = "./tests/data/pointz.shp";
+pub const POINTM_NO_M_PATH: &str = "./tests/data/pointm_no_m.shp";
+pub const POINTZ_NO_M_PATH: &str = "./tests/data/pointz_no_m.shp";
 
 pub const POLYGON_PATH: &str = "./tests/data/polygon.shp";
 pub const POLYGONM_PATH: &str = "./tests/data/polygonm.shp";
 pub const POLYGONZ_PATH: &str = "./tests/data/polygonz.shp";
+pub const POLYGONM_NO_M_PATH: &str = "./tests/data/polygonm_no_m.shp";
 
 pub const MULTIPOINT_PATH: &str = "./tests/data/multipoint.shp";
 pub const MULTIPOINTZ_PATH: &str = "./tests/data/multipointz.shp";
+pub const MULTIPOINTM_NO_M_PATH: &str = "./tests/data/multipointm_no_m.shp";
+pub const MULTIPOINTZ_NO_M_PATH: &str = "./tests/data/multipointz_no_m.shp";
 
 pub const MULTIPATCH_PATH: &str = "./tests/data/multipatch.shp";
 
+/// A .shp of 0 bytes
+pub const EMPTY_PATH: &str = "./tests/data/degenerate/empty.shp";
+/// A .shp with only its header, without records, and a .dbf without records
+pub const HEADER_ONLY_PATH: &str = "./tests/data/degenerate/header_only.shp";
+/// A .shp with only its header, which gives a file length of 128 bytes
+pub const STALE_LENGTH_PATH: &str = "./tests/data/degenerate/stale_length.shp";
+
 pub fn check_line_first_shape(shape: &shapefile::Shape) {
     if let shapefile::Shape::Polyline(shp) = shape {
         assert_eq!(shp.bbox.xmin, 1.0);
@@ -39,6 +51,6 @@ pub fn check_line_first_shape(shape: &shapefile::Shape) {
         ];
         assert_eq!(shp.points(), expected_point.as_slice());
     } else {
-        assert!(false, "The shape is not a Polyline");
+        panic!("The shape is not a Polyline");
     }
 }
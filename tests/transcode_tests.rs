@@ -0,0 +1,219 @@
+extern crate shapefile;
+
+use shapefile::record::transcode::{transcode_multipoint, transcode_polygon, transcode_polyline};
+use shapefile::{Multipoint, MultipointM, MultipointZ, Point, PointM, PointZ};
+use shapefile::{Polygon, PolygonM, PolygonZ, Polyline, PolylineM, PolylineZ};
+use shapefile::{NullShape, Shape, ShapeType, NO_DATA};
+
+const ALL_TYPES: [ShapeType; 14] = [
+    ShapeType::NullShape,
+    ShapeType::Point,
+    ShapeType::PointM,
+    ShapeType::PointZ,
+    ShapeType::Polyline,
+    ShapeType::PolylineM,
+    ShapeType::PolylineZ,
+    ShapeType::Polygon,
+    ShapeType::PolygonM,
+    ShapeType::PolygonZ,
+    ShapeType::Multipoint,
+    ShapeType::MultipointM,
+    ShapeType::MultipointZ,
+    ShapeType::Multipatch,
+];
+
+fn points_z() -> Vec<PointZ> {
+    vec![
+        PointZ::new(0.0, 0.0, 1.0, 10.0),
+        PointZ::new(0.0, 2.0, 2.0, 20.0),
+        PointZ::new(2.0, 2.0, 3.0, 30.0),
+        PointZ::new(0.0, 0.0, 1.0, 10.0),
+        PointZ::new(5.0, 5.0, 4.0, 40.0),
+        PointZ::new(6.0, 5.0, 5.0, 50.0),
+        PointZ::new(5.0, 6.0, 6.0, 60.0),
+        PointZ::new(5.0, 5.0, 4.0, 40.0),
+    ]
+}
+
+/// One shape of every shape type but Multipatch and NullShape
+fn shapes_of_every_type() -> Vec<Shape> {
+    let parts = vec![0, 4];
+    let mut shapes = Vec::<Shape>::new();
+    let point_z = points_z()[2];
+    shapes.push(Shape::Point(point_z.into()));
+    shapes.push(Shape::PointM(point_z.into()));
+    shapes.push(Shape::PointZ(point_z));
+    let polyline_z = PolylineZ::new(points_z(), parts.clone());
+    shapes.push(Shape::Polyline(transcode_polyline(polyline_z.clone())));
+    shapes.push(Shape::PolylineM(transcode_polyline(polyline_z.clone())));
+    shapes.push(Shape::PolylineZ(polyline_z));
+    let polygon_z = PolygonZ::new(points_z(), parts);
+    shapes.push(Shape::Polygon(transcode_polygon(polygon_z.clone())));
+    shapes.push(Shape::PolygonM(transcode_polygon(polygon_z.clone())));
+    shapes.push(Shape::PolygonZ(polygon_z));
+    let multipoint_z = MultipointZ::new(points_z());
+    shapes.push(Shape::Multipoint(transcode_multipoint(multipoint_z.clone())));
+    shapes.push(Shape::MultipointM(transcode_multipoint(multipoint_z.clone())));
+    shapes.push(Shape::MultipointZ(multipoint_z));
+    shapes
+}
+
+/// The points of the shape as PointZ, with its parts
+fn structure_of(shape: &Shape) -> (Vec<PointZ>, Vec<i32>) {
+    match shape {
+        Shape::Point(shp) => (vec![(*shp).into()], vec![]),
+        Shape::PointM(shp) => (vec![(*shp).into()], vec![]),
+        Shape::PointZ(shp) => (vec![*shp], vec![]),
+        Shape::Polyline(shp) => (transcode_polyline(shp.clone()).points, shp.parts.clone()),
+        Shape::PolylineM(shp) => (transcode_polyline(shp.clone()).points, shp.parts.clone()),
+        Shape::PolylineZ(shp) => (shp.points.clone(), shp.parts.clone()),
+        Shape::Polygon(shp) => (transcode_polygon(shp.clone()).points, shp.parts.clone()),
+        Shape::PolygonM(shp) => (transcode_polygon(shp.clone()).points, shp.parts.clone()),
+        Shape::PolygonZ(shp) => (shp.points.clone(), shp.parts.clone()),
+        Shape::Multipoint(shp) => (transcode_multipoint(shp.clone()).points, vec![]),
+        Shape::MultipointM(shp) => (transcode_multipoint(shp.clone()).points, vec![]),
+        Shape::MultipointZ(shp) => (shp.points.clone(), vec![]),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn point_promotion_fills_z_and_m() {
+    let point_m: PointM = Point::new(1.0, 2.0).into();
+    assert_eq!(point_m, PointM::new(1.0, 2.0, NO_DATA));
+    let point_z: PointZ = Point::new(1.0, 2.0).into();
+    assert_eq!(point_z, PointZ::new(1.0, 2.0, 0.0, NO_DATA));
+    let point_z: PointZ = PointM::new(1.0, 2.0, 3.0).into();
+    assert_eq!(point_z, PointZ::new(1.0, 2.0, 0.0, 3.0));
+}
+
+#[test]
+fn point_demotion_drops_z_and_m() {
+    let point: Point = PointM::new(1.0, 2.0, 3.0).into();
+    assert_eq!(point, Point::new(1.0, 2.0));
+    let point: Point = PointZ::new(1.0, 2.0, 3.0, 4.0).into();
+    assert_eq!(point, Point::new(1.0, 2.0));
+    let point_m: PointM = PointZ::new(1.0, 2.0, 3.0, 4.0).into();
+    assert_eq!(point_m, PointM::new(1.0, 2.0, 4.0));
+}
+
+#[test]
+fn transcode_every_pair_of_point_types() {
+    let polyline = Polyline::new(vec![Point::new(1.0, 1.0), Point::new(2.0, 3.0)], vec![0]);
+    let polyline_m: PolylineM = transcode_polyline(polyline.clone());
+    let polyline_z: PolylineZ = transcode_polyline(polyline_m.clone());
+    let _: Polyline = transcode_polyline(polyline_z.clone());
+    let _: Polyline = transcode_polyline(polyline_m);
+    let _: PolylineM = transcode_polyline(polyline_z.clone());
+    let _: PolylineZ = transcode_polyline(polyline.clone());
+    let _: Polyline = transcode_polyline(polyline);
+    assert_eq!(polyline_z.bbox.xmax, 2.0);
+    assert_eq!(polyline_z.points[1], PointZ::new(2.0, 3.0, 0.0, NO_DATA));
+
+    let polygon = Polygon::new(points_z().into_iter().map(Point::from).collect(), vec![0, 4]);
+    let polygon_m: PolygonM = transcode_polygon(polygon.clone());
+    let polygon_z: PolygonZ = transcode_polygon(polygon_m.clone());
+    let _: Polygon = transcode_polygon(polygon_z.clone());
+    let _: Polygon = transcode_polygon(polygon_m);
+    let _: PolygonM = transcode_polygon(polygon_z.clone());
+    let _: PolygonZ = transcode_polygon(polygon);
+    assert_eq!(polygon_z.parts, vec![0, 4]);
+
+    let multipoint = Multipoint::new(vec![Point::new(1.0, 1.0)]);
+    let multipoint_m: MultipointM = transcode_multipoint(multipoint.clone());
+    let multipoint_z: MultipointZ = transcode_multipoint(multipoint_m.clone());
+    let _: Multipoint = transcode_multipoint(multipoint_z.clone());
+    let _: Multipoint = transcode_multipoint(multipoint_m);
+    let _: MultipointM = transcode_multipoint(multipoint_z.clone());
+    let _: MultipointZ = transcode_multipoint(multipoint);
+    assert_eq!(multipoint_z.points[0].m, NO_DATA);
+}
+
+#[test]
+fn m_present_is_only_kept_between_point_types_with_measures() {
+    let mut polyline_z = PolylineZ::new(points_z(), vec![0]);
+    polyline_z.set_m_present(false);
+    let polyline_m: PolylineM = transcode_polyline(polyline_z.clone());
+    assert!(!polyline_m.m_present());
+    let polyline: Polyline = transcode_polyline(polyline_z);
+    let polyline_z: PolylineZ = transcode_polyline(polyline);
+    assert!(polyline_z.m_present());
+
+    let mut polygon_m: PolygonM = transcode_polygon(Polygon::new(vec![], vec![]));
+    polygon_m.set_m_present(false);
+    let polygon_z: PolygonZ = transcode_polygon(polygon_m.clone());
+    assert!(!polygon_z.m_present());
+    let polygon_m: PolygonM = transcode_polygon(transcode_polygon::<_, Point>(polygon_m));
+    assert!(polygon_m.m_present());
+
+    let mut multipoint_m = MultipointM::new(vec![PointM::new(1.0, 1.0, NO_DATA)]);
+    multipoint_m.set_m_present(false);
+    let multipoint: Multipoint = transcode_multipoint(multipoint_m);
+    let multipoint_z: MultipointZ = transcode_multipoint(multipoint);
+    assert!(multipoint_z.m_present());
+}
+
+#[test]
+fn convert_to_keeps_the_structure() {
+    for shape in shapes_of_every_type() {
+        let (points, parts) = structure_of(&shape);
+        for &shape_type in ALL_TYPES.iter() {
+            let converted = match shape.clone().convert_to(shape_type) {
+                Ok(converted) => converted,
+                Err(shapefile::Error::MismatchShapeType { requested, actual }) => {
+                    assert_eq!((requested, actual), (shape_type, shape.shapetype()));
+                    assert_ne!(shape_type.geometry_class(), shape.group());
+                    continue;
+                }
+                Err(e) => panic!("Unexpected error {}", e),
+            };
+            assert_eq!(converted.shapetype(), shape_type);
+
+            let (converted_points, converted_parts) = structure_of(&converted);
+            assert_eq!(converted_parts, parts);
+            assert_eq!(converted_points.len(), points.len());
+            for (converted, original) in converted_points.iter().zip(&points) {
+                assert_eq!((converted.x, converted.y), (original.x, original.y));
+                let z = if shape_type.has_z() && shape.shapetype().has_z() {
+                    original.z
+                } else {
+                    0.0
+                };
+                let m = if shape_type.has_m() { original.m } else { NO_DATA };
+                assert_eq!((converted.z, converted.m), (z, m));
+            }
+
+            // Converting back gives the original structure,
+            // and the original values after a promotion
+            let original_type = shape.shapetype();
+            let back = converted.convert_to(original_type).unwrap();
+            let (back_points, back_parts) = structure_of(&back);
+            assert_eq!(back_parts, parts);
+            let is_promotion = (shape_type.has_z() || !original_type.has_z())
+                && (shape_type.has_m() || !original_type.has_m());
+            for (back, original) in back_points.iter().zip(&points) {
+                assert_eq!((back.x, back.y), (original.x, original.y));
+                if is_promotion {
+                    assert_eq!(back, original);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn convert_to_keeps_multipatch_and_null_shapes() {
+    assert_eq!(
+        Shape::NullShape(NullShape).convert_to(ShapeType::NullShape).unwrap().shapetype(),
+        ShapeType::NullShape
+    );
+    assert!(Shape::NullShape(NullShape).convert_to(ShapeType::Point).is_err());
+    let multipatch = shapefile::Multipatch::from_patches(vec![(
+        shapefile::PatchType::TriangleStrip,
+        points_z()[..3].to_vec(),
+    )])
+    .unwrap();
+    let shape = Shape::Multipatch(multipatch);
+    assert!(shape.clone().convert_to(ShapeType::PolygonZ).is_err());
+    assert_eq!(shape.convert_to(ShapeType::Multipatch).unwrap().shapetype(), ShapeType::Multipatch);
+}
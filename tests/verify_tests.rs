@@ -0,0 +1,114 @@
+extern crate shapefile;
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+use shapefile::verify::Severity;
+use shapefile::{Point, Polygon};
+
+mod testfiles;
+
+#[test]
+fn verify_valid_files() {
+    let report = shapefile::verify(testfiles::LINE_PATH).unwrap();
+    assert!(report.is_valid(), "{}", report);
+    assert_eq!(report.num_records, 1);
+    // line.shp has a .shx but no .dbf
+    assert_eq!(report.warnings().count(), 1);
+
+    let report = shapefile::verify(testfiles::MULTIPATCH_PATH).unwrap();
+    assert!(report.is_valid(), "{}", report);
+    assert_eq!(report.num_records, 1);
+}
+
+#[test]
+fn verify_reports_all_issues() {
+    let dir = std::env::temp_dir().join("shapefile_verify_reports_all_issues");
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("broken.shp");
+
+    let unclosed = Polygon::new(
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 0.0),
+        ],
+        vec![0],
+    );
+    let with_nan = Polygon::new(
+        vec![
+            Point::new(0.0, 0.0),
+            Point::new(0.0, f64::NAN),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 0.0),
+            Point::new(0.0, 0.0),
+        ],
+        vec![0],
+    );
+    {
+        let mut writer = shapefile::Writer::new(File::create(&shp_path).unwrap());
+        writer.add_index_dest(File::create(dir.join("broken.shx")).unwrap());
+        writer.write_shapes(vec![unclosed, with_nan]).unwrap();
+    }
+    // garbage after the last record
+    OpenOptions::new()
+        .append(true)
+        .open(&shp_path)
+        .unwrap()
+        .write_all(&[1, 2, 3])
+        .unwrap();
+
+    let report = shapefile::verify(&shp_path).unwrap();
+    assert!(!report.is_valid());
+    assert_eq!(report.num_records, 2);
+
+    let errors = report.errors().collect::<Vec<_>>();
+    // file length, unclosed ring, NaN (the garbage is after the end given by the header)
+    assert_eq!(
+        errors.iter().map(|issue| issue.record).collect::<Vec<_>>(),
+        vec![None, Some(0), Some(1)]
+    );
+    assert!(errors[1].message.contains("not closed"));
+    assert!(errors[2].message.contains("NaN"));
+
+    // the NaN makes the orientation of the ring undefined, and there is no .dbf
+    let warnings = report.warnings().collect::<Vec<_>>();
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings
+        .iter()
+        .all(|issue| issue.severity == Severity::Warning));
+}
+
+#[test]
+fn verify_reports_corrupt_file_lengths() {
+    let dir = std::env::temp_dir().join("shapefile_verify_reports_corrupt_file_lengths");
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("points.shp");
+    {
+        let mut writer = shapefile::Writer::new(File::create(&shp_path).unwrap());
+        let points = vec![Point::new(1.0, 2.0), Point::new(3.0, 4.0)];
+        writer.write_shapes(points).unwrap();
+    }
+    let shp = std::fs::read(&shp_path).unwrap();
+
+    // A negative file length in the header
+    let mut negative = shp.clone();
+    negative[24..28].copy_from_slice(&(-2i32).to_be_bytes());
+    std::fs::write(&shp_path, &negative).unwrap();
+    let report = shapefile::verify(&shp_path).unwrap();
+    assert_eq!(report.num_records, 0);
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].message.contains("-4 bytes"));
+
+    // A file ending within its last record
+    std::fs::write(&shp_path, &shp[..shp.len() - 4]).unwrap();
+    let report = shapefile::verify(&shp_path).unwrap();
+    assert_eq!(report.num_records, 1);
+    let errors = report.errors().collect::<Vec<_>>();
+    assert_eq!(
+        errors.iter().map(|issue| issue.record).collect::<Vec<_>>(),
+        vec![None, Some(1)]
+    );
+}
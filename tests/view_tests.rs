@@ -0,0 +1,93 @@
+extern crate shapefile;
+
+mod testfiles;
+
+use shapefile::record::view::{GenericPointView, PartView};
+use shapefile::record::MultipartShape;
+use shapefile::{NullShape, Point, PointM, PointZ, Shape};
+
+/// Returns the parts of the shape, as PointZ, read from the concrete shape
+fn concrete_parts(shape: &Shape) -> Vec<Vec<PointZ>> {
+    fn to_z<P: Copy>(parts: Vec<&[P]>) -> Vec<Vec<PointZ>>
+    where
+        PointZ: From<P>,
+    {
+        parts
+            .iter()
+            .map(|part| part.iter().map(|p| PointZ::from(*p)).collect())
+            .collect()
+    }
+    match shape {
+        Shape::Polyline(shp) => to_z(shp.parts().collect()),
+        Shape::PolylineM(shp) => to_z(shp.parts().collect()),
+        Shape::PolylineZ(shp) => to_z(shp.parts().collect()),
+        Shape::Polygon(shp) => to_z(shp.parts().collect()),
+        Shape::PolygonM(shp) => to_z(shp.parts().collect()),
+        Shape::PolygonZ(shp) => to_z(shp.parts().collect()),
+        Shape::Multipatch(shp) => to_z(shp.parts().collect()),
+        _ => panic!("Not a multipart shape"),
+    }
+}
+
+#[test]
+fn parts_of_multipart_shapes() {
+    let paths = [
+        testfiles::LINE_PATH,
+        testfiles::LINEM_PATH,
+        testfiles::LINEZ_PATH,
+        testfiles::POLYGON_PATH,
+        testfiles::POLYGONM_PATH,
+        testfiles::POLYGONZ_PATH,
+        testfiles::MULTIPATCH_PATH,
+    ];
+    for path in &paths {
+        for shape in shapefile::read(path).unwrap() {
+            let parts = shape
+                .parts()
+                .unwrap()
+                .map(|part| part.iter().map(PointZ::from).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+            assert_eq!(parts, concrete_parts(&shape), "{}", path);
+        }
+    }
+}
+
+#[test]
+fn parts_of_shapes_without_parts() {
+    let paths = [
+        testfiles::POINT_PATH,
+        testfiles::POINTZ_PATH,
+        testfiles::MULTIPOINT_PATH,
+    ];
+    for path in &paths {
+        for shape in shapefile::read(path).unwrap() {
+            assert!(shape.parts().is_none());
+        }
+    }
+    assert!(Shape::NullShape(NullShape).parts().is_none());
+}
+
+#[test]
+fn generic_point_view_dimensions() {
+    let shapes = shapefile::read(testfiles::LINEZ_PATH).unwrap();
+    let polyline = shapes[0].as_polyline_z().unwrap();
+    let part = shapes[0].parts().unwrap().next().unwrap();
+    assert_eq!(part, PartView::PointZ(polyline.part(0).unwrap()));
+    let first = part.get(0).unwrap();
+    let expected = polyline.points[0];
+    assert_eq!(
+        (first.x(), first.y(), first.z(), first.m()),
+        (expected.x, expected.y, Some(expected.z), Some(expected.m))
+    );
+
+    let point = Point::new(1.0, 2.0);
+    let view = GenericPointView::Point(&point);
+    assert_eq!((view.z(), view.m()), (None, None));
+    assert_eq!(Point::from(view), point);
+    assert_eq!(PointM::from(view), PointM::from(point));
+
+    let point_m = PointM::new(1.0, 2.0, 3.0);
+    let view = GenericPointView::PointM(&point_m);
+    assert_eq!((view.z(), view.m()), (None, Some(3.0)));
+    assert_eq!(PointZ::from(view), PointZ::from(point_m));
+}
@@ -12,3 +12,932 @@ fn write_polyline() {
     let mut writer = shapefile::writer::Writer::new(cursor);
     writer.write_shapes(polys).unwrap();
 }*/
+
+extern crate byteorder;
+extern crate dbase;
+
+use byteorder::{BigEndian, LittleEndian, WriteBytesExt};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use shapefile::record::EsriShape;
+use shapefile::{PointM, PolylineM, NO_DATA};
+
+fn write_to_memory<S: EsriShape>(shapes: Vec<S>) -> Vec<u8> {
+    let mut shp = Vec::<u8>::new();
+    shapefile::Writer::new(Cursor::new(&mut shp))
+        .write_shapes(shapes)
+        .unwrap();
+    shp
+}
+
+fn write_header(dest: &mut Vec<u8>, file_length: i32, shape_type: i32, ranges: &[f64; 8]) {
+    dest.write_i32::<BigEndian>(9994).unwrap();
+    for _ in 0..5 {
+        dest.write_i32::<BigEndian>(0).unwrap();
+    }
+    dest.write_i32::<BigEndian>(file_length).unwrap();
+    dest.write_i32::<LittleEndian>(1000).unwrap();
+    dest.write_i32::<LittleEndian>(shape_type).unwrap();
+    for value in ranges {
+        dest.write_f64::<LittleEndian>(*value).unwrap();
+    }
+}
+
+#[test]
+fn write_polyline_m_without_measures() {
+    let polyline = PolylineM::new(
+        vec![
+            PointM::new(1.0, 2.0, NO_DATA),
+            PointM::new(3.0, 4.0, NO_DATA),
+        ],
+        vec![0],
+    );
+
+    let mut expected = Vec::<u8>::new();
+    // bbox, z range and m range
+    write_header(&mut expected, 110, 23, &[1.0, 2.0, 3.0, 4.0, 0.0, 0.0, 0.0, 0.0]);
+    expected.write_i32::<BigEndian>(1).unwrap();
+    expected.write_i32::<BigEndian>(56).unwrap();
+    expected.write_i32::<LittleEndian>(23).unwrap();
+    for value in &[1.0, 2.0, 3.0, 4.0] {
+        expected.write_f64::<LittleEndian>(*value).unwrap();
+    }
+    expected.write_i32::<LittleEndian>(1).unwrap();
+    expected.write_i32::<LittleEndian>(2).unwrap();
+    expected.write_i32::<LittleEndian>(0).unwrap();
+    // points, m range, measures
+    for value in &[1.0, 2.0, 3.0, 4.0, 0.0, 0.0, NO_DATA, NO_DATA] {
+        expected.write_f64::<LittleEndian>(*value).unwrap();
+    }
+
+    assert_eq!(write_to_memory(vec![polyline]), expected);
+}
+
+#[test]
+fn m_range_ignores_no_data() {
+    let polylines = vec![
+        PolylineM::new(
+            vec![PointM::new(0.0, 0.0, NO_DATA), PointM::new(1.0, 1.0, 5.0)],
+            vec![0],
+        ),
+        PolylineM::new(
+            vec![PointM::new(0.0, 0.0, 7.0), PointM::new(1.0, 1.0, 6.0)],
+            vec![0],
+        ),
+    ];
+    assert_eq!(polylines[0].m_range(), [5.0, 5.0]);
+
+    let shp = write_to_memory(polylines);
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().m_range, [5.0, 7.0]);
+}
+
+#[test]
+fn m_range_of_point_measures() {
+    let measures = [3.0, 2.0, NO_DATA, 8.5];
+    let points = measures
+        .iter()
+        .enumerate()
+        .map(|(i, m)| PointM::new(i as f64, i as f64, *m))
+        .collect::<Vec<_>>();
+    let shp = write_to_memory(points.clone());
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().m_range, [2.0, 8.5]);
+    assert_eq!(reader.read_as::<PointM>().unwrap(), points);
+
+    // The same points written through the Shape enum
+    let shapes = points
+        .iter()
+        .map(|point| shapefile::Shape::PointM(*point))
+        .collect::<Vec<_>>();
+    let shp = write_to_memory(shapes);
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().m_range, [2.0, 8.5]);
+    let shapes = reader.read().unwrap();
+    for (shape, point) in shapes.iter().zip(&points) {
+        assert_eq!(shape.as_point_m(), Some(point));
+    }
+
+    let points_z = measures
+        .iter()
+        .enumerate()
+        .map(|(i, m)| shapefile::PointZ::new(i as f64, i as f64, -(i as f64), *m))
+        .map(shapefile::Shape::PointZ)
+        .collect::<Vec<_>>();
+    let shp = write_to_memory(points_z);
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().m_range, [2.0, 8.5]);
+    assert_eq!(reader.header().z_range(), [-3.0, 0.0]);
+}
+
+#[test]
+fn m_range_of_points_without_measures() {
+    let points = vec![PointM::new(0.0, 0.0, NO_DATA), PointM::new(1.0, 1.0, NO_DATA)];
+    let shp = write_to_memory(points);
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().m_range, [0.0, 0.0]);
+}
+
+fn diagonal_polylines(n: usize) -> impl Iterator<Item = shapefile::Polyline> + Clone {
+    (0..n).map(|i| {
+        let i = i as f64;
+        let points = (0..(i as usize % 3) + 2)
+            .map(|j| shapefile::Point::new(i + j as f64, i))
+            .collect();
+        shapefile::Polyline::new(points, vec![0])
+    })
+}
+
+#[test]
+fn streamed_is_same_as_write_shapes() {
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(Cursor::new(&mut shp));
+        writer.add_index_dest(Cursor::new(&mut shx));
+        writer
+            .write_shapes(diagonal_polylines(10).collect::<Vec<_>>())
+            .unwrap();
+    }
+
+    // Plain Vec<u8> do not implement Seek
+    let mut streamed_shp = Vec::<u8>::new();
+    let mut streamed_shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(&mut streamed_shp);
+        writer.add_index_dest(&mut streamed_shx);
+        writer
+            .write_shapes_streamed(diagonal_polylines(10))
+            .unwrap();
+    }
+    assert_eq!(streamed_shp, shp);
+    assert_eq!(streamed_shx, shx);
+}
+
+#[test]
+fn streamed_shapes_must_not_change() {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    // Gives one more shape each time it is iterated
+    let num_passes = Rc::new(Cell::new(0usize));
+    let counter = num_passes.clone();
+    let shapes = (0..1).flat_map(move |_| {
+        counter.set(counter.get() + 1);
+        diagonal_polylines(2 + counter.get())
+    });
+
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp);
+    match writer.write_shapes_streamed(shapes) {
+        Err(shapefile::Error::ShapesChangedBetweenPasses) => {}
+        _ => panic!("Expected ShapesChangedBetweenPasses"),
+    }
+    assert_eq!(num_passes.get(), 2);
+}
+
+fn square_at(x: f64) -> shapefile::Polygon {
+    shapefile::Polygon::new(
+        vec![
+            shapefile::Point::new(x, 0.0),
+            shapefile::Point::new(x, 1.0),
+            shapefile::Point::new(x + 1.0, 1.0),
+            shapefile::Point::new(x + 1.0, 0.0),
+            shapefile::Point::new(x, 0.0),
+        ],
+        vec![0],
+    )
+}
+
+#[test]
+fn invalid_shapes_written_as_null() {
+    use shapefile::writer::OnInvalid;
+    use shapefile::{Point, Polygon, Shape};
+    use std::fs::File;
+
+    let dir = std::env::temp_dir().join("shapefile_invalid_shapes_written_as_null");
+    std::fs::create_dir_all(&dir).unwrap();
+    let shp_path = dir.join("polygons.shp");
+
+    let unclosed = Polygon::new(
+        vec![
+            Point::new(10.0, 10.0),
+            Point::new(10.0, 11.0),
+            Point::new(11.0, 11.0),
+            Point::new(11.0, 10.0),
+        ],
+        vec![0],
+    );
+    let polygons = vec![square_at(0.0), unclosed, square_at(2.0)];
+    {
+        let mut writer = shapefile::Writer::new(File::create(&shp_path).unwrap())
+            .with_validation(OnInvalid::WriteNull);
+        writer.add_index_dest(File::create(dir.join("polygons.shx")).unwrap());
+        assert_eq!(writer.write_shapes(polygons).unwrap(), vec![1, 2, 3]);
+        assert_eq!(writer.null_substitutions(), &[1]);
+    }
+    let records = ["a", "b", "c"]
+        .iter()
+        .map(|name| {
+            let mut record = dbase::Record::new();
+            record.insert("NAME".to_string(), dbase::FieldValue::from(*name));
+            record
+        })
+        .collect::<Vec<dbase::Record>>();
+    dbase::Writer::new(File::create(dir.join("polygons.dbf")).unwrap())
+        .write(&records)
+        .unwrap();
+
+    let mut reader = shapefile::Reader::from_path(&shp_path).unwrap();
+    // The unclosed polygon is not part of the extent
+    assert_eq!(reader.header().point_max[0], 3.0);
+    match reader.read_nth_shape(1) {
+        Some(Ok(Shape::NullShape(_))) => {}
+        _ => panic!("Expected a NullShape"),
+    }
+    match reader.read_nth_shape(2) {
+        Some(Ok(Shape::Polygon(polygon))) => assert_eq!(polygon.points[0], Point::new(2.0, 0.0)),
+        _ => panic!("Expected a Polygon"),
+    }
+
+    let features = reader
+        .iter_shapes_and_records()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(features.len(), 3);
+    for ((shape, record), x) in features.iter().zip(&[Some(0.0), None, Some(2.0)]) {
+        match (shape, x) {
+            (Shape::Polygon(polygon), Some(x)) => assert_eq!(polygon.points[0].x, *x),
+            (Shape::NullShape(_), None) => {
+                assert_eq!(record.get("NAME"), Some(&dbase::FieldValue::from("b")))
+            }
+            _ => panic!("Unexpected shape {}", shape),
+        }
+    }
+}
+
+#[test]
+fn invalid_shapes_are_errors() {
+    use shapefile::writer::OnInvalid;
+
+    // A closed ring of 3 points
+    let too_short = || {
+        let mut polygon = square_at(0.0);
+        polygon.points.drain(1..3);
+        polygon
+    };
+
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp).with_validation(OnInvalid::Error);
+    match writer.write_shapes(vec![square_at(0.0), too_short()]) {
+        Err(shapefile::Error::TooFewPointsInRing(0)) => {}
+        _ => panic!("Expected TooFewPointsInRing"),
+    }
+
+    // The same shapes are written without validation
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp);
+    writer.write_shapes(vec![square_at(0.0), too_short()]).unwrap();
+    assert!(writer.null_substitutions().is_empty());
+}
+
+#[test]
+fn snap_precision_rounds_coordinates() {
+    use byteorder::ByteOrder;
+
+    let polyline = PolylineM::new(
+        vec![
+            PointM::new(0.12345, 1.00049, NO_DATA),
+            PointM::new(2.0006, 3.98765, 1.23456),
+        ],
+        vec![0],
+    );
+    let mut shp = Vec::<u8>::new();
+    shapefile::Writer::new(Cursor::new(&mut shp))
+        .snap_precision(3)
+        .write_shapes(vec![polyline])
+        .unwrap();
+
+    let read_f64s = |offset: usize, n: usize| {
+        (0..n)
+            .map(|i| LittleEndian::read_f64(&shp[offset + i * 8..]))
+            .collect::<Vec<f64>>()
+    };
+    let bbox = vec![0.123, 1.0, 2.001, 3.988];
+    // header bbox
+    assert_eq!(read_f64s(36, 4), bbox);
+    // record header, shape type
+    let content = 100 + 8 + 4;
+    assert_eq!(read_f64s(content, 4), bbox);
+    // bbox, number of parts and points, one part
+    assert_eq!(read_f64s(content + 44, 4), vec![0.123, 1.0, 2.001, 3.988]);
+    // m range and measures, NO_DATA is left untouched
+    assert_eq!(read_f64s(content + 76, 4), vec![1.235, 1.235, NO_DATA, 1.235]);
+}
+
+#[test]
+fn snapped_ring_with_too_few_points() {
+    use shapefile::writer::OnInvalid;
+    use shapefile::{Point, Polygon};
+
+    // The last two points of the ring become equal once snapped
+    let collapsing = || {
+        Polygon::new(
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 1.0),
+                Point::new(0.0001, 1.0001),
+                Point::new(0.0, 0.0),
+            ],
+            vec![0],
+        )
+    };
+
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp)
+        .snap_precision_removing_repeated_points(3)
+        .with_validation(OnInvalid::WriteNull);
+    writer
+        .write_shapes(vec![square_at(0.0), collapsing()])
+        .unwrap();
+    assert_eq!(writer.null_substitutions(), &[1]);
+
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp)
+        .snap_precision_removing_repeated_points(3)
+        .with_validation(OnInvalid::Error);
+    match writer.write_shapes(vec![square_at(0.0), collapsing()]) {
+        Err(shapefile::Error::TooFewPointsInRing(0)) => {}
+        _ => panic!("Expected TooFewPointsInRing"),
+    }
+
+    // The repeated point is kept
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(&mut shp)
+        .snap_precision(3)
+        .with_validation(OnInvalid::Error);
+    writer.write_shapes(vec![collapsing()]).unwrap();
+    let polygons = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .read_as::<Polygon>()
+        .unwrap();
+    assert_eq!(polygons[0].points[2], Point::new(0.0, 1.0));
+    assert_eq!(polygons[0].points.len(), 4);
+}
+
+#[test]
+fn ring_closing_snaps_or_appends_the_first_point() {
+    use shapefile::writer::OnInvalid;
+    use shapefile::{Point, Polygon, Shape};
+
+    let unclosed = |last: Point| {
+        Polygon::new(
+            vec![
+                Point::new(0.0, 0.0),
+                Point::new(0.0, 1.0),
+                Point::new(1.0, 1.0),
+                last,
+            ],
+            vec![0],
+        )
+    };
+    let shapes = vec![
+        Shape::Polygon(unclosed(Point::new(1e-9, 0.0))),
+        Shape::Polygon(unclosed(Point::new(1.0, 0.0))),
+    ];
+    let mut shp = Vec::<u8>::new();
+    let mut writer = shapefile::Writer::new(Cursor::new(&mut shp))
+        .with_ring_closing(1e-6)
+        .with_validation(OnInvalid::Error);
+    writer.write_shapes(shapes).unwrap();
+
+    let polygons = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .read_as::<Polygon>()
+        .unwrap();
+    // snapped
+    assert_eq!(polygons[0].points.len(), 4);
+    assert_eq!(polygons[0].points[3], Point::new(0.0, 0.0));
+    assert_eq!(polygons[0].bbox.xmin, 0.0);
+    // too far, appended
+    assert_eq!(polygons[1].points.len(), 5);
+    assert_eq!(polygons[1].points[4], Point::new(0.0, 0.0));
+}
+
+#[test]
+fn write_shapes_with_numbers() {
+    use byteorder::ByteOrder;
+    use shapefile::Point;
+
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(Cursor::new(&mut shp));
+        writer.add_index_dest(Cursor::new(&mut shx));
+        assert_eq!(writer.write_shape_with_number(5, Point::new(1.0, 1.0)).unwrap(), 5);
+        assert_eq!(writer.write_shape_with_number(9, Point::new(2.0, 2.0)).unwrap(), 9);
+        match writer.write_shape_with_number(9, Point::new(3.0, 3.0)) {
+            Err(shapefile::Error::RecordNumberNotIncreasing {
+                previous: 9,
+                number: 9,
+            }) => {}
+            _ => panic!("Expected RecordNumberNotIncreasing"),
+        }
+        writer.write_shape_with_number(42, Point::new(3.0, 3.0)).unwrap();
+        // auto numbering continues after the last number
+        assert_eq!(writer.write_shape(Point::new(4.0, 4.0)).unwrap(), 43);
+        writer.finish().unwrap();
+    }
+
+    // Each point record is 28 bytes long
+    assert_eq!(shp.len(), 100 + 4 * 28);
+    let record_numbers = (0..4)
+        .map(|i| BigEndian::read_i32(&shp[100 + i * 28..]))
+        .collect::<Vec<i32>>();
+    assert_eq!(record_numbers, vec![5, 9, 42, 43]);
+    assert_eq!(BigEndian::read_i32(&shp[24..]), (100 + 4 * 28) / 2);
+    assert_eq!(shx.len(), 100 + 4 * 8);
+    assert_eq!(BigEndian::read_i32(&shx[24..]), (100 + 4 * 8) / 2);
+
+    let mut reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    reader.add_index_source(Cursor::new(shx)).unwrap();
+    assert_eq!(reader.header().point_max, [4.0, 4.0, 0.0]);
+    let point = reader.read_nth_shape_as::<Point>(2).unwrap().unwrap();
+    assert_eq!(point, Point::new(3.0, 3.0));
+}
+
+#[test]
+fn multipatch_from_patches_round_trip() {
+    use shapefile::{Multipatch, PatchType, PointZ};
+
+    let point = |x, y, z| PointZ::new(x, y, z, NO_DATA);
+    let multipatch = Multipatch::from_patches(vec![
+        (
+            PatchType::TriangleStrip,
+            vec![point(0.0, 0.0, 0.0), point(0.0, 1.0, 1.0), point(1.0, 0.0, 2.0), point(1.0, 1.0, 3.0)],
+        ),
+        (
+            PatchType::OuterRing,
+            vec![point(10.0, 10.0, 5.0), point(10.0, 20.0, 5.0), point(20.0, 20.0, 5.0), point(20.0, 10.0, 5.0)],
+        ),
+        (
+            PatchType::InnerRing,
+            vec![point(12.0, 12.0, 5.0), point(14.0, 12.0, 5.0), point(14.0, 14.0, 5.0), point(12.0, 12.0, 5.0)],
+        ),
+    ])
+    .unwrap();
+    // The outer ring was closed, the inner ring already was
+    assert_eq!(multipatch.parts, vec![0, 4, 9]);
+    assert_eq!(multipatch.points.len(), 13);
+    assert_eq!(multipatch.points[8], point(10.0, 10.0, 5.0));
+    let points = multipatch.points.clone();
+
+    let shp = write_to_memory(vec![multipatch]);
+    let read = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .read_as::<Multipatch>()
+        .unwrap();
+    assert_eq!(read.len(), 1);
+    assert_eq!(read[0].parts, vec![0, 4, 9]);
+    assert_eq!(
+        read[0].parts_type,
+        vec![PatchType::TriangleStrip, PatchType::OuterRing, PatchType::InnerRing]
+    );
+    assert_eq!(read[0].points, points);
+    assert_eq!(read[0].z_range, [0.0, 5.0]);
+}
+
+#[test]
+fn multipatch_from_invalid_patches() {
+    use shapefile::{Multipatch, PatchType, PointZ};
+
+    let point = |x, y| PointZ::new(x, y, 0.0, NO_DATA);
+    match Multipatch::from_patches(vec![
+        (PatchType::TriangleFan, vec![point(0.0, 0.0), point(0.0, 1.0), point(1.0, 1.0)]),
+        (PatchType::TriangleStrip, vec![point(0.0, 0.0), point(0.0, 1.0)]),
+    ]) {
+        Err(shapefile::Error::TooFewPointsInTriangles(1)) => {}
+        _ => panic!("Expected TooFewPointsInTriangles(1)"),
+    }
+    match Multipatch::from_patches(vec![(
+        PatchType::Ring,
+        vec![point(0.0, 0.0), point(0.0, 1.0), point(0.0, 0.0)],
+    )]) {
+        Err(shapefile::Error::TooFewPointsInRing(0)) => {}
+        _ => panic!("Expected TooFewPointsInRing(0)"),
+    }
+
+    // The parts type must agree with the parts to be written
+    let mut multipatch = Multipatch::from_patches(vec![(
+        PatchType::TriangleFan,
+        vec![point(0.0, 0.0), point(0.0, 1.0), point(1.0, 1.0)],
+    )])
+    .unwrap();
+    multipatch.parts_type.push(PatchType::Ring);
+    let mut shp = Vec::<u8>::new();
+    match shapefile::Writer::new(Cursor::new(&mut shp)).write_shapes(vec![multipatch]) {
+        Err(shapefile::Error::MalformedShape) => {}
+        _ => panic!("Expected MalformedShape"),
+    }
+}
+
+/// Writes the points received from the channel in a consumer thread,
+/// returns the .shp written and the result of the write
+fn write_from_channel(
+    receiver: std::sync::mpsc::Receiver<shapefile::Point>,
+    limit: Option<(u64, Arc<AtomicBool>)>,
+) -> std::thread::JoinHandle<(Vec<u8>, Result<(), shapefile::Error>)> {
+    std::thread::spawn(move || {
+        let mut writer =
+            shapefile::Writer::new(Cursor::new(Vec::<u8>::new())).with_flush_every(1000);
+        if let Some((max_bytes, limit_approaching)) = limit {
+            writer = writer.with_size_limit(max_bytes, limit_approaching);
+        }
+        let result = writer.write_all_from(receiver);
+        (writer.dest.into_inner(), result)
+    })
+}
+
+#[test]
+fn write_all_from_channel() {
+    let (sender, receiver) = std::sync::mpsc::sync_channel(64);
+    let consumer = write_from_channel(receiver, None);
+    let producers = (0..4)
+        .map(|p| {
+            let sender = sender.clone();
+            std::thread::spawn(move || {
+                for i in 0..25_000 {
+                    sender.send(shapefile::Point::new(p as f64, i as f64)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    drop(sender);
+    for producer in producers {
+        producer.join().unwrap();
+    }
+    let (shp, result) = consumer.join().unwrap();
+    result.unwrap();
+
+    assert_eq!(shp.len(), 100 + 100_000 * 28);
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.header().point_max, [3.0, 24_999.0, 0.0]);
+    let points = reader.read_as::<shapefile::Point>().unwrap();
+    assert_eq!(points.len(), 100_000);
+    for p in 0..4 {
+        let ys = points.iter().filter(|point| point.x == p as f64).map(|point| point.y);
+        assert!(ys.eq((0..25_000).map(|i| i as f64)));
+    }
+}
+
+#[test]
+fn write_all_from_stops_at_size_limit() {
+    let limit_approaching = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = std::sync::mpsc::sync_channel(4);
+    let max_bytes = 100 + 1000 * 28 + 10;
+    let consumer = write_from_channel(receiver, Some((max_bytes, limit_approaching.clone())));
+
+    let mut num_sent = 0usize;
+    while !limit_approaching.load(Ordering::SeqCst) {
+        if sender.send(shapefile::Point::new(num_sent as f64, 0.0)).is_err() {
+            break;
+        }
+        num_sent += 1;
+    }
+    // The producer stopped long before the limit
+    assert!(num_sent < 1000);
+    drop(sender);
+    let (shp, result) = consumer.join().unwrap();
+    result.unwrap();
+    assert_eq!(shp.len(), 100 + num_sent * 28);
+
+    // Producers that do not stop get an error
+    let (sender, receiver) = std::sync::mpsc::sync_channel(4);
+    let never_read = Arc::new(AtomicBool::new(false));
+    let consumer = write_from_channel(receiver, Some((max_bytes, never_read)));
+    let producer = std::thread::spawn(move || {
+        (0..2000)
+            .take_while(|i| sender.send(shapefile::Point::new(*i as f64, 0.0)).is_ok())
+            .count()
+    });
+    let (shp, result) = consumer.join().unwrap();
+    assert!(producer.join().unwrap() < 2000);
+    match result {
+        Err(shapefile::Error::FileSizeLimitReached {
+            limit,
+            records_written: 1000,
+        }) => assert_eq!(limit, max_bytes),
+        _ => panic!("Expected FileSizeLimitReached"),
+    }
+    let reader = shapefile::Reader::new(Cursor::new(shp)).unwrap();
+    assert_eq!(reader.read_as::<shapefile::Point>().unwrap().len(), 1000);
+}
+
+#[test]
+fn write_shape_returns_record_numbers_of_null_substitutions() {
+    use shapefile::writer::OnInvalid;
+    use shapefile::{Point, Polygon};
+
+    let unclosed = Polygon::new(
+        vec![Point::new(0.0, 0.0), Point::new(0.0, 1.0), Point::new(1.0, 1.0)],
+        vec![0],
+    );
+    let mut shp = Vec::<u8>::new();
+    let mut writer =
+        shapefile::Writer::new(Cursor::new(&mut shp)).with_validation(OnInvalid::WriteNull);
+    // Pairs the ids of the features with the numbers of their records
+    let cross_reference = vec![
+        ("a", writer.write_shape(square_at(0.0)).unwrap()),
+        ("b", writer.write_shape_with_number(10, unclosed).unwrap()),
+        ("c", writer.write_shape(square_at(2.0)).unwrap()),
+    ];
+    writer.finish().unwrap();
+    assert_eq!(cross_reference, vec![("a", 1), ("b", 10), ("c", 11)]);
+    assert_eq!(writer.null_substitutions(), &[1]);
+}
+
+#[test]
+fn write_and_read_shapefile_in_memory() {
+    let points = (0..4)
+        .map(|i| shapefile::Point::new(i as f64, -i as f64))
+        .collect::<Vec<_>>();
+    let records = (0..4)
+        .map(|i| {
+            let mut record = dbase::Record::new();
+            record.insert("ID".to_string(), dbase::FieldValue::Numeric(Some(i as f64)));
+            record
+        })
+        .collect::<Vec<_>>();
+
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.add_index_dest(Cursor::new(Vec::<u8>::new()));
+    writer.add_dbase_dest(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points.clone()).unwrap();
+    writer.write_records(records).unwrap();
+    let parts = writer.into_parts().unwrap();
+
+    let mut reader = shapefile::Reader::from_parts_bytes(
+        parts.shp.into_inner(),
+        parts.shx.map(Cursor::into_inner),
+        parts.dbf.map(Cursor::into_inner),
+    )
+    .unwrap();
+    assert!(reader.has_index() && reader.has_attributes());
+    assert_eq!(reader.index_table().unwrap().len(), 4);
+    let features = reader
+        .iter_shapes_and_records_as::<shapefile::Point>()
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(features.len(), 4);
+    for (i, (point, record)) in features.iter().enumerate() {
+        assert_eq!(point, &points[i]);
+        assert_eq!(
+            record.get("ID"),
+            Some(&dbase::FieldValue::Numeric(Some(i as f64)))
+        );
+    }
+
+    // Without a .dbf, the records are not written
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(points).unwrap();
+    writer.write_records(Vec::new()).unwrap();
+    let parts = writer.into_parts().unwrap();
+    assert!(parts.shx.is_none() && parts.dbf.is_none());
+    let reader = shapefile::Reader::from_parts_bytes(parts.shp.into_inner(), None, None).unwrap();
+    assert_eq!(reader.read().unwrap().len(), 4);
+}
+
+#[test]
+fn into_parts_finishes_the_shapes_written_one_by_one() {
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.add_index_dest(Cursor::new(Vec::<u8>::new()));
+    writer.write_shape(shapefile::Point::new(1.0, 2.0)).unwrap();
+    writer.write_shape(shapefile::Point::new(3.0, 4.0)).unwrap();
+    let parts = writer.into_parts().unwrap();
+
+    let mut reader = shapefile::Reader::from_parts_bytes(
+        parts.shp.into_inner(),
+        parts.shx.map(Cursor::into_inner),
+        None,
+    )
+    .unwrap();
+    assert_eq!(reader.header().shape_type, shapefile::ShapeType::Point);
+    assert_eq!(reader.header().point_max[..2], [3.0, 4.0]);
+    assert_eq!(reader.index_table().unwrap().len(), 2);
+    assert_eq!(reader.read_as::<shapefile::Point>().unwrap().len(), 2);
+}
+
+#[test]
+fn records_can_only_be_written_once() {
+    let mut record = dbase::Record::new();
+    record.insert("ID".to_string(), dbase::FieldValue::Numeric(Some(1.0)));
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.add_dbase_dest(Cursor::new(Vec::<u8>::new()));
+    writer.write_shapes(vec![shapefile::Point::new(1.0, 2.0)]).unwrap();
+    writer.write_records(vec![record]).unwrap();
+    match writer.write_records(vec![dbase::Record::new()]) {
+        Err(shapefile::Error::RecordsAlreadyWritten) => {}
+        other => panic!("expected RecordsAlreadyWritten, got {:?}", other),
+    }
+
+    let parts = writer.into_parts().unwrap();
+    let dbf = parts.dbf.unwrap().into_inner();
+    let records = dbase::Reader::new(Cursor::new(dbf)).unwrap().read().unwrap();
+    assert_eq!(records.len(), 1);
+}
+
+#[test]
+fn multipatch_round_trip_with_and_without_measures() {
+    use shapefile::{Multipatch, PatchType, PointZ};
+
+    let points = vec![
+        PointZ::new(0.0, 0.0, 1.0, 10.0),
+        PointZ::new(0.0, 1.0, 2.0, NO_DATA),
+        PointZ::new(1.0, 0.0, 3.0, 30.0),
+        PointZ::new(5.0, 5.0, 4.0, 40.0),
+        PointZ::new(5.0, 6.0, 5.0, 50.0),
+        PointZ::new(6.0, 5.0, 6.0, 60.0),
+    ];
+    let parts_type = vec![PatchType::TriangleStrip, PatchType::TriangleFan];
+    let multipatch = Multipatch::new(points.clone(), vec![0, 3], parts_type.clone());
+
+    let shp = write_to_memory(vec![multipatch]);
+    let read = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read_as::<Multipatch>()
+        .unwrap();
+    assert_eq!(read[0].points, points);
+    assert_eq!(read[0].parts_type, parts_type);
+    assert_eq!(read[0].z_range, [1.0, 6.0]);
+    assert_eq!(read[0].m_range, [10.0, 60.0]);
+    assert!(read[0].m_present());
+    // The record read is written back the same
+    assert_eq!(write_to_memory(read), shp);
+
+    // Without measures, the M values and range are not written
+    let points = points
+        .iter()
+        .map(|p| PointZ::new(p.x, p.y, p.z, NO_DATA))
+        .collect::<Vec<_>>();
+    let mut multipatch = Multipatch::new(points.clone(), vec![0, 3], parts_type);
+    multipatch.set_m_present(false);
+    let shp_without_m = write_to_memory(vec![multipatch]);
+    assert_eq!(shp_without_m.len(), shp.len() - 16 - 8 * points.len());
+    let read = shapefile::Reader::new(Cursor::new(shp_without_m.clone()))
+        .unwrap()
+        .read_as::<Multipatch>()
+        .unwrap();
+    assert_eq!(read[0].points, points);
+    assert!(!read[0].m_present());
+    assert_eq!(write_to_memory(read), shp_without_m);
+}
+
+#[test]
+fn null_shapes_round_trip() {
+    use shapefile::{NullShape, Point, Shape};
+
+    // A NullShape record is its header and its shape type, of 2 16-bit words
+    let shp = write_to_memory(vec![NullShape]);
+    assert_eq!(shp.len(), 100 + 8 + 4);
+    assert_eq!(&shp[104..108], &2i32.to_be_bytes());
+    assert_eq!(&shp[108..112], &0i32.to_le_bytes());
+
+    let shapes = vec![
+        Shape::Point(Point::new(1.0, 2.0)),
+        Shape::NullShape(NullShape),
+        Shape::NullShape(NullShape),
+        Shape::Point(Point::new(3.0, 4.0)),
+    ];
+    let shp = write_to_memory(shapes);
+    let read = shapefile::Reader::new(Cursor::new(shp.clone()))
+        .unwrap()
+        .read()
+        .unwrap();
+    assert_eq!(read.len(), 4);
+    for (i, shape) in read.iter().enumerate() {
+        match (i, shape) {
+            (1, Shape::NullShape(_)) | (2, Shape::NullShape(_)) => {}
+            (0, Shape::Point(_)) | (3, Shape::Point(_)) => {}
+            _ => panic!("Unexpected shape {} at {}", shape, i),
+        }
+    }
+    assert_eq!(write_to_memory(read), shp);
+
+    let nulls = shapefile::Reader::new(Cursor::new(write_to_memory(vec![NullShape; 3])))
+        .unwrap()
+        .read_as::<NullShape>()
+        .unwrap();
+    assert_eq!(nulls, vec![NullShape; 3]);
+}
+
+#[test]
+fn copy_raw_records() {
+    use shapefile::{Point, Polyline, ShapeType};
+
+    let polylines = (0..5)
+        .map(|i| {
+            let x = i as f64;
+            Polyline::new(vec![Point::new(x, -x), Point::new(x + 1.0, 2.0 * x)], vec![0])
+        })
+        .collect::<Vec<_>>();
+    let shp = write_to_memory(polylines.clone());
+
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    writer.add_index_dest(Cursor::new(Vec::<u8>::new()));
+    let raw_records = shapefile::Reader::new(Cursor::new(shp))
+        .unwrap()
+        .iter_raw_records();
+    for raw_record in raw_records {
+        let (number, shape_type, content) = raw_record.unwrap();
+        assert_eq!(shape_type, Some(ShapeType::Polyline));
+        assert_eq!(content.len(), 4 + 32 + 4 + 4 + 4 + 2 * 16);
+        if number % 2 == 1 {
+            assert_eq!(writer.write_raw_record(&content).unwrap(), (number + 1) / 2);
+        }
+    }
+    // A record of an unknown shape type is copied, but not part of the extent
+    let mut unknown = 99i32.to_le_bytes().to_vec();
+    unknown.extend_from_slice(&[1, 2, 3, 4, 5, 6]);
+    assert_eq!(writer.write_raw_record(&unknown).unwrap(), 4);
+    writer.finish().unwrap();
+    let parts = writer.into_parts().unwrap();
+
+    let mut reader = shapefile::Reader::from_parts_bytes(
+        parts.shp.into_inner(),
+        parts.shx.map(Cursor::into_inner),
+        None,
+    )
+    .unwrap();
+    let header = *reader.header();
+    assert_eq!(header.shape_type, ShapeType::Polyline);
+    assert_eq!(header.point_min[..2], [0.0, -4.0]);
+    assert_eq!(header.point_max[..2], [5.0, 8.0]);
+    for (i, expected) in polylines.iter().step_by(2).enumerate() {
+        let polyline = reader.read_nth_shape_as::<Polyline>(i).unwrap().unwrap();
+        assert_eq!(polyline.points, expected.points);
+    }
+    let raw_records = reader
+        .iter_raw_records()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(raw_records.len(), 4);
+    assert_eq!(raw_records[3], (4, None, unknown));
+}
+
+#[test]
+fn invalid_raw_records_are_errors() {
+    use shapefile::{Point, PointZ};
+
+    let mut writer = shapefile::Writer::new(Cursor::new(Vec::<u8>::new()));
+    for content in &[vec![1u8, 0], vec![0, 0, 0, 0, 0]] {
+        match writer.write_raw_record(content) {
+            Err(shapefile::Error::InvalidShapeRecordSize) => {}
+            _ => panic!("Expected an InvalidShapeRecordSize error"),
+        }
+    }
+    // A point record with one coordinate too many
+    let mut content = write_to_memory(vec![Point::new(1.0, 2.0)])[108..].to_vec();
+    content.extend_from_slice(&[0; 8]);
+    match writer.write_raw_record(&content) {
+        Err(shapefile::Error::InvalidShapeRecordSize) => {}
+        _ => panic!("Expected an InvalidShapeRecordSize error"),
+    }
+
+    let point = write_to_memory(vec![Point::new(1.0, 2.0)])[108..].to_vec();
+    let point_z = write_to_memory(vec![PointZ::new(1.0, 2.0, 3.0, 4.0)])[108..].to_vec();
+    assert_eq!(writer.write_raw_record(&point).unwrap(), 1);
+    match writer.write_raw_record(&point_z) {
+        Err(shapefile::Error::MismatchShapeType { .. }) => {}
+        _ => panic!("Expected a MismatchShapeType error"),
+    }
+}
+
+#[test]
+fn written_index_entries_point_to_the_records() {
+    let points = vec![
+        shapefile::Point::new(1.0, 2.0),
+        shapefile::Point::new(3.0, 4.0),
+        shapefile::Point::new(5.0, 6.0),
+    ];
+    let mut shp = Vec::<u8>::new();
+    let mut shx = Vec::<u8>::new();
+    {
+        let mut writer = shapefile::Writer::new(std::io::Cursor::new(&mut shp));
+        writer.add_index_dest(std::io::Cursor::new(&mut shx));
+        writer.write_shapes(points).unwrap();
+    }
+    // The offsets are the ones of the record headers, both values are in 16-bit words
+    let be_i32 = |bytes: &[u8]| i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    assert_eq!(shx.len(), 100 + 3 * 8);
+    for (i, entry) in shx[100..].chunks(8).enumerate() {
+        let offset = be_i32(&entry[..4]) as usize * 2;
+        let content_length = be_i32(&entry[4..]);
+        assert_eq!(offset, 100 + i * 28);
+        assert_eq!(content_length, 10);
+        assert_eq!(be_i32(&shp[offset + 4..offset + 8]), content_length);
+    }
+}
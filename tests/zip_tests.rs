@@ -0,0 +1,179 @@
+#[cfg(feature = "zip")]
+extern crate dbase;
+#[cfg(feature = "zip")]
+extern crate shapefile;
+#[cfg(feature = "zip")]
+extern crate zip;
+
+#[cfg(feature = "zip")]
+mod zip_archive {
+    use std::fs::OpenOptions;
+    use std::io::{Cursor, Read, Write};
+
+    use shapefile::archive::ZipOptions;
+    use shapefile::{Error, Point, Reader, Writer};
+    use zip::write::SimpleFileOptions;
+    use zip::{ZipArchive, ZipWriter};
+
+    const WGS84: &str = "GEOGCS[\"GCS_WGS_1984\",DATUM[\"D_WGS_1984\",\
+                         SPHEROID[\"WGS_1984\",6378137,298.257223563]],\
+                         PRIMEM[\"Greenwich\",0],UNIT[\"Degree\",0.017453292519943295]]";
+
+    fn points() -> Vec<Point> {
+        (0..5).map(|i| Point::new(i as f64, 2.0 * i as f64)).collect()
+    }
+
+    fn in_memory_writer() -> Writer<Cursor<Vec<u8>>> {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        writer.add_index_dest(Cursor::new(Vec::new()));
+        writer.add_dbase_dest(Cursor::new(Vec::new()));
+        writer
+    }
+
+    fn zip_of(files: &[(&str, &[u8])]) -> Cursor<Vec<u8>> {
+        let mut archive = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            archive
+                .start_file(*name, SimpleFileOptions::default())
+                .unwrap();
+            archive.write_all(content).unwrap();
+        }
+        archive.finish().unwrap()
+    }
+
+    #[test]
+    fn write_zipped_shapefile_and_read_it_back() {
+        let records = (0..5)
+            .map(|i| {
+                let mut record = dbase::Record::new();
+                record.insert(
+                    "NAME".to_string(),
+                    dbase::FieldValue::Character(Some(format!("point {}", i))),
+                );
+                record
+            })
+            .collect::<Vec<_>>();
+        let mut writer = in_memory_writer();
+        writer.write_shapes(points()).unwrap();
+        writer.write_records(records).unwrap();
+
+        let options = ZipOptions::new("points").with_prj(WGS84).with_cpg("UTF-8");
+        let dest = writer
+            .finish_into_zip(Cursor::new(Vec::new()), &options)
+            .unwrap();
+
+        let mut archive = ZipArchive::new(Cursor::new(dest.get_ref().clone())).unwrap();
+        let mut names = archive
+            .file_names()
+            .map(|name| name.unwrap().into_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+        assert_eq!(
+            names,
+            vec!["points.cpg", "points.dbf", "points.prj", "points.shp", "points.shx"]
+        );
+        let mut prj = String::new();
+        archive
+            .by_name("points.prj")
+            .unwrap()
+            .read_to_string(&mut prj)
+            .unwrap();
+        assert_eq!(prj, WGS84);
+
+        let reader = Reader::from_zip(dest).unwrap();
+        assert!(reader.has_index() && reader.has_attributes());
+        let features = reader
+            .iter_shapes_and_records_as::<Point>()
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(features.len(), 5);
+        for (i, (point, record)) in features.iter().enumerate() {
+            assert_eq!(point, &points()[i]);
+            assert_eq!(
+                record.get("NAME"),
+                Some(&dbase::FieldValue::Character(Some(format!("point {}", i))))
+            );
+        }
+    }
+
+    #[test]
+    fn write_zip_without_index_and_dbf() {
+        let mut writer = Writer::new(Cursor::new(Vec::new()));
+        for point in points() {
+            writer.write_shape(point).unwrap();
+        }
+        let dest = writer
+            .finish_into_zip(Cursor::new(Vec::new()), &ZipOptions::new("streamed"))
+            .unwrap();
+
+        let archive = ZipArchive::new(Cursor::new(dest.get_ref().clone())).unwrap();
+        assert_eq!(archive.len(), 1);
+        let reader = Reader::from_zip(dest).unwrap();
+        assert!(!reader.has_index() && !reader.has_attributes());
+        assert_eq!(reader.read_as::<Point>().unwrap(), points());
+    }
+
+    #[test]
+    fn write_zip_from_files_on_disk() {
+        let dir = std::env::temp_dir().join("shapefile_write_zip_from_files_on_disk");
+        std::fs::create_dir_all(&dir).unwrap();
+        let open = |name: &str| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(dir.join(name))
+                .unwrap()
+        };
+        let mut writer = Writer::new(open("points.shp"));
+        writer.add_index_dest(open("points.shx"));
+        writer.write_shapes(points()).unwrap();
+        let dest = writer
+            .finish_into_zip(Cursor::new(Vec::new()), &ZipOptions::new("points"))
+            .unwrap();
+
+        let reader = Reader::from_zip(dest).unwrap();
+        assert!(reader.has_index());
+        assert_eq!(reader.read_as::<Point>().unwrap(), points());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn read_zip_with_directory_and_other_case() {
+        let mut writer = in_memory_writer();
+        writer.write_shapes(points()).unwrap();
+        let parts = writer.into_parts().unwrap();
+        let shp = parts.shp.into_inner();
+        let shx = parts.shx.unwrap().into_inner();
+        let archive = zip_of(&[
+            ("__MACOSX/data/._ROADS.shp", b"metadata"),
+            ("data/ROADS.SHP", &shp),
+            ("data/ROADS.shx", &shx),
+            ("other/ROADS.dbf", b"not the dbf of the shapefile"),
+        ]);
+
+        let reader = Reader::from_zip(archive).unwrap();
+        assert!(reader.has_index() && !reader.has_attributes());
+        assert_eq!(reader.read_as::<Point>().unwrap(), points());
+    }
+
+    #[test]
+    fn read_zip_without_single_shp() {
+        let archive = zip_of(&[("readme.txt", b"no shapefile here")]);
+        match Reader::from_zip(archive) {
+            Err(Error::MissingShpInZip) => {}
+            _ => panic!("expected a MissingShpInZip error"),
+        }
+
+        let mut writer = in_memory_writer();
+        writer.write_shapes(points()).unwrap();
+        let shp = writer.into_parts().unwrap().shp.into_inner();
+        let archive = zip_of(&[("a.shp", &shp), ("b.shp", &shp)]);
+        match Reader::from_zip(archive) {
+            Err(Error::MultipleShpInZip(names)) => assert_eq!(names, vec!["a.shp", "b.shp"]),
+            _ => panic!("expected a MultipleShpInZip error"),
+        }
+    }
+}